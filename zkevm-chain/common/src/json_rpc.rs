@@ -45,6 +45,18 @@ pub async fn jsonrpc_request_client<T: Serialize + Send + Sync, R: DeserializeOw
     uri: &Uri,
     method: &str,
     params: T,
+) -> Result<R, String> {
+    jsonrpc_request_client_auth(timeout, client, uri, None, method, params).await
+}
+
+/// Like `jsonrpc_request_client`, authenticating with the bearer token `token` if set.
+pub async fn jsonrpc_request_client_auth<T: Serialize + Send + Sync, R: DeserializeOwned>(
+    timeout: u64,
+    client: &hyper::Client<HttpConnector>,
+    uri: &Uri,
+    token: Option<&str>,
+    method: &str,
+    params: T,
 ) -> Result<R, String> {
     #[derive(Debug, Deserialize)]
     struct JsonRpcResponseInternal<T> {
@@ -52,7 +64,10 @@ pub async fn jsonrpc_request_client<T: Serialize + Send + Sync, R: DeserializeOw
         error: Option<JsonRpcError>,
     }
 
-    let node_req = Request::post(uri);
+    let mut node_req = Request::post(uri);
+    if let Some(token) = token {
+        node_req = node_req.header(hyper::header::AUTHORIZATION, format!("Bearer {token}"));
+    }
     let req_obj = JsonRpcRequest {
         jsonrpc: "2.0".to_string(),
         id: 0.into(),
@@ -1,9 +1,10 @@
 use bus_mapping::circuit_input_builder::{
-    protocol_instance::{BlockMetadata, Transition},
+    protocol_instance::{BlockMetadata, EvidenceType, Transition},
     ProtocolInstance,
 };
 use eth_types::{Address, Bytes, H256};
 use serde::{Deserialize, Serialize};
+use zkevm_circuits::root_circuit::taiko_aggregation::AccumulationSchemeType;
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct ProofResult {
@@ -19,6 +20,9 @@ pub struct ProofResult {
     pub label: String,
     /// Auxiliary
     pub aux: ProofResultInstrumentation,
+    /// Instance hash of the proven block, for the evidence type of the request
+    #[serde(default)]
+    pub instance_hash: H256,
 }
 
 impl std::fmt::Debug for ProofResult {
@@ -28,6 +32,7 @@ impl std::fmt::Debug for ProofResult {
             .field("instance", &self.instance)
             .field("k", &self.k)
             .field("randomness", &format!("{}", &self.randomness))
+            .field("instance_hash", &self.instance_hash)
             .field("aux", &format!("{:#?}", self.aux))
             .finish()
     }
@@ -64,6 +69,46 @@ pub struct Proofs {
     pub gas: u64,
     /// byte code used for evm verifier
     pub bytecode: Bytes,
+    /// Circuit proofs of each block if several blocks were aggregated.
+    /// The aggregation instance starts with their instances in this order.
+    #[serde(default)]
+    pub batch: Vec<ProofResult>,
+    /// Accumulation scheme the proofs and the evm verifier were created with
+    #[serde(default)]
+    pub accumulation_scheme: AccumulationScheme,
+    /// Instance hash of the captured or proven block, for the evidence type of the
+    /// request. The first block of a batch, `batch` holds the hash of each block.
+    #[serde(default)]
+    pub instance_hash: H256,
+}
+
+impl Proofs {
+    /// The key of the trusted verifier hash for these proofs.
+    /// See [`verifier_key`].
+    pub fn verifier_key(&self) -> String {
+        verifier_key(
+            &self.config,
+            self.accumulation_scheme,
+            self.batch.len().max(1),
+        )
+    }
+}
+
+/// Returns the key a trusted verifier hash is pinned under,
+/// `<block_gas_limit>-<scheme>-<batch_size>`.
+/// The evm verifier depends on the circuit parameter bucket, the accumulation
+/// scheme and the number of aggregated blocks, so each combination has its own hash.
+pub fn verifier_key(
+    config: &CircuitConfig,
+    scheme: AccumulationScheme,
+    batch_size: usize,
+) -> String {
+    format!(
+        "{}-{}-{}",
+        config.block_gas_limit,
+        scheme.as_str(),
+        batch_size
+    )
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
@@ -109,6 +154,39 @@ pub struct RequestExtraInstance {
     pub max_bytes_per_tx_list: u64,
     /// anchor_gas_limit
     pub anchor_gas_limit: u64,
+    /// hex encoded blob of the tx list, when `request_meta_data.blob_used`
+    #[serde(default)]
+    pub blob: String,
+    /// the evidence type of the instance hash
+    #[serde(default)]
+    pub evidence_type: RequestEvidenceType,
+}
+
+/// evidence type of the instance hash
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RequestEvidenceType {
+    /// PseZkVerifier.sol
+    #[default]
+    PseZk,
+    /// SgxVerifier.sol
+    Sgx {
+        /// the new public key of the sgx instance
+        new_pubkey: String,
+    },
+}
+
+impl TryFrom<&RequestEvidenceType> for EvidenceType {
+    type Error = String;
+
+    fn try_from(evidence_type: &RequestEvidenceType) -> Result<Self, Self::Error> {
+        Ok(match evidence_type {
+            RequestEvidenceType::PseZk => EvidenceType::PseZk,
+            RequestEvidenceType::Sgx { new_pubkey } => EvidenceType::Sgx {
+                new_pubkey: parse_address(new_pubkey)?,
+            },
+        })
+    }
 }
 
 /// l1 meta hash
@@ -162,35 +240,45 @@ impl PartialEq for RequestExtraInstance {
             && self.block_max_gas_limit == other.block_max_gas_limit
             && self.max_transactions_per_block == other.max_transactions_per_block
             && self.max_bytes_per_tx_list == other.max_bytes_per_tx_list
+            && self.blob == other.blob
+            && self.evidence_type == other.evidence_type
     }
 }
 
-fn parse_hash(input: &str) -> [u8; 32] {
-    H256::from_slice(&hex::decode(input).expect("parse_hash"))
-        .as_fixed_bytes()
-        .clone()
+fn parse_hash(input: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(input).map_err(|e| format!("invalid hash {input}: {e}"))?;
+    if bytes.len() != 32 {
+        return Err(format!("invalid hash {input}: expected 32 bytes"));
+    }
+    Ok(H256::from_slice(&bytes).to_fixed_bytes())
 }
 
-fn parse_address(input: &str) -> Address {
-    Address::from_slice(&hex::decode(input).expect("parse_address"))
+fn parse_address(input: &str) -> Result<Address, String> {
+    let bytes = hex::decode(input).map_err(|e| format!("invalid address {input}: {e}"))?;
+    if bytes.len() != 20 {
+        return Err(format!("invalid address {input}: expected 20 bytes"));
+    }
+    Ok(Address::from_slice(&bytes))
 }
 
-impl From<RequestExtraInstance> for ProtocolInstance {
-    fn from(instance: RequestExtraInstance) -> Self {
-        ProtocolInstance {
+impl TryFrom<RequestExtraInstance> for ProtocolInstance {
+    type Error = String;
+
+    fn try_from(instance: RequestExtraInstance) -> Result<Self, Self::Error> {
+        Ok(ProtocolInstance {
             transition: Transition {
-                parentHash: parse_hash(&instance.parent_hash).into(),
-                blockHash: parse_hash(&instance.block_hash).into(), // constrain: l2 block hash
-                signalRoot: parse_hash(&instance.signal_root).into(), // constrain: ??l2 service account storage root??
-                graffiti: parse_hash(&instance.graffiti).into(),
+                parentHash: parse_hash(&instance.parent_hash)?.into(),
+                blockHash: parse_hash(&instance.block_hash)?.into(), // constrain: l2 block hash
+                signalRoot: parse_hash(&instance.signal_root)?.into(), // constrain: ??l2 service account storage root??
+                graffiti: parse_hash(&instance.graffiti)?.into(),
             },
             block_metadata: BlockMetadata {
-                l1Hash: parse_hash(&instance.request_meta_data.l1_hash).into(),
-                difficulty: parse_hash(&instance.request_meta_data.difficulty).into(),
-                blobHash: parse_hash(&instance.request_meta_data.blob_hash).into(),
-                extraData: parse_hash(&instance.request_meta_data.extra_data).into(),
-                depositsHash: parse_hash(&instance.request_meta_data.deposits_hash).into(),
-                coinbase: parse_address(&instance.request_meta_data.coinbase)
+                l1Hash: parse_hash(&instance.request_meta_data.l1_hash)?.into(),
+                difficulty: parse_hash(&instance.request_meta_data.difficulty)?.into(),
+                blobHash: parse_hash(&instance.request_meta_data.blob_hash)?.into(),
+                extraData: parse_hash(&instance.request_meta_data.extra_data)?.into(),
+                depositsHash: parse_hash(&instance.request_meta_data.deposits_hash)?.into(),
+                coinbase: parse_address(&instance.request_meta_data.coinbase)?
                     .to_fixed_bytes()
                     .into(),
                 id: instance.request_meta_data.id,
@@ -201,10 +289,11 @@ impl From<RequestExtraInstance> for ProtocolInstance {
                 txListByteSize: instance.request_meta_data.tx_list_byte_size,
                 minTier: instance.request_meta_data.min_tier,
                 blobUsed: instance.request_meta_data.blob_used,
-                parentMetaHash: parse_hash(&instance.request_meta_data.parent_metahash).into(),
+                parentMetaHash: parse_hash(&instance.request_meta_data.parent_metahash)?.into(),
             },
-            prover: parse_address(&instance.prover),
-        }
+            prover: parse_address(&instance.prover)?,
+            blob: hex::decode(&instance.blob).map_err(|e| format!("invalid blob: {e}"))?,
+        })
     }
 }
 
@@ -215,6 +304,8 @@ pub enum ProverMode {
     #[default]
     LegacyProver,
     Verifier,
+    KeyGen,
+    ValidateWitness,
 }
 impl From<&str> for ProverMode {
     fn from(input: &str) -> ProverMode {
@@ -223,11 +314,47 @@ impl From<&str> for ProverMode {
             "offline_prover" => ProverMode::OfflineProver,
             "legacy_prover" => ProverMode::LegacyProver,
             "verifier" => ProverMode::Verifier,
+            "keygen" => ProverMode::KeyGen,
+            "validate_witness" => ProverMode::ValidateWitness,
             _ => panic!("invalid mode string: {input}"),
         }
     }
 }
 
+/// Multi-open scheme of the circuit snarks and of the aggregation proof.
+#[derive(PartialEq, Clone, Debug, Copy, Serialize, Deserialize, Default)]
+pub enum AccumulationScheme {
+    #[default]
+    Gwc,
+    Shplonk,
+}
+impl From<&str> for AccumulationScheme {
+    fn from(input: &str) -> AccumulationScheme {
+        match input {
+            "gwc" => AccumulationScheme::Gwc,
+            "shplonk" => AccumulationScheme::Shplonk,
+            _ => panic!("invalid accumulation scheme string: {input}"),
+        }
+    }
+}
+impl AccumulationScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccumulationScheme::Gwc => "gwc",
+            AccumulationScheme::Shplonk => "shplonk",
+        }
+    }
+}
+
+impl From<AccumulationScheme> for AccumulationSchemeType {
+    fn from(scheme: AccumulationScheme) -> AccumulationSchemeType {
+        match scheme {
+            AccumulationScheme::Gwc => AccumulationSchemeType::GwcType,
+            AccumulationScheme::Shplonk => AccumulationSchemeType::ShplonkType,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ProofRequestOptions {
     /// The name of the circuit.
@@ -239,6 +366,11 @@ pub struct ProofRequestOptions {
     pub prover_mode: ProverMode,
     /// the l2 rpc url
     pub rpc: String,
+    /// the l1 rpc url.
+    /// If set, the protocol instance is fetched from the `BlockProposed` event.
+    pub l1_rpc: Option<String>,
+    /// the TaikoL1 contract address emitting `BlockProposed`
+    pub l1_contract: Option<String>,
     /// the protocol instance data
     pub protocol_instance: RequestExtraInstance,
     /// retry proof computation if error
@@ -248,14 +380,32 @@ pub struct ProofRequestOptions {
     pub param: Option<String>,
     /// Witness file to serialize
     pub witness_path: Option<String>,
+    /// Witness files of further blocks to aggregate into one proof
+    /// together with `witness_path`.
+    #[serde(default)]
+    pub batch_witness_paths: Vec<String>,
     /// Proof file to serialize
     pub proof_path: Option<String>,
+    /// Keccak256 hashes of the trusted evm verifier bytecodes, as
+    /// `<block_gas_limit>-<scheme>-<batch_size>=<hash>` entries.
+    /// Required for the verifier mode.
+    #[serde(default)]
+    pub verifier_hashes: Vec<String>,
+    /// Directory for caching proving keys on disk.
+    /// Keys are generated on the fly if not set or not cached yet.
+    pub pk_cache_path: Option<String>,
     /// Only use MockProver if true.
     #[serde(default = "default_bool")]
     pub mock: bool,
     /// Additionaly aggregates the circuit proof if true
     #[serde(default = "default_bool")]
     pub aggregate: bool,
+    /// Accumulation scheme of the circuit snarks and the aggregation proof
+    #[serde(default)]
+    pub accumulation_scheme: AccumulationScheme,
+    /// Runs the MockProver before proving and fails early if it rejects the witness.
+    #[serde(default = "default_bool")]
+    pub preflight: bool,
     /// Runs the MockProver if proofing fails.
     #[serde(default = "default_bool")]
     pub mock_feedback: bool,
@@ -264,24 +414,52 @@ pub struct ProofRequestOptions {
     pub verify_proof: bool,
 }
 
+/// Identifies the task of a request, the options not affecting the result
+/// like the output paths or the proving key cache are ignored.
 impl PartialEq for ProofRequestOptions {
     fn eq(&self, other: &Self) -> bool {
         self.block == other.block
             && self.protocol_instance == other.protocol_instance
             && self.rpc == other.rpc
+            && self.l1_rpc == other.l1_rpc
+            && self.l1_contract == other.l1_contract
+            && self.verifier_hashes == other.verifier_hashes
+            && self.preflight == other.preflight
             && self.param == other.param
             && self.circuit == other.circuit
             && self.mock == other.mock
             && self.aggregate == other.aggregate
+            && self.batch_witness_paths == other.batch_witness_paths
+            && self.accumulation_scheme == other.accumulation_scheme
     }
 }
 
+/// The error result of a cancelled task
+pub const TASK_CANCELLED: &str = "task cancelled";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofRequest {
     pub options: ProofRequestOptions,
     pub result: Option<Result<Proofs, String>>,
     /// A counter to keep track of changes of the `result` field
     pub edition: u64,
+    /// Unix timestamp in seconds at which the `result` was recorded
+    #[serde(default)]
+    pub completed_at: Option<u64>,
+    /// The authenticated client which enqueued the task
+    #[serde(default)]
+    pub client: Option<String>,
+    /// Set if the task was cancelled before completion,
+    /// the `result` holds the `TASK_CANCELLED` error then
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+impl ProofRequest {
+    /// `true` if the task was cancelled before completion
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -299,7 +477,7 @@ pub struct NodeStatus {
     pub obtained: bool,
 }
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CircuitConfig {
     pub block_gas_limit: usize,
     pub max_txs: usize,
@@ -312,6 +490,18 @@ pub struct CircuitConfig {
     pub pad_to: usize,
     pub min_k_aggregation: usize,
     pub keccak_padding: usize,
+    /// Fixed capacities of the precompile and MPT circuits,
+    /// these are not estimated by the autogen test
+    #[serde(default)]
+    pub max_ec_add: usize,
+    #[serde(default)]
+    pub max_ec_mul: usize,
+    #[serde(default)]
+    pub max_modexp: usize,
+    #[serde(default)]
+    pub max_blake2f_rows: usize,
+    #[serde(default)]
+    pub max_mpt_rows: usize,
 }
 
 fn default_bool() -> bool {
@@ -0,0 +1,240 @@
+use crate::task_store::unix_timestamp;
+use hmac::{Hmac, Mac};
+use hyper::HeaderMap;
+use serde::Deserialize;
+use sha2::Sha256;
+
+/// Header naming the client of a HMAC signed request.
+pub const CLIENT_HEADER: &str = "x-proverd-client";
+/// Header with the unix timestamp in seconds of a HMAC signed request.
+pub const TIMESTAMP_HEADER: &str = "x-proverd-timestamp";
+/// Header with the hex encoded HMAC-SHA256 of `{timestamp}.{body}`.
+pub const SIGNATURE_HEADER: &str = "x-proverd-signature";
+/// Signed requests older or newer than this many seconds are rejected.
+const MAX_CLOCK_SKEW: u64 = 300;
+
+/// The scope of a json-rpc method, a scope includes all lower ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// `info` and `status`, used by the peers, and the `/metrics` and `/status` endpoints
+    Read,
+    /// `proof` and `circuit_config`
+    Prove,
+    /// `cancel` and the `flush` methods
+    Admin,
+}
+
+impl Scope {
+    /// The scope required to call `method`.
+    /// Unknown methods require the admin scope.
+    pub fn of_method(method: &str) -> Scope {
+        match method {
+            "info" | "status" => Scope::Read,
+            "proof" | "circuit_config" => Scope::Prove,
+            _ => Scope::Admin,
+        }
+    }
+}
+
+/// A client of the json-rpc api.
+/// Authenticates either with `authorization: Bearer <token>` or
+/// by signing its requests with `hmac_secret`, see `SIGNATURE_HEADER`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthClient {
+    pub id: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub hmac_secret: Option<String>,
+    pub scope: Scope,
+    /// The maximum number of uncompleted `proof` requests of this client.
+    #[serde(default)]
+    pub max_queued: Option<usize>,
+}
+
+/// The clients allowed to use the json-rpc api.
+#[derive(Debug, Clone, Default)]
+pub struct Auth {
+    pub clients: Vec<AuthClient>,
+}
+
+impl Auth {
+    /// Reads the clients from a JSON file holding a list of `AuthClient`.
+    pub fn from_file(path: &str) -> Result<Auth, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+        let clients: Vec<AuthClient> =
+            serde_json::from_slice(&bytes).map_err(|e| format!("{path}: {e}"))?;
+        for client in clients.iter() {
+            if client.token.is_none() && client.hmac_secret.is_none() {
+                return Err(format!(
+                    "client {} has neither token nor hmac_secret",
+                    client.id
+                ));
+            }
+        }
+
+        Ok(Auth { clients })
+    }
+
+    /// Returns the client sending a request with `headers` and `body`.
+    pub fn authenticate(&self, headers: &HeaderMap, body: &[u8]) -> Result<&AuthClient, String> {
+        if let Some(client_id) = header(headers, CLIENT_HEADER) {
+            let client = self
+                .clients
+                .iter()
+                .find(|client| client.id == client_id)
+                .ok_or("unknown client")?;
+            let secret = client
+                .hmac_secret
+                .as_ref()
+                .ok_or("client does not sign requests")?;
+            let timestamp = header(headers, TIMESTAMP_HEADER).ok_or("missing timestamp")?;
+            let signature = header(headers, SIGNATURE_HEADER).ok_or("missing signature")?;
+            verify_signature(secret, timestamp, signature, body, unix_timestamp())?;
+            return Ok(client);
+        }
+
+        let token = header(headers, hyper::header::AUTHORIZATION.as_str())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or("missing credentials")?;
+        self.clients
+            .iter()
+            .find(|client| {
+                client
+                    .token
+                    .as_ref()
+                    .map_or(false, |expected| constant_time_eq(expected, token))
+            })
+            .ok_or_else(|| "invalid token".to_string())
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn mac(secret: &str, timestamp: &str, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key size");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// Returns the hex encoded signature of a request, see `SIGNATURE_HEADER`.
+pub fn sign(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    hex::encode(
+        mac(secret, &timestamp.to_string(), body)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+fn verify_signature(
+    secret: &str,
+    timestamp: &str,
+    signature: &str,
+    body: &[u8],
+    now: u64,
+) -> Result<(), String> {
+    let time: u64 = timestamp.parse().map_err(|_| "invalid timestamp")?;
+    if time.abs_diff(now) > MAX_CLOCK_SKEW {
+        return Err("expired timestamp".to_string());
+    }
+    let signature = hex::decode(signature).map_err(|_| "invalid signature")?;
+    mac(secret, timestamp, body)
+        .verify_slice(&signature)
+        .map_err(|_| "invalid signature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn auth() -> Auth {
+        Auth {
+            clients: vec![
+                AuthClient {
+                    id: "peer".to_string(),
+                    token: Some("peer-token".to_string()),
+                    hmac_secret: None,
+                    scope: Scope::Read,
+                    max_queued: None,
+                },
+                AuthClient {
+                    id: "ops".to_string(),
+                    token: None,
+                    hmac_secret: Some("secret".to_string()),
+                    scope: Scope::Admin,
+                    max_queued: Some(1),
+                },
+            ],
+        }
+    }
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn scopes() {
+        assert!(Scope::of_method("status") < Scope::of_method("proof"));
+        assert_eq!(Scope::of_method("flushAll"), Scope::Admin);
+        assert_eq!(Scope::of_method("cancel"), Scope::Admin);
+        assert_eq!(Scope::of_method("unknown"), Scope::Admin);
+    }
+
+    #[test]
+    fn bearer_token() {
+        let auth = auth();
+        let client = auth
+            .authenticate(
+                &headers(&[("authorization", "Bearer peer-token".to_string())]),
+                b"",
+            )
+            .unwrap();
+        assert_eq!(client.id, "peer");
+
+        let wrong = headers(&[("authorization", "Bearer wrong".to_string())]);
+        assert!(auth.authenticate(&wrong, b"").is_err());
+        assert!(auth.authenticate(&HeaderMap::new(), b"").is_err());
+    }
+
+    #[test]
+    fn hmac_signature() {
+        let auth = auth();
+        let body = br#"{"method":"flushAll"}"#;
+        let now = unix_timestamp();
+        let signed = |timestamp: u64, body: &[u8]| {
+            headers(&[
+                (CLIENT_HEADER, "ops".to_string()),
+                (TIMESTAMP_HEADER, timestamp.to_string()),
+                (SIGNATURE_HEADER, sign("secret", timestamp, body)),
+            ])
+        };
+
+        let client = auth.authenticate(&signed(now, body), body).unwrap();
+        assert_eq!(client.id, "ops");
+
+        // the body was tampered with
+        assert!(auth.authenticate(&signed(now, b"{}"), body).is_err());
+        // replay of an old request
+        let old = now - MAX_CLOCK_SKEW - 1;
+        assert!(auth.authenticate(&signed(old, body), body).is_err());
+    }
+}
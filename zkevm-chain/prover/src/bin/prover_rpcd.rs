@@ -1,8 +1,13 @@
 use clap::Parser;
 use env_logger::Env;
+use std::sync::Arc;
+use std::time::Duration;
 
+use prover::auth::Auth;
 use prover::server::serve;
 use prover::shared_state::SharedState;
+use prover::task_store::{FileTaskStore, MemoryTaskStore, TaskRetention, TaskStore};
+use prover::worker::{run_worker, WorkerConfig, WORKER_ENV};
 use prover::VERSION;
 
 #[derive(Parser, Debug)]
@@ -16,14 +21,84 @@ pub(crate) struct ProverdConfig {
     #[clap(long, env = "PROVERD_LOOKUP")]
     /// A `HOSTNAME:PORT` conformant string that will be used for DNS service discovery of other nodes.
     lookup: Option<String>,
+    #[clap(long, env = "PROVERD_TASK_STORE", default_value = "proverd_tasks.json")]
+    /// The file persisting the queued tasks and their results across restarts.
+    /// An empty string keeps the tasks in memory only.
+    task_store: String,
+    #[clap(long, env = "PROVERD_TASK_RETENTION_SECS")]
+    /// Completed tasks older than this many seconds are evicted.
+    task_retention_secs: Option<u64>,
+    #[clap(long, env = "PROVERD_TASK_RETENTION_COUNT")]
+    /// At most this many completed tasks are kept, the oldest are evicted first.
+    task_retention_count: Option<usize>,
+    #[clap(long, env = "PROVERD_AUTH_CLIENTS")]
+    /// A JSON file listing the clients allowed to use the json-rpc api, with their bearer
+    /// token or HMAC secret, scope (`read`, `prove` or `admin`) and limit of queued proofs.
+    /// `/metrics` and `/status` require the `read` scope and CORS requests of browsers
+    /// are refused. The api is open to anyone if not set.
+    auth_clients: Option<String>,
+    #[clap(long, env = "PROVERD_PEER_TOKEN")]
+    /// The bearer token authenticating this instance at its peers, needs the `read` scope.
+    peer_token: Option<String>,
+    #[clap(long, env = "PROVERD_WORKER")]
+    /// Computes each task in a child process, so that a crash only fails the task.
+    worker: bool,
+    #[clap(long, env = "PROVERD_WORKER_MEMORY_LIMIT_MB", requires = "worker")]
+    /// The address space limit of a worker process in MiB.
+    worker_memory_limit_mb: Option<u64>,
+    #[clap(long, env = "PROVERD_WORKER_TIMEOUT_SECS", requires = "worker")]
+    /// A worker process is killed if its task takes longer than this many seconds.
+    worker_timeout_secs: Option<u64>,
 }
 
 #[tokio::main]
 async fn main() {
-    let config = ProverdConfig::parse();
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+    if std::env::var_os(WORKER_ENV).is_some() {
+        // this is a worker process spawned by the daemon
+        run_worker().await;
+        return;
+    }
+
+    let config = ProverdConfig::parse();
 
-    let shared_state = SharedState::new(SharedState::random_worker_id(), config.lookup);
+    let store: Arc<dyn TaskStore> = match config.task_store.as_str() {
+        "" => Arc::new(MemoryTaskStore),
+        path => Arc::new(FileTaskStore::new(path)),
+    };
+    let retention = TaskRetention {
+        max_age: config.task_retention_secs.map(Duration::from_secs),
+        max_completed: config.task_retention_count,
+    };
+    let mut shared_state = match SharedState::with_task_store(
+        SharedState::random_worker_id(),
+        config.lookup,
+        store,
+        retention,
+    ) {
+        Ok(shared_state) => shared_state,
+        Err(err) => {
+            // starting empty would overwrite the tasks of the previous run
+            log::error!(
+                "can't restore the tasks from the task store {:?}: {}, \
+                 fix or remove the file, or pass an empty task store to keep the tasks in memory",
+                config.task_store,
+                err
+            );
+            std::process::exit(1);
+        }
+    };
+    if let Some(path) = &config.auth_clients {
+        shared_state.ro.auth = Some(Arc::new(Auth::from_file(path).expect("auth clients")));
+    }
+    shared_state.ro.peer_token = config.peer_token;
+    if config.worker {
+        shared_state.ro.worker = Some(WorkerConfig {
+            program: std::env::current_exe().expect("current executable"),
+            memory_limit: config.worker_memory_limit_mb.map(|mb| mb << 20),
+            timeout: config.worker_timeout_secs.map(Duration::from_secs),
+        });
+    }
     {
         // start the http server
         let h1 = serve(&shared_state, &config.bind);
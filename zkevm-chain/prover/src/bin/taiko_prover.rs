@@ -1,6 +1,7 @@
 // use env_logger::Env;
 use clap::Parser;
 use gevulot_shim::{Task, TaskResult};
+use prover::error::ProverError;
 use prover::shared_state::SharedState;
 use serde_json::json;
 use std::fs;
@@ -26,12 +27,26 @@ fn run_task(task: Task) -> Result<TaskResult, Box<dyn Error>> {
 
     println!("taiko prover: args: {:?}", args);
 
-    let proof_path = taiko_prover(&args)?;
-
-    println!("exit prover run_task");
-
-    // Return TaskResult with reference to the generated proof file.
-    task.result(vec![], vec![proof_path])
+    match taiko_prover(&args) {
+        Ok(proof_path) => {
+            println!("exit prover run_task");
+
+            // Return TaskResult with reference to the generated proof file.
+            task.result(vec![], vec![proof_path])
+        }
+        Err(err) => {
+            println!("taiko prover failed: {err}");
+
+            // Report the failure in the TaskResult instead of aborting the shim.
+            let data = match err.downcast_ref::<ProverError>() {
+                Some(ProverError::MockProver(failures)) => {
+                    json!({ "error": "mock prover", "mock_failures": failures })
+                }
+                _ => json!({ "error": err.to_string() }),
+            };
+            task.result(data.to_string().into_bytes(), vec![])
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -43,9 +58,21 @@ pub struct TaikoProverConfig {
     /// Required for witness_capture and offline_prover
     #[clap(short, long, value_parser)]
     pub witness_path: Option<String>,
+    /// Witness files of further blocks to aggregate with `witness_path`, repeatable
+    #[clap(long = "batch-witness", value_parser)]
+    pub batch_witness_paths: Vec<String>,
     /// Required for witness_capture, offline_prover, legacy_prover
     #[clap(short, long, value_parser)]
     pub kparams_path: Option<String>,
+    /// Directory of pre-generated proving keys
+    #[clap(short = 'c', long, value_parser)]
+    pub pk_cache_path: Option<String>,
+    /// Run the MockProver before proving and stop at the first rejection
+    #[clap(long, value_parser)]
+    pub preflight: bool,
+    /// gwc | shplonk
+    #[clap(long, value_parser, default_value = "gwc")]
+    pub accumulation_scheme: AccumulationScheme,
 }
 
 // #[tokio::main]
@@ -89,10 +116,18 @@ fn taiko_prover(args: &Vec<String>) -> Result<String, Box<dyn Error>> {
     let params_path = arg_conf.kparams_path;
     let proof_path = arg_conf.proof_path;
     let witness_path = arg_conf.witness_path;
+    let batch_witness_paths = arg_conf.batch_witness_paths;
+    let pk_cache_path = arg_conf.pk_cache_path;
+    let preflight = arg_conf.preflight;
+    let accumulation_scheme = arg_conf.accumulation_scheme;
 
     println!("params_path: {:?}", params_path);
     println!("proof_path: {:?}", proof_path);
     println!("witness_path: {:?}", witness_path);
+    println!("batch_witness_paths: {:?}", batch_witness_paths);
+    println!("pk_cache_path: {:?}", pk_cache_path);
+    println!("preflight: {:?}", preflight);
+    println!("accumulation_scheme: {:?}", accumulation_scheme);
 
     if witness_path.is_none() {
         return Err(String::from("no witness file parameter").into());
@@ -108,48 +143,6 @@ fn taiko_prover(args: &Vec<String>) -> Result<String, Box<dyn Error>> {
     let rpc_url = "http://dummy.com".to_string();
     let block_num: u64 = 0;
 
-    // mock a RequestExtraInstance struct
-    // the block_hash and parent_hash will get overwritten with real values
-    // when the eth_block is first read in.
-    let protocol_instance = RequestExtraInstance {
-        l1_signal_service: "7a2088a1bFc9d81c55368AE168C2C02570cB814F".to_string(),
-        l2_signal_service: "1000777700000000000000000000000000000007".to_string(),
-        l2_contract: "1000777700000000000000000000000000000001".to_string(),
-        request_meta_data: RequestMetaData {
-            id: 57437,
-            timestamp: 1706084004,
-            l1_height: 800044,
-            l1_hash: "db92d81a16dcd5b684bdf420ef71e3e31b40cda81a43563f9638c9c0390790e4".to_string(),
-            deposits_hash: "569e75fc77c1a856f6daaf9e69d8a9566ca34aa47f9133711ce065a571af0cfd"
-                .to_string(),
-            blob_hash: "94e0d3a174ecf52f531eba85ee1a01f77fccae3f352b4be7ed68bb2cac4969ee"
-                .to_string(),
-            tx_list_byte_offset: 0,
-            tx_list_byte_size: 28706,
-            gas_limit: 15000000,
-            coinbase: "e1e210594771824dad216568b91c9cb4ceed361c".to_string(),
-            difficulty: "055c829a0e081185e57b66ff8fbb0fbf06f5fc58e226764419224495d3036a00"
-                .to_string(),
-            extra_data: "302e31382e302d64657600000000000000000000000000000000000000000000"
-                .to_string(),
-            parent_metahash: "e5c39fecba3dca4aec370e5005fbecac568705f859871794e4f4090c276936fa"
-                .to_string(),
-            ..Default::default()
-        },
-        block_hash: "930e1b7bc4c8354614b0c76aea5c5dc6b6797d6e21ccda43e228cd0cef773490".to_string(),
-        parent_hash: "d6cf6f0c98d11e9e955d97ebd477282831d2f11f55ee13354f2134afc7f85429".to_string(),
-        signal_root: "5c9572d9ec31784e01a393dc17b7ff0786b6534bcfa14715effd02d29222dbf9".to_string(),
-        graffiti: "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
-        prover: "ee85e2fe0e26891882a8CD744432d2BBFbe140dd".to_string(),
-        treasury: "0x1670080000000000000000000000000000010001".to_string(),
-        gas_used: 0,
-        parent_gas_used: 0,
-        block_max_gas_limit: 6000000,
-        max_transactions_per_block: 79,
-        max_bytes_per_tx_list: 120000,
-        anchor_gas_limit: 250000,
-    };
-
     let state = SharedState::new(String::new(), None);
     let request = ProofRequestOptions {
         circuit: "super".to_string(),
@@ -159,10 +152,13 @@ fn taiko_prover(args: &Vec<String>) -> Result<String, Box<dyn Error>> {
         retry: false,
         param: params_path,
         witness_path,
+        batch_witness_paths,
         proof_path: proof_path.clone(),
-        protocol_instance,
+        pk_cache_path,
         mock: false,
+        preflight,
         aggregate: true,
+        accumulation_scheme,
         verify_proof: true,
         ..Default::default()
     };
@@ -173,6 +169,6 @@ fn taiko_prover(args: &Vec<String>) -> Result<String, Box<dyn Error>> {
     let jproof = json!(proofs).to_string();
     println!("taiko prover, write proof string, {:?} bytes", jproof.len());
     println!("taiko prover, proof_path, {:?} bytes", proof_path);
-    write(proof_path.clone(), jproof).unwrap();
+    write(proof_path.clone(), jproof)?;
     Ok(proof_path)
 }
@@ -31,11 +31,12 @@ fn run_task(task: Task) -> Result<TaskResult, Box<dyn Error>> {
     }
     println!("taiko_verifier args: {:?}", &args);
 
-    let (is_success, proof_file) = taiko_verifier(&args)?;
+    let (result, proof_file) = taiko_verifier(&args)?;
 
-    let message: String = match is_success {
-        true => "Taiko verifier result: success".to_string(),
-        false => "Taiko verifier result: fail".to_string(),
+    let is_success = result.is_ok();
+    let message: String = match result {
+        Ok(_) => "Taiko verifier result: success".to_string(),
+        Err(err) => format!("Taiko verifier result: fail: {err}"),
     };
 
     let timestamp = SystemTime::now()
@@ -65,14 +66,25 @@ pub struct TaikoVerifierConfig {
     /// Required for offline_prover, legacy_prover, and verifier
     #[clap(short, long, value_parser, verbatim_doc_comment)]
     pub proof_path: Option<String>,
+    /// Trusted evm verifier hashes as `<block_gas_limit>-<scheme>-<batch_size>=<keccak256>`,
+    /// repeatable or comma separated
+    #[clap(
+        short = 'v',
+        long = "verifier-hash",
+        value_parser,
+        value_delimiter = ',',
+        env = "TAIKO_VERIFIER_HASHES"
+    )]
+    pub verifier_hashes: Vec<String>,
 }
 
 // #[tokio::main]
-fn taiko_verifier(args: &Vec<String>) -> Result<(bool, String), Box<dyn Error>> {
+fn taiko_verifier(args: &Vec<String>) -> Result<(Result<(), String>, String), Box<dyn Error>> {
     println!("taiko_verifier");
     let arg_conf = TaikoVerifierConfig::parse_from(args);
 
     let proof_path = arg_conf.proof_path;
+    let verifier_hashes = arg_conf.verifier_hashes;
 
     let entries = fs::read_dir(".")
         .unwrap()
@@ -93,10 +105,13 @@ fn taiko_verifier(args: &Vec<String>) -> Result<(bool, String), Box<dyn Error>>
     if proof_path.is_none() {
         return Err(String::from("no proof file parameter").into());
     }
+    if verifier_hashes.is_empty() {
+        return Err(String::from("no trusted verifier hash parameter").into());
+    }
 
     let jproof = std::fs::read_to_string(proof_path.clone().unwrap())?;
     let proofs: Proofs = serde_json::from_str(&jproof)?;
-    let result = verify(proofs);
+    let result = verify(proofs, &verifier_hashes);
 
     Ok((result, proof_path.unwrap()))
 }
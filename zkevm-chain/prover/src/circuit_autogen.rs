@@ -5,32 +5,42 @@ macro_rules! match_circuit_params {
             0..=100 => {
                 const CIRCUIT_CONFIG: CircuitConfig = CircuitConfig {
                     block_gas_limit: 820000,
-                    max_txs: 80,
+                    max_txs: 38,
                     max_calldata: 69750,
                     max_bytecode: 139500,
                     max_rws: 50000,
                     max_copy_rows: 50000,
                     max_exp_steps: 27900,
-                    min_k: 19,
+                    min_k: 22,
                     pad_to: 80000,
                     min_k_aggregation: 22,
                     keccak_padding: 500000,
+                    max_ec_add: 64,
+                    max_ec_mul: 64,
+                    max_modexp: 256,
+                    max_blake2f_rows: 1048576,
+                    max_mpt_rows: 3500000,
                 };
                 $on_match
             }
             101..=15200000 => {
                 const CIRCUIT_CONFIG: CircuitConfig = CircuitConfig {
                     block_gas_limit: 15200000,
-                    max_txs: 80,
+                    max_txs: 38,
                     max_calldata: 69750,
                     max_bytecode: 139500,
                     max_rws: 524280,
                     max_copy_rows: 52428,
                     max_exp_steps: 27900,
-                    min_k: 19,
+                    min_k: 22,
                     pad_to: 80000,
                     min_k_aggregation: 22,
                     keccak_padding: 500000,
+                    max_ec_add: 64,
+                    max_ec_mul: 64,
+                    max_modexp: 256,
+                    max_blake2f_rows: 1048576,
+                    max_mpt_rows: 3500000,
                 };
                 $on_match
             }
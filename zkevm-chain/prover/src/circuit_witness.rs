@@ -1,3 +1,4 @@
+use crate::protocol_instance::fetch_protocol_instance;
 use crate::Fr;
 use bus_mapping::circuit_input_builder::Block;
 use bus_mapping::circuit_input_builder::BuilderClient;
@@ -5,20 +6,37 @@ use bus_mapping::circuit_input_builder::CircuitsParams;
 use bus_mapping::circuit_input_builder::ProtocolInstance;
 use bus_mapping::mock::BlockData;
 use bus_mapping::rpc::GethClient;
+use bus_mapping::state_db::CodeDB;
 use eth_types::geth_types;
 use eth_types::geth_types::GethData;
 use eth_types::Address;
 use eth_types::ToBigEndian;
+use eth_types::ToWord;
 use eth_types::Word;
 use eth_types::H256;
 use ethers_providers::Http;
+use futures::StreamExt;
+use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
 use zkevm_circuits::evm_circuit;
 use zkevm_circuits::pi_circuit::PublicData;
+use zkevm_circuits::witness::EMPTY_ROOT;
 use zkevm_common::prover::ProofRequestOptions;
 use zkevm_common::prover::{CircuitConfig, RequestExtraInstance};
 
+/// The number of ancestor blocks requested at the same time.
+const HISTORY_FETCH_CONCURRENCY: usize = 16;
+
+/// Number and parent of a block in the history hashes of the witness.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HistoryLink {
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+}
+
 /// Wrapper struct for circuit witness data.
 #[derive(Serialize, Deserialize)]
 
@@ -30,6 +48,13 @@ pub struct CircuitWitness {
     pub dummy_block: Option<bus_mapping::circuit_input_builder::Block>,
     pub code_db: bus_mapping::state_db::CodeDB,
     pub protocol_instance: ProtocolInstance,
+    /// The request data `protocol_instance` is derived from
+    #[serde(default)]
+    pub request_instance: RequestExtraInstance,
+    /// The ancestors of `eth_block` as captured from the l2 node, oldest first.
+    /// Used to check the order of the history hashes.
+    #[serde(default)]
+    pub history_links: Vec<HistoryLink>,
 }
 
 impl CircuitWitness {
@@ -42,6 +67,8 @@ impl CircuitWitness {
         eth_block.base_fee_per_gas = Some(0.into());
         eth_block.hash = Some(eth_block.parent_hash);
         eth_block.gas_limit = circuit_config.block_gas_limit.into();
+        // an empty block on the empty state
+        eth_block.state_root = EMPTY_ROOT;
 
         let circuit_params = CircuitsParams {
             max_txs: circuit_config.max_txs,
@@ -52,6 +79,14 @@ impl CircuitWitness {
             max_exp_steps: circuit_config.max_exp_steps,
             max_evm_rows: circuit_config.pad_to,
             max_keccak_rows: circuit_config.keccak_padding,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: circuit_config.max_ec_add,
+            max_ec_mul: circuit_config.max_ec_mul,
+            max_modexp: circuit_config.max_modexp,
+            max_blake2f_rows: circuit_config.max_blake2f_rows,
+            max_mpt_rows: circuit_config.max_mpt_rows,
         };
         let empty_data = GethData {
             chain_id: Word::from(99),
@@ -63,6 +98,7 @@ impl CircuitWitness {
         let mut builder =
             BlockData::new_from_geth_data_with_params(empty_data.clone(), circuit_params)
                 .new_circuit_input_builder();
+        builder.block.prev_state_root = EMPTY_ROOT.to_word();
         builder
             .handle_block(&empty_data.eth_block, &empty_data.geth_traces)
             .unwrap();
@@ -73,6 +109,8 @@ impl CircuitWitness {
             dummy_block: None,
             code_db: builder.code_db,
             protocol_instance: ProtocolInstance::default(),
+            request_instance: RequestExtraInstance::default(),
+            history_links: Vec::new(),
         })
     }
 
@@ -96,7 +134,7 @@ impl CircuitWitness {
                 .into());
             });
 
-        let pi: ProtocolInstance = request.protocol_instance.clone().into();
+        let pi = ProtocolInstance::try_from(request.protocol_instance.clone())?;
         let circuits_params = CircuitsParams {
             max_txs: circuit_config.max_txs,
             max_calldata: circuit_config.max_calldata,
@@ -106,7 +144,18 @@ impl CircuitWitness {
             max_exp_steps: circuit_config.max_exp_steps,
             max_evm_rows: circuit_config.pad_to,
             max_keccak_rows: circuit_config.keccak_padding,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: circuit_config.max_ec_add,
+            max_ec_mul: circuit_config.max_ec_mul,
+            max_modexp: circuit_config.max_modexp,
+            max_blake2f_rows: circuit_config.max_blake2f_rows,
+            max_mpt_rows: circuit_config.max_mpt_rows,
         };
+        let history_links = Self::fetch_history_links(&geth_client, &block)
+            .await
+            .map_err(|e| e.to_string())?;
         let builder = BuilderClient::new(geth_client, circuits_params, Some(pi.clone()))
             .await
             .map_err(|e| e.to_string())?;
@@ -120,10 +169,12 @@ impl CircuitWitness {
             .await
             .map_err(|e| e.to_string())?;
         let mut w = Self::dummy(circuit_config)?;
-        w.protocol_instance = request.protocol_instance.clone().into();
+        w.protocol_instance = request.protocol_instance.clone().try_into()?;
+        w.request_instance = request.protocol_instance.clone();
         w.block = builder.block;
         w.code_db = builder.code_db;
         w.eth_block = eth_block;
+        w.history_links = history_links;
 
         let dummy_block = Block::new(
             chain_id.into(),
@@ -138,12 +189,29 @@ impl CircuitWitness {
         Ok(w)
     }
 
+    /// Gathers the witness for the requested block.
+    /// Fetches the protocol instance from l1 first if `l1_rpc` is set.
     pub async fn from_request(
         request: &mut ProofRequestOptions,
     ) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(l1_rpc) = &request.l1_rpc {
+            let l1_contract = request
+                .l1_contract
+                .as_ref()
+                .ok_or("l1_contract is required to fetch the protocol instance")?;
+            fetch_protocol_instance(
+                request.block,
+                l1_rpc,
+                l1_contract,
+                &request.rpc,
+                &mut request.protocol_instance,
+            )
+            .await?;
+        }
         let mut w =
             Self::from_rpc(&request.block, &request.rpc, &mut request.protocol_instance).await?;
-        w.protocol_instance = request.protocol_instance.clone().into();
+        w.protocol_instance = request.protocol_instance.clone().try_into()?;
+        w.request_instance = request.protocol_instance.clone();
         Ok(w)
     }
 
@@ -185,11 +253,21 @@ impl CircuitWitness {
             max_exp_steps: circuit_config.max_exp_steps,
             max_evm_rows: circuit_config.pad_to,
             max_keccak_rows: circuit_config.keccak_padding,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: circuit_config.max_ec_add,
+            max_ec_mul: circuit_config.max_ec_mul,
+            max_modexp: circuit_config.max_modexp,
+            max_blake2f_rows: circuit_config.max_blake2f_rows,
+            max_mpt_rows: circuit_config.max_mpt_rows,
         };
         // println!("*** CircuitsParams {:?}", circuit_config);
         // println!("*** pi {:?}", pi);
 
-        let pi: ProtocolInstance = pi.clone().into();
+        let request_instance = pi.clone();
+        let pi = ProtocolInstance::try_from(pi.clone())?;
+        let history_links = Self::fetch_history_links(&geth_client, &block).await?;
         let builder = BuilderClient::new(geth_client, circuit_params, Some(pi.clone())).await?;
         let (builder, eth_block) = builder.gen_inputs(*block_num).await?;
 
@@ -200,9 +278,57 @@ impl CircuitWitness {
             dummy_block: None,
             code_db: builder.code_db,
             protocol_instance: pi,
+            request_instance,
+            history_links,
         })
     }
 
+    /// Fetches the up to 256 ancestors of `block` the history hashes are made of,
+    /// oldest first. The blocks are requested by number, `HISTORY_FETCH_CONCURRENCY`
+    /// at a time, and checked to be linked up to the parent of `block`.
+    async fn fetch_history_links(
+        geth_client: &GethClient<Http>,
+        block: &eth_types::Block<eth_types::Transaction>,
+    ) -> Result<Vec<HistoryLink>, Box<dyn std::error::Error>> {
+        let number = block.number.unwrap_or_default().as_u64();
+        let links: Vec<HistoryLink> = futures::stream::iter(number.saturating_sub(256)..number)
+            .map(|ancestor| async move {
+                let header = geth_client
+                    .get_block_by_number(ancestor.into())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let link = HistoryLink {
+                    number: header
+                        .number
+                        .ok_or("ancestor block without number")?
+                        .as_u64(),
+                    hash: header.hash.ok_or("ancestor block without hash")?,
+                    parent_hash: header.parent_hash,
+                };
+                if link.number != ancestor {
+                    return Err(format!("requested block {ancestor}, got {}", link.number));
+                }
+                Ok(link)
+            })
+            .buffered(HISTORY_FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        // the blocks by number are only ancestors if they are linked by their hashes
+        let mut next_hash = block.parent_hash;
+        for link in links.iter().rev() {
+            if link.hash != next_hash {
+                return Err(format!(
+                    "block {} {:?} is not an ancestor of block {number}",
+                    link.number, link.hash
+                )
+                .into());
+            }
+            next_hash = link.parent_hash;
+        }
+        Ok(links)
+    }
+
     pub fn evm_witness(&self) -> zkevm_circuits::witness::Block<Fr> {
         let mut block =
             evm_circuit::witness::block_convert(&self.block, &self.code_db).expect("block_convert");
@@ -235,6 +361,227 @@ impl CircuitWitness {
         self.eth_block.gas_used.as_u64()
     }
 
+    /// The instance hash of the block, for the evidence type of the request.
+    pub fn instance_hash(&self) -> Result<H256, String> {
+        let evidence_type = (&self.request_instance.evidence_type).try_into()?;
+        let hash = self
+            .protocol_instance
+            .hash(evidence_type)
+            .map_err(|e| e.to_string())?;
+        Ok(H256::from(hash.0))
+    }
+
+    /// Checks the witness for inconsistencies and returns all problems found.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let eth_block = &self.eth_block;
+        let block = &self.block;
+
+        // header
+        let number = eth_block.number.unwrap_or_default().as_u64();
+        if eth_block.hash.is_none() {
+            problems.push("eth_block has no hash".to_string());
+        }
+        if eth_block.number.is_none() {
+            problems.push("eth_block has no number".to_string());
+        }
+        if block.number != Word::from(number) {
+            problems.push(format!(
+                "block number {} does not match eth_block number {number}",
+                block.number
+            ));
+        }
+        if Some(block.coinbase) != eth_block.author {
+            problems.push(format!(
+                "block coinbase {:?} does not match eth_block author {:?}",
+                block.coinbase, eth_block.author
+            ));
+        }
+        if block.gas_limit != eth_block.gas_limit.as_u64() {
+            problems.push(format!(
+                "block gas limit {} does not match eth_block gas limit {}",
+                block.gas_limit, eth_block.gas_limit
+            ));
+        }
+        if block.timestamp != eth_block.timestamp {
+            problems.push(format!(
+                "block timestamp {} does not match eth_block timestamp {}",
+                block.timestamp, eth_block.timestamp
+            ));
+        }
+        if Some(block.mix_hash) != eth_block.mix_hash {
+            problems.push(format!(
+                "block mix hash {:?} does not match eth_block mix hash {:?}",
+                block.mix_hash, eth_block.mix_hash
+            ));
+        }
+        if block.base_fee != eth_block.base_fee_per_gas.unwrap_or_default() {
+            problems.push(format!(
+                "block base fee {} does not match eth_block base fee {:?}",
+                block.base_fee, eth_block.base_fee_per_gas
+            ));
+        }
+        if block.eth_block.hash != eth_block.hash {
+            problems.push(format!(
+                "block was built from block {:?} instead of {:?}",
+                block.eth_block.hash, eth_block.hash
+            ));
+        }
+
+        // state, without a previous state root the mpt circuit proves the
+        // updates on a trie of its own instead of the state proofs
+        if block.prev_state_root.is_zero() {
+            problems.push("block has no previous state root".to_string());
+        }
+
+        // history hashes, the parent hash is the last one
+        let parent_hash = Word::from_big_endian(eth_block.parent_hash.as_bytes());
+        let history_hashes = std::iter::once(("block", block))
+            .chain(self.dummy_block.iter().map(|block| ("dummy_block", block)));
+        let history_len = std::cmp::min(256, number) as usize;
+        let history_links: HashMap<Word, &HistoryLink> = self
+            .history_links
+            .iter()
+            .map(|link| (link.hash.to_word(), link))
+            .collect();
+        for (name, block) in history_hashes {
+            if block.history_hashes.len() != history_len {
+                problems.push(format!(
+                    "{name} has {} history hashes, {history_len} are expected",
+                    block.history_hashes.len()
+                ));
+            }
+            match block.history_hashes.last() {
+                Some(hash) if *hash == parent_hash => {}
+                Some(hash) => problems.push(format!(
+                    "{name} last history hash {hash:#x} is not the parent hash {parent_hash:#x}"
+                )),
+                None if number > 0 => problems.push(format!("{name} has no history hashes")),
+                None => {}
+            }
+            // the history hashes are the contiguous ancestors of the block, in order
+            if history_links.is_empty() {
+                continue;
+            }
+            let first_number = number.saturating_sub(block.history_hashes.len() as u64);
+            for (i, hash) in block.history_hashes.iter().enumerate() {
+                let Some(link) = history_links.get(hash) else {
+                    problems.push(format!(
+                        "{name} history hash {i} {hash:#x} is not an ancestor of the block"
+                    ));
+                    continue;
+                };
+                if link.number != first_number + i as u64 {
+                    problems.push(format!(
+                        "{name} history hash {i} {hash:#x} is block {}, expected block {}",
+                        link.number,
+                        first_number + i as u64
+                    ));
+                }
+                if i > 0 && link.parent_hash.to_word() != block.history_hashes[i - 1] {
+                    problems.push(format!(
+                        "{name} history hash {i} {hash:#x} does not follow {:#x}",
+                        block.history_hashes[i - 1]
+                    ));
+                }
+            }
+        }
+
+        // protocol instance
+        let pi = &self.protocol_instance;
+        if Some(pi.transition.blockHash.0) != eth_block.hash.map(|hash| hash.0) {
+            problems.push(format!(
+                "protocol instance block hash {} does not match eth_block hash {:?}",
+                pi.transition.blockHash, eth_block.hash
+            ));
+        }
+        if pi.transition.parentHash.0 != eth_block.parent_hash.0 {
+            problems.push(format!(
+                "protocol instance parent hash {} does not match eth_block parent hash {:?}",
+                pi.transition.parentHash, eth_block.parent_hash
+            ));
+        }
+        if pi.block_metadata.id != number {
+            problems.push(format!(
+                "protocol instance block id {} does not match eth_block number {number}",
+                pi.block_metadata.id
+            ));
+        }
+        if Word::from(pi.block_metadata.timestamp) != eth_block.timestamp {
+            problems.push(format!(
+                "protocol instance timestamp {} does not match eth_block timestamp {}",
+                pi.block_metadata.timestamp, eth_block.timestamp
+            ));
+        }
+        if Some(pi.block_metadata.coinbase.as_slice())
+            != eth_block.author.as_ref().map(|a| a.as_bytes())
+        {
+            problems.push(format!(
+                "protocol instance coinbase {} does not match eth_block author {:?}",
+                pi.block_metadata.coinbase, eth_block.author
+            ));
+        }
+        if Some(pi.block_metadata.difficulty.0) != eth_block.mix_hash.map(|hash| hash.0) {
+            problems.push(format!(
+                "protocol instance difficulty {} does not match eth_block mix hash {:?}",
+                pi.block_metadata.difficulty, eth_block.mix_hash
+            ));
+        }
+        match pi.abi_encode() {
+            Ok(encoded) => {
+                if block
+                    .protocol_instance
+                    .as_ref()
+                    .map(|pi| pi.abi_encode().ok())
+                    != Some(Some(encoded))
+                {
+                    problems.push("block was built with a different protocol instance".to_string());
+                }
+            }
+            Err(e) => problems.push(format!("invalid protocol instance: {e}")),
+        }
+        if let Err(e) = self.instance_hash() {
+            problems.push(format!("invalid instance hash: {e}"));
+        }
+
+        // code db
+        for (hash, code) in self.code_db.0.iter() {
+            let code_hash = CodeDB::hash(code);
+            if code_hash != *hash {
+                problems.push(format!(
+                    "code_db entry {hash:?} has code with hash {code_hash:?}"
+                ));
+            }
+        }
+
+        // circuit parameters
+        crate::match_circuit_params!(
+            self.gas_used(),
+            {
+                if self.circuit_config != CIRCUIT_CONFIG {
+                    problems.push(format!(
+                        "circuit config {:?} does not match the parameters for gas used {}: {:?}",
+                        self.circuit_config,
+                        self.gas_used(),
+                        CIRCUIT_CONFIG
+                    ));
+                }
+            },
+            {
+                problems.push(format!(
+                    "No circuit parameters found for block with gas used={}",
+                    self.gas_used()
+                ));
+            }
+        );
+
+        if let Err(err) = evm_circuit::witness::block_convert::<Fr>(&self.block, &self.code_db) {
+            problems.push(format!("block_convert failed: {err:?}"));
+        }
+
+        problems
+    }
+
     pub fn txs(&self) -> Vec<geth_types::Transaction> {
         let txs = self
             .eth_block
@@ -290,6 +637,54 @@ mod test {
     #[allow(unused_imports)]
     use super::*;
 
+    #[test]
+    fn test_validate_history_order() {
+        let circuit_config = crate::match_circuit_params!(0, CIRCUIT_CONFIG, unreachable!());
+        let mut witness = CircuitWitness::dummy(circuit_config).unwrap();
+        let number = witness.eth_block.number.unwrap().as_u64();
+        witness.history_links = (0..number)
+            .map(|i| HistoryLink {
+                number: i,
+                hash: H256::from_low_u64_be(i + 1),
+                parent_hash: H256::from_low_u64_be(i),
+            })
+            .collect();
+        witness.block.history_hashes = witness
+            .history_links
+            .iter()
+            .map(|link| link.hash.to_word())
+            .collect();
+        witness.eth_block.parent_hash = witness.history_links.last().unwrap().hash;
+        let history_problems = |witness: &CircuitWitness| {
+            witness
+                .validate()
+                .into_iter()
+                .filter(|problem| problem.contains("history"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(history_problems(&witness), Vec::<String>::new());
+
+        // misordered
+        witness.block.history_hashes.swap(10, 11);
+        let problems = history_problems(&witness);
+        assert!(
+            problems.contains(&format!(
+                "block history hash 10 {:#x} is block 11, expected block 10",
+                Word::from(12)
+            )),
+            "{problems:?}"
+        );
+
+        // not contiguous
+        witness.block.history_hashes.swap(10, 11);
+        witness.block.history_hashes.remove(0);
+        let problems = history_problems(&witness);
+        assert!(
+            problems.contains(&"block has 255 history hashes, 256 are expected".to_string()),
+            "{problems:?}"
+        );
+    }
+
     #[tokio::test]
     async fn test_geth_client() {
         let urlstr = "http://localhost:8545";
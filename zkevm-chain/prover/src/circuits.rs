@@ -1,6 +1,8 @@
 use crate::circuit_witness::CircuitWitness;
 use crate::Fr;
+use eth_types::{ToBigEndian, H256};
 use ethers_core::abi::AbiEncode;
+use halo2_proofs::halo2curves::ff::PrimeField;
 use rand::Rng;
 // use zkevm_circuits::bytecode_circuit::circuit::BytecodeCircuit;
 // use zkevm_circuits::copy_circuit::CopyCircuit;
@@ -25,23 +27,44 @@ pub fn gen_super_circuit<
     mut _rng: RNG,
 ) -> Result<SuperCircuit<Fr>, String> {
     let block = witness.dummy_evm_witness();
-    assert_eq!(
-        block.context.block_hash.encode_hex(),
-        block
-            .protocol_instance
-            .as_ref()
-            .unwrap()
-            .transition.blockHash.to_string()
-    );
-    assert_eq!(
-        block.context.history_hashes[block.context.history_hashes.len() - 1].encode_hex(),
-        block
-            .protocol_instance
-            .as_ref()
-            .unwrap()
-            .transition.parentHash.to_string()
-    );
+    let protocol_instance = block
+        .protocol_instance
+        .as_ref()
+        .ok_or_else(|| "missing protocol instance".to_string())?;
+    let block_hash = block.context.block_hash.encode_hex();
+    let expected_block_hash = protocol_instance.transition.blockHash.to_string();
+    if block_hash != expected_block_hash {
+        return Err(format!(
+            "block hash mismatch: block {block_hash}, protocol instance {expected_block_hash}"
+        ));
+    }
+    let parent_hash =
+        block.context.history_hashes[block.context.history_hashes.len() - 1].encode_hex();
+    let expected_parent_hash = protocol_instance.transition.parentHash.to_string();
+    if parent_hash != expected_parent_hash {
+        return Err(format!(
+            "parent hash mismatch: block {parent_hash}, protocol instance {expected_parent_hash}"
+        ));
+    }
     let circuit = SuperCircuit::new_from_block(&block);
+    // the MPT instance, the last one, holds the state roots before and after the block
+    let state_roots = [
+        H256(witness.block.prev_state_root.to_be_bytes()),
+        witness.eth_block.state_root,
+    ];
+    let expected_mpt_instance: Vec<Fr> = state_roots
+        .iter()
+        .flat_map(|root| {
+            let (hi, lo) = root.as_bytes().split_at(16);
+            [hi, lo].map(|half| Fr::from_u128(u128::from_be_bytes(half.try_into().unwrap())))
+        })
+        .collect();
+    if circuit.instance().last() != Some(&expected_mpt_instance) {
+        return Err(format!(
+            "state root mismatch: the mpt circuit doesn't prove {:?} -> {:?}",
+            state_roots[0], state_roots[1]
+        ));
+    }
     Ok(circuit)
 }
 
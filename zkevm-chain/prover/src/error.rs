@@ -0,0 +1,97 @@
+use crate::preflight::MockFailure;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use zkevm_common::prover::TASK_CANCELLED;
+
+/// Error type for any failure in the proving pipeline.
+#[derive(Debug)]
+pub enum ProverError {
+    /// Reading or writing a witness file failed.
+    WitnessIo(std::io::Error),
+    /// A witness file could not be decoded.
+    WitnessDecode(serde_json::Error),
+    /// A witness file has an unsupported or corrupted envelope.
+    WitnessFormat(String),
+    /// Gathering the witness from the l2 node failed.
+    WitnessFetch(String),
+    /// The witness failed validation, lists all problems found.
+    InvalidWitness(Vec<String>),
+    /// No circuit parameters are matching the gas used by the block.
+    NoCircuitParams(u64),
+    /// The requested circuit is not supported.
+    UnknownCircuit(String),
+    /// The evidence type of the request can't be proven by the circuits.
+    UnsupportedEvidence(String),
+    /// The circuit could not be built from the witness.
+    Circuit(String),
+    /// Reading the circuit parameters failed.
+    Params(String),
+    /// Generating or loading the proving key failed.
+    KeyGen(String),
+    /// The MockProver rejected the circuit, lists all failures.
+    MockProver(Vec<MockFailure>),
+    /// Creating or verifying the circuit proof failed.
+    Proving(String),
+    /// Aggregating the circuit proof failed.
+    Aggregation(String),
+    /// Compiling the evm verifier contract failed.
+    SolidityCompile(String),
+    /// Verifying the proof with the evm verifier failed.
+    EvmVerify(String),
+    /// Reading or writing a proof file failed.
+    ProofIo(std::io::Error),
+    /// A proof file could not be decoded.
+    ProofDecode(serde_json::Error),
+    /// Reading or writing the task store failed.
+    TaskStoreIo(std::io::Error),
+    /// The task store could not be encoded or decoded.
+    TaskStoreFormat(serde_json::Error),
+    /// The task was cancelled while it was computed.
+    Cancelled,
+    /// The worker process computing the task failed.
+    Worker(String),
+}
+
+impl Display for ProverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ProverError::WitnessIo(err) => write!(f, "witness io: {err}"),
+            ProverError::WitnessDecode(err) => write!(f, "witness decode: {err}"),
+            ProverError::WitnessFormat(err) => write!(f, "witness format: {err}"),
+            ProverError::WitnessFetch(err) => write!(f, "witness fetch: {err}"),
+            ProverError::InvalidWitness(problems) => {
+                write!(f, "invalid witness, {} problems:", problems.len())?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+            ProverError::NoCircuitParams(gas_used) => write!(
+                f,
+                "No circuit parameters found for block with gas used={gas_used}"
+            ),
+            ProverError::UnknownCircuit(circuit) => write!(f, "unknown circuit: {circuit}"),
+            ProverError::UnsupportedEvidence(err) => write!(f, "unsupported evidence: {err}"),
+            ProverError::Circuit(err) => write!(f, "circuit: {err}"),
+            ProverError::Params(err) => write!(f, "params: {err}"),
+            ProverError::KeyGen(err) => write!(f, "keygen: {err}"),
+            ProverError::MockProver(failures) => write!(
+                f,
+                "mock prover: {}",
+                serde_json::to_string(failures).unwrap_or_default()
+            ),
+            ProverError::Proving(err) => write!(f, "proving: {err}"),
+            ProverError::Aggregation(err) => write!(f, "aggregation: {err}"),
+            ProverError::SolidityCompile(err) => write!(f, "solidity compile: {err}"),
+            ProverError::EvmVerify(err) => write!(f, "evm verify: {err}"),
+            ProverError::ProofIo(err) => write!(f, "proof io: {err}"),
+            ProverError::ProofDecode(err) => write!(f, "proof decode: {err}"),
+            ProverError::TaskStoreIo(err) => write!(f, "task store io: {err}"),
+            ProverError::TaskStoreFormat(err) => write!(f, "task store format: {err}"),
+            ProverError::Cancelled => write!(f, "{TASK_CANCELLED}"),
+            ProverError::Worker(err) => write!(f, "worker: {err}"),
+        }
+    }
+}
+
+impl StdError for ProverError {}
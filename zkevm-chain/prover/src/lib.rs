@@ -12,9 +12,17 @@ pub type ProverParams = ParamsKZG<Bn256>;
 pub type ProverCommitmentScheme = KZGCommitmentScheme<Bn256>;
 pub type ProverKey = ProvingKey<G1Affine>;
 
+pub mod auth;
 pub mod circuit_autogen;
 pub mod circuit_witness;
 pub mod circuits;
+pub mod error;
+pub mod metrics;
+pub mod preflight;
+pub mod protocol_instance;
 pub mod server;
 pub mod shared_state;
+pub mod task_store;
 pub mod utils;
+pub mod witness_file;
+pub mod worker;
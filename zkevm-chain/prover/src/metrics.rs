@@ -0,0 +1,275 @@
+use crate::shared_state::{RwState, SharedState};
+use lazy_static::lazy_static;
+use prometheus::core::Collector;
+use prometheus::{
+    exponential_buckets, Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zkevm_common::prover::{CircuitConfig, ProofRequest};
+
+/// Content type of the text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+// phases of a task with a duration histogram
+pub const PHASE_WITNESS: &str = "witness";
+pub const PHASE_KEYGEN: &str = "keygen";
+pub const PHASE_SNARK: &str = "snark";
+pub const PHASE_AGGREGATION: &str = "aggregation";
+pub const PHASE_EVM_VERIFY: &str = "evm_verify";
+
+lazy_static! {
+    pub static ref REGISTRY: Registry = Registry::new();
+    static ref QUEUE_DEPTH: IntGauge = register(IntGauge::new(
+        "proverd_queue_depth",
+        "Number of tasks waiting to be computed"
+    ));
+    static ref TASKS: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new("proverd_tasks", "Number of tasks by state"),
+        &["state"]
+    ));
+    static ref PHASE_DURATION: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new(
+            "proverd_phase_duration_seconds",
+            "Duration of the phases of a task"
+        )
+        .buckets(exponential_buckets(0.1, 3.0, 10).unwrap()),
+        &["phase", "circuit", "config"]
+    ));
+    static ref PK_CACHE: IntCounterVec = register(IntCounterVec::new(
+        Opts::new(
+            "proverd_pk_cache_total",
+            "Proving key lookups in the on-disk cache by result"
+        ),
+        &["result"]
+    ));
+    static ref PEER_MERGE_FAILURES: IntCounter = register(IntCounter::new(
+        "proverd_peer_merge_failures_total",
+        "Number of failed task merges with the peers"
+    ));
+    static ref PROOF_SIZE: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new("proverd_proof_size_bytes", "Size of the computed proofs")
+            .buckets(exponential_buckets(256.0, 2.0, 12).unwrap()),
+        &["proof", "circuit", "config"]
+    ));
+    /// The observations kept for the daemon, if this process is a worker.
+    static ref OBSERVATIONS: Mutex<Option<Vec<Observation>>> = Mutex::new(None);
+}
+
+/// An observation of a task computed in a worker process, which is sent to
+/// the daemon and recorded there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Observation {
+    Phase {
+        phase: String,
+        circuit: String,
+        config: CircuitConfig,
+        duration: Duration,
+    },
+    ProofSize {
+        proof: String,
+        circuit: String,
+        config: CircuitConfig,
+        size: usize,
+    },
+    PkCacheLookup {
+        hit: bool,
+    },
+}
+
+/// Keeps the observations of this process from now on, see `take_observations`.
+pub fn keep_observations() {
+    *OBSERVATIONS.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+}
+
+/// Returns the observations kept since the last call.
+pub fn take_observations() -> Vec<Observation> {
+    OBSERVATIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .map(std::mem::take)
+        .unwrap_or_default()
+}
+
+/// Records the observations of a worker process.
+pub fn observe_all(observations: Vec<Observation>) {
+    for observation in observations {
+        match observation {
+            Observation::Phase {
+                phase,
+                circuit,
+                config,
+                duration,
+            } => observe_phase_duration(&phase, &circuit, &config, duration),
+            Observation::ProofSize {
+                proof,
+                circuit,
+                config,
+                size,
+            } => observe_proof_size(&proof, &circuit, &config, size),
+            Observation::PkCacheLookup { hit } => pk_cache_lookup(hit),
+        }
+    }
+}
+
+fn keep(observation: impl FnOnce() -> Observation) {
+    if let Some(observations) = OBSERVATIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+    {
+        observations.push(observation());
+    }
+}
+
+fn register<T: Collector + Clone + 'static>(metric: prometheus::Result<T>) -> T {
+    let metric = metric.expect("valid metric");
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("unique metric");
+    metric
+}
+
+/// The label of the `CircuitConfig` bucket, its block gas limit.
+fn config_label(config: &CircuitConfig) -> String {
+    config.block_gas_limit.to_string()
+}
+
+/// Records the duration of `phase` of a task, which began at `started`.
+pub fn observe_phase(phase: &str, circuit: &str, config: &CircuitConfig, started: Instant) {
+    observe_phase_duration(phase, circuit, config, started.elapsed());
+}
+
+/// Records the `duration` of `phase` of a task.
+pub fn observe_phase_duration(
+    phase: &str,
+    circuit: &str,
+    config: &CircuitConfig,
+    duration: Duration,
+) {
+    PHASE_DURATION
+        .with_label_values(&[phase, circuit, &config_label(config)])
+        .observe(duration.as_secs_f64());
+    keep(|| Observation::Phase {
+        phase: phase.to_string(),
+        circuit: circuit.to_string(),
+        config: config.clone(),
+        duration,
+    });
+}
+
+/// Records the size of a computed proof, `proof` is either "circuit" or "aggregation".
+pub fn observe_proof_size(proof: &str, circuit: &str, config: &CircuitConfig, size: usize) {
+    if size > 0 {
+        PROOF_SIZE
+            .with_label_values(&[proof, circuit, &config_label(config)])
+            .observe(size as f64);
+        keep(|| Observation::ProofSize {
+            proof: proof.to_string(),
+            circuit: circuit.to_string(),
+            config: config.clone(),
+            size,
+        });
+    }
+}
+
+/// Records a proving key lookup in the on-disk cache.
+pub fn pk_cache_lookup(hit: bool) {
+    PK_CACHE
+        .with_label_values(&[if hit { "hit" } else { "miss" }])
+        .inc();
+    keep(|| Observation::PkCacheLookup { hit });
+}
+
+/// Records a failed task merge with the peers.
+pub fn peer_merge_failure() {
+    PEER_MERGE_FAILURES.inc();
+}
+
+/// The states of a task, see `task_state`.
+const TASK_STATES: [&str; 5] = ["pending", "running", "completed", "failed", "cancelled"];
+
+fn task_state(rw: &RwState, task: &ProofRequest) -> &'static str {
+    match &task.result {
+        None if rw.obtained && rw.pending.as_ref() == Some(&task.options) => "running",
+        None => "pending",
+        Some(Ok(_)) => "completed",
+        Some(Err(_)) if task.is_cancelled() => "cancelled",
+        Some(Err(_)) => "failed",
+    }
+}
+
+/// Returns all metrics in the text exposition format.
+/// The task gauges are updated from the current state of `shared_state`.
+pub async fn encode(shared_state: &SharedState) -> Vec<u8> {
+    {
+        let rw = shared_state.rw.lock().await;
+        let mut counts: HashMap<&str, i64> = TASK_STATES.iter().map(|state| (*state, 0)).collect();
+        for task in rw.tasks.iter() {
+            *counts.get_mut(task_state(&rw, task)).unwrap() += 1;
+        }
+        QUEUE_DEPTH.set(counts["pending"]);
+        for (state, count) in counts {
+            TASKS.with_label_values(&[state]).set(count);
+        }
+    }
+    // metrics without observations yet are registered on first use
+    lazy_static::initialize(&PHASE_DURATION);
+    lazy_static::initialize(&PK_CACHE);
+    lazy_static::initialize(&PEER_MERGE_FAILURES);
+    lazy_static::initialize(&PROOF_SIZE);
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .expect("encode metrics");
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkevm_common::prover::ProofRequestOptions;
+
+    #[tokio::test]
+    async fn encode_task_states() {
+        let ss = SharedState::new("a".to_string(), None);
+        for block in 1..=2 {
+            let options = ProofRequestOptions {
+                block,
+                ..Default::default()
+            };
+            assert!(ss.get_or_enqueue(&options).await.is_none());
+        }
+        ss.rw.lock().await.tasks[1].result = Some(Err("failed".to_string()));
+        observe_proof_size("circuit", "super", &CircuitConfig::default(), 1000);
+
+        let text = String::from_utf8(encode(&ss).await).unwrap();
+        assert!(text.contains("proverd_queue_depth 1"));
+        assert!(text.contains("proverd_tasks{state=\"pending\"} 1"));
+        assert!(text.contains("proverd_tasks{state=\"failed\"} 1"));
+        assert!(text.contains("proverd_tasks{state=\"running\"} 0"));
+        assert!(text.contains(
+            "proverd_proof_size_bytes_count{circuit=\"super\",config=\"0\",proof=\"circuit\"} 1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn worker_observations() {
+        keep_observations();
+        observe_proof_size("circuit", "worker", &CircuitConfig::default(), 1000);
+        let observations = serde_json::to_string(&take_observations()).unwrap();
+
+        // the daemon records the observations of the worker
+        observe_all(serde_json::from_str(&observations).unwrap());
+        let ss = SharedState::new("a".to_string(), None);
+        let text = String::from_utf8(encode(&ss).await).unwrap();
+        assert!(text.contains(
+            "proverd_proof_size_bytes_count{circuit=\"worker\",config=\"0\",proof=\"circuit\"} 2"
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::Fr;
+use halo2_proofs::dev::{FailureLocation, MockProver, VerifyFailure};
+use halo2_proofs::plonk::Circuit;
+use serde::{Deserialize, Serialize};
+
+/// Region and gate names of the super circuit, lowercase, and the sub-circuit they belong to.
+const SUB_CIRCUITS: [(&str, &str); 11] = [
+    ("execution step", "evm"),
+    ("state circuit", "state"),
+    ("anchor", "anchor"),
+    ("bytecode", "bytecode"),
+    ("copy", "copy"),
+    ("keccak", "keccak"),
+    ("exponentiation", "exp"),
+    ("tx table", "tx"),
+    ("('pi')", "pi"),
+    ("pi acc", "pi"),
+    ("block table", "pi"),
+];
+
+/// A `VerifyFailure` of the MockProver in a serializable form.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockFailure {
+    /// The `VerifyFailure` variant, e.g. `ConstraintNotSatisfied`
+    pub kind: String,
+    /// The sub-circuit the region or gate belongs to, if known
+    pub sub_circuit: Option<String>,
+    pub region: Option<String>,
+    /// The gate, constraint or lookup that failed
+    pub gate: Option<String>,
+    /// Offset within `region`
+    pub offset: Option<isize>,
+    /// Row of failures outside of any region
+    pub row: Option<usize>,
+    pub column: Option<String>,
+    /// The failure as printed by halo2
+    pub message: String,
+}
+
+impl MockFailure {
+    fn with_location(mut self, location: &FailureLocation) -> Self {
+        match location {
+            FailureLocation::InRegion { region, offset } => {
+                self.region = Some(region.to_string());
+                self.offset = Some(*offset as isize);
+            }
+            FailureLocation::OutsideRegion { row } => {
+                self.row = Some(*row);
+            }
+        }
+        self
+    }
+
+    fn with_sub_circuit(mut self) -> Self {
+        let names = format!(
+            "{} {}",
+            self.region.as_deref().unwrap_or_default(),
+            self.gate.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+        self.sub_circuit = SUB_CIRCUITS
+            .iter()
+            .find(|(needle, _)| names.contains(needle))
+            .map(|(_, sub_circuit)| sub_circuit.to_string());
+        self
+    }
+}
+
+impl From<&VerifyFailure> for MockFailure {
+    fn from(failure: &VerifyFailure) -> Self {
+        let debug = format!("{failure:?}");
+        let kind = debug
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let base = MockFailure {
+            kind,
+            message: failure.to_string(),
+            ..Default::default()
+        };
+
+        let failure = match failure {
+            VerifyFailure::CellNotAssigned {
+                gate,
+                region,
+                column,
+                offset,
+                ..
+            } => MockFailure {
+                region: Some(region.to_string()),
+                gate: Some(gate.to_string()),
+                offset: Some(*offset),
+                column: Some(format!("{column:?}")),
+                ..base
+            },
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                ..
+            } => MockFailure {
+                gate: Some(constraint.to_string()),
+                ..base
+            }
+            .with_location(location),
+            VerifyFailure::ConstraintPoisoned { constraint } => MockFailure {
+                gate: Some(constraint.to_string()),
+                ..base
+            },
+            VerifyFailure::Lookup {
+                lookup_index,
+                location,
+                ..
+            } => MockFailure {
+                gate: Some(format!("lookup {lookup_index}")),
+                ..base
+            }
+            .with_location(location),
+            VerifyFailure::Permutation { column, location } => MockFailure {
+                column: Some(format!("{column:?}")),
+                ..base
+            }
+            .with_location(location),
+            #[allow(unreachable_patterns)]
+            _ => base,
+        };
+
+        failure.with_sub_circuit()
+    }
+}
+
+/// Runs the MockProver over `circuit` and returns all failures.
+pub fn run_mock_prover<C: Circuit<Fr>>(
+    k: u32,
+    circuit: &C,
+    instance: Vec<Vec<Fr>>,
+) -> Result<(), Vec<MockFailure>> {
+    let prover = MockProver::run(k, circuit, instance).map_err(|err| {
+        vec![MockFailure {
+            kind: "Synthesis".to_string(),
+            message: format!("{err:?}"),
+            ..Default::default()
+        }]
+    })?;
+
+    prover
+        .verify_par()
+        .map_err(|failures| failures.iter().map(MockFailure::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sub_circuit_from_names() {
+        let failure = MockFailure {
+            region: Some("Region 7 ('Execution step')".to_string()),
+            ..Default::default()
+        }
+        .with_sub_circuit();
+        assert_eq!(failure.sub_circuit.as_deref(), Some("evm"));
+
+        let failure = MockFailure {
+            gate: Some("Constraint 0 in gate 3 ('PI acc constraints')".to_string()),
+            ..Default::default()
+        }
+        .with_sub_circuit();
+        assert_eq!(failure.sub_circuit.as_deref(), Some("pi"));
+
+        let failure = MockFailure::default().with_sub_circuit();
+        assert_eq!(failure.sub_circuit, None);
+    }
+}
@@ -0,0 +1,224 @@
+use bus_mapping::circuit_input_builder::protocol_instance::L2_SIGNAL_SERVICE;
+use bus_mapping::circuit_input_builder::ANCHOR_METHOD_SIGNATURE;
+use bus_mapping::rpc::GethClient;
+use eth_types::Address;
+use eth_types::H256;
+use ethers_core::abi::{decode, ParamType, Token};
+use ethers_core::types::Filter;
+use ethers_core::utils::keccak256;
+use ethers_providers::{Http, Middleware, Provider};
+use std::error::Error;
+use std::str::FromStr;
+use zkevm_common::prover::{RequestExtraInstance, RequestMetaData};
+
+/// `TaikoEvents.BlockProposed(uint256 indexed blockId, address indexed assignedProver,
+/// uint96 livenessBond, TaikoData.BlockMetadata meta, TaikoData.EthDeposit[] depositsProcessed)`
+const BLOCK_PROPOSED_EVENT: &str = "BlockProposed(uint256,address,uint96,(bytes32,bytes32,bytes32,bytes32,bytes32,address,uint64,uint32,uint64,uint64,uint24,uint24,uint16,bool,bytes32),(address,uint96,uint64)[])";
+
+fn block_metadata_type() -> ParamType {
+    ParamType::Tuple(vec![
+        // l1Hash
+        ParamType::FixedBytes(32),
+        // difficulty
+        ParamType::FixedBytes(32),
+        // blobHash
+        ParamType::FixedBytes(32),
+        // extraData
+        ParamType::FixedBytes(32),
+        // depositsHash
+        ParamType::FixedBytes(32),
+        // coinbase
+        ParamType::Address,
+        // id
+        ParamType::Uint(64),
+        // gasLimit
+        ParamType::Uint(32),
+        // timestamp
+        ParamType::Uint(64),
+        // l1Height
+        ParamType::Uint(64),
+        // txListByteOffset
+        ParamType::Uint(24),
+        // txListByteSize
+        ParamType::Uint(24),
+        // minTier
+        ParamType::Uint(16),
+        // blobUsed
+        ParamType::Bool,
+        // parentMetaHash
+        ParamType::FixedBytes(32),
+    ])
+}
+
+fn eth_deposit_type() -> ParamType {
+    ParamType::Tuple(vec![
+        // recipient
+        ParamType::Address,
+        // amount
+        ParamType::Uint(96),
+        // id
+        ParamType::Uint(64),
+    ])
+}
+
+fn token_hex(token: &Token) -> Result<String, String> {
+    match token {
+        Token::FixedBytes(bytes) => Ok(hex::encode(bytes)),
+        Token::Address(address) => Ok(hex::encode(address)),
+        _ => Err(format!("expected bytes32 or address, got {token:?}")),
+    }
+}
+
+fn token_u64(token: &Token) -> Result<u64, String> {
+    match token {
+        Token::Uint(value) if value.bits() <= 64 => Ok(value.as_u64()),
+        _ => Err(format!("expected uint64, got {token:?}")),
+    }
+}
+
+fn token_bool(token: &Token) -> Result<bool, String> {
+    match token {
+        Token::Bool(value) => Ok(*value),
+        _ => Err(format!("expected bool, got {token:?}")),
+    }
+}
+
+/// Decodes the `BlockMetadata` tuple of the `BlockProposed` event.
+fn decode_block_metadata(token: &Token) -> Result<RequestMetaData, String> {
+    let fields = match token {
+        Token::Tuple(fields) if fields.len() == 15 => fields,
+        _ => return Err(format!("expected BlockMetadata, got {token:?}")),
+    };
+
+    Ok(RequestMetaData {
+        l1_hash: token_hex(&fields[0])?,
+        difficulty: token_hex(&fields[1])?,
+        blob_hash: token_hex(&fields[2])?,
+        extra_data: token_hex(&fields[3])?,
+        deposits_hash: token_hex(&fields[4])?,
+        coinbase: token_hex(&fields[5])?,
+        id: token_u64(&fields[6])?,
+        gas_limit: token_u64(&fields[7])? as u32,
+        timestamp: token_u64(&fields[8])?,
+        l1_height: token_u64(&fields[9])?,
+        tx_list_byte_offset: token_u64(&fields[10])? as u32,
+        tx_list_byte_size: token_u64(&fields[11])? as u32,
+        min_tier: token_u64(&fields[12])? as u16,
+        blob_used: token_bool(&fields[13])?,
+        parent_metahash: token_hex(&fields[14])?,
+    })
+}
+
+/// Returns the l1 height and the parent gas used from the anchor transaction,
+/// which is always the first transaction of a l2 block.
+fn decode_anchor(
+    block: &eth_types::Block<eth_types::Transaction>,
+) -> Result<(u64, u32), Box<dyn Error>> {
+    let input = block
+        .transactions
+        .first()
+        .map(|tx| tx.input.as_ref())
+        .ok_or("block without anchor transaction")?;
+    if input.len() < 4 || input[..4] != ANCHOR_METHOD_SIGNATURE.to_be_bytes() {
+        return Err("first transaction is not an anchor transaction".into());
+    }
+
+    // anchor(bytes32 l1BlockHash, bytes32 l1SignalRoot, uint64 l1Height, uint32 parentGasUsed)
+    let tokens = decode(
+        &[
+            ParamType::FixedBytes(32),
+            ParamType::FixedBytes(32),
+            ParamType::Uint(64),
+            ParamType::Uint(32),
+        ],
+        &input[4..],
+    )?;
+
+    Ok((token_u64(&tokens[2])?, token_u64(&tokens[3])? as u32))
+}
+
+/// Fills `pi` with the protocol instance of l2 block `block_num`.
+/// The block metadata and the assigned prover are taken from the `BlockProposed`
+/// event emitted by `l1_contract` on `l1_rpc_url`, the remaining values from `l2_rpc_url`.
+/// A `prover` or `graffiti` already set in `pi` is kept, the `blob` of a block
+/// posting its tx list in a blob is required to be set.
+pub async fn fetch_protocol_instance(
+    block_num: u64,
+    l1_rpc_url: &str,
+    l1_contract: &str,
+    l2_rpc_url: &str,
+    pi: &mut RequestExtraInstance,
+) -> Result<(), Box<dyn Error>> {
+    let geth_client = GethClient::new(Http::from_str(l2_rpc_url)?);
+    let block = geth_client.get_block_by_number(block_num.into()).await?;
+    let (l1_height, parent_gas_used) = decode_anchor(&block)?;
+
+    // the block is proposed in the l1 block after the anchored one
+    let l1_provider = Provider::<Http>::try_from(l1_rpc_url)?;
+    let filter = Filter::new()
+        .address(Address::from_str(l1_contract)?)
+        .event(BLOCK_PROPOSED_EVENT)
+        .topic1(H256::from_low_u64_be(block_num))
+        .from_block(l1_height + 1)
+        .to_block(l1_height + 1);
+    let logs = l1_provider.get_logs(&filter).await?;
+    let log = logs.first().ok_or_else(|| {
+        format!(
+            "no BlockProposed event for block {block_num} in l1 block {}",
+            l1_height + 1
+        )
+    })?;
+    let assigned_prover = log
+        .topics
+        .get(2)
+        .ok_or("BlockProposed event without assigned prover")?;
+
+    // livenessBond, meta, depositsProcessed
+    let tokens = decode(
+        &[
+            ParamType::Uint(96),
+            block_metadata_type(),
+            ParamType::Array(Box::new(eth_deposit_type())),
+        ],
+        &log.data,
+    )?;
+    let request_meta_data = decode_block_metadata(&tokens[1])?;
+    if request_meta_data.id != block_num {
+        return Err(format!(
+            "BlockProposed event for block {} instead of {block_num}",
+            request_meta_data.id
+        )
+        .into());
+    }
+    // the blob is not available from the l1 node, it has to come with the request
+    if request_meta_data.blob_used && pi.blob.is_empty() {
+        return Err(
+            format!("block {block_num} uses a blob, which is missing in the request").into(),
+        );
+    }
+
+    let l2_signal_service = if pi.l2_signal_service.is_empty() {
+        *L2_SIGNAL_SERVICE
+    } else {
+        Address::from_str(&pi.l2_signal_service)?
+    };
+    let signal_proof = geth_client
+        .get_proof(l2_signal_service, Vec::new(), block_num.into())
+        .await?;
+
+    pi.l2_signal_service = hex::encode(l2_signal_service);
+    pi.request_meta_data = request_meta_data;
+    pi.block_hash = hex::encode(block.hash.ok_or("block without hash")?);
+    pi.parent_hash = hex::encode(block.parent_hash);
+    pi.signal_root = hex::encode(signal_proof.storage_hash);
+    if pi.graffiti.is_empty() {
+        pi.graffiti = hex::encode(H256::zero());
+    }
+    if pi.prover.is_empty() {
+        pi.prover = hex::encode(Address::from(*assigned_prover));
+    }
+    pi.gas_used = block.gas_used.as_u32();
+    pi.parent_gas_used = parent_gas_used;
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
+use crate::auth::{AuthClient, Scope};
 use crate::circuit_witness::CircuitWitness;
+use crate::metrics;
 use crate::shared_state::SharedState;
 use hyper::body::Buf;
 use hyper::body::HttpBody;
@@ -44,7 +46,10 @@ fn set_headers(headers: &mut hyper::HeaderMap, extended: bool) {
         );
         headers.insert(
             "access-control-allow-headers",
-            HeaderValue::from_static("origin, content-type, accept, x-requested-with"),
+            HeaderValue::from_static(
+                "origin, content-type, accept, x-requested-with, authorization, \
+                 x-proverd-client, x-proverd-timestamp, x-proverd-signature",
+            ),
         );
         headers.insert("access-control-max-age", HeaderValue::from_static("300"));
     }
@@ -53,6 +58,44 @@ fn set_headers(headers: &mut hyper::HeaderMap, extended: bool) {
 async fn handle_request(
     shared_state: SharedState,
     req: Request<Body>,
+) -> Result<Response<Body>, hyper::Error> {
+    let mut resp = route_request(&shared_state, req).await?;
+    if shared_state.ro.auth.is_some() {
+        // browsers of any origin must not use the credentials of a client
+        resp.headers_mut().remove("access-control-allow-origin");
+    }
+
+    Ok(resp)
+}
+
+/// Returns an error response unless the request with `headers` is sent by
+/// a client with at least `scope`, if authentication is enabled.
+fn authorize(
+    shared_state: &SharedState,
+    headers: &hyper::HeaderMap,
+    scope: Scope,
+) -> Result<(), Response<Body>> {
+    let Some(auth) = &shared_state.ro.auth else {
+        return Ok(());
+    };
+    let (status, err) = match auth.authenticate(headers, &[]) {
+        Ok(client) if client.scope >= scope => return Ok(()),
+        Ok(client) => (
+            StatusCode::FORBIDDEN,
+            format!("client {} lacks the {scope:?} scope", client.id),
+        ),
+        Err(err) => (StatusCode::UNAUTHORIZED, err),
+    };
+    log::info!("unauthorized request: {}", err);
+    let mut resp = Response::new(Body::from(err));
+    *resp.status_mut() = status;
+
+    Err(resp)
+}
+
+async fn route_request(
+    shared_state: &SharedState,
+    req: Request<Body>,
 ) -> Result<Response<Body>, hyper::Error> {
     {
         // limits the request size
@@ -77,10 +120,26 @@ async fn handle_request(
             Ok(resp)
         }
 
+        // prometheus metrics
+        (&Method::GET, "/metrics") => {
+            if let Err(resp) = authorize(shared_state, req.headers(), Scope::Read) {
+                return Ok(resp);
+            }
+            let mut resp = Response::new(Body::from(metrics::encode(shared_state).await));
+            resp.headers_mut().insert(
+                "content-type",
+                HeaderValue::from_static(metrics::CONTENT_TYPE),
+            );
+            Ok(resp)
+        }
+
         // returns http 200 if busy else 204.
         // can be used programmatically for e.g. shutting down the instance if no workis being
         // done.
         (&Method::GET, "/status") => {
+            if let Err(resp) = authorize(shared_state, req.headers(), Scope::Read) {
+                return Ok(resp);
+            }
             let rw = shared_state.rw.lock().await;
             let is_busy = rw.pending.is_some() || rw.tasks.iter().any(|e| e.result.is_none());
             drop(rw);
@@ -96,32 +155,49 @@ async fn handle_request(
 
         // json-rpc
         (&Method::POST, "/") => {
-            let body_bytes = hyper::body::aggregate(req.into_body())
-                .await
-                .unwrap()
-                .reader();
+            let (parts, body) = req.into_parts();
+            let body_bytes = hyper::body::to_bytes(body).await?;
+
+            // the authenticated client, if authentication is enabled
+            let client = match &shared_state.ro.auth {
+                Some(auth) => match auth.authenticate(&parts.headers, &body_bytes) {
+                    Ok(client) => Some(client.clone()),
+                    Err(err) => {
+                        log::info!("unauthorized request: {}", err);
+                        let mut resp = json_rpc_error(0.into(), -32001, err);
+                        *resp.status_mut() = StatusCode::UNAUTHORIZED;
+                        return Ok(resp);
+                    }
+                },
+                None => None,
+            };
+
             let json_req: Result<JsonRpcRequest<Vec<serde_json::Value>>, serde_json::Error> =
-                serde_json::from_reader(body_bytes);
+                serde_json::from_reader(body_bytes.reader());
 
             if let Err(err) = json_req {
-                let payload = serde_json::to_vec(&JsonRpcResponseError {
-                    jsonrpc: "2.0".to_string(),
-                    id: 0.into(),
-                    error: JsonRpcError {
-                        // parser error
-                        code: -32700,
-                        message: err.to_string(),
-                    },
-                })
-                .unwrap();
-                let mut resp = Response::new(Body::from(payload));
-                set_headers(resp.headers_mut(), false);
-                return Ok(resp);
+                // parser error
+                return Ok(json_rpc_error(0.into(), -32700, err.to_string()));
             }
 
             let json_req = json_req.unwrap();
-            let result: Result<serde_json::Value, String> =
-                handle_method(json_req.method.as_str(), &json_req.params, &shared_state).await;
+            if let Some(client) = &client {
+                let scope = Scope::of_method(&json_req.method);
+                if client.scope < scope {
+                    let err = format!("method {} requires the {scope:?} scope", json_req.method);
+                    let mut resp = json_rpc_error(json_req.id, -32001, err);
+                    *resp.status_mut() = StatusCode::FORBIDDEN;
+                    return Ok(resp);
+                }
+            }
+
+            let result: Result<serde_json::Value, String> = handle_method(
+                json_req.method.as_str(),
+                &json_req.params,
+                shared_state,
+                client.as_ref(),
+            )
+            .await;
             let payload = match result {
                 Err(err) => {
                     serde_json::to_vec(&JsonRpcResponseError {
@@ -161,10 +237,25 @@ async fn handle_request(
     }
 }
 
+/// Returns a json-rpc error response.
+fn json_rpc_error(id: serde_json::Value, code: i32, message: String) -> Response<Body> {
+    let payload = serde_json::to_vec(&JsonRpcResponseError {
+        jsonrpc: "2.0".to_string(),
+        id,
+        error: JsonRpcError { code, message },
+    })
+    .unwrap();
+    let mut resp = Response::new(Body::from(payload));
+    set_headers(resp.headers_mut(), false);
+    resp
+}
+
+/// `client` is the authenticated client, if authentication is enabled.
 async fn handle_method(
     method: &str,
     params: &[serde_json::Value],
     shared_state: &SharedState,
+    client: Option<&AuthClient>,
 ) -> Result<serde_json::Value, String> {
     match method {
         // enqueues a task for computating proof for any given block
@@ -173,9 +264,11 @@ async fn handle_method(
             let options: ProofRequestOptions =
                 serde_json::from_value(options.to_owned()).map_err(|e| e.to_string())?;
 
-            shared_state
-                .get_or_enqueue(&options)
-                .await
+            let result = match client {
+                Some(client) => shared_state.get_or_enqueue_for(&options, client).await?,
+                None => shared_state.get_or_enqueue(&options).await,
+            };
+            result
                 .map(|result| serde_json::to_value(result?).map_err(|e| e.to_string()))
                 .unwrap_or_else(|| Ok(serde_json::Value::Null))
         }
@@ -204,7 +297,15 @@ async fn handle_method(
             Ok(serde_json::to_value(circuit_config).unwrap())
         }
 
-        // TODO: Add the abilitity to abort the current task.
+        // cancels a pending or running task, returns `false` if there is no
+        // such task or it is already completed
+        "cancel" => {
+            let options = params.get(0).ok_or("expected struct ProofRequestOptions")?;
+            let options: ProofRequestOptions =
+                serde_json::from_value(options.to_owned()).map_err(|e| e.to_string())?;
+
+            Ok(serde_json::Value::Bool(shared_state.cancel(&options).await))
+        }
 
         // returns `NodeInformation`
         // used internally for p2p communication
@@ -242,16 +343,15 @@ async fn handle_method(
             let options = params.get(0).ok_or("expected struct FlushRequestOptions")?;
             let options: FlushRequestOptions =
                 serde_json::from_value(options.to_owned()).map_err(|e| e.to_string())?;
-            let mut rw_state = shared_state.rw.lock().await;
 
             if options.cache {
-                rw_state.pk_cache.clear();
+                shared_state.rw.lock().await.pk_cache.clear();
             }
             if options.pending {
-                rw_state.tasks.retain(|e| e.result.is_some());
+                shared_state.retain_tasks(|e| e.result.is_some()).await;
             }
             if options.completed {
-                rw_state.tasks.retain(|e| e.result.is_none());
+                shared_state.retain_tasks(|e| e.result.is_none()).await;
             }
 
             Ok(serde_json::Value::Bool(true))
@@ -261,25 +361,15 @@ async fn handle_method(
         // the following methods can be used to programmatically
         // prune the `tasks` from the list.
         "flushAll" => {
-            shared_state.rw.lock().await.tasks.clear();
+            shared_state.retain_tasks(|_| false).await;
             Ok(serde_json::Value::Bool(true))
         }
         "flushPending" => {
-            shared_state
-                .rw
-                .lock()
-                .await
-                .tasks
-                .retain(|e| e.result.is_some());
+            shared_state.retain_tasks(|e| e.result.is_some()).await;
             Ok(serde_json::Value::Bool(true))
         }
         "flushCompleted" => {
-            shared_state
-                .rw
-                .lock()
-                .await
-                .tasks
-                .retain(|e| e.result.is_none());
+            shared_state.retain_tasks(|e| e.result.is_none()).await;
             Ok(serde_json::Value::Bool(true))
         }
         _ => Err("this method is not available".to_string()),
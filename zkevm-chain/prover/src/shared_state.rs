@@ -1,33 +1,39 @@
+use crate::auth::{Auth, AuthClient};
 use crate::circuit_witness::CircuitWitness;
 use crate::circuits::*;
+use crate::error::ProverError;
+use crate::metrics;
+use crate::preflight::run_mock_prover;
+use crate::task_store::{unix_timestamp, MemoryTaskStore, TaskRetention, TaskStore};
 use crate::utils::collect_instance_hex;
 use crate::utils::fixed_rng;
 use crate::utils::gen_proof;
+use crate::witness_file;
+use crate::worker::{run_in_worker, WorkerConfig};
 use crate::Fr;
 use crate::G1Affine;
 use crate::ProverKey;
 use crate::ProverParams;
+use crate::VERSION;
 
 use eth_types::Bytes;
 use eth_types::ToBigEndian;
+use eth_types::H256;
 use eth_types::U256;
+use ethers_core::utils::keccak256;
 use serde_json::{json, Value};
 use std::fs::write;
-use std::process::exit;
 use std::str::FromStr;
 use std::time::SystemTime;
 
 mod evm_verifier_helper {
-    pub use circuit_benchmarks::taiko_super_circuit::{
-        evm_verify, gen_verifier, gevulot_evm_verify,
-    };
+    pub use circuit_benchmarks::taiko_super_circuit::{gen_verifier, gevulot_evm_verify};
     // cannot fork
     // pub use snark_verifier::loader::evm;
     pub use zkevm_circuits::root_circuit::taiko_aggregation::AccumulationSchemeType;
     pub use zkevm_circuits::root_circuit::Config;
 }
 
-use halo2_proofs::dev::MockProver;
 use halo2_proofs::plonk::Circuit;
 use halo2_proofs::plonk::{keygen_pk, keygen_vk};
 use halo2_proofs::poly::commitment::Params;
@@ -35,22 +41,27 @@ use halo2_proofs::SerdeFormat;
 use hyper::Uri;
 use rand::{thread_rng, Rng};
 use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
-use snark_verifier_sdk::evm::gen_evm_proof_gwc;
-use snark_verifier_sdk::halo2::gen_snark_gwc;
+use snark_verifier_sdk::evm::{gen_evm_proof_gwc, gen_evm_proof_shplonk};
+use snark_verifier_sdk::halo2::aggregation::AccumulationSchemeSDK;
+use snark_verifier_sdk::halo2::{gen_snark_gwc, gen_snark_shplonk};
 use snark_verifier_sdk::CircuitExt;
-use snark_verifier_sdk::GWC;
+use snark_verifier_sdk::Snark;
+use snark_verifier_sdk::{GWC, SHPLONK};
 use std::collections::HashMap;
 use std::fmt::Write;
 use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
 use std::io::Write as IoWrite;
 use std::net::ToSocketAddrs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::Mutex;
 use zkevm_circuits::root_circuit::TaikoAggregationCircuit;
 use zkevm_circuits::util::SubCircuit;
-use zkevm_common::json_rpc::jsonrpc_request_client;
+use zkevm_common::json_rpc::jsonrpc_request_client_auth;
 use zkevm_common::prover::*;
 
 use libc::c_char;
@@ -62,7 +73,7 @@ extern "C" {
     fn gevulot_compile(src: *const c_char) -> *const c_char;
 }
 
-fn local_compile_solidity(code: String) -> Vec<u8> {
+fn local_compile_solidity(code: String) -> Result<Vec<u8>, ProverError> {
     let template = r#"{
         "language": "Solidity",
         "sources": {
@@ -86,29 +97,32 @@ fn local_compile_solidity(code: String) -> Vec<u8> {
 
     let request = template.replace("asdf", &code);
 
-    let c_string = CString::new(request).expect("CString::new failed");
+    let c_string =
+        CString::new(request).map_err(|e| ProverError::SolidityCompile(e.to_string()))?;
     let cstr = c_string.into_raw(); // Move ownership to C
     let c_buf: *const c_char = unsafe { gevulot_compile(cstr) };
     let c_str: &CStr = unsafe { CStr::from_ptr(c_buf) };
-    let response: &str = c_str.to_str().unwrap();
-
-    let m: HashMap<String, Value> = serde_json::from_str(&response).unwrap();
-    let contracts = m.get("contracts").unwrap();
-    let filea = contracts.get("fileA").unwrap();
-    let halo2 = filea.get("Halo2Verifier").unwrap();
-    let evm = halo2.get("evm").unwrap();
-    let bytecode = evm.get("bytecode").unwrap();
-    let object = bytecode
-        .get("object")
-        .unwrap()
-        .as_str()
-        .unwrap()
-        .to_string();
-
-    let decoded = hex::decode(object).expect("Decoding failed");
+    let response: &str = c_str
+        .to_str()
+        .map_err(|e| ProverError::SolidityCompile(e.to_string()))?;
+
+    let m: HashMap<String, Value> =
+        serde_json::from_str(&response).map_err(|e| ProverError::SolidityCompile(e.to_string()))?;
+    let object = m
+        .get("contracts")
+        .and_then(|contracts| contracts.get("fileA"))
+        .and_then(|filea| filea.get("Halo2Verifier"))
+        .and_then(|halo2| halo2.get("evm"))
+        .and_then(|evm| evm.get("bytecode"))
+        .and_then(|bytecode| bytecode.get("object"))
+        .and_then(|object| object.as_str())
+        // solc reports problems in `errors` instead of the contract output
+        .ok_or_else(|| ProverError::SolidityCompile(format!("no bytecode in {response}")))?;
+
+    let decoded = hex::decode(object).map_err(|e| ProverError::SolidityCompile(e.to_string()))?;
 
     println!("decoded len{:?}", decoded.len());
-    decoded
+    Ok(decoded)
 }
 
 fn get_param_path(path: &String, k: usize) -> PathBuf {
@@ -120,41 +134,280 @@ fn get_param_path(path: &String, k: usize) -> PathBuf {
     }
 }
 
-fn get_or_gen_param(task_options: &ProofRequestOptions, k: usize) -> (Arc<ProverParams>, String) {
+/// Returns the params and an identifier for them.
+/// Params read from a file are identified by the hash of the file content.
+fn get_or_gen_param(
+    task_options: &ProofRequestOptions,
+    k: usize,
+) -> Result<(Arc<ProverParams>, String), ProverError> {
     match &task_options.param {
         Some(v) => {
-            let _cur = std::env::current_dir().unwrap();
             let path = get_param_path(v, k);
-            let file = File::open(&path).expect(&format!("couldn't open path {:?}", path));
+            let bytes = std::fs::read(&path)
+                .map_err(|e| ProverError::Params(format!("couldn't open path {path:?}: {e}")))?;
             let params = Arc::new(
-                ProverParams::read(&mut std::io::BufReader::new(file))
-                    .expect("Failed to read params"),
+                ProverParams::read(&mut bytes.as_slice())
+                    .map_err(|e| ProverError::Params(format!("Failed to read params: {e}")))?,
             );
 
-            (params, path.to_str().unwrap().into())
+            Ok((params, hex::encode(keccak256(&bytes))))
         }
         None => {
             let param = ProverParams::setup(k as u32, fixed_rng());
             if std::env::var("PROVERD_DUMP").is_ok() {
-                param
-                    .write_custom(
-                        &mut File::create(format!("params-{k}")).unwrap(),
-                        SerdeFormat::RawBytesUnchecked,
-                    )
-                    .unwrap();
+                File::create(format!("params-{k}"))
+                    .and_then(|mut file| {
+                        param.write_custom(&mut file, SerdeFormat::RawBytesUnchecked)
+                    })
+                    .map_err(|e| ProverError::Params(e.to_string()))?;
             }
             let param = Arc::new(param);
-            (param, format!("{k}"))
+            Ok((param, format!("{k}")))
         }
     }
 }
 
+/// Writes `pk` and its verifying key next to `path` with the `pk` and `vk` extensions.
+/// Files are written to a temporary location first and then renamed, so that
+/// an interrupted write never leaves a truncated key behind.
+fn write_pk(path: &Path, pk: &ProverKey) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    for (extension, is_pk) in [("vk", false), ("pk", true)] {
+        let target = path.with_extension(extension);
+        let tmp = path.with_extension(format!("{extension}.tmp"));
+        {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            if is_pk {
+                pk.write(&mut writer, SerdeFormat::RawBytes)?;
+            } else {
+                pk.get_vk().write(&mut writer, SerdeFormat::RawBytes)?;
+            }
+            writer.flush()?;
+        }
+        std::fs::rename(&tmp, &target)?;
+    }
+
+    Ok(())
+}
+
+/// Aggregates `snarks` with the accumulation scheme `AS` into `aggregation_proof`
+/// and appends the bytecode of its evm verifier to `bytecode`.
+/// Only generates the proving key in `ProverMode::KeyGen`.
+#[allow(clippy::too_many_arguments)]
+fn aggregate_snarks<AS: AccumulationSchemeSDK>(
+    shared_state: &SharedState,
+    task_options: &ProofRequestOptions,
+    circuit_config: CircuitConfig,
+    mut aggregation_param: ProverParams,
+    param_id: &str,
+    snarks: Vec<Snark>,
+    aggregation_proof: &mut ProofResult,
+    bytecode: &mut Vec<u8>,
+    start: u128,
+) -> Result<(), ProverError> {
+    if aggregation_param.k() as usize > circuit_config.min_k_aggregation {
+        aggregation_param.downsize(circuit_config.min_k_aggregation as u32);
+        aggregation_proof.k = aggregation_param.k() as u8;
+    }
+    let (agg_params, agg_param_id) = (aggregation_param, param_id);
+    aggregation_proof.k = agg_params.k() as u8;
+    let batch_size = snarks.len();
+    let agg_circuit = {
+        let time_started = Instant::now();
+        let v = TaikoAggregationCircuit::<AS>::new(&agg_params, snarks)
+            .map_err(|e| ProverError::Aggregation(format!("{e:?}")))?;
+        aggregation_proof.aux.circuit =
+            Instant::now().duration_since(time_started).as_millis() as u32;
+        v
+    };
+
+    let keygen_started = Instant::now();
+    let agg_pk = {
+        // the aggregation circuit depends on the number of aggregated snarks
+        let mut cache_key = format!(
+            "{}-agg-{}-{}{:?}",
+            &task_options.circuit,
+            &agg_param_id,
+            agg_params.k(),
+            &circuit_config
+        );
+        if batch_size > 1 {
+            cache_key.push_str(&format!("-batch{batch_size}"));
+        }
+        if task_options.accumulation_scheme != AccumulationScheme::Gwc {
+            cache_key.push_str(&format!("-{:?}", task_options.accumulation_scheme));
+        }
+        shared_state
+            .gen_pk(
+                &cache_key,
+                task_options.pk_cache_path.as_deref(),
+                &Arc::new(agg_params.clone()),
+                &agg_circuit,
+                &mut aggregation_proof.aux,
+            )
+            .map_err(|e| ProverError::KeyGen(e.to_string()))?
+    };
+    metrics::observe_phase(
+        metrics::PHASE_KEYGEN,
+        &task_options.circuit,
+        &circuit_config,
+        keygen_started,
+    );
+    shared_state.checkpoint("aggregation keygen")?;
+    println!(
+        "done app_pk {:?} ms",
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            - start
+    );
+
+    if task_options.prover_mode == ProverMode::KeyGen {
+        // only the keys were requested
+        return Ok(());
+    }
+
+    let agg_instance = agg_circuit.instance();
+    for fr in &agg_instance[0] {
+        let frstr = format!("{:?}", fr);
+        aggregation_proof.instance.push(frstr);
+    }
+
+    let proof = {
+        let time_started = Instant::now();
+        // #[cfg(feature = "evm-verifier")]
+        let (num_instances, instances, accumulator_indices) = {
+            (
+                agg_circuit.num_instance().clone(),
+                agg_circuit.instance().clone(),
+                Some(agg_circuit.accumulator_indices()),
+            )
+        };
+
+        println!(
+            "gen_evm_proof {:?} {:?} ms",
+            task_options.accumulation_scheme,
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+                - start
+        );
+        let aggregation_started = Instant::now();
+        let v = match task_options.accumulation_scheme {
+            AccumulationScheme::Gwc => {
+                gen_evm_proof_gwc(&agg_params, &agg_pk, agg_circuit, agg_instance)
+            }
+            AccumulationScheme::Shplonk => {
+                gen_evm_proof_shplonk(&agg_params, &agg_pk, agg_circuit, agg_instance)
+            }
+        };
+        metrics::observe_phase(
+            metrics::PHASE_AGGREGATION,
+            &task_options.circuit,
+            &circuit_config,
+            aggregation_started,
+        );
+        shared_state.checkpoint("aggregation")?;
+        println!(
+            "v length {:?}, {:?} ms",
+            v.len(),
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+                - start
+        );
+        {
+            println!(
+                "start gen_verifier {:?} ms",
+                SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis()
+                    - start
+            );
+
+            let deployment_code = evm_verifier_helper::gen_verifier(
+                &agg_params,
+                &agg_pk.get_vk(),
+                evm_verifier_helper::Config::kzg()
+                    .with_num_instance(num_instances.clone())
+                    .with_accumulator_indices(accumulator_indices),
+                num_instances,
+                task_options.accumulation_scheme.into(),
+            );
+            println!("deployment_code len {:?}", deployment_code.len());
+            // cannot fork
+            // let evm_verifier_bytecode =
+            //     evm_verifier_helper::evm::compile_solidity(&deployment_code);
+            let evm_verifier_bytecode = local_compile_solidity(deployment_code)?;
+
+            println!(
+                "evm_verifier_bytecode len {:?}",
+                evm_verifier_bytecode.len()
+            );
+
+            println!(
+                "evm_verifier_bytecode hash {}={}",
+                verifier_key(
+                    &circuit_config,
+                    task_options.accumulation_scheme,
+                    batch_size
+                ),
+                verifier_bytecode_hash(&evm_verifier_bytecode)
+            );
+
+            bytecode.extend_from_slice(&evm_verifier_bytecode.as_slice());
+            let verify_started = Instant::now();
+            evm_verifier_helper::gevulot_evm_verify(evm_verifier_bytecode, instances, v.clone())
+                .map_err(ProverError::EvmVerify)?;
+            metrics::observe_phase(
+                metrics::PHASE_EVM_VERIFY,
+                &task_options.circuit,
+                &circuit_config,
+                verify_started,
+            );
+            println!("done evm_verify");
+        }
+
+        aggregation_proof.aux.proof =
+            Instant::now().duration_since(time_started).as_millis() as u32;
+        v
+    };
+
+    if std::env::var("PROVERD_DUMP").is_ok() {
+        File::create(format!(
+            "proof-{}-agg--{:?}",
+            task_options.circuit, &circuit_config
+        ))
+        .and_then(|mut file| file.write_all(&proof))
+        .map_err(ProverError::ProofIo)?;
+    }
+    aggregation_proof.proof = proof.into();
+
+    Ok(())
+}
+
 fn compute_proof<C: Circuit<Fr> + Clone + SubCircuit<Fr> + CircuitExt<Fr>>(
     shared_state: &SharedState,
     task_options: &ProofRequestOptions,
     circuit_config: CircuitConfig,
-    circuit: C,
-) -> Result<(CircuitConfig, ProofResult, ProofResult, Vec<u8>), String> {
+    circuits: Vec<C>,
+) -> Result<
+    (
+        CircuitConfig,
+        ProofResult,
+        ProofResult,
+        Vec<u8>,
+        Vec<ProofResult>,
+    ),
+    ProverError,
+> {
     let start = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap()
@@ -183,20 +436,45 @@ fn compute_proof<C: Circuit<Fr> + Clone + SubCircuit<Fr> + CircuitExt<Fr>>(
         ),
         ..Default::default()
     };
+    let mut batch = Vec::<ProofResult>::new();
+
+    if circuits.is_empty() {
+        return Err(ProverError::Aggregation("no circuit to prove".to_string()));
+    }
+    if circuits.len() > 1 && !task_options.aggregate {
+        return Err(ProverError::Aggregation(
+            "a batch of blocks requires aggregation".to_string(),
+        ));
+    }
+    let instances: Vec<Vec<Vec<Fr>>> = circuits.iter().map(|circuit| circuit.instance()).collect();
+    circuit_proof.instance = instances
+        .iter()
+        .flat_map(|instance| collect_instance_hex(instance))
+        .collect();
 
     if task_options.mock {
         // only run the mock prover
         let time_started = Instant::now();
         circuit_proof.k = circuit_config.min_k as u8;
-        circuit_proof.instance = collect_instance_hex(&circuit.instance());
-        let prover = MockProver::run(circuit_config.min_k as u32, &circuit, circuit.instance())
-            .expect("MockProver::run");
-        prover.verify_par().expect("MockProver::verify_par");
+        for (circuit, instance) in circuits.iter().zip(instances.iter()) {
+            run_mock_prover(circuit_config.min_k as u32, circuit, instance.clone())
+                .map_err(ProverError::MockProver)?;
+        }
         circuit_proof.aux.mock = Instant::now().duration_since(time_started).as_millis() as u32;
     } else {
+        if task_options.preflight {
+            let time_started = Instant::now();
+            for (circuit, instance) in circuits.iter().zip(instances.iter()) {
+                run_mock_prover(circuit_config.min_k as u32, circuit, instance.clone())
+                    .map_err(ProverError::MockProver)?;
+            }
+            circuit_proof.aux.mock = Instant::now().duration_since(time_started).as_millis() as u32;
+            println!("done preflight {:?} ms", circuit_proof.aux.mock);
+        }
+
         let universe_k = circuit_config.min_k.max(circuit_config.min_k_aggregation);
-        let (base_param, param_path) = get_or_gen_param(task_options, universe_k);
-        let mut aggregation_param = (*base_param).clone();
+        let (base_param, param_id) = get_or_gen_param(task_options, universe_k)?;
+        let aggregation_param = (*base_param).clone();
         let mut circuit_param = aggregation_param.clone();
         if circuit_param.k() as usize > circuit_config.min_k {
             circuit_param.downsize(circuit_config.min_k as u32);
@@ -212,21 +490,33 @@ fn compute_proof<C: Circuit<Fr> + Clone + SubCircuit<Fr> + CircuitExt<Fr>>(
                 - start
         );
 
-        // generate and cache the prover key
+        // generate and cache the prover key, shared by all circuits of a batch
+        let keygen_started = Instant::now();
         let pk = {
             let cache_key = format!(
-                "{}{}{:?}",
-                &task_options.circuit, &param_path, &circuit_config
+                "{}-{}-{}{:?}",
+                &task_options.circuit,
+                &param_id,
+                circuit_param.k(),
+                &circuit_config
             );
             shared_state
                 .gen_pk(
                     &cache_key,
+                    task_options.pk_cache_path.as_deref(),
                     &Arc::new(circuit_param.clone()),
-                    &circuit,
+                    &circuits[0],
                     &mut circuit_proof.aux,
                 )
-                .map_err(|e| e.to_string())?
+                .map_err(|e| ProverError::KeyGen(e.to_string()))?
         };
+        metrics::observe_phase(
+            metrics::PHASE_KEYGEN,
+            &task_options.circuit,
+            &circuit_config,
+            keygen_started,
+        );
+        shared_state.checkpoint("keygen")?;
 
         println!(
             "done gen_pk {:?} ms",
@@ -237,8 +527,6 @@ fn compute_proof<C: Circuit<Fr> + Clone + SubCircuit<Fr> + CircuitExt<Fr>>(
                 - start
         );
 
-        let circuit_instance = circuit.instance();
-        circuit_proof.instance = collect_instance_hex(&circuit_instance);
         if task_options.aggregate {
             println!(
                 "start aggregate {:?} ms",
@@ -249,141 +537,80 @@ fn compute_proof<C: Circuit<Fr> + Clone + SubCircuit<Fr> + CircuitExt<Fr>>(
                     - start
             );
 
-            let snark = gen_snark_gwc(&circuit_param, &pk, circuit, None::<&str>);
-            circuit_proof.proof = snark.proof.clone().into();
-            if std::env::var("PROVERD_DUMP").is_ok() {
-                File::create(format!(
-                    "proof-{}-{:?}",
-                    task_options.circuit, &circuit_config
-                ))
-                .unwrap()
-                .write_all(&snark.proof)
-                .unwrap();
-            }
-
-            if aggregation_param.k() as usize > circuit_config.min_k_aggregation {
-                aggregation_param.downsize(circuit_config.min_k_aggregation as u32);
-                aggregation_proof.k = aggregation_param.k() as u8;
-            }
-            let (agg_params, agg_param_path) = (aggregation_param, param_path.clone());
-            aggregation_proof.k = agg_params.k() as u8;
-            let agg_circuit = {
-                let time_started = Instant::now();
-                let v = TaikoAggregationCircuit::<GWC>::new(&agg_params, [snark]).unwrap();
-                aggregation_proof.aux.circuit =
-                    Instant::now().duration_since(time_started).as_millis() as u32;
-                v
-            };
-
-            let agg_pk = {
-                let cache_key = format!(
-                    "{}-agg-{}{:?}",
-                    &task_options.circuit, &agg_param_path, &circuit_config
-                );
-                shared_state
-                    .gen_pk(
-                        &cache_key,
-                        &Arc::new(agg_params.clone()),
-                        &agg_circuit,
-                        &mut aggregation_proof.aux,
-                    )
-                    .map_err(|e| e.to_string())?
-            };
-            println!(
-                "done app_pk {:?} ms",
-                SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis()
-                    - start
-            );
-
-            let agg_instance = agg_circuit.instance();
-            for fr in &agg_instance[0] {
-                let frstr = format!("{:?}", fr);
-                aggregation_proof.instance.push(frstr);
-            }
-
-            let proof = {
-                let time_started = Instant::now();
-                // #[cfg(feature = "evm-verifier")]
-                let (num_instances, instances, accumulator_indices) = {
-                    (
-                        agg_circuit.num_instance().clone(),
-                        agg_circuit.instance().clone(),
-                        Some(agg_circuit.accumulator_indices()),
-                    )
+            let batch_size = circuits.len();
+            let mut snarks = Vec::with_capacity(batch_size);
+            for (i, circuit) in circuits.into_iter().enumerate() {
+                let snark_started = Instant::now();
+                let snark = match task_options.accumulation_scheme {
+                    AccumulationScheme::Gwc => {
+                        gen_snark_gwc(&circuit_param, &pk, circuit, None::<&str>)
+                    }
+                    AccumulationScheme::Shplonk => {
+                        gen_snark_shplonk(&circuit_param, &pk, circuit, None::<&str>)
+                    }
                 };
-
-                println!(
-                    "gen_evm_proof_gwc {:?} ms",
-                    SystemTime::now()
-                        .duration_since(SystemTime::UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis()
-                        - start
-                );
-                let v = gen_evm_proof_gwc(&agg_params, &agg_pk, agg_circuit, agg_instance);
-                println!(
-                    "v length {:?}, {:?} ms",
-                    v.len(),
-                    SystemTime::now()
-                        .duration_since(SystemTime::UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis()
-                        - start
-                );
-                {
-                    println!(
-                        "start gen_verifier {:?} ms",
-                        SystemTime::now()
-                            .duration_since(SystemTime::UNIX_EPOCH)
-                            .unwrap()
-                            .as_millis()
-                            - start
-                    );
-
-                    let deployment_code = evm_verifier_helper::gen_verifier(
-                        &agg_params,
-                        &agg_pk.get_vk(),
-                        evm_verifier_helper::Config::kzg()
-                            .with_num_instance(num_instances.clone())
-                            .with_accumulator_indices(accumulator_indices),
-                        num_instances,
-                        evm_verifier_helper::AccumulationSchemeType::GwcType,
-                    );
-                    println!("deployment_code len {:?}", deployment_code.len());
-                    // cannot fork
-                    // let evm_verifier_bytecode =
-                    //     evm_verifier_helper::evm::compile_solidity(&deployment_code);
-                    let evm_verifier_bytecode = local_compile_solidity(deployment_code);
-
-                    println!(
-                        "evm_verifier_bytecode len {:?}",
-                        evm_verifier_bytecode.len()
-                    );
-
-                    bytecode.extend_from_slice(&evm_verifier_bytecode.as_slice());
-                    evm_verifier_helper::evm_verify(evm_verifier_bytecode, instances, v.clone());
-                    println!("done evm_verify");
+                if std::env::var("PROVERD_DUMP").is_ok() {
+                    let mut path = format!("proof-{}-{:?}", task_options.circuit, &circuit_config);
+                    if batch_size > 1 {
+                        path.push_str(&format!("-{i}"));
+                    }
+                    File::create(path)
+                        .and_then(|mut file| file.write_all(&snark.proof))
+                        .map_err(ProverError::ProofIo)?;
                 }
+                snarks.push(snark);
+                metrics::observe_phase(
+                    metrics::PHASE_SNARK,
+                    &task_options.circuit,
+                    &circuit_config,
+                    snark_started,
+                );
+                shared_state.checkpoint("snark")?;
+            }
+            if batch_size == 1 {
+                circuit_proof.proof = snarks[0].proof.clone().into();
+            } else {
+                batch = snarks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, snark)| ProofResult {
+                        label: format!("{}-{i}", circuit_proof.label),
+                        k: circuit_proof.k,
+                        instance: collect_instance_hex(&snark.instances),
+                        proof: snark.proof.clone().into(),
+                        ..Default::default()
+                    })
+                    .collect();
+            }
 
-                aggregation_proof.aux.proof =
-                    Instant::now().duration_since(time_started).as_millis() as u32;
-                v
-            };
-
-            if std::env::var("PROVERD_DUMP").is_ok() {
-                File::create(format!(
-                    "proof-{}-agg--{:?}",
-                    task_options.circuit, &circuit_config
-                ))
-                .unwrap()
-                .write_all(&proof)
-                .unwrap();
+            match task_options.accumulation_scheme {
+                AccumulationScheme::Gwc => aggregate_snarks::<GWC>(
+                    shared_state,
+                    task_options,
+                    circuit_config.clone(),
+                    aggregation_param,
+                    &param_id,
+                    snarks,
+                    &mut aggregation_proof,
+                    &mut bytecode,
+                    start,
+                )?,
+                AccumulationScheme::Shplonk => aggregate_snarks::<SHPLONK>(
+                    shared_state,
+                    task_options,
+                    circuit_config.clone(),
+                    aggregation_param,
+                    &param_id,
+                    snarks,
+                    &mut aggregation_proof,
+                    &mut bytecode,
+                    start,
+                )?,
             }
-            aggregation_proof.proof = proof.into();
         } else {
+            let circuit = circuits.into_iter().next().unwrap();
+            let circuit_instance = instances.into_iter().next().unwrap();
+            let snark_started = Instant::now();
             let proof = gen_proof::<
                 _,
                 _,
@@ -394,17 +621,31 @@ fn compute_proof<C: Circuit<Fr> + Clone + SubCircuit<Fr> + CircuitExt<Fr>>(
                 &circuit_param,
                 &pk,
                 circuit,
-                circuit_instance.clone(),
+                circuit_instance,
                 fixed_rng(),
+                task_options.accumulation_scheme,
                 true,
                 task_options.verify_proof,
                 &mut circuit_proof.aux,
+            )?;
+            metrics::observe_phase(
+                metrics::PHASE_SNARK,
+                &task_options.circuit,
+                &circuit_config,
+                snark_started,
             );
+            shared_state.checkpoint("snark")?;
             circuit_proof.proof = proof.into();
         }
     }
 
-    Ok((circuit_config, circuit_proof, aggregation_proof, bytecode))
+    Ok((
+        circuit_config,
+        circuit_proof,
+        aggregation_proof,
+        bytecode,
+        batch,
+    ))
 }
 
 fn fr_from_string(s: String) -> Fr {
@@ -427,41 +668,155 @@ fn bytes_to_vec(bytes: Bytes) -> Vec<u8> {
     result
 }
 
-pub fn verify(proofs: Proofs) -> bool {
-    println!("verify");
+/// Returns the hex encoded keccak256 hash of the evm verifier `bytecode`.
+pub fn verifier_bytecode_hash(bytecode: &[u8]) -> String {
+    hex::encode(keccak256(bytecode))
+}
+
+/// Returns the trusted hash pinned for `key` in `verifier_hashes`,
+/// a list of `<key>=<hash>` entries.
+pub fn trusted_verifier_hash<'a>(verifier_hashes: &'a [String], key: &str) -> Option<&'a str> {
+    verifier_hashes.iter().find_map(|entry| {
+        entry
+            .split_once('=')
+            .filter(|(entry_key, _)| entry_key.trim() == key)
+            .map(|(_, hash)| hash.trim())
+    })
+}
+
+/// Verifies the aggregation proof in `proofs` with the evm verifier.
+/// The verifier bytecode shipped with `proofs` is only trusted if its hash
+/// matches the hash pinned in `verifier_hashes` for the circuit parameter bucket,
+/// accumulation scheme and batch size of `proofs`, otherwise the proof is rejected.
+pub fn verify(proofs: Proofs, verifier_hashes: &[String]) -> Result<(), String> {
+    let key = proofs.verifier_key();
+    let verifier_hash = trusted_verifier_hash(verifier_hashes, &key)
+        .ok_or_else(|| format!("no trusted verifier hash for {key}"))?;
+    let bytecode: Vec<u8> = bytes_to_vec(proofs.bytecode);
+    let bytecode_hash = verifier_bytecode_hash(&bytecode);
+    let verifier_hash = verifier_hash.trim_start_matches("0x").to_lowercase();
+    if bytecode_hash != verifier_hash {
+        return Err(format!(
+            "untrusted {key} verifier bytecode: expected hash {verifier_hash}, got {bytecode_hash}"
+        ));
+    }
+
+    // the aggregation instance starts with the instances of the aggregated blocks
+    let batch_instance: Vec<&String> = proofs
+        .batch
+        .iter()
+        .flat_map(|proof| proof.instance.iter())
+        .collect();
+    if !batch_instance
+        .iter()
+        .zip(proofs.aggregation.instance.iter())
+        .all(|(a, b)| *a == b)
+        || batch_instance.len() > proofs.aggregation.instance.len()
+    {
+        return Err("aggregation instance does not match the batch instances".to_string());
+    }
+
     let mut instances = Vec::new();
     for i in proofs.aggregation.instance {
         let fr = fr_from_string(i);
         instances.push(fr);
     }
     let instances = vec![instances];
-
-    // Convert the two Bytes structures to Vec<u8>
     let proof: Vec<u8> = bytes_to_vec(proofs.aggregation.proof);
-    let bytecode: Vec<u8> = bytes_to_vec(proofs.bytecode);
 
-    println!("bytecode len is {}", bytecode.len());
-    println!("proof len is {}", proof.len());
+    log::debug!(
+        "verifying a proof of {} bytes with a verifier of {} bytes",
+        proof.len(),
+        bytecode.len()
+    );
     let result = evm_verifier_helper::gevulot_evm_verify(bytecode, instances, proof);
-    println!("gevulot_evm_verify result: {:?}", result);
-    result.is_ok()
+    result
+        .map(|_| ())
+        .map_err(|e| format!("{key} evm verification failed: {e}"))
+}
+
+/// Reads a serialized `CircuitWitness` from `path`.
+fn read_witness(path: Option<&String>) -> Result<CircuitWitness, ProverError> {
+    let path = path.ok_or_else(|| {
+        ProverError::WitnessIo(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "no witness path",
+        ))
+    })?;
+    witness_file::read_witness(path)
+}
+
+/// Reads `witness_path` followed by the `batch_witness_paths` of `task_options`.
+fn read_witnesses(task_options: &ProofRequestOptions) -> Result<Vec<CircuitWitness>, ProverError> {
+    let mut witnesses = vec![read_witness(task_options.witness_path.as_ref())?];
+    for path in task_options.batch_witness_paths.iter() {
+        witnesses.push(witness_file::read_witness(path)?);
+    }
+    Ok(witnesses)
+}
+
+/// The file name of the cached proving key of `cache_key`.
+fn pk_cache_file(cache_key: &str) -> String {
+    hex::encode(keccak256(format!("{VERSION}-{cache_key}")))
+}
+
+/// Returns the instance hash of each of `witnesses`, for the evidence type of
+/// its request.
+fn instance_hashes(witnesses: &[CircuitWitness]) -> Result<Vec<H256>, ProverError> {
+    witnesses
+        .iter()
+        .map(|witness| witness.instance_hash())
+        .collect::<Result<_, _>>()
+        .map_err(|e| ProverError::InvalidWitness(vec![e]))
+}
+
+/// Returns the gas used by the largest block of `witnesses`.
+fn max_gas_used(witnesses: &[CircuitWitness]) -> u64 {
+    witnesses
+        .iter()
+        .map(|witness| witness.gas_used())
+        .max()
+        .unwrap_or_default()
+}
+
+/// Reads serialized `Proofs` from `path`.
+fn read_proofs(path: Option<&String>) -> Result<Proofs, ProverError> {
+    let path = path.ok_or_else(|| {
+        ProverError::ProofIo(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "no proof path",
+        ))
+    })?;
+    let jproof = std::fs::read_to_string(path).map_err(ProverError::ProofIo)?;
+    serde_json::from_str(&jproof).map_err(ProverError::ProofDecode)
 }
 
 macro_rules! compute_proof_wrapper {
-    ($shared_state:expr, $task_options:expr, $witness:expr, $CIRCUIT:ident) => {{
+    ($shared_state:expr, $task_options:expr, $witnesses:expr, $CIRCUIT:ident) => {{
         let timing = Instant::now();
-        let circuit = $CIRCUIT::<
-            { CIRCUIT_CONFIG.max_txs },
-            { CIRCUIT_CONFIG.max_calldata },
-            { CIRCUIT_CONFIG.max_rws },
-            { CIRCUIT_CONFIG.max_copy_rows },
-            _,
-        >(&$witness, fixed_rng())?;
+        let mut circuits = Vec::with_capacity($witnesses.len());
+        for witness in $witnesses.iter() {
+            let circuit = $CIRCUIT::<
+                { CIRCUIT_CONFIG.max_txs },
+                { CIRCUIT_CONFIG.max_calldata },
+                { CIRCUIT_CONFIG.max_rws },
+                { CIRCUIT_CONFIG.max_copy_rows },
+                _,
+            >(witness, fixed_rng())
+            .map_err(ProverError::Circuit)?;
+            circuits.push(circuit);
+        }
         let timing = Instant::now().duration_since(timing).as_millis() as u32;
-        let (circuit_config, mut circuit_proof, aggregation_proof, bytecode) =
-            compute_proof(&$shared_state, &$task_options, CIRCUIT_CONFIG, circuit)?;
+        let (circuit_config, mut circuit_proof, aggregation_proof, bytecode, batch) =
+            compute_proof(&$shared_state, &$task_options, CIRCUIT_CONFIG, circuits)?;
         circuit_proof.aux.circuit = timing;
-        (circuit_config, circuit_proof, aggregation_proof, bytecode)
+        (
+            circuit_config,
+            circuit_proof,
+            aggregation_proof,
+            bytecode,
+            batch,
+        )
     }};
 }
 
@@ -472,6 +827,14 @@ pub struct RoState {
     // a `HOSTNAME:PORT` conformant string that will be used for DNS service discovery of other
     // nodes
     pub node_lookup: Option<String>,
+    /// retention of the completed tasks
+    pub retention: TaskRetention,
+    /// The clients allowed to use the json-rpc api, anyone if not set
+    pub auth: Option<Arc<Auth>>,
+    /// bearer token authenticating this instance at its peers
+    pub peer_token: Option<String>,
+    /// Computes the tasks in worker processes if set
+    pub worker: Option<WorkerConfig>,
 }
 
 pub struct RwState {
@@ -488,17 +851,73 @@ pub struct SharedState {
     pub ro: RoState,
     pub rw: Arc<Mutex<RwState>>,
     pub rwstate: Arc<RwState>,
+    /// persists `tasks` across restarts
+    pub store: Arc<dyn TaskStore>,
+    /// Set to abort the task this instance is computing,
+    /// checked at the checkpoints of the computation.
+    pub abort: Arc<AtomicBool>,
+    /// Numbers the snapshots of `tasks` taken for the task store.
+    snapshots: Arc<AtomicU64>,
+    /// The number of the latest snapshot written to the task store.
+    written_snapshot: Arc<std::sync::Mutex<u64>>,
+}
+
+/// The tasks at the time they were locked, numbered in the order they were taken.
+struct TaskSnapshot {
+    number: u64,
+    tasks: Vec<ProofRequest>,
 }
 
 impl SharedState {
     pub fn new(node_id: String, node_lookup: Option<String>) -> SharedState {
+        Self::with_tasks(
+            node_id,
+            node_lookup,
+            Arc::new(MemoryTaskStore),
+            TaskRetention::default(),
+            Vec::new(),
+        )
+    }
+
+    /// Like `new`, but persists the tasks in `store` and restores the tasks
+    /// of a previous run from it.
+    /// Completed tasks are evicted according to `retention`.
+    pub fn with_task_store(
+        node_id: String,
+        node_lookup: Option<String>,
+        store: Arc<dyn TaskStore>,
+        retention: TaskRetention,
+    ) -> Result<SharedState, ProverError> {
+        let tasks = store.load()?;
+        log::info!("restored {} tasks from the task store", tasks.len());
+
+        Ok(Self::with_tasks(
+            node_id,
+            node_lookup,
+            store,
+            retention,
+            tasks,
+        ))
+    }
+
+    fn with_tasks(
+        node_id: String,
+        node_lookup: Option<String>,
+        store: Arc<dyn TaskStore>,
+        retention: TaskRetention,
+        tasks: Vec<ProofRequest>,
+    ) -> SharedState {
         Self {
             ro: RoState {
                 node_id,
                 node_lookup,
+                retention,
+                auth: None,
+                peer_token: None,
+                worker: None,
             },
             rw: Arc::new(Mutex::new(RwState {
-                tasks: Vec::new(),
+                tasks,
                 pk_cache: HashMap::new(),
                 pending: None,
                 obtained: false,
@@ -509,6 +928,105 @@ impl SharedState {
                 pending: None,
                 obtained: false,
             }),
+            store,
+            abort: Arc::new(AtomicBool::new(false)),
+            snapshots: Arc::new(AtomicU64::new(0)),
+            written_snapshot: Arc::new(std::sync::Mutex::new(0)),
+        }
+    }
+
+    /// A safe point to abort the computation of the current task.
+    /// Returns `ProverError::Cancelled` if the task was cancelled.
+    pub fn checkpoint(&self, stage: &str) -> Result<(), ProverError> {
+        if self.abort.load(Ordering::SeqCst) {
+            log::info!("task cancelled after {}", stage);
+            return Err(ProverError::Cancelled);
+        }
+
+        Ok(())
+    }
+
+    /// Cancels the task with `options` if it is not completed yet.
+    /// A pending task will not be computed anymore, a running task is aborted
+    /// at its next checkpoint. Peers learn about the cancellation through
+    /// the cancelled result of the task.
+    /// Returns `false` if there is no such task or if it is already completed.
+    pub async fn cancel(&self, options: &ProofRequestOptions) -> bool {
+        let mut rw = self.rw.lock().await;
+        let task = rw
+            .tasks
+            .iter_mut()
+            .find(|e| e.options == *options && e.result.is_none());
+        let Some(task) = task else {
+            return false;
+        };
+
+        log::info!("cancel: {:#?}", task.options);
+        task.result = Some(Err(ProverError::Cancelled.to_string()));
+        task.cancelled = true;
+        task.completed_at = Some(unix_timestamp());
+        task.edition += 1;
+        if rw.pending.as_ref() == Some(options) {
+            self.abort.store(true, Ordering::SeqCst);
+        }
+        let snapshot = self.snapshot_tasks(&rw.tasks);
+        drop(rw);
+        self.persist_tasks(snapshot).await;
+
+        true
+    }
+
+    /// Takes a snapshot of `tasks` to persist, while `self.rw` is locked.
+    fn snapshot_tasks(&self, tasks: &[ProofRequest]) -> TaskSnapshot {
+        TaskSnapshot {
+            number: self.snapshots.fetch_add(1, Ordering::SeqCst) + 1,
+            tasks: tasks.to_vec(),
+        }
+    }
+
+    /// Writes `snapshot` to the task store on the blocking thread pool,
+    /// to be called once `self.rw` is unlocked.
+    /// A snapshot older than the one already written is skipped.
+    /// Failures are only logged, the tasks are still kept in memory.
+    async fn persist_tasks(&self, snapshot: TaskSnapshot) {
+        let store = self.store.clone();
+        let written_snapshot = self.written_snapshot.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut written = written_snapshot.lock().unwrap_or_else(|e| e.into_inner());
+            if *written > snapshot.number {
+                return Ok(());
+            }
+            *written = snapshot.number;
+            store.save(&snapshot.tasks)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => log::error!("persisting tasks failed with: {}", err),
+            Err(err) => log::error!("persisting tasks panicked: {}", err),
+        }
+    }
+
+    /// Removes all tasks not matching `predicate`.
+    pub async fn retain_tasks(&self, predicate: impl FnMut(&ProofRequest) -> bool) {
+        let mut rw = self.rw.lock().await;
+        let len = rw.tasks.len();
+        rw.tasks.retain(predicate);
+        if rw.tasks.len() != len {
+            let snapshot = self.snapshot_tasks(&rw.tasks);
+            drop(rw);
+            self.persist_tasks(snapshot).await;
+        }
+    }
+
+    /// Evicts the completed tasks exceeding the retention.
+    pub async fn evict_tasks(&self) {
+        let mut rw = self.rw.lock().await;
+        if self.ro.retention.evict(&mut rw.tasks, unix_timestamp()) {
+            let snapshot = self.snapshot_tasks(&rw.tasks);
+            drop(rw);
+            self.persist_tasks(snapshot).await;
         }
     }
 
@@ -521,7 +1039,59 @@ impl SharedState {
         options: &ProofRequestOptions,
     ) -> Option<Result<Proofs, String>> {
         let mut rw = self.rw.lock().await;
+        let (result, snapshot) = self.get_or_enqueue_locked(&mut rw, options, None);
+        drop(rw);
+        if let Some(snapshot) = snapshot {
+            self.persist_tasks(snapshot).await;
+        }
 
+        result
+    }
+
+    /// Like `get_or_enqueue` for the authenticated `client`, which can have
+    /// at most `client.max_queued` uncompleted tasks.
+    pub async fn get_or_enqueue_for(
+        &self,
+        options: &ProofRequestOptions,
+        client: &AuthClient,
+    ) -> Result<Option<Result<Proofs, String>>, String> {
+        let mut rw = self.rw.lock().await;
+
+        if let Some(max_queued) = client.max_queued {
+            let enqueues = match rw.tasks.iter().find(|e| e.options == *options) {
+                Some(task) => options.retry && matches!(task.result, Some(Err(_))),
+                None => true,
+            };
+            let queued = rw
+                .tasks
+                .iter()
+                .filter(|e| e.result.is_none() && e.client.as_ref() == Some(&client.id))
+                .count();
+            if enqueues && queued >= max_queued {
+                return Err(format!(
+                    "client {} reached its limit of {max_queued} queued proof requests",
+                    client.id
+                ));
+            }
+        }
+
+        let (result, snapshot) = self.get_or_enqueue_locked(&mut rw, options, Some(&client.id));
+        drop(rw);
+        if let Some(snapshot) = snapshot {
+            self.persist_tasks(snapshot).await;
+        }
+
+        Ok(result)
+    }
+
+    /// Also returns the snapshot of the tasks to persist if the task was
+    /// enqueued.
+    fn get_or_enqueue_locked(
+        &self,
+        rw: &mut RwState,
+        options: &ProofRequestOptions,
+        client: Option<&String>,
+    ) -> (Option<Result<Proofs, String>>, Option<TaskSnapshot>) {
         // task already pending or completed?
         let task = rw.tasks.iter_mut().find(|e| e.options == *options);
 
@@ -533,14 +1103,17 @@ impl SharedState {
                     log::debug!("retrying: {:#?}", task);
                     // will be a candidate in `duty_cycle` again
                     task.result = None;
+                    task.completed_at = None;
+                    task.client = client.cloned();
+                    task.cancelled = false;
                     task.edition += 1;
                 } else {
                     log::debug!("completed: {:#?}", task);
-                    return task.result.clone();
+                    return (task.result.clone(), None);
                 }
             } else {
                 log::debug!("pending: {:#?}", task);
-                return None;
+                return (None, None);
             }
         } else {
             // enqueue the task
@@ -548,12 +1121,15 @@ impl SharedState {
                 options: options.clone(),
                 result: None,
                 edition: 0,
+                completed_at: None,
+                client: client.cloned(),
+                cancelled: false,
             };
             log::debug!("enqueue: {:#?}", task);
             rw.tasks.push(task);
         }
 
-        None
+        (None, Some(self.snapshot_tasks(&rw.tasks)))
     }
 
     /// Checks if there is anything to do like:
@@ -566,6 +1142,7 @@ impl SharedState {
             log::error!("merge_tasks_from_peers failed with: {}", err);
             return;
         }
+        self.evict_tasks().await;
 
         let rw = self.rw.lock().await;
         if rw.pending.is_some() || rw.obtained {
@@ -584,7 +1161,20 @@ impl SharedState {
         for task in tasks {
             // signals that this node wants to process this task
             log::debug!("trying to obtain {:#?}", task);
-            self.rw.lock().await.pending = Some(task);
+            {
+                let mut rw = self.rw.lock().await;
+                // the task could have been completed, cancelled or removed
+                // since the lock was released
+                let uncompleted = rw
+                    .tasks
+                    .iter()
+                    .any(|e| e.options == task && e.result.is_none());
+                if !uncompleted {
+                    log::debug!("task is not pending anymore");
+                    continue;
+                }
+                rw.pending = Some(task);
+            }
 
             // notify other peers
             // wrap the object because it's important to clear `pending` on error
@@ -598,6 +1188,7 @@ impl SharedState {
 
                 if obtain_task.is_err() || !obtain_task.unwrap() {
                     self.rw.lock().await.pending = None;
+                    self.abort.store(false, Ordering::SeqCst);
                     log::debug!("failed to obtain task");
                     continue;
                 }
@@ -622,107 +1213,22 @@ impl SharedState {
 
         // Note: this catches any panics for the task itself but will not help in the
         // situation when the process get itself OOM killed, stack overflows etc.
-        // This could be avoided by spawning a subprocess for the proof computation
-        // instead.
+        // unless the task is computed in a worker process, see `RoState::worker`.
 
         // spawn a task to catch panics
         let task_result: Result<Result<Proofs, String>, tokio::task::JoinError> = {
-            let mut task_options_copy = task_options.clone();
+            let task_options_copy = task_options.clone();
             let self_copy = self.clone();
-            let prover_mode = task_options_copy.prover_mode;
             tokio::spawn(async move {
-                // let time1 = Instant::now().elapsed().as_millis();
-                let time1 = SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis();
-
-
-                if prover_mode == ProverMode::Verifier {
-                    let jproof = std::fs::read_to_string(
-                        task_options_copy.clone().proof_path.unwrap(),
-                    )
-                    .unwrap();
-                    let proofs: Proofs = serde_json::from_str(&jproof).unwrap();
-                    let _result = verify(proofs);
-                    exit(0);
-                }
-
-                let witness = match prover_mode {
-                    ProverMode::WitnessCapture | ProverMode::LegacyProver => {
-                        CircuitWitness::from_request(&mut task_options_copy)
-                            .await
-                            .map_err(|e| e.to_string())?
+                match &self_copy.ro.worker {
+                    Some(worker) => {
+                        run_in_worker(worker, &task_options_copy, &self_copy.abort).await
                     }
-                    ProverMode::OfflineProver => {
-                        let jwitness = std::fs::read_to_string(
-                            task_options_copy.clone().witness_path.unwrap(),
-                        )
-                        .unwrap();
-                        serde_json::from_str(&jwitness).unwrap()
-                    }
-                    _ => panic!("invalid prover mode"),
-                };
-
-                if prover_mode == ProverMode::WitnessCapture {
-                    let jwitness = json!(witness).to_string();
-                    write(task_options_copy.witness_path.clone().unwrap(), jwitness).unwrap();
-                    exit(0);
+                    None => self_copy
+                        .run_task(task_options_copy)
+                        .await
+                        .map_err(|e| e.to_string()),
                 }
-
-                let (config, circuit_proof, aggregation_proof, bytecode) = crate::match_circuit_params!(
-                    witness.gas_used(),
-                    {
-                        match task_options_copy.circuit.as_str() {
-                            "super" => {
-                                compute_proof_wrapper!(
-                                    self_copy,
-                                    task_options_copy,
-                                    &witness,
-                                    gen_super_circuit
-                                )
-                            }
-                            _ => panic!("unknown circuit"),
-                        }
-                    },
-                    {
-                        return Err(format!(
-                            "No circuit parameters found for block with gas used={}",
-                            witness.gas_used()
-                        ));
-                    }
-                );
-
-                let bytes: Bytes = Bytes::from_iter(bytecode);
-
-                let res = Proofs {
-                    config,
-                    circuit: circuit_proof,
-                    aggregation: aggregation_proof,
-                    gas: witness.gas_used(),
-                    bytecode: bytes,
-                };
-
-                println!(
-                    "proof.aggregation.proof.len() {}",
-                    res.aggregation.proof.len()
-                );
-
-                let time2 = SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis();
-
-                println!("duration {:?} ms", time2 - time1);
-
-                if prover_mode != ProverMode::WitnessCapture {
-                    let jproof = json!(res).to_string();
-                    write(task_options_copy.proof_path.clone().unwrap(), jproof).unwrap();
-                    println!("created proof, is now written to {:?}", task_options_copy.proof_path.unwrap());
-                    exit(0);
-                }
-
-                Ok(res)
             })
             .await
         };
@@ -754,71 +1260,158 @@ impl SharedState {
             // clear fields
             rw.pending = None;
             rw.obtained = false;
+            self.abort.store(false, Ordering::SeqCst);
             // insert task result
             let task = rw.tasks.iter_mut().find(|e| e.options == task_options);
-            if let Some(task) = task {
-                // found our task, update result
-                task.result = Some(task_result);
-                task.edition += 1;
-            } else {
-                // task was already removed in the meantime,
-                // assume it's obsolete and forget about it
-                log::info!(
-                    "task was already removed, ignoring result {:#?}",
-                    task_options
-                );
+            match task {
+                Some(task) if task.is_cancelled() => {
+                    // cancelled while computing, the cancellation wins
+                    log::info!("task was cancelled, ignoring result {:#?}", task_options);
+                }
+                Some(task) => {
+                    // found our task, update result
+                    task.result = Some(task_result);
+                    task.completed_at = Some(unix_timestamp());
+                    task.edition += 1;
+                    let snapshot = self.snapshot_tasks(&rw.tasks);
+                    drop(rw);
+                    self.persist_tasks(snapshot).await;
+                }
+                None => {
+                    // task was already removed in the meantime,
+                    // assume it's obsolete and forget about it
+                    log::info!(
+                        "task was already removed, ignoring result {:#?}",
+                        task_options
+                    );
+                }
             }
         }
     }
 
-    pub fn prove(&self, task_options: &ProofRequestOptions) -> Result<Proofs, String> {
-        let task_options_copy = task_options.clone();
-        let self_copy = self.clone();
-        // let prover_mode = task_options_copy.prover_mode;
+    /// Runs the task according to its `prover_mode`.
+    /// Writes the witness or proof to `witness_path` or `proof_path` if
+    /// the mode produces one.
+    pub(crate) async fn run_task(
+        &self,
+        mut task_options: ProofRequestOptions,
+    ) -> Result<Proofs, ProverError> {
         let time1 = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
             .as_millis();
+        let prover_mode = task_options.prover_mode;
+
+        if prover_mode == ProverMode::Verifier {
+            let proofs = read_proofs(task_options.proof_path.as_ref())?;
+            let result = verify(proofs.clone(), &task_options.verifier_hashes);
+            println!("verify result: {:?}", result);
+            result.map_err(ProverError::EvmVerify)?;
+            return Ok(proofs);
+        }
 
-        let witness_path = task_options_copy.clone().witness_path.unwrap();
-
-        let witness: CircuitWitness = {
-            let jwitness = std::fs::read_to_string(witness_path).unwrap();
-            serde_json::from_str(&jwitness).unwrap()
+        let witness_started = Instant::now();
+        let mut witnesses = match prover_mode {
+            ProverMode::WitnessCapture | ProverMode::LegacyProver => {
+                let mut witnesses = vec![CircuitWitness::from_request(&mut task_options)
+                    .await
+                    .map_err(|e| ProverError::WitnessFetch(e.to_string()))?];
+                for path in task_options.batch_witness_paths.iter() {
+                    witnesses.push(witness_file::read_witness(path)?);
+                }
+                witnesses
+            }
+            _ => read_witnesses(&task_options)?,
         };
+        metrics::observe_phase(
+            metrics::PHASE_WITNESS,
+            &task_options.circuit,
+            &witnesses[0].circuit_config,
+            witness_started,
+        );
+        self.checkpoint("witness")?;
 
-        let (config, circuit_proof, aggregation_proof, bytecode) = crate::match_circuit_params!(
-            witness.gas_used(),
-            {
-                match task_options_copy.circuit.as_str() {
-                    "super" => {
-                        compute_proof_wrapper!(
-                            self_copy,
-                            task_options_copy,
-                            &witness,
-                            gen_super_circuit
-                        )
+        if prover_mode == ProverMode::WitnessCapture {
+            let witness_path = task_options.witness_path.as_ref().ok_or_else(|| {
+                ProverError::WitnessIo(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "no witness path",
+                ))
+            })?;
+            let witness = witnesses.swap_remove(0);
+            let instance_hash = instance_hashes(std::slice::from_ref(&witness))?[0];
+            witness_file::write_witness(witness_path, &witness)?;
+            println!("created witness, is now written to {:?}", witness_path);
+            log::info!("instance hash: {:?}", instance_hash);
+
+            return Ok(Proofs {
+                gas: witness.gas_used(),
+                instance_hash,
+                ..Default::default()
+            });
+        }
+
+        if prover_mode == ProverMode::ValidateWitness {
+            let mut problems = Vec::new();
+            for (i, witness) in witnesses.iter().enumerate() {
+                for problem in witness.validate() {
+                    if witnesses.len() > 1 {
+                        problems.push(format!("witness {i}: {problem}"));
+                    } else {
+                        problems.push(problem);
                     }
-                    _ => panic!("unknown circuit"),
                 }
-            },
-            {
-                return Err(format!(
-                    "No circuit parameters found for block with gas used={}",
-                    witness.gas_used()
-                ));
             }
+            if !problems.is_empty() {
+                return Err(ProverError::InvalidWitness(problems));
+            }
+            println!("witness is valid");
+
+            return Ok(Proofs {
+                gas: max_gas_used(&witnesses),
+                ..Default::default()
+            });
+        }
+
+        let res = self.prove_witnesses(&task_options, &witnesses)?;
+
+        println!(
+            "proof.aggregation.proof.len() {}",
+            res.aggregation.proof.len()
         );
 
-        let bytes: Bytes = Bytes::from_iter(bytecode);
+        let time2 = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
 
-        let res = Proofs {
-            config,
-            circuit: circuit_proof,
-            aggregation: aggregation_proof,
-            gas: witness.gas_used(),
-            bytecode: bytes,
-        };
+        println!("duration {:?} ms", time2 - time1);
+
+        if prover_mode == ProverMode::KeyGen {
+            println!(
+                "created keys, are now cached in {:?}",
+                task_options.pk_cache_path
+            );
+            return Ok(res);
+        }
+
+        if let Some(proof_path) = &task_options.proof_path {
+            let jproof = json!(res).to_string();
+            write(proof_path, jproof).map_err(ProverError::ProofIo)?;
+            println!("created proof, is now written to {:?}", proof_path);
+        }
+
+        Ok(res)
+    }
+
+    pub fn prove(&self, task_options: &ProofRequestOptions) -> Result<Proofs, ProverError> {
+        let time1 = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let witnesses = read_witnesses(task_options)?;
+        let res = self.prove_witnesses(task_options, &witnesses)?;
 
         let time2 = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -830,6 +1423,79 @@ impl SharedState {
         Ok(res)
     }
 
+    /// Computes the proofs for `witnesses` with the circuit requested in `task_options`.
+    /// Several witnesses are proven with the circuit parameters of the largest block
+    /// and aggregated into one proof.
+    fn prove_witnesses(
+        &self,
+        task_options: &ProofRequestOptions,
+        witnesses: &[CircuitWitness],
+    ) -> Result<Proofs, ProverError> {
+        // the circuits prove the instance hash of PseZkVerifier.sol only,
+        // an sgx instance hash is attested by the sgx prover
+        if let Some(witness) = witnesses
+            .iter()
+            .find(|witness| witness.request_instance.evidence_type != RequestEvidenceType::PseZk)
+        {
+            return Err(ProverError::UnsupportedEvidence(format!(
+                "block {:?} requests an sgx instance hash",
+                witness.eth_block.number
+            )));
+        }
+        let instance_hashes = instance_hashes(witnesses)?;
+        let gas_used = max_gas_used(witnesses);
+        let (config, mut circuit_proof, aggregation_proof, bytecode, mut batch) = crate::match_circuit_params!(
+            gas_used,
+            {
+                // all circuits of a batch share one proving key
+                if witnesses.len() > 1 {
+                    if let Some(witness) = witnesses
+                        .iter()
+                        .find(|witness| witness.circuit_config != CIRCUIT_CONFIG)
+                    {
+                        return Err(ProverError::Aggregation(format!(
+                            "circuit config of block {:?} differs from the batch",
+                            witness.eth_block.number
+                        )));
+                    }
+                }
+                match task_options.circuit.as_str() {
+                    "super" => {
+                        compute_proof_wrapper!(self, task_options, witnesses, gen_super_circuit)
+                    }
+                    _ => return Err(ProverError::UnknownCircuit(task_options.circuit.clone())),
+                }
+            },
+            {
+                return Err(ProverError::NoCircuitParams(gas_used));
+            }
+        );
+
+        let bytes: Bytes = Bytes::from_iter(bytecode);
+        for (proof, result) in [
+            ("circuit", &circuit_proof),
+            ("aggregation", &aggregation_proof),
+        ] {
+            metrics::observe_proof_size(proof, &task_options.circuit, &config, result.proof.len());
+        }
+        let instance_hash = instance_hashes.first().copied().unwrap_or_default();
+        circuit_proof.instance_hash = instance_hash;
+        for (proof, instance_hash) in batch.iter_mut().zip(instance_hashes.iter()) {
+            proof.instance_hash = *instance_hash;
+        }
+
+        Ok(Proofs {
+            config,
+            circuit: circuit_proof,
+            aggregation: aggregation_proof,
+            gas: gas_used,
+            bytecode: bytes,
+            batch,
+            accumulation_scheme: task_options.accumulation_scheme,
+            instance_hash,
+        })
+    }
+
     /// Returns `node_id` and `tasks` for this instance.
     /// Normally used for the rpc api.
     pub async fn get_node_information(&self) -> NodeInformation {
@@ -846,6 +1512,14 @@ impl SharedState {
     ///
     /// Always returns `true` otherwise returns with error.
     pub async fn merge_tasks_from_peers(&self) -> Result<bool, String> {
+        let result = self.merge_with_peers().await;
+        if result.is_err() {
+            metrics::peer_merge_failure();
+        }
+        result
+    }
+
+    async fn merge_with_peers(&self) -> Result<bool, String> {
         const LOG_TAG: &str = "merge_tasks_from_peers:";
 
         if self.ro.node_lookup.is_none() {
@@ -863,9 +1537,15 @@ impl SharedState {
 
         for addr in addrs_iter {
             let uri = Uri::try_from(format!("http://{addr}")).map_err(|e| e.to_string())?;
-            let peer: NodeInformation =
-                jsonrpc_request_client(5000, &hyper_client, &uri, "info", serde_json::json!([]))
-                    .await?;
+            let peer: NodeInformation = jsonrpc_request_client_auth(
+                5000,
+                &hyper_client,
+                &uri,
+                self.ro.peer_token.as_deref(),
+                "info",
+                serde_json::json!([]),
+            )
+            .await?;
 
             if peer.id == self.ro.node_id {
                 log::debug!("{} skipping self({})", LOG_TAG, peer.id);
@@ -879,18 +1559,38 @@ impl SharedState {
         Ok(true)
     }
 
-    // TODO: can this be pre-generated to a file?
-    // related
-    // https://github.com/zcash/halo2/issues/443
-    // https://github.com/zcash/halo2/issues/449
-    /// Compute or retrieve a proving key from cache.
+    /// Compute or retrieve a proving key from the on-disk cache in `cache_dir`.
+    /// Keys are stored with `SerdeFormat::RawBytes` under the hash of `cache_key`
+    /// and the `VERSION` of the prover, so that the keys of the circuits of
+    /// another build are never loaded.
+    /// The verifying key is written alongside for convenience.
     fn gen_pk<C: Circuit<Fr>>(
         &self,
         cache_key: &str,
+        cache_dir: Option<&str>,
         param: &Arc<ProverParams>,
         circuit: &C,
         aux: &mut ProofResultInstrumentation,
     ) -> Result<Arc<ProverKey>, Box<dyn std::error::Error>> {
+        let cache_path = cache_dir.map(|dir| Path::new(dir).join(pk_cache_file(cache_key)));
+
+        if let Some(pk_path) = cache_path.as_ref().map(|path| path.with_extension("pk")) {
+            if pk_path.exists() {
+                let time_started = Instant::now();
+                let pk = ProverKey::read::<_, C>(
+                    &mut BufReader::new(File::open(&pk_path)?),
+                    SerdeFormat::RawBytes,
+                    circuit.params(),
+                )?;
+                aux.pk = Instant::now().duration_since(time_started).as_millis() as u32;
+                log::info!("ProvingKey: loaded key={} from {:?}", cache_key, pk_path);
+                metrics::pk_cache_lookup(true);
+
+                return Ok(Arc::new(pk));
+            }
+        }
+
+        metrics::pk_cache_lookup(false);
         let vk = {
             let time_started = Instant::now();
             let vk = keygen_vk(param.as_ref(), circuit)?;
@@ -903,12 +1603,13 @@ impl SharedState {
             aux.pk = Instant::now().duration_since(time_started).as_millis() as u32;
             pk
         };
-        if std::env::var("PROVERD_DUMP").is_ok() {
-            pk.write(
-                &mut File::create(cache_key).unwrap(),
-                SerdeFormat::RawBytesUnchecked,
-            )
-            .unwrap();
+
+        if let Some(path) = &cache_path {
+            // a read-only cache is not fatal, the key is just not persisted
+            match write_pk(path, &pk) {
+                Ok(_) => log::info!("ProvingKey: cached key={} at {:?}", cache_key, path),
+                Err(err) => log::warn!("ProvingKey: failed to cache key={}: {}", cache_key, err),
+            }
         }
 
         let pk = Arc::new(pk);
@@ -917,8 +1618,7 @@ impl SharedState {
         // rw = self.rw.blocking_lock();
         // rw.pk_cache.insert(cache_key.to_string(), pk);
 
-        log::info!("ProvingKey: generated and cached key={}", cache_key);
-        // }
+        log::info!("ProvingKey: generated key={}", cache_key);
         println!("done gen key");
 
         Ok(pk)
@@ -927,6 +1627,7 @@ impl SharedState {
     async fn merge_tasks(&self, node_info: &NodeInformation) {
         const LOG_TAG: &str = "merge_tasks:";
         let mut rw = self.rw.lock().await;
+        let mut changed = false;
 
         for peer_task in &node_info.tasks {
             let maybe_task = rw.tasks.iter_mut().find(|e| e.options == peer_task.options);
@@ -941,12 +1642,25 @@ impl SharedState {
                 // update result, edition
                 existent_task.edition = peer_task.edition;
                 existent_task.result = peer_task.result.clone();
+                existent_task.completed_at = peer_task.completed_at;
+                existent_task.cancelled = peer_task.cancelled;
                 log::debug!("{} updated {:#?}", LOG_TAG, existent_task);
+                if existent_task.is_cancelled() && rw.pending.as_ref() == Some(&peer_task.options) {
+                    // cancelled on a peer while this instance is working on it
+                    self.abort.store(true, Ordering::SeqCst);
+                }
             } else {
                 // copy task
                 rw.tasks.push(peer_task.clone());
                 log::debug!("{} new task {:#?}", LOG_TAG, peer_task);
             }
+            changed = true;
+        }
+
+        if changed {
+            let snapshot = self.snapshot_tasks(&rw.tasks);
+            drop(rw);
+            self.persist_tasks(snapshot).await;
         }
     }
 
@@ -982,9 +1696,15 @@ impl SharedState {
             .map_err(|e| e.to_string())?;
         for addr in addrs_iter {
             let uri = Uri::try_from(format!("http://{addr}")).map_err(|e| e.to_string())?;
-            let peer: NodeStatus =
-                jsonrpc_request_client(5000, &hyper_client, &uri, "status", serde_json::json!([]))
-                    .await?;
+            let peer: NodeStatus = jsonrpc_request_client_auth(
+                5000,
+                &hyper_client,
+                &uri,
+                self.ro.peer_token.as_deref(),
+                "status",
+                serde_json::json!([]),
+            )
+            .await?;
 
             if peer.id == self.ro.node_id {
                 log::debug!("{} skipping self({})", LOG_TAG, peer.id);
@@ -1117,6 +1837,8 @@ mod test {
             max_transactions_per_block: 79,
             max_bytes_per_tx_list: 120000,
             anchor_gas_limit: 250000,
+            blob: String::new(),
+            evidence_type: RequestEvidenceType::PseZk,
         };
 
         let dummy_req = ProofRequestOptions {
@@ -1124,13 +1846,20 @@ mod test {
             block: protocol_instance.request_meta_data.id,
             prover_mode: ProverMode::LegacyProver,
             rpc: "https://rpc.internal.taiko.xyz/".to_string(),
+            l1_rpc: None,
+            l1_contract: None,
             witness_path: None,
+            batch_witness_paths: Vec::new(),
             proof_path: None,
+            verifier_hashes: Vec::new(),
+            pk_cache_path: None,
             protocol_instance,
             param: Some("./params".to_string()),
             aggregate: false,
+            accumulation_scheme: AccumulationScheme::Gwc,
             retry: true,
             mock: true,
+            preflight: false,
             mock_feedback: false,
             verify_proof: true,
         };
@@ -1149,11 +1878,243 @@ mod test {
         .unwrap();
 
         println!("ready to compute proof");
-        let proof = compute_proof(&ss, &dummy_req, CIRCUIT_CONFIG, super_circuit).unwrap();
+        let proof = compute_proof(&ss, &dummy_req, CIRCUIT_CONFIG, vec![super_circuit]).unwrap();
         println!("proof={:?}", proof);
         Ok(())
     }
 
+    #[test]
+    #[ignore = "needs the ./params file and computes real proofs"]
+    fn test_batch_aggregation() {
+        let ss = SharedState::new("1234".to_owned(), None);
+        const CIRCUIT_CONFIG: CircuitConfig = crate::match_circuit_params!(0, CIRCUIT_CONFIG, {
+            panic!();
+        });
+        let witnesses: Vec<CircuitWitness> = (1..=2)
+            .map(|id| {
+                let mut witness = CircuitWitness::dummy(CIRCUIT_CONFIG).unwrap();
+                witness.protocol_instance.block_metadata.id = id;
+                witness
+            })
+            .collect();
+        let req = ProofRequestOptions {
+            circuit: "super".to_string(),
+            param: Some("./params".to_string()),
+            aggregate: true,
+            ..Default::default()
+        };
+
+        let proofs = ss.prove_witnesses(&req, &witnesses).unwrap();
+        assert_eq!(proofs.batch.len(), 2);
+        assert_ne!(proofs.batch[0].instance, proofs.batch[1].instance);
+        let verifier_hash = format!(
+            "{}={}",
+            proofs.verifier_key(),
+            verifier_bytecode_hash(&proofs.bytecode)
+        );
+        verify(proofs, &[verifier_hash]).unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs the ./params file and computes real proofs"]
+    fn test_shplonk_aggregation() {
+        let ss = SharedState::new("1234".to_owned(), None);
+        const CIRCUIT_CONFIG: CircuitConfig = crate::match_circuit_params!(0, CIRCUIT_CONFIG, {
+            panic!();
+        });
+        let witness = CircuitWitness::dummy(CIRCUIT_CONFIG).unwrap();
+        let req = ProofRequestOptions {
+            circuit: "super".to_string(),
+            param: Some("./params".to_string()),
+            aggregate: true,
+            accumulation_scheme: AccumulationScheme::Shplonk,
+            ..Default::default()
+        };
+
+        let proofs = ss.prove_witnesses(&req, &[witness]).unwrap();
+        assert_eq!(proofs.accumulation_scheme, AccumulationScheme::Shplonk);
+        let verifier_hash = format!(
+            "{}={}",
+            proofs.verifier_key(),
+            verifier_bytecode_hash(&proofs.bytecode)
+        );
+        verify(proofs, &[verifier_hash]).unwrap();
+    }
+
+    #[test]
+    fn test_verifier_hash_keys() {
+        let mut proofs = Proofs {
+            config: CircuitConfig {
+                block_gas_limit: 820000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let hash = verifier_bytecode_hash(&proofs.bytecode);
+        assert_eq!(proofs.verifier_key(), "820000-gwc-1");
+
+        // a hash pinned for another bucket, scheme or batch size is not trusted
+        for key in ["15200000-gwc-1", "820000-shplonk-1", "820000-gwc-2"] {
+            let err = verify(proofs.clone(), &[format!("{key}={hash}")]).unwrap_err();
+            assert!(err.starts_with("no trusted verifier hash"), "{err}");
+        }
+        let err = verify(
+            proofs.clone(),
+            &[format!("820000-gwc-1={}", "00".repeat(32))],
+        )
+        .unwrap_err();
+        assert!(err.starts_with("untrusted 820000-gwc-1"), "{err}");
+
+        proofs.accumulation_scheme = AccumulationScheme::Shplonk;
+        proofs.batch = vec![ProofResult::default(), ProofResult::default()];
+        assert_eq!(proofs.verifier_key(), "820000-shplonk-2");
+        assert_eq!(
+            trusted_verifier_hash(
+                &[
+                    "820000-gwc-1=aa".to_string(),
+                    "820000-shplonk-2=bb".to_string()
+                ],
+                &proofs.verifier_key()
+            ),
+            Some("bb")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_store_restore() {
+        let path =
+            std::env::temp_dir().join(format!("proverd-restore-{}.json", std::process::id()));
+        let store = Arc::new(crate::task_store::FileTaskStore::new(&path));
+        let options = ProofRequestOptions {
+            block: 1,
+            ..Default::default()
+        };
+
+        let ss = SharedState::with_task_store(
+            "a".to_string(),
+            None,
+            store.clone(),
+            TaskRetention::default(),
+        )
+        .unwrap();
+        assert!(ss.get_or_enqueue(&options).await.is_none());
+
+        // a restarted instance still knows about the task
+        let ss = SharedState::with_task_store(
+            "b".to_string(),
+            None,
+            store.clone(),
+            TaskRetention::default(),
+        )
+        .unwrap();
+        assert_eq!(ss.get_node_information().await.tasks.len(), 1);
+        ss.retain_tasks(|_| false).await;
+        assert!(store.load().unwrap().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_distinct_requests() {
+        let ss = SharedState::new("a".to_string(), None);
+        let options = ProofRequestOptions {
+            block: 1,
+            ..Default::default()
+        };
+        assert!(ss.get_or_enqueue(&options).await.is_none());
+        ss.rw.lock().await.tasks[0].result = Some(Err("failed".to_string()));
+
+        // another l1 source or other trusted verifiers are another task
+        for options in [
+            ProofRequestOptions {
+                l1_rpc: Some("http://l1".to_string()),
+                ..options.clone()
+            },
+            ProofRequestOptions {
+                verifier_hashes: vec!["820000-gwc-1=00".to_string()],
+                ..options.clone()
+            },
+        ] {
+            assert!(ss.get_or_enqueue(&options).await.is_none());
+        }
+        assert_eq!(ss.get_node_information().await.tasks.len(), 3);
+
+        // the output paths are not
+        let options = ProofRequestOptions {
+            proof_path: Some("proof.json".to_string()),
+            ..options
+        };
+        assert!(matches!(ss.get_or_enqueue(&options).await, Some(Err(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let ss = SharedState::new("a".to_string(), None);
+        let options = ProofRequestOptions {
+            block: 1,
+            ..Default::default()
+        };
+        assert!(!ss.cancel(&options).await);
+
+        assert!(ss.get_or_enqueue(&options).await.is_none());
+        ss.rw.lock().await.pending = Some(options.clone());
+        assert!(ss.cancel(&options).await);
+        assert!(matches!(
+            ss.checkpoint("witness"),
+            Err(ProverError::Cancelled)
+        ));
+
+        // the cancelled result is reported and not computed again
+        let task = ss.get_node_information().await.tasks.pop().unwrap();
+        assert!(task.is_cancelled());
+        assert_eq!(task.edition, 1);
+        assert!(matches!(
+            ss.get_or_enqueue(&options).await,
+            Some(Err(err)) if err == TASK_CANCELLED
+        ));
+        assert!(!ss.cancel(&options).await);
+
+        // a failure with the same text is not a cancellation
+        let mut failed = task.clone();
+        failed.cancelled = false;
+        assert!(!failed.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_client_queue_limit() {
+        let ss = SharedState::new("a".to_string(), None);
+        let client = AuthClient {
+            id: "client".to_string(),
+            token: Some("token".to_string()),
+            hmac_secret: None,
+            scope: crate::auth::Scope::Prove,
+            max_queued: Some(1),
+        };
+        let options = |block| ProofRequestOptions {
+            block,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            ss.get_or_enqueue_for(&options(1), &client).await,
+            Ok(None)
+        ));
+        // polling a queued task is not limited
+        assert!(matches!(
+            ss.get_or_enqueue_for(&options(1), &client).await,
+            Ok(None)
+        ));
+        assert!(ss.get_or_enqueue_for(&options(2), &client).await.is_err());
+        // other clients are not affected
+        assert!(ss.get_or_enqueue(&options(2)).await.is_none());
+
+        ss.rw.lock().await.tasks[0].result = Some(Err("failed".to_string()));
+        assert!(matches!(
+            ss.get_or_enqueue_for(&options(3), &client).await,
+            Ok(None)
+        ));
+    }
+
     #[warn(dead_code)]
     fn mock_requests() -> Vec<RequestExtraInstance> {
         vec![
@@ -1201,6 +2162,8 @@ mod test {
                 max_transactions_per_block: 79,
                 max_bytes_per_tx_list: 120000,
                 anchor_gas_limit: 250000,
+                blob: String::new(),
+                evidence_type: RequestEvidenceType::PseZk,
             },
             RequestExtraInstance {
                 l1_signal_service: "7a2088a1bFc9d81c55368AE168C2C02570cB814F".to_string(),
@@ -1246,6 +2209,8 @@ mod test {
                 max_transactions_per_block: 79,
                 max_bytes_per_tx_list: 120000,
                 anchor_gas_limit: 250000,
+                blob: String::new(),
+                evidence_type: RequestEvidenceType::PseZk,
             },
             RequestExtraInstance {
                 l1_signal_service: "7a2088a1bFc9d81c55368AE168C2C02570cB814F".to_string(),
@@ -1291,6 +2256,8 @@ mod test {
                 max_transactions_per_block: 79,
                 max_bytes_per_tx_list: 120000,
                 anchor_gas_limit: 250000,
+                blob: String::new(),
+                evidence_type: RequestEvidenceType::PseZk,
             },
         ]
     }
@@ -1314,13 +2281,20 @@ mod test {
             block: protocol_instance.request_meta_data.id,
             prover_mode: ProverMode::LegacyProver,
             rpc: "https://rpc.internal.taiko.xyz/".to_string(),
+            l1_rpc: None,
+            l1_contract: None,
             protocol_instance,
             param: Some("./params".to_string()),
             witness_path: None,
+            batch_witness_paths: Vec::new(),
             proof_path: None,
+            verifier_hashes: Vec::new(),
+            pk_cache_path: None,
             aggregate: true,
+            accumulation_scheme: AccumulationScheme::Gwc,
             retry: true,
             mock: false,
+            preflight: false,
             mock_feedback: false,
             verify_proof: true,
         };
@@ -1337,7 +2311,7 @@ mod test {
         .unwrap();
 
         println!("ready to compute proof");
-        let proof = compute_proof(&ss, &dummy_req, CIRCUIT_CONFIG, super_circuit).unwrap();
+        let proof = compute_proof(&ss, &dummy_req, CIRCUIT_CONFIG, vec![super_circuit]).unwrap();
         println!("proof={:?}", proof);
         Ok(())
     }
@@ -0,0 +1,206 @@
+use crate::error::ProverError;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use zkevm_common::prover::ProofRequest;
+
+/// Persists the tasks of a `SharedState` across restarts.
+pub trait TaskStore: Send + Sync {
+    /// Returns all persisted tasks.
+    fn load(&self) -> Result<Vec<ProofRequest>, ProverError>;
+    /// Replaces the persisted tasks with `tasks`.
+    fn save(&self, tasks: &[ProofRequest]) -> Result<(), ProverError>;
+}
+
+/// Keeps the tasks in memory only, they are lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryTaskStore;
+
+impl TaskStore for MemoryTaskStore {
+    fn load(&self) -> Result<Vec<ProofRequest>, ProverError> {
+        Ok(Vec::new())
+    }
+
+    fn save(&self, _tasks: &[ProofRequest]) -> Result<(), ProverError> {
+        Ok(())
+    }
+}
+
+/// Stores the tasks as a JSON file.
+/// The file is written to a temporary location first and then renamed, so that
+/// an interrupted write never leaves a truncated task list behind.
+#[derive(Debug)]
+pub struct FileTaskStore {
+    path: PathBuf,
+}
+
+impl FileTaskStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl TaskStore for FileTaskStore {
+    fn load(&self) -> Result<Vec<ProofRequest>, ProverError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = std::fs::File::open(&self.path).map_err(ProverError::TaskStoreIo)?;
+        serde_json::from_reader(BufReader::new(file)).map_err(ProverError::TaskStoreFormat)
+    }
+
+    fn save(&self, tasks: &[ProofRequest]) -> Result<(), ProverError> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(ProverError::TaskStoreIo)?;
+        }
+
+        let tmp = self.path.with_extension("tmp");
+        {
+            let file = std::fs::File::create(&tmp).map_err(ProverError::TaskStoreIo)?;
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, tasks).map_err(ProverError::TaskStoreFormat)?;
+            writer.flush().map_err(ProverError::TaskStoreIo)?;
+        }
+        std::fs::rename(&tmp, &self.path).map_err(ProverError::TaskStoreIo)
+    }
+}
+
+/// Limits how long completed tasks are kept.
+/// Pending tasks are never evicted.
+#[derive(Debug, Clone, Default)]
+pub struct TaskRetention {
+    /// Completed tasks older than this are evicted.
+    pub max_age: Option<Duration>,
+    /// At most this many completed tasks are kept, the oldest are evicted first.
+    pub max_completed: Option<usize>,
+}
+
+impl TaskRetention {
+    /// Removes the completed tasks exceeding the retention from `tasks`,
+    /// `now` is a unix timestamp in seconds.
+    /// Completed tasks without a completion time are stamped with `now`.
+    /// Returns `true` if `tasks` changed.
+    pub fn evict(&self, tasks: &mut Vec<ProofRequest>, now: u64) -> bool {
+        let mut changed = false;
+        for task in tasks.iter_mut() {
+            if task.result.is_some() && task.completed_at.is_none() {
+                task.completed_at = Some(now);
+                changed = true;
+            }
+        }
+
+        let len = tasks.len();
+        if let Some(max_age) = self.max_age {
+            let min_completed_at = now.saturating_sub(max_age.as_secs());
+            tasks.retain(|task| task.completed_at.map_or(true, |at| at >= min_completed_at));
+        }
+        if let Some(max_completed) = self.max_completed {
+            // the positions of the completed tasks, newest first
+            let mut completed: Vec<usize> = (0..tasks.len())
+                .filter(|idx| tasks[*idx].completed_at.is_some())
+                .collect();
+            completed.sort_by_key(|idx| Reverse(tasks[*idx].completed_at));
+            let evicted: HashSet<usize> = completed.into_iter().skip(max_completed).collect();
+            let mut idx = 0;
+            tasks.retain(|_| {
+                idx += 1;
+                !evicted.contains(&(idx - 1))
+            });
+        }
+
+        changed || tasks.len() != len
+    }
+}
+
+/// The current unix timestamp in seconds.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkevm_common::prover::{ProofRequestOptions, Proofs};
+
+    fn task(block: u64, completed_at: Option<u64>) -> ProofRequest {
+        ProofRequest {
+            options: ProofRequestOptions {
+                block,
+                ..Default::default()
+            },
+            result: completed_at.map(|_| Err("failed".to_string())),
+            edition: 0,
+            completed_at,
+            client: None,
+            cancelled: false,
+        }
+    }
+
+    fn blocks(tasks: &[ProofRequest]) -> Vec<u64> {
+        tasks.iter().map(|e| e.options.block).collect()
+    }
+
+    #[test]
+    fn file_store_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("proverd-tasks-{}", std::process::id()))
+            .join("tasks.json");
+        let store = FileTaskStore::new(&path);
+        assert!(store.load().unwrap().is_empty());
+
+        let mut tasks = vec![task(1, None), task(2, Some(10))];
+        tasks[1].result = Some(Ok(Proofs::default()));
+        store.save(&tasks).unwrap();
+
+        let loaded = store.load().unwrap();
+        assert_eq!(blocks(&loaded), vec![1, 2]);
+        assert!(loaded[0].result.is_none());
+        assert!(matches!(loaded[1].result, Some(Ok(_))));
+        assert_eq!(loaded[1].completed_at, Some(10));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn evict_by_age() {
+        let retention = TaskRetention {
+            max_age: Some(Duration::from_secs(100)),
+            ..Default::default()
+        };
+        let mut tasks = vec![task(1, Some(50)), task(2, None), task(3, Some(150))];
+        assert!(retention.evict(&mut tasks, 200));
+        assert_eq!(blocks(&tasks), vec![2, 3]);
+        assert!(!retention.evict(&mut tasks, 200));
+    }
+
+    #[test]
+    fn evict_by_count() {
+        let retention = TaskRetention {
+            max_completed: Some(2),
+            ..Default::default()
+        };
+        let mut tasks = vec![
+            task(1, Some(30)),
+            task(2, Some(10)),
+            task(3, None),
+            task(4, Some(30)),
+            task(5, Some(30)),
+        ];
+        assert!(retention.evict(&mut tasks, 100));
+        assert_eq!(blocks(&tasks), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn evict_stamps_completion() {
+        let retention = TaskRetention::default();
+        let mut tasks = vec![task(1, Some(10))];
+        tasks[0].completed_at = None;
+        assert!(retention.evict(&mut tasks, 100));
+        assert_eq!(tasks[0].completed_at, Some(100));
+    }
+}
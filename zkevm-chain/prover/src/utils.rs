@@ -1,3 +1,4 @@
+use crate::error::ProverError;
 use crate::Fr;
 use crate::G1Affine;
 use crate::ProverCommitmentScheme;
@@ -12,7 +13,9 @@ use halo2_proofs::plonk::Circuit;
 use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsProver;
 use halo2_proofs::poly::kzg::multiopen::ProverGWC;
+use halo2_proofs::poly::kzg::multiopen::ProverSHPLONK;
 use halo2_proofs::poly::kzg::multiopen::VerifierGWC;
+use halo2_proofs::poly::kzg::multiopen::VerifierSHPLONK;
 use halo2_proofs::poly::kzg::strategy::SingleStrategy;
 use halo2_proofs::transcript::EncodedChallenge;
 use halo2_proofs::transcript::TranscriptReadBuffer;
@@ -23,6 +26,7 @@ use rand::SeedableRng;
 use std::clone::Clone;
 use std::io::Cursor;
 use std::time::Instant;
+use zkevm_common::prover::AccumulationScheme;
 use zkevm_common::prover::ProofResultInstrumentation;
 
 /// Returns [<len>, ...] of `instance`
@@ -30,8 +34,9 @@ pub fn gen_num_instance(instance: &[Vec<Fr>]) -> Vec<usize> {
     instance.iter().map(|v| v.len()).collect()
 }
 
-/// Returns the finalized transcript.
-/// Runs the MockProver on `create_proof` error and panics afterwards.
+/// Returns the finalized transcript, opened with `scheme`.
+/// Runs the MockProver on `create_proof` error and includes its findings in
+/// the returned error if `mock_feedback` is set.
 #[allow(clippy::too_many_arguments)]
 pub fn gen_proof<
     C: Circuit<Fr> + Clone,
@@ -45,23 +50,38 @@ pub fn gen_proof<
     circuit: C,
     instance: Vec<Vec<Fr>>,
     rng: RNG,
+    scheme: AccumulationScheme,
     mock_feedback: bool,
     verify: bool,
     aux: &mut ProofResultInstrumentation,
-) -> Vec<u8> {
+) -> Result<Vec<u8>, ProverError> {
     println!("gen_proof");
     let mut transcript = TW::init(Vec::new());
     let inputs: Vec<&[Fr]> = instance.iter().map(|v| v.as_slice()).collect();
     let res = {
         let time_started = Instant::now();
-        let v = create_proof::<ProverCommitmentScheme, ProverGWC<_>, _, _, TW, _>(
-            params,
-            pk,
-            &[circuit.clone()],
-            &[inputs.as_slice()],
-            rng,
-            &mut transcript,
-        );
+        let v = match scheme {
+            AccumulationScheme::Gwc => {
+                create_proof::<ProverCommitmentScheme, ProverGWC<_>, _, _, TW, _>(
+                    params,
+                    pk,
+                    &[circuit.clone()],
+                    &[inputs.as_slice()],
+                    rng,
+                    &mut transcript,
+                )
+            }
+            AccumulationScheme::Shplonk => {
+                create_proof::<ProverCommitmentScheme, ProverSHPLONK<_>, _, _, TW, _>(
+                    params,
+                    pk,
+                    &[circuit.clone()],
+                    &[inputs.as_slice()],
+                    rng,
+                    &mut transcript,
+                )
+            }
+        };
         aux.proof = Instant::now().duration_since(time_started).as_millis() as u32;
         v
     };
@@ -71,14 +91,15 @@ pub fn gen_proof<
             let res = {
                 let time_started = Instant::now();
                 let v = MockProver::run(params.k(), &circuit, instance)
-                    .expect("MockProver::run")
-                    .verify_par();
+                    .map(|prover| prover.verify_par());
                 aux.mock = Instant::now().duration_since(time_started).as_millis() as u32;
                 v
             };
-            panic!("gen_proof: {proof_err:#?}\nMockProver: {res:#?}");
+            return Err(ProverError::Proving(format!(
+                "gen_proof: {proof_err:#?}\nMockProver: {res:#?}"
+            )));
         } else {
-            panic!("gen_proof: {proof_err:#?}");
+            return Err(ProverError::Proving(format!("gen_proof: {proof_err:#?}")));
         }
     }
 
@@ -90,13 +111,22 @@ pub fn gen_proof<
         println!("V: inputs: {:?}", inputs);
         let res = {
             let time_started = Instant::now();
-            let v = verify_proof::<_, VerifierGWC<_>, _, TR, _>(
-                params.verifier_params(),
-                pk.get_vk(),
-                SingleStrategy::new(params.verifier_params()),
-                &[inputs.as_slice()],
-                &mut transcript,
-            );
+            let v = match scheme {
+                AccumulationScheme::Gwc => verify_proof::<_, VerifierGWC<_>, _, TR, _>(
+                    params.verifier_params(),
+                    pk.get_vk(),
+                    SingleStrategy::new(params.verifier_params()),
+                    &[inputs.as_slice()],
+                    &mut transcript,
+                ),
+                AccumulationScheme::Shplonk => verify_proof::<_, VerifierSHPLONK<_>, _, TR, _>(
+                    params.verifier_params(),
+                    pk.get_vk(),
+                    SingleStrategy::new(params.verifier_params()),
+                    &[inputs.as_slice()],
+                    &mut transcript,
+                ),
+            };
             aux.verify = Instant::now().duration_since(time_started).as_millis() as u32;
             v
         };
@@ -107,19 +137,22 @@ pub fn gen_proof<
                 let res = {
                     let time_started = Instant::now();
                     let v = MockProver::run(params.k(), &circuit, instance)
-                        .expect("MockProver::run")
-                        .verify_par();
+                        .map(|prover| prover.verify_par());
                     aux.mock = Instant::now().duration_since(time_started).as_millis() as u32;
                     v
                 };
-                panic!("verify_proof: {verify_err:#?}\nMockProver: {res:#?}");
+                return Err(ProverError::Proving(format!(
+                    "verify_proof: {verify_err:#?}\nMockProver: {res:#?}"
+                )));
             } else {
-                panic!("verify_proof: {verify_err:#?}");
+                return Err(ProverError::Proving(format!(
+                    "verify_proof: {verify_err:#?}"
+                )));
             }
         }
     }
 
-    proof
+    Ok(proof)
 }
 
 pub fn verify<
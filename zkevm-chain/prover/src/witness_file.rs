@@ -0,0 +1,258 @@
+use crate::circuit_witness::CircuitWitness;
+use crate::error::ProverError;
+use crate::VERSION;
+use ethers_core::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::path::Path;
+
+/// Magic bytes at the start of a binary witness file.
+pub const WITNESS_MAGIC: [u8; 4] = *b"TKWT";
+/// Version of the witness file envelope.
+pub const WITNESS_FORMAT_VERSION: u32 = 1;
+/// zstd compression level of binary witness files.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Encoding of a witness file, selected by the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitnessFormat {
+    /// `.json`: human readable, for debugging.
+    Json,
+    /// `.bin`: CBOR compressed with zstd.
+    /// bincode is not an option because the ethers block and transaction types
+    /// contain flattened fields, which only self-describing formats support.
+    Binary,
+}
+
+impl WitnessFormat {
+    pub fn from_path(path: &str) -> Result<Self, ProverError> {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(WitnessFormat::Json),
+            Some("bin") => Ok(WitnessFormat::Binary),
+            _ => Err(ProverError::WitnessFormat(format!(
+                "unknown witness file extension of {path}, expected .json or .bin"
+            ))),
+        }
+    }
+}
+
+/// Identifies the witness and the build that created it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WitnessHeader {
+    /// Version of the envelope, see `WITNESS_FORMAT_VERSION`
+    pub format_version: u32,
+    /// `VERSION` of the prover that captured the witness
+    pub prover_version: String,
+    pub chain_id: u64,
+    pub block_number: u64,
+    /// keccak256 of the encoded witness, hex encoded
+    pub content_hash: String,
+}
+
+impl WitnessHeader {
+    fn new(witness: &CircuitWitness, content: &[u8]) -> Self {
+        Self {
+            format_version: WITNESS_FORMAT_VERSION,
+            prover_version: VERSION.to_string(),
+            chain_id: witness.block.chain_id.as_u64(),
+            block_number: witness.eth_block.number.unwrap_or_default().as_u64(),
+            content_hash: hex::encode(keccak256(content)),
+        }
+    }
+
+    /// Rejects witnesses of other envelope or prover versions and corrupted content.
+    fn check(&self, content: &[u8]) -> Result<(), ProverError> {
+        if self.format_version != WITNESS_FORMAT_VERSION {
+            return Err(ProverError::WitnessFormat(format!(
+                "unsupported witness format version {}, expected {WITNESS_FORMAT_VERSION}",
+                self.format_version
+            )));
+        }
+        if self.prover_version != VERSION {
+            return Err(ProverError::WitnessFormat(format!(
+                "witness was captured by prover {}, this is {VERSION}",
+                self.prover_version
+            )));
+        }
+        let content_hash = hex::encode(keccak256(content));
+        if self.content_hash != content_hash {
+            return Err(ProverError::WitnessFormat(format!(
+                "witness content hash mismatch: header {}, content {content_hash}",
+                self.content_hash
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the decoded witness is the one described by the header.
+    fn check_witness(&self, witness: &CircuitWitness) -> Result<(), ProverError> {
+        let expected = WitnessHeader::new(witness, &[]);
+        if self.chain_id != expected.chain_id || self.block_number != expected.block_number {
+            return Err(ProverError::WitnessFormat(format!(
+                "witness of chain {} block {} does not match its header (chain {} block {})",
+                expected.chain_id, expected.block_number, self.chain_id, self.block_number
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonEnvelopeRef<'a> {
+    header: &'a WitnessHeader,
+    witness: &'a RawValue,
+}
+
+#[derive(Deserialize)]
+struct JsonEnvelope {
+    header: Option<WitnessHeader>,
+    witness: Option<Box<RawValue>>,
+}
+
+/// Encodes `witness` in the format selected by the extension of `path`.
+pub fn encode_witness(path: &str, witness: &CircuitWitness) -> Result<Vec<u8>, ProverError> {
+    match WitnessFormat::from_path(path)? {
+        WitnessFormat::Json => {
+            let content = serde_json::to_string(witness)
+                .map_err(|e| ProverError::WitnessFormat(e.to_string()))?;
+            let header = WitnessHeader::new(witness, content.as_bytes());
+            let content = RawValue::from_string(content)
+                .map_err(|e| ProverError::WitnessFormat(e.to_string()))?;
+            serde_json::to_vec(&JsonEnvelopeRef {
+                header: &header,
+                witness: &content,
+            })
+            .map_err(|e| ProverError::WitnessFormat(e.to_string()))
+        }
+        WitnessFormat::Binary => {
+            let mut content = Vec::new();
+            ciborium::ser::into_writer(witness, &mut content)
+                .map_err(|e| ProverError::WitnessFormat(e.to_string()))?;
+            let content =
+                zstd::encode_all(content.as_slice(), ZSTD_LEVEL).map_err(ProverError::WitnessIo)?;
+            let header = WitnessHeader::new(witness, &content);
+            let mut encoded_header = Vec::new();
+            ciborium::ser::into_writer(&header, &mut encoded_header)
+                .map_err(|e| ProverError::WitnessFormat(e.to_string()))?;
+
+            let mut bytes = Vec::with_capacity(8 + encoded_header.len() + content.len());
+            bytes.extend_from_slice(&WITNESS_MAGIC);
+            bytes.extend_from_slice(&(encoded_header.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&encoded_header);
+            bytes.extend_from_slice(&content);
+            Ok(bytes)
+        }
+    }
+}
+
+/// Decodes a witness file.
+/// The format is detected from the content, JSON witnesses without a header are
+/// accepted for compatibility with older witness files.
+pub fn decode_witness(bytes: &[u8]) -> Result<CircuitWitness, ProverError> {
+    if bytes.starts_with(&WITNESS_MAGIC) {
+        let header_len = bytes
+            .get(4..8)
+            .map(|len| u32::from_le_bytes(len.try_into().unwrap()) as usize)
+            .ok_or_else(|| ProverError::WitnessFormat("truncated witness header".to_string()))?;
+        let encoded_header = bytes
+            .get(8..8 + header_len)
+            .ok_or_else(|| ProverError::WitnessFormat("truncated witness header".to_string()))?;
+        let header: WitnessHeader = ciborium::de::from_reader(encoded_header)
+            .map_err(|e| ProverError::WitnessFormat(e.to_string()))?;
+        let content = &bytes[8 + header_len..];
+        header.check(content)?;
+
+        let content = zstd::decode_all(content).map_err(ProverError::WitnessIo)?;
+        let witness: CircuitWitness = ciborium::de::from_reader(content.as_slice())
+            .map_err(|e| ProverError::WitnessFormat(e.to_string()))?;
+        header.check_witness(&witness)?;
+        return Ok(witness);
+    }
+
+    let envelope: JsonEnvelope =
+        serde_json::from_slice(bytes).map_err(ProverError::WitnessDecode)?;
+    match (envelope.header, envelope.witness) {
+        (Some(header), Some(content)) => {
+            header.check(content.get().as_bytes())?;
+            let witness: CircuitWitness =
+                serde_json::from_str(content.get()).map_err(ProverError::WitnessDecode)?;
+            header.check_witness(&witness)?;
+            Ok(witness)
+        }
+        _ => {
+            println!("witness without header, its prover version is unknown");
+            serde_json::from_slice(bytes).map_err(ProverError::WitnessDecode)
+        }
+    }
+}
+
+/// Writes `witness` to `path`, see `encode_witness`.
+pub fn write_witness(path: &str, witness: &CircuitWitness) -> Result<(), ProverError> {
+    let bytes = encode_witness(path, witness)?;
+    std::fs::write(path, bytes).map_err(ProverError::WitnessIo)
+}
+
+/// Reads a witness from `path`, see `decode_witness`.
+pub fn read_witness(path: &str) -> Result<CircuitWitness, ProverError> {
+    let bytes = std::fs::read(path).map_err(ProverError::WitnessIo)?;
+    decode_witness(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkevm_common::prover::CircuitConfig;
+
+    fn dummy_witness() -> CircuitWitness {
+        let circuit_config = crate::match_circuit_params!(0, CIRCUIT_CONFIG, unreachable!());
+        CircuitWitness::dummy(circuit_config).unwrap()
+    }
+
+    #[test]
+    fn witness_roundtrip() {
+        let witness = dummy_witness();
+        for path in ["witness.json", "witness.bin"] {
+            let bytes = encode_witness(path, &witness).unwrap();
+            let decoded = decode_witness(&bytes).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(&witness).unwrap(),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn witness_legacy_json() {
+        let witness = dummy_witness();
+        let bytes = serde_json::to_vec(&witness).unwrap();
+        assert!(decode_witness(&bytes).is_ok());
+    }
+
+    #[test]
+    fn witness_rejects_corruption() {
+        let witness = dummy_witness();
+        let mut bytes = encode_witness("witness.bin", &witness).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(matches!(
+            decode_witness(&bytes),
+            Err(ProverError::WitnessFormat(_))
+        ));
+    }
+
+    #[test]
+    fn witness_rejects_other_prover_version() {
+        let witness = dummy_witness();
+        let bytes = encode_witness("witness.json", &witness).unwrap();
+        let mut envelope: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        envelope["header"]["prover_version"] = "0.0.0".into();
+        let bytes = serde_json::to_vec(&envelope).unwrap();
+        assert!(matches!(
+            decode_witness(&bytes),
+            Err(ProverError::WitnessFormat(_))
+        ));
+    }
+}
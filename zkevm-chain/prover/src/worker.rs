@@ -0,0 +1,209 @@
+use crate::error::ProverError;
+use crate::metrics;
+use crate::shared_state::SharedState;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::os::unix::process::ExitStatusExt;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use zkevm_common::prover::{ProofRequestOptions, Proofs};
+
+/// Set in the environment of a worker process.
+pub const WORKER_ENV: &str = "PROVERD_WORKER_TASK";
+/// Prefixes the line of the worker output holding the task result.
+const RESULT_PREFIX: &str = "PROVERD_WORKER_RESULT ";
+/// Exit code of a rust process after a panic.
+const PANIC_EXIT_CODE: i32 = 101;
+
+/// Runs each task in a child process, so that a crash or a kill because of
+/// the memory limit only fails the task instead of the daemon.
+/// The metrics of the computation are sent back with the result and recorded
+/// by the daemon.
+#[derive(Debug, Clone)]
+pub struct WorkerConfig {
+    /// The executable of the worker, it runs `run_worker` if `WORKER_ENV` is set.
+    pub program: PathBuf,
+    /// Address space limit of the worker in bytes.
+    pub memory_limit: Option<u64>,
+    /// The worker is killed if the task takes longer.
+    pub timeout: Option<Duration>,
+}
+
+/// The result of a worker, with the metrics observed while computing the task.
+#[derive(Serialize, Deserialize)]
+struct WorkerOutput {
+    result: Result<Proofs, String>,
+    observations: Vec<metrics::Observation>,
+}
+
+/// The worker side: reads `ProofRequestOptions` from stdin, computes the task
+/// and writes the result to stdout.
+pub async fn run_worker() {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .expect("read task");
+    let task_options: ProofRequestOptions = serde_json::from_str(&input).expect("decode task");
+
+    metrics::keep_observations();
+    let shared_state = SharedState::new(SharedState::random_worker_id(), None);
+    let result = shared_state
+        .run_task(task_options)
+        .await
+        .map_err(|e| e.to_string());
+    let output = WorkerOutput {
+        result,
+        observations: metrics::take_observations(),
+    };
+    println!(
+        "{RESULT_PREFIX}{}",
+        serde_json::to_string(&output).expect("encode result")
+    );
+}
+
+/// Computes the task with `task_options` in a worker process.
+/// The worker is killed if `abort` is set or it exceeds the timeout.
+/// Other output of the worker is forwarded to stdout.
+pub async fn run_in_worker(
+    config: &WorkerConfig,
+    task_options: &ProofRequestOptions,
+    abort: &AtomicBool,
+) -> Result<Proofs, String> {
+    let mut command = Command::new(&config.program);
+    command
+        .env(WORKER_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(memory_limit) = config.memory_limit {
+        // only calls async-signal-safe functions between fork and exec
+        unsafe {
+            command.pre_exec(move || set_memory_limit(memory_limit));
+        }
+    }
+
+    let worker_error = |err: String| ProverError::Worker(err).to_string();
+    let mut child = command
+        .spawn()
+        .map_err(|e| worker_error(format!("spawn: {e}")))?;
+    {
+        let input = serde_json::to_vec(task_options).map_err(|e| e.to_string())?;
+        let mut stdin = child.stdin.take().expect("piped stdin");
+        // a worker exiting early is classified by its exit status below
+        if let Err(err) = stdin.write_all(&input).await {
+            log::warn!("writing the task to the worker failed: {}", err);
+        }
+        // closing stdin signals the end of the task
+    }
+
+    let stdout = child.stdout.take().expect("piped stdout");
+    let output = tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        let mut result = None;
+        while let Ok(Some(line)) = lines.next_line().await {
+            match line.strip_prefix(RESULT_PREFIX) {
+                Some(json) => result = Some(json.to_string()),
+                None => println!("{line}"),
+            }
+        }
+        result
+    });
+
+    let time_started = Instant::now();
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => {
+                break status.map_err(|e| worker_error(format!("wait: {e}")))?;
+            }
+            _ = tokio::time::sleep(Duration::from_millis(500)) => {
+                if abort.load(Ordering::SeqCst) {
+                    let _ = child.kill().await;
+                    return Err(ProverError::Cancelled.to_string());
+                }
+                if let Some(timeout) = config.timeout {
+                    if time_started.elapsed() > timeout {
+                        let _ = child.kill().await;
+                        return Err(worker_error(format!(
+                            "timed out after {} s",
+                            timeout.as_secs()
+                        )));
+                    }
+                }
+            }
+        }
+    };
+
+    match output.await.ok().flatten() {
+        Some(json) if status.success() => {
+            let output: WorkerOutput = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            metrics::observe_all(output.observations);
+            output.result
+        }
+        _ => Err(worker_error(classify_exit(status))),
+    }
+}
+
+/// Describes why a worker exited without a result.
+fn classify_exit(status: ExitStatus) -> String {
+    match (status.code(), status.signal()) {
+        (Some(0), _) => "exited without a result".to_string(),
+        (Some(PANIC_EXIT_CODE), _) => "panicked".to_string(),
+        (Some(code), _) => format!("exited with code {code}"),
+        (None, Some(libc::SIGKILL)) => "killed, likely out of memory".to_string(),
+        (None, Some(libc::SIGABRT)) => {
+            "aborted, likely a failed allocation or a stack overflow".to_string()
+        }
+        (None, Some(libc::SIGSEGV)) => "segmentation fault".to_string(),
+        (None, Some(signal)) => format!("terminated by signal {signal}"),
+        (None, None) => "terminated".to_string(),
+    }
+}
+
+fn set_memory_limit(bytes: u64) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: bytes as libc::rlim_t,
+        rlim_max: bytes as libc::rlim_t,
+    };
+    if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_classification() {
+        assert_eq!(
+            classify_exit(ExitStatus::from_raw(PANIC_EXIT_CODE << 8)),
+            "panicked"
+        );
+        assert_eq!(
+            classify_exit(ExitStatus::from_raw(libc::SIGKILL)),
+            "killed, likely out of memory"
+        );
+    }
+
+    #[tokio::test]
+    async fn worker_without_result() {
+        // `true` ignores its input and exits successfully
+        let result = run_in_worker(
+            &WorkerConfig {
+                program: "true".into(),
+                memory_limit: None,
+                timeout: None,
+            },
+            &ProofRequestOptions::default(),
+            &AtomicBool::new(false),
+        )
+        .await;
+        assert_eq!(result.unwrap_err(), "worker: exited without a result");
+    }
+}
@@ -226,6 +226,12 @@ macro_rules! estimate {
             min_k_aggregation: 0,
             // TODO: proper worst-case estimate
             keccak_padding: (BLOCK_GAS_LIMIT / KECCAK_WORD_GAS) * 32,
+            // fixed capacities, kept from `circuit_autogen.rs`
+            max_ec_add: 64,
+            max_ec_mul: 64,
+            max_modexp: 256,
+            max_blake2f_rows: 1048576,
+            max_mpt_rows: 3500000,
         };
         let circuit_witness;
 
@@ -272,6 +278,14 @@ macro_rules! estimate {
                 max_exp_steps: circuit_config.max_exp_steps,
                 max_evm_rows: circuit_config.pad_to,
                 max_keccak_rows: circuit_config.keccak_padding,
+                max_ecrecover: 0,
+                max_sha256_rows: 0,
+                max_ripemd160_rows: 0,
+                max_ec_add: circuit_config.max_ec_add,
+                max_ec_mul: circuit_config.max_ec_mul,
+                max_modexp: circuit_config.max_modexp,
+                max_blake2f_rows: circuit_config.max_blake2f_rows,
+                max_mpt_rows: circuit_config.max_mpt_rows,
             };
             let mut builder =
                 BlockData::new_from_geth_data_with_params(block.clone(), circuit_params)
@@ -0,0 +1,24 @@
+#!/bin/sh
+
+# Proves the sample witness with the current circuits and pins the hash of the
+# evm verifier of the proof in the gevulot verifier manifests.
+# The pinned hashes are stale whenever a circuit changes and have to be
+# regenerated with this script, from the root of zkevm-chain.
+
+set -xe
+
+SRS=${SRS:-gevulot/kzg_bn254_22.srs}
+WITNESS=${WITNESS:-gevulot/witness-57437.json}
+PROOF=gevulot/proof.json
+
+cargo build --release --bin prover_cmd
+./target/release/prover_cmd offline_prover -k $SRS -w $WITNESS -p $PROOF | tee PROVER_LOG.txt
+
+# printed by the prover as `evm_verifier_bytecode hash <key>=<hash>`
+PIN=$(sed -n 's/^evm_verifier_bytecode hash //p' PROVER_LOG.txt | tail -n 1)
+test -n "$PIN"
+KEY=${PIN%%=*}
+
+for file in gevulot/manifest_verifier.json gevulot/taiko-ops-verifier.json gevulot/taiko-ops-verifier-fail.json; do
+  sed -i "s/$KEY=[0-9a-z]*/$PIN/" $file
+done
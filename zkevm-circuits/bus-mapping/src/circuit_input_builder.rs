@@ -2,6 +2,7 @@
 //! types from geth / web3 and outputs the circuit inputs.
 
 mod access;
+pub mod blob;
 mod block;
 mod call;
 mod execution;
@@ -74,6 +75,29 @@ pub struct CircuitsParams {
     /// calculated, so the same circuit will not be able to prove different
     /// witnesses.
     pub max_keccak_rows: usize,
+    /// Maximum number of ecRecover precompile calls supported by the Sig
+    /// Circuit.  When 0, the Sig Circuit will be sized to the calls of the
+    /// block.
+    pub max_ecrecover: usize,
+    /// Pad the SHA-256 circuit to this number of rows.  When 0, the SHA-256
+    /// circuit number of rows will be dynamically calculated.
+    pub max_sha256_rows: usize,
+    /// Pad the RIPEMD-160 circuit to this number of rows.  When 0, the
+    /// RIPEMD-160 circuit number of rows will be dynamically calculated.
+    pub max_ripemd160_rows: usize,
+    /// Maximum number of BN254 point additions supported by the ECC Circuit.
+    pub max_ec_add: usize,
+    /// Maximum number of BN254 scalar multiplications supported by the ECC
+    /// Circuit.
+    pub max_ec_mul: usize,
+    /// Maximum number of modexp calls supported by the ModExp Circuit.
+    pub max_modexp: usize,
+    /// Pad the Blake2F circuit to this number of rows.  When 0, the Blake2F
+    /// circuit number of rows will be dynamically calculated.
+    pub max_blake2f_rows: usize,
+    /// Pad the MPT circuit to this number of rows.  When 0, the MPT circuit
+    /// number of rows will be dynamically calculated.
+    pub max_mpt_rows: usize,
 }
 
 impl Default for CircuitsParams {
@@ -90,6 +114,14 @@ impl Default for CircuitsParams {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: 0,
+            max_ec_mul: 0,
+            max_modexp: 0,
+            max_blake2f_rows: 0,
+            max_mpt_rows: 0,
         }
     }
 }
@@ -336,6 +368,14 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
     }
     // EVM Circuit
     keccak_inputs.extend_from_slice(&block.sha3_inputs);
+    // Sig Circuit
+    let sign_datas: Vec<SignData> = block
+        .precompile_events
+        .ecrecover
+        .iter()
+        .map(|event| event.sign_data())
+        .collect();
+    keccak_inputs.extend_from_slice(&keccak_inputs_sign_verify(&sign_datas));
     // MPT Circuit
     // TODO https://github.com/privacy-scaling-explorations/zkevm-circuits/issues/696
     Ok(keccak_inputs)
@@ -647,8 +687,8 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         let access_set =
             Self::get_state_accesses(&eth_block, &geth_traces, &self.protocol_instance)?;
         let (proofs, codes) = self.get_state(block_num, access_set).await?;
-        let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
-        let builder = self.gen_inputs_from_state(
+        let (state_db, code_db) = Self::build_state_code_db(proofs.clone(), codes);
+        let mut builder = self.gen_inputs_from_state(
             state_db,
             code_db,
             &eth_block,
@@ -656,6 +696,8 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             history_hashes,
             prev_state_root,
         )?;
+        // Keep the proofs so that the state root transitions can be proven
+        builder.block.state_proofs = proofs;
         Ok((builder, eth_block))
     }
 }
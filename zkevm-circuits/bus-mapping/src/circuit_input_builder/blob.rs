@@ -0,0 +1,189 @@
+//! EIP-4844 blobs carrying the tx list of a Taiko block.
+//!
+//! A blob is made of `FIELD_ELEMENTS_PER_BLOB` big endian BLS12-381 scalars,
+//! the evaluations of its polynomial at the roots of unity of the domain, in
+//! bit reversed order.  The tx list is stored in the blob as the byte string
+//! of its field elements without their most significant byte, which is kept
+//! at zero so that every element is a canonical scalar.  The tx list of a
+//! block is the `txListByteSize` bytes at `txListByteOffset` of this string.
+
+use alloy_primitives::U256;
+use bls12_381::Scalar;
+use once_cell::sync::Lazy;
+use sha3::{Digest, Keccak256};
+
+/// Number of field elements of a blob
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+/// Number of bytes of a field element
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+/// Number of bytes of a blob
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT;
+/// Number of data bytes stored in a field element
+pub const DATA_BYTES_PER_FIELD_ELEMENT: usize = BYTES_PER_FIELD_ELEMENT - 1;
+/// Number of data bytes stored in a blob
+pub const BLOB_DATA_LEN: usize = FIELD_ELEMENTS_PER_BLOB * DATA_BYTES_PER_FIELD_ELEMENT;
+
+/// The modulus of the BLS12-381 scalar field
+pub static BLS_MODULUS: Lazy<U256> = Lazy::new(|| {
+    U256::from_str_radix(
+        "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+        16,
+    )
+    .expect("invalid bls modulus")
+});
+
+/// The roots of unity of the blob domain, in the bit reversed order of the
+/// field elements of a blob.
+pub static ROOTS_OF_UNITY: Lazy<Vec<Scalar>> = Lazy::new(|| {
+    // 7 is the primitive root of unity of the scalar field used by EIP-4844
+    let order_exponent = (*BLS_MODULUS - U256::from(1)) / U256::from(FIELD_ELEMENTS_PER_BLOB);
+    let root = Scalar::from(7).pow_vartime(order_exponent.as_limbs());
+    let roots = std::iter::successors(Some(Scalar::one()), |power| Some(power * root))
+        .take(FIELD_ELEMENTS_PER_BLOB)
+        .collect::<Vec<_>>();
+    let bits = FIELD_ELEMENTS_PER_BLOB.trailing_zeros();
+    (0..FIELD_ELEMENTS_PER_BLOB)
+        .map(|i| roots[i.reverse_bits() >> (usize::BITS - bits)])
+        .collect()
+});
+
+/// Returns the scalar of a big endian field element.
+/// Returns None if the element is not canonical.
+pub fn scalar_from_be_bytes(bytes: &[u8]) -> Option<Scalar> {
+    let mut le_bytes: [u8; BYTES_PER_FIELD_ELEMENT] = bytes.try_into().ok()?;
+    le_bytes.reverse();
+    Scalar::from_bytes(&le_bytes).into()
+}
+
+/// Returns the big endian bytes of a scalar.
+pub fn scalar_to_be_bytes(scalar: &Scalar) -> [u8; BYTES_PER_FIELD_ELEMENT] {
+    let mut bytes = scalar.to_bytes();
+    bytes.reverse();
+    bytes
+}
+
+/// Returns the blob storing `data`, which has at most `BLOB_DATA_LEN` bytes.
+pub fn encode_blob(data: &[u8]) -> Vec<u8> {
+    assert!(data.len() <= BLOB_DATA_LEN, "data does not fit in a blob");
+    let mut blob = vec![0u8; BYTES_PER_BLOB];
+    for (element, chunk) in blob
+        .chunks_exact_mut(BYTES_PER_FIELD_ELEMENT)
+        .zip(data.chunks(DATA_BYTES_PER_FIELD_ELEMENT))
+    {
+        element[1..1 + chunk.len()].copy_from_slice(chunk);
+    }
+    blob
+}
+
+/// Returns the data bytes stored in a blob.  A blob shorter than
+/// `BYTES_PER_BLOB`, like the empty one, is padded with zeros.
+/// Returns None if the blob is longer than `BYTES_PER_BLOB`.
+pub fn blob_data(blob: &[u8]) -> Option<Vec<u8>> {
+    Some(
+        padded_blob(blob)?
+            .chunks_exact(BYTES_PER_FIELD_ELEMENT)
+            .flat_map(|element| element[1..].to_vec())
+            .collect(),
+    )
+}
+
+/// Returns the field elements of a blob, padded with zeros like in
+/// `blob_data`.  Returns None if the blob is too long or if an element is not
+/// canonical.
+pub fn blob_field_elements(blob: &[u8]) -> Option<Vec<Scalar>> {
+    padded_blob(blob)?
+        .chunks_exact(BYTES_PER_FIELD_ELEMENT)
+        .map(scalar_from_be_bytes)
+        .collect()
+}
+
+fn padded_blob(blob: &[u8]) -> Option<Vec<u8>> {
+    if blob.len() > BYTES_PER_BLOB {
+        return None;
+    }
+    let mut padded = blob.to_vec();
+    padded.resize(BYTES_PER_BLOB, 0);
+    Some(padded)
+}
+
+/// The Fiat-Shamir challenge at which the blob of a block is evaluated,
+/// `keccak(blobHash || txListHash) mod BLS_MODULUS`, as in `PseZkVerifier.sol`.
+pub fn point_challenge(blob_hash: &[u8; 32], tx_list_hash: &[u8; 32]) -> Scalar {
+    let mut hasher = Keccak256::new();
+    hasher.update(blob_hash);
+    hasher.update(tx_list_hash);
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(&hasher.finalize());
+    wide[..32].reverse();
+    Scalar::from_bytes_wide(&wide)
+}
+
+/// Evaluates the polynomial of the blob with field elements `elements` at `z`,
+/// with the barycentric formula
+/// `P(z) = (z^N - 1) / N * sum(e_i * w_i / (z - w_i))`.
+pub fn evaluate_blob(elements: &[Scalar], z: Scalar) -> Scalar {
+    assert_eq!(elements.len(), FIELD_ELEMENTS_PER_BLOB);
+    if let Some(idx) = ROOTS_OF_UNITY.iter().position(|root| *root == z) {
+        return elements[idx];
+    }
+
+    let mut sum = Scalar::zero();
+    for (element, root) in elements.iter().zip(ROOTS_OF_UNITY.iter()) {
+        // z is not a root of unity, so the inverse exists
+        sum += element * root * (z - root).invert().unwrap();
+    }
+    let n = Scalar::from(FIELD_ELEMENTS_PER_BLOB as u64);
+    let z_pow_n = z.pow_vartime(&[FIELD_ELEMENTS_PER_BLOB as u64, 0, 0, 0]);
+    (z_pow_n - Scalar::one()) * n.invert().unwrap() * sum
+}
+
+#[cfg(test)]
+mod blob_tests {
+    use super::*;
+
+    #[test]
+    fn roots_of_unity() {
+        let n = FIELD_ELEMENTS_PER_BLOB as u64;
+        assert_eq!(ROOTS_OF_UNITY[0], Scalar::one());
+        assert_eq!(ROOTS_OF_UNITY[1], -Scalar::one());
+        // The root at index n / 2 is the generator of the domain
+        let generator = ROOTS_OF_UNITY[FIELD_ELEMENTS_PER_BLOB / 2];
+        assert_eq!(generator.pow_vartime(&[n, 0, 0, 0]), Scalar::one());
+        assert_eq!(generator.pow_vartime(&[n / 2, 0, 0, 0]), -Scalar::one());
+    }
+
+    #[test]
+    fn encode_data() {
+        let data = (0..100u8).collect::<Vec<_>>();
+        let blob = encode_blob(&data);
+        assert_eq!(blob.len(), BYTES_PER_BLOB);
+        assert!(blob
+            .iter()
+            .step_by(BYTES_PER_FIELD_ELEMENT)
+            .all(|b| *b == 0));
+        let stored = blob_data(&blob).unwrap();
+        assert_eq!(stored[..data.len()], data[..]);
+        assert!(stored[data.len()..].iter().all(|b| *b == 0));
+        assert_eq!(blob_data(&[]), Some(vec![0; BLOB_DATA_LEN]));
+        // Too long to be a blob
+        let long_blob = vec![0; BYTES_PER_BLOB + 1];
+        assert_eq!(blob_data(&long_blob), None);
+        assert_eq!(blob_field_elements(&long_blob), None);
+    }
+
+    #[test]
+    fn evaluate() {
+        let z = point_challenge(&[1; 32], &[2; 32]);
+
+        // The polynomial with constant evaluations is constant
+        let constant = vec![Scalar::from(5); FIELD_ELEMENTS_PER_BLOB];
+        assert_eq!(evaluate_blob(&constant, z), Scalar::from(5));
+
+        // The polynomial whose evaluations are the roots is the identity
+        assert_eq!(evaluate_blob(&ROOTS_OF_UNITY, z), z);
+
+        // At a root, the evaluation is the field element of the root
+        let elements = blob_field_elements(&encode_blob(&[7; 200])).unwrap();
+        assert_eq!(evaluate_blob(&elements, ROOTS_OF_UNITY[3]), elements[3]);
+    }
+}
@@ -6,9 +6,10 @@ use super::{
 };
 use crate::{
     operation::{OperationContainer, RWCounter},
+    precompile::PrecompileEvents,
     Error,
 };
-use eth_types::{evm_unimplemented, Address, Hash, Word};
+use eth_types::{evm_types::MIN_BLOB_GASPRICE, evm_unimplemented, Address, Hash, Word};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -74,6 +75,8 @@ pub struct Block {
     pub mix_hash: Hash,
     /// base fee
     pub base_fee: Word,
+    /// blob base fee
+    pub blob_base_fee: Word,
     /// State root of the previous block
     pub prev_state_root: Word,
     /// Container of operations done in this block.
@@ -88,6 +91,8 @@ pub struct Block {
     pub sha3_inputs: Vec<Vec<u8>>,
     /// Exponentiation events in the block.
     pub exp_events: Vec<ExpEvent>,
+    /// Inputs of the precompile calls in the block.
+    pub precompile_events: PrecompileEvents,
     /// Circuits Setup Paramteres
     pub circuits_params: CircuitsParams,
     /// Original block from geth
@@ -95,6 +100,10 @@ pub struct Block {
     /// Protocol instance from protocol
     /// If this is set, means we are in the taiko context
     pub protocol_instance: Option<ProtocolInstance>,
+    /// Merkle proofs of the accounts and storage slots accessed in the
+    /// block, against the state root of the previous block.  Required, the
+    /// state root transition of the block can't be proven without them.
+    pub state_proofs: Vec<eth_types::EIP1186ProofResponse>,
 }
 
 impl Block {
@@ -131,6 +140,9 @@ impl Block {
                 .mix_hash
                 .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            // L2 blocks don't carry blobs, so there is never any excess blob
+            // gas and the blob base fee stays at its minimum.
+            blob_base_fee: MIN_BLOB_GASPRICE.into(),
             prev_state_root,
             container: OperationContainer::new(),
             txs: Vec::new(),
@@ -147,9 +159,11 @@ impl Block {
             copy_events: Vec::new(),
             exp_events: Vec::new(),
             sha3_inputs: Vec::new(),
+            precompile_events: PrecompileEvents::default(),
             circuits_params,
             eth_block: eth_block.clone(),
             protocol_instance,
+            state_proofs: Vec::new(),
         })
     }
 
@@ -205,6 +205,10 @@ pub enum CopyDataType {
     /// scenario where we wish to accumulate the value (RLC) over all rows.
     /// This is used for Copy Lookup from SHA3 opcode verification.
     RlcAcc,
+    /// When both the source and the destination for the copy event are the
+    /// memory of the same call, as in MCOPY.  All the bytes are read before
+    /// any of them is written, so overlapping ranges are copied correctly.
+    MemoryCopy,
 }
 
 impl From<CopyDataType> for usize {
@@ -277,16 +281,33 @@ impl CopyEvent {
 
     /// rw counter increase left at step index
     pub fn rw_counter_increase_left(&self, step_index: usize) -> u64 {
+        if self.src_type == CopyDataType::MemoryCopy {
+            // Every step of a memory copy does one rw operation, although
+            // the rw counters of the steps are not in order.
+            return self.rw_counter_delta() - u64::try_from(step_index).unwrap();
+        }
         self.rw_counter(self.bytes.len() * 2) - self.rw_counter(step_index)
     }
 
     /// Number of rw operations performed by this copy event
     pub fn rw_counter_delta(&self) -> u64 {
+        if self.src_type == CopyDataType::MemoryCopy {
+            return u64::try_from(self.bytes.len() * 2).unwrap();
+        }
         self.rw_counter_increase(self.bytes.len() * 2)
     }
 
     // increase in rw counter from the start of the copy event to step index
     fn rw_counter_increase(&self, step_index: usize) -> u64 {
+        if self.src_type == CopyDataType::MemoryCopy {
+            // The reads of all the bytes come before the writes.
+            let byte_index = u64::try_from(step_index / 2).unwrap();
+            return if step_index % 2 == 0 {
+                byte_index
+            } else {
+                u64::try_from(self.bytes.len()).unwrap() + byte_index
+            };
+        }
         let source_rw_increase = match self.src_type {
             CopyDataType::Bytecode | CopyDataType::TxCalldata => 0,
             CopyDataType::Memory => std::cmp::min(
@@ -295,12 +316,17 @@ impl CopyEvent {
                     .checked_sub(self.src_addr)
                     .unwrap_or_default(),
             ),
-            CopyDataType::RlcAcc | CopyDataType::TxLog | CopyDataType::Padding => unreachable!(),
+            CopyDataType::RlcAcc
+            | CopyDataType::TxLog
+            | CopyDataType::Padding
+            | CopyDataType::MemoryCopy => unreachable!(),
         };
         let destination_rw_increase = match self.dst_type {
             CopyDataType::RlcAcc | CopyDataType::Bytecode => 0,
             CopyDataType::TxLog | CopyDataType::Memory => u64::try_from(step_index).unwrap() / 2,
-            CopyDataType::TxCalldata | CopyDataType::Padding => unreachable!(),
+            CopyDataType::TxCalldata | CopyDataType::Padding | CopyDataType::MemoryCopy => {
+                unreachable!()
+            }
         };
         source_rw_increase + destination_rw_increase
     }
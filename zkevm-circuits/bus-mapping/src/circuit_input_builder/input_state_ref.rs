@@ -19,6 +19,7 @@ use crate::{
 use eth_types::{
     evm_types::{
         gas_utils::memory_expansion_gas_cost, Gas, GasCost, MemoryAddress, OpcodeId, StackAddress,
+        MAX_CODE_SIZE,
     },
     Address, Bytecode, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256, U256,
 };
@@ -842,6 +843,22 @@ impl<'a> CircuitInputStateRef<'a> {
                     None
                 }
             }
+            OperationRef(Target::TransientStorage, idx) => {
+                let operation = &self.block.container.transient_storage[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TransientStorage(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
+            OperationRef(Target::TxCreatedAccount, idx) => {
+                let operation = &self.block.container.tx_created_account[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TxCreatedAccount(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
@@ -874,12 +891,26 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::TxRefund(op) => {
                 self.sdb.set_refund(op.value);
             }
+            OpEnum::TransientStorage(op) => {
+                self.sdb
+                    .set_transient_storage(&op.address, &op.key, &op.value);
+            }
+            OpEnum::TxCreatedAccount(op) => {
+                if !op.is_created_prev && op.is_created {
+                    self.sdb.add_created_account(op.address);
+                }
+                if op.is_created_prev && !op.is_created {
+                    self.sdb.remove_created_account(&op.address);
+                }
+            }
             _ => unreachable!(),
         };
     }
 
-    /// Handle a reversion group
-    fn handle_reversion(&mut self) {
+    /// Handle a reversion group.  The reversion of an operation of a step that
+    /// is not yet part of the transaction (e.g. the call to a precompile that
+    /// fails in the same opcode) is attached to `current_exec_step`.
+    fn handle_reversion(&mut self, current_exec_step: &mut ExecStep) {
         let reversion_group = self
             .tx_ctx
             .reversion_groups
@@ -896,9 +927,13 @@ impl<'a> CircuitInputStateRef<'a> {
                     false,
                     op,
                 );
-                self.tx.steps_mut()[step_index]
-                    .bus_mapping_instance
-                    .push(rev_op_ref);
+                if step_index < self.tx.steps().len() {
+                    self.tx.steps_mut()[step_index]
+                        .bus_mapping_instance
+                        .push(rev_op_ref);
+                } else {
+                    current_exec_step.bus_mapping_instance.push(rev_op_ref);
+                }
             }
         }
 
@@ -927,7 +962,8 @@ impl<'a> CircuitInputStateRef<'a> {
         let (return_data_offset, return_data_length) = {
             if !self.call()?.is_root {
                 let (offset, length) = match step.op {
-                    OpcodeId::RETURN | OpcodeId::REVERT => {
+                    // A RETURN or REVERT halting with an error leaves no return data.
+                    OpcodeId::RETURN | OpcodeId::REVERT if exec_step.error.is_none() => {
                         let offset = step.stack.nth_last(0)?.as_usize();
                         let length = step.stack.nth_last(1)?.as_usize();
                         // At the moment it conflicts with `call_ctx` and `caller_ctx`.
@@ -975,7 +1011,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
         // Handle reversion if this call doesn't end successfully
         if !call.is_success {
-            self.handle_reversion();
+            self.handle_reversion(exec_step);
         }
 
         // If current call has caller.
@@ -1014,7 +1050,11 @@ impl<'a> CircuitInputStateRef<'a> {
         let geth_step = steps
             .get(0)
             .ok_or(Error::InternalError("invalid index 0"))?;
-        let is_return_revert = geth_step.op == OpcodeId::REVERT || geth_step.op == OpcodeId::RETURN;
+        // A RETURN that halts with an error (e.g. when storing the code of a
+        // create) is handled like any other exception.
+        let is_return_revert = (geth_step.op == OpcodeId::REVERT
+            || geth_step.op == OpcodeId::RETURN)
+            && exec_step.error.is_none();
 
         if !is_return_revert && !call.is_success {
             // add call failure ops for exception cases
@@ -1054,7 +1094,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
         let [last_callee_return_data_offset, last_callee_return_data_length] = match geth_step.op {
             OpcodeId::STOP => [Word::zero(); 2],
-            OpcodeId::REVERT | OpcodeId::RETURN => {
+            OpcodeId::REVERT | OpcodeId::RETURN if is_return_revert => {
                 let offset = geth_step.stack.nth_last(0)?;
                 let length = geth_step.stack.nth_last(1)?;
                 // This is the convention we are using for memory addresses so that there is no
@@ -1214,6 +1254,7 @@ impl<'a> CircuitInputStateRef<'a> {
                     OpcodeId::RETURNDATACOPY => Some(ExecError::ReturnDataOutOfBounds),
                     // Break write protection (CALL with value will be handled below)
                     OpcodeId::SSTORE
+                    | OpcodeId::TSTORE
                     | OpcodeId::CREATE
                     | OpcodeId::CREATE2
                     | OpcodeId::SELFDESTRUCT
@@ -1243,14 +1284,33 @@ impl<'a> CircuitInputStateRef<'a> {
                 if call.is_create() {
                     let offset = step.stack.nth_last(0)?;
                     let length = step.stack.nth_last(1)?;
-                    if length > Word::from(0x6000u64) {
+                    if length > Word::from(MAX_CODE_SIZE) {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > Word::zero()
                         && !call_ctx.memory.is_empty()
                         && call_ctx.memory.0.get(offset.low_u64() as usize) == Some(&0xef)
                     {
                         return Ok(Some(ExecError::InvalidCreationCode));
-                    } else if Word::from(200u64) * length > Word::from(step.gas.0) {
+                    } else if Word::from(GasCost::CODE_DEPOSIT_BYTE_COST.as_u64()) * length
+                        > Word::from(
+                            step.gas.0 - {
+                                // The code deposit is paid after RETURN's memory expansion.
+                                let curr_memory_word_size = call_ctx.memory.word_size() as u64;
+                                let next_memory_word_size = if length.is_zero() {
+                                    curr_memory_word_size
+                                } else {
+                                    max(
+                                        curr_memory_word_size,
+                                        (offset.low_u64() + length.low_u64() + 31) / 32,
+                                    )
+                                };
+                                memory_expansion_gas_cost(
+                                    curr_memory_word_size,
+                                    next_memory_word_size,
+                                )
+                            },
+                        )
+                    {
                         return Ok(Some(ExecError::CodeStoreOutOfGas));
                     } else {
                         return Err(Error::UnexpectedExecStepError(
@@ -1312,11 +1372,15 @@ impl<'a> CircuitInputStateRef<'a> {
             }
 
             // Address collision
-            if matches!(step.op, OpcodeId::CREATE2) {
-                let address = self.create2_address(step)?;
+            if matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2) {
+                let address = match step.op {
+                    OpcodeId::CREATE => self.create_address()?,
+                    OpcodeId::CREATE2 => self.create2_address(step)?,
+                    _ => unreachable!(),
+                };
 
-                let (found, _) = self.sdb.get_account(&address);
-                if found {
+                let (found, account) = self.sdb.get_account(&address);
+                if found && (account.nonce != 0 || account.code_hash != CodeDB::empty_code_hash()) {
                     return Ok(Some(ExecError::ContractAddressCollision));
                 }
             }
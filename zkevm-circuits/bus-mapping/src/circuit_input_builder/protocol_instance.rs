@@ -2,7 +2,12 @@
 
 use alloy_primitives::{B256, U256};
 
+use super::blob::{
+    blob_data, blob_field_elements, evaluate_blob, point_challenge, scalar_to_be_bytes,
+};
+use crate::Error;
 use alloy_sol_types::{sol, SolValue};
+use bls12_381::Scalar;
 use eth_types::Address;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -35,7 +40,7 @@ sol! {
     struct BlockMetadata {
         bytes32 l1Hash; // slot 1
         bytes32 difficulty; // slot 2
-        bytes32 blobHash; //or txListHash (if Blob not yet supported), // slot 3
+        bytes32 blobHash; // or txListHash if blobUsed is false, // slot 3
         bytes32 extraData; // slot 4
         bytes32 depositsHash; // slot 5
         address coinbase; // L2 coinbase, // slot 6
@@ -70,13 +75,25 @@ sol! {
         uint256 pointValue;
     }
 
+    #[derive(Debug, Default, Deserialize, Serialize)]
+    struct SgxVerifierSignedHash {
+        bytes32 parentHash;
+        bytes32 blockHash;
+        bytes32 signalRoot;
+        bytes32 graffiti;
+        address newInstance;
+        address prover;
+        bytes32 metaHash;
+    }
+
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum EvidenceType {
     Sgx {
         new_pubkey: Address, // the evidence signature public key
     },
+    #[default]
     PseZk,
 }
 
@@ -85,6 +102,9 @@ pub struct ProtocolInstance {
     pub transition: Transition,
     pub block_metadata: BlockMetadata,
     pub prover: Address,
+    /// The blob of the block when `blobUsed`, see `blob::encode_blob`
+    #[serde(default)]
+    pub blob: Vec<u8>,
 }
 
 impl ProtocolInstance {
@@ -97,27 +117,51 @@ impl ProtocolInstance {
     //     uint256 pointValue
     // )
     // return keccak256(abi.encode(tran, prover, metaHash, txListHash, pointValue));
-    pub fn hash(&self, evidence_type: EvidenceType) -> B256 {
-        match evidence_type {
-            EvidenceType::Sgx { new_pubkey: _ } => todo!(),
+    /// SgxVerifier.sol
+    // function getSignedHash(
+    //     TaikoData.Transition memory tran,
+    //     address newInstance,
+    //     address prover,
+    //     bytes32 metaHash
+    // )
+    // return keccak256(abi.encode(tran, newInstance, prover, metaHash));
+    pub fn hash(&self, evidence_type: EvidenceType) -> Result<B256, Error> {
+        Ok(match evidence_type {
+            EvidenceType::Sgx { new_pubkey } => {
+                // keccak256(abi.encode(tran, newInstance, prover, metaHash));
+                keccak(self.sgx_abi_encode(new_pubkey)).into()
+            }
             EvidenceType::PseZk => {
                 // keccak256(abi.encode(tran, prover, metaHash, txListHash, pointValue));
-                keccak(self.abi_encode()).into()
+                keccak(self.abi_encode()?).into()
             }
-        }
+        })
     }
 
-    pub fn abi_encode(&self) -> Vec<u8> {
+    pub fn abi_encode(&self) -> Result<Vec<u8>, Error> {
         let meta_hash = keccak(self.block_metadata.abi_encode());
-        PseZkVerifierCalcInstance {
+        Ok(PseZkVerifierCalcInstance {
             parentHash: self.transition.parentHash,
             blockHash: self.transition.blockHash,
             signalRoot: self.transition.signalRoot,
             graffiti: self.transition.graffiti,
             metaHash: meta_hash.into(),
             prover: self.prover.as_fixed_bytes().into(),
-            txListHash: self.block_metadata.blobHash,
-            pointValue: U256::from(0),
+            txListHash: self.tx_list_hash_b256()?,
+            pointValue: self.point_value_u256()?,
+        }
+        .abi_encode())
+    }
+
+    pub fn sgx_abi_encode(&self, new_pubkey: Address) -> Vec<u8> {
+        SgxVerifierSignedHash {
+            parentHash: self.transition.parentHash,
+            blockHash: self.transition.blockHash,
+            signalRoot: self.transition.signalRoot,
+            graffiti: self.transition.graffiti,
+            newInstance: new_pubkey.as_fixed_bytes().into(),
+            prover: self.prover.as_fixed_bytes().into(),
+            metaHash: keccak(self.meta_data()).into(),
         }
         .abi_encode()
     }
@@ -144,15 +188,85 @@ impl ProtocolInstance {
     }
 
     pub fn meta_hash(&self) -> Vec<u8> {
-        keccak(self.block_metadata.abi_encode()).into()
+        keccak(self.meta_data()).into()
     }
 
-    pub fn tx_list_hash(&self) -> Vec<u8> {
-        self.block_metadata.blobHash.abi_encode()
+    pub fn meta_data(&self) -> Vec<u8> {
+        self.block_metadata.abi_encode()
     }
 
-    pub fn point_value(&self) -> Vec<u8> {
-        U256::from(0).abi_encode()
+    pub fn tx_list_hash(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.tx_list_hash_b256()?.abi_encode())
+    }
+
+    pub fn point_value(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.point_value_u256()?.abi_encode())
+    }
+
+    /// Whether the tx list is posted in a blob instead of the calldata
+    pub fn blob_used(&self) -> bool {
+        self.block_metadata.blobUsed
+    }
+
+    /// The tx list stored in the blob, empty in calldata mode.  Fails if the
+    /// blob is too long or if the range of the tx list is out of its data.
+    pub fn blob_tx_list(&self) -> Result<Vec<u8>, Error> {
+        if !self.blob_used() {
+            return Ok(Vec::new());
+        }
+        let data = blob_data(&self.blob).ok_or(Error::InvalidBlob("blob too long"))?;
+        let offset = self.block_metadata.txListByteOffset as usize;
+        let size = self.block_metadata.txListByteSize as usize;
+        data.get(offset..offset + size)
+            .map(|tx_list| tx_list.to_vec())
+            .ok_or(Error::InvalidBlob("tx list out of the blob data"))
+    }
+
+    /// The hash of the tx list in blob mode, the blob hash otherwise.
+    pub fn tx_list_hash_b256(&self) -> Result<B256, Error> {
+        Ok(if self.blob_used() {
+            keccak(self.blob_tx_list()?).into()
+        } else {
+            self.block_metadata.blobHash
+        })
+    }
+
+    /// The challenge at which the blob is evaluated
+    pub fn point_challenge(&self) -> Result<Scalar, Error> {
+        Ok(point_challenge(
+            &self.block_metadata.blobHash.0,
+            &self.tx_list_hash_b256()?.0,
+        ))
+    }
+
+    /// The evaluation of the blob at the point challenge in blob mode, 0
+    /// otherwise.  Fails if the blob is too long or if an element is not
+    /// canonical.
+    pub fn point_value_u256(&self) -> Result<U256, Error> {
+        if !self.blob_used() {
+            return Ok(U256::from(0));
+        }
+        let elements = blob_field_elements(&self.blob).ok_or(Error::InvalidBlob(
+            "blob too long or with a non canonical element",
+        ))?;
+        let value = evaluate_blob(&elements, self.point_challenge()?);
+        Ok(U256::from_be_bytes(scalar_to_be_bytes(&value)))
+    }
+
+    /// The inputs of the keccak hashes computed by the PI circuit
+    pub fn keccak_inputs(&self) -> Result<Vec<Vec<u8>>, Error> {
+        let mut inputs = vec![self.abi_encode()?, self.meta_data()];
+        if self.blob_used() {
+            inputs.push(self.blob_tx_list()?);
+            inputs.push(
+                [
+                    self.block_metadata.blobHash.as_slice(),
+                    self.tx_list_hash_b256()?.as_slice(),
+                ]
+                .concat(),
+            );
+        }
+        Ok(inputs)
     }
 }
 
@@ -162,3 +276,79 @@ pub fn keccak(data: impl AsRef<[u8]>) -> [u8; 32] {
     // std::hint::black_box(sha2::Sha256::digest(&data));
     Keccak256::digest(data).into()
 }
+
+#[cfg(test)]
+mod protocol_instance_tests {
+    use super::*;
+    use crate::circuit_input_builder::blob::{encode_blob, BLOB_DATA_LEN, BYTES_PER_BLOB};
+
+    #[test]
+    fn sgx_hash() {
+        let pi = ProtocolInstance {
+            transition: Transition {
+                parentHash: [1; 32].into(),
+                blockHash: [2; 32].into(),
+                signalRoot: [3; 32].into(),
+                graffiti: [4; 32].into(),
+            },
+            prover: Address::repeat_byte(5),
+            ..Default::default()
+        };
+        let new_pubkey = Address::repeat_byte(6);
+
+        // abi.encode(tran, newInstance, prover, metaHash) is the concatenation of the words
+        let mut words = vec![[1; 32], [2; 32], [3; 32], [4; 32]].concat();
+        words.extend([0; 12].iter().chain(new_pubkey.as_bytes()));
+        words.extend([0; 12].iter().chain(pi.prover.as_bytes()));
+        words.extend(keccak(pi.meta_data()));
+        assert_eq!(pi.sgx_abi_encode(new_pubkey), words);
+
+        assert_eq!(
+            pi.hash(EvidenceType::Sgx { new_pubkey }).unwrap(),
+            B256::from(keccak(&words))
+        );
+        // keccak256(abi.encode(tran, newInstance, prover, metaHash)) of SgxVerifier.sol,
+        // with the meta hash of the default metadata
+        assert_eq!(
+            pi.meta_hash(),
+            B256::from_str("0x29d9fc594c3d0bcdc855bca1750d1341463553ed49a566d00037c8e3d8994457")
+                .unwrap()
+                .to_vec()
+        );
+        assert_eq!(
+            pi.hash(EvidenceType::Sgx { new_pubkey }).unwrap(),
+            B256::from_str("0x6c43b4a920aba893960f6b203e711b5032e1c57be86a95c48e9580cb0b595b12")
+                .unwrap()
+        );
+        assert_ne!(
+            pi.hash(EvidenceType::Sgx { new_pubkey }).unwrap(),
+            pi.hash(EvidenceType::PseZk).unwrap()
+        );
+    }
+
+    #[test]
+    fn blob_tx_list() {
+        let mut pi = ProtocolInstance {
+            block_metadata: BlockMetadata {
+                txListByteOffset: 2,
+                txListByteSize: 3,
+                blobUsed: true,
+                ..Default::default()
+            },
+            blob: encode_blob(&[1, 2, 3, 4, 5, 6]),
+            ..Default::default()
+        };
+        assert_eq!(pi.blob_tx_list().unwrap(), vec![3, 4, 5]);
+
+        // The range of the tx list is out of the data of the blob
+        pi.block_metadata.txListByteOffset = (BLOB_DATA_LEN - 2) as u32;
+        assert!(matches!(pi.blob_tx_list(), Err(Error::InvalidBlob(_))));
+        assert!(pi.abi_encode().is_err());
+
+        // The blob is too long
+        pi.block_metadata.txListByteOffset = 0;
+        pi.blob = vec![0; BYTES_PER_BLOB + 1];
+        assert!(matches!(pi.blob_tx_list(), Err(Error::InvalidBlob(_))));
+        assert!(matches!(pi.point_value_u256(), Err(Error::InvalidBlob(_))));
+    }
+}
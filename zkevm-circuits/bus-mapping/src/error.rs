@@ -6,9 +6,12 @@ use ethers_providers::ProviderError;
 use serde::{Deserialize, Serialize};
 use std::error::Error as StdError;
 
-use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+use crate::{
+    geth_errors::{
+        GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
+        GETH_ERR_STACK_UNDERFLOW,
+    },
+    precompile::PrecompileCalls,
 };
 
 /// Error type for any BusMapping related failure.
@@ -41,6 +44,12 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// Call to a precompiled contract the circuits can't prove
+    UnsupportedPrecompile(PrecompileCalls),
+    /// Calls to a precompiled contract over the capacity of its circuit
+    PrecompileCapacityExceeded(PrecompileCalls),
+    /// Invalid blob, or tx list out of the data of the blob, of a Taiko block
+    InvalidBlob(&'static str),
 }
 
 impl From<eth_types::Error> for Error {
@@ -75,7 +84,7 @@ pub enum OogError {
     /// expansion gas cost
     DynamicMemoryExpansion,
     /// Out of Gas for CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY,
-    /// which copy a specified chunk of memory
+    /// MCOPY, which copy a specified chunk of memory
     MemoryCopy,
     /// Out of Gas for BALANCE, EXTCODESIZE, EXTCODEHASH, which possibly touch
     /// an extra account
@@ -110,8 +119,8 @@ pub enum ExecError {
     StackUnderflow,
     /// Out of Gas
     OutOfGas(OogError),
-    /// For SSTORE, LOG0, LOG1, LOG2, LOG3, LOG4, CREATE, CALL, CREATE2,
-    /// SELFDESTRUCT
+    /// For SSTORE, TSTORE, LOG0, LOG1, LOG2, LOG3, LOG4, CREATE, CALL,
+    /// CREATE2, SELFDESTRUCT
     WriteProtection,
     /// For CALL, CALLCODE, DELEGATECALL, STATICCALL
     Depth,
@@ -146,7 +155,8 @@ pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
             OpcodeId::CALLDATACOPY
             | OpcodeId::CODECOPY
             | OpcodeId::EXTCODECOPY
-            | OpcodeId::RETURNDATACOPY => OogError::MemoryCopy,
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::MCOPY => OogError::MemoryCopy,
             OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
                 OogError::AccountAccess
             }
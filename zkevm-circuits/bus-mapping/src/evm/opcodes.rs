@@ -3,11 +3,10 @@ use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecState, ExecStep},
     error::{ExecError, OogError},
     evm::OpcodeId,
-    operation::TxAccessListAccountOp,
     Error,
 };
 use core::fmt::Debug;
-use eth_types::{evm_unimplemented, GethExecStep, ToAddress};
+use eth_types::{evm_unimplemented, GethExecStep};
 
 pub use self::sha3::Sha3CodeGen;
 
@@ -31,6 +30,7 @@ mod extcodehash;
 mod extcodesize;
 mod gasprice;
 mod logs;
+mod mcopy;
 mod mload;
 mod mstore;
 mod number;
@@ -40,19 +40,30 @@ mod return_revert;
 mod returndatacopy;
 mod returndatasize;
 mod selfbalance;
+mod selfdestruct;
 mod sha3;
 mod sload;
 mod sstore;
 mod stackonlyop;
 mod stop;
 mod swap;
+mod tload;
+mod tstore;
 
+mod error_code_store;
+mod error_invalid_creation_code;
 mod error_invalid_jump;
+mod error_oog_account_access;
 mod error_oog_call;
+mod error_oog_create2;
+mod error_oog_dynamic_memory;
 mod error_oog_exp;
 mod error_oog_log;
 mod error_oog_memory_copy;
+mod error_oog_self_destruct;
+mod error_oog_sha3;
 mod error_oog_sload_sstore;
+mod error_oog_static_memory;
 mod error_return_data_outofbound;
 mod error_simple;
 mod error_write_protection;
@@ -74,14 +85,22 @@ use callop::CallOpcode;
 use callvalue::Callvalue;
 use codecopy::Codecopy;
 use codesize::Codesize;
-use create::DummyCreate;
+use create::Create;
 use dup::Dup;
+use error_code_store::ErrorCodeStore;
+use error_invalid_creation_code::ErrorCreationCode;
 use error_invalid_jump::InvalidJump;
+use error_oog_account_access::OOGAccountAccess;
 use error_oog_call::OOGCall;
+use error_oog_create2::OOGCreate2;
+use error_oog_dynamic_memory::OOGDynamicMemory;
 use error_oog_exp::OOGExp;
 use error_oog_log::ErrorOOGLog;
 use error_oog_memory_copy::OOGMemoryCopy;
+use error_oog_self_destruct::OOGSelfDestruct;
+use error_oog_sha3::OOGSha3;
 use error_oog_sload_sstore::OOGSloadSstore;
+use error_oog_static_memory::OOGStaticMemory;
 use error_return_data_outofbound::ErrorReturnDataOutOfBound;
 use error_simple::ErrorSimple;
 use error_write_protection::ErrorWriteProtection;
@@ -91,6 +110,7 @@ use extcodehash::Extcodehash;
 use extcodesize::Extcodesize;
 use gasprice::GasPrice;
 use logs::Log;
+use mcopy::Mcopy;
 use mload::Mload;
 use mstore::Mstore;
 use origin::Origin;
@@ -99,11 +119,14 @@ use return_revert::ReturnRevert;
 use returndatacopy::Returndatacopy;
 use returndatasize::Returndatasize;
 use selfbalance::Selfbalance;
+use selfdestruct::Selfdestruct;
 use sload::Sload;
 use sstore::Sstore;
 use stackonlyop::StackOnlyOpcode;
 use stop::Stop;
 use swap::Swap;
+use tload::Tload;
+use tstore::Tstore;
 
 #[cfg(feature = "test")]
 pub use callop::tests::PrecompileCallArgs;
@@ -210,12 +233,17 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CHAINID => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::SELFBALANCE => Selfbalance::gen_associated_ops,
         OpcodeId::BASEFEE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
+        OpcodeId::BLOBHASH => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        OpcodeId::BLOBBASEFEE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::POP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
         OpcodeId::MLOAD => Mload::gen_associated_ops,
         OpcodeId::MSTORE => Mstore::<false>::gen_associated_ops,
         OpcodeId::MSTORE8 => Mstore::<true>::gen_associated_ops,
         OpcodeId::SLOAD => Sload::gen_associated_ops,
         OpcodeId::SSTORE => Sstore::gen_associated_ops,
+        OpcodeId::TLOAD => Tload::gen_associated_ops,
+        OpcodeId::TSTORE => Tstore::gen_associated_ops,
+        OpcodeId::MCOPY => Mcopy::gen_associated_ops,
         OpcodeId::JUMP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
         OpcodeId::JUMPI => StackOnlyOpcode::<2, 0>::gen_associated_ops,
         OpcodeId::PC => StackOnlyOpcode::<0, 1>::gen_associated_ops,
@@ -262,18 +290,9 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CALL | OpcodeId::CALLCODE => CallOpcode::<7>::gen_associated_ops,
         OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => CallOpcode::<6>::gen_associated_ops,
         OpcodeId::RETURN | OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
-        OpcodeId::SELFDESTRUCT => {
-            evm_unimplemented!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
-            DummySelfDestruct::gen_associated_ops
-        }
-        OpcodeId::CREATE => {
-            evm_unimplemented!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-            DummyCreate::<false>::gen_associated_ops
-        }
-        OpcodeId::CREATE2 => {
-            evm_unimplemented!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-            DummyCreate::<true>::gen_associated_ops
-        }
+        OpcodeId::SELFDESTRUCT => Selfdestruct::gen_associated_ops,
+        OpcodeId::CREATE => Create::<false>::gen_associated_ops,
+        OpcodeId::CREATE2 => Create::<true>::gen_associated_ops,
         _ => {
             evm_unimplemented!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
             Dummy::gen_associated_ops
@@ -288,25 +307,38 @@ fn fn_gen_error_state_associated_ops(
     match error {
         ExecError::InvalidJump => Some(InvalidJump::gen_associated_ops),
         ExecError::InvalidOpcode => Some(ErrorSimple::gen_associated_ops),
+        ExecError::OutOfGas(OogError::AccountAccess) => Some(OOGAccountAccess::gen_associated_ops),
         ExecError::OutOfGas(OogError::Call) => Some(OOGCall::gen_associated_ops),
         ExecError::OutOfGas(OogError::Constant) => Some(ErrorSimple::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Create2) => Some(OOGCreate2::gen_associated_ops),
+        ExecError::OutOfGas(OogError::DynamicMemoryExpansion) => {
+            Some(OOGDynamicMemory::gen_associated_ops)
+        }
         ExecError::OutOfGas(OogError::Exp) => Some(OOGExp::gen_associated_ops),
         ExecError::OutOfGas(OogError::Log) => Some(ErrorOOGLog::gen_associated_ops),
         ExecError::OutOfGas(OogError::MemoryCopy) => Some(OOGMemoryCopy::gen_associated_ops),
+        ExecError::OutOfGas(OogError::SelfDestruct) => Some(OOGSelfDestruct::gen_associated_ops),
+        ExecError::OutOfGas(OogError::Sha3) => Some(OOGSha3::gen_associated_ops),
         ExecError::OutOfGas(OogError::SloadSstore) => Some(OOGSloadSstore::gen_associated_ops),
+        ExecError::OutOfGas(OogError::StaticMemoryExpansion) => {
+            Some(OOGStaticMemory::gen_associated_ops)
+        }
         ExecError::StackOverflow => Some(ErrorSimple::gen_associated_ops),
         ExecError::StackUnderflow => Some(ErrorSimple::gen_associated_ops),
-        // call & callcode can encounter InsufficientBalance error, Use pop-7 generic CallOpcode
-        ExecError::InsufficientBalance => Some(CallOpcode::<7>::gen_associated_ops),
+        // call, callcode, create & create2 can encounter InsufficientBalance error, and create &
+        // create2 can encounter ContractAddressCollision, both handled by the opcode itself
+        ExecError::InsufficientBalance | ExecError::ContractAddressCollision => {
+            Some(fn_gen_associated_ops(&geth_step.op))
+        }
         ExecError::WriteProtection => Some(ErrorWriteProtection::gen_associated_ops),
         ExecError::ReturnDataOutOfBounds => Some(ErrorReturnDataOutOfBound::gen_associated_ops),
-        ExecError::Depth => {
-            let op = geth_step.op;
-            if !op.is_call() {
-                evm_unimplemented!("TODO: ErrDepth for CREATE is not implemented yet");
-            }
-            Some(fn_gen_associated_ops(&op))
+        ExecError::CodeStoreOutOfGas | ExecError::MaxCodeSizeExceeded => {
+            Some(ErrorCodeStore::gen_associated_ops)
         }
+        ExecError::InvalidCreationCode => Some(ErrorCreationCode::gen_associated_ops),
+        // CALL, CALLCODE, DELEGATECALL, STATICCALL, CREATE & CREATE2 push 0 to the stack when the
+        // call depth limit is reached, which is handled by the opcode itself
+        ExecError::Depth => Some(fn_gen_associated_ops(&geth_step.op)),
         // more future errors place here
         _ => {
             evm_unimplemented!("TODO: error state {:?} not implemented", error);
@@ -389,55 +421,3 @@ pub fn gen_associated_steps(
 
     fn_gen_associated_steps(state, execution_step)
 }
-
-#[derive(Debug, Copy, Clone)]
-struct DummySelfDestruct;
-
-impl Opcode for DummySelfDestruct {
-    fn gen_associated_ops(
-        state: &mut CircuitInputStateRef,
-        geth_steps: &[GethExecStep],
-    ) -> Result<Vec<ExecStep>, Error> {
-        dummy_gen_selfdestruct_ops(state, geth_steps)
-    }
-}
-fn dummy_gen_selfdestruct_ops(
-    state: &mut CircuitInputStateRef,
-    geth_steps: &[GethExecStep],
-) -> Result<Vec<ExecStep>, Error> {
-    let geth_step = &geth_steps[0];
-    let mut exec_step = state.new_step(geth_step)?;
-    let sender = state.call()?.address;
-    let receiver = geth_step.stack.last()?.to_address();
-
-    let is_warm = state.sdb.check_account_in_access_list(&receiver);
-    state.push_op_reversible(
-        &mut exec_step,
-        TxAccessListAccountOp {
-            tx_id: state.tx_ctx.id(),
-            address: receiver,
-            is_warm: true,
-            is_warm_prev: is_warm,
-        },
-    )?;
-
-    let (found, _) = state.sdb.get_account(&receiver);
-    if !found {
-        return Err(Error::AccountNotFound(receiver));
-    }
-    let (found, sender_account) = state.sdb.get_account(&sender);
-    if !found {
-        return Err(Error::AccountNotFound(sender));
-    }
-    let value = sender_account.balance;
-    // NOTE: In this dummy implementation we assume that the receiver already
-    // exists.
-    state.transfer(&mut exec_step, sender, receiver, true, false, value)?;
-
-    if state.call()?.is_persistent {
-        state.sdb.destruct_account(sender);
-    }
-
-    state.handle_return(&mut exec_step, geth_steps, false)?;
-    Ok(vec![exec_step])
-}
@@ -1,7 +1,10 @@
 use super::TxExecSteps;
 use crate::{
     circuit_input_builder::{protocol_instance, CircuitInputStateRef, ExecState, ExecStep},
-    operation::{AccountField, AccountOp, CallContextField, TxReceiptField, TxRefundOp, RW},
+    operation::{
+        AccountField, AccountOp, CallContextField, TxCreatedAccountOp, TxReceiptField, TxRefundOp,
+        RW,
+    },
     state_db::CodeDB,
     Error,
 };
@@ -152,6 +155,15 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
                     value_prev: 0.into(),
                 },
             )?;
+            state.push_op_reversible(
+                &mut exec_step,
+                TxCreatedAccountOp {
+                    tx_id: state.tx_ctx.id(),
+                    address: call.address,
+                    is_created: true,
+                    is_created_prev: false,
+                },
+            )?;
             for (field, value) in [
                 (CallContextField::Depth, call.depth.into()),
                 (
@@ -1,22 +1,19 @@
 use super::Opcode;
 use crate::{
-    circuit_input_builder::{
-        CallKind, CircuitInputStateRef, CodeSource, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
-    },
+    circuit_input_builder::{CallKind, CircuitInputStateRef, CodeSource, ExecStep},
     evm::opcodes::precompiles::gen_associated_ops as precompile_associated_ops,
-    operation::{AccountField, CallContextField, MemoryOp, TxAccessListAccountOp, RW},
-    precompile::{execute_precompiled, is_precompiled, PrecompileCalls},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp},
+    precompile::{is_precompiled, PrecompileCalls},
     state_db::CodeDB,
     Error,
 };
 use eth_types::{
     evm_types::{
         gas_utils::{eip150_gas, memory_expansion_gas_cost},
-        GasCost,
+        GasCost, GAS_STIPEND_CALL_WITH_VALUE,
     },
     GethExecStep, ToWord, Word,
 };
-use std::cmp::min;
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the `OpcodeId::CALL`, `OpcodeId::CALLCODE`,
@@ -220,38 +217,35 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
         let gas_specified = geth_step.stack.last()?;
         let callee_gas_left = eip150_gas(geth_step.gas.0 - gas_cost, gas_specified);
 
+        // The caller's context saved when entering the callee, and restored
+        // when returning from it.
+        let caller_state: [(CallContextField, Word); 5] = [
+            (
+                CallContextField::ProgramCounter,
+                (geth_step.pc.0 + 1).into(),
+            ),
+            (
+                CallContextField::StackPointer,
+                (geth_step.stack.stack_pointer().0 + N_ARGS - 1).into(),
+            ),
+            (
+                CallContextField::GasLeft,
+                (geth_step.gas.0 - gas_cost - callee_gas_left).into(),
+            ),
+            (CallContextField::MemorySize, next_memory_word_size.into()),
+            (
+                CallContextField::ReversibleWriteCounter,
+                (exec_step.reversible_write_counter + 1).into(),
+            ),
+        ];
+
         // There are 4 branches from here.
         // add failure case for insufficient balance or error depth in the future.
         match (!is_precheck_ok, is_precompile, is_empty_code_hash) {
             // 1. Call to precompiled.
             (false, true, _) => {
-                assert!(call.is_success, "call to precompile should not fail");
-                let caller_ctx = state.caller_ctx()?;
-                let caller_memory = caller_ctx.memory.0.clone();
-                let code_address = code_address.unwrap();
-                let (result, contract_gas_cost) = execute_precompiled(
-                    &code_address,
-                    if args_length != 0 {
-                        &caller_memory[args_offset..args_offset + args_length]
-                    } else {
-                        &[]
-                    },
-                    callee_gas_left,
-                );
-
-                log::trace!(
-                    "precompile return data len {} gas {}",
-                    result.len(),
-                    contract_gas_cost
-                );
-
-                let caller_ctx_mut = state.caller_ctx_mut()?;
-                caller_ctx_mut.return_data = result.clone();
-                let length = min(result.len(), ret_length);
-                if length > 0 {
-                    caller_ctx_mut.memory.extend_at_least(ret_offset + length);
-                    caller_ctx_mut.memory.0[ret_offset..ret_offset + length]
-                        .copy_from_slice(&result[..length]);
+                for (field, value) in caller_state.iter().cloned() {
+                    state.call_context_write(&mut exec_step, current_call.call_id, field, value);
                 }
 
                 for (field, value) in [
@@ -284,128 +278,23 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                     state.call_context_write(&mut exec_step, call.call_id, field, value);
                 }
 
-                // return while restoring some of caller's context.
-                for (field, value) in [
-                    (CallContextField::LastCalleeId, call.call_id.into()),
-                    (CallContextField::LastCalleeReturnDataOffset, 0.into()),
-                    (
-                        CallContextField::LastCalleeReturnDataLength,
-                        result.len().into(),
-                    ),
-                ] {
-                    state.call_context_write(&mut exec_step, current_call.call_id, field, value);
-                }
-
-                // insert a copy event (input) for this step
-                let rw_counter_start = state.block_ctx.rwc;
-                if call.is_success && call.call_data_length > 0 {
-                    let bytes: Vec<(u8, bool)> = caller_memory
-                        .iter()
-                        .skip(call.call_data_offset as usize)
-                        .take(call.call_data_length as usize)
-                        .map(|b| (*b, false))
-                        .collect();
-                    for (i, &(byte, _is_code)) in bytes.iter().enumerate() {
-                        // push caller memory read
-                        state.push_op(
-                            &mut exec_step,
-                            RW::READ,
-                            MemoryOp::new(
-                                call.caller_id,
-                                (call.call_data_offset + i as u64).into(),
-                                byte,
-                            ),
-                        );
-                        // push callee memory write
-                        state.push_op(
-                            &mut exec_step,
-                            RW::WRITE,
-                            MemoryOp::new(call.call_id, i.into(), byte),
-                        );
-                    }
-                    state.push_copy(
-                        &mut exec_step,
-                        CopyEvent {
-                            src_id: NumberOrHash::Number(call.caller_id),
-                            src_type: CopyDataType::Memory,
-                            src_addr: call.call_data_offset,
-                            src_addr_end: call.call_data_offset + call.call_data_length,
-                            dst_id: NumberOrHash::Number(call.call_id),
-                            dst_type: CopyDataType::Memory,
-                            dst_addr: 0,
-                            log_id: None,
-                            rw_counter_start,
-                            bytes,
-                        },
-                    );
-                }
-
-                // insert another copy event (output) for this step.
-                let rw_counter_start = state.block_ctx.rwc;
-                if call.is_success && call.call_data_length > 0 && length > 0 {
-                    let bytes: Vec<(u8, bool)> = caller_memory
-                        .iter()
-                        .skip(call.call_data_offset as usize)
-                        .take(length)
-                        .map(|b| (*b, false))
-                        .collect();
-                    for (i, &(byte, _is_code)) in bytes.iter().enumerate() {
-                        // push callee memory read
-                        state.push_op(
-                            &mut exec_step,
-                            RW::READ,
-                            MemoryOp::new(call.call_id, i.into(), byte),
-                        );
-                        // push caller memory write
-                        state.push_op(
-                            &mut exec_step,
-                            RW::WRITE,
-                            MemoryOp::new(
-                                call.caller_id,
-                                (call.return_data_offset + i as u64).into(),
-                                byte,
-                            ),
-                        );
-                    }
-                    state.push_copy(
-                        &mut exec_step,
-                        CopyEvent {
-                            src_id: NumberOrHash::Number(call.call_id),
-                            src_type: CopyDataType::Memory,
-                            src_addr: 0,
-                            src_addr_end: length as u64,
-                            dst_id: NumberOrHash::Number(call.caller_id),
-                            dst_type: CopyDataType::Memory,
-                            dst_addr: call.return_data_offset,
-                            log_id: None,
-                            rw_counter_start,
-                            bytes,
-                        },
-                    );
-                }
-
-                // TODO: when more precompiles are supported and each have their own different
-                // behaviour, we can separate out the logic specified here.
-                let precompile_call: PrecompileCalls = code_address.0[19].into();
+                // The precompile runs in its own step, which returns to the
+                // caller.
+                let precompile_call: PrecompileCalls = code_address.unwrap().0[19].into();
+                let precompile_gas_left = if has_value {
+                    callee_gas_left + GAS_STIPEND_CALL_WITH_VALUE
+                } else {
+                    callee_gas_left
+                };
                 let precompile_step = precompile_associated_ops(
                     state,
-                    geth_steps[1].clone(),
+                    geth_steps,
                     call.clone(),
                     precompile_call,
+                    precompile_gas_left,
+                    &caller_state,
                 )?;
 
-                state.handle_return(&mut exec_step, geth_steps, false)?;
-
-                let real_cost = geth_steps[0].gas.0 - geth_steps[1].gas.0;
-                if real_cost != exec_step.gas_cost.0 {
-                    log::warn!(
-                        "precompile gas fixed from {} to {}, step {:?}",
-                        exec_step.gas_cost.0,
-                        real_cost,
-                        geth_steps[0]
-                    );
-                }
-                exec_step.gas_cost = GasCost(real_cost);
                 Ok(vec![exec_step, precompile_step])
             }
             // 2. Call to account with empty code.
@@ -422,25 +311,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
             }
             // 3. Call to account with non-empty code.
             (false, _, false) => {
-                for (field, value) in [
-                    (
-                        CallContextField::ProgramCounter,
-                        (geth_step.pc.0 + 1).into(),
-                    ),
-                    (
-                        CallContextField::StackPointer,
-                        (geth_step.stack.stack_pointer().0 + N_ARGS - 1).into(),
-                    ),
-                    (
-                        CallContextField::GasLeft,
-                        (geth_step.gas.0 - gas_cost - callee_gas_left).into(),
-                    ),
-                    (CallContextField::MemorySize, next_memory_word_size.into()),
-                    (
-                        CallContextField::ReversibleWriteCounter,
-                        (exec_step.reversible_write_counter + 1).into(),
-                    ),
-                ] {
+                for (field, value) in caller_state {
                     state.call_context_write(&mut exec_step, current_call.call_id, field, value);
                 }
 
@@ -698,10 +569,10 @@ pub mod tests {
                     PUSH1(2) // y1
                     PUSH1(0x20)
                     MSTORE
-                    PUSH1(1) // x2
+                    PUSH32(word!("0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3")) // x2
                     PUSH1(0x40)
                     MSTORE
-                    PUSH1(2) // y2
+                    PUSH32(word!("0x15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4")) // y2
                     PUSH1(0x60)
                     MSTORE
                 },
@@ -712,11 +583,11 @@ pub mod tests {
                 stack_value: vec![
                     (
                         Word::from(0x80),
-                        word!("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3"),
+                        word!("769bf9ac56bea3ff40232bcb1b6bd159315d84715b8e679f2d355961915abf0"),
                     ),
                     (
                         Word::from(0xA0),
-                        word!("15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4"),
+                        word!("2ab799bee0489429554fdb7c8d086475319e63b40b9c5b57cdf1ff3dd9fe2261"),
                     ),
                 ],
                 ..Default::default()
@@ -750,52 +621,6 @@ pub mod tests {
                 ],
                 ..Default::default()
             },
-            PrecompileCallArgs {
-                name: "ecPairing",
-                setup_code: bytecode! {
-                    PUSH32(word!("0x23a8eb0b0996252cb548a4487da97b02422ebc0e834613f954de6c7e0afdc1fc"))
-                    PUSH32(word!("0x2a23af9a5ce2ba2796c1f4e453a370eb0af8c212d9dc9acd8fc02c2e907baea2"))
-                    PUSH32(word!("0x091058a3141822985733cbdddfed0fd8d6c104e9e9eff40bf5abfef9ab163bc7"))
-                    PUSH32(word!("0x1971ff0471b09fa93caaf13cbf443c1aede09cc4328f5a62aad45f40ec133eb4"))
-                    PUSH32(word!("0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"))
-                    PUSH32(word!("0x0000000000000000000000000000000000000000000000000000000000000001"))
-                    PUSH32(word!("0x2fe02e47887507adf0ff1743cbac6ba291e66f59be6bd763950bb16041a0a85e"))
-                    PUSH32(word!("0x2bd368e28381e8eccb5fa81fc26cf3f048eea9abfdd85d7ed3ab3698d63e4f90"))
-                    PUSH32(word!("0x22606845ff186793914e03e21df544c34ffe2f2f3504de8a79d9159eca2d98d9"))
-                    PUSH32(word!("0x1fb19bb476f6b9e44e2a32234da8212f61cd63919354bc06aef31e3cfaff3ebc"))
-                    PUSH32(word!("0x2c0f001f52110ccfe69108924926e45f0b0c868df0e7bde1fe16d3242dc715f6"))
-                    PUSH32(word!("0x2cf44499d5d27bb186308b7af7af02ac5bc9eeb6a3d147c186b21fb1b76e18da"))
-
-                    PUSH1(12)
-                    PUSH2(0x200)
-                    MSTORE
-
-                    JUMPDEST
-
-                    PUSH2(0x200)
-                    MLOAD
-                    PUSH1(12)
-                    SUB
-                    PUSH1(0x20)
-                    MUL
-                    MSTORE
-                    PUSH1(1)
-                    PUSH2(0x200)
-                    MLOAD
-                    SUB
-                    DUP1
-                    PUSH2(0x200)
-                    MSTORE
-                    PUSH2(0x192)
-                    JUMPI
-                },
-                ret_size: Word::from(0x20),
-                call_data_length: Word::from(0x180),
-                address: Word::from(0x8),
-                stack_value: vec![(Word::from(0x0), Word::from(1))],
-                max_rws: 3000,
-                ..Default::default()
-            },
             PrecompileCallArgs {
                 name: "blake2f",
                 setup_code: bytecode! {
@@ -898,4 +723,301 @@ pub mod tests {
             }
         }
     }
+
+    // The calls that the circuits can't prove are refused.
+    #[test]
+    fn test_unsupported_precompiled_call() {
+        use crate::{circuit_input_builder::CircuitsParams, mock::BlockData, Error};
+        use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, word, Word};
+        use mock::{
+            test_ctx::{
+                helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+                LoggerConfig,
+            },
+            TestContext,
+        };
+
+        let test_vector = [
+            PrecompileCallArgs {
+                name: "modexp of a 33 bytes base",
+                setup_code: bytecode! {
+                    PUSH1(33) // Bsize
+                    PUSH1(0)
+                    MSTORE
+                    PUSH1(1) // Esize
+                    PUSH1(0x20)
+                    MSTORE
+                    PUSH1(1) // Msize
+                    PUSH1(0x40)
+                    MSTORE
+                    PUSH1(0x08) // B
+                    PUSH1(0x80)
+                    MSTORE8
+                    PUSH1(0x09) // E
+                    PUSH1(0x81)
+                    MSTORE8
+                    PUSH1(0x0A) // M
+                    PUSH1(0x82)
+                    MSTORE8
+                },
+                ret_size: Word::from(0x01),
+                ret_offset: Word::from(0xBF),
+                call_data_length: Word::from(0x83),
+                address: Word::from(0x5),
+                ..Default::default()
+            },
+            PrecompileCallArgs {
+                name: "ecAdd doubling",
+                setup_code: bytecode! {
+                    PUSH1(1) // x1
+                    PUSH1(0)
+                    MSTORE
+                    PUSH1(2) // y1
+                    PUSH1(0x20)
+                    MSTORE
+                    PUSH1(1) // x2
+                    PUSH1(0x40)
+                    MSTORE
+                    PUSH1(2) // y2
+                    PUSH1(0x60)
+                    MSTORE
+                },
+                ret_size: Word::from(0x40),
+                ret_offset: Word::from(0x80),
+                call_data_length: Word::from(0x80),
+                address: Word::from(0x6),
+                ..Default::default()
+            },
+            PrecompileCallArgs {
+                name: "ecMul by zero",
+                setup_code: bytecode! {
+                    PUSH1(1) // x1
+                    PUSH1(0)
+                    MSTORE
+                    PUSH1(2) // y1
+                    PUSH1(0x20)
+                    MSTORE
+                },
+                ret_size: Word::from(0x40),
+                ret_offset: Word::from(0x60),
+                call_data_length: Word::from(0x60),
+                address: Word::from(0x7),
+                ..Default::default()
+            },
+            PrecompileCallArgs {
+                name: "ecPairing",
+                setup_code: bytecode! {
+                    PUSH32(word!("0x23a8eb0b0996252cb548a4487da97b02422ebc0e834613f954de6c7e0afdc1fc"))
+                    PUSH32(word!("0x2a23af9a5ce2ba2796c1f4e453a370eb0af8c212d9dc9acd8fc02c2e907baea2"))
+                    PUSH32(word!("0x091058a3141822985733cbdddfed0fd8d6c104e9e9eff40bf5abfef9ab163bc7"))
+                    PUSH32(word!("0x1971ff0471b09fa93caaf13cbf443c1aede09cc4328f5a62aad45f40ec133eb4"))
+                    PUSH32(word!("0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"))
+                    PUSH32(word!("0x0000000000000000000000000000000000000000000000000000000000000001"))
+                    PUSH32(word!("0x2fe02e47887507adf0ff1743cbac6ba291e66f59be6bd763950bb16041a0a85e"))
+                    PUSH32(word!("0x2bd368e28381e8eccb5fa81fc26cf3f048eea9abfdd85d7ed3ab3698d63e4f90"))
+                    PUSH32(word!("0x22606845ff186793914e03e21df544c34ffe2f2f3504de8a79d9159eca2d98d9"))
+                    PUSH32(word!("0x1fb19bb476f6b9e44e2a32234da8212f61cd63919354bc06aef31e3cfaff3ebc"))
+                    PUSH32(word!("0x2c0f001f52110ccfe69108924926e45f0b0c868df0e7bde1fe16d3242dc715f6"))
+                    PUSH32(word!("0x2cf44499d5d27bb186308b7af7af02ac5bc9eeb6a3d147c186b21fb1b76e18da"))
+
+                    PUSH1(12)
+                    PUSH2(0x200)
+                    MSTORE
+
+                    JUMPDEST
+
+                    PUSH2(0x200)
+                    MLOAD
+                    PUSH1(12)
+                    SUB
+                    PUSH1(0x20)
+                    MUL
+                    MSTORE
+                    PUSH1(1)
+                    PUSH2(0x200)
+                    MLOAD
+                    SUB
+                    DUP1
+                    PUSH2(0x200)
+                    MSTORE
+                    PUSH2(0x192)
+                    JUMPI
+                },
+                ret_size: Word::from(0x20),
+                call_data_length: Word::from(0x180),
+                address: Word::from(0x8),
+                max_rws: 3000,
+                ..Default::default()
+            },
+        ];
+
+        for test_call in test_vector.iter() {
+            let code = test_call.with_call_op(OpcodeId::CALL);
+            let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+                None,
+                account_0_code_account_1_no_code(code),
+                tx_from_1_to_0,
+                |block, _tx| block.number(0xcafeu64),
+                LoggerConfig {
+                    enable_memory: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .into();
+
+            let mut builder = BlockData::new_from_geth_data_with_params(
+                block.clone(),
+                CircuitsParams {
+                    max_rws: test_call.max_rws,
+                    ..Default::default()
+                },
+            )
+            .new_circuit_input_builder();
+            let result = builder.handle_block(&block.eth_block, &block.geth_traces);
+            assert!(
+                matches!(result, Err(Error::UnsupportedPrecompile(_))),
+                "{} is not refused",
+                test_call.name
+            );
+        }
+    }
+
+    // The Blake2F calls that don't fit in the rows of the Blake2F circuit are
+    // refused.
+    #[test]
+    fn test_blake2f_over_capacity() {
+        use crate::{circuit_input_builder::CircuitsParams, mock::BlockData, Error};
+        use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, word, Word};
+        use mock::{
+            test_ctx::{
+                helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+                LoggerConfig,
+            },
+            TestContext,
+        };
+
+        // A compression of 1 round, which takes 4 blocks of 16 rows.
+        let test_call = PrecompileCallArgs {
+            name: "blake2f",
+            setup_code: bytecode! {
+                PUSH32(word!("0000000100000000000000000000000000000000000000000000000000000000"))
+                PUSH1(0)
+                MSTORE
+            },
+            ret_size: Word::from(0x40),
+            call_data_length: Word::from(0xd5),
+            address: Word::from(0x9),
+            ..Default::default()
+        };
+        let code = test_call.with_call_op(OpcodeId::CALL);
+        let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+            LoggerConfig {
+                enable_memory: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .into();
+
+        for (max_blake2f_rows, refused) in [(64, true), (80, false)] {
+            let mut builder = BlockData::new_from_geth_data_with_params(
+                block.clone(),
+                CircuitsParams {
+                    max_blake2f_rows,
+                    ..Default::default()
+                },
+            )
+            .new_circuit_input_builder();
+            let result = builder.handle_block(&block.eth_block, &block.geth_traces);
+            if refused {
+                assert!(
+                    matches!(result, Err(Error::PrecompileCapacityExceeded(_))),
+                    "{} is not refused with {max_blake2f_rows} rows",
+                    test_call.name
+                );
+            } else {
+                assert!(result.is_ok(), "{} is refused", test_call.name);
+            }
+        }
+    }
+
+    // The modexp calls over the capacity of the ModExp circuit are refused.
+    #[test]
+    fn test_modexp_over_capacity() {
+        use crate::{circuit_input_builder::CircuitsParams, mock::BlockData, Error};
+        use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, word, Word};
+        use mock::{
+            test_ctx::{
+                helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+                LoggerConfig,
+            },
+            TestContext,
+        };
+
+        // 8 ** 9 mod 10
+        let test_call = PrecompileCallArgs {
+            name: "modexp",
+            setup_code: bytecode! {
+                PUSH1(1) // Bsize
+                PUSH1(0)
+                MSTORE
+                PUSH1(1) // Esize
+                PUSH1(0x20)
+                MSTORE
+                PUSH1(1) // Msize
+                PUSH1(0x40)
+                MSTORE
+                PUSH32(word!("0x08090A0000000000000000000000000000000000000000000000000000000000")) // B, E and M
+                PUSH1(0x60)
+                MSTORE
+            },
+            ret_size: Word::from(0x01),
+            ret_offset: Word::from(0x9F),
+            call_data_length: Word::from(0x63),
+            address: Word::from(0x5),
+            ..Default::default()
+        };
+        // Two calls in the same tx
+        let mut code = test_call.with_call_op(OpcodeId::CALL);
+        code.append(&test_call.with_call_op(OpcodeId::STATICCALL));
+        let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+            LoggerConfig {
+                enable_memory: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .into();
+
+        for (max_modexp, refused) in [(1, true), (2, false)] {
+            let mut builder = BlockData::new_from_geth_data_with_params(
+                block.clone(),
+                CircuitsParams {
+                    max_modexp,
+                    ..Default::default()
+                },
+            )
+            .new_circuit_input_builder();
+            let result = builder.handle_block(&block.eth_block, &block.geth_traces);
+            if refused {
+                assert!(
+                    matches!(result, Err(Error::PrecompileCapacityExceeded(_))),
+                    "{} is not refused with a capacity of {max_modexp}",
+                    test_call.name
+                );
+            } else {
+                assert!(result.is_ok(), "{} is refused", test_call.name);
+                assert_eq!(builder.block.precompile_events.modexp.len(), 2);
+            }
+        }
+    }
 }
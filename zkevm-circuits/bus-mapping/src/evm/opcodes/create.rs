@@ -1,27 +1,40 @@
 use crate::{
-    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+    },
     evm::Opcode,
-    operation::{AccountField, AccountOp, CallContextField, TxAccessListAccountOp},
+    operation::{
+        AccountField, AccountOp, CallContextField, MemoryOp, TxAccessListAccountOp,
+        TxCreatedAccountOp, RW,
+    },
     state_db::CodeDB,
     Error,
 };
-use eth_types::{evm_types::gas_utils::memory_expansion_gas_cost, GethExecStep, ToWord, Word};
+use eth_types::{Bytecode, GethExecStep, ToBigEndian, ToWord, Word};
+use ethers_core::utils::{keccak256, rlp};
 
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the `OpcodeId::CREATE` and `OpcodeId::CREATE2`.
+/// - CREATE: IS_CREATE2 = false, pops `value, offset, length`
+/// - CREATE2: IS_CREATE2 = true, pops `value, offset, length, salt`
+///
+/// Besides the successful case, this also handles the failure cases where the
+/// init code is never executed: depth, insufficient balance and contract
+/// address collision.
 #[derive(Debug, Copy, Clone)]
-pub struct DummyCreate<const IS_CREATE2: bool>;
+pub(crate) struct Create<const IS_CREATE2: bool>;
 
-impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
+impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
     fn gen_associated_ops(
         state: &mut CircuitInputStateRef,
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
-        // TODO: replace dummy create here
         let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
 
         let offset = geth_step.stack.nth_last(1)?.as_usize();
         let length = geth_step.stack.nth_last(2)?.as_usize();
 
-        let curr_memory_word_size = (state.call_ctx()?.memory.len() as u64) / 32;
         if length != 0 {
             state
                 .call_ctx_mut()?
@@ -30,7 +43,34 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
         }
         let next_memory_word_size = (state.call_ctx()?.memory.len() as u64) / 32;
 
-        let mut exec_step = state.new_step(geth_step)?;
+        // The callee call_id is the rw_counter of this step, so the call must
+        // be parsed before any operation is pushed.
+        let call = state.parse_call(geth_step)?;
+        let current_call = state.call()?.clone();
+        let tx_id = state.tx_ctx.id();
+
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            // NOTE: For `RwCounterEndOfReversion` we use the `0` value as a
+            // placeholder, and later set the proper value in
+            // `CircuitInputBuilder::set_value_ops_call_context_rwc_eor`
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
+            (
+                CallContextField::IsPersistent,
+                (current_call.is_persistent as u64).into(),
+            ),
+            (
+                CallContextField::IsStatic,
+                (current_call.is_static as u64).into(),
+            ),
+            (CallContextField::Depth, current_call.depth.into()),
+            (
+                CallContextField::CalleeAddress,
+                current_call.address.to_word(),
+            ),
+        ] {
+            state.call_context_read(&mut exec_step, current_call.call_id, field, value);
+        }
 
         let n_pop = if IS_CREATE2 { 4 } else { 3 };
         for i in 0..n_pop {
@@ -40,147 +80,370 @@ impl<const IS_CREATE2: bool> Opcode for DummyCreate<IS_CREATE2> {
                 geth_step.stack.nth_last(i)?,
             )?;
         }
-
-        let address = if IS_CREATE2 {
-            state.create2_address(&geth_steps[0])?
-        } else {
-            state.create_address()?
-        };
-
-        // TODO: rename this to initialization call?
-        let call = state.parse_call(geth_step)?;
         state.stack_write(
             &mut exec_step,
             geth_step.stack.nth_last_filled(n_pop - 1),
             if call.is_success {
-                address.to_word()
+                call.address.to_word()
             } else {
                 Word::zero()
             },
         )?;
 
-        let tx_id = state.tx_ctx.id();
-        let current_call = state.call()?.clone();
-
-        // Quote from [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929)
-        // > When a CREATE or CREATE2 opcode is called,
-        // > immediately (i.e. before checks are done to determine
-        // > whether or not the address is unclaimed)
-        // > add the address being created to accessed_addresses,
-        // > but gas costs of CREATE and CREATE2 are unchanged
-        let is_warm = state.sdb.check_account_in_access_list(&address);
-        state.push_op_reversible(
+        let (found, caller_account) = state.sdb.get_account(&call.caller_address);
+        if !found {
+            return Err(Error::AccountNotFound(call.caller_address));
+        }
+        let caller_balance = caller_account.balance;
+        let caller_nonce = caller_account.nonce;
+        state.account_read(
             &mut exec_step,
-            TxAccessListAccountOp {
-                tx_id: state.tx_ctx.id(),
-                address,
-                is_warm: true,
-                is_warm_prev: is_warm,
-            },
-        )?;
+            call.caller_address,
+            AccountField::Balance,
+            caller_balance,
+        );
 
-        // Increase caller's nonce
-        let nonce_prev = state.sdb.get_nonce(&call.caller_address);
-        state.push_op_reversible(
-            &mut exec_step,
-            AccountOp {
-                address: call.caller_address,
-                field: AccountField::Nonce,
-                value: (nonce_prev + 1).into(),
-                value_prev: nonce_prev.into(),
-            },
-        )?;
+        // Precheck is OK when depth is in range and caller balance is sufficient
+        let is_precheck_ok = geth_step.depth < 1025 && caller_balance >= call.value;
 
-        // Add callee into access list
-        let is_warm = state.sdb.check_account_in_access_list(&call.address);
-        state.push_op_reversible(
-            &mut exec_step,
-            TxAccessListAccountOp {
-                tx_id,
-                address: call.address,
-                is_warm: true,
-                is_warm_prev: is_warm,
-            },
-        )?;
+        let (callee_exists, is_address_collision) = if is_precheck_ok {
+            // Increase caller's nonce
+            state.push_op_reversible(
+                &mut exec_step,
+                AccountOp {
+                    address: call.caller_address,
+                    field: AccountField::Nonce,
+                    value: (caller_nonce + 1).into(),
+                    value_prev: caller_nonce.into(),
+                },
+            )?;
 
-        state.push_call(call.clone());
+            // Quote from [EIP-2929](https://eips.ethereum.org/EIPS/eip-2929)
+            // > When a CREATE or CREATE2 opcode is called,
+            // > immediately (i.e. before checks are done to determine
+            // > whether or not the address is unclaimed)
+            // > add the address being created to accessed_addresses,
+            // > but gas costs of CREATE and CREATE2 are unchanged
+            let is_warm = state.sdb.check_account_in_access_list(&call.address);
+            state.push_op_reversible(
+                &mut exec_step,
+                TxAccessListAccountOp {
+                    tx_id,
+                    address: call.address,
+                    is_warm: true,
+                    is_warm_prev: is_warm,
+                },
+            )?;
 
-        state.transfer(
-            &mut exec_step,
-            call.caller_address,
-            call.address,
-            true,
-            true,
-            call.value,
-        )?;
+            // Read the code hash and nonce of the contract address to detect
+            // an address collision.
+            let (callee_exists, callee_code_hash, callee_nonce) = {
+                let (_, callee_account) = state.sdb.get_account(&call.address);
+                if callee_account.is_empty() {
+                    (false, Word::zero(), Word::zero())
+                } else {
+                    (
+                        true,
+                        callee_account.code_hash.to_word(),
+                        callee_account.nonce.into(),
+                    )
+                }
+            };
+            state.account_read(
+                &mut exec_step,
+                call.address,
+                AccountField::CodeHash,
+                callee_code_hash,
+            );
+            state.account_read(
+                &mut exec_step,
+                call.address,
+                AccountField::Nonce,
+                callee_nonce,
+            );
+            let is_address_collision = !callee_nonce.is_zero()
+                || !(callee_code_hash.is_zero()
+                    || callee_code_hash == CodeDB::empty_code_hash().to_word());
 
-        // Increase callee's nonce
-        let nonce_prev = state.sdb.get_nonce(&call.address);
-        debug_assert!(nonce_prev == 0);
-        state.push_op_reversible(
-            &mut exec_step,
-            AccountOp {
-                address: call.address,
-                field: AccountField::Nonce,
-                value: 1.into(),
-                value_prev: 0.into(),
-            },
-        )?;
+            // Copy the init code from memory, binding it to its code hash.
+            if length > 0 {
+                let init_code = state.call_ctx()?.memory.0[offset..offset + length].to_vec();
+                handle_copy(
+                    state,
+                    &mut exec_step,
+                    current_call.call_id,
+                    offset,
+                    init_code,
+                )?;
+            }
+
+            // Keccak input used to derive the contract address.
+            state.block.sha3_inputs.push(if IS_CREATE2 {
+                let salt = geth_step.stack.nth_last(3)?;
+                [
+                    &[0xffu8][..],
+                    call.caller_address.as_bytes(),
+                    &salt.to_be_bytes()[..],
+                    call.code_hash.as_bytes(),
+                ]
+                .concat()
+            } else {
+                let mut stream = rlp::RlpStream::new();
+                stream.begin_list(2);
+                stream.append(&call.caller_address);
+                stream.append(&Word::from(caller_nonce));
+                stream.out().to_vec()
+            });
+            debug_assert_eq!(
+                &keccak256(state.block.sha3_inputs.last().unwrap())[12..],
+                call.address.as_bytes()
+            );
 
-        let memory_expansion_gas_cost =
-            memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size);
+            (callee_exists, is_address_collision)
+        } else {
+            (false, false)
+        };
 
-        // EIP-150: all but one 64th of the caller's gas is sent to the callee.
-        let caller_gas_left =
-            (geth_step.gas.0 - geth_step.gas_cost.0 - memory_expansion_gas_cost) / 64;
+        // Switch to callee's call context
+        state.push_call(call.clone());
 
         for (field, value) in [
+            (CallContextField::RwCounterEndOfReversion, 0.into()),
             (
-                CallContextField::ProgramCounter,
-                (geth_step.pc.0 + 1).into(),
-            ),
-            (
-                CallContextField::StackPointer,
-                geth_step.stack.nth_last_filled(n_pop - 1).0.into(),
-            ),
-            (CallContextField::GasLeft, caller_gas_left.into()),
-            (CallContextField::MemorySize, next_memory_word_size.into()),
-            (
-                CallContextField::ReversibleWriteCounter,
-                // +3 is because we do some transfers after pushing the call. can be just push the
-                // call later?
-                (exec_step.reversible_write_counter + 3).into(),
+                CallContextField::IsPersistent,
+                (call.is_persistent as u64).into(),
             ),
         ] {
-            state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+            state.call_context_write(&mut exec_step, call.call_id, field, value);
         }
 
-        for (field, value) in [
-            (CallContextField::CallerId, current_call.call_id.into()),
-            (CallContextField::IsSuccess, call.is_success.to_word()),
-            (CallContextField::IsPersistent, call.is_persistent.to_word()),
-            (CallContextField::TxId, state.tx_ctx.id().into()),
-            (
-                CallContextField::CallerAddress,
-                current_call.address.to_word(),
-            ),
-            (CallContextField::CalleeAddress, call.address.to_word()),
-            (
-                CallContextField::RwCounterEndOfReversion,
-                call.rw_counter_end_of_reversion.to_word(),
-            ),
-            (CallContextField::IsPersistent, call.is_persistent.to_word()),
-        ] {
-            state.call_context_write(&mut exec_step, call.call_id, field, value);
+        if is_precheck_ok && !is_address_collision {
+            // Create the account if it doesn't exist yet and transfer the value.
+            state.transfer(
+                &mut exec_step,
+                call.caller_address,
+                call.address,
+                callee_exists,
+                !callee_exists,
+                call.value,
+            )?;
+
+            // Increase callee's nonce
+            state.push_op_reversible(
+                &mut exec_step,
+                AccountOp {
+                    address: call.address,
+                    field: AccountField::Nonce,
+                    value: 1.into(),
+                    value_prev: 0.into(),
+                },
+            )?;
+
+            // Mark the callee as created in this transaction for EIP-6780.
+            state.push_op_reversible(
+                &mut exec_step,
+                TxCreatedAccountOp {
+                    tx_id,
+                    address: call.address,
+                    is_created: true,
+                    is_created_prev: state.sdb.is_created_account(&call.address),
+                },
+            )?;
         }
 
-        if call.code_hash == CodeDB::empty_code_hash() {
-            // 1. Create with empty initcode.
-            state.handle_return(&mut exec_step, geth_steps, false)?;
+        if is_precheck_ok && !is_address_collision && length > 0 {
+            // 1. Create with non-empty init code, enter the callee's context.
+            let gas_cost = geth_step.gas_cost.0;
+            let gas_remaining = geth_step.gas.0 - gas_cost;
+            let caller_gas_left = gas_remaining / 64;
+
+            for (field, value) in [
+                (
+                    CallContextField::ProgramCounter,
+                    (geth_step.pc.0 + 1).into(),
+                ),
+                (
+                    CallContextField::StackPointer,
+                    geth_step.stack.nth_last_filled(n_pop - 1).0.into(),
+                ),
+                (CallContextField::GasLeft, caller_gas_left.into()),
+                (CallContextField::MemorySize, next_memory_word_size.into()),
+                (
+                    CallContextField::ReversibleWriteCounter,
+                    (exec_step.reversible_write_counter + 2).into(),
+                ),
+            ] {
+                state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+            }
+
+            for (field, value) in [
+                (CallContextField::CallerId, current_call.call_id.into()),
+                (CallContextField::TxId, tx_id.into()),
+                (CallContextField::Depth, call.depth.into()),
+                (
+                    CallContextField::CallerAddress,
+                    call.caller_address.to_word(),
+                ),
+                (CallContextField::CalleeAddress, call.address.to_word()),
+                (CallContextField::CallDataOffset, 0.into()),
+                (CallContextField::CallDataLength, 0.into()),
+                (CallContextField::Value, call.value),
+                (CallContextField::IsSuccess, (call.is_success as u64).into()),
+                (CallContextField::IsStatic, 0.into()),
+                (CallContextField::LastCalleeId, 0.into()),
+                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                (CallContextField::LastCalleeReturnDataLength, 0.into()),
+                (CallContextField::IsRoot, 0.into()),
+                (CallContextField::IsCreate, 1.into()),
+                (CallContextField::CodeHash, call.code_hash.to_word()),
+            ] {
+                state.call_context_write(&mut exec_step, call.call_id, field, value);
+            }
+
             Ok(vec![exec_step])
         } else {
-            // 2. Create with non-empty initcode.
+            // 2. Create with empty init code, or failure (depth, insufficient
+            // balance or address collision) before running the init code.
+            for (field, value) in [
+                (CallContextField::LastCalleeId, call.call_id.into()),
+                (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+                (CallContextField::LastCalleeReturnDataLength, 0.into()),
+            ] {
+                state.call_context_write(&mut exec_step, current_call.call_id, field, value);
+            }
+            state.handle_return(&mut exec_step, geth_steps, false)?;
             Ok(vec![exec_step])
         }
     }
 }
+
+/// Push the memory reads and the copy event of the init code, which is copied
+/// from the caller's memory into the bytecode identified by its code hash.
+fn handle_copy(
+    state: &mut CircuitInputStateRef,
+    step: &mut ExecStep,
+    caller_id: usize,
+    offset: usize,
+    init_code: Vec<u8>,
+) -> Result<(), Error> {
+    let code_hash = CodeDB::hash(&init_code);
+    let length = init_code.len();
+    let bytes: Vec<_> = Bytecode::from(init_code)
+        .code
+        .iter()
+        .map(|element| (element.value, element.is_code))
+        .collect();
+
+    let rw_counter_start = state.block_ctx.rwc;
+    for (i, (byte, _)) in bytes.iter().enumerate() {
+        state.push_op(
+            step,
+            RW::READ,
+            MemoryOp::new(caller_id, (offset + i).into(), *byte),
+        );
+    }
+
+    state.push_copy(
+        step,
+        CopyEvent {
+            rw_counter_start,
+            src_type: CopyDataType::Memory,
+            src_id: NumberOrHash::Number(caller_id),
+            src_addr: offset.try_into().unwrap(),
+            src_addr_end: (offset + length).try_into().unwrap(),
+            dst_type: CopyDataType::Bytecode,
+            dst_id: NumberOrHash::Hash(code_hash),
+            dst_addr: 0,
+            log_id: None,
+            bytes,
+        },
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{AccountField, AccountOp, Target, RW},
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, word, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(code: eth_types::Bytecode, opcode: OpcodeId) {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(opcode))
+            .unwrap();
+
+        // The caller nonce is increased by the create step.
+        let caller_nonce_op = step
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.0 == Target::Account)
+            .map(|op_ref| &builder.block.container.account[op_ref.1])
+            .find(|op| op.op().field == AccountField::Nonce)
+            .unwrap();
+        assert_eq!(caller_nonce_op.rw(), RW::WRITE);
+        assert_eq!(
+            caller_nonce_op.op(),
+            &AccountOp {
+                address: tx.calls()[0].address,
+                field: AccountField::Nonce,
+                value: Word::from(1),
+                value_prev: Word::zero(),
+            }
+        );
+    }
+
+    #[test]
+    fn create_empty_init_code() {
+        test_ok(
+            bytecode! {
+                PUSH1(0)
+                PUSH1(0)
+                PUSH1(0)
+                CREATE
+                STOP
+            },
+            OpcodeId::CREATE,
+        );
+    }
+
+    #[test]
+    fn create2_non_empty_init_code() {
+        test_ok(
+            bytecode! {
+                // init code: PUSH1(0) PUSH1(0) RETURN
+                PUSH5(word!("0x60006000f3"))
+                PUSH1(0)
+                MSTORE
+                PUSH1(0x1234)
+                PUSH1(5)
+                PUSH1(27)
+                PUSH1(0)
+                CREATE2
+                STOP
+            },
+            OpcodeId::CREATE2,
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::Opcode,
+    Error,
+};
+use eth_types::{evm_types::OpcodeId, GethExecStep};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::CodeStoreOutOfGas`](crate::error::ExecError::CodeStoreOutOfGas)
+/// and
+/// [`ExecError::MaxCodeSizeExceeded`](crate::error::ExecError::MaxCodeSizeExceeded)
+/// errors of a RETURN in a creation context.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorCodeStore;
+
+impl Opcode for ErrorCodeStore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = geth_steps.get(1);
+        exec_step.error = state.get_step_err(geth_step, next_step)?;
+
+        debug_assert_eq!(geth_step.op, OpcodeId::RETURN);
+        debug_assert!(state.call()?.is_create());
+        debug_assert!(matches!(
+            exec_step.error,
+            Some(ExecError::CodeStoreOutOfGas | ExecError::MaxCodeSizeExceeded)
+        ));
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::Opcode,
+    Error,
+};
+use eth_types::{evm_types::OpcodeId, GethExecStep};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`ExecError::InvalidCreationCode`](crate::error::ExecError::InvalidCreationCode),
+/// i.e. a RETURN in a creation context whose code starts with `0xef` (EIP-3541).
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorCreationCode;
+
+impl Opcode for ErrorCreationCode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = geth_steps.get(1);
+        exec_step.error = state.get_step_err(geth_step, next_step)?;
+
+        debug_assert_eq!(geth_step.op, OpcodeId::RETURN);
+        debug_assert!(state.call()?.is_create());
+        debug_assert_eq!(exec_step.error, Some(ExecError::InvalidCreationCode));
+
+        let offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(0), offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(1), length)?;
+
+        // Read the first byte of the code to be deposited.
+        let byte = state.call_ctx()?.memory.0[offset.as_usize()];
+        state.memory_read(&mut exec_step, offset.as_usize().into(), byte)?;
+
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,88 @@
+use super::{Opcode, OpcodeId};
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OogError::SelfDestruct`](crate::error::OogError::SelfDestruct).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OOGSelfDestruct;
+
+impl Opcode for OOGSelfDestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        debug_assert_eq!(geth_step.op, OpcodeId::SELFDESTRUCT);
+
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::SelfDestruct));
+
+        let call_id = state.call()?.call_id;
+        let callee_address = state.call()?.address;
+        let tx_id = state.tx_ctx.id();
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            tx_id.into(),
+        );
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::CalleeAddress,
+            callee_address.to_word(),
+        );
+
+        let beneficiary = geth_step.stack.last()?;
+        state.stack_read(&mut exec_step, geth_step.stack.last_filled(), beneficiary)?;
+        let beneficiary = beneficiary.to_address();
+
+        let is_warm = state.sdb.check_account_in_access_list(&beneficiary);
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id,
+                address: beneficiary,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        );
+
+        // The beneficiary code hash and the callee balance decide whether the
+        // new account cost applies.
+        let (_, beneficiary_account) = state.sdb.get_account(&beneficiary);
+        let beneficiary_code_hash = if beneficiary_account.is_empty() {
+            Word::zero()
+        } else {
+            beneficiary_account.code_hash.to_word()
+        };
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            beneficiary_code_hash,
+        );
+
+        let (_, callee_account) = state.sdb.get_account(&callee_address);
+        let balance = callee_account.balance;
+        state.account_read(
+            &mut exec_step,
+            callee_address,
+            AccountField::Balance,
+            balance,
+        );
+
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::{ExecError, OogError},
+    evm::Opcode,
+    Error,
+};
+use eth_types::{evm_types::OpcodeId, GethExecStep};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OogError::StaticMemoryExpansion`](crate::error::OogError::StaticMemoryExpansion).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OOGStaticMemory;
+
+impl Opcode for OOGStaticMemory {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        debug_assert!(
+            [OpcodeId::MLOAD, OpcodeId::MSTORE, OpcodeId::MSTORE8].contains(&geth_step.op)
+        );
+
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::OutOfGas(OogError::StaticMemoryExpansion));
+
+        // Only the memory address is needed to compute the memory expansion.
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            geth_step.stack.last()?,
+        )?;
+
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -0,0 +1,199 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+    },
+    Error,
+};
+use eth_types::{evm_types::MemoryAddress, GethExecStep, Word};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Mcopy;
+
+impl Opcode for Mcopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_steps = vec![gen_mcopy_step(state, geth_step)?];
+
+        let length = geth_step.stack.nth_last(2)?;
+        if !length.is_zero() {
+            let copy_event = gen_copy_event(state, geth_step)?;
+            state.push_copy(&mut exec_steps[0], copy_event);
+        }
+        Ok(exec_steps)
+    }
+}
+
+fn gen_mcopy_step(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+) -> Result<ExecStep, Error> {
+    let mut exec_step = state.new_step(geth_step)?;
+    let dst_offset = geth_step.stack.nth_last(0)?;
+    let src_offset = geth_step.stack.nth_last(1)?;
+    let length = geth_step.stack.nth_last(2)?;
+
+    state.stack_read(
+        &mut exec_step,
+        geth_step.stack.nth_last_filled(0),
+        dst_offset,
+    )?;
+    state.stack_read(
+        &mut exec_step,
+        geth_step.stack.nth_last_filled(1),
+        src_offset,
+    )?;
+    state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
+
+    Ok(exec_step)
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+) -> Result<CopyEvent, Error> {
+    let rw_counter_start = state.block_ctx.rwc;
+
+    // Both offsets are within range since the length is non-zero.
+    let dst_addr = geth_step.stack.nth_last(0)?.as_u64();
+    let src_addr = geth_step.stack.nth_last(1)?.as_u64();
+    let length = geth_step.stack.nth_last(2)?.as_u64();
+
+    // The memory is expanded to cover both the source and the destination.
+    let memory = &mut state.call_ctx_mut()?.memory;
+    memory.extend_at_least((src_addr.max(dst_addr) + length) as usize);
+    let bytes = memory.read_chunk(MemoryAddress::from(src_addr), MemoryAddress::from(length));
+    memory.copy_from(dst_addr.into(), Word::zero(), length.into(), &bytes);
+
+    // Read all the bytes before writing any of them, so that overlapping
+    // ranges are copied as if through an intermediate buffer.
+    let mut exec_step = state.new_step(geth_step)?;
+    for (idx, byte) in bytes.iter().enumerate() {
+        state.memory_read(&mut exec_step, (src_addr + idx as u64).into(), *byte)?;
+    }
+    for (idx, byte) in bytes.iter().enumerate() {
+        state.memory_write(&mut exec_step, (dst_addr + idx as u64).into(), *byte)?;
+    }
+
+    let call_id = state.call()?.call_id;
+    Ok(CopyEvent {
+        src_type: CopyDataType::MemoryCopy,
+        src_id: NumberOrHash::Number(call_id),
+        src_addr,
+        src_addr_end: src_addr + length,
+        dst_type: CopyDataType::MemoryCopy,
+        dst_id: NumberOrHash::Number(call_id),
+        dst_addr,
+        log_id: None,
+        rw_counter_start,
+        bytes: bytes.into_iter().map(|byte| (byte, false)).collect(),
+    })
+}
+
+#[cfg(test)]
+mod mcopy_tests {
+    use crate::{
+        circuit_input_builder::{CopyDataType, ExecState, NumberOrHash},
+        mock::BlockData,
+        operation::{MemoryOp, StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn mcopy_opcode_overlapping() {
+        let code = bytecode! {
+            PUSH32(Word::from(0x0102030405u64))
+            PUSH1(0x00)
+            MSTORE
+            // Copy the last 5 bytes of the word one byte forward.
+            PUSH1(0x05)
+            PUSH1(0x1b)
+            PUSH1(0x1c)
+            MCOPY
+            STOP
+        };
+
+        // Get the execution steps from the external tracer
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::MCOPY))
+            .unwrap();
+
+        assert_eq!(
+            [0, 1, 2]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|operation| (operation.rw(), operation.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1021), Word::from(0x1c)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1022), Word::from(0x1b)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(1, StackAddress::from(1023), Word::from(0x05)),
+                ),
+            ]
+        );
+
+        // The bytes are read before the overlapping writes.
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let copy_start = builder.block.container.memory.len() - 2 * bytes.len();
+        assert_eq!(
+            builder.block.container.memory[copy_start..]
+                .iter()
+                .map(|operation| (operation.rw(), operation.op().clone()))
+                .collect::<Vec<_>>(),
+            bytes
+                .iter()
+                .enumerate()
+                .map(|(idx, byte)| (RW::READ, MemoryOp::new(1, (0x1b + idx).into(), *byte)))
+                .chain(bytes.iter().enumerate().map(|(idx, byte)| {
+                    (RW::WRITE, MemoryOp::new(1, (0x1c + idx).into(), *byte))
+                }))
+                .collect::<Vec<_>>(),
+        );
+
+        let copy_event = &builder.block.copy_events[0];
+        assert_eq!(copy_event.src_type, CopyDataType::MemoryCopy);
+        assert_eq!(copy_event.src_id, NumberOrHash::Number(1));
+        assert_eq!(copy_event.dst_id, NumberOrHash::Number(1));
+        assert_eq!(
+            copy_event
+                .bytes
+                .iter()
+                .map(|(byte, _)| *byte)
+                .collect::<Vec<_>>(),
+            bytes
+        );
+        assert_eq!(copy_event.rw_counter_delta(), 2 * bytes.len() as u64);
+    }
+}
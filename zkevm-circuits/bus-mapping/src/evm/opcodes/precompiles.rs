@@ -1,24 +1,187 @@
-use eth_types::{GethExecStep, ToWord, Word};
+use eth_types::{
+    evm_types::{Gas, GasCost},
+    GethExecStep, ToWord, Word,
+};
+use std::{cmp::min, iter};
 
 use crate::{
-    circuit_input_builder::{Call, CircuitInputStateRef, ExecState, ExecStep},
-    operation::CallContextField,
-    precompile::PrecompileCalls,
+    circuit_input_builder::{
+        Call, CircuitInputStateRef, CopyDataType, CopyEvent, ExecState, ExecStep, NumberOrHash,
+    },
+    operation::{CallContextField, MemoryOp, RW},
+    precompile::{
+        blake2f_num_rows, execute_precompiled, modexp_lengths, EcAddOp, EcMulOp, EcRecoverEvent,
+        ModExpOp, PrecompileCalls, BLAKE2F_ROUNDS_LEN, BLAKE2F_ROWS_PER_BLOCK, ECRECOVER_INPUT_LEN,
+        EC_ADD_INPUT_LEN, EC_MUL_INPUT_LEN, MODEXP_HEADER_LEN,
+    },
     Error,
 };
 
+/// Generate the step of a call to a precompiled contract, which runs in the
+/// context of the callee and returns to the caller in the same step.
+/// `caller_state` are the fields of the caller's context saved by the call
+/// step, which are read back when restoring it.
 pub fn gen_associated_ops(
     state: &mut CircuitInputStateRef,
-    geth_step: GethExecStep,
+    geth_steps: &[GethExecStep],
     call: Call,
     precompile: PrecompileCalls,
+    gas_left: u64,
+    caller_state: &[(CallContextField, Word)],
 ) -> Result<ExecStep, Error> {
     assert_eq!(call.code_address(), Some(precompile.into()));
-    let mut exec_step = state.new_step(&geth_step)?;
+    // The circuits have no circuit for the pairing check of ecPairing, which
+    // doesn't fit in the rows of the super circuit, and the ModExp circuit
+    // only works on operands of up to 32 bytes.  Refuse the witness instead
+    // of producing a step that can't be proven.  The same goes for the calls
+    // to the ECC precompiles that the ECC circuit doesn't support below.
+    let input = state.call_ctx()?.call_data.clone();
+    let modexp_lengths = modexp_lengths(&input);
+    if precompile == PrecompileCalls::Bn128Pairing
+        || (precompile == PrecompileCalls::Modexp && modexp_lengths.is_none())
+    {
+        return Err(Error::UnsupportedPrecompile(precompile));
+    }
+    let mut exec_step = state.new_step(&geth_steps[1])?;
     exec_step.exec_state = ExecState::Precompile(precompile);
+    // The precompile starts with a fresh context.
+    exec_step.pc = 0.into();
+    exec_step.stack_size = 0;
+    exec_step.memory_size = 0;
+    exec_step.gas_left = Gas(gas_left);
+
+    let (output, gas_cost) = execute_precompiled(&precompile.into(), &input, gas_left);
+    // A failing precompile consumes all the gas and returns nothing.
+    let (output, gas_cost) = if call.is_success {
+        (output, gas_cost)
+    } else {
+        (vec![], gas_left)
+    };
+    exec_step.gas_cost = GasCost(gas_cost);
 
     common_call_ctx_reads(state, &mut exec_step, &call);
 
+    if !call.is_success {
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::RwCounterEndOfReversion,
+            call.rw_counter_end_of_reversion.into(),
+        );
+    }
+
+    // The gas cost of Blake2F depends on the number of rounds, which is read
+    // even when the call runs out of gas.
+    if precompile == PrecompileCalls::Blake2F {
+        copy_input_to_rlc(state, &mut exec_step, &call, &input, BLAKE2F_ROUNDS_LEN);
+    }
+    // The gas cost of modexp depends on the lengths and the exponent, the
+    // whole input is read even when the call runs out of gas.
+    if let (PrecompileCalls::Modexp, Some(lengths)) = (precompile, modexp_lengths) {
+        let length = MODEXP_HEADER_LEN + lengths.iter().sum::<usize>();
+        copy_input_to_rlc(state, &mut exec_step, &call, &input, length);
+    }
+
+    if call.is_success {
+        match precompile {
+            PrecompileCalls::Identity => {
+                copy_input_to_memory(state, &mut exec_step, &call, &input);
+            }
+            PrecompileCalls::ECRecover => {
+                copy_input_to_rlc(state, &mut exec_step, &call, &input, ECRECOVER_INPUT_LEN);
+                let mut padded_input = input.clone();
+                padded_input.resize(ECRECOVER_INPUT_LEN, 0);
+                if let Some(event) = EcRecoverEvent::new(&padded_input, &output) {
+                    state.block.precompile_events.ecrecover.push(event);
+                }
+            }
+            PrecompileCalls::Sha256 => {
+                copy_input_to_rlc(state, &mut exec_step, &call, &input, input.len());
+                state.block.precompile_events.sha256.push(input.clone());
+            }
+            PrecompileCalls::Ripemd160 => {
+                copy_input_to_rlc(state, &mut exec_step, &call, &input, input.len());
+                state.block.precompile_events.ripemd160.push(input.clone());
+            }
+            PrecompileCalls::Bn128Add => {
+                copy_input_to_rlc(state, &mut exec_step, &call, &input, EC_ADD_INPUT_LEN);
+                let mut padded_input = input.clone();
+                padded_input.resize(EC_ADD_INPUT_LEN, 0);
+                let op = EcAddOp::new(&padded_input, &output);
+                if !op.is_supported() {
+                    return Err(Error::UnsupportedPrecompile(precompile));
+                }
+                state.block.precompile_events.ec_add.push(op);
+            }
+            PrecompileCalls::Bn128Mul => {
+                copy_input_to_rlc(state, &mut exec_step, &call, &input, EC_MUL_INPUT_LEN);
+                let mut padded_input = input.clone();
+                padded_input.resize(EC_MUL_INPUT_LEN, 0);
+                let op = EcMulOp::new(&padded_input, &output);
+                if !op.is_supported() {
+                    return Err(Error::UnsupportedPrecompile(precompile));
+                }
+                state.block.precompile_events.ec_mul.push(op);
+            }
+            PrecompileCalls::Modexp => {
+                // The result is zero when the modulus is zero, which needs no
+                // proof by the ModExp circuit.
+                let op = ModExpOp::new(&input, modexp_lengths.unwrap(), &output);
+                if !op.modulus.is_zero() {
+                    let max_ops = state.block.circuits_params.max_modexp;
+                    if max_ops > 0 && state.block.precompile_events.modexp.len() >= max_ops {
+                        return Err(Error::PrecompileCapacityExceeded(precompile));
+                    }
+                    state.block.precompile_events.modexp.push(op);
+                }
+            }
+            PrecompileCalls::Blake2F => {
+                // The number of rounds is only bounded by the gas, refuse the
+                // calls that don't fit in the rows of the Blake2F circuit
+                // before they are compressed in there.  A block of rows is
+                // left for the unusable rows at the end of the circuit.
+                let max_rows = state.block.circuits_params.max_blake2f_rows;
+                let num_rows: usize = iter::once(&input)
+                    .chain(state.block.precompile_events.blake2f.iter())
+                    .map(|input| blake2f_num_rows(input))
+                    .sum();
+                if max_rows > 0 && num_rows + BLAKE2F_ROWS_PER_BLOCK > max_rows {
+                    return Err(Error::PrecompileCapacityExceeded(precompile));
+                }
+                copy_input_to_rlc(state, &mut exec_step, &call, &input, input.len());
+                state.block.precompile_events.blake2f.push(input.clone());
+            }
+            _ => {
+                copy_input_to_rlc(state, &mut exec_step, &call, &input, input.len());
+            }
+        }
+
+        // The output of the identity precompile is already in its memory.
+        if precompile != PrecompileCalls::Identity {
+            for (i, byte) in output.iter().enumerate() {
+                state.memory_write(&mut exec_step, i.into(), *byte)?;
+            }
+        }
+
+        copy_output_to_caller(state, &mut exec_step, &call, &output);
+    }
+
+    restore_caller_context(state, &mut exec_step, &call, caller_state, output.len())?;
+    state.handle_return(&mut exec_step, geth_steps, false)?;
+
+    // The return data of the precompile is its output.
+    let caller_ctx = state.call_ctx_mut()?;
+    let length = min(output.len(), call.return_data_length as usize);
+    if length > 0 {
+        let offset = call.return_data_offset as usize;
+        caller_ctx.memory.extend_at_least(offset + length);
+        caller_ctx.memory.0[offset..offset + length].copy_from_slice(&output[..length]);
+    }
+    caller_ctx.return_data = output.clone();
+    let caller = state.call_mut()?;
+    caller.last_callee_return_data_offset = 0;
+    caller.last_callee_return_data_length = output.len() as u64;
+
     Ok(exec_step)
 }
 
@@ -53,3 +216,181 @@ fn common_call_ctx_reads(state: &mut CircuitInputStateRef, exec_step: &mut ExecS
         state.call_context_read(exec_step, call.call_id, field, value);
     }
 }
+
+/// Copy the call data from the caller's memory to the memory of the
+/// precompile.
+fn copy_input_to_memory(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    input: &[u8],
+) {
+    if input.is_empty() {
+        return;
+    }
+    let rw_counter_start = state.block_ctx.rwc;
+    for (i, byte) in input.iter().enumerate() {
+        state.push_op(
+            exec_step,
+            RW::READ,
+            MemoryOp::new(
+                call.caller_id,
+                (call.call_data_offset + i as u64).into(),
+                *byte,
+            ),
+        );
+        state.push_op(
+            exec_step,
+            RW::WRITE,
+            MemoryOp::new(call.call_id, i.into(), *byte),
+        );
+    }
+    state.push_copy(
+        exec_step,
+        CopyEvent {
+            src_id: NumberOrHash::Number(call.caller_id),
+            src_type: CopyDataType::Memory,
+            src_addr: call.call_data_offset,
+            src_addr_end: call.call_data_offset + call.call_data_length,
+            dst_id: NumberOrHash::Number(call.call_id),
+            dst_type: CopyDataType::Memory,
+            dst_addr: 0,
+            log_id: None,
+            rw_counter_start,
+            bytes: input.iter().map(|byte| (*byte, false)).collect(),
+        },
+    );
+}
+
+/// Accumulate `length` bytes of the call data, padded with zeros, from the
+/// caller's memory into an RLC.
+fn copy_input_to_rlc(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    input: &[u8],
+    length: usize,
+) {
+    if length == 0 {
+        return;
+    }
+    let rw_counter_start = state.block_ctx.rwc;
+    for (i, byte) in input.iter().take(length).enumerate() {
+        state.push_op(
+            exec_step,
+            RW::READ,
+            MemoryOp::new(
+                call.caller_id,
+                (call.call_data_offset + i as u64).into(),
+                *byte,
+            ),
+        );
+    }
+    let mut bytes: Vec<_> = input.iter().map(|byte| (*byte, false)).collect();
+    bytes.resize(length, (0, false));
+    state.push_copy(
+        exec_step,
+        CopyEvent {
+            src_id: NumberOrHash::Number(call.caller_id),
+            src_type: CopyDataType::Memory,
+            src_addr: call.call_data_offset,
+            src_addr_end: call.call_data_offset + call.call_data_length,
+            dst_id: NumberOrHash::Number(call.call_id),
+            dst_type: CopyDataType::RlcAcc,
+            dst_addr: 0,
+            log_id: None,
+            rw_counter_start,
+            bytes,
+        },
+    );
+}
+
+/// Copy the part of the output that fits in the return data area from the
+/// memory of the precompile to the caller's memory.
+fn copy_output_to_caller(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    output: &[u8],
+) {
+    let length = min(output.len(), call.return_data_length as usize);
+    if length == 0 {
+        return;
+    }
+    let rw_counter_start = state.block_ctx.rwc;
+    for (i, byte) in output.iter().take(length).enumerate() {
+        state.push_op(
+            exec_step,
+            RW::READ,
+            MemoryOp::new(call.call_id, i.into(), *byte),
+        );
+        state.push_op(
+            exec_step,
+            RW::WRITE,
+            MemoryOp::new(
+                call.caller_id,
+                (call.return_data_offset + i as u64).into(),
+                *byte,
+            ),
+        );
+    }
+    state.push_copy(
+        exec_step,
+        CopyEvent {
+            src_id: NumberOrHash::Number(call.call_id),
+            src_type: CopyDataType::Memory,
+            src_addr: 0,
+            src_addr_end: length as u64,
+            dst_id: NumberOrHash::Number(call.caller_id),
+            dst_type: CopyDataType::Memory,
+            dst_addr: call.return_data_offset,
+            log_id: None,
+            rw_counter_start,
+            bytes: output[..length].iter().map(|byte| (*byte, false)).collect(),
+        },
+    );
+}
+
+/// Read back the caller's context saved by the call step and set the caller's
+/// last callee to the precompile.
+fn restore_caller_context(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    call: &Call,
+    caller_state: &[(CallContextField, Word)],
+    return_data_length: usize,
+) -> Result<(), Error> {
+    let caller = state.caller()?.clone();
+    state.call_context_read(
+        exec_step,
+        call.call_id,
+        CallContextField::CallerId,
+        caller.call_id.into(),
+    );
+    for (field, value) in [
+        (CallContextField::IsRoot, (caller.is_root as u64).into()),
+        (
+            CallContextField::IsCreate,
+            (caller.is_create() as u64).into(),
+        ),
+        (CallContextField::CodeHash, caller.code_hash.to_word()),
+    ]
+    .into_iter()
+    .chain(caller_state.iter().cloned())
+    {
+        state.call_context_read(exec_step, caller.call_id, field, value);
+    }
+
+    for (field, value) in [
+        (CallContextField::LastCalleeId, call.call_id.into()),
+        (CallContextField::LastCalleeReturnDataOffset, 0.into()),
+        (
+            CallContextField::LastCalleeReturnDataLength,
+            return_data_length.into(),
+        ),
+    ] {
+        state.call_context_write(exec_step, caller.call_id, field, value);
+    }
+
+    Ok(())
+}
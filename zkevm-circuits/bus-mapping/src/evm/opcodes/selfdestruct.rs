@@ -0,0 +1,256 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{
+        AccountField, AccountOp, CallContextField, TxAccessListAccountOp, TxCreatedAccountOp, RW,
+    },
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+///
+/// The whole balance of the account is sent to the beneficiary.  Following
+/// EIP-6780, the account is only deleted when it was created in the same
+/// transaction, in which case the balance left to the account (when the
+/// beneficiary is the account itself) is burnt.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Selfdestruct;
+
+impl Opcode for Selfdestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let call = state.call()?.clone();
+        let tx_id = state.tx_ctx.id();
+        let sender = call.address;
+        let beneficiary = geth_step.stack.last()?;
+        let receiver = beneficiary.to_address();
+
+        state.stack_read(&mut exec_step, geth_step.stack.last_filled(), beneficiary)?;
+
+        for (field, value) in [
+            (CallContextField::TxId, tx_id.into()),
+            (CallContextField::IsSuccess, 1.into()),
+            (CallContextField::IsStatic, (call.is_static as u64).into()),
+            (
+                CallContextField::RwCounterEndOfReversion,
+                call.rw_counter_end_of_reversion.into(),
+            ),
+            (
+                CallContextField::IsPersistent,
+                (call.is_persistent as u64).into(),
+            ),
+            (CallContextField::CalleeAddress, sender.to_word()),
+        ] {
+            state.call_context_read(&mut exec_step, call.call_id, field, value);
+        }
+
+        let is_warm = state.sdb.check_account_in_access_list(&receiver);
+        state.push_op_reversible(
+            &mut exec_step,
+            TxAccessListAccountOp {
+                tx_id,
+                address: receiver,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+
+        // For non-existing accounts the code_hash is 0 in the rw_table.
+        let (_, receiver_account) = state.sdb.get_account(&receiver);
+        let receiver_code_hash = if receiver_account.is_empty() {
+            Word::zero()
+        } else {
+            receiver_account.code_hash.to_word()
+        };
+        state.account_read(
+            &mut exec_step,
+            receiver,
+            AccountField::CodeHash,
+            receiver_code_hash,
+        );
+
+        let (_, sender_account) = state.sdb.get_account(&sender);
+        let value = sender_account.balance;
+        state.account_read(&mut exec_step, sender, AccountField::Balance, value);
+
+        let is_created = state.sdb.is_created_account(&sender);
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TxCreatedAccountOp {
+                tx_id,
+                address: sender,
+                is_created,
+                is_created_prev: is_created,
+            },
+        );
+
+        state.transfer(
+            &mut exec_step,
+            sender,
+            receiver,
+            !receiver_code_hash.is_zero(),
+            false,
+            value,
+        )?;
+
+        // Delete the account created in this transaction.
+        if is_created {
+            let (_, sender_account) = state.sdb.get_account(&sender);
+            for (field, value_prev) in [
+                (AccountField::Balance, sender_account.balance),
+                (AccountField::Nonce, sender_account.nonce.into()),
+                (AccountField::CodeHash, sender_account.code_hash.to_word()),
+            ] {
+                state.push_op_reversible(
+                    &mut exec_step,
+                    AccountOp {
+                        address: sender,
+                        field,
+                        value: Word::zero(),
+                        value_prev,
+                    },
+                )?;
+            }
+
+            if call.is_persistent {
+                state.sdb.destruct_account(sender);
+            }
+        }
+
+        state.handle_return(&mut exec_step, geth_steps, !call.is_root)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod selfdestruct_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState, mock::BlockData, operation::Target, state_db::CodeDB,
+    };
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, Bytecode, Word};
+    use mock::{
+        eth,
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn selfdestruct_account_ops(code: Bytecode, is_create: bool) -> Vec<AccountOp> {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code.clone());
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).value(eth(1));
+                if is_create {
+                    txs[0].input(code.into());
+                } else {
+                    txs[0].to(accs[0].address);
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::SELFDESTRUCT))
+            .unwrap();
+
+        step.bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.target() == Target::Account)
+            .map(|op_ref| {
+                builder.block.container.account[op_ref.as_usize()]
+                    .op()
+                    .clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn selfdestruct_existing_account_keeps_account() {
+        let beneficiary = address!("0x0000000000000000000000000000000000fe1234");
+        let code = bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        };
+
+        // The balance is sent to the new beneficiary, and the account isn't
+        // deleted.
+        let balance = eth(11);
+        assert_eq!(
+            selfdestruct_account_ops(code, false),
+            vec![
+                AccountOp::new(
+                    beneficiary,
+                    AccountField::CodeHash,
+                    Word::zero(),
+                    Word::zero()
+                ),
+                AccountOp::new(MOCK_ACCOUNTS[0], AccountField::Balance, balance, balance),
+                AccountOp::new(
+                    beneficiary,
+                    AccountField::CodeHash,
+                    CodeDB::empty_code_hash().to_word(),
+                    Word::zero(),
+                ),
+                AccountOp::new(
+                    MOCK_ACCOUNTS[0],
+                    AccountField::Balance,
+                    Word::zero(),
+                    balance
+                ),
+                AccountOp::new(beneficiary, AccountField::Balance, balance, Word::zero()),
+            ]
+        );
+    }
+
+    #[test]
+    fn selfdestruct_created_account_deletes_account() {
+        // Send the balance to the account itself, which burns it.
+        let code = bytecode! {
+            ADDRESS
+            SELFDESTRUCT
+        };
+
+        let ops = selfdestruct_account_ops(code, true);
+        let contract = ops[0].address;
+        let balance = eth(1);
+        assert_eq!(
+            ops[ops.len() - 3..],
+            [
+                AccountOp::new(contract, AccountField::Balance, Word::zero(), balance),
+                AccountOp::new(contract, AccountField::Nonce, Word::zero(), Word::one()),
+                AccountOp::new(
+                    contract,
+                    AccountField::CodeHash,
+                    Word::zero(),
+                    CodeDB::empty_code_hash().to_word(),
+                ),
+            ]
+        );
+    }
+}
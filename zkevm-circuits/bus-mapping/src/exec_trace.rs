@@ -25,6 +25,8 @@ impl fmt::Debug for OperationRef {
                 Target::CallContext => "CallContext",
                 Target::TxReceipt => "TxReceipt",
                 Target::TxLog => "TxLog",
+                Target::TransientStorage => "TransientStorage",
+                Target::TxCreatedAccount => "TxCreatedAccount",
             },
             self.1
         ))
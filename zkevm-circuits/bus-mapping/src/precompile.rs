@@ -1,6 +1,6 @@
 //! precompile helpers
 
-use eth_types::{evm_types::GasCost, Address};
+use eth_types::{evm_types::GasCost, sign_types::SignData, Address, ToBigEndian, Word};
 use revm_precompile::{Precompile, Precompiles};
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
@@ -13,8 +13,9 @@ pub fn is_precompiled(address: &Address) -> bool {
 }
 
 pub(crate) fn execute_precompiled(address: &Address, input: &[u8], gas: u64) -> (Vec<u8>, u64) {
-    let Some(Precompile::Standard(precompile_fn)) = Precompiles::berlin()
-        .get(address.as_fixed_bytes())  else {
+    let Some(Precompile::Standard(precompile_fn)) =
+        Precompiles::berlin().get(address.as_fixed_bytes())
+    else {
         panic!("calling non-exist precompiled contract address")
     };
 
@@ -105,3 +106,230 @@ impl PrecompileCalls {
         (*self).into()
     }
 }
+
+/// Number of bytes of the ecRecover input: the message hash, `v`, `r` and `s`
+/// as 32 bytes words.  Shorter inputs are padded with zeros.
+pub const ECRECOVER_INPUT_LEN: usize = 128;
+
+/// Call to the ecRecover precompile whose signature recovered an address.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EcRecoverEvent {
+    /// Hash of the signed message.
+    pub msg_hash: Word,
+    /// Recovery id of the signature, 27 or 28.
+    pub sig_v: u8,
+    /// Signature `r`.
+    pub sig_r: Word,
+    /// Signature `s`.
+    pub sig_s: Word,
+    /// Recovered address.
+    pub recovered_addr: Address,
+}
+
+impl EcRecoverEvent {
+    /// Parse the (padded) input of an ecRecover call.  Returns `None` when the
+    /// call didn't recover an address.
+    pub(crate) fn new(input: &[u8], output: &[u8]) -> Option<Self> {
+        if output.len() != 32 {
+            return None;
+        }
+        let sig_v = Word::from_big_endian(&input[32..64]);
+        Some(Self {
+            msg_hash: Word::from_big_endian(&input[..32]),
+            sig_v: sig_v.low_u64() as u8,
+            sig_r: Word::from_big_endian(&input[64..96]),
+            sig_s: Word::from_big_endian(&input[96..128]),
+            recovered_addr: Address::from_slice(&output[12..]),
+        })
+    }
+
+    /// Signature data to be verified by the SignVerify chip.
+    pub fn sign_data(&self) -> SignData {
+        SignData::from_signature(
+            self.sig_v - 27,
+            &self.sig_r,
+            &self.sig_s,
+            &self.msg_hash.to_be_bytes(),
+        )
+        .expect("the signature of an ecRecover event recovers a public key")
+    }
+}
+
+/// Number of bytes of the ecAdd input: the coordinates of both points as 32
+/// bytes words.  Shorter inputs are padded with zeros.
+pub const EC_ADD_INPUT_LEN: usize = 128;
+
+/// Number of bytes of the ecMul input: the coordinates of the point and the
+/// scalar as 32 bytes words.  Shorter inputs are padded with zeros.
+pub const EC_MUL_INPUT_LEN: usize = 96;
+
+/// Number of bytes of the Blake2F input: the number of rounds, the state `h`,
+/// the message `m`, the offset counters `t` and the final block flag `f`.
+pub const BLAKE2F_INPUT_LEN: usize = 213;
+
+/// Number of bytes of the number of rounds at the start of the Blake2F input.
+pub const BLAKE2F_ROUNDS_LEN: usize = 4;
+
+/// Number of rows of a block of the Blake2F circuit, which has two header
+/// blocks, a block per round and a footer block per compression.
+pub const BLAKE2F_ROWS_PER_BLOCK: usize = 16;
+
+/// Number of rows of the Blake2F circuit compressing a Blake2F `input` of
+/// [`BLAKE2F_INPUT_LEN`] bytes.
+pub fn blake2f_num_rows(input: &[u8]) -> usize {
+    let rounds = u32::from_be_bytes(input[..BLAKE2F_ROUNDS_LEN].try_into().unwrap());
+    (3 + rounds as usize) * BLAKE2F_ROWS_PER_BLOCK
+}
+
+/// Number of bytes of the header of the modexp input: the lengths of the
+/// base, the exponent and the modulus as 32 bytes words.
+pub const MODEXP_HEADER_LEN: usize = 96;
+
+/// Maximum length of the base, the exponent and the modulus of the modexp
+/// calls supported by the ModExp circuit, which works on 32 bytes words.
+pub const MODEXP_MAX_LEN: usize = 32;
+
+/// Lengths of the base, the exponent and the modulus of a modexp call, read
+/// from the header of its `input` padded with zeros.  Returns `None` when one
+/// of them is longer than [`MODEXP_MAX_LEN`].
+pub fn modexp_lengths(input: &[u8]) -> Option<[usize; 3]> {
+    let mut header = input[..input.len().min(MODEXP_HEADER_LEN)].to_vec();
+    header.resize(MODEXP_HEADER_LEN, 0);
+    let lengths = [0, 1, 2].map(|idx| Word::from_big_endian(&header[idx * 32..(idx + 1) * 32]));
+    if lengths.iter().any(|len| *len > Word::from(MODEXP_MAX_LEN)) {
+        return None;
+    }
+    Some(lengths.map(|len| len.as_usize()))
+}
+
+/// Parse two big-endian 32 bytes words.
+fn word_pair(bytes: &[u8]) -> (Word, Word) {
+    (
+        Word::from_big_endian(&bytes[..32]),
+        Word::from_big_endian(&bytes[32..64]),
+    )
+}
+
+/// Successful call to the ecAdd precompile.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EcAddOp {
+    /// First point.
+    pub p: (Word, Word),
+    /// Second point.
+    pub q: (Word, Word),
+    /// Sum of the points.
+    pub r: (Word, Word),
+}
+
+impl EcAddOp {
+    /// Parse the (padded) input and the output of an ecAdd call.
+    pub(crate) fn new(input: &[u8], output: &[u8]) -> Self {
+        Self {
+            p: word_pair(&input[..64]),
+            q: word_pair(&input[64..128]),
+            r: word_pair(output),
+        }
+    }
+
+    /// Whether the ECC circuit can prove the call, which requires that none
+    /// of the points is the point at infinity and that the points have
+    /// different `x`.
+    pub fn is_supported(&self) -> bool {
+        !is_infinity(&self.p) && !is_infinity(&self.q) && self.p.0 != self.q.0
+    }
+}
+
+/// Successful call to the ecMul precompile.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EcMulOp {
+    /// Point.
+    pub p: (Word, Word),
+    /// Scalar, not reduced modulo the order of the group.
+    pub s: Word,
+    /// Product of the point by the scalar.
+    pub r: (Word, Word),
+}
+
+impl EcMulOp {
+    /// Parse the (padded) input and the output of an ecMul call.
+    pub(crate) fn new(input: &[u8], output: &[u8]) -> Self {
+        Self {
+            p: word_pair(&input[..64]),
+            s: Word::from_big_endian(&input[64..96]),
+            r: word_pair(output),
+        }
+    }
+
+    /// Whether the ECC circuit can prove the call, which requires that the
+    /// point is not the point at infinity and that the scalar is not a
+    /// multiple of the order of the group, so that the product isn't either.
+    pub fn is_supported(&self) -> bool {
+        !is_infinity(&self.p) && !(self.s % Word::from_big_endian(&BN254_ORDER)).is_zero()
+    }
+}
+
+/// Successful call to the modexp precompile with a nonzero modulus.  The
+/// operands are at most [`MODEXP_MAX_LEN`] bytes long.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModExpOp {
+    /// Base.
+    pub base: Word,
+    /// Exponent.
+    pub exponent: Word,
+    /// Modulus.
+    pub modulus: Word,
+    /// Base to the power of the exponent, modulo the modulus.
+    pub result: Word,
+}
+
+impl ModExpOp {
+    /// Parse the input, with the `lengths` of its operands, and the output of
+    /// a modexp call.
+    pub(crate) fn new(input: &[u8], lengths: [usize; 3], output: &[u8]) -> Self {
+        let mut padded_input = input.to_vec();
+        padded_input.resize(MODEXP_HEADER_LEN + lengths.iter().sum::<usize>(), 0);
+        let mut offset = MODEXP_HEADER_LEN;
+        let [base, exponent, modulus] = lengths.map(|length| {
+            let word = Word::from_big_endian(&padded_input[offset..offset + length]);
+            offset += length;
+            word
+        });
+        Self {
+            base,
+            exponent,
+            modulus,
+            result: Word::from_big_endian(output),
+        }
+    }
+}
+
+/// Order of the BN254 group, in big-endian.
+const BN254_ORDER: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+// The point at infinity is encoded as (0, 0) by the precompiles.
+fn is_infinity(point: &(Word, Word)) -> bool {
+    point.0.is_zero() && point.1.is_zero()
+}
+
+/// Inputs of the calls to precompiled contracts in a block that are proven by
+/// dedicated circuits.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PrecompileEvents {
+    /// Signatures recovered by the ecRecover calls.
+    pub ecrecover: Vec<EcRecoverEvent>,
+    /// Inputs of the SHA2-256 calls.
+    pub sha256: Vec<Vec<u8>>,
+    /// Inputs of the RIPEMD-160 calls.
+    pub ripemd160: Vec<Vec<u8>>,
+    /// Successful ecAdd calls.
+    pub ec_add: Vec<EcAddOp>,
+    /// Successful ecMul calls.
+    pub ec_mul: Vec<EcMulOp>,
+    /// Successful modexp calls with a nonzero modulus.
+    pub modexp: Vec<ModExpOp>,
+    /// Inputs of the successful Blake2F calls.
+    pub blake2f: Vec<Vec<u8>>,
+}
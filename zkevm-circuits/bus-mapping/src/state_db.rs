@@ -109,6 +109,11 @@ pub struct StateDB {
     // state before current transaction, to calculate gas cost for some opcodes like sstore.
     // So both dirty storage and committed storage are needed.
     dirty_storage: HashMap<(Address, Word), Word>,
+    // `transient_storage` contains the EIP-1153 storage, which is discarded when current
+    // transaction finishes.
+    transient_storage: HashMap<(Address, Word), Word>,
+    // Accounts created in current transaction, which `SELFDESTRUCT` deletes since EIP-6780.
+    created_account: HashSet<Address>,
     // Accounts that have been through `SELFDESTRUCT` under the situation that `is_persistent` is
     // `true`. These accounts will be reset once `commit_tx` is called.
     destructed_account: HashSet<Address>,
@@ -196,6 +201,20 @@ impl StateDB {
         self.dirty_storage.insert((*addr, *key), *value);
     }
 
+    /// Get a reference to the transient storage value at `addr` and `key`.
+    /// Returns a zero [`Word`] when the `key` wasn't written in current tx.
+    pub fn get_transient_storage(&self, addr: &Address, key: &Word) -> &Word {
+        self.transient_storage
+            .get(&(*addr, *key))
+            .unwrap_or(&VALUE_ZERO)
+    }
+
+    /// Set transient storage value at `addr` and `key`.
+    /// The transient storage is cleared in `commit_tx` method.
+    pub fn set_transient_storage(&mut self, addr: &Address, key: &Word, value: &Word) {
+        self.transient_storage.insert((*addr, *key), *value);
+    }
+
     /// Get nonce of account with `addr`.
     pub fn get_nonce(&self, addr: &Address) -> u64 {
         let (_, account) = self.get_account(addr);
@@ -244,6 +263,23 @@ impl StateDB {
         debug_assert!(exist);
     }
 
+    /// Check whether `addr` was created in current transaction.
+    pub fn is_created_account(&self, addr: &Address) -> bool {
+        self.created_account.contains(addr)
+    }
+
+    /// Add `addr` to the accounts created in current transaction. Returns
+    /// `true` if it wasn't created before.
+    pub fn add_created_account(&mut self, addr: Address) -> bool {
+        self.created_account.insert(addr)
+    }
+
+    /// Remove `addr` from the accounts created in current transaction.
+    pub fn remove_created_account(&mut self, addr: &Address) {
+        let exist = self.created_account.remove(addr);
+        debug_assert!(exist);
+    }
+
     /// Set account as self destructed.
     pub fn destruct_account(&mut self, addr: Address) {
         self.destructed_account.insert(addr);
@@ -259,7 +295,8 @@ impl StateDB {
         self.refund = value;
     }
 
-    /// Clear access list and refund, and commit dirty storage.
+    /// Clear access list, refund, transient storage and created accounts, and
+    /// commit dirty storage.
     /// It should be invoked before processing
     /// with new transaction with the same [`StateDB`].
     pub fn commit_tx(&mut self) {
@@ -270,6 +307,8 @@ impl StateDB {
             *ptr = value;
         }
         self.dirty_storage = HashMap::new();
+        self.transient_storage = HashMap::new();
+        self.created_account = HashSet::new();
         for addr in self.destructed_account.clone() {
             let (_, account) = self.get_account_mut(&addr);
             *account = ACCOUNT_ZERO.clone();
@@ -196,6 +196,14 @@ mod tests {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: 0,
+            max_ec_mul: 0,
+            max_modexp: 0,
+            max_blake2f_rows: 0,
+            max_mpt_rows: 0,
         };
         let protocol_instance = ProtocolInstance::default();
         let (_, super_circuit, _, _) =
@@ -498,6 +506,14 @@ mod tests {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: 0,
+            max_ec_mul: 0,
+            max_modexp: 0,
+            max_blake2f_rows: 0,
+            max_mpt_rows: 0,
         };
         let (_, circuit, instance, _) =
             SuperCircuit::build(block, circuits_params, ProtocolInstance::default()).unwrap();
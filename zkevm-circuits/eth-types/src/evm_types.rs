@@ -74,10 +74,15 @@ impl From<Gas> for u64 {
 
 /// Once per word of the init code when creating a contract.
 pub const INIT_CODE_WORD_GAS: u64 = 2;
+/// Maximum size of the code of a contract (EIP-170).
+pub const MAX_CODE_SIZE: u64 = 0x6000;
 /// Quotient for max refund of gas used
 pub const MAX_REFUND_QUOTIENT_OF_GAS_USED: usize = 5;
 /// Gas stipend when CALL or CALLCODE is attached with value.
 pub const GAS_STIPEND_CALL_WITH_VALUE: u64 = 2300;
+/// Minimum blob gas price (EIP-4844), which is the blob base fee of a block
+/// without excess blob gas.
+pub const MIN_BLOB_GASPRICE: u64 = 1;
 
 /// Defines the gas consumption.
 #[derive(
@@ -177,12 +182,21 @@ impl GasCost {
     pub const PRECOMPILE_BN256ADD: Self = Self(150);
     /// Base gas price for precompile call: BN256 scalar multiplication
     pub const PRECOMPILE_BN256MUL: Self = Self(6000);
-    /// Base gas price for precompile call: BN256 pairing per point
+    /// Base gas price for precompile call: BN256 pairing
     pub const PRECOMPILE_BN256PAIRING: Self = Self(45000);
+    /// Per-pair gas price for BN256 pairing
+    pub const PRECOMPILE_BN256PAIRING_PER_PAIR: Self = Self(34000);
     /// Base gas price for precompile call: MODEXP
     pub const PRECOMPILE_MODEXP: Self = Self(0);
+    /// Minimum gas price for precompile call: MODEXP (EIP-2565)
+    pub const PRECOMPILE_MODEXP_MIN: Self = Self(200);
+    /// Divisor of the multiplication complexity times the iteration count of
+    /// the gas price for precompile call: MODEXP (EIP-2565)
+    pub const PRECOMPILE_MODEXP_QUAD_DIVISOR: Self = Self(3);
     /// Base gas price for precompile call: BLAKE2F
     pub const PRECOMPILE_BLAKE2F: Self = Self(0);
+    /// Per-round gas price for BLAKE2F
+    pub const PRECOMPILE_BLAKE2F_PER_ROUND: Self = Self(1);
 }
 
 impl GasCost {
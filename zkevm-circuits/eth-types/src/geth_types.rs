@@ -1,9 +1,8 @@
 //! Types needed for generating Ethereum traces
 
 use crate::{
-    sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
-    AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
-    ToWord, Word, U64,
+    sign_types::SignData, AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash,
+    ToBigEndian, ToWord, Word, U64,
 };
 use ethers_core::{
     types::{
@@ -13,9 +12,6 @@ use ethers_core::{
     utils::get_contract_address,
 };
 use ethers_signers::{LocalWallet, Signer};
-use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
-use num::Integer;
-use num_bigint::BigUint;
 use serde::{Deserialize, Serialize, Serializer};
 use serde_with::serde_as;
 use sha3::{Digest, Keccak256};
@@ -223,16 +219,6 @@ fn recover_v(v: u64, chain_id: u64) -> u64 {
 impl Transaction {
     /// Return the SignData associated with this Transaction.
     pub fn sign_data(&self, chain_id: u64) -> Result<SignData, Error> {
-        let sig_r_le = self.r.to_le_bytes();
-        let sig_s_le = self.s.to_le_bytes();
-        let sig_r = ct_option_ok_or(
-            secp256k1::Fq::from_repr(sig_r_le),
-            Error::Signature(libsecp256k1::Error::InvalidSignature),
-        )?;
-        let sig_s = ct_option_ok_or(
-            secp256k1::Fq::from_repr(sig_s_le),
-            Error::Signature(libsecp256k1::Error::InvalidSignature),
-        )?;
         // msg = rlp([chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gas, to, value, data,
         // accessList])
         let req: Eip1559TransactionRequest = self.into();
@@ -246,20 +232,7 @@ impl Transaction {
             .try_into()
             .expect("hash length isn't 32 bytes");
         let v = recover_v(self.v, chain_id) as u8;
-        let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
-        // msg_hash = msg_hash % q
-        let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
-        let msg_hash = msg_hash.mod_floor(&*SECP256K1_Q);
-        let msg_hash_le = biguint_to_32bytes_le(msg_hash);
-        let msg_hash = ct_option_ok_or(
-            secp256k1::Fq::from_repr(msg_hash_le),
-            libsecp256k1::Error::InvalidMessage,
-        )?;
-        Ok(SignData {
-            signature: (sig_r, sig_s),
-            pk,
-            msg_hash,
-        })
+        Ok(SignData::from_signature(v, &self.r, &self.s, &msg_hash)?)
     }
 
     /// Compute call data gas cost from call data
@@ -283,7 +283,7 @@ impl<F: Field> ToScalar<F> for usize {
 }
 
 /// Struct used to define the storage proof
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct StorageProof {
     /// Storage key
     pub key: U256,
@@ -294,7 +294,7 @@ pub struct StorageProof {
 }
 
 /// Struct used to define the result of `eth_getProof` call
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EIP1186ProofResponse {
     /// Account address
@@ -1,4 +1,20 @@
 //! This module generates traces by connecting to an external tracer
+//!
+//! The tracer backend is selected by cargo feature: `geth-tracer` (default)
+//! uses go-ethereum through `geth_utils`, `revm-tracer` executes natively
+//! with revm. `mock` and `testool` pass both features through.
+//!
+//! Cargo unifies features across the workspace, so enabling `revm-tracer` for
+//! any crate, e.g. `cargo test -p testool --features revm-tracer`, switches every
+//! crate of the build to revm, since it takes precedence over `geth-tracer`.
+//! The Go toolchain is still required as long as any crate of the build enables
+//! `geth-tracer`, e.g. through the default features of `mock`.
+
+#[cfg(not(any(feature = "geth-tracer", feature = "revm-tracer")))]
+compile_error!("enable one of the features geth-tracer or revm-tracer");
+
+#[cfg(feature = "revm-tracer")]
+pub mod revm_tracer;
 
 use eth_types::{
     geth_types::{Account, BlockConstants, Transaction},
@@ -62,7 +78,20 @@ impl LoggerConfig {
 }
 
 /// Creates a trace for the specified config
+#[cfg(feature = "revm-tracer")]
+pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    revm_tracer::trace(config)
+}
+
+/// Creates a trace for the specified config
+#[cfg(not(feature = "revm-tracer"))]
 pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    geth_trace(config)
+}
+
+/// Creates a trace for the specified config with go-ethereum
+#[cfg(feature = "geth-tracer")]
+pub fn geth_trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
     // Get the trace
     let trace_string = geth_utils::trace(&serde_json::to_string(&config).unwrap()).map_err(
         |error| match error {
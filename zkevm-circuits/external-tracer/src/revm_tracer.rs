@@ -0,0 +1,470 @@
+//! Native tracer backend executing a [`TraceConfig`] with revm.
+//!
+//! Mirrors `geth_utils::trace`: the accounts are loaded into an in-memory
+//! state, the transactions are executed one after another on the Cancun
+//! ruleset and every step is recorded like geth's `logger.StructLogger`.
+//! With `taiko` set the first transaction is the anchor transaction, which is
+//! exempt from the balance check as in taiko-geth.
+
+use crate::{LoggerConfig, TraceConfig};
+use eth_types::{
+    evm_types::{Gas, GasCost, Memory, OpcodeId, ProgramCounter, Stack, Storage},
+    Error, GethExecStep, GethExecTrace, Word,
+};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    interpreter::{CallInputs, CreateInputs, Gas as RevmGas, InstructionResult, Interpreter},
+    primitives::{
+        AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Output, SpecId, TransactTo, B256,
+        U256,
+    },
+    Database, EVMData, Inspector, EVM,
+};
+use std::collections::HashMap;
+
+fn to_address(address: &eth_types::Address) -> Address {
+    Address::from(address.0)
+}
+
+fn to_u256(word: &Word) -> U256 {
+    U256::from_limbs(word.0)
+}
+
+fn to_word(value: &U256) -> Word {
+    Word(value.into_limbs())
+}
+
+/// Error string of a failed step, as reported by geth.
+fn step_error(result: InstructionResult) -> Option<String> {
+    let error = match result {
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG => "out of gas",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "invalid opcode",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::StateChangeDuringStaticCall => "write protection",
+        InstructionResult::OutOfOffset => "return data out of bounds",
+        InstructionResult::CallTooDeep => "max call depth exceeded",
+        InstructionResult::OverflowPayment | InstructionResult::OutOfFund => {
+            "insufficient balance for transfer"
+        }
+        InstructionResult::CreateCollision => "contract address collision",
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded",
+        InstructionResult::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        InstructionResult::CreateInitcodeSizeLimit => "max initcode size exceeded",
+        _ => return None,
+    };
+    Some(error.to_string())
+}
+
+/// A step whose instruction is being executed.
+struct PendingStep {
+    /// Index into `StructLogger::steps`
+    index: usize,
+    /// Remaining gas before the step
+    gas: u64,
+    /// Storage key read by `SLOAD`
+    sload_key: Option<Word>,
+    /// Gas forwarded to a call or create and the gas it returned
+    forwarded_gas: u64,
+    returned_gas: u64,
+}
+
+/// Records the steps of one transaction like geth's `logger.StructLogger`.
+struct StructLogger {
+    config: LoggerConfig,
+    steps: Vec<GethExecStep>,
+    /// Steps being executed, innermost last
+    pending: Vec<PendingStep>,
+    /// Storage slots read or written so far, per contract
+    storage: HashMap<Address, HashMap<Word, Word>>,
+    /// Refund counter of each active call frame, the outermost first
+    frame_refunds: Vec<i64>,
+}
+
+impl StructLogger {
+    fn new(config: LoggerConfig) -> Self {
+        Self {
+            config,
+            steps: Vec::new(),
+            pending: Vec::new(),
+            storage: HashMap::new(),
+            frame_refunds: Vec::new(),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for StructLogger {
+    fn step(&mut self, interp: &mut Interpreter, data: &mut EVMData<'_, DB>) -> InstructionResult {
+        let depth = data.journaled_state.depth() as usize;
+        let op = interp.current_opcode();
+        let stack = interp.stack.data();
+
+        self.frame_refunds.truncate(depth);
+        self.frame_refunds.resize(depth, 0);
+        self.frame_refunds[depth - 1] = interp.gas.refunded();
+        let refund = self.frame_refunds.iter().sum::<i64>().max(0) as u64;
+
+        let mut storage = None;
+        let mut sload_key = None;
+        if !self.config.disable_storage {
+            let contract_storage = self.storage.entry(interp.contract.address).or_default();
+            if op == OpcodeId::SSTORE.as_u8() && stack.len() >= 2 {
+                let key = to_word(&stack[stack.len() - 1]);
+                let value = to_word(&stack[stack.len() - 2]);
+                contract_storage.insert(key, value);
+                storage = Some(contract_storage.clone());
+            } else if op == OpcodeId::SLOAD.as_u8() && !stack.is_empty() {
+                // the value is known after the step, see `step_end`
+                sload_key = Some(to_word(&stack[stack.len() - 1]));
+                storage = Some(contract_storage.clone());
+            }
+        }
+
+        self.pending.push(PendingStep {
+            index: self.steps.len(),
+            gas: interp.gas.remaining(),
+            sload_key,
+            forwarded_gas: 0,
+            returned_gas: 0,
+        });
+        self.steps.push(GethExecStep {
+            pc: ProgramCounter(interp.program_counter()),
+            op: OpcodeId::from(op),
+            gas: Gas(interp.gas.remaining()),
+            gas_cost: GasCost(0),
+            refund: Gas(refund),
+            depth: depth as u16,
+            error: None,
+            stack: if self.config.disable_stack {
+                Stack::new()
+            } else {
+                Stack(stack.iter().map(to_word).collect())
+            },
+            memory: if self.config.enable_memory {
+                Memory(interp.memory.data().clone())
+            } else {
+                Memory::new()
+            },
+            storage: Storage(storage.unwrap_or_default()),
+        });
+
+        InstructionResult::Continue
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        _data: &mut EVMData<'_, DB>,
+        eval: InstructionResult,
+    ) -> InstructionResult {
+        let Some(pending) = self.pending.pop() else {
+            return InstructionResult::Continue;
+        };
+        let step = &mut self.steps[pending.index];
+
+        // geth charges the gas forwarded to a call as part of its cost,
+        // but not the gas forwarded to a create.
+        let mut gas_cost = pending.gas.saturating_sub(interp.gas.remaining());
+        if step.op.is_create() {
+            gas_cost = (gas_cost + pending.returned_gas).saturating_sub(pending.forwarded_gas);
+        } else if step.op.is_call() {
+            gas_cost += pending.returned_gas;
+        }
+        step.gas_cost = GasCost(gas_cost);
+        step.error = step_error(eval);
+
+        if let (Some(key), Ok(value)) = (pending.sload_key, interp.stack.peek(0)) {
+            let contract_storage = self.storage.entry(interp.contract.address).or_default();
+            contract_storage.insert(key, to_word(&value));
+            step.storage = Storage(contract_storage.clone());
+        }
+
+        InstructionResult::Continue
+    }
+
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+    ) -> (InstructionResult, RevmGas, Bytes) {
+        // the call of the transaction itself has no step
+        if let Some(pending) = self.pending.last_mut() {
+            pending.forwarded_gas = inputs.gas_limit;
+        }
+        (InstructionResult::Continue, RevmGas::new(0), Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CallInputs,
+        remaining_gas: RevmGas,
+        ret: InstructionResult,
+        out: Bytes,
+    ) -> (InstructionResult, RevmGas, Bytes) {
+        if let Some(pending) = self.pending.last_mut() {
+            pending.returned_gas = remaining_gas.remaining();
+        }
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (InstructionResult, Option<Address>, RevmGas, Bytes) {
+        if let Some(pending) = self.pending.last_mut() {
+            pending.forwarded_gas = inputs.gas_limit;
+        }
+        (
+            InstructionResult::Continue,
+            None,
+            RevmGas::new(0),
+            Bytes::new(),
+        )
+    }
+
+    fn create_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CreateInputs,
+        ret: InstructionResult,
+        address: Option<Address>,
+        remaining_gas: RevmGas,
+        out: Bytes,
+    ) -> (InstructionResult, Option<Address>, RevmGas, Bytes) {
+        if let Some(pending) = self.pending.last_mut() {
+            pending.returned_gas = remaining_gas.remaining();
+        }
+        (ret, address, remaining_gas, out)
+    }
+}
+
+/// Creates a trace for the specified config with revm
+pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
+    let block_gas_limit = config.block_constants.gas_limit.as_u64();
+    let txs_gas_limit: u64 = config
+        .transactions
+        .iter()
+        .map(|tx| tx.gas_limit.as_u64())
+        .sum();
+    if txs_gas_limit > block_gas_limit {
+        return Err(Error::TracingError(format!(
+            "txs total gas: {txs_gas_limit} Exceeds block gas limit: {block_gas_limit}"
+        )));
+    }
+
+    // Setup state db with accounts from argument
+    let mut db = CacheDB::new(EmptyDB::default());
+    for (address, account) in config.accounts.iter() {
+        let code = Bytecode::new_raw(Bytes::copy_from_slice(&account.code));
+        let info = AccountInfo::new(
+            to_u256(&account.balance),
+            account.nonce.as_u64(),
+            code.hash_slow(),
+            code,
+        );
+        db.insert_account_info(to_address(address), info);
+        for (key, value) in account.storage.iter() {
+            db.insert_account_storage(to_address(address), to_u256(key), to_u256(value))
+                .map_err(|e| Error::TracingError(format!("{e:?}")))?;
+        }
+    }
+    // history_hashes[history_hashes.len() - 1] is the hash of the parent block
+    let number = config.block_constants.number.as_u64();
+    for (i, hash) in config.history_hashes.iter().rev().enumerate() {
+        if let Some(n) = number.checked_sub(i as u64 + 1) {
+            db.block_hashes
+                .insert(U256::from(n), B256::from(to_u256(hash).to_be_bytes()));
+        }
+    }
+
+    let mut evm = EVM::new();
+    evm.database(db);
+    evm.env.cfg.chain_id = config.chain_id.as_u64();
+    evm.env.cfg.spec_id = SpecId::CANCUN;
+    evm.env.cfg.disable_base_fee = true;
+    evm.env.block.number = U256::from(number);
+    evm.env.block.coinbase = to_address(&config.block_constants.coinbase);
+    evm.env.block.timestamp = to_u256(&config.block_constants.timestamp);
+    evm.env.block.gas_limit = U256::from(block_gas_limit);
+    evm.env.block.basefee = to_u256(&config.block_constants.base_fee);
+    evm.env.block.prevrandao = Some(B256::from(config.block_constants.mix_hash.0));
+    evm.env.block.difficulty = U256::ZERO;
+
+    // Run the transactions with tracing enabled.
+    let mut traces = Vec::with_capacity(config.transactions.len());
+    for (i, tx) in config.transactions.iter().enumerate() {
+        let tx_env = &mut evm.env.tx;
+        tx_env.caller = to_address(&tx.from);
+        tx_env.transact_to = match tx.to {
+            Some(to) => TransactTo::Call(to_address(&to)),
+            None => TransactTo::create(),
+        };
+        tx_env.nonce = Some(tx.nonce.as_u64());
+        tx_env.value = to_u256(&tx.value);
+        tx_env.gas_limit = tx.gas_limit.as_u64();
+        // only support EIP-1559 txs
+        tx_env.gas_price = to_u256(&tx.gas_fee_cap);
+        tx_env.gas_priority_fee = Some(to_u256(&tx.gas_tip_cap));
+        tx_env.data = Bytes::copy_from_slice(&tx.call_data);
+        tx_env.chain_id = Some(config.chain_id.as_u64());
+        tx_env.access_list = tx
+            .access_list
+            .iter()
+            .flat_map(|access_list| access_list.0.iter())
+            .map(|item| {
+                (
+                    to_address(&item.address),
+                    item.storage_keys
+                        .iter()
+                        .map(|key| U256::from_be_bytes(key.0))
+                        .collect(),
+                )
+            })
+            .collect();
+        // treat the first tx as anchor tx when Taiko is enabled
+        evm.env.cfg.disable_balance_check = config.taiko && i == 0;
+
+        let mut logger = StructLogger::new(config.logger_config.clone());
+        let result = evm.inspect_commit(&mut logger).map_err(|e| {
+            Error::TracingError(format!("Failed to apply config.Transactions[{i}]: {e:?}"))
+        })?;
+
+        let (failed, output) = match &result {
+            ExecutionResult::Success { output, .. } => match output {
+                Output::Call(bytes) => (false, bytes.clone()),
+                Output::Create(bytes, _) => (false, bytes.clone()),
+            },
+            ExecutionResult::Revert { output, .. } => (true, output.clone()),
+            ExecutionResult::Halt { .. } => (true, Bytes::new()),
+        };
+        traces.push(GethExecTrace {
+            gas: Gas(result.gas_used()),
+            failed,
+            return_value: hex::encode(output),
+            struct_logs: logger.steps,
+        });
+    }
+
+    Ok(traces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{
+        address, bytecode,
+        geth_types::{Account, BlockConstants, Transaction},
+        word,
+    };
+
+    fn trace_config(code: eth_types::Bytes) -> TraceConfig {
+        let from = address!("0x00000000000000000000000000000000000000fe");
+        let to = address!("0x00000000000000000000000000000000000000ff");
+        let accounts = [
+            Account {
+                address: from,
+                balance: word!("0x100000000000000000000"),
+                ..Default::default()
+            },
+            Account {
+                address: to,
+                code,
+                ..Default::default()
+            },
+        ];
+        TraceConfig {
+            chain_id: Word::from(1337),
+            history_hashes: vec![Word::zero(); 256],
+            block_constants: BlockConstants {
+                number: 256.into(),
+                gas_limit: Word::from(10_000_000),
+                base_fee: Word::zero(),
+                ..Default::default()
+            },
+            accounts: accounts
+                .into_iter()
+                .map(|account| (account.address, account))
+                .collect(),
+            transactions: vec![Transaction {
+                from,
+                to: Some(to),
+                gas_limit: 1_000_000.into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// Compares against a trace recorded with `geth_utils::trace`.
+    #[test]
+    fn revm_trace_matches_geth_sstore() {
+        let code = bytecode! {
+            PUSH1(0x02)
+            PUSH1(0x03)
+            ADD
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let recorded: Vec<GethExecTrace> =
+            serde_json::from_str(include_str!("../testdata/sstore.json")).unwrap();
+
+        let traces = trace(&trace_config(code.into())).unwrap();
+        assert_eq!(traces, recorded);
+    }
+
+    /// Compares against a trace recorded with `geth_utils::trace`.
+    #[test]
+    fn revm_trace_matches_geth_revert() {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            REVERT
+        };
+        let recorded: Vec<GethExecTrace> =
+            serde_json::from_str(include_str!("../testdata/revert.json")).unwrap();
+
+        let traces = trace(&trace_config(code.into())).unwrap();
+        assert_eq!(traces, recorded);
+    }
+
+    /// Compares against a live trace of `geth_utils::trace`.
+    #[cfg(feature = "geth-tracer")]
+    #[test]
+    fn revm_trace_matches_geth_live() {
+        let code = bytecode! {
+            PUSH1(0x40)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x00)
+            SLOAD
+            PUSH1(0x01)
+            SSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let mut config = trace_config(code.into());
+        config.logger_config = crate::LoggerConfig::enable_memory();
+        assert_eq!(trace(&config).unwrap(), crate::geth_trace(&config).unwrap());
+    }
+
+    #[test]
+    fn revm_trace_anchor_without_balance() {
+        let mut config = trace_config(bytecode! { STOP }.into());
+        config.transactions[0].from = address!("0x0000777735367b36bc9b61c50022d9d0700db4ec");
+        config.transactions[0].gas_fee_cap = Word::from(1);
+        config.taiko = true;
+        assert!(trace(&config).is_ok());
+
+        config.taiko = false;
+        assert!(trace(&config).is_err());
+    }
+}
@@ -145,6 +145,7 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		BerlinBlock:                   big.NewInt(0),
 		LondonBlock:                   big.NewInt(0),
 		ShanghaiTime:                  newUint64(0),
+		CancunTime:                    newUint64(0),
 		MergeNetsplitBlock:            nil,
 		TerminalTotalDifficulty:       common.Big0,
 		TerminalTotalDifficultyPassed: true,
@@ -401,6 +401,7 @@ mod public_data_test {
                 .unwrap()
                 .as_fixed_bytes()
                 .into(),
+            ..Default::default()
         };
         vec![protocol_instance]
     }
@@ -437,6 +438,14 @@ mod public_data_test {
             max_exp_steps: 27900,
             max_evm_rows: 80000,
             max_keccak_rows: 0,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: 0,
+            max_ec_mul: 0,
+            max_modexp: 0,
+            max_blake2f_rows: 0,
+            max_mpt_rows: 0,
         };
         let protocol_instance = gen_requests()[0].clone();
         let block = gen_block(circuits_params, protocol_instance).await;
@@ -455,6 +464,14 @@ mod public_data_test {
             max_exp_steps: 27900,
             max_evm_rows: 80000,
             max_keccak_rows: 0,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: 0,
+            max_ec_mul: 0,
+            max_modexp: 0,
+            max_blake2f_rows: 0,
+            max_mpt_rows: 0,
         };
 
         let protocol_instance: ProtocolInstance = ProtocolInstance::default();
@@ -480,6 +497,14 @@ mod public_data_test {
             max_exp_steps: 27900,
             max_evm_rows: 80000,
             max_keccak_rows: 20000,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: 0,
+            max_ec_mul: 0,
+            max_modexp: 0,
+            max_blake2f_rows: 0,
+            max_mpt_rows: 0,
         };
 
         let requests = gen_requests();
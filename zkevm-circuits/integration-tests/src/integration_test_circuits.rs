@@ -69,6 +69,14 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_evm_rows: MAX_EVM_ROWS,
     max_exp_steps: MAX_EXP_STEPS,
     max_keccak_rows: MAX_KECCAK_ROWS,
+    max_ecrecover: 0,
+    max_sha256_rows: 0,
+    max_ripemd160_rows: 0,
+    max_ec_add: 0,
+    max_ec_mul: 0,
+    max_modexp: 0,
+    max_blake2f_rows: 0,
+    max_mpt_rows: 0,
 };
 
 const EVM_CIRCUIT_DEGREE: u32 = 20;
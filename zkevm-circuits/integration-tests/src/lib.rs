@@ -162,6 +162,14 @@ pub async fn build_circuit_input_builder_block(block_num: u64) {
             max_evm_rows: 0,
             max_exp_steps: 1000,
             max_keccak_rows: 0,
+            max_ecrecover: 0,
+            max_sha256_rows: 0,
+            max_ripemd160_rows: 0,
+            max_ec_add: 0,
+            max_ec_mul: 0,
+            max_modexp: 0,
+            max_blake2f_rows: 0,
+            max_mpt_rows: 0,
         },
         Default::default(),
     )
@@ -180,12 +188,12 @@ pub async fn build_circuit_input_builder_block(block_num: u64) {
     let (proofs, codes) = cli.get_state(block_num, access_set).await.unwrap();
 
     // 4. Build a partial StateDB from step 3
-    let (state_db, code_db) = build_state_code_db(proofs, codes);
+    let (state_db, code_db) = build_state_code_db(proofs.clone(), codes);
     trace!("StateDB: {:#?}", state_db);
 
     // 5. For each step in TxExecTraces, gen the associated ops and state
     // circuit inputs
-    let builder = cli
+    let mut builder = cli
         .gen_inputs_from_state(
             state_db,
             code_db,
@@ -195,6 +203,7 @@ pub async fn build_circuit_input_builder_block(block_num: u64) {
             prev_state_root,
         )
         .unwrap();
+    builder.block.state_proofs = proofs;
 
     trace!("CircuitInputBuilder: {:#?}", builder);
 }
@@ -180,6 +180,18 @@ fn get_exec_steps_occupancy() {
         keccak_table,
         LOOKUP_CONFIG[6].1,
         exp_table,
-        LOOKUP_CONFIG[7].1
+        LOOKUP_CONFIG[7].1,
+        sig_table,
+        LOOKUP_CONFIG[8].1,
+        sha256_table,
+        LOOKUP_CONFIG[9].1,
+        ripemd160_table,
+        LOOKUP_CONFIG[10].1,
+        ecc_table,
+        LOOKUP_CONFIG[11].1,
+        blake2f_table,
+        LOOKUP_CONFIG[12].1,
+        modexp_table,
+        LOOKUP_CONFIG[13].1
     );
 }
@@ -0,0 +1,993 @@
+//! The Blake2F circuit implementation.
+//!
+//! Each call to the Blake2F precompile is laid out in blocks of
+//! [`ROWS_PER_BLOCK`](param::ROWS_PER_BLOCK) rows: two header blocks
+//! absorbing the input, one block per round and a footer block computing the
+//! output.  A header row absorbs one item of the input (the number of rounds,
+//! a word of `h`, `m` or `t`, or the final block flag) into the input RLC, and
+//! the items are carried over all the rows of the call.  A round row applies
+//! half of a `G` function to the local work vector of the row and writes the
+//! result to the next row, so the position of a row in its block selects the
+//! words mixed and the rotations.  The message schedule of a round is a
+//! one-hot cell, rotated from one round to the next.  The words are decomposed
+//! in boolean cells so that the xors and rotations are simple expressions over
+//! the bits.  The last row of the footer exposes the output in the
+//! [`Blake2fTable`].
+mod param;
+mod util;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::Blake2fCircuit as TestBlake2fCircuit;
+
+pub(crate) use self::util::blake2f;
+
+use self::{param::*, util::*};
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+        pow_of_two_expr,
+    },
+    table::{Blake2fTable, LookupTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use eth_types::Field;
+use gadgets::util::{not, sum, xor, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// Blake2fCircuitConfig
+#[derive(Clone, Debug)]
+pub struct Blake2fCircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_positions: [Column<Fixed>; ROWS_PER_BLOCK],
+    is_header0: Column<Advice>,
+    is_header1: Column<Advice>,
+    is_round: Column<Advice>,
+    is_footer: Column<Advice>,
+    items: [Column<Advice>; NUM_ITEMS],
+    counter: Column<Advice>,
+    counter_inv: Column<Advice>,
+    sigmas: [Column<Advice>; NUM_SIGMAS],
+    work: [Column<Advice>; NUM_WORK_WORDS],
+    a_bits: [Column<Advice>; NUM_BITS_PER_WORD],
+    b_bits: [Column<Advice>; NUM_BITS_PER_WORD],
+    c_bits: [Column<Advice>; NUM_BITS_PER_WORD],
+    d_bits: [Column<Advice>; NUM_BITS_PER_WORD],
+    carry_a: [Column<Advice>; 2],
+    carry_c: Column<Advice>,
+    input_rlc: Column<Advice>,
+    output_rlc: Column<Advice>,
+    /// The columns for other circuits to lookup Blake2F results
+    pub blake2f_table: Blake2fTable,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct Blake2fCircuitConfigArgs<F: Field> {
+    /// Blake2fTable
+    pub blake2f_table: Blake2fTable,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Blake2fCircuitConfig<F> {
+    type ConfigArgs = Blake2fCircuitConfigArgs<F>;
+
+    /// Return a new Blake2fCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            blake2f_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = meta.fixed_column();
+        let q_first = meta.fixed_column();
+        let q_positions = array_init::array_init(|_| meta.fixed_column());
+        let is_header0 = meta.advice_column();
+        let is_header1 = meta.advice_column();
+        let is_round = meta.advice_column();
+        let is_footer = meta.advice_column();
+        let items = array_init::array_init(|_| meta.advice_column());
+        let counter = meta.advice_column();
+        let counter_inv = meta.advice_column();
+        let sigmas = array_init::array_init(|_| meta.advice_column());
+        let work = array_init::array_init(|_| meta.advice_column());
+        let a_bits = array_init::array_init(|_| meta.advice_column());
+        let b_bits = array_init::array_init(|_| meta.advice_column());
+        let c_bits = array_init::array_init(|_| meta.advice_column());
+        let d_bits = array_init::array_init(|_| meta.advice_column());
+        let carry_a = array_init::array_init(|_| meta.advice_column());
+        let carry_c = meta.advice_column();
+        let input_rlc = meta.advice_column_in(SecondPhase);
+        let output_rlc = meta.advice_column_in(SecondPhase);
+
+        let bits = |meta: &mut VirtualCells<F>, word: &[Column<Advice>; NUM_BITS_PER_WORD]| {
+            word.iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .collect::<Vec<_>>()
+        };
+        // The bytes of a word in input order
+        let bytes = |idx: usize, bits: &[Expression<F>]| {
+            let bytes_le = bits
+                .chunks(NUM_BITS_PER_BYTE)
+                .map(decode)
+                .collect::<Vec<_>>();
+            item_bytes(idx, &bytes_le)
+        };
+        let accumulate = |acc: Expression<F>, bytes: Vec<Expression<F>>| {
+            bytes
+                .into_iter()
+                .fold(acc, |acc, byte| acc * challenges.keccak_input() + byte)
+        };
+        let flags = |meta: &mut VirtualCells<F>, rot: Rotation| {
+            [is_header0, is_header1, is_round, is_footer]
+                .map(|column| meta.query_advice(column, rot))
+        };
+
+        meta.create_gate("boolean checks", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for column in a_bits
+                .iter()
+                .chain(b_bits.iter())
+                .chain(c_bits.iter())
+                .chain(d_bits.iter())
+                .chain(carry_a.iter())
+                .chain(sigmas.iter())
+                .chain([carry_c, is_header0, is_header1, is_round, is_footer].iter())
+            {
+                cb.require_boolean("boolean cell", meta.query_advice(*column, Rotation::cur()));
+            }
+            // Padding rows have no flag set
+            cb.require_boolean(
+                "at most one flag is set",
+                sum::expr(flags(meta, Rotation::cur())),
+            );
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("block flags", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_block_start = meta.query_fixed(q_positions[0], Rotation::cur());
+            let [is_header0, is_header1, is_round, is_footer] = flags(meta, Rotation::cur());
+            let [is_header0_prev, is_header1_prev, is_round_prev, is_footer_prev] =
+                flags(meta, Rotation::prev());
+            cb.condition(not::expr(q_block_start.clone()), |cb| {
+                for (flag, flag_prev) in [
+                    (is_header0.clone(), is_header0_prev.clone()),
+                    (is_header1.clone(), is_header1_prev.clone()),
+                    (is_round.clone(), is_round_prev.clone()),
+                    (is_footer.clone(), is_footer_prev),
+                ] {
+                    cb.require_equal(
+                        "flags are the same on all the rows of the block",
+                        flag,
+                        flag_prev,
+                    );
+                }
+            });
+            cb.condition(meta.query_fixed(q_first, Rotation::cur()), |cb| {
+                cb.require_zero(
+                    "the first block is a header0 or padding block",
+                    is_header1.clone() + is_round.clone() + is_footer.clone(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("block transitions", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let [_, is_header1, is_round, is_footer] = flags(meta, Rotation::cur());
+            let [is_header0_prev, is_header1_prev, is_round_prev, _] =
+                flags(meta, Rotation::prev());
+            // The counter of the previous block is the number of rounds left
+            let counter_prev = meta.query_advice(counter, Rotation::prev());
+            let no_round_left =
+                1.expr() - counter_prev.clone() * meta.query_advice(counter_inv, Rotation::cur());
+            cb.require_zero(
+                "no_round_left is 1 only when the counter is 0",
+                counter_prev * no_round_left.clone(),
+            );
+            cb.condition(is_header0_prev.clone(), |cb| {
+                cb.require_equal(
+                    "header0 is followed by header1",
+                    is_header1.clone(),
+                    1.expr(),
+                );
+            });
+            cb.condition(is_header1_prev.clone() + is_round_prev.clone(), |cb| {
+                cb.require_equal(
+                    "a round follows while rounds are left",
+                    is_round.clone(),
+                    not::expr(no_round_left.clone()),
+                );
+                cb.require_equal(
+                    "the footer follows the last round",
+                    is_footer.clone(),
+                    no_round_left,
+                );
+            });
+            cb.condition(
+                1.expr() - is_header0_prev - is_header1_prev - is_round_prev,
+                |cb| {
+                    cb.require_zero(
+                        "a footer or padding block is followed by a header0 or padding block",
+                        is_header1 + is_round + is_footer,
+                    );
+                },
+            );
+            cb.gate(
+                meta.query_fixed(q_positions[0], Rotation::cur())
+                    * not::expr(meta.query_fixed(q_first, Rotation::cur())),
+            )
+        });
+
+        meta.create_gate("items", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The items are set by the header rows of a call and carried over
+            // all its rows
+            let [is_header0, is_header1, is_round, is_footer] = flags(meta, Rotation::cur());
+            let is_call_start =
+                meta.query_fixed(q_positions[0], Rotation::cur()) * is_header0.clone();
+            cb.condition(
+                not::expr(is_call_start) * (is_header0 + is_header1.clone() + is_round + is_footer),
+                |cb| {
+                    for column in items {
+                        cb.require_equal(
+                            "items are carried over",
+                            meta.query_advice(column, Rotation::cur()),
+                            meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                },
+            );
+            cb.condition(is_header1, |cb| {
+                cb.require_equal(
+                    "counter is the number of rounds in header1",
+                    meta.query_advice(counter, Rotation::cur()),
+                    meta.query_advice(items[ITEM_ROUNDS], Rotation::cur()),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("header", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let [is_header0, is_header1, _, _] = flags(meta, Rotation::cur());
+            let b = bits(meta, &b_bits);
+            let input_rlc_cur = meta.query_advice(input_rlc, Rotation::cur());
+            let input_rlc_prev = meta.query_advice(input_rlc, Rotation::prev());
+            for (position, q_position) in q_positions.iter().enumerate() {
+                let q_position = meta.query_fixed(*q_position, Rotation::cur());
+                for (block, is_header) in [is_header0.clone(), is_header1.clone()]
+                    .into_iter()
+                    .enumerate()
+                {
+                    let idx = block * ROWS_PER_BLOCK + position;
+                    cb.condition(q_position.clone() * is_header, |cb| {
+                        if idx < NUM_ITEMS {
+                            cb.require_equal(
+                                "item is the composition of its bits",
+                                meta.query_advice(items[idx], Rotation::cur()),
+                                decode(&b),
+                            );
+                            for bit in b[item_num_bits(idx)..].iter() {
+                                cb.require_zero("item is in range", bit.clone());
+                            }
+                        }
+                        // The input RLC starts at the first item
+                        let acc = if idx == ITEM_ROUNDS {
+                            0.expr()
+                        } else {
+                            input_rlc_prev.clone()
+                        };
+                        cb.require_equal(
+                            "input_rlc accumulates the bytes of the item",
+                            input_rlc_cur.clone(),
+                            accumulate(acc, bytes(idx, &b)),
+                        );
+                    });
+                }
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("init", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The last row of header1 decomposes `t` to initialize the work
+            // vector of the next row
+            let b = bits(meta, &b_bits);
+            let d = bits(meta, &d_bits);
+            let item = |meta: &mut VirtualCells<F>, idx: usize| {
+                meta.query_advice(items[idx], Rotation::cur())
+            };
+            cb.require_equal("b is t0", decode(&b), item(meta, ITEM_T));
+            cb.require_equal("d is t1", decode(&d), item(meta, ITEM_T + 1));
+            let f = item(meta, ITEM_F);
+            let init = (0..NUM_STATE_WORDS)
+                .map(|idx| item(meta, ITEM_H + idx))
+                .chain(IV[..4].iter().map(|iv| Expression::Constant(F::from(*iv))))
+                .chain([
+                    decode(&xor_constant(&b, IV[4])),
+                    decode(&xor_constant(&d, IV[5])),
+                    Expression::Constant(F::from(IV[6]))
+                        + f * (Expression::Constant(F::from(!IV[6]))
+                            - Expression::Constant(F::from(IV[6]))),
+                    Expression::Constant(F::from(IV[7])),
+                ]);
+            for (column, value) in work.iter().zip(init) {
+                cb.require_equal(
+                    "initial work vector",
+                    meta.query_advice(*column, Rotation::next()),
+                    value,
+                );
+            }
+            cb.gate(
+                meta.query_fixed(q_positions[ROWS_PER_BLOCK - 1], Rotation::cur())
+                    * meta.query_advice(is_header1, Rotation::cur()),
+            )
+        });
+
+        meta.create_gate("round", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_round = meta.query_advice(is_round, Rotation::cur());
+            let [a_bits, b_bits, c_bits, d_bits] =
+                [&a_bits, &b_bits, &c_bits, &d_bits].map(|word| bits(meta, word));
+            let carry_a = meta.query_advice(carry_a[0], Rotation::cur())
+                + 2.expr() * meta.query_advice(carry_a[1], Rotation::cur());
+            let carry_c = meta.query_advice(carry_c, Rotation::cur());
+            let sigmas = sigmas.map(|column| meta.query_advice(column, Rotation::cur()));
+            for (position, q_position) in q_positions.iter().enumerate() {
+                let q_position = meta.query_fixed(*q_position, Rotation::cur());
+                let [a, b, c, d] = G_WORDS[position / 2];
+                let [r1, r2] = G_ROTATIONS[position % 2];
+                let v = work.map(|column| meta.query_advice(column, Rotation::cur()));
+                let v_next = work.map(|column| meta.query_advice(column, Rotation::next()));
+                // The message word of the schedule of the round
+                let x = sum::expr(sigmas.iter().zip(SIGMA.iter()).map(|(sigma, schedule)| {
+                    sigma.clone()
+                        * meta.query_advice(items[ITEM_M + schedule[position]], Rotation::cur())
+                }));
+                cb.condition(q_position * is_round.clone(), |cb| {
+                    cb.require_equal("b bits", decode(&b_bits), v[b].clone());
+                    cb.require_equal("d bits", decode(&d_bits), v[d].clone());
+                    cb.require_equal(
+                        "a' = a + b + x",
+                        decode(&a_bits) + carry_a.clone() * pow_of_two_expr(NUM_BITS_PER_WORD),
+                        v[a].clone() + v[b].clone() + x,
+                    );
+                    let d_next = decode(&rotate_right(&xor_bits(&d_bits, &a_bits), r1));
+                    cb.require_equal(
+                        "c' = c + d'",
+                        decode(&c_bits) + carry_c.clone() * pow_of_two_expr(NUM_BITS_PER_WORD),
+                        v[c].clone() + d_next.clone(),
+                    );
+                    let b_next = decode(&rotate_right(&xor_bits(&b_bits, &c_bits), r2));
+                    for (idx, (value_next, value)) in v_next.iter().zip(v.iter()).enumerate() {
+                        let expected = match idx {
+                            idx if idx == a => decode(&a_bits),
+                            idx if idx == b => b_next.clone(),
+                            idx if idx == c => decode(&c_bits),
+                            idx if idx == d => d_next.clone(),
+                            _ => value.clone(),
+                        };
+                        cb.require_equal(
+                            "work vector of the next row",
+                            value_next.clone(),
+                            expected,
+                        );
+                    }
+                });
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("round counter", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_block_start = meta.query_fixed(q_positions[0], Rotation::cur());
+            let is_header1_prev = meta.query_advice(is_header1, Rotation::prev());
+            let counter_cur = meta.query_advice(counter, Rotation::cur());
+            let counter_prev = meta.query_advice(counter, Rotation::prev());
+            let sigmas_cur = sigmas.map(|column| meta.query_advice(column, Rotation::cur()));
+            let sigmas_prev = sigmas.map(|column| meta.query_advice(column, Rotation::prev()));
+            cb.require_equal(
+                "one message schedule",
+                sum::expr(sigmas_cur.clone()),
+                1.expr(),
+            );
+            cb.condition(q_block_start.clone(), |cb| {
+                cb.require_equal(
+                    "counter decreases by one every round",
+                    counter_cur.clone(),
+                    counter_prev.clone() - 1.expr(),
+                );
+                // The previous block is header1 or a round
+                for (idx, sigma) in sigmas_cur.iter().enumerate() {
+                    cb.require_equal(
+                        "the schedules are used cyclically",
+                        sigma.clone(),
+                        is_header1_prev.clone() * (idx == 0).expr()
+                            + not::expr(is_header1_prev.clone())
+                                * sigmas_prev[(idx + NUM_SIGMAS - 1) % NUM_SIGMAS].clone(),
+                    );
+                }
+            });
+            cb.condition(not::expr(q_block_start), |cb| {
+                cb.require_equal("counter is carried over", counter_cur, counter_prev);
+                for (sigma, sigma_prev) in sigmas_cur.iter().zip(sigmas_prev.iter()) {
+                    cb.require_equal(
+                        "schedule is carried over",
+                        sigma.clone(),
+                        sigma_prev.clone(),
+                    );
+                }
+            });
+            cb.gate(
+                meta.query_fixed(q_enable, Rotation::cur())
+                    * meta.query_advice(is_round, Rotation::cur()),
+            )
+        });
+
+        meta.create_gate("footer", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_footer = meta.query_advice(is_footer, Rotation::cur());
+            let [a_bits, b_bits, c_bits, d_bits] =
+                [&a_bits, &b_bits, &c_bits, &d_bits].map(|word| bits(meta, word));
+            let output_rlc_cur = meta.query_advice(output_rlc, Rotation::cur());
+            let output_rlc_prev = meta.query_advice(output_rlc, Rotation::prev());
+            // The position selectors are only set on the rows of the circuit, so
+            // the gate is not multiplied by `q_enable` to keep the xor of three
+            // words within the degree.
+            cb.condition(
+                meta.query_fixed(q_enable, Rotation::cur())
+                    - meta.query_fixed(q_positions[0], Rotation::cur()),
+                |cb| {
+                    for column in work {
+                        cb.require_equal(
+                            "work vector is carried over",
+                            meta.query_advice(column, Rotation::cur()),
+                            meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                },
+            );
+            for (position, q_position) in q_positions.iter().enumerate() {
+                let q_position = meta.query_fixed(*q_position, Rotation::cur());
+                cb.condition(q_position, |cb| {
+                    if position < NUM_STATE_WORDS {
+                        // h'[i] = h[i] ^ v[i] ^ v[i + 8]
+                        for (name, word, column) in [
+                            ("b is h", &b_bits, items[ITEM_H + position]),
+                            ("d is v[i]", &d_bits, work[position]),
+                            ("a is v[i + 8]", &a_bits, work[position + NUM_STATE_WORDS]),
+                        ] {
+                            cb.require_equal(
+                                name,
+                                decode(word),
+                                meta.query_advice(column, Rotation::cur()),
+                            );
+                        }
+                        for (((c, b), d), a) in c_bits
+                            .iter()
+                            .zip(b_bits.iter())
+                            .zip(d_bits.iter())
+                            .zip(a_bits.iter())
+                        {
+                            cb.require_equal(
+                                "c = b ^ d ^ a",
+                                c.clone(),
+                                xor::expr(xor::expr(b.clone(), d.clone()), a.clone()),
+                            );
+                        }
+                        // The output RLC starts at the first word
+                        let acc = if position == 0 {
+                            0.expr()
+                        } else {
+                            output_rlc_prev.clone()
+                        };
+                        cb.require_equal(
+                            "output_rlc accumulates the bytes of the word",
+                            output_rlc_cur.clone(),
+                            accumulate(acc, bytes(ITEM_H, &c_bits)),
+                        );
+                    } else {
+                        cb.require_equal(
+                            "output_rlc is carried over",
+                            output_rlc_cur.clone(),
+                            output_rlc_prev.clone(),
+                        );
+                    }
+                });
+            }
+            cb.gate(is_footer)
+        });
+
+        meta.create_gate("input carry over", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            cb.condition(
+                meta.query_advice(is_round, Rotation::cur())
+                    + meta.query_advice(is_footer, Rotation::cur()),
+                |cb| {
+                    cb.require_equal(
+                        "input_rlc is carried over",
+                        meta.query_advice(input_rlc, Rotation::cur()),
+                        meta.query_advice(input_rlc, Rotation::prev()),
+                    );
+                },
+            );
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("table", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // A row of the table is enabled only at the end of a footer
+            let is_output = meta.query_fixed(q_positions[ROWS_PER_BLOCK - 1], Rotation::cur())
+                * meta.query_advice(is_footer, Rotation::cur());
+            cb.require_equal(
+                "is_enabled",
+                meta.query_advice(blake2f_table.is_enabled, Rotation::cur()),
+                is_output.clone(),
+            );
+            cb.condition(is_output, |cb| {
+                for (name, table_column, column) in [
+                    ("rounds", blake2f_table.rounds, items[ITEM_ROUNDS]),
+                    ("input_rlc", blake2f_table.input_rlc, input_rlc),
+                    ("output_rlc", blake2f_table.output_rlc, output_rlc),
+                ] {
+                    cb.require_equal(
+                        name,
+                        meta.query_advice(table_column, Rotation::cur()),
+                        meta.query_advice(column, Rotation::cur()),
+                    );
+                }
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        Self {
+            q_enable,
+            q_first,
+            q_positions,
+            is_header0,
+            is_header1,
+            is_round,
+            is_footer,
+            items,
+            counter,
+            counter_inv,
+            sigmas,
+            work,
+            a_bits,
+            b_bits,
+            c_bits,
+            d_bits,
+            carry_a,
+            carry_c,
+            input_rlc,
+            output_rlc,
+            blake2f_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Blake2fCircuitConfig<F> {
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[Blake2fRow<F>],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assign blake2f rows",
+            |mut region| {
+                for (offset, blake2f_row) in witness.iter().enumerate() {
+                    self.set_row(&mut region, offset, blake2f_row)?;
+                }
+                if witness.is_empty() {
+                    // Keep an all-zero row for the disabled lookups
+                    self.blake2f_table
+                        .assign_row(&mut region, 0, [Value::known(F::ZERO); 4])?;
+                }
+                self.blake2f_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
+    fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &Blake2fRow<F>,
+    ) -> Result<(), Error> {
+        let position = offset % ROWS_PER_BLOCK;
+        // Fixed selectors
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, true),
+            ("q_first", self.q_first, offset == 0),
+        ]
+        .into_iter()
+        .chain(
+            self.q_positions
+                .iter()
+                .enumerate()
+                .map(|(idx, column)| ("q_position", *column, idx == position)),
+        ) {
+            region.assign_fixed(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+
+        // Flags and counters
+        let counter = F::from(row.counter);
+        for (name, column, value) in [
+            (
+                "is_header0",
+                self.is_header0,
+                F::from(row.is_header0 as u64),
+            ),
+            (
+                "is_header1",
+                self.is_header1,
+                F::from(row.is_header1 as u64),
+            ),
+            ("is_round", self.is_round, F::from(row.is_round as u64)),
+            ("is_footer", self.is_footer, F::from(row.is_footer as u64)),
+            ("counter", self.counter, counter),
+            (
+                "counter_inv",
+                self.counter_inv,
+                F::from(row.counter_prev).invert().unwrap_or(F::ZERO),
+            ),
+        ]
+        .into_iter()
+        .chain(
+            self.sigmas
+                .iter()
+                .enumerate()
+                .map(|(idx, column)| ("sigma", *column, F::from((row.sigma == Some(idx)) as u64))),
+        )
+        .chain(
+            self.items
+                .iter()
+                .zip(row.items.iter())
+                .map(|(column, item)| ("item", *column, F::from(*item))),
+        )
+        .chain(
+            self.work
+                .iter()
+                .zip(row.work.iter())
+                .map(|(column, word)| ("work", *column, F::from(*word))),
+        )
+        .chain([
+            ("carry_a", self.carry_a[0], F::from(row.carry_a & 1)),
+            ("carry_a", self.carry_a[1], F::from(row.carry_a >> 1)),
+            ("carry_c", self.carry_c, F::from(row.carry_c)),
+        ]) {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(value),
+            )?;
+        }
+
+        // Words
+        for (name, columns, value) in [
+            ("a", &self.a_bits, row.a),
+            ("b", &self.b_bits, row.b),
+            ("c", &self.c_bits, row.c),
+            ("d", &self.d_bits, row.d),
+        ] {
+            for (bit, column) in into_bits(value).into_iter().zip(columns.iter()) {
+                region.assign_advice(
+                    || format!("assign {} {}", name, offset),
+                    *column,
+                    offset,
+                    || Value::known(F::from(bit as u64)),
+                )?;
+            }
+        }
+
+        // RLCs
+        for (name, column, value) in [
+            ("input_rlc", self.input_rlc, row.input_rlc),
+            ("output_rlc", self.output_rlc, row.output_rlc),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || value,
+            )?;
+        }
+
+        self.blake2f_table.assign_row(
+            region,
+            offset,
+            if row.is_footer && position == ROWS_PER_BLOCK - 1 {
+                [
+                    Value::known(F::ONE),
+                    Value::known(F::from(row.items[ITEM_ROUNDS])),
+                    row.input_rlc,
+                    row.output_rlc,
+                ]
+            } else {
+                [Value::known(F::ZERO); 4]
+            },
+        )
+    }
+
+    fn annotate_circuit(&self, region: &mut Region<F>) {
+        region.name_column(|| "BLAKE2F_q_enable", self.q_enable);
+        region.name_column(|| "BLAKE2F_q_first", self.q_first);
+        region.name_column(|| "BLAKE2F_is_header0", self.is_header0);
+        region.name_column(|| "BLAKE2F_is_header1", self.is_header1);
+        region.name_column(|| "BLAKE2F_is_round", self.is_round);
+        region.name_column(|| "BLAKE2F_is_footer", self.is_footer);
+        region.name_column(|| "BLAKE2F_counter", self.counter);
+        region.name_column(|| "BLAKE2F_counter_inv", self.counter_inv);
+        region.name_column(|| "BLAKE2F_input_rlc", self.input_rlc);
+        region.name_column(|| "BLAKE2F_output_rlc", self.output_rlc);
+    }
+}
+
+/// A row of the Blake2F circuit
+#[derive(Clone, Debug)]
+pub(crate) struct Blake2fRow<F> {
+    is_header0: bool,
+    is_header1: bool,
+    is_round: bool,
+    is_footer: bool,
+    items: [u64; NUM_ITEMS],
+    counter: u64,
+    /// Counter of the previous row, inverted on the first row of a block
+    counter_prev: u64,
+    sigma: Option<usize>,
+    work: [u64; NUM_WORK_WORDS],
+    a: u64,
+    b: u64,
+    c: u64,
+    d: u64,
+    carry_a: u64,
+    carry_c: u64,
+    input_rlc: Value<F>,
+    output_rlc: Value<F>,
+}
+
+impl<F: Field> Default for Blake2fRow<F> {
+    fn default() -> Self {
+        Self {
+            is_header0: false,
+            is_header1: false,
+            is_round: false,
+            is_footer: false,
+            items: [0; NUM_ITEMS],
+            counter: 0,
+            counter_prev: 0,
+            sigma: None,
+            work: [0; NUM_WORK_WORDS],
+            a: 0,
+            b: 0,
+            c: 0,
+            d: 0,
+            carry_a: 0,
+            carry_c: 0,
+            input_rlc: Value::known(F::ZERO),
+            output_rlc: Value::known(F::ZERO),
+        }
+    }
+}
+
+/// Generates the rows of the compression of a valid `input`.
+fn blake2f_rows<F: Field>(
+    rows: &mut Vec<Blake2fRow<F>>,
+    input: &[u8],
+    challenges: Challenges<Value<F>>,
+) {
+    let items = parse_items(input);
+    let rounds = items[ITEM_ROUNDS];
+    let accumulate = |acc: Value<F>, bytes: Vec<u8>| {
+        bytes.into_iter().fold(acc, |acc, byte| {
+            acc * challenges.keccak_input() + Value::known(F::from(byte as u64))
+        })
+    };
+    let mut call_rows = Vec::new();
+
+    // Header
+    let mut input_rlc = Value::known(F::ZERO);
+    for idx in 0..2 * ROWS_PER_BLOCK {
+        let b = items.get(idx).copied().unwrap_or_default();
+        input_rlc = accumulate(input_rlc, item_bytes(idx, &b.to_le_bytes()));
+        call_rows.push(Blake2fRow {
+            is_header0: idx < ROWS_PER_BLOCK,
+            is_header1: idx >= ROWS_PER_BLOCK,
+            items,
+            counter: if idx >= ROWS_PER_BLOCK { rounds } else { 0 },
+            b,
+            input_rlc,
+            ..Default::default()
+        });
+    }
+    // The last row of header1 decomposes `t`
+    let init = call_rows.last_mut().unwrap();
+    init.b = items[ITEM_T];
+    init.d = items[ITEM_T + 1];
+
+    // Rounds
+    let mut work = init_work(&items);
+    for round in 0..rounds {
+        let sigma = round as usize % NUM_SIGMAS;
+        for position in 0..ROWS_PER_BLOCK {
+            let [a, b, c, d] = G_WORDS[position / 2];
+            let mut row = Blake2fRow {
+                is_round: true,
+                items,
+                counter: rounds - 1 - round,
+                counter_prev: if position == 0 { rounds - round } else { 0 },
+                sigma: Some(sigma),
+                work,
+                b: work[b],
+                d: work[d],
+                input_rlc,
+                ..Default::default()
+            };
+            (row.carry_a, row.carry_c) =
+                half_g(&mut work, position, items[ITEM_M + SIGMA[sigma][position]]);
+            (row.a, row.c) = (work[a], work[c]);
+            call_rows.push(row);
+        }
+    }
+
+    // Footer
+    let mut output_rlc = Value::known(F::ZERO);
+    for position in 0..ROWS_PER_BLOCK {
+        let mut row = Blake2fRow {
+            is_footer: true,
+            items,
+            work,
+            input_rlc,
+            ..Default::default()
+        };
+        if position < NUM_STATE_WORDS {
+            row.b = items[ITEM_H + position];
+            row.d = work[position];
+            row.a = work[position + NUM_STATE_WORDS];
+            row.c = row.b ^ row.d ^ row.a;
+            output_rlc = accumulate(output_rlc, row.c.to_le_bytes().to_vec());
+        }
+        row.output_rlc = output_rlc;
+        call_rows.push(row);
+    }
+
+    rows.extend(call_rows);
+}
+
+/// Generates the rows of the Blake2F circuit compressing all the `inputs`,
+/// padded with padding blocks up to `capacity` blocks.
+pub(crate) fn multi_blake2f<F: Field>(
+    inputs: &[Vec<u8>],
+    challenges: Challenges<Value<F>>,
+    capacity: Option<usize>,
+) -> Result<Vec<Blake2fRow<F>>, Error> {
+    // Check that we are not over capacity before generating the rows, the
+    // number of rounds of an input is only bounded by the gas.
+    let total_blocks = inputs.iter().map(|input| num_blocks(input)).sum::<usize>();
+    if let Some(capacity) = capacity {
+        if total_blocks > capacity {
+            log::error!("{total_blocks} Blake2F blocks > capacity = {capacity}");
+            return Err(Error::BoundsFailure);
+        }
+    }
+    let mut rows = Vec::with_capacity(capacity.unwrap_or(total_blocks) * ROWS_PER_BLOCK);
+    for input in inputs {
+        blake2f_rows(&mut rows, input, challenges);
+    }
+    if let Some(capacity) = capacity {
+        // Pad with padding blocks to the expected capacity
+        rows.resize(capacity * ROWS_PER_BLOCK, Blake2fRow::default());
+    }
+    Ok(rows)
+}
+
+/// Number of blocks of the compression of `input`.
+fn num_blocks(input: &[u8]) -> usize {
+    3 + parse_items(input)[ITEM_ROUNDS] as usize
+}
+
+/// Blake2fCircuit
+#[derive(Default, Clone, Debug)]
+pub struct Blake2fCircuit<F: Field> {
+    inputs: Vec<Vec<u8>>,
+    num_rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SubCircuit<F> for Blake2fCircuit<F> {
+    type Config = Blake2fCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    /// The `block.circuits_params.max_blake2f_rows` parameter, when enabled,
+    /// sets up the circuit to support a fixed number of blocks,
+    /// independently of the blocks required by the inputs.
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            block.circuits_params.max_blake2f_rows,
+            block.precompile_events.blake2f.clone(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        (
+            block
+                .precompile_events
+                .blake2f
+                .iter()
+                .map(|input| num_blocks(input) * ROWS_PER_BLOCK)
+                .sum(),
+            block.circuits_params.max_blake2f_rows,
+        )
+    }
+
+    /// Make the assignments to the Blake2fCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let witness = self.generate_witness(*challenges)?;
+        config.assign(layouter, witness.as_slice())
+    }
+}
+
+impl<F: Field> Blake2fCircuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
+        Blake2fCircuit {
+            inputs,
+            num_rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of blocks that can be assigned in this circuit
+    pub fn capacity(&self) -> Option<usize> {
+        if self.num_rows > 0 {
+            Some(self.num_rows.saturating_sub(Self::unusable_rows()) / ROWS_PER_BLOCK)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the witness using the inputs to be compressed, fails if they
+    /// don't fit in the capacity of the circuit
+    pub(crate) fn generate_witness(
+        &self,
+        challenges: Challenges<Value<F>>,
+    ) -> Result<Vec<Blake2fRow<F>>, Error> {
+        multi_blake2f(self.inputs.as_slice(), challenges, self.capacity())
+    }
+}
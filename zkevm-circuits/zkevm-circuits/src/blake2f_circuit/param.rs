@@ -0,0 +1,72 @@
+pub(crate) const MAX_DEGREE: usize = 5;
+
+pub(crate) const NUM_BITS_PER_BYTE: usize = 8;
+pub(crate) const NUM_BYTES_PER_WORD: usize = 8;
+pub(crate) const NUM_BITS_PER_WORD: usize = NUM_BYTES_PER_WORD * NUM_BITS_PER_BYTE;
+
+/// Number of words of the state `h`.
+pub(crate) const NUM_STATE_WORDS: usize = 8;
+/// Number of words of the message `m`.
+pub(crate) const NUM_MESSAGE_WORDS: usize = 16;
+/// Number of words of the local work vector `v`.
+pub(crate) const NUM_WORK_WORDS: usize = 16;
+/// Number of message schedules, used cyclically by the rounds.
+pub(crate) const NUM_SIGMAS: usize = 10;
+
+/// Every block has one row per half of a `G` function of a round.
+pub(crate) const ROWS_PER_BLOCK: usize = bus_mapping::precompile::BLAKE2F_ROWS_PER_BLOCK;
+
+/// The input is absorbed by the rows of the two header blocks, one item per
+/// row: the number of rounds, `h`, `m`, `t` and `f`.
+pub(crate) const ITEM_ROUNDS: usize = 0;
+pub(crate) const ITEM_H: usize = ITEM_ROUNDS + 1;
+pub(crate) const ITEM_M: usize = ITEM_H + NUM_STATE_WORDS;
+pub(crate) const ITEM_T: usize = ITEM_M + NUM_MESSAGE_WORDS;
+pub(crate) const ITEM_F: usize = ITEM_T + 2;
+pub(crate) const NUM_ITEMS: usize = ITEM_F + 1;
+
+/// Number of bytes of the number of rounds, big-endian.
+pub(crate) const NUM_BYTES_ROUNDS: usize = 4;
+
+/// Initialization vector.
+pub(crate) const IV: [u64; NUM_STATE_WORDS] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Message word absorbed by each half of the `G` functions of a round, per
+/// message schedule.
+pub(crate) const SIGMA: [[usize; ROWS_PER_BLOCK]; NUM_SIGMAS] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Words `a`, `b`, `c` and `d` of the work vector mixed by each `G` function
+/// of a round.
+pub(crate) const G_WORDS: [[usize; 4]; ROWS_PER_BLOCK / 2] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Right rotations of `d` and `b` in each half of a `G` function.
+pub(crate) const G_ROTATIONS: [[usize; 2]; 2] = [[32, 24], [16, 63]];
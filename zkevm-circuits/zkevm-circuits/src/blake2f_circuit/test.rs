@@ -0,0 +1,107 @@
+use super::*;
+use crate::util::unusable_rows;
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use log::error;
+
+#[test]
+fn blake2f_circuit_unusable_rows() {
+    assert_eq!(
+        Blake2fCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, Blake2fCircuit::<Fr>>(()),
+    )
+}
+
+/// Input of the EIP-152 test vectors hashing "abc", with the given number of
+/// rounds.
+fn input(rounds: u32) -> Vec<u8> {
+    let mut input = rounds.to_be_bytes().to_vec();
+    // The initialization vector with the BLAKE2b-512 parameter block
+    let h = [IV[0] ^ 0x01010040]
+        .into_iter()
+        .chain(IV[1..].iter().copied());
+    input.extend(h.flat_map(u64::to_le_bytes));
+    input.extend(b"abc");
+    input.extend([0; 125]);
+    input.extend([3, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend([0; 8]);
+    input.push(1);
+    input
+}
+
+fn verify<F: Field>(k: u32, inputs: Vec<Vec<u8>>, success: bool) {
+    let circuit = Blake2fCircuit::new(2usize.pow(k), inputs);
+
+    let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+    let verify_result = prover.verify();
+    if verify_result.is_ok() != success {
+        if let Some(errors) = verify_result.err() {
+            for error in errors.iter() {
+                error!("{}", error);
+            }
+        }
+        panic!();
+    }
+}
+
+#[test]
+fn blake2f_circuit_output() {
+    assert_eq!(
+        hex::encode(blake2f(&input(12))),
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+         7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+    );
+}
+
+#[test]
+fn blake2f_circuit_empty() {
+    verify::<Fr>(8, vec![], true);
+}
+
+#[test]
+fn blake2f_circuit_simple() {
+    let k = 10;
+    let inputs = vec![input(0), input(1), input(12)];
+    verify::<Fr>(k, inputs, true);
+}
+
+#[test]
+fn blake2f_circuit_over_capacity() {
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(0x100)),
+        Value::known(Fr::from(0x200)),
+        Value::known(Fr::from(0x300)),
+    );
+    // Refused before the rows of the rounds are generated
+    let inputs = vec![input(u32::MAX)];
+    assert!(multi_blake2f(&inputs, challenges, Some(4)).is_err());
+}
+
+#[test]
+fn blake2f_circuit_table() {
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(0x100)),
+        Value::known(Fr::from(0x200)),
+        Value::known(Fr::from(0x300)),
+    );
+    let inputs = vec![input(0), input(1), input(12)];
+    let rows = multi_blake2f(&inputs, challenges, None).unwrap();
+    let table_rows = rows
+        .iter()
+        .skip(ROWS_PER_BLOCK - 1)
+        .step_by(ROWS_PER_BLOCK)
+        .filter(|row| row.is_footer)
+        .collect::<Vec<_>>();
+    assert_eq!(table_rows.len(), inputs.len());
+    for (row, input) in table_rows.into_iter().zip(inputs.iter()) {
+        let [_, rounds, input_rlc, output_rlc] = Blake2fTable::assignments(input, &challenges);
+        rounds
+            .zip(Value::known(Fr::from(row.items[ITEM_ROUNDS])))
+            .assert_if_known(|(a, b)| a == b);
+        input_rlc
+            .zip(row.input_rlc)
+            .assert_if_known(|(a, b)| a == b);
+        output_rlc
+            .zip(row.output_rlc)
+            .assert_if_known(|(a, b)| a == b);
+    }
+}
@@ -0,0 +1,130 @@
+use super::param::*;
+use eth_types::Field;
+use gadgets::util::{xor, Expr};
+use halo2_proofs::plonk::Expression;
+
+/// Composes a word from its little-endian bits.
+pub(crate) fn decode<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .rev()
+        .fold(0.expr(), |acc, bit| acc * 2.expr() + bit.clone())
+}
+
+/// Bits of `x >>> n`.
+pub(crate) fn rotate_right<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..NUM_BITS_PER_WORD)
+        .map(|idx| bits[(idx + n) % NUM_BITS_PER_WORD].clone())
+        .collect()
+}
+
+/// Bits of `x ^ y`.
+pub(crate) fn xor_bits<F: Field>(x: &[Expression<F>], y: &[Expression<F>]) -> Vec<Expression<F>> {
+    x.iter()
+        .zip(y.iter())
+        .map(|(x, y)| xor::expr(x.clone(), y.clone()))
+        .collect()
+}
+
+/// Bits of `x ^ c` for a constant `c`.
+pub(crate) fn xor_constant<F: Field>(bits: &[Expression<F>], c: u64) -> Vec<Expression<F>> {
+    bits.iter()
+        .enumerate()
+        .map(|(idx, bit)| {
+            if (c >> idx) & 1 == 1 {
+                1.expr() - bit.clone()
+            } else {
+                bit.clone()
+            }
+        })
+        .collect()
+}
+
+/// Number of bits of the item `idx` of the input, 0 for the header rows
+/// without an item.  The final block flag is a single bit.
+pub(crate) fn item_num_bits(idx: usize) -> usize {
+    match idx {
+        ITEM_ROUNDS => NUM_BYTES_ROUNDS * NUM_BITS_PER_BYTE,
+        ITEM_F => 1,
+        idx if idx < NUM_ITEMS => NUM_BITS_PER_WORD,
+        _ => 0,
+    }
+}
+
+/// Bytes of the item `idx` of the input in input order, from the
+/// little-endian bytes of its value.  The number of rounds is big-endian, the
+/// words are little-endian.
+pub(crate) fn item_bytes<T: Clone>(idx: usize, bytes_le: &[T]) -> Vec<T> {
+    match idx {
+        ITEM_ROUNDS => bytes_le[..NUM_BYTES_ROUNDS].iter().rev().cloned().collect(),
+        ITEM_F => bytes_le[..1].to_vec(),
+        idx if idx < NUM_ITEMS => bytes_le[..NUM_BYTES_PER_WORD].to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// Little-endian bits of a word.
+pub(crate) fn into_bits(value: u64) -> [bool; NUM_BITS_PER_WORD] {
+    array_init::array_init(|idx| (value >> idx) & 1 == 1)
+}
+
+/// Items of a Blake2F input of [`BLAKE2F_INPUT_LEN`] bytes.
+///
+/// [`BLAKE2F_INPUT_LEN`]: bus_mapping::precompile::BLAKE2F_INPUT_LEN
+pub(crate) fn parse_items(input: &[u8]) -> [u64; NUM_ITEMS] {
+    let mut items = [0; NUM_ITEMS];
+    items[ITEM_ROUNDS] = u32::from_be_bytes(input[..NUM_BYTES_ROUNDS].try_into().unwrap()) as u64;
+    for (item, word) in items[ITEM_H..ITEM_F]
+        .iter_mut()
+        .zip(input[NUM_BYTES_ROUNDS..].chunks_exact(NUM_BYTES_PER_WORD))
+    {
+        *item = u64::from_le_bytes(word.try_into().unwrap());
+    }
+    items[ITEM_F] = *input.last().unwrap() as u64;
+    items
+}
+
+/// Work vector at the start of the rounds.
+pub(crate) fn init_work(items: &[u64; NUM_ITEMS]) -> [u64; NUM_WORK_WORDS] {
+    let mut work = [0; NUM_WORK_WORDS];
+    work[..NUM_STATE_WORDS].copy_from_slice(&items[ITEM_H..ITEM_M]);
+    work[NUM_STATE_WORDS..].copy_from_slice(&IV);
+    work[12] ^= items[ITEM_T];
+    work[13] ^= items[ITEM_T + 1];
+    if items[ITEM_F] == 1 {
+        work[14] = !work[14];
+    }
+    work
+}
+
+/// Applies the half `position % 2` of the `G` function `position / 2` of a
+/// round mixing the message word `x`.  Returns the carries of the additions
+/// to `a` and `c`.
+pub(crate) fn half_g(work: &mut [u64; NUM_WORK_WORDS], position: usize, x: u64) -> (u64, u64) {
+    let [a, b, c, d] = G_WORDS[position / 2];
+    let [r1, r2] = G_ROTATIONS[position % 2];
+    let sum_a = work[a] as u128 + work[b] as u128 + x as u128;
+    work[a] = sum_a as u64;
+    work[d] = (work[d] ^ work[a]).rotate_right(r1 as u32);
+    let sum_c = work[c] as u128 + work[d] as u128;
+    work[c] = sum_c as u64;
+    work[b] = (work[b] ^ work[c]).rotate_right(r2 as u32);
+    (
+        (sum_a >> NUM_BITS_PER_WORD) as u64,
+        (sum_c >> NUM_BITS_PER_WORD) as u64,
+    )
+}
+
+/// Output of the Blake2F compression of a valid input.
+pub(crate) fn blake2f(input: &[u8]) -> Vec<u8> {
+    let items = parse_items(input);
+    let mut work = init_work(&items);
+    for round in 0..items[ITEM_ROUNDS] as usize {
+        for position in 0..ROWS_PER_BLOCK {
+            let x = items[ITEM_M + SIGMA[round % NUM_SIGMAS][position]];
+            half_g(&mut work, position, x);
+        }
+    }
+    (0..NUM_STATE_WORDS)
+        .flat_map(|idx| (items[ITEM_H + idx] ^ work[idx] ^ work[idx + 8]).to_le_bytes())
+        .collect()
+}
@@ -19,7 +19,7 @@ use eth_types::{Field, Word};
 use gadgets::{
     binary_number::BinaryNumberChip,
     less_than::{LtChip, LtConfig, LtInstruction},
-    util::{and, not, or, Expr},
+    util::{and, not, Expr},
 };
 use halo2_proofs::{
     circuit::{Layouter, Region, Value},
@@ -136,6 +136,11 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
         meta.create_gate("verify row", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
+            // The reads of a memory copy come before all of its writes, so the
+            // rw counter increases from a read to the next read and from a
+            // write to the next write, rather than from a row to the next row.
+            let is_memory_copy = tag.value_equals(CopyDataType::MemoryCopy, Rotation::cur())(meta);
+
             cb.require_boolean(
                 "is_first is boolean",
                 meta.query_advice(is_first, Rotation::cur()),
@@ -188,24 +193,44 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                         meta.query_advice(src_addr_end, Rotation::cur()),
                         meta.query_advice(src_addr_end, Rotation(2)),
                     );
+                    cb.condition(is_memory_copy.clone(), |cb| {
+                        cb.require_equal(
+                            "rows[0].rw_counter + 1 == rows[2].rw_counter for memory copy",
+                            meta.query_advice(rw_counter, Rotation::cur()) + 1.expr(),
+                            meta.query_advice(rw_counter, Rotation(2)),
+                        );
+                    });
+                },
+            );
+            cb.condition(
+                meta.query_advice(is_first, Rotation::cur()) * is_memory_copy.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "rows[1].rw_counter == rows[0].rw_counter + bytes_left for memory copy",
+                        meta.query_advice(rw_counter, Rotation::cur())
+                            + meta.query_advice(bytes_left, Rotation::cur()),
+                        meta.query_advice(rw_counter, Rotation::next()),
+                    );
                 },
             );
 
+            // The tags are mutually exclusive, so their sum is boolean.
             let rw_diff = and::expr([
-                or::expr([
-                    tag.value_equals(CopyDataType::Memory, Rotation::cur())(meta),
-                    tag.value_equals(CopyDataType::TxLog, Rotation::cur())(meta),
-                ]),
+                tag.value_equals(CopyDataType::Memory, Rotation::cur())(meta)
+                    + tag.value_equals(CopyDataType::TxLog, Rotation::cur())(meta)
+                    + is_memory_copy.clone(),
                 not::expr(meta.query_advice(is_pad, Rotation::cur())),
             ]);
             cb.condition(
                 not::expr(meta.query_advice(is_last, Rotation::cur())),
                 |cb| {
-                    cb.require_equal(
-                        "rows[0].rw_counter + rw_diff == rows[1].rw_counter",
-                        meta.query_advice(rw_counter, Rotation::cur()) + rw_diff.clone(),
-                        meta.query_advice(rw_counter, Rotation::next()),
-                    );
+                    cb.condition(not::expr(is_memory_copy), |cb| {
+                        cb.require_equal(
+                            "rows[0].rw_counter + rw_diff == rows[1].rw_counter",
+                            meta.query_advice(rw_counter, Rotation::cur()) + rw_diff.clone(),
+                            meta.query_advice(rw_counter, Rotation::next()),
+                        );
+                    });
                     cb.require_equal(
                         "rows[0].rwc_inc_left - rw_diff == rows[1].rwc_inc_left",
                         meta.query_advice(rwc_inc_left, Rotation::cur()) - rw_diff.clone(),
@@ -325,7 +350,8 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
 
         meta.lookup_any("Memory lookup", |meta| {
             let cond = meta.query_fixed(q_enable, Rotation::cur())
-                * tag.value_equals(CopyDataType::Memory, Rotation::cur())(meta)
+                * (tag.value_equals(CopyDataType::Memory, Rotation::cur())(meta)
+                    + tag.value_equals(CopyDataType::MemoryCopy, Rotation::cur())(meta))
                 * not::expr(meta.query_advice(is_pad, Rotation::cur()));
             vec![
                 meta.query_advice(rw_counter, Rotation::cur()),
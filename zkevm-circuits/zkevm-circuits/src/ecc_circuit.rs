@@ -0,0 +1,578 @@
+//! The ECC circuit implementation.
+//!
+//! The circuit verifies the BN254 point additions and scalar multiplications
+//! of the ecAdd and ecMul precompile calls of a block with the
+//! [`GeneralEccChip`] used by the Tx Circuit, and copies the RLC of the
+//! arguments and results into the [`EccTable`] looked up by the EVM Circuit.
+//!
+//! Only a subset of the calls is supported, and bus-mapping refuses to build
+//! the witness of a block with a successful call outside of it (see
+//! `EcAddOp::is_supported` and `EcMulOp::is_supported`):
+//! - The point at infinity is not supported as an argument, and the additions of points with the
+//!   same `x` coordinate are not supported.
+//! - The scalar of an ecMul call, which the circuit reduces modulo the order of the group, must not
+//!   be a multiple of the order.
+//!
+//! The pairing check of the ecPairing precompile, which takes millions of rows
+//! in the non-native arithmetic of the chip, doesn't fit in the rows of the
+//! super circuit, so the ecPairing calls are not supported at all.
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::EccCircuit as TestEccCircuit;
+
+use crate::{
+    table::{EccTable, LookupTable},
+    tx_circuit::sign_verify::{
+        integer_to_bytes_le, SignVerifyConfig, BIT_LEN_LAST_LIMB, BIT_LEN_LIMB, NUMBER_OF_LIMBS,
+    },
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use bus_mapping::precompile::{EcAddOp, EcMulOp, PrecompileCalls};
+use ecc::{maingate, EccConfig, GeneralEccChip};
+use eth_types::{Field, ToLittleEndian, Word};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{AssignedCell, Layouter, Value},
+    halo2curves::{
+        bn256::{Fq, Fr, G1Affine},
+        ff::PrimeField,
+        group::{Curve, Group},
+    },
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, SecondPhase, Selector},
+};
+use integer::{AssignedInteger, IntegerInstructions, Range};
+use itertools::Itertools;
+use log::error;
+use maingate::{
+    AssignedValue, MainGate, MainGateConfig, MainGateInstructions, RangeChip, RangeConfig,
+    RangeInstructions, RegionCtx, Term,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::{iter, marker::PhantomData};
+
+/// Config for EccCircuit
+#[derive(Clone, Debug)]
+pub struct EccCircuitConfig<F: Field> {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+    q_rlc: Selector,
+    rlc: Column<Advice>,
+    /// The columns for the EVM Circuit to lookup the ECC operations
+    pub ecc_table: EccTable,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct EccCircuitConfigArgs<F: Field> {
+    /// EccTable
+    pub ecc_table: EccTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for EccCircuitConfig<F> {
+    type ConfigArgs = EccCircuitConfigArgs<F>;
+
+    /// Return a new EccCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            ecc_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        for column in <EccTable as LookupTable<F>>::advice_columns(&ecc_table) {
+            meta.enable_equality(column);
+        }
+
+        let (rns_base, rns_scalar) =
+            GeneralEccChip::<G1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+        let main_gate_config = MainGate::<F>::configure(meta);
+        let range_config = RangeChip::<F>::configure(
+            meta,
+            &main_gate_config,
+            vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS, 8],
+            [rns_base.overflow_lengths(), rns_scalar.overflow_lengths()].concat(),
+        );
+
+        // The coordinates and scalars are accumulated as EVM words
+        let q_rlc = meta.selector();
+        let rlc = meta.advice_column_in(SecondPhase);
+        meta.enable_equality(rlc);
+        SignVerifyConfig::configure_rlc(
+            meta,
+            "evm_word_rlc",
+            main_gate_config.clone(),
+            q_rlc,
+            rlc,
+            challenges.evm_word(),
+        );
+
+        Self {
+            main_gate_config,
+            range_config,
+            q_rlc,
+            rlc,
+            ecc_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> EccCircuitConfig<F> {
+    /// Load ECC RangeChip table.
+    pub fn load_aux_tables(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let range_chip = RangeChip::<F>::new(self.range_config.clone());
+        range_chip.load_table(layouter)
+    }
+
+    fn ecc_chip_config(&self) -> EccConfig {
+        EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+    }
+
+    // Assign the RLC of a word from its little endian bytes, verified by the
+    // `evm_word_rlc` gate.
+    fn assign_word_rlc(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        main_gate: &MainGate<F>,
+        name: &str,
+        assigned_le: &[AssignedValue<F>; 32],
+        word: Word,
+        challenge: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let zero = main_gate.assign_constant(ctx, F::ZERO)?;
+        let columns = self.main_gate_config.advices();
+        // The values of the bytes are passed explicitly, as the values of the
+        // cells of the first phase are unknown in the second phase.
+        let inputs_le = assigned_le
+            .iter()
+            .zip(word.to_le_bytes())
+            .map(|(assigned, byte)| (assigned.cell(), Value::known(F::from(byte as u64))))
+            .collect_vec();
+        let num_padding = (columns.len() - inputs_le.len() % columns.len()) % columns.len();
+        let inputs_be = iter::repeat((zero.cell(), Value::known(F::ZERO)))
+            .take(num_padding)
+            .chain(inputs_le.into_iter().rev())
+            .collect_vec();
+
+        let mut rlc = Value::known(F::ZERO);
+        for (chunk_idx, chunk) in inputs_be.chunks_exact(columns.len()).enumerate() {
+            ctx.enable(self.q_rlc)?;
+            let assigned_rlc =
+                ctx.assign_advice(|| format!("{name}_rlc[{chunk_idx}]"), self.rlc, rlc)?;
+            for ((idx, column), (cell, value)) in
+                (chunk_idx * chunk.len()..).zip(columns).zip(chunk)
+            {
+                let copied = ctx.assign_advice(|| format!("{name}_byte[{idx}]"), column, *value)?;
+                ctx.constrain_equal(*cell, copied.cell())?;
+            }
+            if chunk_idx == 0 {
+                ctx.constrain_equal(zero.cell(), assigned_rlc.cell())?;
+            }
+            rlc = iter::once(rlc)
+                .chain(chunk.iter().map(|(_, value)| *value))
+                .fold(Value::known(F::ZERO), |acc, input| acc * challenge + input);
+            ctx.next();
+        }
+
+        let assigned_rlc = ctx.assign_advice(|| format!("{name}_rlc"), self.rlc, rlc)?;
+        ctx.next();
+
+        Ok(assigned_rlc)
+    }
+
+    // Assign the little endian bytes of the scalar `word` of an ecMul call,
+    // constrained to be congruent to `scalar` modulo the order of the group.
+    // The order is the modulus of the native field, so the bytes are composed
+    // in the native field and compared to the native value of `scalar`.
+    fn assign_scalar_bytes_le(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        main_gate: &MainGate<F>,
+        range_chip: &RangeChip<F>,
+        scalar: &AssignedInteger<Fr, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        word: Word,
+    ) -> Result<[AssignedValue<F>; 32], Error> {
+        let mut limbs = Vec::new();
+        let mut bytes = Vec::new();
+        for (idx, bit_len) in [BIT_LEN_LIMB, BIT_LEN_LIMB, BIT_LEN_LIMB, BIT_LEN_LAST_LIMB]
+            .into_iter()
+            .enumerate()
+        {
+            let limb = (word >> (idx * BIT_LEN_LIMB)) & ((Word::one() << bit_len) - 1);
+            let (composed, limb_bytes) = range_chip.decompose(
+                ctx,
+                Value::known(F::from_u128(limb.as_u128())),
+                8,
+                bit_len,
+            )?;
+            limbs.push(composed);
+            bytes.extend(limb_bytes);
+        }
+        let terms = limbs
+            .iter()
+            .enumerate()
+            .map(|(idx, limb)| {
+                Term::Assigned(limb, F::from(2).pow_vartime([(idx * BIT_LEN_LIMB) as u64]))
+            })
+            .collect_vec();
+        let native = main_gate.compose(ctx, &terms, F::ZERO)?;
+        main_gate.assert_equal(ctx, &native, scalar.native())?;
+
+        Ok(bytes.try_into().unwrap())
+    }
+}
+
+/// ECC Circuit for verifying the ecAdd and ecMul calls
+#[derive(Clone, Debug)]
+pub struct EccCircuit<F: Field> {
+    /// Max number of ecAdd calls
+    pub max_add_ops: usize,
+    /// Max number of ecMul calls
+    pub max_mul_ops: usize,
+    /// Successful ecAdd calls
+    pub add_ops: Vec<EcAddOp>,
+    /// Successful ecMul calls
+    pub mul_ops: Vec<EcMulOp>,
+    /// Aux generator for EccChip
+    pub aux_generator: G1Affine,
+    /// Window size for EccChip
+    pub window_size: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Default for EccCircuit<F> {
+    fn default() -> Self {
+        Self::new(0, 0, Vec::new(), Vec::new())
+    }
+}
+
+/// An operation of the table as the arguments and results of the chip.
+#[derive(Clone)]
+enum EccOp {
+    Add(G1Affine, G1Affine, G1Affine),
+    Mul(G1Affine, Word, G1Affine),
+}
+
+// Return the point of the coordinates, if they are the coordinates of a point
+// of the curve other than the point at infinity.
+fn to_point((x, y): (Word, Word)) -> Option<G1Affine> {
+    let x = Option::from(Fq::from_bytes(&x.to_le_bytes()))?;
+    let y = Option::from(Fq::from_bytes(&y.to_le_bytes()))?;
+    Option::from(G1Affine::from_xy(x, y))
+}
+
+fn to_word(value: [u8; 32]) -> Word {
+    Word::from_little_endian(&value)
+}
+
+impl<F: Field> EccCircuit<F> {
+    /// Return a new EccCircuit
+    pub fn new(
+        max_add_ops: usize,
+        max_mul_ops: usize,
+        add_ops: Vec<EcAddOp>,
+        mul_ops: Vec<EcMulOp>,
+    ) -> Self {
+        // TODO: Investigate if it is safe to use a random point as aux generator that
+        // is choosen by the prover, see `SignVerifyChip::new`.
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let aux_generator = <G1Affine as CurveAffine>::CurveExt::random(&mut rng).to_affine();
+        Self {
+            max_add_ops,
+            max_mul_ops,
+            add_ops,
+            mul_ops,
+            aux_generator,
+            window_size: 4,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the minimum number of rows required to prove an input of a
+    /// particular size.
+    pub fn min_num_rows(num_add_ops: usize, num_mul_ops: usize) -> usize {
+        if num_add_ops + num_mul_ops == 0 {
+            return 1;
+        }
+        // The value rows_range_chip_table is the one of the SignVerifyChip, which
+        // uses the same range chip configuration.  The other values are estimates
+        // of the rows used by the chips.  TODO: Figure out a way to get these
+        // numbers automatically.
+        let rows_range_chip_table = 295188;
+        let rows_ecc_chip_aux = 226;
+        let rows_ec_add = 1100;
+        let rows_ec_mul = 52000;
+        std::cmp::max(
+            rows_range_chip_table,
+            rows_ecc_chip_aux + rows_ec_add * num_add_ops + rows_ec_mul * num_mul_ops,
+        )
+    }
+
+    // Return the operations of the chip, padded to the max number of
+    // operations with valid operations.
+    fn ops(&self) -> Result<Vec<EccOp>, Error> {
+        let generator = G1Affine::generator();
+        let double = (generator + generator).to_affine();
+        let padding_add = EccOp::Add(generator, double, (double + generator).to_affine());
+        let padding_mul = EccOp::Mul(generator, Word::one(), generator);
+
+        let add_ops = self.add_ops.iter().map(|op| {
+            let (p, q, r) = (to_point(op.p), to_point(op.q), to_point(op.r));
+            match (p, q, r) {
+                (Some(p), Some(q), Some(r)) if p.x != q.x => Ok(EccOp::Add(p, q, r)),
+                _ => {
+                    error!("ecAdd {:?} is not supported", op);
+                    Err(Error::Synthesis)
+                }
+            }
+        });
+        let mul_ops = self
+            .mul_ops
+            .iter()
+            .map(|op| match (to_point(op.p), to_point(op.r)) {
+                (Some(p), Some(r)) if Fr::from_raw(op.s.0) != Fr::from(0) => {
+                    Ok(EccOp::Mul(p, op.s, r))
+                }
+                _ => {
+                    error!("ecMul {:?} is not supported", op);
+                    Err(Error::Synthesis)
+                }
+            });
+
+        add_ops
+            .chain(iter::repeat_with(|| Ok(padding_add.clone())))
+            .take(self.max_add_ops)
+            .chain(
+                mul_ops
+                    .chain(iter::repeat_with(|| Ok(padding_mul.clone())))
+                    .take(self.max_mul_ops),
+            )
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn assign_op(
+        &self,
+        config: &EccCircuitConfig<F>,
+        ctx: &mut RegionCtx<'_, F>,
+        main_gate: &MainGate<F>,
+        range_chip: &RangeChip<F>,
+        ecc_chip: &GeneralEccChip<G1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        op: &EccOp,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<[AssignedCell<F, F>; 8], Error> {
+        let one = main_gate.assign_constant(ctx, F::ONE)?;
+        let word_rlc = |ctx: &mut RegionCtx<'_, F>,
+                        name: &str,
+                        int: &AssignedInteger<_, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+                        word: Word| {
+            let assigned_le = integer_to_bytes_le(ctx, range_chip, int)?;
+            config.assign_word_rlc(
+                ctx,
+                main_gate,
+                name,
+                &assigned_le,
+                word,
+                challenges.evm_word(),
+            )
+        };
+
+        match op {
+            EccOp::Add(p, q, r) => {
+                let op_type =
+                    main_gate.assign_constant(ctx, F::from(PrecompileCalls::Bn128Add.address()))?;
+                let assigned_p = ecc_chip.assign_point(ctx, Value::known(*p))?;
+                let assigned_q = ecc_chip.assign_point(ctx, Value::known(*q))?;
+                let assigned_r = ecc_chip.add(ctx, &assigned_p, &assigned_q)?;
+                let assigned_r = ecc_chip.normalize(ctx, &assigned_r)?;
+                Ok([
+                    one,
+                    op_type,
+                    word_rlc(ctx, "p_x", assigned_p.x(), to_word(p.x.to_bytes()))?,
+                    word_rlc(ctx, "p_y", assigned_p.y(), to_word(p.y.to_bytes()))?,
+                    word_rlc(ctx, "q_x", assigned_q.x(), to_word(q.x.to_bytes()))?,
+                    word_rlc(ctx, "q_y", assigned_q.y(), to_word(q.y.to_bytes()))?,
+                    word_rlc(ctx, "r_x", assigned_r.x(), to_word(r.x.to_bytes()))?,
+                    word_rlc(ctx, "r_y", assigned_r.y(), to_word(r.y.to_bytes()))?,
+                ])
+            }
+            EccOp::Mul(p, s, r) => {
+                let zero = main_gate.assign_constant(ctx, F::ZERO)?;
+                let op_type =
+                    main_gate.assign_constant(ctx, F::from(PrecompileCalls::Bn128Mul.address()))?;
+                let scalar_chip = ecc_chip.scalar_field_chip();
+                let assigned_p = ecc_chip.assign_point(ctx, Value::known(*p))?;
+                // The scalar of the call is any word, the chip multiplies by
+                // its reduction modulo the order of the group.
+                let assigned_s = scalar_chip.assign_integer(
+                    ctx,
+                    ecc_chip.new_unassigned_scalar(Value::known(Fr::from_raw(s.0))),
+                    Range::Remainder,
+                )?;
+                let assigned_r = ecc_chip.mul(ctx, &assigned_p, &assigned_s, self.window_size)?;
+                let assigned_r = ecc_chip.normalize(ctx, &assigned_r)?;
+                let s_le =
+                    config.assign_scalar_bytes_le(ctx, main_gate, range_chip, &assigned_s, *s)?;
+                Ok([
+                    one,
+                    op_type,
+                    word_rlc(ctx, "p_x", assigned_p.x(), to_word(p.x.to_bytes()))?,
+                    word_rlc(ctx, "p_y", assigned_p.y(), to_word(p.y.to_bytes()))?,
+                    config.assign_word_rlc(
+                        ctx,
+                        main_gate,
+                        "s",
+                        &s_le,
+                        *s,
+                        challenges.evm_word(),
+                    )?,
+                    zero,
+                    word_rlc(ctx, "r_x", assigned_r.x(), to_word(r.x.to_bytes()))?,
+                    word_rlc(ctx, "r_y", assigned_r.y(), to_word(r.y.to_bytes()))?,
+                ])
+            }
+        }
+    }
+}
+
+impl<F: Field> SubCircuit<F> for EccCircuit<F> {
+    type Config = EccCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    /// The `block.circuits_params.max_ec_add` and `max_ec_mul` parameters,
+    /// when enabled, set up the circuit to support a fixed number of
+    /// operations, independently of the calls of the block.
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let events = &block.precompile_events;
+        let max = |param: usize, num_ops: usize| if param > 0 { param } else { num_ops };
+        Self::new(
+            max(block.circuits_params.max_ec_add, events.ec_add.len()),
+            max(block.circuits_params.max_ec_mul, events.ec_mul.len()),
+            events.ec_add.clone(),
+            events.ec_mul.clone(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let events = &block.precompile_events;
+        let params = &block.circuits_params;
+        (
+            Self::min_num_rows(events.ec_add.len(), events.ec_mul.len()),
+            Self::min_num_rows(
+                params.max_ec_add.max(events.ec_add.len()),
+                params.max_ec_mul.max(events.ec_mul.len()),
+            ),
+        )
+    }
+
+    /// Make the assignments to the EccCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        for (name, num_ops, max_ops) in [
+            ("add_ops", self.add_ops.len(), self.max_add_ops),
+            ("mul_ops", self.mul_ops.len(), self.max_mul_ops),
+        ] {
+            if num_ops > max_ops {
+                error!("{}.len() = {} > max = {}", name, num_ops, max_ops);
+                return Err(Error::Synthesis);
+            }
+        }
+        // The scalars of the ecMul calls are reduced in the native field.
+        if self.max_mul_ops > 0 && F::MODULUS != Fr::MODULUS {
+            error!("ecMul requires the scalar field of BN254 as native field");
+            return Err(Error::Synthesis);
+        }
+        let ops = self.ops()?;
+
+        let main_gate = MainGate::new(config.main_gate_config.clone());
+        let range_chip = RangeChip::new(config.range_config.clone());
+        let mut ecc_chip = GeneralEccChip::<G1Affine, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+            config.ecc_chip_config(),
+        );
+
+        if !ops.is_empty() {
+            config.load_aux_tables(layouter)?;
+        }
+        if self.max_mul_ops > 0 {
+            layouter.assign_region(
+                || "ecc chip aux",
+                |region| {
+                    let mut ctx = RegionCtx::new(region, 0);
+                    ecc_chip.assign_aux_generator(&mut ctx, Value::known(self.aux_generator))?;
+                    ecc_chip.assign_aux(&mut ctx, self.window_size, 1)?;
+                    log::debug!("ecc chip aux: {} rows", ctx.offset());
+                    Ok(())
+                },
+            )?;
+        }
+
+        let assigned_rows = layouter.assign_region(
+            || "ecc chip operations",
+            |region| {
+                let mut ctx = RegionCtx::new(region, 0);
+                let assigned_rows = ops
+                    .iter()
+                    .map(|op| {
+                        self.assign_op(
+                            config,
+                            &mut ctx,
+                            &main_gate,
+                            &range_chip,
+                            &ecc_chip,
+                            op,
+                            challenges,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                log::debug!("ecc chip operations: {} rows", ctx.offset());
+                Ok(assigned_rows)
+            },
+        )?;
+
+        layouter.assign_region(
+            || "ecc table",
+            |mut region| {
+                let ecc_table_columns =
+                    <EccTable as LookupTable<F>>::advice_columns(&config.ecc_table);
+                for column in ecc_table_columns.iter() {
+                    region.assign_advice(
+                        || "ecc table all-zero row",
+                        *column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                for (idx, assigned_row) in assigned_rows.iter().enumerate() {
+                    for (assigned, column) in assigned_row.iter().zip_eq(ecc_table_columns.iter()) {
+                        assigned.copy_advice(|| "ecc table", &mut region, *column, idx + 1)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn instance(&self) -> Vec<Vec<F>> {
+        // The maingate expects an instance column, but we don't use it, so we return an
+        // "empty" instance column
+        vec![vec![]]
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+use crate::util::{log2_ceil, unusable_rows};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::G1,
+};
+
+#[test]
+fn ecc_circuit_unusable_rows() {
+    assert_eq!(
+        EccCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, EccCircuit::<Fr>>(()),
+    )
+}
+
+fn run<F: Field>(
+    max_ops: (usize, usize),
+    add_ops: Vec<EcAddOp>,
+    mul_ops: Vec<EcMulOp>,
+) -> Result<(), Vec<VerifyFailure>> {
+    let (max_add_ops, max_mul_ops) = max_ops;
+    let k = log2_ceil(
+        EccCircuit::<Fr>::unusable_rows()
+            + EccCircuit::<Fr>::min_num_rows(max_add_ops, max_mul_ops),
+    );
+    // EccChip -> MainGate instance column
+    let circuit = EccCircuit::<F>::new(max_add_ops, max_mul_ops, add_ops, mul_ops);
+
+    let prover = match MockProver::run(k, &circuit, vec![vec![]]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    prover.verify()
+}
+
+fn coordinates(point: G1) -> (Word, Word) {
+    let point = point.to_affine();
+    (to_word(point.x.to_bytes()), to_word(point.y.to_bytes()))
+}
+
+fn add_ops() -> Vec<EcAddOp> {
+    let g = G1::generator();
+    vec![
+        EcAddOp {
+            p: coordinates(g),
+            q: coordinates(g * Fr::from(2)),
+            r: coordinates(g * Fr::from(3)),
+        },
+        EcAddOp {
+            p: coordinates(g * Fr::from(5)),
+            q: coordinates(g * Fr::from(7)),
+            r: coordinates(g * Fr::from(12)),
+        },
+    ]
+}
+
+fn mul_ops() -> Vec<EcMulOp> {
+    let g = G1::generator();
+    vec![EcMulOp {
+        p: coordinates(g * Fr::from(2)),
+        s: Word::from(0x1234),
+        r: coordinates(g * Fr::from(2 * 0x1234)),
+    }]
+}
+
+#[test]
+fn ecc_circuit_no_ops() {
+    assert_eq!(run::<Fr>((0, 0), vec![], vec![]), Ok(()));
+}
+
+#[test]
+fn ecc_circuit_add_mul() {
+    assert_eq!(run::<Fr>((3, 1), add_ops(), mul_ops()), Ok(()));
+}
+
+#[test]
+fn ecc_circuit_unreduced_scalar() {
+    let g = G1::generator();
+    let order = Word::from_str_radix(&Fr::MODULUS[2..], 16).unwrap();
+    let op = EcMulOp {
+        p: coordinates(g),
+        s: order + 7,
+        r: coordinates(g * Fr::from(7)),
+    };
+    assert_eq!(run::<Fr>((0, 1), vec![], vec![op]), Ok(()));
+}
+
+#[test]
+#[should_panic]
+fn ecc_circuit_2add_1max() {
+    let _ = run::<Fr>((1, 0), add_ops(), vec![]);
+}
@@ -23,7 +23,8 @@ pub use crate::witness;
 use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
     table::{
-        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LookupTable, RwTable, TxTable,
+        Blake2fTable, BlockTable, BytecodeTable, CopyTable, EccTable, ExpTable, KeccakTable,
+        LookupTable, ModExpTable, Ripemd160Table, RwTable, Sha256Table, SigTable, TxTable,
     },
     util::{Challenges, SubCircuit, SubCircuitConfig},
 };
@@ -49,6 +50,12 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     exp_table: ExpTable,
+    sig_table: SigTable,
+    sha256_table: Sha256Table,
+    ripemd160_table: Ripemd160Table,
+    ecc_table: EccTable,
+    blake2f_table: Blake2fTable,
+    modexp_table: ModExpTable,
 }
 
 /// Circuit configuration arguments
@@ -69,6 +76,18 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// ExpTable
     pub exp_table: ExpTable,
+    /// SigTable
+    pub sig_table: SigTable,
+    /// Sha256Table
+    pub sha256_table: Sha256Table,
+    /// Ripemd160Table
+    pub ripemd160_table: Ripemd160Table,
+    /// EccTable
+    pub ecc_table: EccTable,
+    /// Blake2fTable
+    pub blake2f_table: Blake2fTable,
+    /// ModExpTable
+    pub modexp_table: ModExpTable,
     /// Taiko
     pub is_taiko: bool,
 }
@@ -88,6 +107,12 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            sig_table,
+            sha256_table,
+            ripemd160_table,
+            ecc_table,
+            blake2f_table,
+            modexp_table,
             is_taiko,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -105,6 +130,12 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &copy_table,
             &keccak_table,
             &exp_table,
+            &sig_table,
+            &sha256_table,
+            &ripemd160_table,
+            &ecc_table,
+            &blake2f_table,
+            &modexp_table,
             is_taiko,
         ));
 
@@ -119,6 +150,12 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         copy_table.annotate_columns(meta);
         keccak_table.annotate_columns(meta);
         exp_table.annotate_columns(meta);
+        sig_table.annotate_columns(meta);
+        sha256_table.annotate_columns(meta);
+        ripemd160_table.annotate_columns(meta);
+        ecc_table.annotate_columns(meta);
+        blake2f_table.annotate_columns(meta);
+        modexp_table.annotate_columns(meta);
 
         Self {
             fixed_table,
@@ -131,6 +168,12 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            sig_table,
+            sha256_table,
+            ripemd160_table,
+            ecc_table,
+            blake2f_table,
+            modexp_table,
         }
     }
 }
@@ -434,6 +477,12 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let keccak_table = KeccakTable::construct(meta);
         let exp_table = ExpTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
+        let sha256_table = Sha256Table::construct(meta);
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        let ecc_table = EccTable::construct(meta);
+        let blake2f_table = Blake2fTable::construct(meta);
+        let modexp_table = ModExpTable::construct(meta);
         let challenges = Challenges::construct(meta);
         let challenges_expr = challenges.exprs(meta);
 
@@ -449,6 +498,12 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     copy_table,
                     keccak_table,
                     exp_table,
+                    sig_table,
+                    sha256_table,
+                    ripemd160_table,
+                    ecc_table,
+                    blake2f_table,
+                    modexp_table,
                     is_taiko,
                 },
             ),
@@ -491,6 +546,37 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             .keccak_table
             .dev_load(&mut layouter, &block.sha3_inputs, &challenges)?;
         config.exp_table.load(&mut layouter, block)?;
+        config.sig_table.dev_load(
+            &mut layouter,
+            &block.precompile_events.ecrecover,
+            &challenges,
+        )?;
+        config.sha256_table.dev_load(
+            &mut layouter,
+            &block.precompile_events.sha256,
+            &challenges,
+        )?;
+        config.ripemd160_table.dev_load(
+            &mut layouter,
+            &block.precompile_events.ripemd160,
+            &challenges,
+        )?;
+        config.ecc_table.dev_load(
+            &mut layouter,
+            &block.precompile_events.ec_add,
+            &block.precompile_events.ec_mul,
+            &challenges,
+        )?;
+        config.blake2f_table.dev_load(
+            &mut layouter,
+            &block.precompile_events.blake2f,
+            &challenges,
+        )?;
+        config.modexp_table.dev_load(
+            &mut layouter,
+            &block.precompile_events.modexp,
+            &challenges,
+        )?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
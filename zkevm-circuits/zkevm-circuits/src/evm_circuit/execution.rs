@@ -1,8 +1,10 @@
 use super::{
     param::{
-        BLOCK_TABLE_LOOKUPS, BYTECODE_TABLE_LOOKUPS, COPY_TABLE_LOOKUPS, EXP_TABLE_LOOKUPS,
-        FIXED_TABLE_LOOKUPS, KECCAK_TABLE_LOOKUPS, N_BYTE_LOOKUPS, N_COPY_COLUMNS,
-        N_PHASE1_COLUMNS, RW_TABLE_LOOKUPS, TX_TABLE_LOOKUPS,
+        BLAKE2F_TABLE_LOOKUPS, BLOCK_TABLE_LOOKUPS, BYTECODE_TABLE_LOOKUPS, COPY_TABLE_LOOKUPS,
+        ECC_TABLE_LOOKUPS, EXP_TABLE_LOOKUPS, FIXED_TABLE_LOOKUPS, KECCAK_TABLE_LOOKUPS,
+        MODEXP_TABLE_LOOKUPS, N_BYTE_LOOKUPS, N_COPY_COLUMNS, N_PHASE1_COLUMNS,
+        RIPEMD160_TABLE_LOOKUPS, RW_TABLE_LOOKUPS, SHA256_TABLE_LOOKUPS, SIG_TABLE_LOOKUPS,
+        TX_TABLE_LOOKUPS,
     },
     step::HasExecutionState,
     util::{instrumentation::Instrument, CachedRegion, CellManager, StoredExpression},
@@ -45,6 +47,8 @@ mod address;
 mod balance;
 mod begin_tx;
 mod bitwise;
+mod blobbasefee;
+mod blobhash;
 mod block_ctx;
 mod blockhash;
 mod byte;
@@ -58,17 +62,24 @@ mod chainid;
 mod codecopy;
 mod codesize;
 mod comparator;
-mod dummy;
+mod create;
 mod dup;
 mod end_block;
 mod end_tx;
+mod error_code_store;
+mod error_invalid_creation_code;
 mod error_invalid_jump;
 mod error_invalid_opcode;
+mod error_oog_account_access;
 mod error_oog_call;
 mod error_oog_constant;
+mod error_oog_create2;
+mod error_oog_dynamic_memory;
 mod error_oog_exp;
 mod error_oog_log;
 mod error_oog_memory_copy;
+mod error_oog_self_destruct;
+mod error_oog_sha3;
 mod error_oog_sload_sstore;
 mod error_oog_static_memory;
 mod error_return_data_oo_bound;
@@ -85,6 +96,7 @@ mod jump;
 mod jumpdest;
 mod jumpi;
 mod logs;
+mod mcopy;
 mod memory;
 mod msize;
 mod mul_div_mod;
@@ -103,6 +115,7 @@ mod returndatasize;
 mod sar;
 mod sdiv_smod;
 mod selfbalance;
+mod selfdestruct;
 mod sha3;
 mod shl_shr;
 mod signed_comparator;
@@ -111,6 +124,8 @@ mod sload;
 mod sstore;
 mod stop;
 mod swap;
+mod tload;
+mod tstore;
 
 use self::sha3::Sha3Gadget;
 use add_sub::AddSubGadget;
@@ -119,6 +134,8 @@ use address::AddressGadget;
 use balance::BalanceGadget;
 use begin_tx::BeginTxGadget;
 use bitwise::BitwiseGadget;
+use blobbasefee::BlobBaseFeeGadget;
+use blobhash::BlobHashGadget;
 use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
 use blockhash::BlockHashGadget;
 use byte::ByteGadget;
@@ -132,18 +149,26 @@ use chainid::ChainIdGadget;
 use codecopy::CodeCopyGadget;
 use codesize::CodesizeGadget;
 use comparator::ComparatorGadget;
-use dummy::DummyGadget;
+use create::CreateGadget;
 use dup::DupGadget;
 use end_block::EndBlockGadget;
 use end_tx::EndTxGadget;
+use error_code_store::ErrorCodeStoreGadget;
+use error_invalid_creation_code::ErrorInvalidCreationCodeGadget;
 use error_invalid_jump::ErrorInvalidJumpGadget;
 use error_invalid_opcode::ErrorInvalidOpcodeGadget;
+use error_oog_account_access::ErrorOOGAccountAccessGadget;
 use error_oog_call::ErrorOOGCallGadget;
 use error_oog_constant::ErrorOOGConstantGadget;
+use error_oog_create2::ErrorOOGCreate2Gadget;
+use error_oog_dynamic_memory::ErrorOOGDynamicMemoryGadget;
 use error_oog_exp::ErrorOOGExpGadget;
 use error_oog_log::ErrorOOGLogGadget;
 use error_oog_memory_copy::ErrorOOGMemoryCopyGadget;
+use error_oog_self_destruct::ErrorOOGSelfDestructGadget;
+use error_oog_sha3::ErrorOOGSha3Gadget;
 use error_oog_sload_sstore::ErrorOOGSloadSstoreGadget;
+use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
 use error_return_data_oo_bound::ErrorReturnDataOutOfBoundGadget;
 use error_stack::ErrorStackGadget;
 use error_write_protection::ErrorWriteProtectionGadget;
@@ -158,6 +183,7 @@ use jump::JumpGadget;
 use jumpdest::JumpdestGadget;
 use jumpi::JumpiGadget;
 use logs::LogGadget;
+use mcopy::McopyGadget;
 use memory::MemoryGadget;
 use msize::MsizeGadget;
 use mul_div_mod::MulDivModGadget;
@@ -166,6 +192,10 @@ use opcode_not::NotGadget;
 use origin::OriginGadget;
 use pc::PcGadget;
 use pop::PopGadget;
+use precompiles::{
+    Blake2fGadget, EcAddGadget, EcMulGadget, EcPairingGadget, EcrecoverGadget, IdentityGadget,
+    ModExpGadget, Ripemd160Gadget, Sha256Gadget,
+};
 use push::PushGadget;
 use push0::DummyPush0Gadget;
 use return_revert::ReturnRevertGadget;
@@ -174,6 +204,7 @@ use returndatasize::ReturnDataSizeGadget;
 use sar::SarGadget;
 use sdiv_smod::SignedDivModGadget;
 use selfbalance::SelfbalanceGadget;
+use selfdestruct::SelfDestructGadget;
 use shl_shr::ShlShrGadget;
 use signed_comparator::SignedComparatorGadget;
 use signextend::SignextendGadget;
@@ -181,6 +212,8 @@ use sload::SloadGadget;
 use sstore::SstoreGadget;
 use stop::StopGadget;
 use swap::SwapGadget;
+use tload::TloadGadget;
+use tstore::TstoreGadget;
 
 pub(crate) trait ExecutionGadget<F: Field> {
     const NAME: &'static str;
@@ -232,6 +265,8 @@ pub struct ExecutionConfig<F> {
     address_gadget: Box<AddressGadget<F>>,
     balance_gadget: Box<BalanceGadget<F>>,
     bitwise_gadget: Box<BitwiseGadget<F>>,
+    blobbasefee_gadget: Box<BlobBaseFeeGadget<F>>,
+    blobhash_gadget: Box<BlobHashGadget<F>>,
     byte_gadget: Box<ByteGadget<F>>,
     call_op_gadget: Box<CallOpGadget<F>>,
     call_value_gadget: Box<CallValueGadget<F>>,
@@ -255,6 +290,7 @@ pub struct ExecutionConfig<F> {
     jumpdest_gadget: Box<JumpdestGadget<F>>,
     jumpi_gadget: Box<JumpiGadget<F>>,
     log_gadget: Box<LogGadget<F>>,
+    mcopy_gadget: Box<McopyGadget<F>>,
     memory_gadget: Box<MemoryGadget<F>>,
     msize_gadget: Box<MsizeGadget<F>>,
     mul_div_mod_gadget: Box<MulDivModGadget<F>>,
@@ -273,15 +309,17 @@ pub struct ExecutionConfig<F> {
     shl_shr_gadget: Box<ShlShrGadget<F>>,
     returndatasize_gadget: Box<ReturnDataSizeGadget<F>>,
     returndatacopy_gadget: Box<ReturnDataCopyGadget<F>>,
-    create_gadget: Box<DummyGadget<F, 3, 1, { ExecutionState::CREATE }>>,
-    create2_gadget: Box<DummyGadget<F, 4, 1, { ExecutionState::CREATE2 }>>,
-    selfdestruct_gadget: Box<DummyGadget<F, 1, 0, { ExecutionState::SELFDESTRUCT }>>,
+    create_gadget: Box<CreateGadget<F, false>>,
+    create2_gadget: Box<CreateGadget<F, true>>,
+    selfdestruct_gadget: Box<SelfDestructGadget<F>>,
     signed_comparator_gadget: Box<SignedComparatorGadget<F>>,
     signextend_gadget: Box<SignextendGadget<F>>,
     sload_gadget: Box<SloadGadget<F>>,
     sstore_gadget: Box<SstoreGadget<F>>,
     stop_gadget: Box<StopGadget<F>>,
     swap_gadget: Box<SwapGadget<F>>,
+    tload_gadget: Box<TloadGadget<F>>,
+    tstore_gadget: Box<TstoreGadget<F>>,
     blockhash_gadget: Box<BlockHashGadget<F>>,
     block_ctx_u64_gadget: Box<BlockCtxU64Gadget<F>>,
     block_ctx_u160_gadget: Box<BlockCtxU160Gadget<F>>,
@@ -292,29 +330,31 @@ pub struct ExecutionConfig<F> {
     error_oog_exp: Box<ErrorOOGExpGadget<F>>,
     error_oog_memory_copy: Box<ErrorOOGMemoryCopyGadget<F>>,
     error_oog_sload_sstore: Box<ErrorOOGSloadSstoreGadget<F>>,
-    error_oog_static_memory_gadget:
-        Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasStaticMemoryExpansion }>>,
+    error_oog_static_memory_gadget: Box<ErrorOOGStaticMemoryGadget<F>>,
     error_stack: Box<ErrorStackGadget<F>>,
     error_write_protection: Box<ErrorWriteProtectionGadget<F>>,
-    error_oog_dynamic_memory_gadget:
-        Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasDynamicMemoryExpansion }>>,
+    error_oog_dynamic_memory_gadget: Box<ErrorOOGDynamicMemoryGadget<F>>,
     error_oog_log: Box<ErrorOOGLogGadget<F>>,
-    error_oog_account_access:
-        Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasAccountAccess }>>,
-    error_oog_sha3: Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSHA3 }>>,
-    error_oog_ext_codecopy: Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasEXTCODECOPY }>>,
-    error_oog_create2: Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasCREATE2 }>>,
-    error_oog_self_destruct:
-        Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSELFDESTRUCT }>>,
-    error_oog_code_store: Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasCodeStore }>>,
+    error_oog_account_access: Box<ErrorOOGAccountAccessGadget<F>>,
+    error_oog_sha3: Box<ErrorOOGSha3Gadget<F>>,
+    error_oog_create2: Box<ErrorOOGCreate2Gadget<F>>,
+    error_oog_self_destruct: Box<ErrorOOGSelfDestructGadget<F>>,
+    error_oog_code_store: Box<ErrorCodeStoreGadget<F, false>>,
+    error_max_code_size_exceeded: Box<ErrorCodeStoreGadget<F, true>>,
     error_invalid_jump: Box<ErrorInvalidJumpGadget<F>>,
     error_invalid_opcode: Box<ErrorInvalidOpcodeGadget<F>>,
-    error_depth: Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorDepth }>>,
-    error_contract_address_collision:
-        Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorContractAddressCollision }>>,
-    error_invalid_creation_code:
-        Box<DummyGadget<F, 0, 0, { ExecutionState::ErrorInvalidCreationCode }>>,
+    error_invalid_creation_code: Box<ErrorInvalidCreationCodeGadget<F>>,
     error_return_data_out_of_bound: Box<ErrorReturnDataOutOfBoundGadget<F>>,
+    // precompiles
+    precompile_ecrecover_gadget: Box<EcrecoverGadget<F>>,
+    precompile_sha256_gadget: Box<Sha256Gadget<F>>,
+    precompile_ripemd160_gadget: Box<Ripemd160Gadget<F>>,
+    precompile_identity_gadget: Box<IdentityGadget<F>>,
+    precompile_modexp_gadget: Box<ModExpGadget<F>>,
+    precompile_bn256_add_gadget: Box<EcAddGadget<F>>,
+    precompile_bn256_scalar_mul_gadget: Box<EcMulGadget<F>>,
+    precompile_bn256_pairing_gadget: Box<EcPairingGadget<F>>,
+    precompile_blake2f_gadget: Box<Blake2fGadget<F>>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
@@ -332,6 +372,12 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        sig_table: &dyn LookupTable<F>,
+        sha256_table: &dyn LookupTable<F>,
+        ripemd160_table: &dyn LookupTable<F>,
+        ecc_table: &dyn LookupTable<F>,
+        blake2f_table: &dyn LookupTable<F>,
+        modexp_table: &dyn LookupTable<F>,
         is_taiko: bool,
     ) -> Self {
         let mut instrument = Instrument::default();
@@ -500,6 +546,8 @@ impl<F: Field> ExecutionConfig<F> {
             add_sub_gadget: configure_gadget!(),
             addmod_gadget: configure_gadget!(),
             bitwise_gadget: configure_gadget!(),
+            blobbasefee_gadget: configure_gadget!(),
+            blobhash_gadget: configure_gadget!(),
             byte_gadget: configure_gadget!(),
             call_op_gadget: configure_gadget!(),
             call_value_gadget: configure_gadget!(),
@@ -521,6 +569,7 @@ impl<F: Field> ExecutionConfig<F> {
             jumpdest_gadget: configure_gadget!(),
             jumpi_gadget: configure_gadget!(),
             log_gadget: configure_gadget!(),
+            mcopy_gadget: configure_gadget!(),
             memory_gadget: configure_gadget!(),
             msize_gadget: configure_gadget!(),
             mul_div_mod_gadget: configure_gadget!(),
@@ -553,6 +602,8 @@ impl<F: Field> ExecutionConfig<F> {
             sstore_gadget: configure_gadget!(),
             stop_gadget: configure_gadget!(),
             swap_gadget: configure_gadget!(),
+            tload_gadget: configure_gadget!(),
+            tstore_gadget: configure_gadget!(),
             block_ctx_u64_gadget: configure_gadget!(),
             block_ctx_u160_gadget: configure_gadget!(),
             block_ctx_u256_gadget: configure_gadget!(),
@@ -567,18 +618,26 @@ impl<F: Field> ExecutionConfig<F> {
             error_oog_memory_copy: configure_gadget!(),
             error_oog_account_access: configure_gadget!(),
             error_oog_sha3: configure_gadget!(),
-            error_oog_ext_codecopy: configure_gadget!(),
             error_oog_exp: configure_gadget!(),
             error_oog_create2: configure_gadget!(),
             error_oog_self_destruct: configure_gadget!(),
             error_oog_code_store: configure_gadget!(),
+            error_max_code_size_exceeded: configure_gadget!(),
             error_invalid_jump: configure_gadget!(),
             error_invalid_opcode: configure_gadget!(),
             error_write_protection: configure_gadget!(),
-            error_depth: configure_gadget!(),
-            error_contract_address_collision: configure_gadget!(),
             error_invalid_creation_code: configure_gadget!(),
             error_return_data_out_of_bound: configure_gadget!(),
+            // precompiles
+            precompile_ecrecover_gadget: configure_gadget!(),
+            precompile_sha256_gadget: configure_gadget!(),
+            precompile_ripemd160_gadget: configure_gadget!(),
+            precompile_identity_gadget: configure_gadget!(),
+            precompile_modexp_gadget: configure_gadget!(),
+            precompile_bn256_add_gadget: configure_gadget!(),
+            precompile_bn256_scalar_mul_gadget: configure_gadget!(),
+            precompile_bn256_pairing_gadget: configure_gadget!(),
+            precompile_blake2f_gadget: configure_gadget!(),
             // step and presets
             step: step_curr,
             height_map,
@@ -597,6 +656,12 @@ impl<F: Field> ExecutionConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            sig_table,
+            sha256_table,
+            ripemd160_table,
+            ecc_table,
+            blake2f_table,
+            modexp_table,
             &challenges,
             &cell_manager,
         );
@@ -816,6 +881,12 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        sig_table: &dyn LookupTable<F>,
+        sha256_table: &dyn LookupTable<F>,
+        ripemd160_table: &dyn LookupTable<F>,
+        ecc_table: &dyn LookupTable<F>,
+        blake2f_table: &dyn LookupTable<F>,
+        modexp_table: &dyn LookupTable<F>,
         challenges: &Challenges<Expression<F>>,
         cell_manager: &CellManager<F>,
     ) {
@@ -832,6 +903,12 @@ impl<F: Field> ExecutionConfig<F> {
                         Table::Copy => copy_table,
                         Table::Keccak => keccak_table,
                         Table::Exp => exp_table,
+                        Table::Sig => sig_table,
+                        Table::Sha256 => sha256_table,
+                        Table::Ripemd160 => ripemd160_table,
+                        Table::Ecc => ecc_table,
+                        Table::Blake2f => blake2f_table,
+                        Table::ModExp => modexp_table,
                     }
                     .table_exprs(meta);
                     vec![(
@@ -1045,6 +1122,12 @@ impl<F: Field> ExecutionConfig<F> {
             ("EVM_lookup_copy", COPY_TABLE_LOOKUPS),
             ("EVM_lookup_keccak", KECCAK_TABLE_LOOKUPS),
             ("EVM_lookup_exp", EXP_TABLE_LOOKUPS),
+            ("EVM_lookup_sig", SIG_TABLE_LOOKUPS),
+            ("EVM_lookup_sha256", SHA256_TABLE_LOOKUPS),
+            ("EVM_lookup_ripemd160", RIPEMD160_TABLE_LOOKUPS),
+            ("EVM_lookup_ecc", ECC_TABLE_LOOKUPS),
+            ("EVM_lookup_blake2f", BLAKE2F_TABLE_LOOKUPS),
+            ("EVM_lookup_modexp", MODEXP_TABLE_LOOKUPS),
             ("EVM_adv_phase2", N_PHASE2_COLUMNS),
             ("EVM_copy", N_COPY_COLUMNS),
             ("EVM_lookup_byte", N_BYTE_LOOKUPS),
@@ -1187,6 +1270,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ADDRESS => assign_exec_step!(self.address_gadget),
             ExecutionState::BALANCE => assign_exec_step!(self.balance_gadget),
             ExecutionState::BITWISE => assign_exec_step!(self.bitwise_gadget),
+            ExecutionState::BLOBBASEFEE => assign_exec_step!(self.blobbasefee_gadget),
+            ExecutionState::BLOBHASH => assign_exec_step!(self.blobhash_gadget),
             ExecutionState::BYTE => assign_exec_step!(self.byte_gadget),
             ExecutionState::CALL_OP => assign_exec_step!(self.call_op_gadget),
             ExecutionState::CALLDATACOPY => assign_exec_step!(self.calldatacopy_gadget),
@@ -1198,6 +1283,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::CODECOPY => assign_exec_step!(self.codecopy_gadget),
             ExecutionState::CODESIZE => assign_exec_step!(self.codesize_gadget),
             ExecutionState::CMP => assign_exec_step!(self.comparator_gadget),
+            ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
+            ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
             ExecutionState::DUP => assign_exec_step!(self.dup_gadget),
             ExecutionState::EXP => assign_exec_step!(self.exp_gadget),
             ExecutionState::EXTCODEHASH => assign_exec_step!(self.extcodehash_gadget),
@@ -1209,6 +1296,7 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::JUMPDEST => assign_exec_step!(self.jumpdest_gadget),
             ExecutionState::JUMPI => assign_exec_step!(self.jumpi_gadget),
             ExecutionState::LOG => assign_exec_step!(self.log_gadget),
+            ExecutionState::MCOPY => assign_exec_step!(self.mcopy_gadget),
             ExecutionState::MEMORY => assign_exec_step!(self.memory_gadget),
             ExecutionState::MSIZE => assign_exec_step!(self.msize_gadget),
             ExecutionState::MUL_DIV_MOD => assign_exec_step!(self.mul_div_mod_gadget),
@@ -1231,8 +1319,6 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::SELFBALANCE => assign_exec_step!(self.selfbalance_gadget),
             // dummy gadgets
             ExecutionState::EXTCODECOPY => assign_exec_step!(self.extcodecopy_gadget),
-            ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
-            ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
             ExecutionState::SELFDESTRUCT => assign_exec_step!(self.selfdestruct_gadget),
             // end of dummy gadgets
             ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
@@ -1242,6 +1328,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::SSTORE => assign_exec_step!(self.sstore_gadget),
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::SWAP => assign_exec_step!(self.swap_gadget),
+            ExecutionState::TLOAD => assign_exec_step!(self.tload_gadget),
+            ExecutionState::TSTORE => assign_exec_step!(self.tstore_gadget),
             // dummy errors
             ExecutionState::ErrorOutOfGasStaticMemoryExpansion => {
                 assign_exec_step!(self.error_oog_static_memory_gadget)
@@ -1270,9 +1358,6 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorOutOfGasSHA3 => {
                 assign_exec_step!(self.error_oog_sha3)
             }
-            ExecutionState::ErrorOutOfGasEXTCODECOPY => {
-                assign_exec_step!(self.error_oog_ext_codecopy)
-            }
             ExecutionState::ErrorOutOfGasEXP => {
                 assign_exec_step!(self.error_oog_exp)
             }
@@ -1286,13 +1371,13 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorOutOfGasCodeStore => {
                 assign_exec_step!(self.error_oog_code_store)
             }
+            ExecutionState::ErrorMaxCodeSizeExceeded => {
+                assign_exec_step!(self.error_max_code_size_exceeded)
+            }
             ExecutionState::ErrorStack => {
                 assign_exec_step!(self.error_stack)
             }
 
-            ExecutionState::ErrorInsufficientBalance => {
-                assign_exec_step!(self.call_op_gadget)
-            }
             ExecutionState::ErrorInvalidJump => {
                 assign_exec_step!(self.error_invalid_jump)
             }
@@ -1302,18 +1387,40 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorWriteProtection => {
                 assign_exec_step!(self.error_write_protection)
             }
-            ExecutionState::ErrorDepth => {
-                assign_exec_step!(self.error_depth)
-            }
-            ExecutionState::ErrorContractAddressCollision => {
-                assign_exec_step!(self.error_contract_address_collision)
-            }
             ExecutionState::ErrorInvalidCreationCode => {
                 assign_exec_step!(self.error_invalid_creation_code)
             }
             ExecutionState::ErrorReturnDataOutOfBound => {
                 assign_exec_step!(self.error_return_data_out_of_bound)
             }
+            // precompiles
+            ExecutionState::PrecompileEcRecover => {
+                assign_exec_step!(self.precompile_ecrecover_gadget)
+            }
+            ExecutionState::PrecompileSha256 => {
+                assign_exec_step!(self.precompile_sha256_gadget)
+            }
+            ExecutionState::PrecompileRipemd160 => {
+                assign_exec_step!(self.precompile_ripemd160_gadget)
+            }
+            ExecutionState::PrecompileIdentity => {
+                assign_exec_step!(self.precompile_identity_gadget)
+            }
+            ExecutionState::PrecompileBn256Add => {
+                assign_exec_step!(self.precompile_bn256_add_gadget)
+            }
+            ExecutionState::PrecompileBn256ScalarMul => {
+                assign_exec_step!(self.precompile_bn256_scalar_mul_gadget)
+            }
+            ExecutionState::PrecompileBn256Pairing => {
+                assign_exec_step!(self.precompile_bn256_pairing_gadget)
+            }
+            ExecutionState::PrecompileBlake2f => {
+                assign_exec_step!(self.precompile_blake2f_gadget)
+            }
+            ExecutionState::PrecompileBigModExp => {
+                assign_exec_step!(self.precompile_modexp_gadget)
+            }
 
             unimpl_state => evm_unimplemented!("unimplemented ExecutionState: {:?}", unimpl_state),
         }
@@ -260,6 +260,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 0.expr(),
                 Some(&mut reversion_info),
             );
+            cb.tx_created_account_write(
+                tx_id.expr(),
+                call_callee_address.expr(),
+                1.expr(),
+                0.expr(),
+                Some(&mut reversion_info),
+            );
             for (field_tag, value) in [
                 (CallContextFieldTag::Depth, 1.expr()),
                 (CallContextFieldTag::CallerAddress, tx_caller_address.expr()),
@@ -288,7 +295,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                // 21 + a reads and writes:
+                // 23 + a reads and writes:
                 //   - Write CallContext TxId
                 //   - Write CallContext RwCounterEndOfReversion
                 //   - Write CallContext IsPersistent
@@ -299,6 +306,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write TxAccessListAccount (Coinbase) for EIP-3651
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
+                //   - Write TxCreatedAccount (Callee) (Reversible)
                 //   - Write CallContext Depth
                 //   - Write CallContext CallerAddress
                 //   - Write CallContext CalleeAddress
@@ -312,16 +320,17 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsRoot
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
-                rw_counter: Delta(22.expr() + transfer_with_gas_fee.rw_delta()),
+                rw_counter: Delta(23.expr() + transfer_with_gas_fee.rw_delta()),
                 call_id: To(call_id.expr()),
                 is_root: To(true.expr()),
                 is_create: To(tx_is_create.expr()),
                 code_hash: To(cb.curr.state.code_hash.expr()),
                 gas_left: To(gas_left.clone()),
-                // There are a + 1 reversible writes:
+                // There are a + 2 reversible writes:
                 //  - a TransferWithGasFeeGadget
                 //  - Callee Account Nonce
-                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 1.expr()),
+                //  - Callee TxCreatedAccount
+                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 2.expr()),
                 log_id: To(0.expr()),
                 ..StepStateTransition::new_context()
             });
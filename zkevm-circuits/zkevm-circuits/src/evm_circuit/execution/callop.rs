@@ -1,14 +1,14 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_U64},
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_U64},
         step::ExecutionState,
         util::{
             and,
             common_gadget::{CommonCallGadget, TransferGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
-                Transition::{Delta, To},
+                Transition::{Any, Delta, To},
             },
             math_gadget::{
                 ConstantDivisionGadget, IsZeroGadget, LtGadget, LtWordGadget, MinMaxGadget,
@@ -22,7 +22,7 @@ use crate::{
     table::{AccountFieldTag, CallContextFieldTag},
     util::Expr,
 };
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, precompile::is_precompiled};
+use bus_mapping::{evm::OpcodeId, precompile::is_precompiled};
 use eth_types::{
     evm_types::GAS_STIPEND_CALL_WITH_VALUE, Field, ToAddress, ToLittleEndian, ToScalar, U256,
 };
@@ -62,9 +62,6 @@ pub(crate) struct CallOpGadget<F> {
     is_code_address_zero: IsZeroGadget<F>,
     is_precompile_lt: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
     precompile_gadget: PrecompileGadget<F>,
-    precompile_return_length: Cell<F>,
-    precompile_return_length_zero: IsZeroGadget<F>,
-    return_data_copy_size: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
 }
 
 impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
@@ -192,14 +189,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             not::expr(is_code_address_zero.expr()),
             is_precompile_lt.expr(),
         ]);
-        let precompile_return_length = cb.query_cell();
-        let precompile_return_length_zero =
-            IsZeroGadget::construct(cb, precompile_return_length.expr());
-        let return_data_copy_size = MinMaxGadget::construct(
-            cb,
-            precompile_return_length.expr(),
-            call_gadget.rd_address.length(),
-        );
 
         // Verify transfer only for CALL opcode in the successful case.  If value == 0,
         // skip the transfer (this is necessary for non-existing accounts, which
@@ -254,10 +243,53 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             select::expr(is_call.expr() + is_callcode.expr(), 6.expr(), 5.expr());
         let memory_expansion = call_gadget.memory_expansion.clone();
 
+        // For CALL opcode, it has an extra stack pop `value` (+1) and if the value is
+        // not zero, two account write for `transfer` call (+2).
+        //
+        // For CALLCODE opcode, it has an extra stack pop `value` and one account read
+        // for caller balance (+2).
+        //
+        // For DELEGATECALL opcode, it has two extra call context lookups for current
+        // caller address and value (+2).
+        //
+        // No extra lookups for STATICCALL opcode.
+        let transfer_rwc_delta =
+            is_call.expr() * not::expr(transfer.value_is_zero.expr()) * 2.expr();
+        let rw_counter_extra = is_call.expr() * 1.expr()
+            + transfer_rwc_delta.clone()
+            + is_callcode.expr()
+            + is_delegatecall.expr() * 2.expr();
+
         // handle precompile calls.
         let precompile_gadget = cb.condition(
             and::expr([is_precompile.expr(), is_precheck_ok.expr()]),
             |cb| {
+                // Save caller's call state
+                for (field_tag, value) in [
+                    (
+                        CallContextFieldTag::ProgramCounter,
+                        cb.curr.state.program_counter.expr() + 1.expr(),
+                    ),
+                    (
+                        CallContextFieldTag::StackPointer,
+                        cb.curr.state.stack_pointer.expr() + stack_pointer_delta.expr(),
+                    ),
+                    (
+                        CallContextFieldTag::GasLeft,
+                        cb.curr.state.gas_left.expr() - gas_cost.clone() - callee_gas_left.clone(),
+                    ),
+                    (
+                        CallContextFieldTag::MemorySize,
+                        memory_expansion.next_memory_word_size(),
+                    ),
+                    (
+                        CallContextFieldTag::ReversibleWriteCounter,
+                        cb.curr.state.reversible_write_counter.expr() + 1.expr(),
+                    ),
+                ] {
+                    cb.call_context_lookup(true.expr(), None, field_tag, value);
+                }
+
                 // Write to callee's context.
                 for (field_tag, value) in [
                     (
@@ -294,75 +326,29 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     );
                 }
 
-                // Save caller's call state
-                for (field_tag, value) in [
-                    (CallContextFieldTag::LastCalleeId, callee_call_id.expr()),
-                    (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
-                    (
-                        CallContextFieldTag::LastCalleeReturnDataLength,
-                        precompile_return_length.expr(),
-                    ),
-                ] {
-                    cb.call_context_lookup(true.expr(), None, field_tag, value);
-                }
+                // The precompile runs in its own step with a fresh context, which returns to
+                // the caller once it is done.
+                let precompile_gadget =
+                    PrecompileGadget::construct(cb, call_gadget.callee_address_expr());
 
-                // copy table lookup to verify the copying of bytes:
-                // - from caller's memory (`call_data_length` bytes starting at `call_data_offset`)
-                // - to the current call's memory (`call_data_length` bytes starting at `0`).
-                cb.condition(call_gadget.cd_address.has_length(), |cb| {
-                    cb.copy_table_lookup(
-                        cb.curr.state.call_id.expr(),
-                        CopyDataType::Memory.expr(),
-                        callee_call_id.expr(),
-                        CopyDataType::Memory.expr(),
-                        call_gadget.cd_address.offset(),
-                        call_gadget.cd_address.address(),
-                        0.expr(),
-                        call_gadget.cd_address.length(),
-                        0.expr(),
-                        2.expr() * call_gadget.cd_address.length(), // reads + writes
-                    );
+                // Give gas stipend if value is not zero
+                let callee_gas_left = callee_gas_left.clone()
+                    + call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr();
+
+                cb.require_step_state_transition(StepStateTransition {
+                    rw_counter: Delta(30.expr() + rw_counter_extra.clone()),
+                    call_id: To(callee_call_id.expr()),
+                    is_root: To(false.expr()),
+                    is_create: To(false.expr()),
+                    code_hash: Any,
+                    gas_left: To(callee_gas_left),
+                    // For CALL opcode, `transfer` invocation has two account write if value is not
+                    // zero.
+                    reversible_write_counter: To(transfer_rwc_delta.clone()),
+                    ..StepStateTransition::new_context()
                 });
 
-                // copy table lookup to verify the copying of bytes if the precompile call was
-                // successful.
-                // - from precompile call's memory (min(rd_length, precompile_return_length) bytes
-                //   starting at `0`)
-                // - to caller's memory (min(rd_length, precompile_return_length) bytes starting at
-                //   `return_data_offset`).
-                cb.condition(
-                    and::expr([
-                        call_gadget.is_success.expr(),
-                        call_gadget.cd_address.has_length(),
-                        call_gadget.rd_address.has_length(),
-                        not::expr(precompile_return_length_zero.expr()),
-                    ]),
-                    |cb| {
-                        cb.copy_table_lookup(
-                            callee_call_id.expr(),
-                            CopyDataType::Memory.expr(),
-                            cb.curr.state.call_id.expr(),
-                            CopyDataType::Memory.expr(),
-                            0.expr(),
-                            return_data_copy_size.min(),
-                            call_gadget.rd_address.offset(),
-                            return_data_copy_size.min(),
-                            0.expr(),
-                            2.expr() * return_data_copy_size.min(), // reads + writes
-                        );
-                    },
-                );
-
-                PrecompileGadget::construct(
-                    cb,
-                    call_gadget.is_success.expr(),
-                    call_gadget.callee_address_expr(),
-                    cb.curr.state.call_id.expr(),
-                    call_gadget.cd_address.offset(),
-                    call_gadget.cd_address.length(),
-                    call_gadget.rd_address.offset(),
-                    call_gadget.rd_address.length(),
-                )
+                precompile_gadget
             },
         );
 
@@ -383,25 +369,8 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
                 }
 
-                // For CALL opcode, it has an extra stack pop `value` (+1) and if the value is
-                // not zero, two account write for `transfer` call (+2).
-                //
-                // For CALLCODE opcode, it has an extra stack pop `value` and one account read
-                // for caller balance (+2).
-                //
-                // For DELEGATECALL opcode, it has two extra call context lookups for current
-                // caller address and value (+2).
-                //
-                // No extra lookups for STATICCALL opcode.
-                let transfer_rwc_delta =
-                    is_call.expr() * not::expr(transfer.value_is_zero.expr()) * 2.expr();
-                let rw_counter_delta = 21.expr()
-                    + is_call.expr() * 1.expr()
-                    + transfer_rwc_delta.clone()
-                    + is_callcode.expr()
-                    + is_delegatecall.expr() * 2.expr();
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(rw_counter_delta),
+                    rw_counter: Delta(21.expr() + rw_counter_extra.clone()),
                     program_counter: Delta(1.expr()),
                     stack_pointer: Delta(stack_pointer_delta.expr()),
                     gas_left: Delta(
@@ -411,7 +380,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                     memory_word_size: To(memory_expansion.next_memory_word_size()),
                     // For CALL opcode, `transfer` invocation has two account write if value is not
                     // zero.
-                    reversible_write_counter: Delta(1.expr() + transfer_rwc_delta),
+                    reversible_write_counter: Delta(1.expr() + transfer_rwc_delta.clone()),
                     ..StepStateTransition::default()
                 });
             },
@@ -527,25 +496,8 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                 let callee_gas_left = callee_gas_left
                     + call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr();
 
-                // For CALL opcode, it has an extra stack pop `value` (+1) and if the value is
-                // not zero, two account write for `transfer` call (+2).
-                //
-                // For CALLCODE opcode, it has an extra stack pop `value` and one account read
-                // for caller balance (+2).
-                //
-                // For DELEGATECALL opcode, it has two extra call context lookups for current
-                // caller address and value (+2).
-                //
-                // No extra lookups for STATICCALL opcode.
-                let transfer_rwc_delta =
-                    is_call.expr() * not::expr(transfer.value_is_zero.expr()) * 2.expr();
-                let rw_counter_delta = 41.expr()
-                    + is_call.expr() * 1.expr()
-                    + transfer_rwc_delta.clone()
-                    + is_callcode.expr()
-                    + is_delegatecall.expr() * 2.expr();
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(rw_counter_delta),
+                    rw_counter: Delta(41.expr() + rw_counter_extra),
                     call_id: To(callee_call_id.expr()),
                     is_root: To(false.expr()),
                     is_create: To(false.expr()),
@@ -586,9 +538,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             is_code_address_zero,
             is_precompile_lt,
             precompile_gadget,
-            precompile_return_length,
-            precompile_return_length_zero,
-            return_data_copy_size,
         }
     }
 
@@ -783,33 +732,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                 callee_address.to_address().0[19].into(),
             )?;
         }
-        let precompile_return_length = if is_precompiled(&callee_address.to_address()) {
-            let value_rw = block.get_rws(step, 27 + rw_offset);
-            assert_eq!(
-                value_rw.field_tag(),
-                Some(CallContextFieldTag::LastCalleeReturnDataLength as u64),
-            );
-            value_rw.call_context_value()
-        } else {
-            0.into()
-        };
-        self.precompile_return_length.assign(
-            region,
-            offset,
-            Value::known(precompile_return_length.to_scalar().unwrap()),
-        )?;
-        self.precompile_return_length_zero.assign(
-            region,
-            offset,
-            precompile_return_length.to_scalar().unwrap(),
-        )?;
-        self.return_data_copy_size.assign(
-            region,
-            offset,
-            precompile_return_length.to_scalar().unwrap(),
-            rd_length.to_scalar().unwrap(),
-        )?;
-
         Ok(())
     }
 }
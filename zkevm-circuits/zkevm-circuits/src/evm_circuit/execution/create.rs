@@ -0,0 +1,825 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{
+            N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64,
+            N_BYTES_WORD,
+        },
+        step::ExecutionState,
+        util::{
+            and,
+            common_gadget::TransferGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            math_gadget::{
+                ConstantDivisionGadget, ContractCreateGadget, IsEqualGadget, IsZeroGadget,
+                LtGadget, LtWordGadget,
+            },
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget, MemoryWordSizeGadget},
+            not, or, CachedRegion, Cell, StepRws, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, state_db::CodeDB};
+use eth_types::{
+    evm_types::{GasCost, INIT_CODE_WORD_GAS},
+    Field, ToAddress, ToBigEndian, ToLittleEndian, ToScalar, ToWord, U256,
+};
+use ethers_core::utils::{keccak256, rlp};
+use gadgets::util::expr_from_bytes;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for CREATE and CREATE2 opcodes, covering the successful case as well
+/// as the failures detected before the init code runs (depth, insufficient
+/// balance and contract address collision).
+#[derive(Clone, Debug)]
+pub(crate) struct CreateGadget<F, const IS_CREATE2: bool> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    is_static: Cell<F>,
+    depth: Cell<F>,
+    caller_address: Cell<F>,
+
+    value: Word<F>,
+    init_code: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    init_code_word_size: MemoryWordSizeGadget<F>,
+    // Keccak256 hash of the address derivation input, in little-endian bytes.
+    // The contract address is its lowest 20 bytes.
+    keccak_output: [Cell<F>; N_BYTES_WORD],
+    is_success: Cell<F>,
+
+    caller_balance: Word<F>,
+    is_insufficient_balance: LtWordGadget<F>,
+    is_depth_ok: LtGadget<F, N_BYTES_U64>,
+
+    was_warm: Cell<F>,
+    prev_code_hash: Cell<F>,
+    prev_code_hash_is_zero: IsZeroGadget<F>,
+    prev_code_hash_is_empty: IsEqualGadget<F>,
+    callee_nonce: Cell<F>,
+    callee_nonce_is_zero: IsZeroGadget<F>,
+
+    callee_reversion_info: ReversionInfo<F>,
+    transfer: TransferGadget<F>,
+    callee_was_created: Cell<F>,
+    create: ContractCreateGadget<F, IS_CREATE2>,
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+}
+
+impl<F: Field, const IS_CREATE2: bool> ExecutionGadget<F> for CreateGadget<F, IS_CREATE2> {
+    const NAME: &'static str = if IS_CREATE2 { "CREATE2" } else { "CREATE" };
+
+    const EXECUTION_STATE: ExecutionState = if IS_CREATE2 {
+        ExecutionState::CREATE2
+    } else {
+        ExecutionState::CREATE
+    };
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_equal(
+            "Opcode is CREATE or CREATE2",
+            opcode.expr(),
+            if IS_CREATE2 {
+                OpcodeId::CREATE2
+            } else {
+                OpcodeId::CREATE
+            }
+            .expr(),
+        );
+
+        // Use rw_counter of the step which triggers next call as its call_id.
+        let callee_call_id = cb.curr.state.rw_counter.clone();
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let mut reversion_info = cb.reversion_info_read(None);
+        let [is_static, depth, caller_address] = [
+            CallContextFieldTag::IsStatic,
+            CallContextFieldTag::Depth,
+            CallContextFieldTag::CalleeAddress,
+        ]
+        .map(|field_tag| cb.call_context(None, field_tag));
+        cb.require_zero("CREATE must not be in static call stack", is_static.expr());
+
+        let create = ContractCreateGadget::construct(cb);
+        cb.require_equal(
+            "caller address equivalence",
+            caller_address.expr(),
+            create.caller_address(),
+        );
+
+        let value = cb.query_word_rlc();
+        let init_code_offset = cb.query_cell_phase2();
+        let init_code_length = cb.query_word_rlc();
+        cb.stack_pop(value.expr());
+        cb.stack_pop(init_code_offset.expr());
+        cb.stack_pop(init_code_length.expr());
+        if IS_CREATE2 {
+            cb.stack_pop(create.salt_word_rlc(cb));
+        }
+        let init_code = MemoryAddressGadget::construct(cb, init_code_offset, init_code_length);
+
+        let keccak_output: [Cell<F>; N_BYTES_WORD] = array_init::array_init(|_| cb.query_byte());
+        let contract_address = expr_from_bytes(&keccak_output[0..N_BYTES_ACCOUNT_ADDRESS]);
+        let contract_address_rlc = cb.word_rlc::<N_BYTES_ACCOUNT_ADDRESS>(
+            keccak_output[0..N_BYTES_ACCOUNT_ADDRESS]
+                .iter()
+                .map(Expr::expr)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        );
+        let is_success = cb.query_bool();
+        cb.stack_push(is_success.expr() * contract_address_rlc);
+
+        let caller_balance = cb.query_word_rlc();
+        cb.account_read(
+            caller_address.expr(),
+            AccountFieldTag::Balance,
+            caller_balance.expr(),
+        );
+        let is_insufficient_balance = LtWordGadget::construct(cb, &caller_balance, &value);
+        // depth < 1025
+        let is_depth_ok = LtGadget::construct(cb, depth.expr(), 1025.expr());
+        let is_precheck_ok = and::expr([
+            is_depth_ok.expr(),
+            not::expr(is_insufficient_balance.expr()),
+        ]);
+
+        let was_warm = cb.query_bool();
+        let prev_code_hash = cb.query_cell_phase2();
+        let callee_nonce = cb.query_cell();
+        cb.condition(is_precheck_ok.expr(), |cb| {
+            // Increase caller's nonce
+            cb.account_write(
+                caller_address.expr(),
+                AccountFieldTag::Nonce,
+                create.caller_nonce() + 1.expr(),
+                create.caller_nonce(),
+                Some(&mut reversion_info),
+            );
+
+            // Add the contract address to the access list, regardless of
+            // whether the creation succeeds.
+            cb.account_access_list_write(
+                tx_id.expr(),
+                contract_address.clone(),
+                1.expr(),
+                was_warm.expr(),
+                Some(&mut reversion_info),
+            );
+
+            // Read the contract account to detect an address collision.
+            cb.account_read(
+                contract_address.clone(),
+                AccountFieldTag::CodeHash,
+                prev_code_hash.expr(),
+            );
+            cb.account_read(
+                contract_address.clone(),
+                AccountFieldTag::Nonce,
+                callee_nonce.expr(),
+            );
+
+            // Copy the init code from memory into the bytecode identified by
+            // its code hash.
+            cb.condition(init_code.has_length(), |cb| {
+                cb.copy_table_lookup(
+                    cb.curr.state.call_id.expr(),
+                    CopyDataType::Memory.expr(),
+                    create.code_hash_word_rlc(cb),
+                    CopyDataType::Bytecode.expr(),
+                    init_code.offset(),
+                    init_code.address(),
+                    0.expr(),
+                    init_code.length(),
+                    0.expr(),
+                    init_code.length(),
+                );
+            });
+
+            // Derive the contract address.
+            let keccak_output_rlc = cb.word_rlc::<N_BYTES_WORD>(
+                keccak_output
+                    .iter()
+                    .map(Expr::expr)
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .unwrap(),
+            );
+            cb.keccak_table_lookup(
+                create.input_rlc(cb),
+                create.input_length(),
+                keccak_output_rlc,
+            );
+        });
+        cb.condition(not::expr(init_code.has_length()), |cb| {
+            cb.require_equal(
+                "empty init code has the empty code hash",
+                create.code_hash_word_rlc(cb),
+                cb.empty_code_hash_rlc(),
+            );
+        });
+
+        // The contract address collides when the account has a non-zero nonce
+        // or non-empty code.
+        let prev_code_hash_is_zero = IsZeroGadget::construct(cb, prev_code_hash.expr());
+        let prev_code_hash_is_empty =
+            IsEqualGadget::construct(cb, prev_code_hash.expr(), cb.empty_code_hash_rlc());
+        let callee_nonce_is_zero = IsZeroGadget::construct(cb, callee_nonce.expr());
+        let not_address_collision = and::expr([
+            callee_nonce_is_zero.expr(),
+            or::expr([
+                prev_code_hash_is_zero.expr(),
+                prev_code_hash_is_empty.expr(),
+            ]),
+        ]);
+        let is_create_ok = and::expr([is_precheck_ok.expr(), not_address_collision]);
+
+        cb.condition(not::expr(is_create_ok.expr()), |cb| {
+            cb.require_zero(
+                "is_success is zero when the creation fails before running init code",
+                is_success.expr(),
+            );
+        });
+        cb.condition(
+            and::expr([is_create_ok.expr(), not::expr(init_code.has_length())]),
+            |cb| {
+                cb.require_equal(
+                    "is_success is one when the init code is empty",
+                    is_success.expr(),
+                    1.expr(),
+                );
+            },
+        );
+
+        // Propagate rw_counter_end_of_reversion and is_persistent
+        let mut callee_reversion_info = cb.reversion_info_write(Some(callee_call_id.expr()));
+        cb.require_equal(
+            "callee_is_persistent == is_persistent ⋅ is_success",
+            callee_reversion_info.is_persistent(),
+            reversion_info.is_persistent() * is_success.expr(),
+        );
+        cb.condition(is_success.expr() * (1.expr() - reversion_info.is_persistent()), |cb| {
+            cb.require_equal(
+                "callee_rw_counter_end_of_reversion == rw_counter_end_of_reversion - (reversible_write_counter + 1)",
+                callee_reversion_info.rw_counter_end_of_reversion(),
+                reversion_info.rw_counter_of_reversion(1.expr()),
+            );
+        });
+
+        let callee_was_created = cb.query_bool();
+        let transfer = cb.condition(is_create_ok.expr(), |cb| {
+            // Create the contract account if it doesn't exist yet.
+            cb.condition(prev_code_hash_is_zero.expr(), |cb| {
+                cb.account_write(
+                    contract_address.clone(),
+                    AccountFieldTag::CodeHash,
+                    cb.empty_code_hash_rlc(),
+                    0.expr(),
+                    Some(&mut callee_reversion_info),
+                );
+            });
+
+            let transfer = TransferGadget::construct(
+                cb,
+                caller_address.expr(),
+                contract_address.clone(),
+                1.expr(),
+                value.clone(),
+                &mut callee_reversion_info,
+            );
+
+            // Increase callee's nonce
+            cb.account_write(
+                contract_address.clone(),
+                AccountFieldTag::Nonce,
+                1.expr(),
+                0.expr(),
+                Some(&mut callee_reversion_info),
+            );
+
+            // Mark the callee as created in this transaction for EIP-6780.
+            cb.tx_created_account_write(
+                tx_id.expr(),
+                contract_address.clone(),
+                1.expr(),
+                callee_was_created.expr(),
+                Some(&mut callee_reversion_info),
+            );
+
+            transfer
+        });
+        let callee_reversible_write_counter = prev_code_hash_is_zero.expr()
+            + 2.expr() * not::expr(transfer.value_is_zero.expr())
+            + 2.expr();
+
+        // Sum up the gas cost: the constant cost, the memory expansion and the
+        // init code words, plus hashing the init code for CREATE2.
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [init_code.address()]);
+        let init_code_word_size = MemoryWordSizeGadget::construct(cb, init_code.length());
+        let init_code_word_cost = if IS_CREATE2 {
+            INIT_CODE_WORD_GAS.expr() + GasCost::COPY_SHA3.expr()
+        } else {
+            INIT_CODE_WORD_GAS.expr()
+        };
+        let gas_cost = GasCost::CREATE.expr()
+            + memory_expansion.gas_cost()
+            + init_code_word_cost * init_code_word_size.expr();
+        // Apply EIP 150
+        let gas_remaining = cb.curr.state.gas_left.expr() - gas_cost.clone();
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_remaining.clone(), 64);
+        let callee_gas_left = gas_remaining - one_64th_gas.quotient();
+
+        let stack_pointer_delta = if IS_CREATE2 { 3 } else { 2 };
+
+        // handle creation with non-empty init code, which enters the callee's
+        // context.
+        cb.condition(
+            and::expr([is_create_ok.expr(), init_code.has_length()]),
+            |cb| {
+                // Save caller's call state
+                for (field_tag, value) in [
+                    (
+                        CallContextFieldTag::ProgramCounter,
+                        cb.curr.state.program_counter.expr() + 1.expr(),
+                    ),
+                    (
+                        CallContextFieldTag::StackPointer,
+                        cb.curr.state.stack_pointer.expr() + stack_pointer_delta.expr(),
+                    ),
+                    (CallContextFieldTag::GasLeft, one_64th_gas.quotient()),
+                    (
+                        CallContextFieldTag::MemorySize,
+                        memory_expansion.next_memory_word_size(),
+                    ),
+                    (
+                        CallContextFieldTag::ReversibleWriteCounter,
+                        cb.curr.state.reversible_write_counter.expr() + 2.expr(),
+                    ),
+                ] {
+                    cb.call_context_lookup(true.expr(), None, field_tag, value);
+                }
+
+                // Setup next call's context.
+                for (field_tag, value) in [
+                    (CallContextFieldTag::CallerId, cb.curr.state.call_id.expr()),
+                    (CallContextFieldTag::TxId, tx_id.expr()),
+                    (CallContextFieldTag::Depth, depth.expr() + 1.expr()),
+                    (CallContextFieldTag::CallerAddress, caller_address.expr()),
+                    (CallContextFieldTag::CalleeAddress, contract_address.clone()),
+                    (CallContextFieldTag::CallDataOffset, 0.expr()),
+                    (CallContextFieldTag::CallDataLength, 0.expr()),
+                    (CallContextFieldTag::Value, value.expr()),
+                    (CallContextFieldTag::IsSuccess, is_success.expr()),
+                    (CallContextFieldTag::IsStatic, 0.expr()),
+                    (CallContextFieldTag::LastCalleeId, 0.expr()),
+                    (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+                    (CallContextFieldTag::LastCalleeReturnDataLength, 0.expr()),
+                    (CallContextFieldTag::IsRoot, 0.expr()),
+                    (CallContextFieldTag::IsCreate, 1.expr()),
+                    (CallContextFieldTag::CodeHash, create.code_hash_word_rlc(cb)),
+                ] {
+                    cb.call_context_lookup(
+                        true.expr(),
+                        Some(callee_call_id.expr()),
+                        field_tag,
+                        value,
+                    );
+                }
+
+                cb.require_step_state_transition(StepStateTransition {
+                    rw_counter: Delta(cb.rw_counter_offset()),
+                    call_id: To(callee_call_id.expr()),
+                    is_root: To(false.expr()),
+                    is_create: To(true.expr()),
+                    code_hash: To(create.code_hash_word_rlc(cb)),
+                    gas_left: To(callee_gas_left.clone()),
+                    reversible_write_counter: To(callee_reversible_write_counter.clone()),
+                    ..StepStateTransition::new_context()
+                });
+            },
+        );
+
+        // handle creation with empty init code, and the failures before
+        // running the init code.
+        cb.condition(
+            not::expr(and::expr([is_create_ok.expr(), init_code.has_length()])),
+            |cb| {
+                // Save caller's call state
+                for (field_tag, value) in [
+                    (CallContextFieldTag::LastCalleeId, callee_call_id.expr()),
+                    (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+                    (CallContextFieldTag::LastCalleeReturnDataLength, 0.expr()),
+                ] {
+                    cb.call_context_lookup(true.expr(), None, field_tag, value);
+                }
+
+                // On address collision the gas passed to the callee is
+                // consumed.
+                let is_address_collision = is_precheck_ok.expr() * not::expr(is_create_ok.expr());
+                cb.require_step_state_transition(StepStateTransition {
+                    rw_counter: Delta(cb.rw_counter_offset()),
+                    program_counter: Delta(1.expr()),
+                    stack_pointer: Delta(stack_pointer_delta.expr()),
+                    gas_left: Delta(
+                        -gas_cost.clone() - is_address_collision * callee_gas_left.clone(),
+                    ),
+                    memory_word_size: To(memory_expansion.next_memory_word_size()),
+                    reversible_write_counter: Delta(
+                        is_precheck_ok.expr() * 2.expr()
+                            + is_create_ok.expr() * callee_reversible_write_counter.clone(),
+                    ),
+                    ..StepStateTransition::default()
+                });
+            },
+        );
+
+        Self {
+            opcode,
+            tx_id,
+            reversion_info,
+            is_static,
+            depth,
+            caller_address,
+            value,
+            init_code,
+            memory_expansion,
+            init_code_word_size,
+            keccak_output,
+            is_success,
+            caller_balance,
+            is_insufficient_balance,
+            is_depth_ok,
+            was_warm,
+            prev_code_hash,
+            prev_code_hash_is_zero,
+            prev_code_hash_is_empty,
+            callee_nonce,
+            callee_nonce_is_zero,
+            callee_reversion_info,
+            transfer,
+            callee_was_created,
+            create,
+            one_64th_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let mut rws = StepRws::new(block, step);
+
+        let tx_id = rws.next().call_context_value();
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
+        // Skip the reversion info reads, assigned from the call below.
+        rws.offset_add(3);
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        let [is_static, depth, caller_address] = [(); 3].map(|_| rws.next().call_context_value());
+        self.is_static
+            .assign(region, offset, Value::known(F::from(is_static.low_u64())))?;
+        self.depth
+            .assign(region, offset, Value::known(F::from(depth.low_u64())))?;
+        self.caller_address.assign(
+            region,
+            offset,
+            Value::known(
+                caller_address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+
+        let [value, init_code_start, init_code_length] = [(); 3].map(|_| rws.next().stack_value());
+        let salt = if IS_CREATE2 {
+            Some(rws.next().stack_value())
+        } else {
+            None
+        };
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        let init_code_address =
+            self.init_code
+                .assign(region, offset, init_code_start, init_code_length)?;
+
+        let contract_address = rws.next().stack_value();
+        let is_success = !contract_address.is_zero();
+        self.is_success
+            .assign(region, offset, Value::known(F::from(is_success as u64)))?;
+
+        let (caller_balance, _) = rws.next().account_value_pair();
+        self.caller_balance
+            .assign(region, offset, Some(caller_balance.to_le_bytes()))?;
+        self.is_insufficient_balance
+            .assign(region, offset, caller_balance, value)?;
+        self.is_depth_ok
+            .assign(region, offset, F::from(depth.low_u64()), F::from(1025))?;
+        let is_precheck_ok = depth.low_u64() < 1025 && caller_balance >= value;
+
+        let (caller_nonce, was_warm, prev_code_hash, callee_nonce, init_code) = if is_precheck_ok {
+            let (_, caller_nonce) = rws.next().account_value_pair();
+            let (_, was_warm) = rws.next().tx_access_list_value_pair();
+            let (prev_code_hash, _) = rws.next().account_value_pair();
+            let (callee_nonce, _) = rws.next().account_value_pair();
+            let init_code: Vec<u8> = (0..init_code_length.as_usize())
+                .map(|_| rws.next().memory_value())
+                .collect();
+            (
+                caller_nonce.low_u64(),
+                was_warm,
+                prev_code_hash,
+                callee_nonce,
+                init_code,
+            )
+        } else {
+            (0, false, U256::zero(), U256::zero(), vec![])
+        };
+        self.was_warm
+            .assign(region, offset, Value::known(F::from(was_warm as u64)))?;
+        self.prev_code_hash
+            .assign(region, offset, region.word_rlc(prev_code_hash))?;
+        self.prev_code_hash_is_zero.assign_value(
+            region,
+            offset,
+            region.word_rlc(prev_code_hash),
+        )?;
+        self.prev_code_hash_is_empty.assign_value(
+            region,
+            offset,
+            region.word_rlc(prev_code_hash),
+            region.empty_code_hash_rlc(),
+        )?;
+        self.callee_nonce.assign(
+            region,
+            offset,
+            Value::known(F::from(callee_nonce.low_u64())),
+        )?;
+        self.callee_nonce_is_zero
+            .assign(region, offset, F::from(callee_nonce.low_u64()))?;
+        let is_address_collision = !callee_nonce.is_zero()
+            || !(prev_code_hash.is_zero() || prev_code_hash == CodeDB::empty_code_hash().to_word());
+
+        let [callee_rw_counter_end_of_reversion, callee_is_persistent] =
+            [(); 2].map(|_| rws.next().call_context_value());
+        self.callee_reversion_info.assign(
+            region,
+            offset,
+            callee_rw_counter_end_of_reversion.low_u64() as usize,
+            callee_is_persistent.low_u64() != 0,
+        )?;
+
+        // conditionally assign
+        let mut callee_was_created = false;
+        if is_precheck_ok && !is_address_collision {
+            if prev_code_hash.is_zero() {
+                rws.next();
+            }
+            if !value.is_zero() {
+                let [caller_balance_pair, callee_balance_pair] =
+                    [(); 2].map(|_| rws.next().account_value_pair());
+                self.transfer.assign(
+                    region,
+                    offset,
+                    caller_balance_pair,
+                    callee_balance_pair,
+                    value,
+                )?;
+            }
+            // Skip the callee nonce write.
+            rws.next();
+            callee_was_created = rws.next().tx_created_account_value_pair().1;
+        }
+        self.callee_was_created.assign(
+            region,
+            offset,
+            Value::known(F::from(callee_was_created as u64)),
+        )?;
+
+        let code_hash = CodeDB::hash(&init_code);
+        let keccak_input = if IS_CREATE2 {
+            [
+                &[0xffu8][..],
+                caller_address.to_address().as_bytes(),
+                &salt.unwrap_or_default().to_be_bytes()[..],
+                code_hash.as_bytes(),
+            ]
+            .concat()
+        } else {
+            let mut stream = rlp::RlpStream::new();
+            stream.begin_list(2);
+            stream.append(&caller_address.to_address());
+            stream.append(&U256::from(caller_nonce));
+            stream.out().to_vec()
+        };
+        for (c, v) in self
+            .keccak_output
+            .iter()
+            .rev()
+            .zip(keccak256(&keccak_input).iter())
+        {
+            c.assign(region, offset, Value::known(F::from(*v as u64)))?;
+        }
+        self.create.assign(
+            region,
+            offset,
+            caller_address.to_address(),
+            caller_nonce,
+            Some(code_hash.to_word()),
+            salt,
+        )?;
+
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [init_code_address],
+        )?;
+        let init_code_word_size =
+            self.init_code_word_size
+                .assign(region, offset, init_code_length.as_u64())?;
+        let init_code_word_cost = if IS_CREATE2 {
+            INIT_CODE_WORD_GAS + GasCost::COPY_SHA3.as_u64()
+        } else {
+            INIT_CODE_WORD_GAS
+        };
+        let gas_cost = GasCost::CREATE.as_u64()
+            + memory_expansion_gas_cost
+            + init_code_word_cost * init_code_word_size;
+        self.one_64th_gas
+            .assign(region, offset, (step.gas_left.0 - gas_cost).into())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::circuit_input_builder::CircuitsParams;
+    use eth_types::{bytecode, evm_types::OpcodeId, word, Bytecode, ToBigEndian, Word};
+    use ethers_core::utils::get_create2_address;
+    use itertools::Itertools;
+    use mock::{eth, test_ctx::helpers::*, TestContext, MOCK_ACCOUNTS};
+
+    // Init code which deploys `code_length` zero bytes, or reverts.
+    fn init_code(is_success: bool, code_length: u64) -> Bytecode {
+        let mut code = bytecode! {
+            PUSH1(code_length)
+            PUSH1(0)
+        };
+        code.write_op(if is_success {
+            OpcodeId::RETURN
+        } else {
+            OpcodeId::REVERT
+        });
+        code
+    }
+
+    // Creator code which stores the init code in memory and creates a
+    // contract from it.
+    fn creator_code(init_code: Bytecode, value: Word, is_create2: bool) -> Bytecode {
+        let init_code = init_code.code();
+        let length = init_code.len();
+        assert!(length <= 32);
+        let mut code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+        };
+        if is_create2 {
+            code.op_create2(value, 32 - length, length, word!("0x1234"));
+        } else {
+            code.op_create(value, 32 - length, length);
+        }
+        code.op_stop();
+        code
+    }
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn create_empty_init_code() {
+        for is_create2 in [false, true] {
+            test_ok(creator_code(Bytecode::default(), Word::zero(), is_create2));
+        }
+    }
+
+    #[test]
+    fn create_non_empty_init_code() {
+        for ((is_success, value), is_create2) in [true, false]
+            .into_iter()
+            .cartesian_product([Word::zero(), eth(1)])
+            .cartesian_product([false, true])
+        {
+            test_ok(creator_code(init_code(is_success, 2), value, is_create2));
+        }
+    }
+
+    #[test]
+    fn create_insufficient_balance() {
+        for is_create2 in [false, true] {
+            test_ok(creator_code(init_code(true, 2), eth(11), is_create2));
+        }
+    }
+
+    #[test]
+    fn create_address_collision() {
+        // A contract account already exists at the address of the CREATE2.
+        let init_code = init_code(true, 2);
+        let address = get_create2_address(
+            MOCK_ACCOUNTS[0],
+            word!("0x1234").to_be_bytes(),
+            init_code.code(),
+        );
+        let code = creator_code(init_code, Word::zero(), true);
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+                accs[2].address(address).nonce(1);
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn create_depth() {
+        // Init code which copies itself to memory and creates a contract from
+        // it, recursing until the call depth limit is reached.
+        let code = bytecode! {
+            CODESIZE
+            PUSH1(0)
+            PUSH1(0)
+            CODECOPY
+            CODESIZE
+            PUSH1(0)
+            PUSH1(0)
+            CREATE
+        };
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .to(accs[0].address)
+                    .from(accs[1].address)
+                    .gas(word!("0x2386F26FC10000"));
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx)
+            .params(CircuitsParams {
+                max_rws: 300000,
+                ..Default::default()
+            })
+            .run();
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::LtGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
+            CachedRegion, Cell,
+        },
+    },
+    util::Expr,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId, MAX_CODE_SIZE},
+    Field,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the errors of a RETURN in a creation context that fails to store
+/// the code: [`ExecutionState::ErrorMaxCodeSizeExceeded`] when
+/// `IS_MAX_CODE_SIZE_EXCEEDED`, [`ExecutionState::ErrorOutOfGasCodeStore`]
+/// otherwise.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorCodeStoreGadget<F, const IS_MAX_CODE_SIZE_EXCEEDED: bool> {
+    opcode: Cell<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    // check if the code size is larger than MAX_CODE_SIZE
+    max_code_size_exceeded: LtGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    // check if gas left is less than the cost of storing the code
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field, const IS_MAX_CODE_SIZE_EXCEEDED: bool> ExecutionGadget<F>
+    for ErrorCodeStoreGadget<F, IS_MAX_CODE_SIZE_EXCEEDED>
+{
+    const NAME: &'static str = if IS_MAX_CODE_SIZE_EXCEEDED {
+        "ErrorMaxCodeSizeExceeded"
+    } else {
+        "ErrorOutOfGasCodeStore"
+    };
+
+    const EXECUTION_STATE: ExecutionState = if IS_MAX_CODE_SIZE_EXCEEDED {
+        ExecutionState::ErrorMaxCodeSizeExceeded
+    } else {
+        ExecutionState::ErrorOutOfGasCodeStore
+    };
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorCodeStore checking at RETURN",
+            opcode.expr(),
+            OpcodeId::RETURN.expr(),
+        );
+        cb.require_equal(
+            "ErrorCodeStore only happens in a creation context",
+            cb.curr.state.is_create.expr(),
+            1.expr(),
+        );
+
+        let offset = cb.query_cell_phase2();
+        let length = cb.query_word_rlc();
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+        let memory_address = MemoryAddressGadget::construct(cb, offset, length);
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+
+        let max_code_size_exceeded =
+            LtGadget::construct(cb, MAX_CODE_SIZE.expr(), memory_address.length());
+        // The code deposit is paid after the memory expansion of RETURN.
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            memory_expansion.gas_cost()
+                + GasCost::CODE_DEPOSIT_BYTE_COST.expr() * memory_address.length(),
+        );
+
+        if IS_MAX_CODE_SIZE_EXCEEDED {
+            cb.require_equal(
+                "code size is larger than MAX_CODE_SIZE",
+                max_code_size_exceeded.expr(),
+                1.expr(),
+            );
+        } else {
+            cb.require_zero(
+                "code size is not larger than MAX_CODE_SIZE",
+                max_code_size_exceeded.expr(),
+            );
+            cb.require_equal(
+                "gas left is less than the code store cost",
+                insufficient_gas.expr(),
+                1.expr(),
+            );
+        }
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 4.expr());
+
+        Self {
+            opcode,
+            memory_address,
+            memory_expansion,
+            max_code_size_exceeded,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, length] = [0, 1].map(|index| block.get_rws(step, index).stack_value());
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+
+        // The length fits in 5 bytes, a larger one would run out of gas when
+        // expanding the memory.
+        let length = length.low_u64();
+        self.max_code_size_exceeded.assign(
+            region,
+            offset,
+            F::from(MAX_CODE_SIZE),
+            F::from(length),
+        )?;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left.0),
+            F::from(memory_expansion_gas_cost + GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, evm_types::MAX_CODE_SIZE, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    // Creates a contract whose init code returns `code_length` zero bytes.
+    fn test_ok(code_length: u64) {
+        let init_code = bytecode! {
+            PUSH2(code_length)
+            PUSH1(0)
+            RETURN
+        }
+        .code();
+
+        let mut code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+        };
+        code.op_create(0, 32 - init_code.len(), init_code.len());
+        code.op_stop();
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn error_max_code_size_exceeded() {
+        test_ok(MAX_CODE_SIZE + 1);
+    }
+
+    #[test]
+    fn error_oog_code_store() {
+        // Storing 0x2000 bytes costs more than the gas forwarded to the init
+        // code out of the default 1M gas limit.
+        test_ok(0x2000);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget},
+            CachedRegion, Cell,
+        },
+    },
+    util::Expr,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::{evm_types::OpcodeId, Field};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for the [`ExecutionState::ErrorInvalidCreationCode`] error, i.e. a
+/// RETURN in a creation context whose code starts with `0xef` (EIP-3541).
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidCreationCodeGadget<F> {
+    opcode: Cell<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    first_byte: Cell<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidCreationCodeGadget<F> {
+    const NAME: &'static str = "ErrorInvalidCreationCode";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidCreationCode;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorInvalidCreationCode checking at RETURN",
+            opcode.expr(),
+            OpcodeId::RETURN.expr(),
+        );
+        cb.require_equal(
+            "ErrorInvalidCreationCode only happens in a creation context",
+            cb.curr.state.is_create.expr(),
+            1.expr(),
+        );
+
+        let offset = cb.query_cell_phase2();
+        let length = cb.query_word_rlc();
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+        let memory_address = MemoryAddressGadget::construct(cb, offset, length);
+        cb.require_equal(
+            "code to be deposited is not empty",
+            memory_address.has_length(),
+            1.expr(),
+        );
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+
+        let first_byte = cb.query_cell();
+        cb.memory_lookup(0.expr(), memory_address.offset(), first_byte.expr(), None);
+        cb.require_equal(
+            "code to be deposited starts with 0xef",
+            first_byte.expr(),
+            0xef.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 5.expr());
+
+        Self {
+            opcode,
+            memory_address,
+            memory_expansion,
+            first_byte,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, length] = [0, 1].map(|index| block.get_rws(step, index).stack_value());
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, length)?;
+        self.memory_expansion
+            .assign(region, offset, step.memory_word_size(), [memory_address])?;
+
+        let first_byte = block.get_rws(step, 2).memory_value();
+        self.first_byte
+            .assign(region, offset, Value::known(F::from(first_byte as u64)))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 5)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    #[test]
+    fn error_invalid_creation_code() {
+        // Init code which returns a code starting with 0xef.
+        let init_code = bytecode! {
+            PUSH1(0xef)
+            PUSH1(0)
+            MSTORE8
+            PUSH1(1)
+            PUSH1(0)
+            RETURN
+        }
+        .code();
+
+        let mut code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+        };
+        code.op_create(0, 32 - init_code.len(), init_code.len());
+        code.op_stop();
+
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
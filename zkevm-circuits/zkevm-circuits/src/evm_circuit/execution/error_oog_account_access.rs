@@ -0,0 +1,235 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            from_bytes,
+            math_gadget::LtGadget,
+            select, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget to implement the corresponding out of gas errors for
+/// [`OpcodeId::BALANCE`], [`OpcodeId::EXTCODESIZE`] and
+/// [`OpcodeId::EXTCODEHASH`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGAccountAccessGadget<F> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    address_word: Word<F>,
+    is_warm: Cell<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGAccountAccessGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasAccountAccess";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasAccountAccess;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_in_set(
+            "ErrorOutOfGasAccountAccess opcode must be BALANCE, EXTCODESIZE or EXTCODEHASH",
+            opcode.expr(),
+            vec![
+                OpcodeId::BALANCE.expr(),
+                OpcodeId::EXTCODESIZE.expr(),
+                OpcodeId::EXTCODEHASH.expr(),
+            ],
+        );
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+
+        let address_word = cb.query_word_rlc();
+        let address = from_bytes::expr(&address_word.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+        cb.stack_pop(address_word.expr());
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(tx_id.expr(), address, is_warm.expr());
+
+        let gas_cost = select::expr(
+            is_warm.expr(),
+            GasCost::WARM_ACCESS.expr(),
+            GasCost::COLD_ACCOUNT_ACCESS.expr(),
+        );
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 5.expr());
+
+        Self {
+            opcode,
+            tx_id,
+            address_word,
+            is_warm,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        let address = block.get_rws(step, 1).stack_value();
+        let (is_warm, _) = block.get_rws(step, 2).tx_access_list_value_pair();
+
+        let gas_cost = if is_warm {
+            GasCost::WARM_ACCESS
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS
+        }
+        .as_u64();
+        log::debug!(
+            "ErrorOutOfGasAccountAccess: opcode = {}, gas_left = {}, gas_cost = {}",
+            opcode,
+            step.gas_left.0,
+            gas_cost,
+        );
+
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.address_word
+            .assign(region, offset, Some(address.to_le_bytes()))?;
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left.0),
+            F::from(gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 5)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, Bytecode, ToWord,
+    };
+    use mock::{
+        eth, generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+        MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+    };
+
+    const TESTING_OPCODES: &[OpcodeId] = &[
+        OpcodeId::BALANCE,
+        OpcodeId::EXTCODESIZE,
+        OpcodeId::EXTCODEHASH,
+    ];
+
+    #[test]
+    fn test_oog_account_access_cold() {
+        let cold_address: Address = address!("0x0000000000000000000000000000000000fe1234");
+
+        for opcode in TESTING_OPCODES {
+            let code = bytecode! {
+                PUSH20(cold_address.to_word())
+                .write_op(*opcode)
+            };
+            let gas_cost = OpcodeId::PUSH20.constant_gas_cost().0 + GasCost::COLD_ACCOUNT_ACCESS.0;
+
+            test_root(code.clone(), gas_cost);
+            test_internal(code, gas_cost);
+        }
+    }
+
+    #[test]
+    fn test_oog_account_access_warm() {
+        for opcode in TESTING_OPCODES {
+            // The callee address is always in the access list.
+            let code = bytecode! {
+                ADDRESS
+                .write_op(*opcode)
+            };
+            let gas_cost = OpcodeId::ADDRESS.constant_gas_cost().0 + GasCost::WARM_ACCESS.0;
+
+            test_root(code.clone(), gas_cost);
+            test_internal(code, gas_cost);
+        }
+    }
+
+    fn test_root(code: Bytecode, gas_cost: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                // Decrease expected gas cost (by 1) to trigger out of gas error.
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas((GasCost::TX.0 + gas_cost - 1).into());
+            },
+            |block, _tx| block.number(0xcafe_u64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal(code_b: Bytecode, gas_cost_b: u64) {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code A calls code B.
+        // Decrease expected gas cost (by 1) to trigger out of gas error.
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            pushdata: rand_bytes(32),
+            return_data_offset: 0x00usize,
+            return_data_size: 0x00usize,
+            call_data_length: 0x20usize,
+            call_data_offset: 0x10usize,
+            gas: gas_cost_b - 1,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::LtGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget, MemoryWordSizeGadget},
+            CachedRegion, Cell, Word,
+        },
+    },
+    util::Expr,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId, INIT_CODE_WORD_GAS},
+    Field, ToLittleEndian,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget to implement the corresponding out of gas error for
+/// [`OpcodeId::CREATE2`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGCreate2Gadget<F> {
+    opcode: Cell<F>,
+    value: Word<F>,
+    salt: Word<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    init_code_word_size: MemoryWordSizeGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGCreate2Gadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasCREATE2";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCREATE2;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasCREATE2 opcode must be CREATE2",
+            opcode.expr(),
+            OpcodeId::CREATE2.expr(),
+        );
+
+        let value = cb.query_word_rlc();
+        let offset = cb.query_cell_phase2();
+        let length = cb.query_word_rlc();
+        let salt = cb.query_word_rlc();
+        cb.stack_pop(value.expr());
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+        cb.stack_pop(salt.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, offset, length);
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        let init_code_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+
+        let gas_cost = GasCost::CREATE.expr()
+            + memory_expansion.gas_cost()
+            + (INIT_CODE_WORD_GAS.expr() + GasCost::COPY_SHA3.expr()) * init_code_word_size.expr();
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "gas left is less than gas required",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 6.expr());
+
+        Self {
+            opcode,
+            value,
+            salt,
+            memory_address,
+            memory_expansion,
+            init_code_word_size,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let [value, memory_offset, length, salt] =
+            [0, 1, 2, 3].map(|index| block.get_rws(step, index).stack_value());
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.salt.assign(region, offset, Some(salt.to_le_bytes()))?;
+
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let init_code_word_size =
+            self.init_code_word_size
+                .assign(region, offset, length.low_u64())?;
+
+        let gas_cost = GasCost::CREATE.as_u64()
+            + memory_expansion_gas_cost
+            + (INIT_CODE_WORD_GAS + GasCost::COPY_SHA3.as_u64()) * init_code_word_size;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left.0),
+            F::from(gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 6)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{bytecode, word, Word};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    #[test]
+    fn error_oog_create2() {
+        let mut code = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            MSTORE
+        };
+        code.op_create2(0, 0, 32, word!("0x1234"));
+        code.op_stop();
+
+        // Enough gas for the transaction and the pushes, but not for CREATE2.
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(eth(10))
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(30_000));
+            },
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
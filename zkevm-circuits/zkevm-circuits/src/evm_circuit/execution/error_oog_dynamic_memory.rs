@@ -0,0 +1,302 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::{IsEqualGadget, LtGadget},
+            memory_gadget::{MemoryAddressGadget, MemoryExpansionGadget, MemoryWordSizeGadget},
+            CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId, INIT_CODE_WORD_GAS},
+    Field, ToLittleEndian, U256,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget to implement the corresponding out of gas errors for
+/// [`OpcodeId::CREATE`], [`OpcodeId::RETURN`] and [`OpcodeId::REVERT`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGDynamicMemoryGadget<F> {
+    opcode: Cell<F>,
+    is_create: IsEqualGadget<F>,
+    /// Extra stack pop for `CREATE`
+    value: Word<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    init_code_word_size: MemoryWordSizeGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGDynamicMemoryGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasDynamicMemoryExpansion";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasDynamicMemoryExpansion;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_in_set(
+            "ErrorOutOfGasDynamicMemoryExpansion opcode must be CREATE, RETURN or REVERT",
+            opcode.expr(),
+            vec![
+                OpcodeId::CREATE.expr(),
+                OpcodeId::RETURN.expr(),
+                OpcodeId::REVERT.expr(),
+            ],
+        );
+
+        let is_create = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::CREATE.expr());
+
+        let value = cb.query_word_rlc();
+        let offset = cb.query_cell_phase2();
+        let length = cb.query_word_rlc();
+        cb.condition(is_create.expr(), |cb| {
+            cb.stack_pop(value.expr());
+        });
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(length.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, offset, length);
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        let init_code_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+
+        // CREATE additionally has a constant gas cost and a cost per word of
+        // init code, following EIP-3860.
+        let gas_cost = is_create.expr()
+            * (GasCost::CREATE.expr() + INIT_CODE_WORD_GAS.expr() * init_code_word_size.expr())
+            + memory_expansion.gas_cost();
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget =
+            CommonErrorGadget::construct(cb, opcode.expr(), 4.expr() + is_create.expr());
+
+        Self {
+            opcode,
+            is_create,
+            value,
+            memory_address,
+            memory_expansion,
+            init_code_word_size,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        let is_create = opcode == OpcodeId::CREATE;
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.is_create.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::CREATE.as_u64()),
+        )?;
+
+        let (value, rw_offset) = if is_create {
+            (block.get_rws(step, 0).stack_value(), 1)
+        } else {
+            (U256::zero(), 0)
+        };
+        let [memory_offset, length] =
+            [rw_offset, rw_offset + 1].map(|index| block.get_rws(step, index).stack_value());
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let init_code_word_size =
+            self.init_code_word_size
+                .assign(region, offset, length.low_u64())?;
+
+        let gas_cost = if is_create {
+            GasCost::CREATE.as_u64() + INIT_CODE_WORD_GAS * init_code_word_size
+        } else {
+            0
+        } + memory_expansion_gas_cost;
+        log::debug!(
+            "ErrorOutOfGasDynamicMemoryExpansion: opcode = {}, gas_left = {}, gas_cost = {}",
+            opcode,
+            step.gas_left.0,
+            gas_cost,
+        );
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left.0),
+            F::from(gas_cost),
+        )?;
+
+        self.common_error_gadget.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            4 + usize::from(is_create),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use eth_types::{
+        bytecode,
+        evm_types::{gas_utils::memory_expansion_gas_cost, GasCost, OpcodeId, INIT_CODE_WORD_GAS},
+        Bytecode, U256,
+    };
+    use itertools::Itertools;
+    use mock::{
+        eth, generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+        MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+    };
+
+    const TESTING_OFFSET_LENGTH_PAIRS: &[(u64, u64)] = &[(0x20, 0x40), (0x2000, 0x200)];
+
+    #[test]
+    fn test_oog_dynamic_memory_return_revert() {
+        for (opcode, (offset, length)) in [OpcodeId::RETURN, OpcodeId::REVERT]
+            .iter()
+            .cartesian_product(TESTING_OFFSET_LENGTH_PAIRS.iter())
+        {
+            let testing_data = TestingData::new_for_return_revert(*opcode, *offset, *length);
+
+            test_root(&testing_data);
+            test_internal(&testing_data);
+        }
+    }
+
+    #[test]
+    fn test_oog_dynamic_memory_create() {
+        for (offset, length) in TESTING_OFFSET_LENGTH_PAIRS {
+            let testing_data = TestingData::new_for_create(*offset, *length);
+
+            test_root(&testing_data);
+            test_internal(&testing_data);
+        }
+    }
+
+    struct TestingData {
+        bytecode: Bytecode,
+        gas_cost: u64,
+    }
+
+    impl TestingData {
+        fn new_for_return_revert(opcode: OpcodeId, offset: u64, length: u64) -> Self {
+            let bytecode = bytecode! {
+                PUSH32(length)
+                PUSH32(offset)
+                .write_op(opcode)
+            };
+
+            let memory_word_size = (offset + length + 31) / 32;
+            let gas_cost = OpcodeId::PUSH32.constant_gas_cost().0 * 2
+                + memory_expansion_gas_cost(0, memory_word_size);
+
+            Self { bytecode, gas_cost }
+        }
+
+        fn new_for_create(offset: u64, length: u64) -> Self {
+            let bytecode = bytecode! {
+                PUSH32(length)
+                PUSH32(offset)
+                PUSH32(U256::zero()) // value
+                CREATE
+            };
+
+            let memory_word_size = (offset + length + 31) / 32;
+            let gas_cost = OpcodeId::PUSH32.constant_gas_cost().0 * 3
+                + GasCost::CREATE.0
+                + INIT_CODE_WORD_GAS * ((length + 31) / 32)
+                + memory_expansion_gas_cost(0, memory_word_size);
+
+            Self { bytecode, gas_cost }
+        }
+    }
+
+    fn test_root(testing_data: &TestingData) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(testing_data.bytecode.clone()),
+            |mut txs, accs| {
+                // Decrease expected gas cost (by 1) to trigger out of gas error.
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas((GasCost::TX.0 + testing_data.gas_cost - 1).into());
+            },
+            |block, _tx| block.number(0xcafe_u64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal(testing_data: &TestingData) {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code B gets called by code A, so the call is an internal call.
+        let code_b = testing_data.bytecode.clone();
+        let gas_cost_b = testing_data.gas_cost;
+
+        // code A calls code B.
+        // Decrease expected gas cost (by 1) to trigger out of gas error.
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            pushdata: rand_bytes(32),
+            return_data_offset: 0x00usize,
+            return_data_size: 0x00usize,
+            call_data_length: 0x20usize,
+            call_data_offset: 0x10usize,
+            gas: gas_cost_b - 1,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
@@ -24,7 +24,8 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget to implement the corresponding out of gas errors for
 /// [`OpcodeId::CALLDATACOPY`], [`OpcodeId::CODECOPY`],
-/// [`OpcodeId::EXTCODECOPY`] and [`OpcodeId::RETURNDATACOPY`].
+/// [`OpcodeId::EXTCODECOPY`], [`OpcodeId::RETURNDATACOPY`] and
+/// [`OpcodeId::MCOPY`].
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorOOGMemoryCopyGadget<F> {
     opcode: Cell<F>,
@@ -33,14 +34,16 @@ pub(crate) struct ErrorOOGMemoryCopyGadget<F> {
     tx_id: Cell<F>,
     /// Extra stack pop for `EXTCODECOPY`
     external_address: Word<F>,
-    /// Source offset
-    src_offset: Word<F>,
+    /// Source offset, and size to copy for `MCOPY` (zero otherwise) since it
+    /// also expands the memory over the source
+    src_memory_addr: MemoryAddressGadget<F>,
     /// Destination offset and size to copy
     dst_memory_addr: MemoryAddressGadget<F>,
-    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
     memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     is_extcodecopy: IsZeroGadget<F>,
+    is_mcopy: IsZeroGadget<F>,
     common_error_gadget: CommonErrorGadget<F>,
 }
 
@@ -52,25 +55,28 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
         cb.require_in_set(
-            "ErrorOutOfGasMemoryCopy opcode must be CALLDATACOPY, CODECOPY, EXTCODECOPY or RETURNDATACOPY",
+            "ErrorOutOfGasMemoryCopy opcode must be CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY or MCOPY",
             opcode.expr(),
             vec![
                 OpcodeId::CALLDATACOPY.expr(),
                 OpcodeId::CODECOPY.expr(),
                 OpcodeId::EXTCODECOPY.expr(),
                 OpcodeId::RETURNDATACOPY.expr(),
+                OpcodeId::MCOPY.expr(),
             ],
         );
 
         let dst_offset = cb.query_cell_phase2();
-        let src_offset = cb.query_word_rlc();
+        let src_offset = cb.query_cell_phase2();
         let copy_size = cb.query_word_rlc();
+        let src_size = cb.query_word_rlc();
         let external_address = cb.query_word_rlc();
         let is_warm = cb.query_bool();
         let tx_id = cb.query_cell();
 
         let is_extcodecopy =
             IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::EXTCODECOPY.expr());
+        let is_mcopy = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::MCOPY.expr());
 
         cb.condition(is_extcodecopy.expr(), |cb| {
             cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
@@ -90,8 +96,18 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
         cb.stack_pop(src_offset.expr());
         cb.stack_pop(copy_size.expr());
 
+        // Only MCOPY expands the memory over the source range.
+        cb.require_equal(
+            "src_size == copy_size for MCOPY and 0 otherwise",
+            src_size.expr(),
+            is_mcopy.expr() * copy_size.expr(),
+        );
+        let src_memory_addr = MemoryAddressGadget::construct(cb, src_offset, src_size);
         let dst_memory_addr = MemoryAddressGadget::construct(cb, dst_offset, copy_size);
-        let memory_expansion = MemoryExpansionGadget::construct(cb, [dst_memory_addr.address()]);
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            [dst_memory_addr.address(), src_memory_addr.address()],
+        );
         let memory_copier_gas = MemoryCopierGasGadget::construct(
             cb,
             dst_memory_addr.length(),
@@ -107,7 +123,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
                 GasCost::WARM_ACCESS.expr(),
                 GasCost::COLD_ACCOUNT_ACCESS.expr(),
             ),
-            // Constant gas cost is same for CALLDATACOPY, CODECOPY and RETURNDATACOPY.
+            // Constant gas cost is same for CALLDATACOPY, CODECOPY, RETURNDATACOPY and MCOPY.
             OpcodeId::CALLDATACOPY.constant_gas_cost().expr(),
         );
 
@@ -136,12 +152,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             is_warm,
             tx_id,
             external_address,
-            src_offset,
+            src_memory_addr,
             dst_memory_addr,
             memory_expansion,
             memory_copier_gas,
             insufficient_gas,
             is_extcodecopy,
+            is_mcopy,
             common_error_gadget,
         }
     }
@@ -157,6 +174,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
     ) -> Result<(), Error> {
         let opcode = step.opcode().unwrap();
         let is_extcodecopy = opcode == OpcodeId::EXTCODECOPY;
+        let is_mcopy = opcode == OpcodeId::MCOPY;
 
         log::debug!(
             "ErrorOutOfGasMemoryCopy: opcode = {}, gas_left = {}, gas_cost = {}",
@@ -186,14 +204,21 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             .assign(region, offset, Value::known(F::from(transaction.id as u64)))?;
         self.external_address
             .assign(region, offset, Some(external_address.to_le_bytes()))?;
-        self.src_offset
-            .assign(region, offset, Some(src_offset.to_le_bytes()))?;
-        let memory_addr = self
+        let src_memory_addr = self.src_memory_addr.assign(
+            region,
+            offset,
+            src_offset,
+            if is_mcopy { copy_size } else { U256::zero() },
+        )?;
+        let dst_memory_addr = self
             .dst_memory_addr
             .assign(region, offset, dst_offset, copy_size)?;
-        let (_, memory_expansion_cost) =
-            self.memory_expansion
-                .assign(region, offset, step.memory_word_size(), [memory_addr])?;
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [dst_memory_addr, src_memory_addr],
+        )?;
         let memory_copier_gas = self.memory_copier_gas.assign(
             region,
             offset,
@@ -220,6 +245,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             offset,
             F::from(opcode.as_u64()) - F::from(OpcodeId::EXTCODECOPY.as_u64()),
         )?;
+        self.is_mcopy.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()) - F::from(OpcodeId::MCOPY.as_u64()),
+        )?;
         self.common_error_gadget.assign(
             region,
             offset,
@@ -285,6 +315,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_oog_memory_copy_for_mcopy() {
+        for ((dst_offset, copy_size), src_offset) in TESTING_DST_OFFSET_COPY_SIZE_PAIRS
+            .iter()
+            .cartesian_product([0x00, 0x4000])
+        {
+            let testing_data = TestingData::new_for_mcopy(src_offset, *dst_offset, *copy_size);
+
+            test_root(&testing_data);
+            test_internal(&testing_data);
+        }
+    }
+
     struct TestingData {
         bytecode: Bytecode,
         gas_cost: u64,
@@ -308,6 +351,28 @@ mod tests {
             Self { bytecode, gas_cost }
         }
 
+        pub fn new_for_mcopy(src_offset: u64, dst_offset: u64, copy_size: u64) -> Self {
+            let bytecode = bytecode! {
+                PUSH32(copy_size)
+                PUSH32(src_offset)
+                PUSH32(dst_offset)
+                MCOPY
+            };
+
+            // The memory is expanded over both the source and the destination.
+            let memory_word_size = if copy_size == 0 {
+                0
+            } else {
+                (src_offset.max(dst_offset) + copy_size + 31) / 32
+            };
+
+            let gas_cost = OpcodeId::PUSH32.constant_gas_cost().0 * 3
+                + OpcodeId::MCOPY.constant_gas_cost().0
+                + memory_copier_gas_cost(0, memory_word_size, copy_size);
+
+            Self { bytecode, gas_cost }
+        }
+
         pub fn new_for_extcodecopy(is_warm: bool, dst_offset: u64, copy_size: u64) -> Self {
             let external_address = MOCK_ACCOUNTS[4];
 
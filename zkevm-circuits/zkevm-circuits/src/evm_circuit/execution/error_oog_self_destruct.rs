@@ -0,0 +1,301 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            from_bytes,
+            math_gadget::{IsZeroGadget, LtGadget},
+            not, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget to implement the corresponding out of gas errors for
+/// [`OpcodeId::SELFDESTRUCT`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSelfDestructGadget<F> {
+    opcode: Cell<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    beneficiary: Word<F>,
+    is_warm: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    beneficiary_not_exists: IsZeroGadget<F>,
+    balance: Cell<F>,
+    balance_is_zero: IsZeroGadget<F>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSelfDestructGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasSELFDESTRUCT opcode must be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let beneficiary = cb.query_word_rlc();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_read(tx_id.expr(), beneficiary_address.expr(), is_warm.expr());
+
+        // For non-existing accounts the code_hash must be 0 in the rw_table.
+        let beneficiary_code_hash = cb.query_cell_phase2();
+        cb.account_read(
+            beneficiary_address.expr(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.expr(),
+        );
+        let beneficiary_not_exists = IsZeroGadget::construct(cb, beneficiary_code_hash.expr());
+
+        let balance = cb.query_cell_phase2();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            balance.expr(),
+        );
+        let balance_is_zero = IsZeroGadget::construct(cb, balance.expr());
+
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + not::expr(is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + not::expr(balance_is_zero.expr())
+                * beneficiary_not_exists.expr()
+                * GasCost::NEW_ACCOUNT.expr();
+
+        let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 8.expr());
+
+        Self {
+            opcode,
+            tx_id,
+            callee_address,
+            beneficiary,
+            is_warm,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            balance,
+            balance_is_zero,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        let beneficiary = block.get_rws(step, 2).stack_value();
+        let (is_warm, _) = block.get_rws(step, 3).tx_access_list_value_pair();
+        let (beneficiary_code_hash, _) = block.get_rws(step, 4).account_value_pair();
+        let (balance, _) = block.get_rws(step, 5).account_value_pair();
+
+        let gas_cost = GasCost::SELFDESTRUCT.as_u64()
+            + if is_warm {
+                0
+            } else {
+                GasCost::COLD_ACCOUNT_ACCESS.as_u64()
+            }
+            + if !balance.is_zero() && beneficiary_code_hash.is_zero() {
+                GasCost::NEW_ACCOUNT.as_u64()
+            } else {
+                0
+            };
+
+        log::debug!(
+            "ErrorOutOfGasSELFDESTRUCT: gas_left = {}, gas_cost = {}",
+            step.gas_left.0,
+            gas_cost,
+        );
+
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(
+                call.address
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        )?;
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            region.word_rlc(beneficiary_code_hash),
+        )?;
+        self.beneficiary_not_exists.assign_value(
+            region,
+            offset,
+            region.word_rlc(beneficiary_code_hash),
+        )?;
+        self.balance
+            .assign(region, offset, region.word_rlc(balance))?;
+        self.balance_is_zero
+            .assign_value(region, offset, region.word_rlc(balance))?;
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left.0),
+            F::from(gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 8)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use eth_types::{
+        address, bytecode,
+        evm_types::{GasCost, OpcodeId},
+        Address, Bytecode, ToWord, Word,
+    };
+    use mock::{
+        eth, generate_mock_call_bytecode, MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+    };
+
+    const BENEFICIARY: Address = address!("0x0000000000000000000000000000000000fe1234");
+
+    #[test]
+    fn test_oog_self_destruct_cold_beneficiary() {
+        let code = bytecode! {
+            PUSH20(BENEFICIARY.to_word())
+            SELFDESTRUCT
+        };
+        // The cost of a new account isn't charged when there's no balance.
+        let gas_cost = OpcodeId::PUSH20.constant_gas_cost().0
+            + GasCost::SELFDESTRUCT.0
+            + GasCost::COLD_ACCOUNT_ACCESS.0;
+
+        test_root(code.clone(), Word::zero(), gas_cost);
+        test_internal(code, Word::zero(), gas_cost);
+    }
+
+    #[test]
+    fn test_oog_self_destruct_new_account() {
+        let code = bytecode! {
+            PUSH20(BENEFICIARY.to_word())
+            SELFDESTRUCT
+        };
+        let gas_cost = OpcodeId::PUSH20.constant_gas_cost().0
+            + GasCost::SELFDESTRUCT.0
+            + GasCost::COLD_ACCOUNT_ACCESS.0
+            + GasCost::NEW_ACCOUNT.0;
+
+        test_root(code.clone(), eth(1), gas_cost);
+        test_internal(code, eth(1), gas_cost);
+    }
+
+    #[test]
+    fn test_oog_self_destruct_warm_beneficiary() {
+        let code = bytecode! {
+            ADDRESS
+            SELFDESTRUCT
+        };
+        let gas_cost = OpcodeId::ADDRESS.constant_gas_cost().0 + GasCost::SELFDESTRUCT.0;
+
+        test_root(code.clone(), eth(1), gas_cost);
+        test_internal(code, eth(1), gas_cost);
+    }
+
+    fn test_root(code: Bytecode, balance: Word, gas_cost: u64) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(balance)
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                // Decrease expected gas cost (by 1) to trigger out of gas error.
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas((GasCost::TX.0 + gas_cost - 1).into());
+            },
+            |block, _tx| block.number(0xcafe_u64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal(code_b: Bytecode, balance: Word, gas_cost_b: u64) {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code A calls code B.
+        // Decrease expected gas cost (by 1) to trigger out of gas error.
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            pushdata: rand_bytes(32),
+            return_data_offset: 0x00usize,
+            return_data_size: 0x00usize,
+            call_data_length: 0x20usize,
+            call_data_offset: 0x10usize,
+            gas: gas_cost_b - 1,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).balance(balance).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
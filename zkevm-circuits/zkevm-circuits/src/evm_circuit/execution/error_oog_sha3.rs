@@ -0,0 +1,238 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::LtGadget,
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field,
+};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget to implement the corresponding out of gas errors for
+/// [`OpcodeId::SHA3`].
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorOOGSha3Gadget<F> {
+    opcode: Cell<F>,
+    memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY_SHA3 }>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorOOGSha3Gadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasSHA3";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasSHA3;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorOutOfGasSHA3 opcode must be SHA3",
+            opcode.expr(),
+            OpcodeId::SHA3.expr(),
+        );
+
+        let offset = cb.query_cell_phase2();
+        let size = cb.query_word_rlc();
+        cb.stack_pop(offset.expr());
+        cb.stack_pop(size.expr());
+
+        let memory_address = MemoryAddressGadget::construct(cb, offset, size);
+        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            OpcodeId::SHA3.constant_gas_cost().expr() + memory_copier_gas.gas_cost(),
+        );
+        cb.require_equal(
+            "Gas left is less than gas cost",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 4.expr());
+
+        Self {
+            opcode,
+            memory_address,
+            memory_expansion,
+            memory_copier_gas,
+            insufficient_gas,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let [memory_offset, size] = [0, 1].map(|index| block.get_rws(step, index).stack_value());
+        let memory_address = self
+            .memory_address
+            .assign(region, offset, memory_offset, size)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [memory_address],
+        )?;
+        let memory_copier_gas = self.memory_copier_gas.assign(
+            region,
+            offset,
+            size.low_u64(),
+            memory_expansion_gas_cost,
+        )?;
+
+        let gas_cost = OpcodeId::SHA3.constant_gas_cost().0 + memory_copier_gas;
+        log::debug!(
+            "ErrorOutOfGasSHA3: gas_left = {}, gas_cost = {}",
+            step.gas_left.0,
+            gas_cost,
+        );
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left.0),
+            F::from(gas_cost),
+        )?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use eth_types::{
+        bytecode,
+        evm_types::{gas_utils::memory_expansion_gas_cost, GasCost, OpcodeId},
+        Bytecode,
+    };
+    use mock::{
+        eth, generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+        MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+    };
+
+    #[test]
+    fn test_oog_sha3() {
+        for (offset, size) in [(0x00, 0x20), (0x40, 0x1000), (0x2000, 0x00)] {
+            let testing_data = TestingData::new(offset, size);
+
+            test_root(&testing_data);
+            test_internal(&testing_data);
+        }
+    }
+
+    struct TestingData {
+        bytecode: Bytecode,
+        gas_cost: u64,
+    }
+
+    impl TestingData {
+        fn new(offset: u64, size: u64) -> Self {
+            let bytecode = bytecode! {
+                PUSH32(size)
+                PUSH32(offset)
+                SHA3
+            };
+
+            let memory_word_size = if size == 0 {
+                0
+            } else {
+                (offset + size + 31) / 32
+            };
+            let gas_cost = OpcodeId::PUSH32.constant_gas_cost().0 * 2
+                + OpcodeId::SHA3.constant_gas_cost().0
+                + GasCost::COPY_SHA3.0 * ((size + 31) / 32)
+                + memory_expansion_gas_cost(0, memory_word_size);
+
+            Self { bytecode, gas_cost }
+        }
+    }
+
+    fn test_root(testing_data: &TestingData) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(testing_data.bytecode.clone()),
+            |mut txs, accs| {
+                // Decrease expected gas cost (by 1) to trigger out of gas error.
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas((GasCost::TX.0 + testing_data.gas_cost - 1).into());
+            },
+            |block, _tx| block.number(0xcafe_u64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal(testing_data: &TestingData) {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code B gets called by code A, so the call is an internal call.
+        let code_b = testing_data.bytecode.clone();
+        let gas_cost_b = testing_data.gas_cost;
+
+        // code A calls code B.
+        // Decrease expected gas cost (by 1) to trigger out of gas error.
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            pushdata: rand_bytes(32),
+            return_data_offset: 0x00usize,
+            return_data_size: 0x00usize,
+            call_data_length: 0x20usize,
+            call_data_offset: 0x10usize,
+            gas: gas_cost_b - 1,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
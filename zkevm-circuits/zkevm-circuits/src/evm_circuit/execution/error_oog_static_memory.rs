@@ -4,18 +4,21 @@ use crate::{
         param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
         util::{
-            constraint_builder::EVMConstraintBuilder,
-            math_gadget::{IsEqualGadget, IsZeroGadget, RangeCheckGadget},
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
             memory_gadget::{address_high, address_low, MemoryExpansionGadget},
-            CachedRegion, Cell, Word,
+            not, or, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
 use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
-use halo2_proofs::plonk::Error;
+use halo2_proofs::{circuit::Value, plonk::Error};
 
+/// Gadget to implement the corresponding out of gas errors for
+/// [`OpcodeId::MLOAD`], [`OpcodeId::MSTORE`] and [`OpcodeId::MSTORE8`].
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorOOGStaticMemoryGadget<F> {
     opcode: Cell<F>,
@@ -31,8 +34,9 @@ pub(crate) struct ErrorOOGStaticMemoryGadget<F> {
     // Even memory size at most could be 2^35 - 1, the qudratic part of memory
     // expansion gas cost could be at most 2^61 - 2^27, due to the constant
     // division by 512, which still fits in 8 bytes.
-    insufficient_gas: RangeCheckGadget<F, N_BYTES_GAS>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     is_mstore8: IsEqualGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
@@ -40,12 +44,21 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
 
     const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasStaticMemoryExpansion;
 
-    // Support other OOG due to pure memory including CREATE, RETURN and REVERT
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
+        cb.require_in_set(
+            "ErrorOutOfGasStaticMemoryExpansion opcode must be MLOAD, MSTORE or MSTORE8",
+            opcode.expr(),
+            vec![
+                OpcodeId::MLOAD.expr(),
+                OpcodeId::MSTORE.expr(),
+                OpcodeId::MSTORE8.expr(),
+            ],
+        );
 
         // Query address by a full word
         let address = cb.query_word_rlc();
+        cb.stack_pop(address.expr());
 
         // Check if this is an MSTORE8
         let is_mstore8 = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::MSTORE8.expr());
@@ -59,22 +72,22 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
 
         // Check if the memory address is too large
         let address_in_range = IsZeroGadget::construct(cb, address_high::expr(&address));
+
         // Check if the amount of gas available is less than the amount of gas
-        // required
-        let insufficient_gas = cb.condition(address_in_range.expr(), |cb| {
-            RangeCheckGadget::construct(
-                cb,
-                OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost()
-                    - cb.curr.state.gas_left.expr(),
-            )
-        });
+        // required. MLOAD, MSTORE and MSTORE8 have the same constant gas cost.
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost(),
+        );
 
-        // Pop the address from the stack
-        // We still have to do this to verify the correctness of `address`
-        cb.stack_pop(address.expr());
+        cb.require_equal(
+            "Memory address is overflow or gas left is less than cost",
+            or::expr([not::expr(address_in_range.expr()), insufficient_gas.expr()]),
+            1.expr(),
+        );
 
-        // TODO: Use ContextSwitchGadget to switch call context to caller's and
-        // consume all gas_left.
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 3.expr());
 
         Self {
             opcode,
@@ -83,6 +96,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
             memory_expansion,
             insufficient_gas,
             is_mstore8,
+            common_error_gadget,
         }
     }
 
@@ -92,10 +106,12 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
 
         // Inputs/Outputs
         let address = block.get_rws(step, 0).stack_value();
@@ -118,21 +134,160 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
         )?;
 
         // Memory expansion
-        self.memory_expansion.assign(
+        let (_, memory_expansion_gas_cost) =
+            self.memory_expansion.assign(
+                region,
+                offset,
+                step.memory_word_size(),
+                [address_low::value(address.to_le_bytes())
+                    + if is_mstore8 == F::ONE { 1 } else { 32 }],
+            )?;
+
+        // Gas insufficient check
+        let gas_cost = OpcodeId::MLOAD.constant_gas_cost().0 + memory_expansion_gas_cost;
+        log::debug!(
+            "ErrorOutOfGasStaticMemoryExpansion: opcode = {}, gas_left = {}, gas_cost = {}",
+            opcode,
+            step.gas_left.0,
+            gas_cost,
+        );
+        self.insufficient_gas.assign(
             region,
             offset,
-            step.memory_word_size(),
-            [
-                address_low::value(address.to_le_bytes())
-                    + if is_mstore8 == F::ONE { 1 } else { 32 },
-            ],
+            F::from(step.gas_left.0),
+            F::from(gas_cost),
         )?;
 
-        // Gas insufficient check
-        // Get `gas_available` variable here once it's available
-        self.insufficient_gas
-            .assign(region, offset, F::from(step.gas_cost.0 - step.gas_left.0))?;
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use eth_types::{
+        bytecode,
+        evm_types::{gas_utils::memory_expansion_gas_cost, GasCost, OpcodeId},
+        Bytecode, U256,
+    };
+    use mock::{
+        eth, generate_mock_call_bytecode, test_ctx::helpers::account_0_code_account_1_no_code,
+        MockCallBytecodeParams, TestContext, MOCK_ACCOUNTS,
+    };
+
+    const TESTING_OPCODES: &[OpcodeId] = &[OpcodeId::MLOAD, OpcodeId::MSTORE, OpcodeId::MSTORE8];
+
+    #[test]
+    fn test_oog_static_memory_expansion() {
+        for (opcode, address) in TESTING_OPCODES
+            .iter()
+            .flat_map(|opcode| [0x40u64, 0x2000, 0x10000].map(|address| (*opcode, address)))
+        {
+            let testing_data = TestingData::new(opcode, U256::from(address));
+
+            test_root(&testing_data);
+            test_internal(&testing_data);
+        }
+    }
+
+    #[test]
+    fn test_oog_static_memory_address_overflow() {
+        for opcode in TESTING_OPCODES {
+            // The memory expansion cost doesn't fit in the available gas, so
+            // the gas left of the transaction is arbitrary.
+            let testing_data = TestingData {
+                bytecode: bytecode! {
+                    PUSH32(U256::from(0xff))
+                    PUSH32(U256::MAX)
+                    .write_op(*opcode)
+                },
+                gas_cost: OpcodeId::PUSH32.constant_gas_cost().0 * 2 + 1,
+            };
+
+            test_root(&testing_data);
+            test_internal(&testing_data);
+        }
+    }
+
+    struct TestingData {
+        bytecode: Bytecode,
+        gas_cost: u64,
+    }
+
+    impl TestingData {
+        fn new(opcode: OpcodeId, address: U256) -> Self {
+            let bytecode = bytecode! {
+                PUSH32(U256::from(0xff))
+                PUSH32(address)
+                .write_op(opcode)
+            };
+
+            let length = if opcode == OpcodeId::MSTORE8 { 1 } else { 32 };
+            let memory_word_size = (address.as_u64() + length + 31) / 32;
+
+            let gas_cost = OpcodeId::PUSH32.constant_gas_cost().0 * 2
+                + opcode.constant_gas_cost().0
+                + memory_expansion_gas_cost(0, memory_word_size);
+
+            Self { bytecode, gas_cost }
+        }
+    }
+
+    fn test_root(testing_data: &TestingData) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(testing_data.bytecode.clone()),
+            |mut txs, accs| {
+                // Decrease expected gas cost (by 1) to trigger out of gas error.
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas((GasCost::TX.0 + testing_data.gas_cost - 1).into());
+            },
+            |block, _tx| block.number(0xcafe_u64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal(testing_data: &TestingData) {
+        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
+
+        // code B gets called by code A, so the call is an internal call.
+        let code_b = testing_data.bytecode.clone();
+        let gas_cost_b = testing_data.gas_cost;
+
+        // code A calls code B.
+        // Decrease expected gas cost (by 1) to trigger out of gas error.
+        let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
+            address: addr_b,
+            pushdata: rand_bytes(32),
+            return_data_offset: 0x00usize,
+            return_data_size: 0x00usize,
+            call_data_length: 0x20usize,
+            call_data_offset: 0x10usize,
+            gas: gas_cost_b - 1,
+            ..MockCallBytecodeParams::default()
+        });
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(addr_b).code(code_b);
+                accs[1].address(addr_a).code(code_a);
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[1].address);
+            },
+            |block, _tx| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
@@ -44,11 +44,12 @@ impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
         // max_degree. otherwise need to do fixed lookup for these opcodes
         // checking.
         cb.require_in_set(
-            "ErrorWriteProtection only happens in [CALL, SSTORE, CREATE, CREATE2, SELFDESTRUCT, LOG0..4 ]",
+            "ErrorWriteProtection only happens in [CALL, SSTORE, TSTORE, CREATE, CREATE2, SELFDESTRUCT, LOG0..4 ]",
             opcode.expr(),
             vec![
                 OpcodeId::CALL.expr(),
                 OpcodeId::SSTORE.expr(),
+                OpcodeId::TSTORE.expr(),
                 OpcodeId::CREATE.expr(),
                 OpcodeId::CREATE2.expr(),
                 OpcodeId::SELFDESTRUCT.expr(),
@@ -218,14 +219,15 @@ mod test {
 
     #[test]
     fn test_write_protection() {
-        // test sstore with write protection error
-        test_internal_write_protection(false);
+        // test sstore and tstore with write protection error
+        test_internal_write_protection(OpcodeId::SSTORE);
+        test_internal_write_protection(OpcodeId::TSTORE);
         // test call with write protection error
-        test_internal_write_protection(true);
+        test_internal_write_protection(OpcodeId::CALL);
     }
 
     // ErrorWriteProtection error happen in internal call
-    fn test_internal_write_protection(is_call: bool) {
+    fn test_internal_write_protection(opcode: OpcodeId) {
         let mut caller_bytecode = bytecode! {
             PUSH1(0)
             PUSH1(0)
@@ -246,7 +248,7 @@ mod test {
             PUSH1(0x02)
         };
 
-        if is_call {
+        if opcode == OpcodeId::CALL {
             callee_bytecode.append(&bytecode! {
                 PUSH1(0)
                 PUSH1(0)
@@ -261,8 +263,8 @@ mod test {
             });
         } else {
             callee_bytecode.append(&bytecode! {
-                // this SSTORE got error: ErrorWriteProtection
-                SSTORE
+                // this SSTORE or TSTORE got error: ErrorWriteProtection
+                .write_op(opcode)
                 STOP
             });
         }
@@ -0,0 +1,192 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                EVMConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            memory_gadget::{MemoryAddressGadget, MemoryCopierGasGadget, MemoryExpansionGadget},
+            CachedRegion,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field};
+use halo2_proofs::plonk::Error;
+
+/// Gadget for MCOPY.  The source and the destination may overlap, so the copy
+/// circuit reads all the source bytes before writing any of the destination
+/// bytes, which takes `2 * length` rw counters.
+#[derive(Clone, Debug)]
+pub(crate) struct McopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    src_memory_address: MemoryAddressGadget<F>,
+    dst_memory_address: MemoryAddressGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+}
+
+impl<F: Field> ExecutionGadget<F> for McopyGadget<F> {
+    const NAME: &'static str = "MCOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::MCOPY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let dst_offset = cb.query_cell_phase2();
+        let src_offset = cb.query_cell_phase2();
+        let length = cb.query_word_rlc();
+
+        // Pop dst_offset, src_offset, length from stack
+        cb.stack_pop(dst_offset.expr());
+        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(length.expr());
+
+        // Calculate the next memory size and the gas cost for this memory
+        // access, which covers both the source and the destination.
+        let src_memory_address = MemoryAddressGadget::construct(cb, src_offset, length.clone());
+        let dst_memory_address = MemoryAddressGadget::construct(cb, dst_offset, length);
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            [src_memory_address.address(), dst_memory_address.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_memory_address.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        cb.condition(dst_memory_address.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::MemoryCopy.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::MemoryCopy.expr(),
+                src_memory_address.offset(),
+                src_memory_address.address(),
+                dst_memory_address.offset(),
+                dst_memory_address.length(),
+                0.expr(), // for MCOPY rlc_acc is 0
+                2.expr() * dst_memory_address.length(),
+            );
+        });
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            // 3 stack pop + `2 * length` memory reads and writes
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            gas_left: Delta(
+                -(OpcodeId::MCOPY.constant_gas_cost().expr() + memory_copier_gas.gas_cost()),
+            ),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            src_memory_address,
+            dst_memory_address,
+            memory_expansion,
+            memory_copier_gas,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [dst_offset, src_offset, length] =
+            [0, 1, 2].map(|index| block.get_rws(step, index).stack_value());
+        let src_memory_address = self
+            .src_memory_address
+            .assign(region, offset, src_offset, length)?;
+        let dst_memory_address = self
+            .dst_memory_address
+            .assign(region, offset, dst_offset, length)?;
+
+        // Memory expansion
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [src_memory_address, dst_memory_address],
+        )?;
+
+        self.memory_copier_gas.assign(
+            region,
+            offset,
+            length.as_u64(),
+            memory_expansion_gas_cost,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
+    use eth_types::bytecode;
+    use mock::test_ctx::TestContext;
+
+    fn test_ok(dst_offset: usize, src_offset: usize, length: usize) {
+        let value = rand_word();
+        let bytecode = bytecode! {
+            // Fill the memory around the source with random bytes.
+            PUSH32(value)
+            PUSH32(src_offset)
+            MSTORE
+            PUSH32(length)
+            PUSH32(src_offset)
+            PUSH32(dst_offset)
+            #[start]
+            MCOPY
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .run();
+    }
+
+    #[test]
+    fn mcopy_gadget_simple() {
+        test_ok(0x40, 0x00, 0x20);
+    }
+
+    #[test]
+    fn mcopy_gadget_empty() {
+        test_ok(0x40, 0x00, 0x00);
+    }
+
+    #[test]
+    fn mcopy_gadget_overlapping() {
+        // The destination is after the source.
+        test_ok(0x05, 0x00, 0x30);
+        // The destination is before the source.
+        test_ok(0x00, 0x05, 0x30);
+    }
+
+    #[test]
+    fn mcopy_gadget_expand_over_source() {
+        test_ok(0x00, 0x100, 0x20);
+    }
+}
@@ -58,13 +58,13 @@ impl<F: Field> ExecutionGadget<F> for MulModGadget<F> {
         let d = cb.query_word_rlc();
         let e = cb.query_word_rlc();
 
-        // 1.  k1 * n + a_reduced  == a
+        // 1. k1 * n + a_reduced  == a
         let modword = ModGadget::construct(cb, [&a, &n, &a_reduced]);
 
-        // 2.  a_reduced * b + 0 == d * 2^256 + e
+        // 2. a_reduced * b + 0 == d * 2^256 + e
         let mul512_left = MulAddWords512Gadget::construct(cb, [&a_reduced, &b, &d, &e], None);
 
-        // 3.  k2 * n + r == d * 2^256 + e
+        // 3. k2 * n + r == d * 2^256 + e
         let mul512_right = MulAddWords512Gadget::construct(cb, [&k, &n, &d, &e], Some(&r));
 
         // (r < n ) or n == 0
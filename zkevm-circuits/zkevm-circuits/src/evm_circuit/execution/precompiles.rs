@@ -1 +1,281 @@
+use bus_mapping::{circuit_input_builder::CopyDataType, precompile::PrecompileCalls};
+use eth_types::{Field, ToScalar};
+use gadgets::util::{not, select, Expr};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
 
+use crate::{
+    evm_circuit::{
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS},
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::{IsZeroGadget, LtGadget, MinMaxGadget},
+            CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    witness::{Block, Call, ExecStep},
+};
+
+mod blake2f;
+mod ec_add;
+mod ec_mul;
+mod ec_pairing;
+mod ecrecover;
+mod identity;
+mod modexp;
+mod ripemd160;
+mod sha256;
+
+pub(crate) use blake2f::Blake2fGadget;
+pub(crate) use ec_add::EcAddGadget;
+pub(crate) use ec_mul::EcMulGadget;
+pub(crate) use ec_pairing::EcPairingGadget;
+pub(crate) use ecrecover::EcrecoverGadget;
+pub(crate) use identity::IdentityGadget;
+pub(crate) use modexp::ModExpGadget;
+pub(crate) use ripemd160::Ripemd160Gadget;
+pub(crate) use sha256::Sha256Gadget;
+
+/// Gadget for the context of a call to a precompiled contract, written by the
+/// call step to the context of the callee.  It also checks that the callee
+/// address is the one of the precompile.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileCallGadget<F> {
+    is_success: Cell<F>,
+    callee_address: Cell<F>,
+    caller_id: Cell<F>,
+    cd_offset: Cell<F>,
+    cd_length: Cell<F>,
+    rd_offset: Cell<F>,
+    rd_length: Cell<F>,
+    rw_counter_end_of_reversion: Cell<F>,
+}
+
+impl<F: Field> PrecompileCallGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, precompile: PrecompileCalls) -> Self {
+        let is_success = cb.query_bool();
+        let [callee_address, caller_id, cd_offset, cd_length, rd_offset, rd_length] =
+            [(); 6].map(|_| cb.query_cell());
+        for (field_tag, cell) in [
+            (CallContextFieldTag::IsSuccess, &is_success),
+            (CallContextFieldTag::CalleeAddress, &callee_address),
+            (CallContextFieldTag::CallerId, &caller_id),
+            (CallContextFieldTag::CallDataOffset, &cd_offset),
+            (CallContextFieldTag::CallDataLength, &cd_length),
+            (CallContextFieldTag::ReturnDataOffset, &rd_offset),
+            (CallContextFieldTag::ReturnDataLength, &rd_length),
+        ] {
+            cb.call_context_lookup(false.expr(), None, field_tag, cell.expr());
+        }
+
+        cb.precompile_info_lookup(
+            ExecutionState::from(precompile).as_u64().expr(),
+            callee_address.expr(),
+            precompile.base_gas_cost().expr(),
+        );
+
+        let rw_counter_end_of_reversion = cb.query_cell();
+        cb.condition(not::expr(is_success.expr()), |cb| {
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::RwCounterEndOfReversion,
+                rw_counter_end_of_reversion.expr(),
+            );
+        });
+
+        Self {
+            is_success,
+            callee_address,
+            caller_id,
+            cd_offset,
+            cd_length,
+            rd_offset,
+            rd_length,
+            rw_counter_end_of_reversion,
+        }
+    }
+
+    pub(crate) fn is_success(&self) -> Expression<F> {
+        self.is_success.expr()
+    }
+
+    pub(crate) fn caller_id(&self) -> Expression<F> {
+        self.caller_id.expr()
+    }
+
+    pub(crate) fn cd_offset(&self) -> Expression<F> {
+        self.cd_offset.expr()
+    }
+
+    pub(crate) fn cd_length(&self) -> Expression<F> {
+        self.cd_length.expr()
+    }
+
+    /// Assign the call context and return the number of rw lookups done by the
+    /// gadget.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<usize, Error> {
+        for (i, cell) in [
+            &self.is_success,
+            &self.callee_address,
+            &self.caller_id,
+            &self.cd_offset,
+            &self.cd_length,
+            &self.rd_offset,
+            &self.rd_length,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let value = block.get_rws(step, i).call_context_value();
+            cell.assign(
+                region,
+                offset,
+                Value::known(
+                    value
+                        .to_scalar()
+                        .expect("unexpected U256 -> Scalar conversion failure"),
+                ),
+            )?;
+        }
+        self.rw_counter_end_of_reversion.assign(
+            region,
+            offset,
+            Value::known(F::from(call.rw_counter_end_of_reversion as u64)),
+        )?;
+
+        Ok(if call.is_success { 7 } else { 8 })
+    }
+}
+
+/// Gadget for the return from a call to a precompiled contract.  It checks the
+/// gas cost, copies the output to the return data area of the caller and
+/// restores the caller's context.
+#[derive(Clone, Debug)]
+pub(crate) struct PrecompileReturnGadget<F> {
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    return_data_copy_size: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    return_data_copy_size_is_zero: IsZeroGadget<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> PrecompileReturnGadget<F> {
+    /// `output_length` is the length of the output of a successful call, which
+    /// is left in the memory of the precompile from offset 0.
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        call: &PrecompileCallGadget<F>,
+        gas_cost: Expression<F>,
+        output_length: Expression<F>,
+    ) -> Self {
+        // The precompiles supported fail only when they run out of gas.
+        let insufficient_gas =
+            LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost.clone());
+        cb.require_equal(
+            "is_success == 1 - insufficient_gas",
+            call.is_success(),
+            1.expr() - insufficient_gas.expr(),
+        );
+
+        // A failed call returns nothing.
+        let output_length = call.is_success() * output_length;
+        let return_data_copy_size =
+            MinMaxGadget::construct(cb, output_length.clone(), call.rd_length.expr());
+        let return_data_copy_size_is_zero =
+            IsZeroGadget::construct(cb, return_data_copy_size.min());
+        cb.condition(not::expr(return_data_copy_size_is_zero.expr()), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                call.caller_id(),
+                CopyDataType::Memory.expr(),
+                0.expr(),
+                return_data_copy_size.min(),
+                call.rd_offset.expr(),
+                return_data_copy_size.min(),
+                0.expr(),
+                2.expr() * return_data_copy_size.min(), // reads + writes
+            );
+        });
+
+        // A failed call consumes all its gas.
+        let restore_context = RestoreContextGadget::construct(
+            cb,
+            call.is_success(),
+            0.expr(),
+            0.expr(),
+            output_length,
+            select::expr(call.is_success(), gas_cost, cb.curr.state.gas_left.expr()),
+            0.expr(),
+        );
+
+        cb.condition(not::expr(call.is_success()), |cb| {
+            let rw_counter_end_of_step =
+                cb.curr.state.rw_counter.expr() + cb.rw_counter_offset() - 1.expr();
+            cb.require_equal(
+                "rw_counter_end_of_reversion = rw_counter_end_of_step + reversible_counter",
+                call.rw_counter_end_of_reversion.expr(),
+                rw_counter_end_of_step + cb.curr.state.reversible_write_counter.expr(),
+            );
+        });
+
+        Self {
+            insufficient_gas,
+            return_data_copy_size,
+            return_data_copy_size_is_zero,
+            restore_context,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        call: &Call,
+        step: &ExecStep,
+        gas_cost: u64,
+        output_length: u64,
+        rw_offset: usize,
+    ) -> Result<(), Error> {
+        self.insufficient_gas.assign(
+            region,
+            offset,
+            F::from(step.gas_left.0),
+            F::from(gas_cost),
+        )?;
+
+        let output_length = if call.is_success { output_length } else { 0 };
+        let copy_size = output_length.min(call.return_data_length);
+        self.return_data_copy_size.assign(
+            region,
+            offset,
+            F::from(output_length),
+            F::from(call.return_data_length),
+        )?;
+        self.return_data_copy_size_is_zero
+            .assign(region, offset, F::from(copy_size))?;
+
+        self.restore_context.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            rw_offset + 2 * copy_size as usize,
+        )
+    }
+}
@@ -0,0 +1,240 @@
+use bus_mapping::{
+    circuit_input_builder::CopyDataType,
+    precompile::{PrecompileCalls, BLAKE2F_INPUT_LEN, BLAKE2F_ROUNDS_LEN},
+};
+use eth_types::{evm_types::GasCost, Field};
+use gadgets::util::Expr;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        from_bytes, rlc, CachedRegion, Cell,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+use super::{PrecompileCallGadget, PrecompileReturnGadget};
+
+/// Number of bytes of the output of the Blake2F precompile, the new state `h`.
+const BLAKE2F_OUTPUT_LEN: usize = 64;
+
+/// Gadget for the Blake2F precompile.  The number of rounds, which sets the
+/// gas cost, is read from the first 4 bytes of the input of the caller even
+/// when the call runs out of gas.  The input of a successful call is
+/// accumulated from the memory of the caller and its compression is looked up
+/// in the Blake2F table.  The output is written to the memory of the
+/// precompile.
+///
+/// The precompile fails on the inputs with an invalid length or final block
+/// flag, which the gadget doesn't support, so only the calls that succeed or
+/// run out of gas can be proven.
+#[derive(Clone, Debug)]
+pub(crate) struct Blake2fGadget<F> {
+    call: PrecompileCallGadget<F>,
+    rounds: [Cell<F>; BLAKE2F_ROUNDS_LEN],
+    input_rlc: Cell<F>,
+    output: [Cell<F>; BLAKE2F_OUTPUT_LEN],
+    ret: PrecompileReturnGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Blake2fGadget<F> {
+    const NAME: &'static str = "BLAKE2F";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileBlake2f;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let call = PrecompileCallGadget::construct(cb, PrecompileCalls::Blake2F);
+
+        cb.require_equal(
+            "cd_length == BLAKE2F_INPUT_LEN",
+            call.cd_length(),
+            BLAKE2F_INPUT_LEN.expr(),
+        );
+
+        // The number of rounds is big-endian.  The bytes are accumulated in
+        // reverse order, so the RLC starts with the least significant byte.
+        let rounds = cb.query_bytes();
+        let rounds_le = rounds
+            .iter()
+            .rev()
+            .map(|byte| byte.expr())
+            .collect::<Vec<_>>();
+        cb.copy_table_lookup(
+            call.caller_id(),
+            CopyDataType::Memory.expr(),
+            cb.curr.state.call_id.expr(),
+            CopyDataType::RlcAcc.expr(),
+            call.cd_offset(),
+            call.cd_offset() + call.cd_length(),
+            0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+            BLAKE2F_ROUNDS_LEN.expr(),
+            rlc::expr(&rounds_le, cb.challenges().keccak_input()),
+            BLAKE2F_ROUNDS_LEN.expr(),
+        );
+        let num_rounds = from_bytes::expr(&rounds_le);
+
+        let input_rlc = cb.query_cell_phase2();
+        let output = cb.query_bytes();
+        cb.condition(call.is_success(), |cb| {
+            cb.copy_table_lookup(
+                call.caller_id(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                call.cd_offset(),
+                call.cd_offset() + call.cd_length(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                call.cd_length(),
+                input_rlc.expr(),
+                call.cd_length(),
+            );
+            let output_rev = output
+                .iter()
+                .rev()
+                .map(|byte| byte.expr())
+                .collect::<Vec<_>>();
+            cb.blake2f_table_lookup(
+                num_rounds.clone(),
+                input_rlc.expr(),
+                rlc::expr(&output_rev, cb.challenges().keccak_input()),
+            );
+            for (address, byte) in output.iter().enumerate() {
+                cb.memory_lookup(1.expr(), address.expr(), byte.expr(), None);
+            }
+        });
+
+        let gas_cost = GasCost::PRECOMPILE_BLAKE2F.expr()
+            + GasCost::PRECOMPILE_BLAKE2F_PER_ROUND.expr() * num_rounds;
+
+        let ret = PrecompileReturnGadget::construct(cb, &call, gas_cost, BLAKE2F_OUTPUT_LEN.expr());
+
+        Self {
+            call,
+            rounds,
+            input_rlc,
+            output,
+            ret,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let mut rw_offset = self.call.assign(region, offset, block, call, step)?;
+
+        let mut rounds = [0; BLAKE2F_ROUNDS_LEN];
+        for (i, (cell, byte)) in self.rounds.iter().zip(rounds.iter_mut()).enumerate() {
+            *byte = block.get_rws(step, rw_offset + i).memory_value();
+            cell.assign(region, offset, Value::known(F::from(*byte as u64)))?;
+        }
+        rw_offset += BLAKE2F_ROUNDS_LEN;
+        let gas_cost = GasCost::PRECOMPILE_BLAKE2F.as_u64()
+            + GasCost::PRECOMPILE_BLAKE2F_PER_ROUND.as_u64() * u32::from_be_bytes(rounds) as u64;
+
+        let mut output = [0; BLAKE2F_OUTPUT_LEN];
+        if call.is_success {
+            let input: Vec<u8> = (rw_offset..rw_offset + BLAKE2F_INPUT_LEN)
+                .map(|i| block.get_rws(step, i).memory_value())
+                .collect();
+            rw_offset += input.len();
+            self.input_rlc.assign(
+                region,
+                offset,
+                region
+                    .challenges()
+                    .keccak_input()
+                    .map(|randomness| rlc::value(input.iter().rev(), randomness)),
+            )?;
+
+            for (i, byte) in output.iter_mut().enumerate() {
+                *byte = block.get_rws(step, rw_offset + i).memory_value();
+            }
+            rw_offset += output.len();
+        } else {
+            self.input_rlc
+                .assign(region, offset, Value::known(F::ZERO))?;
+        }
+        for (cell, byte) in self.output.iter().zip(output) {
+            cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+        }
+
+        self.ret.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            gas_cost,
+            BLAKE2F_OUTPUT_LEN as u64,
+            rw_offset,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{bytecode, word, Bytecode, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    // Compress the input of the EIP-152 test vectors hashing "abc".
+    fn call_blake2f(rounds: u32, gas: u64) -> Bytecode {
+        let rounds = Word::from(rounds) << 224;
+        let h = word!("0x0000000048c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f");
+        bytecode! {
+            PUSH32(rounds + h)
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(word!("0x3af54fa5d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e13"))
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(word!("0x19cde05b61626300000000000000000000000000000000000000000000000000"))
+            PUSH1(0x40)
+            MSTORE
+            PUSH32(word!("0x0000000003000000000000000000000000000000010000000000000000000000"))
+            PUSH1(0xc0)
+            MSTORE
+            PUSH1(0x40)
+            PUSH1(0xe0)
+            PUSH1(0xd5)
+            PUSH1(0x00)
+            PUSH1(PrecompileCalls::Blake2F.address())
+            PUSH32(gas)
+            STATICCALL
+            STOP
+        }
+    }
+
+    #[test]
+    fn precompile_blake2f() {
+        test_ok(call_blake2f(12, 1000));
+        test_ok(call_blake2f(0, 1000));
+    }
+
+    #[test]
+    fn precompile_blake2f_oog() {
+        test_ok(call_blake2f(12, 11));
+    }
+}
@@ -0,0 +1,241 @@
+use bus_mapping::{
+    circuit_input_builder::CopyDataType,
+    precompile::{PrecompileCalls, EC_ADD_INPUT_LEN},
+};
+use eth_types::{evm_types::GasCost, Field};
+use gadgets::util::{not, Expr};
+use halo2_proofs::plonk::Error;
+
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::N_BYTES_MEMORY_ADDRESS,
+    step::ExecutionState,
+    util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        math_gadget::{IsZeroGadget, MinMaxGadget},
+        rlc, CachedRegion, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+use super::{PrecompileCallGadget, PrecompileReturnGadget};
+
+/// Gadget for the ecAdd precompile.  The 128 bytes of input, padded with
+/// zeros, are accumulated from the memory of the caller and split into the
+/// coordinates of the points `p` and `q`.  The sum of the points is looked up
+/// in the ECC table and its coordinates are written to the memory of the
+/// precompile as its output.
+///
+/// The precompile fails on the inputs that are not points of the curve, which
+/// the gadget doesn't support, so only the calls that succeed or run out of
+/// gas can be proven.
+#[derive(Clone, Debug)]
+pub(crate) struct EcAddGadget<F> {
+    call: PrecompileCallGadget<F>,
+    input_is_zero: IsZeroGadget<F>,
+    input_copy_size: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    p_x: Word<F>,
+    p_y: Word<F>,
+    q_x: Word<F>,
+    q_y: Word<F>,
+    r_x: Word<F>,
+    r_y: Word<F>,
+    ret: PrecompileReturnGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EcAddGadget<F> {
+    const NAME: &'static str = "EC_ADD";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileBn256Add;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let call = PrecompileCallGadget::construct(cb, PrecompileCalls::Bn128Add);
+
+        let [p_x, p_y, q_x, q_y] = [(); 4].map(|_| cb.query_word_rlc());
+
+        // The input is accumulated in reverse order, so the RLC starts with
+        // the least significant byte of `q.y`.
+        let input_is_zero = IsZeroGadget::construct(cb, call.cd_length());
+        let input_copy_size =
+            MinMaxGadget::construct(cb, call.cd_length(), EC_ADD_INPUT_LEN.expr());
+        cb.condition(call.is_success() * not::expr(input_is_zero.expr()), |cb| {
+            let input_bytes = [&q_y, &q_x, &p_y, &p_x]
+                .iter()
+                .flat_map(|word| word.cells.iter().map(|cell| cell.expr()))
+                .collect::<Vec<_>>();
+            cb.copy_table_lookup(
+                call.caller_id(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                call.cd_offset(),
+                call.cd_offset() + call.cd_length(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                EC_ADD_INPUT_LEN.expr(),
+                rlc::expr(&input_bytes, cb.challenges().keccak_input()),
+                input_copy_size.min(),
+            );
+        });
+
+        // The coordinates of the sum are written to the memory of the
+        // precompile in big-endian.
+        let [r_x, r_y] = [(); 2].map(|_| cb.query_word_rlc());
+        cb.condition(call.is_success(), |cb| {
+            cb.ecc_table_lookup(
+                PrecompileCalls::Bn128Add.address().expr(),
+                [p_x.expr(), p_y.expr(), q_x.expr(), q_y.expr()],
+                [r_x.expr(), r_y.expr()],
+            );
+            for (address, byte) in [&r_x, &r_y]
+                .iter()
+                .flat_map(|word| word.cells.iter().rev())
+                .enumerate()
+            {
+                cb.memory_lookup(1.expr(), address.expr(), byte.expr(), None);
+            }
+        });
+
+        let ret = PrecompileReturnGadget::construct(
+            cb,
+            &call,
+            GasCost::PRECOMPILE_BN256ADD.expr(),
+            64.expr(),
+        );
+
+        Self {
+            call,
+            input_is_zero,
+            input_copy_size,
+            p_x,
+            p_y,
+            q_x,
+            q_y,
+            r_x,
+            r_y,
+            ret,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let mut rw_offset = self.call.assign(region, offset, block, call, step)?;
+
+        let input_length = call.call_data_length;
+        self.input_is_zero
+            .assign(region, offset, F::from(input_length))?;
+        let input_copy_size = input_length.min(EC_ADD_INPUT_LEN as u64);
+        self.input_copy_size.assign(
+            region,
+            offset,
+            F::from(input_length),
+            F::from(EC_ADD_INPUT_LEN as u64),
+        )?;
+
+        let mut input = [0; EC_ADD_INPUT_LEN];
+        let mut output = [0; 64];
+        if call.is_success {
+            for (i, byte) in input.iter_mut().take(input_copy_size as usize).enumerate() {
+                *byte = block.get_rws(step, rw_offset + i).memory_value();
+            }
+            rw_offset += input_copy_size as usize;
+            for (i, byte) in output.iter_mut().enumerate() {
+                *byte = block.get_rws(step, rw_offset + i).memory_value();
+            }
+            rw_offset += output.len();
+        }
+        for (word, bytes) in [
+            &self.p_x, &self.p_y, &self.q_x, &self.q_y, &self.r_x, &self.r_y,
+        ]
+        .into_iter()
+        .zip(input.chunks_exact(32).chain(output.chunks_exact(32)))
+        {
+            let mut bytes: [u8; 32] = bytes.try_into().unwrap();
+            bytes.reverse();
+            word.assign(region, offset, Some(bytes))?;
+        }
+
+        self.ret.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            GasCost::PRECOMPILE_BN256ADD.as_u64(),
+            64,
+            rw_offset,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{bytecode, Bytecode, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    // Add the generator (1, 2) to its double.
+    fn call_ec_add(input_length: u64, gas: u64) -> Bytecode {
+        let double = (
+            Word::from_dec_str(
+                "1368015179489954701390400359078579693043519447331113978918064868415326638035",
+            )
+            .unwrap(),
+            Word::from_dec_str(
+                "9918110051302171585080402603319702774565515993150576347155970296011118125764",
+            )
+            .unwrap(),
+        );
+        bytecode! {
+            PUSH1(1)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(2)
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(double.0)
+            PUSH1(0x40)
+            MSTORE
+            PUSH32(double.1)
+            PUSH1(0x60)
+            MSTORE
+            PUSH1(0x40)
+            PUSH1(0x80)
+            PUSH1(input_length)
+            PUSH1(0x00)
+            PUSH1(PrecompileCalls::Bn128Add.address())
+            PUSH32(gas)
+            STATICCALL
+            STOP
+        }
+    }
+
+    #[test]
+    fn precompile_ec_add() {
+        test_ok(call_ec_add(128, 1000));
+    }
+
+    #[test]
+    fn precompile_ec_add_oog() {
+        test_ok(call_ec_add(128, 149));
+    }
+}
@@ -0,0 +1,48 @@
+use eth_types::Field;
+use gadgets::util::Expr;
+use halo2_proofs::plonk::Error;
+
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        CachedRegion,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+/// Gadget for the ecPairing precompile, which is not supported by the
+/// circuits: the pairing check doesn't fit in the rows of the super circuit.
+/// bus-mapping refuses to build a witness calling it, and the execution state
+/// is unsatisfiable so that a block calling ecPairing can't be proven.
+#[derive(Clone, Debug)]
+pub(crate) struct EcPairingGadget<F> {
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
+    const NAME: &'static str = "EC_PAIRING";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileBn256Pairing;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        cb.require_zero("ecPairing precompile is not supported", 1.expr());
+
+        Self {
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        _: &mut CachedRegion<'_, '_, F>,
+        _: usize,
+        _: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        _: &ExecStep,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,469 @@
+use bus_mapping::{
+    circuit_input_builder::CopyDataType,
+    precompile::{PrecompileCalls, ECRECOVER_INPUT_LEN},
+};
+use eth_types::{evm_types::GasCost, Field, Word as U256};
+use gadgets::util::{not, Expr};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    param::N_BYTES_MEMORY_ADDRESS,
+    step::ExecutionState,
+    util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        from_bytes,
+        math_gadget::{ComparisonGadget, IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget},
+        rlc, split_u256, CachedRegion, Cell, RandomLinearCombination, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+use super::{PrecompileCallGadget, PrecompileReturnGadget};
+
+/// Gadget for the ecRecover precompile.  The 128 bytes of input, padded with
+/// zeros, are accumulated from the memory of the caller and split into the
+/// message hash and the signature `v`, `r` and `s`.  When the call recovers an
+/// address, the signature is looked up in the sig table and the address is
+/// written to the memory of the precompile as its output.
+///
+/// The sig table only proves the signatures that recover an address, so a
+/// successful call must recover an address whenever `v` is 27 or 28 and `r`
+/// and `s` are in `[1, n)`.  A signature in range whose `r` is not the x of a
+/// curve point, or which recovers the point at infinity, can't be proven.
+#[derive(Clone, Debug)]
+pub(crate) struct EcrecoverGadget<F> {
+    call: PrecompileCallGadget<F>,
+    input_is_zero: IsZeroGadget<F>,
+    input_copy_size: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    msg_hash: Word<F>,
+    sig_v: Word<F>,
+    sig_r: Word<F>,
+    sig_s: Word<F>,
+    sig_v_is_27: IsEqualGadget<F>,
+    sig_v_is_28: IsEqualGadget<F>,
+    sig_r_in_range: SigScalarGadget<F>,
+    sig_s_in_range: SigScalarGadget<F>,
+    sig_r_and_s_in_range: Cell<F>,
+    sig_in_range: Cell<F>,
+    recovered: Cell<F>,
+    recovered_addr: RandomLinearCombination<F, 20>,
+    ret: PrecompileReturnGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for EcrecoverGadget<F> {
+    const NAME: &'static str = "ECRECOVER";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileEcRecover;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let call = PrecompileCallGadget::construct(cb, PrecompileCalls::ECRecover);
+
+        let [msg_hash, sig_v, sig_r, sig_s] = [(); 4].map(|_| cb.query_word_rlc());
+
+        // The input is accumulated in reverse order, so the RLC starts with
+        // the least significant byte of `s`.
+        let input_is_zero = IsZeroGadget::construct(cb, call.cd_length());
+        let input_copy_size =
+            MinMaxGadget::construct(cb, call.cd_length(), ECRECOVER_INPUT_LEN.expr());
+        cb.condition(call.is_success() * not::expr(input_is_zero.expr()), |cb| {
+            let input_bytes = [&sig_s, &sig_r, &sig_v, &msg_hash]
+                .iter()
+                .flat_map(|word| word.cells.iter().map(|cell| cell.expr()))
+                .collect::<Vec<_>>();
+            cb.copy_table_lookup(
+                call.caller_id(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                call.cd_offset(),
+                call.cd_offset() + call.cd_length(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                ECRECOVER_INPUT_LEN.expr(),
+                rlc::expr(&input_bytes, cb.challenges().keccak_input()),
+                input_copy_size.min(),
+            );
+        });
+
+        // The RLC of a word is its least significant byte plus the RLC of
+        // the other bytes, so `v` is 27 iff its RLC is 27.
+        let sig_v_is_27 = IsEqualGadget::construct(cb, sig_v.expr(), 27.expr());
+        let sig_v_is_28 = IsEqualGadget::construct(cb, sig_v.expr(), 28.expr());
+        let sig_r_in_range = SigScalarGadget::construct(cb, &sig_r);
+        let sig_s_in_range = SigScalarGadget::construct(cb, &sig_s);
+        let sig_r_and_s_in_range = cb.copy(sig_r_in_range.expr() * sig_s_in_range.expr());
+        let sig_in_range =
+            cb.copy((sig_v_is_27.expr() + sig_v_is_28.expr()) * sig_r_and_s_in_range.expr());
+
+        let recovered = cb.query_bool();
+        let recovered_addr = cb.query_word_rlc();
+        cb.condition(not::expr(call.is_success()), |cb| {
+            cb.require_zero("a failed call recovers nothing", recovered.expr());
+        });
+        cb.condition(call.is_success() * sig_in_range.expr(), |cb| {
+            cb.require_equal(
+                "a successful call with a signature in range recovers an address",
+                recovered.expr(),
+                1.expr(),
+            );
+        });
+        cb.condition(recovered.expr(), |cb| {
+            for byte in sig_v.cells[1..].iter() {
+                cb.require_zero("sig_v < 256", byte.expr());
+            }
+            cb.require_in_set(
+                "sig_v is 27 or 28",
+                sig_v.cells[0].expr(),
+                vec![27.expr(), 28.expr()],
+            );
+            cb.sig_table_lookup(
+                msg_hash.expr(),
+                sig_v.cells[0].expr(),
+                sig_r.expr(),
+                sig_s.expr(),
+                from_bytes::expr(&recovered_addr.cells),
+            );
+
+            // The address is written to the memory of the precompile in
+            // big-endian, after 12 zero bytes.
+            for address in 0..12 {
+                cb.memory_lookup(1.expr(), address.expr(), 0.expr(), None);
+            }
+            for (address, byte) in recovered_addr.cells.iter().rev().enumerate() {
+                cb.memory_lookup(1.expr(), (12 + address).expr(), byte.expr(), None);
+            }
+        });
+
+        let ret = PrecompileReturnGadget::construct(
+            cb,
+            &call,
+            GasCost::PRECOMPILE_EC_RECOVER_BASE.expr(),
+            recovered.expr() * 32.expr(),
+        );
+
+        Self {
+            call,
+            input_is_zero,
+            input_copy_size,
+            msg_hash,
+            sig_v,
+            sig_r,
+            sig_s,
+            sig_v_is_27,
+            sig_v_is_28,
+            sig_r_in_range,
+            sig_s_in_range,
+            sig_r_and_s_in_range,
+            sig_in_range,
+            recovered,
+            recovered_addr,
+            ret,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let mut rw_offset = self.call.assign(region, offset, block, call, step)?;
+
+        let input_length = call.call_data_length;
+        self.input_is_zero
+            .assign(region, offset, F::from(input_length))?;
+        let input_copy_size = input_length.min(ECRECOVER_INPUT_LEN as u64);
+        self.input_copy_size.assign(
+            region,
+            offset,
+            F::from(input_length),
+            F::from(ECRECOVER_INPUT_LEN as u64),
+        )?;
+
+        let mut input = [0; ECRECOVER_INPUT_LEN];
+        if call.is_success {
+            for (i, byte) in input.iter_mut().take(input_copy_size as usize).enumerate() {
+                *byte = block.get_rws(step, rw_offset + i).memory_value();
+            }
+            rw_offset += input_copy_size as usize;
+        }
+        for (word, bytes) in [&self.msg_hash, &self.sig_v, &self.sig_r, &self.sig_s]
+            .into_iter()
+            .zip(input.chunks_exact(32))
+        {
+            let mut bytes: [u8; 32] = bytes.try_into().unwrap();
+            bytes.reverse();
+            word.assign(region, offset, Some(bytes))?;
+        }
+
+        let [_, sig_v, sig_r, sig_s] =
+            [0, 1, 2, 3].map(|i| U256::from_big_endian(&input[i * 32..(i + 1) * 32]));
+        let sig_v_rlc = region.word_rlc(sig_v);
+        let sig_v_is_27 =
+            self.sig_v_is_27
+                .assign_value(region, offset, sig_v_rlc, Value::known(F::from(27)))?;
+        let sig_v_is_28 =
+            self.sig_v_is_28
+                .assign_value(region, offset, sig_v_rlc, Value::known(F::from(28)))?;
+        let sig_r_in_range = self.sig_r_in_range.assign(region, offset, sig_r)?;
+        let sig_s_in_range = self.sig_s_in_range.assign(region, offset, sig_s)?;
+        let sig_r_and_s_in_range = F::from((sig_r_in_range && sig_s_in_range) as u64);
+        self.sig_r_and_s_in_range
+            .assign(region, offset, Value::known(sig_r_and_s_in_range))?;
+        self.sig_in_range.assign(
+            region,
+            offset,
+            (sig_v_is_27 + sig_v_is_28).map(|v_is_valid| v_is_valid * sig_r_and_s_in_range),
+        )?;
+
+        // A successful call returns the recovered address, which is the last
+        // rw of the step.
+        let recovered = call.is_success
+            && block
+                .get_rws(step, step.rw_indices_len() - 1)
+                .call_context_value()
+                .as_u64()
+                == 32;
+        self.recovered
+            .assign(region, offset, Value::known(F::from(recovered as u64)))?;
+        let mut recovered_addr = [0; 20];
+        if recovered {
+            for (i, byte) in recovered_addr.iter_mut().enumerate() {
+                *byte = block.get_rws(step, rw_offset + 12 + i).memory_value();
+            }
+            recovered_addr.reverse();
+            rw_offset += 32;
+        }
+        self.recovered_addr
+            .assign(region, offset, Some(recovered_addr))?;
+
+        self.ret.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            GasCost::PRECOMPILE_EC_RECOVER_BASE.as_u64(),
+            if recovered { 32 } else { 0 },
+            rw_offset,
+        )
+    }
+}
+
+/// The order `n` of the secp256k1 curve, split in its high and low 128 bits.
+const SECP256K1_N_HI: u128 = 0xfffffffffffffffffffffffffffffffe;
+const SECP256K1_N_LO: u128 = 0xbaaedce6af48a03bbfd25e8cd0364141;
+
+/// Returns `1` when a signature scalar `r` or `s` is in `[1, n)`, and returns
+/// `0` otherwise.
+#[derive(Clone, Debug)]
+struct SigScalarGadget<F> {
+    is_zero: IsZeroGadget<F>,
+    comparison_hi: ComparisonGadget<F, 16>,
+    lt_lo: LtGadget<F, 16>,
+    lt_n: Cell<F>,
+    in_range: Cell<F>,
+}
+
+impl<F: Field> SigScalarGadget<F> {
+    fn construct(cb: &mut EVMConstraintBuilder<F>, value: &Word<F>) -> Self {
+        let is_zero = IsZeroGadget::construct(cb, value.expr());
+        let comparison_hi = ComparisonGadget::construct(
+            cb,
+            from_bytes::expr(&value.cells[16..]),
+            Expression::Constant(F::from_u128(SECP256K1_N_HI)),
+        );
+        let lt_lo = LtGadget::construct(
+            cb,
+            from_bytes::expr(&value.cells[..16]),
+            Expression::Constant(F::from_u128(SECP256K1_N_LO)),
+        );
+        let (hi_lt, hi_eq) = comparison_hi.expr();
+        let lt_n = cb.copy(hi_lt + hi_eq * lt_lo.expr());
+        let in_range = cb.copy(not::expr(is_zero.expr()) * lt_n.expr());
+
+        Self {
+            is_zero,
+            comparison_hi,
+            lt_lo,
+            lt_n,
+            in_range,
+        }
+    }
+
+    fn expr(&self) -> Expression<F> {
+        self.in_range.expr()
+    }
+
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        value: U256,
+    ) -> Result<bool, Error> {
+        self.is_zero
+            .assign_value(region, offset, region.word_rlc(value))?;
+        let (lo, hi) = split_u256(&value);
+        self.comparison_hi.assign(
+            region,
+            offset,
+            F::from_u128(hi.as_u128()),
+            F::from_u128(SECP256K1_N_HI),
+        )?;
+        self.lt_lo.assign(
+            region,
+            offset,
+            F::from_u128(lo.as_u128()),
+            F::from_u128(SECP256K1_N_LO),
+        )?;
+        let lt_n = hi.as_u128() < SECP256K1_N_HI
+            || (hi.as_u128() == SECP256K1_N_HI && lo.as_u128() < SECP256K1_N_LO);
+        self.lt_n
+            .assign(region, offset, Value::known(F::from(lt_n as u64)))?;
+        let in_range = !value.is_zero() && lt_n;
+        self.in_range
+            .assign(region, offset, Value::known(F::from(in_range as u64)))?;
+        Ok(in_range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{bytecode, word, Bytecode, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn call_ecrecover(
+        msg_hash: Word,
+        sig_v: Word,
+        sig_r: Word,
+        sig_s: Word,
+        input_length: u64,
+        gas: u64,
+    ) -> Bytecode {
+        bytecode! {
+            PUSH32(msg_hash)
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(sig_v)
+            PUSH1(0x20)
+            MSTORE
+            PUSH32(sig_r)
+            PUSH1(0x40)
+            MSTORE
+            PUSH32(sig_s)
+            PUSH1(0x60)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x80)
+            PUSH1(input_length)
+            PUSH1(0x00)
+            PUSH1(PrecompileCalls::ECRecover.address())
+            PUSH32(gas)
+            STATICCALL
+            STOP
+        }
+    }
+
+    const MSG_HASH: &str = "0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3";
+    const SIG_R: &str = "0x9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608";
+    const SIG_S: &str = "0x4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada";
+    const SECP256K1_N: &str = "0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+    #[test]
+    fn precompile_ecrecover() {
+        test_ok(call_ecrecover(
+            word!(MSG_HASH),
+            Word::from(28),
+            word!(SIG_R),
+            word!(SIG_S),
+            128,
+            5000,
+        ));
+    }
+
+    #[test]
+    fn precompile_ecrecover_invalid_sig_v() {
+        test_ok(call_ecrecover(
+            word!(MSG_HASH),
+            Word::from(29),
+            word!(SIG_R),
+            word!(SIG_S),
+            128,
+            5000,
+        ));
+    }
+
+    #[test]
+    fn precompile_ecrecover_sig_out_of_range() {
+        // `r` and `s` must be below the order of the curve.
+        test_ok(call_ecrecover(
+            word!(MSG_HASH),
+            Word::from(28),
+            word!(SECP256K1_N),
+            word!(SIG_S),
+            128,
+            5000,
+        ));
+        test_ok(call_ecrecover(
+            word!(MSG_HASH),
+            Word::from(28),
+            word!(SIG_R),
+            word!(SECP256K1_N),
+            128,
+            5000,
+        ));
+    }
+
+    #[test]
+    fn precompile_ecrecover_short_input() {
+        // The input is padded with zeros, so `s` is zero.
+        test_ok(call_ecrecover(
+            word!(MSG_HASH),
+            Word::from(28),
+            word!(SIG_R),
+            word!(SIG_S),
+            96,
+            5000,
+        ));
+        test_ok(call_ecrecover(
+            word!(MSG_HASH),
+            Word::from(28),
+            word!(SIG_R),
+            word!(SIG_S),
+            0,
+            5000,
+        ));
+    }
+
+    #[test]
+    fn precompile_ecrecover_oog() {
+        test_ok(call_ecrecover(
+            word!(MSG_HASH),
+            Word::from(28),
+            word!(SIG_R),
+            word!(SIG_S),
+            128,
+            2999,
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use bus_mapping::{circuit_input_builder::CopyDataType, precompile::PrecompileCalls};
+use eth_types::{evm_types::GasCost, Field};
+use gadgets::util::{not, Expr};
+use halo2_proofs::plonk::Error;
+
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        constraint_builder::EVMConstraintBuilder, math_gadget::IsZeroGadget,
+        memory_gadget::MemoryWordSizeGadget, CachedRegion,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+use super::{PrecompileCallGadget, PrecompileReturnGadget};
+
+/// Gadget for the identity precompile, which returns its input.  The input is
+/// copied from the memory of the caller to the memory of the precompile, where
+/// it is the output of the call.
+#[derive(Clone, Debug)]
+pub(crate) struct IdentityGadget<F> {
+    call: PrecompileCallGadget<F>,
+    input_is_zero: IsZeroGadget<F>,
+    input_word_size: MemoryWordSizeGadget<F>,
+    ret: PrecompileReturnGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for IdentityGadget<F> {
+    const NAME: &'static str = "IDENTITY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileIdentity;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let call = PrecompileCallGadget::construct(cb, PrecompileCalls::Identity);
+
+        let input_is_zero = IsZeroGadget::construct(cb, call.cd_length());
+        cb.condition(call.is_success() * not::expr(input_is_zero.expr()), |cb| {
+            cb.copy_table_lookup(
+                call.caller_id(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                call.cd_offset(),
+                call.cd_offset() + call.cd_length(),
+                0.expr(),
+                call.cd_length(),
+                0.expr(),
+                2.expr() * call.cd_length(), // reads + writes
+            );
+        });
+
+        let input_word_size = MemoryWordSizeGadget::construct(cb, call.cd_length());
+        let gas_cost = GasCost::PRECOMPILE_IDENTITY_BASE.expr()
+            + GasCost::PRECOMPILE_IDENTITY_PER_WORD.expr() * input_word_size.expr();
+
+        let ret = PrecompileReturnGadget::construct(cb, &call, gas_cost, call.cd_length());
+
+        Self {
+            call,
+            input_is_zero,
+            input_word_size,
+            ret,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let mut rw_offset = self.call.assign(region, offset, block, call, step)?;
+
+        let input_length = call.call_data_length;
+        self.input_is_zero
+            .assign(region, offset, F::from(input_length))?;
+        if call.is_success {
+            rw_offset += 2 * input_length as usize;
+        }
+
+        let input_word_size = self.input_word_size.assign(region, offset, input_length)?;
+        let gas_cost = GasCost::PRECOMPILE_IDENTITY_BASE.as_u64()
+            + GasCost::PRECOMPILE_IDENTITY_PER_WORD.as_u64() * input_word_size;
+
+        self.ret.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            gas_cost,
+            input_length,
+            rw_offset,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{bytecode, word, Bytecode};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn call_identity(input_length: u64, output_length: u64, gas: u64) -> Bytecode {
+        bytecode! {
+            PUSH32(word!("0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"))
+            PUSH1(0)
+            MSTORE
+            PUSH1(output_length)
+            PUSH1(0x40)
+            PUSH1(input_length)
+            PUSH1(0)
+            PUSH1(PrecompileCalls::Identity.address())
+            PUSH32(gas)
+            STATICCALL
+            STOP
+        }
+    }
+
+    #[test]
+    fn precompile_identity() {
+        test_ok(call_identity(32, 32, 1000));
+        // The output is longer than the return data area.
+        test_ok(call_identity(20, 10, 1000));
+        // The output is shorter than the return data area.
+        test_ok(call_identity(10, 20, 1000));
+        test_ok(call_identity(0, 32, 1000));
+    }
+
+    #[test]
+    fn precompile_identity_oog() {
+        test_ok(call_identity(32, 32, 17));
+    }
+
+    #[test]
+    fn precompile_identity_with_value() {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x40)
+            PUSH1(0x20)
+            PUSH1(0)
+            PUSH1(1)
+            PUSH1(PrecompileCalls::Identity.address())
+            PUSH2(1000)
+            CALL
+            STOP
+        };
+        test_ok(code);
+    }
+}
@@ -0,0 +1,604 @@
+use bus_mapping::{
+    circuit_input_builder::CopyDataType,
+    precompile::{PrecompileCalls, MODEXP_HEADER_LEN, MODEXP_MAX_LEN},
+};
+use eth_types::{evm_types::GasCost, Field, ToBigEndian, ToLittleEndian};
+use gadgets::util::{not, sum, Expr};
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{Error, Expression},
+};
+
+use crate::evm_circuit::{
+    param::N_BYTES_MEMORY_ADDRESS,
+    step::ExecutionState,
+    util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        math_gadget::{IsZeroGadget, MinMaxGadget, RangeCheckGadget},
+        rlc, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+use super::{PrecompileCallGadget, PrecompileReturnGadget};
+use crate::evm_circuit::execution::ExecutionGadget;
+
+/// Gadget for the modexp precompile on operands of up to 32 bytes, the only
+/// ones bus-mapping accepts.  The lengths of the operands are one-hot encoded,
+/// which bounds them, and the header and the operands are accumulated from the
+/// memory of the caller even when the call runs out of gas, since the gas cost
+/// depends on the lengths and on the exponent.
+///
+/// The result of a successful call with a nonzero modulus is looked up in the
+/// ModExp table, while a zero modulus gives a zero result.  The result is
+/// written to the memory of the precompile in big-endian, on the length of the
+/// modulus.
+#[derive(Clone, Debug)]
+pub(crate) struct ModExpGadget<F> {
+    call: PrecompileCallGadget<F>,
+    /// One-hot encoding of the lengths of the base, the exponent and the
+    /// modulus.
+    lengths: [[Cell<F>; MODEXP_MAX_LEN + 1]; 3],
+    base: Word<F>,
+    exponent: Word<F>,
+    modulus: Word<F>,
+    input_reads: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    num_input_reads: Cell<F>,
+    /// RLCs of the input up to the base, up to the exponent and in whole.
+    input_rlcs: [Cell<F>; 3],
+    /// One-hot encoding of the most significant nonzero byte of the exponent,
+    /// in big-endian.
+    exponent_msb: [Cell<F>; MODEXP_MAX_LEN],
+    exponent_msb_byte: Cell<F>,
+    /// One-hot encoding of the number of bits of the most significant nonzero
+    /// byte of the exponent.
+    exponent_msb_bits: [Cell<F>; 8],
+    words: MinMaxGadget<F, 1>,
+    complexity: Cell<F>,
+    iterations: MinMaxGadget<F, 2>,
+    quotient: Cell<F>,
+    quotient_range: RangeCheckGadget<F, 2>,
+    remainder: Cell<F>,
+    min_gas_cost: MinMaxGadget<F, 2>,
+    gas_cost: Cell<F>,
+    modulus_is_zero: IsZeroGadget<F>,
+    is_proved: Cell<F>,
+    result: Word<F>,
+    output: [Cell<F>; MODEXP_MAX_LEN],
+    output_writes: [Cell<F>; MODEXP_MAX_LEN],
+    ret: PrecompileReturnGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ModExpGadget<F> {
+    const NAME: &'static str = "MODEXP";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileBigModExp;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let call = PrecompileCallGadget::construct(cb, PrecompileCalls::Modexp);
+
+        let lengths = [(); 3].map(|_| [(); MODEXP_MAX_LEN + 1].map(|_| cb.query_bool()));
+        for selectors in lengths.iter() {
+            cb.require_equal("length is one-hot encoded", sum::expr(selectors), 1.expr());
+        }
+        let [base_length, exponent_length, modulus_length] =
+            [0, 1, 2].map(|idx| one_hot_value(&lengths[idx]));
+
+        // The bytes of the operands beyond their length are zero.
+        let [base, exponent, modulus] = [(); 3].map(|_| cb.query_word_rlc());
+        for (selectors, word) in lengths.iter().zip([&base, &exponent, &modulus]) {
+            for (idx, byte) in word.cells.iter().enumerate() {
+                cb.require_zero(
+                    "operand byte beyond the length is zero",
+                    sum::expr(&selectors[..=idx]) * byte.expr(),
+                );
+            }
+        }
+
+        // The input is the header with the lengths as big-endian words,
+        // followed by the operands in big-endian.  Its RLC is accumulated one
+        // operand at a time, with the powers of the randomness of their
+        // lengths.
+        let keccak_input = cb.challenges().keccak_input();
+        let powers_of_randomness = cb
+            .challenges()
+            .keccak_powers_of_randomness::<MODEXP_MAX_LEN>();
+        let power_of_length = |selectors: &[Cell<F>; MODEXP_MAX_LEN + 1]| {
+            selectors[0].expr()
+                + sum::expr(
+                    selectors[1..]
+                        .iter()
+                        .zip(powers_of_randomness.iter())
+                        .map(|(selector, power)| selector.expr() * power.clone()),
+                )
+        };
+        let word_power = powers_of_randomness[MODEXP_MAX_LEN - 1].clone();
+        let header_rlc = base_length.clone() * word_power.clone() * word_power.clone()
+            + exponent_length.clone() * word_power
+            + modulus_length.clone();
+        let operands_rlc = [&base, &exponent, &modulus].map(|word| {
+            rlc::expr(
+                &word
+                    .cells
+                    .iter()
+                    .map(|cell| cell.expr())
+                    .collect::<Vec<_>>(),
+                keccak_input.clone(),
+            )
+        });
+        let input_rlcs = [(); 3].map(|_| cb.query_cell_phase2());
+        let mut prev_rlc = header_rlc;
+        for ((input_rlc, selectors), operand_rlc) in
+            input_rlcs.iter().zip(lengths.iter()).zip(operands_rlc)
+        {
+            cb.require_equal(
+                "input rlc accumulates the operand",
+                input_rlc.expr(),
+                prev_rlc * power_of_length(selectors) + operand_rlc,
+            );
+            prev_rlc = input_rlc.expr();
+        }
+
+        let input_length = MODEXP_HEADER_LEN.expr()
+            + base_length.clone()
+            + exponent_length.clone()
+            + modulus_length.clone();
+        let input_reads = MinMaxGadget::construct(cb, call.cd_length(), input_length.clone());
+        let num_input_reads = cb.copy(input_reads.min());
+        cb.copy_table_lookup(
+            call.caller_id(),
+            CopyDataType::Memory.expr(),
+            cb.curr.state.call_id.expr(),
+            CopyDataType::RlcAcc.expr(),
+            call.cd_offset(),
+            call.cd_offset() + call.cd_length(),
+            0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+            input_length,
+            input_rlcs[2].expr(),
+            num_input_reads.expr(),
+        );
+
+        // The number of bits of the exponent is found from its most
+        // significant nonzero byte, the bytes before it being zero.
+        let exponent_msb = [(); MODEXP_MAX_LEN].map(|_| cb.query_bool());
+        let exponent_is_nonzero = sum::expr(&exponent_msb);
+        cb.require_boolean(
+            "at most one most significant byte",
+            exponent_is_nonzero.clone(),
+        );
+        let exponent_be = exponent.cells.iter().rev().collect::<Vec<_>>();
+        for (idx, byte) in exponent_be.iter().enumerate() {
+            cb.require_zero(
+                "exponent bytes before the most significant one are zero",
+                not::expr(sum::expr(&exponent_msb[..=idx])) * byte.expr(),
+            );
+        }
+        let exponent_msb_byte = cb.copy(sum::expr(
+            exponent_msb
+                .iter()
+                .zip(exponent_be.iter())
+                .map(|(selector, byte)| selector.expr() * byte.expr()),
+        ));
+        let exponent_msb_bits = [(); 8].map(|_| cb.query_bool());
+        cb.require_equal(
+            "the most significant byte has a number of bits if the exponent is nonzero",
+            sum::expr(&exponent_msb_bits),
+            exponent_is_nonzero.clone(),
+        );
+        let msb_pow = sum::expr(
+            exponent_msb_bits
+                .iter()
+                .enumerate()
+                .map(|(idx, selector)| selector.expr() * (1u64 << idx).expr()),
+        );
+        cb.condition(exponent_is_nonzero, |cb| {
+            cb.range_lookup(exponent_msb_byte.expr() - msb_pow.clone(), 256);
+            cb.range_lookup(
+                2.expr() * msb_pow - 1.expr() - exponent_msb_byte.expr(),
+                256,
+            );
+        });
+        let exponent_bits = sum::expr(
+            exponent_msb
+                .iter()
+                .enumerate()
+                .map(|(idx, selector)| selector.expr() * (8 * (MODEXP_MAX_LEN - 1 - idx)).expr()),
+        ) + one_hot_value(&exponent_msb_bits)
+            + sum::expr(&exponent_msb_bits);
+
+        // EIP-2565: max(200, words(max(base_length, modulus_length)) ** 2
+        //   * max(exponent_bits - 1, 1) / 3)
+        let num_words = |selectors: &[Cell<F>; MODEXP_MAX_LEN + 1]| {
+            sum::expr(
+                selectors
+                    .iter()
+                    .enumerate()
+                    .map(|(length, selector)| selector.expr() * ((length + 7) / 8).expr()),
+            )
+        };
+        let words = MinMaxGadget::construct(cb, num_words(&lengths[0]), num_words(&lengths[2]));
+        let complexity = cb.copy(words.max() * words.max());
+        let iterations = MinMaxGadget::construct(cb, exponent_bits, 2.expr());
+        let quotient = cb.query_cell();
+        let quotient_range = RangeCheckGadget::construct(cb, quotient.expr());
+        let remainder = cb.query_cell();
+        cb.range_lookup(remainder.expr(), 5);
+        cb.range_lookup(remainder.expr() + 2.expr(), 5);
+        cb.require_equal(
+            "complexity * iterations == quotient * divisor + remainder",
+            complexity.expr() * (iterations.max() - 1.expr()),
+            quotient.expr() * GasCost::PRECOMPILE_MODEXP_QUAD_DIVISOR.expr() + remainder.expr(),
+        );
+        let min_gas_cost =
+            MinMaxGadget::construct(cb, GasCost::PRECOMPILE_MODEXP_MIN.expr(), quotient.expr());
+        let gas_cost = cb.copy(min_gas_cost.max());
+
+        let modulus_is_zero = IsZeroGadget::construct(cb, sum::expr(&modulus.cells));
+        let result = cb.query_word_rlc();
+        cb.condition(modulus_is_zero.expr(), |cb| {
+            cb.require_zero(
+                "result is zero for a zero modulus",
+                sum::expr(&result.cells),
+            );
+        });
+        let is_proved = cb.copy(call.is_success() * not::expr(modulus_is_zero.expr()));
+        cb.condition(is_proved.expr(), |cb| {
+            cb.modexp_table_lookup(
+                [base.expr(), exponent.expr(), modulus.expr()],
+                result.expr(),
+            );
+        });
+
+        // The output is the result in big-endian on the length of the modulus,
+        // the result being smaller than the modulus.
+        let output = cb.query_bytes();
+        let output_writes = [(); MODEXP_MAX_LEN].map(|_| cb.query_bool());
+        for (address, (byte, is_write)) in output.iter().zip(output_writes.iter()).enumerate() {
+            let selectors = &lengths[2][address + 1..];
+            cb.require_equal(
+                "output byte is the result byte at the address",
+                byte.expr(),
+                sum::expr(
+                    selectors
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, selector)| selector.expr() * result.cells[idx].expr()),
+                ),
+            );
+            cb.require_equal(
+                "output byte is written if the address is within the output",
+                is_write.expr(),
+                call.is_success() * sum::expr(selectors),
+            );
+            cb.condition(is_write.expr(), |cb| {
+                cb.memory_lookup(1.expr(), address.expr(), byte.expr(), None);
+            });
+        }
+
+        let ret = PrecompileReturnGadget::construct(cb, &call, gas_cost.expr(), modulus_length);
+
+        Self {
+            call,
+            lengths,
+            base,
+            exponent,
+            modulus,
+            input_reads,
+            num_input_reads,
+            input_rlcs,
+            exponent_msb,
+            exponent_msb_byte,
+            exponent_msb_bits,
+            words,
+            complexity,
+            iterations,
+            quotient,
+            quotient_range,
+            remainder,
+            min_gas_cost,
+            gas_cost,
+            modulus_is_zero,
+            is_proved,
+            result,
+            output,
+            output_writes,
+            ret,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let mut rw_offset = self.call.assign(region, offset, block, call, step)?;
+
+        // The lengths are in the last byte of the words of the header, the
+        // input being padded with zeros.
+        let read_input = |length: usize| {
+            let mut input: Vec<u8> = (rw_offset..rw_offset + length)
+                .map(|i| block.get_rws(step, i).memory_value())
+                .collect();
+            input.resize(MODEXP_HEADER_LEN + 3 * MODEXP_MAX_LEN, 0);
+            input
+        };
+        let cd_length = call.call_data_length as usize;
+        let header = read_input(cd_length.min(MODEXP_HEADER_LEN));
+        let lengths = [0, 1, 2].map(|idx| header[(idx + 1) * 32 - 1] as usize);
+        let input_length = MODEXP_HEADER_LEN + lengths.iter().sum::<usize>();
+        let num_input_reads = cd_length.min(input_length);
+        let input = read_input(num_input_reads);
+        rw_offset += num_input_reads;
+
+        for (selectors, length) in self.lengths.iter().zip(lengths) {
+            for (idx, selector) in selectors.iter().enumerate() {
+                selector.assign(
+                    region,
+                    offset,
+                    Value::known(F::from((idx == length) as u64)),
+                )?;
+            }
+        }
+        let mut operand_offset = MODEXP_HEADER_LEN;
+        let [base, exponent, modulus] = lengths.map(|length| {
+            let word =
+                eth_types::Word::from_big_endian(&input[operand_offset..operand_offset + length]);
+            operand_offset += length;
+            word
+        });
+        for (cells, word) in [&self.base, &self.exponent, &self.modulus]
+            .into_iter()
+            .zip([base, exponent, modulus])
+        {
+            cells.assign(region, offset, Some(word.to_le_bytes()))?;
+        }
+
+        self.input_reads.assign(
+            region,
+            offset,
+            F::from(cd_length as u64),
+            F::from(input_length as u64),
+        )?;
+        self.num_input_reads.assign(
+            region,
+            offset,
+            Value::known(F::from(num_input_reads as u64)),
+        )?;
+        let mut prefix_length = MODEXP_HEADER_LEN;
+        for (cell, length) in self.input_rlcs.iter().zip(lengths) {
+            prefix_length += length;
+            let prefix = &input[..prefix_length];
+            cell.assign(
+                region,
+                offset,
+                region
+                    .challenges()
+                    .keccak_input()
+                    .map(|randomness| rlc::value(prefix.iter().rev(), randomness)),
+            )?;
+        }
+
+        let exponent_be = exponent.to_be_bytes();
+        let msb = exponent_be.iter().position(|byte| *byte != 0);
+        for (idx, selector) in self.exponent_msb.iter().enumerate() {
+            selector.assign(
+                region,
+                offset,
+                Value::known(F::from((msb == Some(idx)) as u64)),
+            )?;
+        }
+        let msb_byte = msb.map_or(0, |idx| exponent_be[idx]);
+        self.exponent_msb_byte
+            .assign(region, offset, Value::known(F::from(msb_byte as u64)))?;
+        let msb_bits = 8 - msb_byte.leading_zeros() as usize;
+        for (idx, selector) in self.exponent_msb_bits.iter().enumerate() {
+            selector.assign(
+                region,
+                offset,
+                Value::known(F::from((idx + 1 == msb_bits) as u64)),
+            )?;
+        }
+        let exponent_bits = exponent.bits() as u64;
+
+        let num_words = |length: usize| (length as u64 + 7) / 8;
+        let words = num_words(lengths[0]).max(num_words(lengths[2]));
+        self.words.assign(
+            region,
+            offset,
+            F::from(num_words(lengths[0])),
+            F::from(num_words(lengths[2])),
+        )?;
+        let complexity = words * words;
+        self.complexity
+            .assign(region, offset, Value::known(F::from(complexity)))?;
+        self.iterations
+            .assign(region, offset, F::from(exponent_bits), F::from(2))?;
+        let iterations = exponent_bits.max(2) - 1;
+        let divisor = GasCost::PRECOMPILE_MODEXP_QUAD_DIVISOR.as_u64();
+        let quotient = complexity * iterations / divisor;
+        let remainder = complexity * iterations % divisor;
+        self.quotient
+            .assign(region, offset, Value::known(F::from(quotient)))?;
+        self.quotient_range
+            .assign(region, offset, F::from(quotient))?;
+        self.remainder
+            .assign(region, offset, Value::known(F::from(remainder)))?;
+        let min_gas = GasCost::PRECOMPILE_MODEXP_MIN.as_u64();
+        self.min_gas_cost
+            .assign(region, offset, F::from(min_gas), F::from(quotient))?;
+        let gas_cost = min_gas.max(quotient);
+        self.gas_cost
+            .assign(region, offset, Value::known(F::from(gas_cost)))?;
+
+        let modulus_sum = modulus
+            .to_le_bytes()
+            .iter()
+            .map(|byte| *byte as u64)
+            .sum::<u64>();
+        self.modulus_is_zero
+            .assign(region, offset, F::from(modulus_sum))?;
+        self.is_proved.assign(
+            region,
+            offset,
+            Value::known(F::from((call.is_success && !modulus.is_zero()) as u64)),
+        )?;
+
+        let modulus_length = lengths[2];
+        let mut output = [0; MODEXP_MAX_LEN];
+        if call.is_success {
+            for (i, byte) in output[..modulus_length].iter_mut().enumerate() {
+                *byte = block.get_rws(step, rw_offset + i).memory_value();
+            }
+            rw_offset += modulus_length;
+        }
+        let result = eth_types::Word::from_big_endian(&output[..modulus_length]);
+        self.result
+            .assign(region, offset, Some(result.to_le_bytes()))?;
+        for (address, (cell, is_write)) in self
+            .output
+            .iter()
+            .zip(self.output_writes.iter())
+            .enumerate()
+        {
+            cell.assign(
+                region,
+                offset,
+                Value::known(F::from(output[address] as u64)),
+            )?;
+            is_write.assign(
+                region,
+                offset,
+                Value::known(F::from(
+                    (call.is_success && address < modulus_length) as u64,
+                )),
+            )?;
+        }
+
+        self.ret.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            gas_cost,
+            modulus_length as u64,
+            rw_offset,
+        )
+    }
+}
+
+/// Value encoded by the one-hot `selectors`, the index of the one set.
+fn one_hot_value<F: Field>(selectors: &[Cell<F>]) -> Expression<F> {
+    sum::expr(
+        selectors
+            .iter()
+            .enumerate()
+            .map(|(value, selector)| selector.expr() * value.expr()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{bytecode, word, Bytecode, Word};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    // Call modexp on the operands of `lengths` bytes, stored in big-endian
+    // after the header, with `input_length` bytes of call data.
+    fn call_modexp(
+        lengths: [u64; 3],
+        [base, exponent, modulus]: [Word; 3],
+        input_length: u64,
+        gas: u64,
+    ) -> Bytecode {
+        let mut code = Bytecode::default();
+        // The operands are stored from the last one, each word overwriting
+        // the zeros on the left of the next operand, and then the header.
+        let ends = [
+            96 + lengths[0],
+            96 + lengths[0] + lengths[1],
+            96 + lengths[0] + lengths[1] + lengths[2],
+        ];
+        for (word, end) in [(modulus, ends[2]), (exponent, ends[1]), (base, ends[0])] {
+            code.append(&bytecode! {
+                PUSH32(word)
+                PUSH32(end - 32)
+                MSTORE
+            });
+        }
+        for (length, address) in lengths.into_iter().zip([0x00, 0x20, 0x40]) {
+            code.append(&bytecode! {
+                PUSH1(length)
+                PUSH1(address)
+                MSTORE
+            });
+        }
+        code.append(&bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH32(input_length)
+            PUSH1(0x00)
+            PUSH1(PrecompileCalls::Modexp.address())
+            PUSH32(gas)
+            STATICCALL
+            STOP
+        });
+        code
+    }
+
+    #[test]
+    fn precompile_modexp() {
+        // 3 ** 5 % 7
+        let operands = [3, 5, 7].map(Word::from);
+        test_ok(call_modexp([1, 1, 1], operands, 99, 1000));
+        let operands = [
+            word!("0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe"),
+            Word::MAX,
+            word!("0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47"),
+        ];
+        test_ok(call_modexp([32, 32, 32], operands, 192, 2000));
+        let operands = [0x1234, 0, 0x10001].map(Word::from);
+        test_ok(call_modexp([2, 0, 3], operands, 101, 1000));
+    }
+
+    #[test]
+    fn precompile_modexp_truncated_input() {
+        // The missing bytes of the input are zeros.
+        let operands = [3, 5, 7].map(Word::from);
+        test_ok(call_modexp([1, 1, 2], operands, 99, 1000));
+        test_ok(call_modexp([1, 1, 1], operands, 40, 1000));
+        test_ok(call_modexp([0, 0, 0], operands, 0, 1000));
+    }
+
+    #[test]
+    fn precompile_modexp_zero_modulus() {
+        let operands = [3, 5, 0].map(Word::from);
+        test_ok(call_modexp([1, 1, 1], operands, 99, 1000));
+        test_ok(call_modexp([1, 1, 0], operands, 98, 1000));
+    }
+
+    #[test]
+    fn precompile_modexp_oog() {
+        let operands = [3, 5, 7].map(Word::from);
+        test_ok(call_modexp([1, 1, 1], operands, 99, 199));
+        let operands = [Word::MAX; 3];
+        test_ok(call_modexp([32, 32, 32], operands, 192, 1359));
+    }
+}
@@ -0,0 +1,185 @@
+use bus_mapping::{circuit_input_builder::CopyDataType, precompile::PrecompileCalls};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
+use gadgets::util::{not, Expr};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+        math_gadget::IsZeroGadget,
+        memory_gadget::MemoryWordSizeGadget,
+        rlc, CachedRegion, Cell, Word,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+use super::{PrecompileCallGadget, PrecompileReturnGadget};
+
+/// Gadget for the SHA2-256 precompile.  The input is accumulated from the
+/// memory of the caller and its hash is looked up in the SHA-256 table.  The
+/// hash is written to the memory of the precompile as its output.
+#[derive(Clone, Debug)]
+pub(crate) struct Sha256Gadget<F> {
+    call: PrecompileCallGadget<F>,
+    input_is_zero: IsZeroGadget<F>,
+    input_rlc: Cell<F>,
+    input_word_size: MemoryWordSizeGadget<F>,
+    output: Word<F>,
+    ret: PrecompileReturnGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for Sha256Gadget<F> {
+    const NAME: &'static str = "SHA256";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileSha256;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let call = PrecompileCallGadget::construct(cb, PrecompileCalls::Sha256);
+
+        let input_is_zero = IsZeroGadget::construct(cb, call.cd_length());
+        let input_rlc = cb.query_cell_phase2();
+        cb.condition(call.is_success() * not::expr(input_is_zero.expr()), |cb| {
+            cb.copy_table_lookup(
+                call.caller_id(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::RlcAcc.expr(),
+                call.cd_offset(),
+                call.cd_offset() + call.cd_length(),
+                0.expr(), // dst_addr for CopyDataType::RlcAcc is 0.
+                call.cd_length(),
+                input_rlc.expr(),
+                call.cd_length(),
+            );
+        });
+        cb.condition(input_is_zero.expr(), |cb| {
+            cb.require_zero("input_rlc == 0 for empty input", input_rlc.expr());
+        });
+
+        // The hash is written to the memory of the precompile in big-endian.
+        let output = cb.query_word_rlc();
+        cb.condition(call.is_success(), |cb| {
+            cb.sha256_table_lookup(input_rlc.expr(), call.cd_length(), output.expr());
+            for (address, byte) in output.cells.iter().rev().enumerate() {
+                cb.memory_lookup(1.expr(), address.expr(), byte.expr(), None);
+            }
+        });
+
+        let input_word_size = MemoryWordSizeGadget::construct(cb, call.cd_length());
+        let gas_cost = GasCost::PRECOMPILE_SHA256_BASE.expr()
+            + GasCost::PRECOMPILE_SHA256_PER_WORD.expr() * input_word_size.expr();
+
+        let ret = PrecompileReturnGadget::construct(cb, &call, gas_cost, 32.expr());
+
+        Self {
+            call,
+            input_is_zero,
+            input_rlc,
+            input_word_size,
+            output,
+            ret,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let mut rw_offset = self.call.assign(region, offset, block, call, step)?;
+
+        let input_length = call.call_data_length;
+        self.input_is_zero
+            .assign(region, offset, F::from(input_length))?;
+
+        let input_word_size = self.input_word_size.assign(region, offset, input_length)?;
+        let gas_cost = GasCost::PRECOMPILE_SHA256_BASE.as_u64()
+            + GasCost::PRECOMPILE_SHA256_PER_WORD.as_u64() * input_word_size;
+
+        if call.is_success {
+            let input: Vec<u8> = (rw_offset..rw_offset + input_length as usize)
+                .map(|i| block.get_rws(step, i).memory_value())
+                .collect();
+            rw_offset += input.len();
+            self.input_rlc.assign(
+                region,
+                offset,
+                region
+                    .challenges()
+                    .keccak_input()
+                    .map(|randomness| rlc::value(input.iter().rev(), randomness)),
+            )?;
+
+            let output: Vec<u8> = (rw_offset..rw_offset + 32)
+                .map(|i| block.get_rws(step, i).memory_value())
+                .collect();
+            rw_offset += output.len();
+            self.output.assign(
+                region,
+                offset,
+                Some(eth_types::Word::from_big_endian(&output).to_le_bytes()),
+            )?;
+        } else {
+            self.input_rlc
+                .assign(region, offset, Value::known(F::ZERO))?;
+            self.output.assign(region, offset, Some([0; 32]))?;
+        }
+
+        self.ret
+            .assign(region, offset, block, call, step, gas_cost, 32, rw_offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::precompile::PrecompileCalls;
+    use eth_types::{bytecode, word, Bytecode};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    fn test_ok(code: Bytecode) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _| block,
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn call_sha256(input_length: u64, output_length: u64, gas: u64) -> Bytecode {
+        bytecode! {
+            PUSH32(word!("0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"))
+            PUSH1(0)
+            MSTORE
+            PUSH1(output_length)
+            PUSH1(0x40)
+            PUSH1(input_length)
+            PUSH1(0)
+            PUSH1(PrecompileCalls::Sha256.address())
+            PUSH32(gas)
+            STATICCALL
+            STOP
+        }
+    }
+
+    #[test]
+    fn precompile_sha256() {
+        test_ok(call_sha256(32, 32, 1000));
+        test_ok(call_sha256(5, 20, 1000));
+        test_ok(call_sha256(0, 32, 1000));
+    }
+
+    #[test]
+    fn precompile_sha256_oog() {
+        test_ok(call_sha256(32, 32, 71));
+    }
+}
@@ -0,0 +1,447 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_ACCOUNT_ADDRESS,
+        step::ExecutionState,
+        util::{
+            common_gadget::{RestoreContextGadget, TransferGadget},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            not, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for [`OpcodeId::SELFDESTRUCT`].
+///
+/// The whole balance of the callee is transferred to the beneficiary.  As
+/// specified in EIP-6780, the callee account is only deleted (balance, nonce
+/// and code hash set to 0) when it was created in the same transaction.
+#[derive(Clone, Debug)]
+pub(crate) struct SelfDestructGadget<F> {
+    opcode: Cell<F>,
+    beneficiary: Word<F>,
+    tx_id: Cell<F>,
+    is_static: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    callee_address: Cell<F>,
+    is_warm: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    beneficiary_not_exists: IsZeroGadget<F>,
+    value: Word<F>,
+    is_created: Cell<F>,
+    is_beneficiary_self: IsEqualGadget<F>,
+    transfer: TransferGadget<F>,
+    nonce: Cell<F>,
+    code_hash: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfDestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.require_equal(
+            "Opcode should be SELFDESTRUCT",
+            opcode.expr(),
+            OpcodeId::SELFDESTRUCT.expr(),
+        );
+
+        let beneficiary = cb.query_word_rlc();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        // Call ends with SELFDESTRUCT must be successful
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
+        let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
+        cb.require_zero("is_static == false", is_static.expr());
+        let mut reversion_info = cb.reversion_info_read(None);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            beneficiary_address.expr(),
+            1.expr(),
+            is_warm.expr(),
+            Some(&mut reversion_info),
+        );
+
+        // For non-existing accounts the code_hash must be 0 in the rw_table.
+        let beneficiary_code_hash = cb.query_cell_phase2();
+        cb.account_read(
+            beneficiary_address.expr(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.expr(),
+        );
+        let beneficiary_not_exists = IsZeroGadget::construct(cb, beneficiary_code_hash.expr());
+
+        let value = cb.query_word_rlc();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            value.expr(),
+        );
+
+        let is_created = cb.query_bool();
+        cb.tx_created_account_read(tx_id.expr(), callee_address.expr(), is_created.expr());
+
+        let is_beneficiary_self =
+            IsEqualGadget::construct(cb, beneficiary_address.expr(), callee_address.expr());
+
+        // The whole balance goes to the beneficiary, which may be the callee
+        // itself.
+        let transfer = TransferGadget::construct(
+            cb,
+            callee_address.expr(),
+            beneficiary_address.expr(),
+            not::expr(beneficiary_not_exists.expr()),
+            value.clone(),
+            &mut reversion_info,
+        );
+
+        // Delete the callee if it was created in the same transaction.  When
+        // the beneficiary is the callee itself, the balance is burnt.
+        let nonce = cb.query_cell();
+        let code_hash = cb.query_cell_phase2();
+        cb.condition(is_created.expr(), |cb| {
+            for (field_tag, value_prev) in [
+                (
+                    AccountFieldTag::Balance,
+                    is_beneficiary_self.expr() * value.expr(),
+                ),
+                (AccountFieldTag::Nonce, nonce.expr()),
+                (AccountFieldTag::CodeHash, code_hash.expr()),
+            ] {
+                cb.account_write(
+                    callee_address.expr(),
+                    field_tag,
+                    0.expr(),
+                    value_prev,
+                    Some(&mut reversion_info),
+                );
+            }
+        });
+
+        let has_value = not::expr(transfer.value_is_zero.expr());
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + not::expr(is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + has_value.expr() * beneficiary_not_exists.expr() * GasCost::NEW_ACCOUNT.expr();
+
+        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(cb.rw_counter_offset()),
+                gas_left: Delta(-gas_cost.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call
+        let restore_context = cb.condition(not::expr(cb.curr.state.is_root.expr()), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                true.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                gas_cost.expr(),
+                // +1 Write TxAccessListAccount
+                // +1 Write Account (beneficiary) CodeHash if created
+                // +2 Write Account Balance of the transfer
+                // +3 Write Account fields of the deleted callee
+                1.expr()
+                    + has_value * (beneficiary_not_exists.expr() + 2.expr())
+                    + is_created.expr() * 3.expr(),
+            )
+        });
+
+        Self {
+            opcode,
+            beneficiary,
+            tx_id,
+            is_static,
+            reversion_info,
+            callee_address,
+            is_warm,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            value,
+            is_created,
+            is_beneficiary_self,
+            transfer,
+            nonce,
+            code_hash,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode().unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let beneficiary = block.get_rws(step, 0).stack_value();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.is_static
+            .assign(region, offset, Value::known(F::from(call.is_static as u64)))?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+        let callee_address = call
+            .address
+            .to_scalar()
+            .expect("unexpected Address -> Scalar conversion failure");
+        self.callee_address
+            .assign(region, offset, Value::known(callee_address))?;
+
+        let (_, is_warm) = block.get_rws(step, 7).tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+
+        let (beneficiary_code_hash, _) = block.get_rws(step, 8).account_value_pair();
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            region.word_rlc(beneficiary_code_hash),
+        )?;
+        self.beneficiary_not_exists.assign_value(
+            region,
+            offset,
+            region.word_rlc(beneficiary_code_hash),
+        )?;
+
+        let (value, _) = block.get_rws(step, 9).account_value_pair();
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+
+        let (is_created, _) = block.get_rws(step, 10).tx_created_account_value_pair();
+        self.is_created
+            .assign(region, offset, Value::known(F::from(is_created as u64)))?;
+
+        let beneficiary_address = beneficiary
+            .to_address()
+            .to_scalar()
+            .expect("unexpected Address -> Scalar conversion failure");
+        self.is_beneficiary_self
+            .assign(region, offset, beneficiary_address, callee_address)?;
+
+        let mut rw_offset = 11;
+        if beneficiary_code_hash.is_zero() && !value.is_zero() {
+            rw_offset += 1;
+        }
+        let (sender_balance_pair, receiver_balance_pair) = if value.is_zero() {
+            ((U256::zero(), U256::zero()), (U256::zero(), U256::zero()))
+        } else {
+            rw_offset += 2;
+            (
+                block.get_rws(step, rw_offset - 2).account_value_pair(),
+                block.get_rws(step, rw_offset - 1).account_value_pair(),
+            )
+        };
+        self.transfer.assign(
+            region,
+            offset,
+            sender_balance_pair,
+            receiver_balance_pair,
+            value,
+        )?;
+
+        let (nonce, code_hash) = if is_created {
+            rw_offset += 3;
+            (
+                block.get_rws(step, rw_offset - 2).account_value_pair().1,
+                block.get_rws(step, rw_offset - 1).account_value_pair().1,
+            )
+        } else {
+            (U256::zero(), U256::zero())
+        };
+        self.nonce.assign(
+            region,
+            offset,
+            Value::known(
+                nonce
+                    .to_scalar()
+                    .expect("unexpected U256 -> Scalar conversion failure"),
+            ),
+        )?;
+        self.code_hash
+            .assign(region, offset, region.word_rlc(code_hash))?;
+
+        if !call.is_root {
+            self.restore_context
+                .assign(region, offset, block, call, step, rw_offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{address, bytecode, Address, Bytecode, ToWord, Word};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    const BENEFICIARY: Address = address!("0x0000000000000000000000000000000000fe1234");
+
+    fn test_root_ok(code: Bytecode, balance: Word) {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(balance)
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    fn test_internal_ok(code: Bytecode, balance: Word) {
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0]
+                    .address(MOCK_ACCOUNTS[0])
+                    .balance(balance)
+                    .code(code);
+                accs[1].address(MOCK_ACCOUNTS[1]).code(bytecode! {
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH20(MOCK_ACCOUNTS[0].to_word())
+                    GAS
+                    CALL
+                    STOP
+                });
+                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[2].address).to(accs[1].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn selfdestruct_gadget_new_beneficiary() {
+        let code = bytecode! {
+            PUSH20(BENEFICIARY.to_word())
+            SELFDESTRUCT
+        };
+
+        test_root_ok(code.clone(), eth(1));
+        test_internal_ok(code, eth(1));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_zero_balance() {
+        let code = bytecode! {
+            PUSH20(BENEFICIARY.to_word())
+            SELFDESTRUCT
+        };
+
+        test_root_ok(code.clone(), Word::zero());
+        test_internal_ok(code, Word::zero());
+    }
+
+    #[test]
+    fn selfdestruct_gadget_warm_existing_beneficiary() {
+        let code = bytecode! {
+            PUSH20(MOCK_ACCOUNTS[1].to_word())
+            SELFDESTRUCT
+        };
+
+        test_root_ok(code.clone(), eth(1));
+        test_internal_ok(code, eth(1));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_self_beneficiary() {
+        let code = bytecode! {
+            ADDRESS
+            SELFDESTRUCT
+        };
+
+        test_root_ok(code.clone(), eth(1));
+        test_internal_ok(code, eth(1));
+    }
+
+    #[test]
+    fn selfdestruct_gadget_created_account() {
+        // The account created by the transaction is deleted and its balance
+        // burnt.
+        let code = bytecode! {
+            ADDRESS
+            SELFDESTRUCT
+        };
+
+        let ctx = TestContext::<1, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .value(eth(1))
+                    .input(code.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
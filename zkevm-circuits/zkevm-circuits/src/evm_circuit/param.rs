@@ -35,7 +35,13 @@ pub(crate) const EVM_LOOKUP_COLS: usize = FIXED_TABLE_LOOKUPS
     + BLOCK_TABLE_LOOKUPS
     + COPY_TABLE_LOOKUPS
     + KECCAK_TABLE_LOOKUPS
-    + EXP_TABLE_LOOKUPS;
+    + EXP_TABLE_LOOKUPS
+    + SIG_TABLE_LOOKUPS
+    + SHA256_TABLE_LOOKUPS
+    + RIPEMD160_TABLE_LOOKUPS
+    + ECC_TABLE_LOOKUPS
+    + BLAKE2F_TABLE_LOOKUPS
+    + MODEXP_TABLE_LOOKUPS;
 
 /// Lookups done per row.
 pub const LOOKUP_CONFIG: &[(Table, usize)] = &[
@@ -47,6 +53,12 @@ pub const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::Copy, COPY_TABLE_LOOKUPS),
     (Table::Keccak, KECCAK_TABLE_LOOKUPS),
     (Table::Exp, EXP_TABLE_LOOKUPS),
+    (Table::Sig, SIG_TABLE_LOOKUPS),
+    (Table::Sha256, SHA256_TABLE_LOOKUPS),
+    (Table::Ripemd160, RIPEMD160_TABLE_LOOKUPS),
+    (Table::Ecc, ECC_TABLE_LOOKUPS),
+    (Table::Blake2f, BLAKE2F_TABLE_LOOKUPS),
+    (Table::ModExp, MODEXP_TABLE_LOOKUPS),
 ];
 
 /// Fixed Table lookups done in EVMCircuit
@@ -73,6 +85,24 @@ pub const KECCAK_TABLE_LOOKUPS: usize = 1;
 /// Exp Table lookups done in EVMCircuit
 pub const EXP_TABLE_LOOKUPS: usize = 1;
 
+/// Sig Table lookups done in EVMCircuit
+pub const SIG_TABLE_LOOKUPS: usize = 1;
+
+/// SHA-256 Table lookups done in EVMCircuit
+pub const SHA256_TABLE_LOOKUPS: usize = 1;
+
+/// RIPEMD-160 Table lookups done in EVMCircuit
+pub const RIPEMD160_TABLE_LOOKUPS: usize = 1;
+
+/// ECC Table lookups done in EVMCircuit
+pub const ECC_TABLE_LOOKUPS: usize = 1;
+
+/// Blake2F Table lookups done in EVMCircuit
+pub const BLAKE2F_TABLE_LOOKUPS: usize = 1;
+
+/// ModExp Table lookups done in EVMCircuit
+pub const MODEXP_TABLE_LOOKUPS: usize = 1;
+
 /// Maximum number of bytes that an integer can fit in field without wrapping
 /// around.
 pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
@@ -16,7 +16,7 @@ use bus_mapping::{
     evm::OpcodeId,
     precompile::PrecompileCalls,
 };
-use eth_types::{evm_unimplemented, Field, ToWord};
+use eth_types::{Field, ToWord};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Advice, Column, ConstraintSystem, Error, Expression},
@@ -88,6 +88,8 @@ pub enum ExecutionState {
     BLOCKCTXU256, // DIFFICULTY, BASEFEE
     CHAINID,
     SELFBALANCE,
+    BLOBHASH,
+    BLOBBASEFEE,
     POP,
     MEMORY, // MLOAD, MSTORE, MSTORE8
     SLOAD,
@@ -98,6 +100,9 @@ pub enum ExecutionState {
     MSIZE,
     GAS,
     JUMPDEST,
+    TLOAD,
+    TSTORE,
+    MCOPY,
     PUSH0,
     PUSH, // PUSH1, PUSH2, ..., PUSH32
     DUP,  // DUP1, DUP2, ..., DUP16
@@ -112,9 +117,6 @@ pub enum ExecutionState {
     ErrorInvalidOpcode,
     ErrorStack,
     ErrorWriteProtection,
-    ErrorDepth,
-    ErrorInsufficientBalance,
-    ErrorContractAddressCollision,
     ErrorInvalidCreationCode,
     ErrorMaxCodeSizeExceeded,
     ErrorInvalidJump,
@@ -128,7 +130,6 @@ pub enum ExecutionState {
     ErrorOutOfGasLOG,
     ErrorOutOfGasEXP,
     ErrorOutOfGasSHA3,
-    ErrorOutOfGasEXTCODECOPY,
     ErrorOutOfGasCall,
     ErrorOutOfGasSloadSstore,
     ErrorOutOfGasCREATE2,
@@ -162,9 +163,10 @@ impl From<&ExecError> for ExecutionState {
             ExecError::InvalidOpcode => ExecutionState::ErrorInvalidOpcode,
             ExecError::StackOverflow | ExecError::StackUnderflow => ExecutionState::ErrorStack,
             ExecError::WriteProtection => ExecutionState::ErrorWriteProtection,
-            ExecError::Depth => ExecutionState::ErrorDepth,
-            ExecError::InsufficientBalance => ExecutionState::ErrorInsufficientBalance,
-            ExecError::ContractAddressCollision => ExecutionState::ErrorContractAddressCollision,
+            // Constrained by the gadgets of the calls and creations, which are
+            // told apart by the opcode of the step.
+            ExecError::Depth | ExecError::InsufficientBalance => ExecutionState::CALL_OP,
+            ExecError::ContractAddressCollision => ExecutionState::CREATE,
             ExecError::InvalidCreationCode => ExecutionState::ErrorInvalidCreationCode,
             ExecError::InvalidJump => ExecutionState::ErrorInvalidJump,
             ExecError::ReturnDataOutOfBounds => ExecutionState::ErrorReturnDataOutOfBound,
@@ -195,7 +197,17 @@ impl From<&ExecError> for ExecutionState {
 impl From<&ExecStep> for ExecutionState {
     fn from(step: &ExecStep) -> Self {
         if let Some(error) = step.error.as_ref() {
-            return error.into();
+            return match (ExecutionState::from(error), &step.exec_state) {
+                (
+                    ExecutionState::CALL_OP | ExecutionState::CREATE,
+                    ExecState::Op(OpcodeId::CREATE),
+                ) => ExecutionState::CREATE,
+                (
+                    ExecutionState::CALL_OP | ExecutionState::CREATE,
+                    ExecState::Op(OpcodeId::CREATE2),
+                ) => ExecutionState::CREATE2,
+                (state, _) => state,
+            };
         }
         match step.exec_state {
             ExecState::Op(op) => {
@@ -215,13 +227,6 @@ impl From<&ExecStep> for ExecutionState {
                     return ExecutionState::LOG;
                 }
 
-                macro_rules! dummy {
-                    ($name:expr) => {{
-                        evm_unimplemented!("{:?} is implemented with DummyGadget", $name);
-                        $name
-                    }};
-                }
-
                 match op {
                     OpcodeId::ADD | OpcodeId::SUB => ExecutionState::ADD_SUB,
                     OpcodeId::ADDMOD => ExecutionState::ADDMOD,
@@ -268,9 +273,14 @@ impl From<&ExecStep> for ExecutionState {
                     OpcodeId::SHL | OpcodeId::SHR => ExecutionState::SHL_SHR,
                     OpcodeId::SLOAD => ExecutionState::SLOAD,
                     OpcodeId::SSTORE => ExecutionState::SSTORE,
+                    OpcodeId::TLOAD => ExecutionState::TLOAD,
+                    OpcodeId::TSTORE => ExecutionState::TSTORE,
+                    OpcodeId::MCOPY => ExecutionState::MCOPY,
                     OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
                     OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
                     OpcodeId::CHAINID => ExecutionState::CHAINID,
+                    OpcodeId::BLOBHASH => ExecutionState::BLOBHASH,
+                    OpcodeId::BLOBBASEFEE => ExecutionState::BLOBBASEFEE,
                     OpcodeId::ISZERO => ExecutionState::ISZERO,
                     OpcodeId::CALL
                     | OpcodeId::CALLCODE
@@ -284,10 +294,9 @@ impl From<&ExecStep> for ExecutionState {
                     OpcodeId::RETURN | OpcodeId::REVERT => ExecutionState::RETURN_REVERT,
                     OpcodeId::RETURNDATASIZE => ExecutionState::RETURNDATASIZE,
                     OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
-                    // dummy ops
-                    OpcodeId::CREATE => dummy!(ExecutionState::CREATE),
-                    OpcodeId::CREATE2 => dummy!(ExecutionState::CREATE2),
-                    OpcodeId::SELFDESTRUCT => dummy!(ExecutionState::SELFDESTRUCT),
+                    OpcodeId::CREATE => ExecutionState::CREATE,
+                    OpcodeId::CREATE2 => ExecutionState::CREATE2,
+                    OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
                     _ => unimplemented!("unimplemented opcode {:?}", op),
                 }
             }
@@ -347,7 +356,6 @@ impl ExecutionState {
                 | Self::ErrorOutOfGasLOG
                 | Self::ErrorOutOfGasEXP
                 | Self::ErrorOutOfGasSHA3
-                | Self::ErrorOutOfGasEXTCODECOPY
                 | Self::ErrorOutOfGasCall
                 | Self::ErrorOutOfGasSloadSstore
                 | Self::ErrorOutOfGasCREATE2
@@ -412,6 +420,8 @@ impl ExecutionState {
             Self::BLOCKCTXU256 => vec![OpcodeId::DIFFICULTY, OpcodeId::BASEFEE],
             Self::CHAINID => vec![OpcodeId::CHAINID],
             Self::SELFBALANCE => vec![OpcodeId::SELFBALANCE],
+            Self::BLOBHASH => vec![OpcodeId::BLOBHASH],
+            Self::BLOBBASEFEE => vec![OpcodeId::BLOBBASEFEE],
             Self::POP => vec![OpcodeId::POP],
             Self::MEMORY => {
                 vec![OpcodeId::MLOAD, OpcodeId::MSTORE, OpcodeId::MSTORE8]
@@ -424,6 +434,9 @@ impl ExecutionState {
             Self::MSIZE => vec![OpcodeId::MSIZE],
             Self::GAS => vec![OpcodeId::GAS],
             Self::JUMPDEST => vec![OpcodeId::JUMPDEST],
+            Self::TLOAD => vec![OpcodeId::TLOAD],
+            Self::TSTORE => vec![OpcodeId::TSTORE],
+            Self::MCOPY => vec![OpcodeId::MCOPY],
             Self::PUSH0 => vec![OpcodeId::PUSH0],
             Self::PUSH => vec![
                 OpcodeId::PUSH1,
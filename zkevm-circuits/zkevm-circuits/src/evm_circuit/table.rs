@@ -142,6 +142,12 @@ pub enum Table {
     Copy,
     Keccak,
     Exp,
+    Sig,
+    Sha256,
+    Ripemd160,
+    Ecc,
+    Blake2f,
+    ModExp,
 }
 
 #[derive(Clone, Debug)]
@@ -291,6 +297,64 @@ pub(crate) enum Lookup<F> {
         exponent_lo_hi: [Expression<F>; 2],
         exponentiation_lo_hi: [Expression<F>; 2],
     },
+    /// Lookup to sig table, which contains the addresses recovered from
+    /// signatures by the ecRecover precompile.
+    SigTable {
+        /// RLC of the message hash.
+        msg_hash_rlc: Expression<F>,
+        /// Recovery id of the signature, 27 or 28.
+        sig_v: Expression<F>,
+        /// RLC of the signature `r`.
+        sig_r_rlc: Expression<F>,
+        /// RLC of the signature `s`.
+        sig_s_rlc: Expression<F>,
+        /// Address recovered from the signature.
+        recovered_addr: Expression<F>,
+    },
+    /// Lookup to SHA-256 table.
+    Sha256Table {
+        /// Accumulator to the input.
+        input_rlc: Expression<F>,
+        /// Length of input that is being hashed.
+        input_len: Expression<F>,
+        /// RLC representation of the output hash.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to RIPEMD-160 table.
+    Ripemd160Table {
+        /// Accumulator to the input.
+        input_rlc: Expression<F>,
+        /// Length of input that is being hashed.
+        input_len: Expression<F>,
+        /// RLC representation of the output hash, left padded to 32 bytes.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to ECC table, which contains the BN254 operations of the
+    /// ecAdd and ecMul precompiles.
+    EccTable {
+        /// Address of the precompile of the operation.
+        op_type: Expression<F>,
+        /// RLC of the arguments of the operation.
+        args_rlc: [Expression<F>; 4],
+        /// RLC of the coordinates of the result.
+        output_rlc: [Expression<F>; 2],
+    },
+    /// Lookup to Blake2F table.
+    Blake2fTable {
+        /// Number of rounds of the compression.
+        rounds: Expression<F>,
+        /// Accumulator to the input.
+        input_rlc: Expression<F>,
+        /// RLC representation of the output.
+        output_rlc: Expression<F>,
+    },
+    /// Lookup to ModExp table.
+    ModExpTable {
+        /// RLC of the base, the exponent and the modulus.
+        args_rlc: [Expression<F>; 3],
+        /// RLC of the result.
+        result_rlc: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -310,6 +374,12 @@ impl<F: Field> Lookup<F> {
             Self::CopyTable { .. } => Table::Copy,
             Self::KeccakTable { .. } => Table::Keccak,
             Self::ExpTable { .. } => Table::Exp,
+            Self::SigTable { .. } => Table::Sig,
+            Self::Sha256Table { .. } => Table::Sha256,
+            Self::Ripemd160Table { .. } => Table::Ripemd160,
+            Self::EccTable { .. } => Table::Ecc,
+            Self::Blake2fTable { .. } => Table::Blake2f,
+            Self::ModExpTable { .. } => Table::ModExp,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -421,6 +491,67 @@ impl<F: Field> Lookup<F> {
                 exponentiation_lo_hi[0].clone(),
                 exponentiation_lo_hi[1].clone(),
             ],
+            Self::SigTable {
+                msg_hash_rlc,
+                sig_v,
+                sig_r_rlc,
+                sig_s_rlc,
+                recovered_addr,
+            } => vec![
+                1.expr(), // is_enabled
+                msg_hash_rlc.clone(),
+                sig_v.clone(),
+                sig_r_rlc.clone(),
+                sig_s_rlc.clone(),
+                recovered_addr.clone(),
+            ],
+            Self::Sha256Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            }
+            | Self::Ripemd160Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            } => vec![
+                1.expr(), // is_enabled
+                input_rlc.clone(),
+                input_len.clone(),
+                output_rlc.clone(),
+            ],
+            Self::EccTable {
+                op_type,
+                args_rlc,
+                output_rlc,
+            } => [
+                vec![
+                    1.expr(), // is_enabled
+                    op_type.clone(),
+                ],
+                args_rlc.to_vec(),
+                output_rlc.to_vec(),
+            ]
+            .concat(),
+            Self::Blake2fTable {
+                rounds,
+                input_rlc,
+                output_rlc,
+            } => vec![
+                1.expr(), // is_enabled
+                rounds.clone(),
+                input_rlc.clone(),
+                output_rlc.clone(),
+            ],
+            Self::ModExpTable {
+                args_rlc,
+                result_rlc,
+            } => [
+                vec![1.expr()], // is_enabled
+                args_rlc.to_vec(),
+                vec![result_rlc.clone()],
+            ]
+            .concat(),
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
@@ -1026,6 +1026,108 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Transient Storage
+
+    pub(crate) fn transient_storage_read(
+        &mut self,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+        tx_id: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "transient_storage_read",
+            false.expr(),
+            Target::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    pub(crate) fn transient_storage_write(
+        &mut self,
+        account_address: Expression<F>,
+        key: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        tx_id: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TransientStorage write",
+            Target::TransientStorage,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                key,
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
+    // Tx Created Account
+
+    pub(crate) fn tx_created_account_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxCreatedAccount read",
+            false.expr(),
+            Target::TxCreatedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    pub(crate) fn tx_created_account_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TxCreatedAccount write",
+            Target::TxCreatedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value,
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
     // Call context
 
     pub(crate) fn call_context(
@@ -1326,6 +1428,116 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Sig Table
+
+    pub(crate) fn sig_table_lookup(
+        &mut self,
+        msg_hash_rlc: Expression<F>,
+        sig_v: Expression<F>,
+        sig_r_rlc: Expression<F>,
+        sig_s_rlc: Expression<F>,
+        recovered_addr: Expression<F>,
+    ) {
+        self.add_lookup(
+            "sig table lookup",
+            Lookup::SigTable {
+                msg_hash_rlc,
+                sig_v,
+                sig_r_rlc,
+                sig_s_rlc,
+                recovered_addr,
+            },
+        );
+    }
+
+    // SHA-256 Table
+
+    pub(crate) fn sha256_table_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "sha256 lookup",
+            Lookup::Sha256Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            },
+        );
+    }
+
+    // RIPEMD-160 Table
+
+    pub(crate) fn ripemd160_table_lookup(
+        &mut self,
+        input_rlc: Expression<F>,
+        input_len: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "ripemd160 lookup",
+            Lookup::Ripemd160Table {
+                input_rlc,
+                input_len,
+                output_rlc,
+            },
+        );
+    }
+
+    // ECC Table
+
+    pub(crate) fn ecc_table_lookup(
+        &mut self,
+        op_type: Expression<F>,
+        args_rlc: [Expression<F>; 4],
+        output_rlc: [Expression<F>; 2],
+    ) {
+        self.add_lookup(
+            "ecc lookup",
+            Lookup::EccTable {
+                op_type,
+                args_rlc,
+                output_rlc,
+            },
+        );
+    }
+
+    // Blake2F Table
+
+    pub(crate) fn blake2f_table_lookup(
+        &mut self,
+        rounds: Expression<F>,
+        input_rlc: Expression<F>,
+        output_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "blake2f lookup",
+            Lookup::Blake2fTable {
+                rounds,
+                input_rlc,
+                output_rlc,
+            },
+        );
+    }
+
+    // ModExp Table
+
+    pub(crate) fn modexp_table_lookup(
+        &mut self,
+        args_rlc: [Expression<F>; 3],
+        result_rlc: Expression<F>,
+    ) {
+        self.add_lookup(
+            "modexp lookup",
+            Lookup::ModExpTable {
+                args_rlc,
+                result_rlc,
+            },
+        );
+    }
+
     // Validation
 
     pub(crate) fn validate_degree(&self, degree: usize, name: &'static str) {
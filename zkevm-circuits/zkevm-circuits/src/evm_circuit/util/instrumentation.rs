@@ -100,6 +100,24 @@ impl Instrument {
                     CellType::Lookup(Table::Exp) => {
                         report.exp_table = data_entry;
                     }
+                    CellType::Lookup(Table::Sig) => {
+                        report.sig_table = data_entry;
+                    }
+                    CellType::Lookup(Table::Sha256) => {
+                        report.sha256_table = data_entry;
+                    }
+                    CellType::Lookup(Table::Ripemd160) => {
+                        report.ripemd160_table = data_entry;
+                    }
+                    CellType::Lookup(Table::Ecc) => {
+                        report.ecc_table = data_entry;
+                    }
+                    CellType::Lookup(Table::Blake2f) => {
+                        report.blake2f_table = data_entry;
+                    }
+                    CellType::Lookup(Table::ModExp) => {
+                        report.modexp_table = data_entry;
+                    }
                 }
             }
             report_collection.push(report);
@@ -125,6 +143,12 @@ pub struct ExecStateReport {
     pub copy_table: StateReportRow,
     pub keccak_table: StateReportRow,
     pub exp_table: StateReportRow,
+    pub sig_table: StateReportRow,
+    pub sha256_table: StateReportRow,
+    pub ripemd160_table: StateReportRow,
+    pub ecc_table: StateReportRow,
+    pub blake2f_table: StateReportRow,
+    pub modexp_table: StateReportRow,
 }
 
 impl From<ExecutionState> for ExecStateReport {
@@ -285,6 +285,19 @@ impl<F: Field, const IS_CREATE2: bool> ContractCreateGadget<F, IS_CREATE2> {
         self.salt.expr()
     }
 
+    /// Salt word RLC, as popped from the stack by CREATE2.
+    pub(crate) fn salt_word_rlc(&self, cb: &EVMConstraintBuilder<F>) -> Expression<F> {
+        cb.word_rlc::<N_BYTES_WORD>(
+            self.salt
+                .cells
+                .iter()
+                .map(Expr::expr)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        )
+    }
+
     /// Caller address' RLC value.
     pub(crate) fn caller_address_rlc(&self) -> Expression<F> {
         self.caller_address.expr()
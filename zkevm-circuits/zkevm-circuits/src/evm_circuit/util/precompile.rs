@@ -3,6 +3,8 @@ use eth_types::Field;
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
 
+use crate::evm_circuit::step::ExecutionState;
+
 use super::{
     constraint_builder::EVMConstraintBuilder, math_gadget::BinaryNumberGadget, CachedRegion,
 };
@@ -13,20 +15,30 @@ pub struct PrecompileGadget<F> {
 }
 
 impl<F: Field> PrecompileGadget<F> {
-    #[allow(clippy::too_many_arguments)]
     pub(crate) fn construct(
         cb: &mut EVMConstraintBuilder<F>,
-        _is_success: Expression<F>,
         callee_address: Expression<F>,
-        _caller_id: Expression<F>,
-        _cd_offset: Expression<F>,
-        _cd_length: Expression<F>,
-        _rd_offset: Expression<F>,
-        _rd_length: Expression<F>,
     ) -> Self {
         let address = BinaryNumberGadget::construct(cb, callee_address.expr());
 
-        // match the `address.value_equals` `PrecompileCalls` variant and execute the code
+        // The next step executes the precompile at the callee address.  The
+        // precompiles without a gadget are left unconstrained.
+        for precompile in [
+            PrecompileCalls::ECRecover,
+            PrecompileCalls::Sha256,
+            PrecompileCalls::Ripemd160,
+            PrecompileCalls::Identity,
+            PrecompileCalls::Modexp,
+            PrecompileCalls::Bn128Add,
+            PrecompileCalls::Bn128Mul,
+            PrecompileCalls::Blake2F,
+        ] {
+            cb.constrain_next_step(
+                ExecutionState::from(precompile),
+                Some(address.value_equals(precompile)),
+                |_| {},
+            );
+        }
 
         Self { address }
     }
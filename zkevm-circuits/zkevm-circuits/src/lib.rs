@@ -17,14 +17,21 @@
 #![deny(unsafe_code)]
 #![deny(clippy::debug_assert_with_mut_call)]
 
+pub mod blake2f_circuit;
 pub mod bytecode_circuit;
 pub mod circuit_tools;
 pub mod copy_circuit;
+pub mod ecc_circuit;
 pub mod evm_circuit;
 pub mod exp_circuit;
 pub mod keccak_circuit;
+pub mod modexp_circuit;
+pub mod mpt_circuit;
 pub mod pi_circuit;
+pub mod ripemd160_circuit;
 pub mod root_circuit;
+pub mod sha256_circuit;
+pub mod sig_circuit;
 pub mod state_circuit;
 pub mod super_circuit;
 pub mod table;
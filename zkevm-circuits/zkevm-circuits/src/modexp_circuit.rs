@@ -0,0 +1,839 @@
+//! The ModExp circuit implementation.
+//!
+//! Each call to the modexp precompile with operands of up to 32 bytes and a
+//! nonzero modulus is laid out in a block of [`ROWS_PER_CALL`] rows.  The
+//! header row decomposes the base, the exponent and the modulus in bytes, and
+//! they are carried over all the rows of the call as 64 bits limbs.  The step
+//! rows compute the power by square and multiply, from the most significant
+//! bit of the exponent: each step is a square row followed by a multiply row,
+//! which multiplies by the base or by one depending on the bit.  A step row
+//! proves `a * b = q * m + r` with `q` and `r` in bytes, and with the carries
+//! of the schoolbook product of the limbs, and `a` is the `r` of the previous
+//! step row.  The bits of the exponent are accumulated by limb and checked
+//! against the exponent.  The footer row proves that the last `r` is smaller
+//! than the modulus, and exposes the call in the [`ModExpTable`].  The blocks
+//! are padded to the capacity with calls computing `0 ** 0 % 1`.
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::ModExpCircuit as TestModExpCircuit;
+
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+        pow_of_two_expr, rlc,
+    },
+    table::{ByteTable, LookupTable, ModExpTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use bus_mapping::precompile::ModExpOp;
+use eth_types::{Field, ToLittleEndian, Word, U512};
+use gadgets::util::{not, sum, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+    },
+    poly::Rotation,
+};
+use log::error;
+use std::marker::PhantomData;
+
+const MAX_DEGREE: usize = 5;
+
+/// Number of bits of the exponent, one step per bit.
+const NUM_BITS: usize = 256;
+/// Number of 64 bits limbs of a word.
+const NUM_LIMBS: usize = 4;
+const NUM_BITS_PER_LIMB: usize = 64;
+const NUM_BYTES_PER_LIMB: usize = 8;
+const NUM_BYTES_PER_WORD: usize = 32;
+/// Number of bytes of the carries of the product of the limbs, which are
+/// offset by [`CARRY_OFFSET_BITS`] to be positive.
+const NUM_CARRIES: usize = 3;
+const NUM_BYTES_PER_CARRY: usize = 9;
+const CARRY_OFFSET_BITS: usize = 67;
+/// Number of byte columns, enough for the three operands of the header.
+const NUM_BYTES: usize = 3 * NUM_BYTES_PER_WORD;
+
+/// A call has a header row, a square row and a multiply row per bit of the
+/// exponent, and a footer row.
+pub const ROWS_PER_CALL: usize = 2 + 2 * NUM_BITS;
+
+/// ModExpCircuitConfig
+#[derive(Clone, Debug)]
+pub struct ModExpCircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_header: Column<Fixed>,
+    q_first_step: Column<Fixed>,
+    q_square: Column<Fixed>,
+    q_mul: Column<Fixed>,
+    /// Multiply rows of the most significant bit of a limb of the exponent
+    q_limb_start: Column<Fixed>,
+    /// Multiply rows of the least significant bit of a limb of the exponent
+    q_exp_limbs: [Column<Fixed>; NUM_LIMBS],
+    q_footer: Column<Fixed>,
+    bytes: [Column<Advice>; NUM_BYTES],
+    base: [Column<Advice>; NUM_LIMBS],
+    exponent: [Column<Advice>; NUM_LIMBS],
+    modulus: [Column<Advice>; NUM_LIMBS],
+    a: [Column<Advice>; NUM_LIMBS],
+    b: [Column<Advice>; NUM_LIMBS],
+    bit: Column<Advice>,
+    exp_acc: Column<Advice>,
+    base_rlc: Column<Advice>,
+    exponent_rlc: Column<Advice>,
+    modulus_rlc: Column<Advice>,
+    /// The columns for other circuits to lookup ModExp results
+    pub modexp_table: ModExpTable,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct ModExpCircuitConfigArgs<F: Field> {
+    /// ModExpTable
+    pub modexp_table: ModExpTable,
+    /// ByteTable
+    pub byte_table: ByteTable,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for ModExpCircuitConfig<F> {
+    type ConfigArgs = ModExpCircuitConfigArgs<F>;
+
+    /// Return a new ModExpCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            modexp_table,
+            byte_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = meta.fixed_column();
+        let q_header = meta.fixed_column();
+        let q_first_step = meta.fixed_column();
+        let q_square = meta.fixed_column();
+        let q_mul = meta.fixed_column();
+        let q_limb_start = meta.fixed_column();
+        let q_exp_limbs = array_init::array_init(|_| meta.fixed_column());
+        let q_footer = meta.fixed_column();
+        let bytes = array_init::array_init(|_| meta.advice_column());
+        let base = array_init::array_init(|_| meta.advice_column());
+        let exponent = array_init::array_init(|_| meta.advice_column());
+        let modulus = array_init::array_init(|_| meta.advice_column());
+        let a = array_init::array_init(|_| meta.advice_column());
+        let b = array_init::array_init(|_| meta.advice_column());
+        let bit = meta.advice_column();
+        let exp_acc = meta.advice_column();
+        let base_rlc = meta.advice_column_in(SecondPhase);
+        let exponent_rlc = meta.advice_column_in(SecondPhase);
+        let modulus_rlc = meta.advice_column_in(SecondPhase);
+
+        // The bytes of a row, from little-endian bytes to limbs and words
+        let query_bytes = |meta: &mut VirtualCells<F>, rot: Rotation| {
+            bytes.map(|column| meta.query_advice(column, rot))
+        };
+        let limbs = |bytes: &[Expression<F>]| -> [Expression<F>; NUM_LIMBS] {
+            array_init::array_init(|idx| {
+                compose(&bytes[idx * NUM_BYTES_PER_LIMB..(idx + 1) * NUM_BYTES_PER_LIMB])
+            })
+        };
+        let query_limbs =
+            |meta: &mut VirtualCells<F>, limbs: &[Column<Advice>; NUM_LIMBS], rot: Rotation| {
+                limbs.map(|column| meta.query_advice(column, rot))
+            };
+        let one = || [1.expr(), 0.expr(), 0.expr(), 0.expr()];
+
+        for column in bytes {
+            meta.lookup_any("modexp byte", |meta| {
+                let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+                let byte = meta.query_advice(column, Rotation::cur());
+                vec![(q_enable * byte, byte_table.table_exprs(meta)[0].clone())]
+            });
+        }
+
+        meta.create_gate("header", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let bytes = query_bytes(meta, Rotation::cur());
+            for (idx, (name, limb_columns, rlc_column)) in [
+                ("base", base, base_rlc),
+                ("exponent", exponent, exponent_rlc),
+                ("modulus", modulus, modulus_rlc),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let word = &bytes[idx * NUM_BYTES_PER_WORD..(idx + 1) * NUM_BYTES_PER_WORD];
+                for (limb, expr) in query_limbs(meta, &limb_columns, Rotation::cur())
+                    .into_iter()
+                    .zip(limbs(word))
+                {
+                    cb.require_equal(name, limb, expr);
+                }
+                cb.require_equal(
+                    "rlc of the word",
+                    meta.query_advice(rlc_column, Rotation::cur()),
+                    rlc::expr(word, challenges.evm_word()),
+                );
+            }
+            cb.require_zero(
+                "exp_acc starts at zero",
+                meta.query_advice(exp_acc, Rotation::cur()),
+            );
+            cb.gate(meta.query_fixed(q_header, Rotation::cur()))
+        });
+
+        meta.create_gate("operands carry over", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for columns in [base, exponent, modulus] {
+                for column in columns {
+                    cb.require_equal(
+                        "limb is carried over",
+                        meta.query_advice(column, Rotation::cur()),
+                        meta.query_advice(column, Rotation::prev()),
+                    );
+                }
+            }
+            for column in [base_rlc, exponent_rlc, modulus_rlc] {
+                cb.require_equal(
+                    "rlc is carried over",
+                    meta.query_advice(column, Rotation::cur()),
+                    meta.query_advice(column, Rotation::prev()),
+                );
+            }
+            cb.gate(sum::expr(
+                [q_square, q_mul, q_footer].map(|column| meta.query_fixed(column, Rotation::cur())),
+            ))
+        });
+
+        meta.create_gate("step", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_first_step = meta.query_fixed(q_first_step, Rotation::cur());
+            let q_square = meta.query_fixed(q_square, Rotation::cur());
+            let q_mul = meta.query_fixed(q_mul, Rotation::cur());
+            let bytes = query_bytes(meta, Rotation::cur());
+            let a = query_limbs(meta, &a, Rotation::cur());
+            let b = query_limbs(meta, &b, Rotation::cur());
+            let base = query_limbs(meta, &base, Rotation::cur());
+            let modulus = query_limbs(meta, &modulus, Rotation::cur());
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let exp_acc_cur = meta.query_advice(exp_acc, Rotation::cur());
+            let exp_acc_prev = meta.query_advice(exp_acc, Rotation::prev());
+            let q = limbs(&bytes[..NUM_BYTES_PER_WORD]);
+            let r = limbs(&bytes[NUM_BYTES_PER_WORD..2 * NUM_BYTES_PER_WORD]);
+            let r_prev = limbs(
+                &query_bytes(meta, Rotation::prev())[NUM_BYTES_PER_WORD..2 * NUM_BYTES_PER_WORD],
+            );
+
+            // The power starts at one and is the remainder of the previous step
+            for (idx, ((a, one), r_prev)) in a.iter().zip(one()).zip(r_prev).enumerate() {
+                cb.condition(q_first_step.clone(), |cb| {
+                    cb.require_equal("a is one on the first step", a.clone(), one.clone());
+                });
+                cb.condition(not::expr(q_first_step.clone()), |cb| {
+                    cb.require_equal("a is the previous remainder", a.clone(), r_prev);
+                });
+                cb.condition(q_square.clone(), |cb| {
+                    cb.require_equal("b is a on square rows", b[idx].clone(), a.clone());
+                });
+                cb.condition(q_mul.clone(), |cb| {
+                    cb.require_equal(
+                        "b is the base or one on multiply rows",
+                        b[idx].clone(),
+                        bit.clone() * base[idx].clone() + not::expr(bit.clone()) * one,
+                    );
+                });
+            }
+
+            // The bits of the exponent
+            cb.condition(q_square.clone(), |cb| {
+                cb.require_equal(
+                    "exp_acc is carried over on square rows",
+                    exp_acc_cur.clone(),
+                    exp_acc_prev.clone(),
+                );
+            });
+            cb.condition(q_mul.clone(), |cb| {
+                cb.require_boolean("bit is boolean", bit.clone());
+                cb.require_equal(
+                    "exp_acc accumulates the bits of a limb",
+                    exp_acc_cur.clone(),
+                    exp_acc_prev
+                        * 2.expr()
+                        * not::expr(meta.query_fixed(q_limb_start, Rotation::cur()))
+                        + bit.clone(),
+                );
+            });
+            let exponent = query_limbs(meta, &exponent, Rotation::cur());
+            for (q_exp_limb, limb) in q_exp_limbs.iter().zip(exponent) {
+                cb.condition(meta.query_fixed(*q_exp_limb, Rotation::cur()), |cb| {
+                    cb.require_equal(
+                        "exp_acc is the limb of the exponent",
+                        exp_acc_cur.clone(),
+                        limb,
+                    );
+                });
+            }
+
+            // a * b = q * m + r, limb by limb, with the carries of two limbs
+            let mut carries = (0..NUM_CARRIES)
+                .map(|idx| {
+                    let start = 2 * NUM_BYTES_PER_WORD + idx * NUM_BYTES_PER_CARRY;
+                    compose(&bytes[start..start + NUM_BYTES_PER_CARRY])
+                        - pow_of_two_expr(CARRY_OFFSET_BITS)
+                })
+                .collect::<Vec<_>>();
+            carries.push(0.expr());
+            let terms = (0..2 * NUM_LIMBS)
+                .map(|k| {
+                    let products = (0..NUM_LIMBS)
+                        .filter(|i| k >= *i && k - i < NUM_LIMBS)
+                        .map(|i| {
+                            a[i].clone() * b[k - i].clone() - q[i].clone() * modulus[k - i].clone()
+                        })
+                        .collect::<Vec<_>>();
+                    let term = sum::expr(products);
+                    if k < NUM_LIMBS {
+                        term - r[k].clone()
+                    } else {
+                        term
+                    }
+                })
+                .collect::<Vec<_>>();
+            for idx in 0..NUM_LIMBS {
+                let carry_in = if idx == 0 {
+                    0.expr()
+                } else {
+                    carries[idx - 1].clone()
+                };
+                cb.require_equal(
+                    "a * b = q * m + r",
+                    terms[2 * idx].clone()
+                        + terms[2 * idx + 1].clone() * pow_of_two_expr(NUM_BITS_PER_LIMB)
+                        + carry_in,
+                    carries[idx].clone() * pow_of_two_expr(2 * NUM_BITS_PER_LIMB),
+                );
+            }
+
+            cb.gate(q_square + q_mul)
+        });
+
+        meta.create_gate("footer", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let bytes = query_bytes(meta, Rotation::cur());
+            let bytes_prev = query_bytes(meta, Rotation::prev());
+            let result = &bytes_prev[NUM_BYTES_PER_WORD..2 * NUM_BYTES_PER_WORD];
+            let modulus = query_limbs(meta, &modulus, Rotation::cur());
+            // result + d + 1 = m with d >= 0, so that result < m
+            let d = limbs(&bytes[..NUM_BYTES_PER_WORD]);
+            let carries = &bytes[NUM_BYTES_PER_WORD..NUM_BYTES_PER_WORD + NUM_LIMBS - 1];
+            for (idx, ((r, d), m)) in limbs(result).into_iter().zip(d).zip(modulus).enumerate() {
+                let carry_in = if idx == 0 {
+                    1.expr()
+                } else {
+                    carries[idx - 1].clone()
+                };
+                let carry_out = carries.get(idx).cloned().unwrap_or_else(|| 0.expr());
+                cb.require_equal(
+                    "result + d + 1 = m",
+                    r + d + carry_in,
+                    m + carry_out * pow_of_two_expr(NUM_BITS_PER_LIMB),
+                );
+            }
+            cb.require_equal(
+                "result_rlc",
+                meta.query_advice(modexp_table.result_rlc, Rotation::cur()),
+                rlc::expr(result, challenges.evm_word()),
+            );
+            cb.gate(meta.query_fixed(q_footer, Rotation::cur()))
+        });
+
+        meta.create_gate("table", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // A row of the table is enabled only on the footer of a call
+            let q_footer = meta.query_fixed(q_footer, Rotation::cur());
+            cb.require_equal(
+                "is_enabled",
+                meta.query_advice(modexp_table.is_enabled, Rotation::cur()),
+                q_footer.clone(),
+            );
+            cb.condition(q_footer, |cb| {
+                for (name, table_column, column) in [
+                    ("base_rlc", modexp_table.base_rlc, base_rlc),
+                    ("exponent_rlc", modexp_table.exponent_rlc, exponent_rlc),
+                    ("modulus_rlc", modexp_table.modulus_rlc, modulus_rlc),
+                ] {
+                    cb.require_equal(
+                        name,
+                        meta.query_advice(table_column, Rotation::cur()),
+                        meta.query_advice(column, Rotation::cur()),
+                    );
+                }
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        Self {
+            q_enable,
+            q_header,
+            q_first_step,
+            q_square,
+            q_mul,
+            q_limb_start,
+            q_exp_limbs,
+            q_footer,
+            bytes,
+            base,
+            exponent,
+            modulus,
+            a,
+            b,
+            bit,
+            exp_acc,
+            base_rlc,
+            exponent_rlc,
+            modulus_rlc,
+            modexp_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Composes little-endian bytes.
+fn compose<F: Field>(bytes: &[Expression<F>]) -> Expression<F> {
+    bytes
+        .iter()
+        .rev()
+        .fold(0.expr(), |acc, byte| acc * 256.expr() + byte.clone())
+}
+
+impl<F: Field> ModExpCircuitConfig<F> {
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[ModExpRow<F>],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assign modexp rows",
+            |mut region| {
+                // Keep an all-zero row for the disabled lookups
+                self.set_row(&mut region, 0, &ModExpRow::default())?;
+                for (offset, modexp_row) in witness.iter().enumerate() {
+                    self.set_row(&mut region, offset + 1, modexp_row)?;
+                }
+                self.modexp_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
+    fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &ModExpRow<F>,
+    ) -> Result<(), Error> {
+        // Position in the call, the all-zero row has none
+        let position = offset.checked_sub(1).map(|idx| idx % ROWS_PER_CALL);
+        let step = position
+            .filter(|position| (1..ROWS_PER_CALL - 1).contains(position))
+            .map(|position| (position - 1) / 2);
+        let is_mul = position.map_or(false, |position| position > 0 && position % 2 == 0);
+        let is_step = step.is_some();
+        let is_mul_step = |f: fn(usize) -> bool| is_step && is_mul && f(step.unwrap());
+
+        // Fixed selectors
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, true),
+            ("q_header", self.q_header, position == Some(0)),
+            ("q_first_step", self.q_first_step, position == Some(1)),
+            ("q_square", self.q_square, is_step && !is_mul),
+            ("q_mul", self.q_mul, is_step && is_mul),
+            (
+                "q_limb_start",
+                self.q_limb_start,
+                is_mul_step(|step| step % NUM_BITS_PER_LIMB == 0),
+            ),
+            (
+                "q_footer",
+                self.q_footer,
+                position == Some(ROWS_PER_CALL - 1),
+            ),
+        ]
+        .into_iter()
+        .chain(self.q_exp_limbs.iter().enumerate().map(|(idx, column)| {
+            (
+                "q_exp_limb",
+                *column,
+                is_mul_step(|step| step % NUM_BITS_PER_LIMB == NUM_BITS_PER_LIMB - 1)
+                    && step.unwrap() / NUM_BITS_PER_LIMB == NUM_LIMBS - 1 - idx,
+            )
+        })) {
+            region.assign_fixed(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+
+        // Bytes, limbs and bits
+        for (name, columns, values) in [
+            ("bytes", &self.bytes[..], row.bytes.map(u64::from).to_vec()),
+            ("base", &self.base[..], row.base.0.to_vec()),
+            ("exponent", &self.exponent[..], row.exponent.0.to_vec()),
+            ("modulus", &self.modulus[..], row.modulus.0.to_vec()),
+            ("a", &self.a[..], row.a.0.to_vec()),
+            ("b", &self.b[..], row.b.0.to_vec()),
+            ("bit", &[self.bit][..], vec![row.bit as u64]),
+            ("exp_acc", &[self.exp_acc][..], vec![row.exp_acc]),
+        ] {
+            for (column, value) in columns.iter().zip(values) {
+                region.assign_advice(
+                    || format!("assign {} {}", name, offset),
+                    *column,
+                    offset,
+                    || Value::known(F::from(value)),
+                )?;
+            }
+        }
+
+        // RLCs
+        for (name, column, value) in [
+            ("base_rlc", self.base_rlc, row.base_rlc),
+            ("exponent_rlc", self.exponent_rlc, row.exponent_rlc),
+            ("modulus_rlc", self.modulus_rlc, row.modulus_rlc),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || value,
+            )?;
+        }
+
+        // Table
+        let is_footer = position == Some(ROWS_PER_CALL - 1);
+        for (name, column, value) in [
+            (
+                "is_enabled",
+                self.modexp_table.is_enabled,
+                Value::known(F::from(is_footer as u64)),
+            ),
+            ("base_rlc", self.modexp_table.base_rlc, row.base_rlc),
+            (
+                "exponent_rlc",
+                self.modexp_table.exponent_rlc,
+                row.exponent_rlc,
+            ),
+            (
+                "modulus_rlc",
+                self.modexp_table.modulus_rlc,
+                row.modulus_rlc,
+            ),
+            ("result_rlc", self.modexp_table.result_rlc, row.result_rlc),
+        ] {
+            region.assign_advice(
+                || format!("assign table {} {}", name, offset),
+                column,
+                offset,
+                || {
+                    if is_footer {
+                        value
+                    } else {
+                        Value::known(F::ZERO)
+                    }
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    fn annotate_circuit(&self, region: &mut Region<F>) {
+        region.name_column(|| "MODEXP_q_enable", self.q_enable);
+        region.name_column(|| "MODEXP_q_header", self.q_header);
+        region.name_column(|| "MODEXP_q_first_step", self.q_first_step);
+        region.name_column(|| "MODEXP_q_square", self.q_square);
+        region.name_column(|| "MODEXP_q_mul", self.q_mul);
+        region.name_column(|| "MODEXP_q_limb_start", self.q_limb_start);
+        region.name_column(|| "MODEXP_q_footer", self.q_footer);
+        region.name_column(|| "MODEXP_bit", self.bit);
+        region.name_column(|| "MODEXP_exp_acc", self.exp_acc);
+        region.name_column(|| "MODEXP_base_rlc", self.base_rlc);
+        region.name_column(|| "MODEXP_exponent_rlc", self.exponent_rlc);
+        region.name_column(|| "MODEXP_modulus_rlc", self.modulus_rlc);
+    }
+}
+
+/// A row of the ModExp circuit
+#[derive(Clone, Debug)]
+pub(crate) struct ModExpRow<F> {
+    /// The operands on the header, `q`, `r` and the carries on the steps,
+    /// and `m - 1 - r` and its carries on the footer
+    bytes: [u8; NUM_BYTES],
+    base: Word,
+    exponent: Word,
+    modulus: Word,
+    a: Word,
+    b: Word,
+    bit: bool,
+    exp_acc: u64,
+    base_rlc: Value<F>,
+    exponent_rlc: Value<F>,
+    modulus_rlc: Value<F>,
+    result_rlc: Value<F>,
+}
+
+impl<F: Field> Default for ModExpRow<F> {
+    fn default() -> Self {
+        Self {
+            bytes: [0; NUM_BYTES],
+            base: Word::zero(),
+            exponent: Word::zero(),
+            modulus: Word::zero(),
+            a: Word::zero(),
+            b: Word::zero(),
+            bit: false,
+            exp_acc: 0,
+            base_rlc: Value::known(F::ZERO),
+            exponent_rlc: Value::known(F::ZERO),
+            modulus_rlc: Value::known(F::ZERO),
+            result_rlc: Value::known(F::ZERO),
+        }
+    }
+}
+
+/// Generates the step of `a * b` modulo `m`, returns the row with `q`, `r`
+/// and the carries, and `r`.
+fn mul_mod_row<F: Field>(row: ModExpRow<F>, a: Word, b: Word) -> (ModExpRow<F>, Word) {
+    let m = row.modulus;
+    let product = a.full_mul(b);
+    let (q, r) = product.div_mod(U512::from(m));
+    let q = Word::try_from(q).expect("q < 2**256 when a < m");
+    let r = Word::try_from(r).unwrap();
+
+    let mut bytes = [0; NUM_BYTES];
+    bytes[..NUM_BYTES_PER_WORD].copy_from_slice(&q.to_le_bytes());
+    bytes[NUM_BYTES_PER_WORD..2 * NUM_BYTES_PER_WORD].copy_from_slice(&r.to_le_bytes());
+    // The carries of the pairs of limbs, from the partial sums of the limbs of
+    // a * b and of q * m + r, which are exact multiples of their weight.
+    let partial_sum = |x: &Word, y: &Word, z: Option<&Word>, num_limbs: usize| {
+        let mut acc = U512::zero();
+        for k in 0..num_limbs {
+            for i in (0..NUM_LIMBS).filter(|i| k >= *i && k - i < NUM_LIMBS) {
+                acc += (U512::from(x.0[i]) * U512::from(y.0[k - i])) << (NUM_BITS_PER_LIMB * k);
+            }
+            if let Some(z) = z.filter(|_| k < NUM_LIMBS) {
+                acc += U512::from(z.0[k]) << (NUM_BITS_PER_LIMB * k);
+            }
+        }
+        acc
+    };
+    for idx in 0..NUM_CARRIES {
+        let num_limbs = 2 * (idx + 1);
+        let positive = partial_sum(&a, &b, None, num_limbs);
+        let negative = partial_sum(&q, &m, Some(&r), num_limbs);
+        let shift = NUM_BITS_PER_LIMB * num_limbs;
+        let offset = U512::one() << CARRY_OFFSET_BITS;
+        let carry = if positive >= negative {
+            offset + ((positive - negative) >> shift)
+        } else {
+            offset - ((negative - positive) >> shift)
+        };
+        let start = 2 * NUM_BYTES_PER_WORD + idx * NUM_BYTES_PER_CARRY;
+        let carry = carry.low_u128().to_le_bytes();
+        bytes[start..start + NUM_BYTES_PER_CARRY].copy_from_slice(&carry[..NUM_BYTES_PER_CARRY]);
+    }
+    (ModExpRow { bytes, a, b, ..row }, r)
+}
+
+/// Generates the rows of a modexp call with a nonzero modulus.
+fn modexp_rows<F: Field>(
+    rows: &mut Vec<ModExpRow<F>>,
+    op: &ModExpOp,
+    challenges: &Challenges<Value<F>>,
+) {
+    let [_, base_rlc, exponent_rlc, modulus_rlc, result_rlc] =
+        ModExpTable::assignments(op, challenges);
+
+    // Header
+    let mut bytes = [0; NUM_BYTES];
+    for (idx, word) in [op.base, op.exponent, op.modulus].iter().enumerate() {
+        bytes[idx * NUM_BYTES_PER_WORD..(idx + 1) * NUM_BYTES_PER_WORD]
+            .copy_from_slice(&word.to_le_bytes());
+    }
+    let header = ModExpRow {
+        bytes,
+        base: op.base,
+        exponent: op.exponent,
+        modulus: op.modulus,
+        base_rlc,
+        exponent_rlc,
+        modulus_rlc,
+        ..Default::default()
+    };
+    rows.push(header.clone());
+
+    // Steps, from the most significant bit of the exponent
+    let row = ModExpRow {
+        bytes: [0; NUM_BYTES],
+        ..header
+    };
+    let mut power = Word::one();
+    let mut exp_acc = 0;
+    for step in 0..NUM_BITS {
+        let bit = op.exponent.bit(NUM_BITS - 1 - step);
+        let (square, r) = mul_mod_row(
+            ModExpRow {
+                exp_acc,
+                ..row.clone()
+            },
+            power,
+            power,
+        );
+        rows.push(square);
+        power = r;
+
+        exp_acc = if step % NUM_BITS_PER_LIMB == 0 {
+            0
+        } else {
+            2 * exp_acc
+        } + bit as u64;
+        let (mul, r) = mul_mod_row(
+            ModExpRow {
+                bit,
+                exp_acc,
+                ..row.clone()
+            },
+            power,
+            if bit { op.base } else { Word::one() },
+        );
+        rows.push(mul);
+        power = r;
+    }
+    // Footer, with d = m - 1 - result.  The result is the one computed by the
+    // steps, a wrong result of the call only fails the lookup of its RLC.
+    let d = op.modulus - Word::one() - power;
+    let mut bytes = [0; NUM_BYTES];
+    bytes[..NUM_BYTES_PER_WORD].copy_from_slice(&d.to_le_bytes());
+    let mut carry = 1;
+    for idx in 0..NUM_LIMBS - 1 {
+        let sum = power.0[idx] as u128 + d.0[idx] as u128 + carry - op.modulus.0[idx] as u128;
+        carry = sum >> NUM_BITS_PER_LIMB;
+        bytes[NUM_BYTES_PER_WORD + idx] = carry as u8;
+    }
+    rows.push(ModExpRow {
+        bytes,
+        result_rlc,
+        ..row
+    });
+}
+
+/// The call used to pad the circuit to its capacity.
+fn padding_op() -> ModExpOp {
+    ModExpOp {
+        base: Word::zero(),
+        exponent: Word::zero(),
+        modulus: Word::one(),
+        result: Word::zero(),
+    }
+}
+
+/// Generates the rows of the ModExp circuit for all the `ops`, padded with
+/// padding calls up to `capacity` calls.
+pub(crate) fn multi_modexp<F: Field>(
+    ops: &[ModExpOp],
+    challenges: &Challenges<Value<F>>,
+    capacity: usize,
+) -> Result<Vec<ModExpRow<F>>, Error> {
+    if ops.len() > capacity {
+        error!("{} modexp calls > capacity = {}", ops.len(), capacity);
+        return Err(Error::Synthesis);
+    }
+    let padding = padding_op();
+    let mut rows = Vec::with_capacity(capacity * ROWS_PER_CALL);
+    for op in ops
+        .iter()
+        .chain(std::iter::repeat(&padding).take(capacity - ops.len()))
+    {
+        modexp_rows(&mut rows, op, challenges);
+    }
+    Ok(rows)
+}
+
+/// ModExpCircuit
+#[derive(Default, Clone, Debug)]
+pub struct ModExpCircuit<F: Field> {
+    ops: Vec<ModExpOp>,
+    max_ops: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SubCircuit<F> for ModExpCircuit<F> {
+    type Config = ModExpCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    /// The `block.circuits_params.max_modexp` parameter, when enabled, sets up
+    /// the circuit to support a fixed number of calls, independently of the
+    /// calls of the block.
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let ops = &block.precompile_events.modexp;
+        let max_modexp = block.circuits_params.max_modexp;
+        Self::new(
+            if max_modexp > 0 {
+                max_modexp
+            } else {
+                ops.len()
+            },
+            ops.clone(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let num_ops = block.precompile_events.modexp.len();
+        (
+            Self::min_num_rows(num_ops),
+            Self::min_num_rows(block.circuits_params.max_modexp.max(num_ops)),
+        )
+    }
+
+    /// Make the assignments to the ModExpCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let witness = multi_modexp(&self.ops, challenges, self.max_ops)?;
+        config.assign(layouter, witness.as_slice())
+    }
+}
+
+impl<F: Field> ModExpCircuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(max_ops: usize, ops: Vec<ModExpOp>) -> Self {
+        ModExpCircuit {
+            ops,
+            max_ops,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the minimum number of rows required to prove `num_ops` calls,
+    /// with the all-zero row
+    pub fn min_num_rows(num_ops: usize) -> usize {
+        1 + num_ops * ROWS_PER_CALL
+    }
+}
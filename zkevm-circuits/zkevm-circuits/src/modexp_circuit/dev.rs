@@ -0,0 +1,52 @@
+pub use super::ModExpCircuit;
+
+use crate::{
+    modexp_circuit::{ModExpCircuitConfig, ModExpCircuitConfigArgs},
+    table::{ByteTable, ModExpTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for ModExpCircuit<F> {
+    type Config = (ModExpCircuitConfig<F>, ByteTable, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let modexp_table = ModExpTable::construct(meta);
+        let byte_table = ByteTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            ModExpCircuitConfig::new(
+                meta,
+                ModExpCircuitConfigArgs {
+                    modexp_table,
+                    byte_table: byte_table.clone(),
+                    challenges,
+                },
+            )
+        };
+
+        (config, byte_table, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, byte_table, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&mut layouter);
+        byte_table.load(&mut layouter)?;
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
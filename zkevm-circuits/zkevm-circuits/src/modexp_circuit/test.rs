@@ -0,0 +1,90 @@
+use super::*;
+use crate::util::{log2_ceil, unusable_rows};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+};
+
+#[test]
+fn modexp_circuit_unusable_rows() {
+    assert_eq!(
+        ModExpCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, ModExpCircuit::<Fr>>(()),
+    )
+}
+
+fn run<F: Field>(max_ops: usize, ops: Vec<ModExpOp>) -> Result<(), Vec<VerifyFailure>> {
+    // The byte table takes 256 rows
+    let k = log2_ceil(
+        ModExpCircuit::<Fr>::unusable_rows() + ModExpCircuit::<Fr>::min_num_rows(max_ops).max(256),
+    );
+    let circuit = ModExpCircuit::<F>::new(max_ops, ops);
+
+    let prover = match MockProver::run(k, &circuit, vec![]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    prover.verify()
+}
+
+fn op(base: Word, exponent: Word, modulus: Word) -> ModExpOp {
+    let mut result = Word::one() % modulus;
+    for idx in (0..exponent.bits()).rev() {
+        result = Word::try_from(result.full_mul(result) % U512::from(modulus)).unwrap();
+        if exponent.bit(idx) {
+            result = Word::try_from(result.full_mul(base) % U512::from(modulus)).unwrap();
+        }
+    }
+    ModExpOp {
+        base,
+        exponent,
+        modulus,
+        result,
+    }
+}
+
+fn ops() -> Vec<ModExpOp> {
+    vec![
+        op(Word::from(8), Word::from(9), Word::from(10)),
+        op(Word::from(3), Word::zero(), Word::from(7)),
+        op(Word::MAX, Word::MAX, Word::MAX - 1),
+        op(
+            Word::from(0x1234),
+            Word::from(0x5678) << 128,
+            (Word::one() << 255) + 19,
+        ),
+    ]
+}
+
+#[test]
+fn modexp_circuit_result() {
+    assert_eq!(ops()[0].result, Word::from(8));
+    assert_eq!(ops()[1].result, Word::one());
+}
+
+#[test]
+fn modexp_circuit_no_ops() {
+    assert_eq!(run::<Fr>(0, vec![]), Ok(()));
+}
+
+#[test]
+fn modexp_circuit_simple() {
+    assert_eq!(run::<Fr>(5, ops()), Ok(()));
+}
+
+#[test]
+fn modexp_circuit_over_capacity() {
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(0x100)),
+        Value::known(Fr::from(0x200)),
+        Value::known(Fr::from(0x300)),
+    );
+    assert!(multi_modexp(&ops(), &challenges, 3).is_err());
+}
+
+#[test]
+fn modexp_circuit_wrong_result() {
+    let mut ops = ops();
+    ops[0].result = Word::from(9);
+    assert!(run::<Fr>(4, ops).is_err());
+}
@@ -0,0 +1,1955 @@
+//! The MPT circuit implementation.
+//!
+//! The circuit proves the MPT updates of a block, filling the [`MptTable`]
+//! looked up by the state circuit.  Each update walks the account trie, and for
+//! a storage update the storage trie of the account, from the old and new
+//! roots down to the key.  A row holds one RLP item of a node: a child of a
+//! branch, the path or the child of an extension, the path of a leaf or a
+//! field of its value.  The items are accumulated into the RLC of their node,
+//! which is hashed with a lookup to the [`KeccakTable`] on the last row of the
+//! node, and the hash of a node is the child followed by its parent.
+//!
+//! The old and new tries are walked in lockstep, with both nodes on the same
+//! rows, while they have the same structure, so that the children off the
+//! path of the key are checked to be unchanged.  The walks end with a
+//! terminal on each side:
+//! - the leaf of the key,
+//! - an empty child or an empty trie, when the key is absent,
+//! - a leaf or an extension whose path diverges from the key, when the key is absent,
+//! - a branch with two children, the leaf of the key and a sibling, when the insertion or the
+//!   removal of the key splits or merges a node.  The sibling is moved by the update, so it's
+//!   checked to be the node that diverges from the key on the other side.
+//!
+//! The values of the leaves are carried to the last row of the walk, where
+//! they are checked against the values of the update.
+//!
+//! The updates are chained, each one starting from the new root of the
+//! previous one.  The old root of the first update and the new root of the last
+//! one are held by the bytes of the last two rows, and exposed as the instance
+//! of the circuit, so that it proves the state root transition of the block.
+mod cells;
+mod param;
+mod rows;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::MptCircuit as TestMptCircuit;
+
+use self::{
+    cells::{MptCells, MptRlcCells, SideCells, SideRlcCells},
+    param::*,
+    rows::{mpt_rows, MptRow},
+};
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::{KeccakTable, LookupTable, MptTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness::{self, MptUpdateRow, MptUpdates, EMPTY_ROOT},
+};
+use eth_types::{Field, ToScalar};
+use gadgets::util::{not, split_u256, sum, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, SecondPhase,
+        TableColumn, VirtualCells,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// MptCircuitConfig
+#[derive(Clone, Debug)]
+pub struct MptCircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_last: Column<Fixed>,
+    /// The bytes with their high nibble
+    nibble_table: [TableColumn; 2],
+    cells: MptCells<Column<Advice>>,
+    rlcs: MptRlcCells<Column<Advice>>,
+    /// The high and low 128 bits of the roots of the last two rows
+    root_word: [Column<Advice>; 2],
+    /// The old and new roots, as their high and low 128 bits
+    instance: Column<Instance>,
+    /// The MPT table filled by the circuit
+    pub mpt_table: MptTable,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct MptCircuitConfigArgs<F: Field> {
+    /// MptTable
+    pub mpt_table: MptTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+/// `values[0] * base^(n - 1) + .. + values[n - 1]`, the RLC of the keccak
+/// inputs and of the big endian words.
+fn be_rlc<F: Field>(values: &[Expression<F>], base: Expression<F>) -> Expression<F> {
+    values
+        .iter()
+        .fold(0.expr(), |acc, value| acc * base.clone() + value.clone())
+}
+
+/// `values[0] + values[1] * base + ..`, the RLC of the nibbles of the keys.
+fn le_rlc<F: Field>(values: &[Expression<F>], base: Expression<F>) -> Expression<F> {
+    values
+        .iter()
+        .rev()
+        .fold(0.expr(), |acc, value| acc * base.clone() + value.clone())
+}
+
+fn pow<F: Field>(base: Expression<F>, exponent: usize) -> Expression<F> {
+    (0..exponent).fold(1.expr(), |acc, _| acc * base.clone())
+}
+
+/// The RLC of the path nibbles of a path item, the first nibble with the
+/// lowest power.  The first content byte holds the flags in its high nibble,
+/// and the first nibble of an odd path in its low nibble.
+fn path_rlc<F: Field>(cells: &MptCells<Expression<F>>, r: Expression<F>) -> Expression<F> {
+    let nibbles: Vec<_> = (2..NUM_ITEM_BYTES)
+        .flat_map(|j| [cells.hi[j - 1].clone(), cells.lo(j)])
+        .collect();
+    let odd = cells.is_odd.clone();
+    odd.clone() * cells.lo(1) + (1.expr() + odd * (r.clone() - 1.expr())) * le_rlc(&nibbles, r)
+}
+
+/// `r^n` for a path of `n` nibbles
+fn path_pow<F: Field>(cells: &MptCells<Expression<F>>, r: Expression<F>) -> Expression<F> {
+    le_rlc(&cells.len_flags[1..], r.clone() * r.clone())
+        * (1.expr() + cells.is_odd.clone() * (r - 1.expr()))
+}
+
+/// The RLC of the first nibbles of `aux` as long as the path of the row,
+/// compared with the path of a node diverging from the key.
+fn aux_head_rlc<F: Field>(cells: &MptCells<Expression<F>>, r: Expression<F>) -> Expression<F> {
+    let terms: Vec<_> = cells
+        .aux
+        .iter()
+        .enumerate()
+        .map(|(t, nibble)| {
+            let u = t / 2;
+            let mut selector = cells.len_at_least(u + 2);
+            if t % 2 == 0 {
+                selector = selector + cells.is_odd.clone() * cells.len_flags[u + 1].clone();
+            }
+            nibble.clone() * selector
+        })
+        .collect();
+    le_rlc(&terms, r)
+}
+
+impl<F: Field> SubCircuitConfig<F> for MptCircuitConfig<F> {
+    type ConfigArgs = MptCircuitConfigArgs<F>;
+
+    /// Return a new MptCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            mpt_table,
+            keccak_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = meta.fixed_column();
+        let q_first = meta.fixed_column();
+        let q_last = meta.fixed_column();
+        let nibble_table = array_init::array_init(|_| meta.lookup_table_column());
+        let cells = MptCells::<()>::default().map(|_| meta.advice_column());
+        let rlcs = MptRlcCells::<()>::default().map(|_| meta.advice_column_in(SecondPhase));
+        let root_word = array_init::array_init(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        for column in root_word {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        let r = challenges.keccak_input();
+        let word_r = challenges.evm_word();
+        let empty_root = be_rlc(&EMPTY_ROOT.0.map(|byte| byte.expr()), word_r.clone());
+        let query = |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            (
+                cells.map(|column| meta.query_advice(*column, rotation)),
+                rlcs.map(|column| meta.query_advice(*column, rotation)),
+            )
+        };
+        // The word RLC of the 32 bytes following the header of a hash item
+        let bytes_word = |cells: &MptCells<Expression<F>>| {
+            be_rlc(&cells.bytes[1..=NUM_HASH_BYTES], word_r.clone())
+        };
+        // The word RLC of the hash of the new child in a lockstep row
+        let aux_word =
+            |cells: &MptCells<Expression<F>>| be_rlc(&cells.aux[..NUM_HASH_BYTES], word_r.clone());
+        // The child of the new node of a lockstep row, from `aux`
+        let aux_child = |cells: &MptCells<Expression<F>>| {
+            let hash_item = RLP_HASH.expr() * pow(r.clone(), NUM_HASH_BYTES)
+                + be_rlc(&cells.aux[..NUM_HASH_BYTES], r.clone());
+            let full_pow = pow(r.clone(), NUM_HASH_BYTES + 1);
+            let empty = cells.c.empty.clone();
+            (
+                hash_item
+                    - empty.clone()
+                        * (RLP_HASH.expr() * pow(r.clone(), NUM_HASH_BYTES) - RLP_EMPTY.expr()),
+                full_pow.clone() - empty.clone() * (full_pow - r.clone()),
+                (NUM_HASH_BYTES + 1).expr() - NUM_HASH_BYTES.expr() * empty,
+            )
+        };
+
+        meta.create_gate("mpt row", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, _) = query(meta, Rotation::cur());
+            let q_first = meta.query_fixed(q_first, Rotation::cur());
+
+            for cell in cur
+                .tags
+                .iter()
+                .chain(cur.len_flags.iter())
+                .chain(cur.proof_flags.iter())
+                .chain([
+                    &cur.is_short,
+                    &cur.is_odd,
+                    &cur.lockstep,
+                    &cur.node_end,
+                    &cur.role_wrong,
+                    &cur.role_moved,
+                    &cur.is_mod,
+                    &cur.is_moved,
+                    &cur.moved_direct,
+                    &cur.moved_pending,
+                    &cur.in_storage,
+                ])
+            {
+                cb.require_boolean("boolean cell", cell.clone());
+            }
+            for side in [&cur.s, &cur.c] {
+                for cell in side.hdr.iter().chain([
+                    &side.on,
+                    &side.empty,
+                    &side.hash_empty,
+                    &side.next_empty,
+                    &side.root_empty,
+                    &side.wrong,
+                    &side.split,
+                    &side.expect_branch,
+                ]) {
+                    cb.require_boolean("boolean side cell", cell.clone());
+                }
+                cb.require_zero(
+                    "only the walk rows are on a side",
+                    not::expr(cur.is_walk()) * side.on.clone(),
+                );
+            }
+            cb.require_equal("one tag per row", sum::expr(cur.tags.iter()), 1.expr());
+            cb.require_boolean(
+                "a node has at most one role",
+                cur.role_wrong.clone() + cur.role_moved.clone(),
+            );
+            cb.require_zero(
+                "the roles are set on the leaves and extensions",
+                (cur.role_wrong.clone() + cur.role_moved.clone())
+                    * (not::expr(cur.is_node()) + cur.tag(Tag::Branch)),
+            );
+            cb.require_equal(
+                "node_open is set on the rows of a node before its end",
+                cur.node_open.clone(),
+                cur.is_node() * not::expr(cur.node_end.clone()),
+            );
+            cb.require_equal(
+                "the nodes of one row end on their last item",
+                cur.node_end.clone() * not::expr(cur.tag(Tag::Branch)),
+                sum::expr(
+                    [Tag::ExtensionChild, Tag::AccountCodeHash, Tag::StorageValue]
+                        .map(|tag| cur.tag(tag)),
+                ),
+            );
+            cb.require_zero(
+                "only the branches and the extensions are walked in lockstep",
+                cur.lockstep.clone()
+                    * (not::expr(cur.tag(Tag::Branch) + cur.tag(Tag::ExtensionPath))
+                        - cur.tag(Tag::ExtensionChild)),
+            );
+            cb.condition(cur.lockstep.clone(), |cb| {
+                cb.require_zero(
+                    "the lockstep nodes are on the path",
+                    cur.role_wrong.clone() + cur.role_moved.clone(),
+                );
+                cb.require_equal(
+                    "lockstep rows are on the old side",
+                    cur.s.on.clone(),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "lockstep rows are on the new side",
+                    cur.c.on.clone(),
+                    1.expr(),
+                );
+            });
+            cb.condition(cur.is_walk() * not::expr(cur.lockstep.clone()), |cb| {
+                cb.require_equal(
+                    "a walk row is on one side",
+                    cur.s.on.clone() + cur.c.on.clone(),
+                    1.expr(),
+                );
+            });
+            cb.condition(cur.is_walk(), |cb| {
+                cb.require_equal(
+                    "the stage of a walk row is given by its sides",
+                    cur.stage.clone(),
+                    cur.s.on.clone() + 2.expr() * cur.c.on.clone()
+                        - 3.expr() * cur.lockstep.clone(),
+                );
+            });
+            cb.condition(cur.is_key(), |cb| {
+                cb.require_zero("the key rows are in stage 0", cur.stage.clone());
+            });
+            cb.condition(cur.tag(Tag::End) + cur.tag(Tag::Padding), |cb| {
+                cb.require_equal("the end rows are in stage 3", cur.stage.clone(), 3.expr());
+            });
+            cb.require_zero(
+                "the children are picked in the branches",
+                (cur.is_mod.clone() + cur.is_moved.clone()) * not::expr(cur.tag(Tag::Branch)),
+            );
+            cb.require_zero(
+                "a child is not both on the path and moved",
+                cur.is_mod.clone() * cur.is_moved.clone(),
+            );
+            cb.require_zero(
+                "only a moved child is moved by its hash",
+                cur.moved_direct.clone() * not::expr(cur.is_moved.clone()),
+            );
+            cb.condition(q_first, |cb| {
+                cb.require_equal(
+                    "the first row is a start or padding row",
+                    cur.tag(Tag::Start) + cur.tag(Tag::Padding),
+                    1.expr(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt row transitions", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, _) = query(meta, Rotation::cur());
+            let (prev, _) = query(meta, Rotation::prev());
+            let not_first = not::expr(meta.query_fixed(q_first, Rotation::cur()));
+            let stage_delta = cur.stage.clone() - prev.stage.clone();
+            let node_start = cur.is_node() * not::expr(prev.node_open.clone());
+
+            cb.condition(cur.is_walk() + cur.tag(Tag::End), |cb| {
+                cb.require_boolean("the stage goes up by at most 1", stage_delta.clone());
+                cb.require_zero(
+                    "the stage goes up after a terminal",
+                    prev.is_terminal() * not::expr(stage_delta.clone()),
+                );
+            });
+            cb.condition(cur.is_walk(), |cb| {
+                cb.require_zero(
+                    "the walk of a side ends with a terminal",
+                    stage_delta.clone() * prev.stage.clone() * not::expr(prev.is_terminal()),
+                );
+                cb.require_zero(
+                    "the empty rows and the diverging nodes are alone on their side",
+                    (cur.tag(Tag::Empty) + cur.role_wrong.clone() * node_start.clone())
+                        * not::expr(stage_delta.clone()),
+                );
+            });
+            cb.condition(cur.tag(Tag::End), |cb| {
+                cb.require_equal("the new walk is over", prev.is_terminal(), 1.expr());
+                cb.require_equal("the end follows the new walk", prev.stage.clone(), 2.expr());
+            });
+            cb.condition(cur.tag(Tag::Start) + cur.tag(Tag::Padding), |cb| {
+                cb.require_equal(
+                    "an update starts after an end",
+                    prev.stage.clone(),
+                    3.expr(),
+                );
+            });
+            cb.require_equal(
+                "the storage trie is walked after the account trie of a storage update",
+                cur.tag(Tag::StorageKey),
+                prev.tag(Tag::End) * prev.needs_storage() * not::expr(prev.in_storage.clone()),
+            );
+
+            // The items of a node are on consecutive rows
+            cb.condition(prev.node_open.clone(), |cb| {
+                cb.require_equal("a node goes on in the next row", cur.is_node(), 1.expr());
+                for (name, cell, prev_cell) in [
+                    ("old side", &cur.s.on, &prev.s.on),
+                    ("new side", &cur.c.on, &prev.c.on),
+                    ("lockstep", &cur.lockstep, &prev.lockstep),
+                    ("role_wrong", &cur.role_wrong, &prev.role_wrong),
+                    ("role_moved", &cur.role_moved, &prev.role_moved),
+                ] {
+                    cb.require_equal(name, cell.clone(), prev_cell.clone());
+                }
+            });
+            cb.require_zero(
+                "the children of a branch are consecutive",
+                prev.tag(Tag::Branch) * prev.node_open.clone() * not::expr(cur.tag(Tag::Branch)),
+            );
+            for (prev_tag, tag) in [
+                (Tag::ExtensionPath, Tag::ExtensionChild),
+                (Tag::AccountNonce, Tag::AccountBalance),
+                (Tag::AccountBalance, Tag::AccountStorageRoot),
+                (Tag::AccountStorageRoot, Tag::AccountCodeHash),
+            ] {
+                cb.require_zero(
+                    "the items of a node are in order",
+                    prev.tag(prev_tag) * not::expr(cur.tag(tag)),
+                );
+            }
+            cb.condition(prev.tag(Tag::LeafPath), |cb| {
+                cb.require_equal(
+                    "a storage leaf holds a value",
+                    cur.tag(Tag::StorageValue),
+                    prev.in_storage.clone(),
+                );
+                cb.require_equal(
+                    "an account leaf holds the fields of an account",
+                    cur.tag(Tag::AccountNonce),
+                    not::expr(prev.in_storage.clone()),
+                );
+            });
+            cb.require_zero(
+                "the items after the first one of a node follow an open node",
+                sum::expr(
+                    [
+                        Tag::ExtensionChild,
+                        Tag::AccountNonce,
+                        Tag::AccountBalance,
+                        Tag::AccountStorageRoot,
+                        Tag::AccountCodeHash,
+                        Tag::StorageValue,
+                    ]
+                    .map(|tag| cur.tag(tag)),
+                ) * not::expr(prev.node_open.clone()),
+            );
+            cb.condition(cur.tag(Tag::Branch), |cb| {
+                cb.require_equal(
+                    "the children of a branch are indexed from 0",
+                    cur.idx.clone(),
+                    prev.node_open.clone() * (prev.idx.clone() + 1.expr()),
+                );
+                cb.require_zero(
+                    "a branch ends with its last child",
+                    cur.node_end.clone() * (cur.idx.clone() - (NUM_BRANCH_CHILDREN - 1).expr()),
+                );
+            });
+            // The first row has no previous row to follow
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()) * not_first)
+        });
+
+        meta.create_gate("mpt items", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let flags = &cur.len_flags;
+            let short = cur.is_short.clone();
+
+            cb.condition(cur.is_node(), |cb| {
+                cb.require_equal("one length per item", sum::expr(flags.iter()), 1.expr());
+                cb.require_equal(
+                    "item_rlc is the RLC of the item",
+                    cur_rlc.item_rlc.clone() * be_rlc(flags, r.clone()),
+                    be_rlc(&cur.bytes, r.clone()),
+                );
+                cb.require_equal(
+                    "item_pow is r to the length of the item",
+                    cur_rlc.item_pow.clone(),
+                    le_rlc(flags, r.clone()) * (r.clone() - short.clone() * (r.clone() - 1.expr())),
+                );
+                cb.require_zero(
+                    "the header of an item is its length",
+                    not::expr(short.clone())
+                        * (cur.bytes[0].clone() - RLP_EMPTY.expr() - cur.len()),
+                );
+                cb.require_zero(
+                    "a single byte has no header",
+                    short.clone() * cur.bytes[0].clone(),
+                );
+                cb.require_zero(
+                    "a single byte item has one byte",
+                    short.clone() * not::expr(flags[1].clone()),
+                );
+                for j in 1..NUM_ITEM_BYTES {
+                    cb.require_zero(
+                        "the bytes after the item are zero",
+                        cur.bytes[j].clone() * not::expr(cur.len_at_least(j)),
+                    );
+                }
+            });
+            cb.condition(cur.tag(Tag::Branch), |cb| {
+                cb.require_equal(
+                    "a child of a branch is empty or a hash",
+                    flags[0].clone() + flags[NUM_HASH_BYTES].clone(),
+                    1.expr(),
+                );
+                cb.require_zero("a child of a branch has a header", short.clone());
+            });
+            cb.condition(
+                cur.tag(Tag::ExtensionChild)
+                    + cur.tag(Tag::AccountStorageRoot)
+                    + cur.tag(Tag::AccountCodeHash),
+                |cb| {
+                    cb.require_equal(
+                        "a hash has 32 bytes",
+                        flags[NUM_HASH_BYTES].clone(),
+                        1.expr(),
+                    );
+                    cb.require_zero("a hash has a header", short.clone());
+                },
+            );
+
+            cb.condition(cur.is_path(), |cb| {
+                cb.require_zero("a path has a flag byte", flags[0].clone());
+                cb.require_equal(
+                    "the flags of a path are its kind and parity",
+                    cur.hi[0].clone(),
+                    2.expr() * cur.tag(Tag::LeafPath) + cur.is_odd.clone(),
+                );
+                cb.require_zero(
+                    "an even path pads its flags with zero",
+                    not::expr(cur.is_odd.clone()) * cur.lo(1),
+                );
+                cb.require_zero(
+                    "the path of an extension isn't empty",
+                    cur.tag(Tag::ExtensionPath) * flags[1].clone() * not::expr(cur.is_odd.clone()),
+                );
+                cb.require_equal(
+                    "path_rlc is the RLC of the nibbles of the path",
+                    cur_rlc.path_rlc.clone(),
+                    path_rlc(&cur, r.clone()),
+                );
+                cb.require_equal(
+                    "path_pow is r to the number of nibbles",
+                    cur_rlc.path_pow.clone(),
+                    path_pow(&cur, r.clone()),
+                );
+            });
+
+            // The fields of the leaves
+            let value = cur_rlc.value.clone();
+            cb.condition(
+                cur.tag(Tag::AccountNonce) + cur.tag(Tag::AccountBalance),
+                |cb| {
+                    cb.require_zero(
+                        "an integer has no leading zero",
+                        not::expr(flags[0].clone())
+                            * (cur.bytes[1].clone() * cur_rlc.inv.clone() - 1.expr()),
+                    );
+                },
+            );
+            cb.condition(cur.tag(Tag::AccountNonce), |cb| {
+                cb.require_equal(
+                    "the nonce is the integer of the item",
+                    value.clone() * be_rlc(flags, 256.expr()),
+                    be_rlc(&cur.bytes[1..], 256.expr()),
+                );
+            });
+            cb.condition(
+                cur.tag(Tag::AccountBalance)
+                    + cur.tag(Tag::AccountStorageRoot)
+                    + cur.tag(Tag::AccountCodeHash),
+                |cb| {
+                    cb.require_equal(
+                        "the value is the word of the item",
+                        value.clone() * be_rlc(flags, word_r.clone()),
+                        be_rlc(&cur.bytes[1..], word_r.clone()),
+                    );
+                },
+            );
+            cb.condition(cur.tag(Tag::StorageValue), |cb| {
+                cb.require_equal(
+                    "the value is the word of the RLP string of the item",
+                    (value.clone() - short.clone() * cur.bytes[1].clone())
+                        * be_rlc(flags, word_r.clone()),
+                    be_rlc(&cur.bytes[2..], word_r.clone()),
+                );
+                cb.condition(not::expr(short.clone()), |cb| {
+                    cb.require_zero(
+                        "a value string holds a string",
+                        flags[0].clone() + flags[1].clone(),
+                    );
+                    cb.require_equal(
+                        "the header of the value string",
+                        cur.bytes[1].clone(),
+                        RLP_EMPTY.expr() + cur.len() - 1.expr(),
+                    );
+                    cb.require_equal(
+                        "a value has no leading zero",
+                        cur.bytes[2].clone() * cur_rlc.inv.clone(),
+                        1.expr(),
+                    );
+                });
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt nodes", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (prev, prev_rlc) = query(meta, Rotation::prev());
+            let branch_end = cur.tag(Tag::Branch) * cur.node_end.clone();
+            let nonce = cur.tag(Tag::AccountNonce);
+            let generic = (
+                cur_rlc.item_rlc.clone(),
+                cur_rlc.item_pow.clone(),
+                cur.item_len(),
+            );
+            // The header and the length of the encoding of the account
+            let account_len = cur.account_len.clone();
+            let account_header = RLP_STRING_1.expr() * pow(r.clone(), 3)
+                + (account_len.clone() + 2.expr()) * r.clone() * r.clone()
+                + RLP_LIST_1.expr() * r.clone()
+                + account_len;
+
+            cb.condition(cur.tag(Tag::AccountNonce), |cb| {
+                cb.require_equal(
+                    "the fields of an account start with the nonce",
+                    cur.value_len.clone(),
+                    cur.item_len(),
+                );
+            });
+            cb.condition(
+                cur.tag(Tag::AccountBalance)
+                    + cur.tag(Tag::AccountStorageRoot)
+                    + cur.tag(Tag::AccountCodeHash),
+                |cb| {
+                    cb.require_equal(
+                        "value_len accumulates the lengths of the fields",
+                        cur.value_len.clone(),
+                        prev.value_len.clone() + cur.item_len(),
+                    );
+                    cb.require_equal(
+                        "account_len is the same for all the fields",
+                        cur.account_len.clone(),
+                        prev.account_len.clone(),
+                    );
+                },
+            );
+            cb.condition(cur.tag(Tag::AccountCodeHash), |cb| {
+                cb.require_equal(
+                    "account_len is the length of the fields",
+                    cur.value_len.clone(),
+                    cur.account_len.clone(),
+                );
+            });
+
+            for is_new in [false, true] {
+                let (side, prev_side, side_rlc, prev_side_rlc) = if is_new {
+                    (&cur.c, &prev.c, &cur_rlc.c, &prev_rlc.c)
+                } else {
+                    (&cur.s, &prev.s, &cur_rlc.s, &prev_rlc.s)
+                };
+                let on = side.on.clone();
+
+                // The item added by the row to the node of the side
+                let (item, item_pow, item_len) = if is_new {
+                    let from_aux = cur.lockstep.clone() * cur.is_child();
+                    let (aux_item, aux_pow, aux_len) = aux_child(&cur);
+                    (
+                        from_aux.clone() * aux_item
+                            + not::expr(from_aux.clone()) * generic.0.clone(),
+                        from_aux.clone() * aux_pow
+                            + not::expr(from_aux.clone()) * generic.1.clone(),
+                        from_aux.clone() * aux_len + not::expr(from_aux) * generic.2.clone(),
+                    )
+                } else {
+                    generic.clone()
+                };
+                cb.condition(on.clone() * branch_end.clone(), |cb| {
+                    cb.require_equal(
+                        "a branch ends with an empty value",
+                        side_rlc.item.clone(),
+                        item.clone() * r.clone() + RLP_EMPTY.expr(),
+                    );
+                    cb.require_equal(
+                        "item_pow of the end of a branch",
+                        side_rlc.item_pow.clone(),
+                        item_pow.clone() * r.clone(),
+                    );
+                    cb.require_equal(
+                        "item_len of the end of a branch",
+                        side.item_len.clone(),
+                        item_len.clone() + 1.expr(),
+                    );
+                });
+                cb.condition(on.clone() * nonce.clone(), |cb| {
+                    cb.require_equal(
+                        "the fields of an account are in a string",
+                        side_rlc.item.clone(),
+                        account_header.clone() * generic.1.clone() + generic.0.clone(),
+                    );
+                    cb.require_equal(
+                        "item_pow of the nonce",
+                        side_rlc.item_pow.clone(),
+                        generic.1.clone() * pow(r.clone(), 4),
+                    );
+                    cb.require_equal(
+                        "item_len of the nonce",
+                        side.item_len.clone(),
+                        generic.2.clone() + 4.expr(),
+                    );
+                });
+                cb.condition(
+                    on.clone() * (cur.is_node() - branch_end.clone() - nonce.clone()),
+                    |cb| {
+                        cb.require_equal("item of the side", side_rlc.item.clone(), item.clone());
+                        cb.require_equal(
+                            "item_pow of the side",
+                            side_rlc.item_pow.clone(),
+                            item_pow.clone(),
+                        );
+                        cb.require_equal(
+                            "item_len of the side",
+                            side.item_len.clone(),
+                            item_len.clone(),
+                        );
+                    },
+                );
+
+                // The children
+                cb.condition(on.clone() * cur.is_child(), |cb| {
+                    if is_new {
+                        cb.require_zero(
+                            "the empty flag of a child out of lockstep",
+                            not::expr(cur.lockstep.clone())
+                                * (side.empty.clone() - cur.len_flags[0].clone()),
+                        );
+                        for aux in cur.aux[..NUM_HASH_BYTES].iter() {
+                            cb.require_zero(
+                                "an empty child has no hash",
+                                side.empty.clone() * aux.clone(),
+                            );
+                        }
+                    } else {
+                        cb.require_equal(
+                            "the empty flag of a child",
+                            side.empty.clone(),
+                            cur.len_flags[0].clone(),
+                        );
+                    }
+                    cb.require_zero(
+                        "an extension has a child",
+                        cur.tag(Tag::ExtensionChild) * side.empty.clone(),
+                    );
+                });
+
+                // The nodes
+                cb.condition(on.clone() * cur.is_node(), |cb| {
+                    let open = prev.node_open.clone();
+                    cb.require_equal(
+                        "node_rlc accumulates the items of the node",
+                        side_rlc.node_rlc.clone(),
+                        open.clone() * prev_side_rlc.node_rlc.clone() * side_rlc.item_pow.clone()
+                            + side_rlc.item.clone(),
+                    );
+                    cb.require_equal(
+                        "node_pow accumulates the powers of the items",
+                        side_rlc.node_pow.clone(),
+                        (open.clone() * prev_side_rlc.node_pow.clone() + not::expr(open.clone()))
+                            * side_rlc.item_pow.clone(),
+                    );
+                    cb.require_equal(
+                        "node_len accumulates the lengths of the items",
+                        side.node_len.clone(),
+                        open * prev_side.node_len.clone() + side.item_len.clone(),
+                    );
+                });
+                let [short_list, long_list_1, long_list_2] = side.hdr.clone();
+                let node_end = on.clone() * cur.node_end.clone();
+                cb.require_equal(
+                    "the header of a node is set at its end",
+                    sum::expr(side.hdr.iter()),
+                    node_end.clone(),
+                );
+                if is_new {
+                    cb.require_equal(
+                        "the new nodes are hashed at their end",
+                        side.lookup_on.clone(),
+                        node_end.clone(),
+                    );
+                } else {
+                    cb.require_equal(
+                        "the old nodes and the keys are hashed",
+                        side.lookup_on.clone(),
+                        node_end.clone() + cur.is_key(),
+                    );
+                }
+                cb.condition(node_end, |cb| {
+                    let len = side.node_len.clone();
+                    let hi = side.hdr_hi.clone();
+                    let header_rlc = short_list.clone() * (RLP_SHORT_LIST.expr() + len.clone())
+                        + long_list_1.clone() * (RLP_LIST_1.expr() * r.clone() + len.clone())
+                        + long_list_2.clone()
+                            * (RLP_LIST_2.expr() * r.clone() * r.clone()
+                                + hi.clone() * r.clone()
+                                + len.clone()
+                                - 256.expr() * hi);
+                    cb.require_equal(
+                        "the node is hashed with its header",
+                        side_rlc.lookup_rlc.clone(),
+                        header_rlc * side_rlc.node_pow.clone() + side_rlc.node_rlc.clone(),
+                    );
+                    cb.require_equal(
+                        "the length of the node with its header",
+                        side.lookup_len.clone(),
+                        short_list + 2.expr() * long_list_1 + 3.expr() * long_list_2 + len,
+                    );
+                    cb.require_equal(
+                        "the hash of the node is the child of its parent",
+                        side_rlc.lookup_hash.clone(),
+                        cur.role_moved.clone() * cur_rlc.moved_hash.clone()
+                            + not::expr(cur.role_moved.clone()) * prev_side_rlc.hash.clone(),
+                    );
+                });
+            }
+            cb.condition(cur.tag(Tag::Start), |cb| {
+                cb.require_equal(
+                    "the address is hashed",
+                    cur_rlc.s.lookup_rlc.clone(),
+                    be_rlc(&cur.aux[..NUM_ADDRESS_BYTES], r.clone()),
+                );
+                cb.require_equal(
+                    "the address has 20 bytes",
+                    cur.s.lookup_len.clone(),
+                    NUM_ADDRESS_BYTES.expr(),
+                );
+            });
+            cb.condition(cur.tag(Tag::StorageKey), |cb| {
+                cb.require_equal(
+                    "the storage key is hashed",
+                    cur_rlc.s.lookup_rlc.clone(),
+                    be_rlc(&cur.aux[..NUM_HASH_BYTES], r.clone()),
+                );
+                cb.require_equal(
+                    "the storage key has 32 bytes",
+                    cur.s.lookup_len.clone(),
+                    NUM_HASH_BYTES.expr(),
+                );
+            });
+            cb.condition(cur.is_key(), |cb| {
+                cb.require_equal(
+                    "the key hash is in the bytes of a key row",
+                    cur_rlc.s.lookup_hash.clone(),
+                    bytes_word(&cur),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt key walk", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (prev, prev_rlc) = query(meta, Rotation::prev());
+            let stage_delta = cur.stage.clone() - prev.stage.clone();
+            let old_start = stage_delta.clone() * cur.s.on.clone();
+            let new_start = stage_delta * cur.c.on.clone();
+            let cur_acc = cur_rlc.cur_acc.clone();
+            let cur_mult = cur_rlc.cur_mult.clone();
+            let path_branch_end = cur.tag(Tag::Branch) * cur.node_end.clone();
+            let path_extension = cur.tag(Tag::ExtensionPath) * cur.role_path();
+
+            cb.condition(cur.is_key(), |cb| {
+                cb.require_equal(
+                    "key_full is the RLC of the nibbles of the key hash",
+                    cur_rlc.key_full.clone(),
+                    le_rlc(&cur.key_nibbles(), r.clone()),
+                );
+                cb.require_zero("no nibble walked at the key row", cur_rlc.acc.clone());
+                cb.require_equal("mult starts at 1", cur_rlc.mult.clone(), 1.expr());
+            });
+            cb.condition(cur.is_walk() + cur.tag(Tag::End), |cb| {
+                cb.require_equal(
+                    "the key is the same for the walk",
+                    cur_rlc.key_full.clone(),
+                    prev_rlc.key_full.clone(),
+                );
+            });
+            cb.condition(cur.is_walk(), |cb| {
+                // The new side resumes the walk where the lockstep rows ended
+                cb.require_equal(
+                    "cur_acc is the acc of the previous row of the side",
+                    cur_acc.clone(),
+                    new_start.clone() * prev_rlc.lock_acc.clone()
+                        + not::expr(new_start.clone()) * prev_rlc.acc.clone(),
+                );
+                cb.require_equal(
+                    "cur_mult is the mult of the previous row of the side",
+                    cur_mult.clone(),
+                    new_start.clone() * prev_rlc.lock_mult.clone()
+                        + not::expr(new_start) * prev_rlc.mult.clone(),
+                );
+                cb.require_equal(
+                    "lock_acc is the acc at the end of the lockstep rows",
+                    cur_rlc.lock_acc.clone(),
+                    old_start.clone() * cur_acc.clone()
+                        + not::expr(old_start.clone()) * prev_rlc.lock_acc.clone(),
+                );
+                cb.require_equal(
+                    "lock_mult is the mult at the end of the lockstep rows",
+                    cur_rlc.lock_mult.clone(),
+                    old_start.clone() * cur_mult.clone()
+                        + not::expr(old_start) * prev_rlc.lock_mult.clone(),
+                );
+                cb.require_equal(
+                    "acc adds the nibbles of the branches and the extensions",
+                    cur_rlc.acc.clone(),
+                    cur_acc.clone()
+                        + path_branch_end.clone() * cur_mult.clone() * cur.mod_idx.clone()
+                        + path_extension.clone() * cur_mult.clone() * cur_rlc.path_rlc.clone(),
+                );
+                cb.require_equal(
+                    "mult follows the number of nibbles walked",
+                    cur_rlc.mult.clone(),
+                    cur_mult.clone()
+                        * (1.expr()
+                            + path_branch_end * (r.clone() - 1.expr())
+                            + path_extension * (cur_rlc.path_pow.clone() - 1.expr())),
+                );
+            });
+            cb.condition(cur.tag(Tag::LeafPath) * cur.role_path(), |cb| {
+                cb.require_equal(
+                    "the path of the leaf ends the key",
+                    cur_acc.clone() + cur_mult.clone() * cur_rlc.path_rlc.clone(),
+                    cur_rlc.key_full.clone(),
+                );
+                cb.require_equal(
+                    "the key has 64 nibbles",
+                    cur_mult.clone() * cur_rlc.path_pow.clone(),
+                    pow(r.clone(), NUM_KEY_NIBBLES),
+                );
+            });
+            cb.condition(
+                cur.tag(Tag::Empty) + cur.is_path() * cur.role_wrong.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "aux holds the nibbles of the key left to walk",
+                        cur_rlc.key_full.clone(),
+                        cur_acc.clone() + cur_mult.clone() * le_rlc(&cur.aux, r.clone()),
+                    );
+                },
+            );
+            cb.condition(cur.is_path() * cur.role_wrong.clone(), |cb| {
+                cb.require_equal(
+                    "the path of the node diverges from the key",
+                    (aux_head_rlc(&cur, r.clone()) - cur_rlc.path_rlc.clone())
+                        * cur_rlc.inv.clone(),
+                    1.expr(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt branches", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (prev, prev_rlc) = query(meta, Rotation::prev());
+            let open = prev.node_open.clone();
+            let lockstep = cur.lockstep.clone();
+
+            cb.condition(cur.tag(Tag::Branch), |cb| {
+                cb.require_equal(
+                    "mod_seen counts the children on the path",
+                    cur.mod_seen.clone(),
+                    open.clone() * prev.mod_seen.clone() + cur.is_mod.clone(),
+                );
+                cb.require_equal(
+                    "moved_seen counts the moved children",
+                    cur.moved_seen.clone(),
+                    open.clone() * prev.moved_seen.clone() + cur.is_moved.clone(),
+                );
+                cb.require_equal(
+                    "mod_idx is the index of the child on the path",
+                    cur.mod_idx.clone(),
+                    open.clone() * prev.mod_idx.clone() + cur.is_mod.clone() * cur.idx.clone(),
+                );
+                for (side, prev_side) in [(&cur.s, &prev.s), (&cur.c, &prev.c)] {
+                    cb.require_zero(
+                        "count is the number of children of the branch",
+                        side.on.clone()
+                            * (side.count.clone()
+                                - open.clone() * prev_side.count.clone()
+                                - not::expr(side.empty.clone())),
+                    );
+                }
+                cb.require_zero(
+                    "the children off the path are unchanged",
+                    lockstep.clone()
+                        * not::expr(cur.is_mod.clone())
+                        * (cur.s.empty.clone() - cur.c.empty.clone()),
+                );
+                cb.require_zero(
+                    "the hashes off the path are unchanged",
+                    lockstep.clone()
+                        * not::expr(cur.is_mod.clone())
+                        * (bytes_word(&cur) - aux_word(&cur)),
+                );
+                // A branch out of lockstep has two children, the leaf of the
+                // key and the moved sibling.
+                cb.require_zero(
+                    "the other children of a split branch are empty",
+                    not::expr(lockstep.clone())
+                        * not::expr(cur.is_mod.clone() + cur.is_moved.clone())
+                        * not::expr(cur.len_flags[0].clone()),
+                );
+                cb.require_zero(
+                    "the leaf and the sibling of a split branch are not empty",
+                    (cur.is_mod.clone() + cur.is_moved.clone()) * cur.len_flags[0].clone(),
+                );
+            });
+            cb.condition(cur.tag(Tag::Branch) * cur.node_end.clone(), |cb| {
+                cb.require_equal(
+                    "a branch has one child on the path",
+                    cur.mod_seen.clone(),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "a split branch has one moved child",
+                    cur.moved_seen.clone(),
+                    not::expr(lockstep.clone()),
+                );
+            });
+            cb.condition(cur.is_moved.clone(), |cb| {
+                cb.require_equal(
+                    "moved_acc is the key of the moved child",
+                    cur_rlc.moved_acc.clone(),
+                    cur_rlc.cur_acc.clone() + cur_rlc.cur_mult.clone() * cur.idx.clone(),
+                );
+                cb.require_equal(
+                    "moved_mult is the mult of the moved child",
+                    cur_rlc.moved_mult.clone(),
+                    cur_rlc.cur_mult.clone() * r.clone(),
+                );
+                cb.require_equal(
+                    "moved_hash is the hash of the moved child",
+                    cur_rlc.moved_hash.clone(),
+                    bytes_word(&cur),
+                );
+            });
+            let moved_start = cur.role_moved.clone() * cur.is_path();
+            cb.condition(cur.is_walk() * not::expr(cur.is_moved.clone()), |cb| {
+                for (name, cell, prev_cell) in [
+                    ("moved_acc", &cur_rlc.moved_acc, &prev_rlc.moved_acc),
+                    ("moved_mult", &cur_rlc.moved_mult, &prev_rlc.moved_mult),
+                    ("moved_hash", &cur_rlc.moved_hash, &prev_rlc.moved_hash),
+                ] {
+                    cb.require_equal(name, cell.clone(), prev_cell.clone());
+                }
+            });
+            cb.condition(cur.is_walk(), |cb| {
+                cb.require_equal(
+                    "moved_pending is set by a moved child until the moved node",
+                    cur.moved_pending.clone(),
+                    cur.is_moved.clone() * not::expr(cur.moved_direct.clone())
+                        + not::expr(cur.is_moved.clone() + moved_start.clone())
+                            * prev.moved_pending.clone(),
+                );
+            });
+            cb.condition(moved_start, |cb| {
+                cb.require_equal(
+                    "a moved node follows a moved child",
+                    prev.moved_pending.clone(),
+                    1.expr(),
+                );
+            });
+            cb.condition(cur.tag(Tag::LeafPath) * cur.role_path(), |cb| {
+                cb.require_zero(
+                    "the leaf of the key follows the moved node",
+                    prev.moved_pending.clone(),
+                );
+            });
+            cb.condition(cur.is_key(), |cb| {
+                cb.require_zero(
+                    "nothing is moved at the key rows",
+                    cur.moved_pending.clone(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt side registers", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (prev, prev_rlc) = query(meta, Rotation::prev());
+            let carried = cur.is_walk() + cur.tag(Tag::End);
+            let node_start = cur.is_node() * not::expr(prev.node_open.clone());
+
+            for is_new in [false, true] {
+                let (side, prev_side, side_rlc, prev_side_rlc) = if is_new {
+                    (&cur.c, &prev.c, &cur_rlc.c, &prev_rlc.c)
+                } else {
+                    (&cur.s, &prev.s, &cur_rlc.s, &prev_rlc.s)
+                };
+                let on = side.on.clone();
+                let child_word = if is_new {
+                    cur.lockstep.clone() * aux_word(&cur)
+                        + not::expr(cur.lockstep.clone()) * bytes_word(&cur)
+                } else {
+                    bytes_word(&cur)
+                };
+
+                // The child on the path of the key, followed at the end of
+                // the node
+                let set_next = on.clone()
+                    * (cur.tag(Tag::Branch) * cur.is_mod.clone() + cur.tag(Tag::ExtensionChild));
+                let follow = on.clone() * cur.is_child() * cur.node_end.clone() * cur.role_path();
+                cb.condition(carried.clone(), |cb| {
+                    cb.require_zero(
+                        "next is the hash of the child on the path",
+                        set_next.clone() * (side_rlc.next.clone() - child_word.clone()),
+                    );
+                    cb.require_zero(
+                        "next_empty is set for an empty child on the path",
+                        set_next.clone() * (side.next_empty.clone() - side.empty.clone()),
+                    );
+                    cb.require_zero(
+                        "next is kept",
+                        not::expr(set_next.clone())
+                            * (side_rlc.next.clone() - prev_side_rlc.next.clone()),
+                    );
+                    cb.require_zero(
+                        "next_empty is kept",
+                        not::expr(set_next.clone())
+                            * (side.next_empty.clone() - prev_side.next_empty.clone()),
+                    );
+                    cb.require_zero(
+                        "hash is the next node to walk",
+                        follow.clone() * (side_rlc.hash.clone() - side_rlc.next.clone()),
+                    );
+                    cb.require_zero(
+                        "hash_empty is set when the next node is empty",
+                        follow.clone() * (side.hash_empty.clone() - side.next_empty.clone()),
+                    );
+                    cb.require_zero(
+                        "hash is kept",
+                        not::expr(follow.clone())
+                            * (side_rlc.hash.clone() - prev_side_rlc.hash.clone()),
+                    );
+                    cb.require_zero(
+                        "hash_empty is kept",
+                        not::expr(follow.clone())
+                            * (side.hash_empty.clone() - prev_side.hash_empty.clone()),
+                    );
+                    cb.require_equal(
+                        "wrong is set by a node diverging from the key",
+                        side.wrong.clone(),
+                        prev_side.wrong.clone()
+                            + on.clone() * cur.node_end.clone() * cur.role_wrong.clone(),
+                    );
+                    cb.require_equal(
+                        "split is set by a branch out of lockstep",
+                        side.split.clone(),
+                        prev_side.split.clone()
+                            + on.clone()
+                                * cur.tag(Tag::Branch)
+                                * cur.node_end.clone()
+                                * not::expr(cur.lockstep.clone()),
+                    );
+                    cb.require_zero(
+                        "count is kept out of the branches",
+                        not::expr(on.clone() * cur.tag(Tag::Branch))
+                            * (side.count.clone() - prev_side.count.clone()),
+                    );
+                    cb.require_equal(
+                        "expect_branch is set by an extension on the path",
+                        side.expect_branch.clone(),
+                        on.clone() * cur.tag(Tag::ExtensionChild) * cur.role_path()
+                            + not::expr(on.clone()) * prev_side.expect_branch.clone(),
+                    );
+                    cb.require_zero(
+                        "the child of an extension is a branch",
+                        on.clone()
+                            * prev_side.expect_branch.clone()
+                            * not::expr(cur.tag(Tag::Branch)),
+                    );
+                    for (tag, cell, prev_cell) in [
+                        (Tag::AccountNonce, &side_rlc.nonce, &prev_side_rlc.nonce),
+                        (
+                            Tag::AccountBalance,
+                            &side_rlc.balance,
+                            &prev_side_rlc.balance,
+                        ),
+                        (
+                            Tag::AccountStorageRoot,
+                            &side_rlc.storage_root,
+                            &prev_side_rlc.storage_root,
+                        ),
+                        (
+                            Tag::AccountCodeHash,
+                            &side_rlc.code_hash,
+                            &prev_side_rlc.code_hash,
+                        ),
+                        (Tag::StorageValue, &side_rlc.value, &prev_side_rlc.value),
+                    ] {
+                        let set = on.clone() * cur.tag(tag) * cur.role_path();
+                        cb.require_zero(
+                            "the field is the value of the leaf of the key",
+                            set.clone() * (cell.clone() - cur_rlc.value.clone()),
+                        );
+                        cb.require_zero(
+                            "the field is kept",
+                            not::expr(set) * (cell.clone() - prev_cell.clone()),
+                        );
+                    }
+                });
+                cb.condition(
+                    on.clone() * node_start.clone() * not::expr(cur.role_moved.clone()),
+                    |cb| {
+                        cb.require_zero("a node is not empty", prev_side.hash_empty.clone());
+                    },
+                );
+                cb.condition(on.clone() * cur.tag(Tag::Empty), |cb| {
+                    cb.require_equal("the key is absent", prev_side.hash_empty.clone(), 1.expr());
+                    cb.require_equal(
+                        "the branch of an empty child has other children",
+                        side.count.clone() * (side.count.clone() - 1.expr()) * cur_rlc.inv.clone(),
+                        1.expr(),
+                    );
+                });
+
+                // The registers are reset at the key rows, where the walk
+                // starts from the root.
+                cb.condition(cur.is_key(), |cb| {
+                    cb.require_equal(
+                        "root_empty is set for the empty root",
+                        (side_rlc.hash.clone() - empty_root.clone()) * side_rlc.root_inv.clone(),
+                        not::expr(side.root_empty.clone()),
+                    );
+                    cb.require_zero(
+                        "root_empty is only set for the empty root",
+                        side.root_empty.clone() * (side_rlc.hash.clone() - empty_root.clone()),
+                    );
+                    cb.require_equal(
+                        "hash_empty is set for the empty root",
+                        side.hash_empty.clone(),
+                        side.root_empty.clone(),
+                    );
+                    cb.require_equal("a root stands for a branch", side.count.clone(), 2.expr());
+                    for cell in [&side.wrong, &side.split, &side.expect_branch] {
+                        cb.require_zero("no terminal seen at the key row", cell.clone());
+                    }
+                    for (cell, value) in [
+                        (&side_rlc.nonce, 0.expr()),
+                        (&side_rlc.balance, 0.expr()),
+                        (&side_rlc.storage_root, empty_root.clone()),
+                        (&side_rlc.code_hash, 0.expr()),
+                        (&side_rlc.value, 0.expr()),
+                    ] {
+                        cb.require_equal("the fields of an absent key", cell.clone(), value);
+                    }
+                });
+                cb.condition(cur.tag(Tag::StorageKey), |cb| {
+                    cb.require_equal(
+                        "the storage trie of the account",
+                        side_rlc.hash.clone(),
+                        prev_side_rlc.storage_root.clone(),
+                    );
+                });
+            }
+            cb.condition(cur.tag(Tag::AccountCodeHash) * cur.role_path(), |cb| {
+                let fields = |side: &SideCells<Expression<F>>,
+                              side_rlc: &SideRlcCells<Expression<F>>| {
+                    side.on.clone()
+                        * (side_rlc.nonce.clone()
+                            + r.clone() * side_rlc.balance.clone()
+                            + pow(r.clone(), 2)
+                                * (side_rlc.storage_root.clone() - empty_root.clone())
+                            + pow(r.clone(), 3) * side_rlc.code_hash.clone())
+                };
+                cb.require_equal(
+                    "an account leaf isn't the empty account",
+                    (fields(&cur.s, &cur_rlc.s) + fields(&cur.c, &cur_rlc.c)) * cur_rlc.inv.clone(),
+                    1.expr(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt moved nodes", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (prev, prev_rlc) = query(meta, Rotation::prev());
+            let role_moved = cur.role_moved.clone();
+            let one_item = (
+                cur.s.on.clone() * cur_rlc.s.item.clone()
+                    + cur.c.on.clone() * cur_rlc.c.item.clone(),
+                cur.s.on.clone() * cur_rlc.s.item_pow.clone()
+                    + cur.c.on.clone() * cur_rlc.c.item_pow.clone(),
+                cur.s.on.clone() * cur.s.item_len.clone()
+                    + cur.c.on.clone() * cur.c.item_len.clone(),
+            );
+
+            // The key of a node and the items after its path
+            cb.condition(cur.is_path(), |cb| {
+                let base_acc = role_moved.clone() * cur_rlc.moved_acc.clone()
+                    + not::expr(role_moved.clone()) * cur_rlc.cur_acc.clone();
+                let base_mult = role_moved.clone() * cur_rlc.moved_mult.clone()
+                    + not::expr(role_moved.clone()) * cur_rlc.cur_mult.clone();
+                cb.require_equal(
+                    "node_key is the key of the node up to the end of its path",
+                    cur_rlc.node_key.clone(),
+                    base_acc + base_mult.clone() * cur_rlc.path_rlc.clone(),
+                );
+                cb.require_equal(
+                    "node_mult is the mult of node_key",
+                    cur_rlc.node_mult.clone(),
+                    base_mult * cur_rlc.path_pow.clone(),
+                );
+                cb.require_zero("rest_rlc starts after the path", cur_rlc.rest_rlc.clone());
+                cb.require_zero("rest_len starts after the path", cur.rest_len.clone());
+            });
+            cb.condition(cur.is_node() - cur.is_path(), |cb| {
+                cb.require_equal(
+                    "node_key is kept",
+                    cur_rlc.node_key.clone(),
+                    prev_rlc.node_key.clone(),
+                );
+                cb.require_equal(
+                    "node_mult is kept",
+                    cur_rlc.node_mult.clone(),
+                    prev_rlc.node_mult.clone(),
+                );
+                cb.require_equal(
+                    "rest_rlc accumulates the items after the path",
+                    cur_rlc.rest_rlc.clone(),
+                    prev_rlc.rest_rlc.clone() * one_item.1.clone() + one_item.0.clone(),
+                );
+                cb.require_equal(
+                    "rest_len accumulates the lengths after the path",
+                    cur.rest_len.clone(),
+                    prev.rest_len.clone() + one_item.2.clone(),
+                );
+            });
+
+            // The node diverging from the key on one side is the node moved by
+            // the split branch on the other side: it has the same key and the
+            // same items after its path.  A moved branch is only referenced
+            // by its hash, and matched with the child of an extension.
+            let node_event = cur.node_end.clone() * (cur.role_wrong.clone() + role_moved);
+            let direct_event = cur.is_moved.clone() * cur.moved_direct.clone();
+            let node_info = [
+                cur_rlc.node_key.clone(),
+                cur_rlc.node_mult.clone(),
+                cur_rlc.rest_rlc.clone(),
+                cur.rest_len.clone(),
+            ];
+            let direct_info = [
+                cur_rlc.cur_acc.clone() + cur_rlc.cur_mult.clone() * cur.idx.clone(),
+                cur_rlc.cur_mult.clone() * r.clone(),
+                cur_rlc.item_rlc.clone(),
+                (NUM_HASH_BYTES + 1).expr(),
+            ];
+            let others = [
+                (&cur_rlc.other_key, &prev_rlc.other_key),
+                (&cur_rlc.other_mult, &prev_rlc.other_mult),
+                (&cur_rlc.other_rest_rlc, &prev_rlc.other_rest_rlc),
+                (&cur.other_rest_len, &prev.other_rest_len),
+            ];
+            for (event, info) in [
+                (node_event.clone(), node_info),
+                (direct_event.clone(), direct_info),
+            ] {
+                cb.condition(cur.s.on.clone() * event.clone(), |cb| {
+                    for ((other, _), value) in others.iter().zip(info.iter()) {
+                        cb.require_equal(
+                            "the old side records the moved node",
+                            (*other).clone(),
+                            value.clone(),
+                        );
+                    }
+                });
+                cb.condition(cur.c.on.clone() * event, |cb| {
+                    for ((_, prev_other), value) in others.iter().zip(info.iter()) {
+                        cb.require_equal(
+                            "the new side matches the moved node",
+                            (*prev_other).clone(),
+                            value.clone(),
+                        );
+                    }
+                });
+            }
+            cb.condition(
+                cur.is_walk() * not::expr(cur.s.on.clone() * (node_event + direct_event)),
+                |cb| {
+                    for (other, prev_other) in others.iter() {
+                        cb.require_equal(
+                            "the moved node is kept",
+                            (*other).clone(),
+                            (*prev_other).clone(),
+                        );
+                    }
+                },
+            );
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt updates", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (prev, prev_rlc) = query(meta, Rotation::prev());
+            let [address, storage_key, proof_type, new_root, old_root, new_value, old_value] =
+                <[Expression<F>; 7]>::try_from(mpt_table.table_exprs(meta)).unwrap();
+            let table = [
+                address.clone(),
+                storage_key.clone(),
+                proof_type.clone(),
+                new_root.clone(),
+                old_root.clone(),
+                new_value.clone(),
+                old_value.clone(),
+            ];
+            let flags = cur.proof_flags.clone();
+
+            cb.condition(cur.tag(Tag::Start), |cb| {
+                cb.require_equal(
+                    "one proof type per update",
+                    sum::expr(flags.iter()),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "the proof type of the update",
+                    proof_type,
+                    sum::expr(
+                        flags
+                            .iter()
+                            .enumerate()
+                            .map(|(i, flag)| (i + 1).expr() * flag.clone()),
+                    ),
+                );
+                cb.require_equal(
+                    "the address of the update",
+                    address,
+                    be_rlc(&cur.aux[..NUM_ADDRESS_BYTES], 256.expr()),
+                );
+                cb.require_equal("storage_key", storage_key, cur_rlc.storage_key.clone());
+                cb.require_equal("old_value", old_value, cur_rlc.old_value.clone());
+                cb.require_equal("new_value", new_value, cur_rlc.new_value.clone());
+                cb.require_equal("old_root", old_root, cur_rlc.s.hash.clone());
+                cb.require_equal("new_root", new_root, cur_rlc.c.hash.clone());
+                cb.require_equal(
+                    "root is the new root of the update",
+                    cur_rlc.root.clone(),
+                    cur_rlc.c.hash.clone(),
+                );
+                cb.require_zero(
+                    "an account update has no storage key",
+                    not::expr(cur.needs_storage()) * cur_rlc.storage_key.clone(),
+                );
+                cb.require_zero("the account trie is walked first", cur.in_storage.clone());
+            });
+            cb.condition(not::expr(cur.tag(Tag::Start)), |cb| {
+                for value in table {
+                    cb.require_zero("the MPT table is only set at the start rows", value);
+                }
+            });
+            cb.condition(
+                cur.is_walk() + cur.tag(Tag::End) + cur.tag(Tag::StorageKey),
+                |cb| {
+                    for (cell, prev_cell) in cur.proof_flags.iter().zip(prev.proof_flags.iter()) {
+                        cb.require_equal("the proof type is kept", cell.clone(), prev_cell.clone());
+                    }
+                    for (name, cell, prev_cell) in [
+                        (
+                            "storage_key is kept",
+                            &cur_rlc.storage_key,
+                            &prev_rlc.storage_key,
+                        ),
+                        ("old_value is kept", &cur_rlc.old_value, &prev_rlc.old_value),
+                        ("new_value is kept", &cur_rlc.new_value, &prev_rlc.new_value),
+                    ] {
+                        cb.require_equal(name, cell.clone(), prev_cell.clone());
+                    }
+                },
+            );
+            cb.condition(cur.is_walk() + cur.tag(Tag::End), |cb| {
+                cb.require_equal(
+                    "in_storage is kept",
+                    cur.in_storage.clone(),
+                    prev.in_storage.clone(),
+                );
+            });
+            cb.condition(cur.tag(Tag::StorageKey), |cb| {
+                cb.require_equal(
+                    "the storage trie is walked",
+                    cur.in_storage.clone(),
+                    1.expr(),
+                );
+                cb.require_equal(
+                    "the storage key of the update",
+                    cur_rlc.storage_key.clone(),
+                    be_rlc(&cur.aux[..NUM_HASH_BYTES], word_r.clone()),
+                );
+            });
+            cb.condition(cur.is_key() * cur.is_non_existing(), |cb| {
+                cb.require_equal(
+                    "the trie is unchanged when the key is absent",
+                    cur_rlc.s.hash.clone(),
+                    cur_rlc.c.hash.clone(),
+                );
+            });
+
+            cb.condition(cur.tag(Tag::End), |cb| {
+                let (wrong_old, split_old) = (cur.s.wrong.clone(), cur.s.split.clone());
+                let (wrong_new, split_new) = (cur.c.wrong.clone(), cur.c.split.clone());
+                cb.require_zero(
+                    "a node diverging from the old key stays or is moved",
+                    wrong_old.clone() * not::expr(wrong_new.clone() + split_new.clone()),
+                );
+                cb.require_zero(
+                    "a split old branch is merged",
+                    split_old.clone() * not::expr(wrong_new.clone()),
+                );
+                cb.require_zero(
+                    "a node diverging from the new key was there or was moved",
+                    wrong_new.clone() * not::expr(wrong_old.clone() + split_old),
+                );
+                cb.require_zero(
+                    "a split new branch splits a node",
+                    split_new * not::expr(wrong_old.clone()),
+                );
+                cb.require_zero(
+                    "a node diverging on both sides is unchanged",
+                    wrong_old * wrong_new * (cur_rlc.s.hash.clone() - cur_rlc.c.hash.clone()),
+                );
+                cb.require_zero("the moved node is walked", prev.moved_pending.clone());
+            });
+            cb.condition(
+                cur.tag(Tag::End) * not::expr(cur.in_storage.clone()),
+                |cb| {
+                    for (flag, old, new) in [
+                        (flags[0].clone(), &cur_rlc.s.nonce, &cur_rlc.c.nonce),
+                        (flags[1].clone(), &cur_rlc.s.balance, &cur_rlc.c.balance),
+                        (
+                            flags[2].clone() + flags[3].clone(),
+                            &cur_rlc.s.code_hash,
+                            &cur_rlc.c.code_hash,
+                        ),
+                    ] {
+                        cb.require_zero(
+                            "the old field is the old value",
+                            flag.clone() * (old.clone() - cur_rlc.old_value.clone()),
+                        );
+                        cb.require_zero(
+                            "the new field is the new value",
+                            flag.clone() * (new.clone() - cur_rlc.new_value.clone()),
+                        );
+                        cb.require_zero(
+                            "the other fields are unchanged",
+                            not::expr(flag) * (old.clone() - new.clone()),
+                        );
+                    }
+                    cb.require_zero(
+                        "the storage root changes with the storage",
+                        not::expr(cur.needs_storage())
+                            * (cur_rlc.s.storage_root.clone() - cur_rlc.c.storage_root.clone()),
+                    );
+                },
+            );
+            cb.condition(cur.tag(Tag::End) * cur.in_storage.clone(), |cb| {
+                cb.require_equal(
+                    "the old storage value",
+                    cur_rlc.s.value.clone(),
+                    cur_rlc.old_value.clone(),
+                );
+                cb.require_equal(
+                    "the new storage value",
+                    cur_rlc.c.value.clone(),
+                    cur_rlc.new_value.clone(),
+                );
+            });
+            cb.condition(meta.query_fixed(q_last, Rotation::cur()), |cb| {
+                cb.require_equal("the last row is padding", cur.tag(Tag::Padding), 1.expr());
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt roots", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (_, prev_rlc) = query(meta, Rotation::prev());
+            let q_first = meta.query_fixed(q_first, Rotation::cur());
+
+            cb.condition(q_first.clone(), |cb| {
+                cb.require_equal(
+                    "first_root is the old root of the first update, or the root without updates",
+                    cur_rlc.first_root.clone(),
+                    cur.tag(Tag::Start) * cur_rlc.s.hash.clone()
+                        + cur.tag(Tag::Padding) * cur_rlc.root.clone(),
+                );
+            });
+            cb.condition(not::expr(q_first), |cb| {
+                cb.require_equal(
+                    "first_root is kept",
+                    cur_rlc.first_root.clone(),
+                    prev_rlc.first_root.clone(),
+                );
+                cb.condition(cur.tag(Tag::Start), |cb| {
+                    cb.require_equal(
+                        "an update starts from the new root of the previous one",
+                        cur_rlc.s.hash.clone(),
+                        prev_rlc.root.clone(),
+                    );
+                });
+                cb.condition(not::expr(cur.tag(Tag::Start)), |cb| {
+                    cb.require_equal("root is kept", cur_rlc.root.clone(), prev_rlc.root.clone());
+                });
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("mpt root words", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let (cur, cur_rlc) = query(meta, Rotation::cur());
+            let (prev, _) = query(meta, Rotation::prev());
+            let half = NUM_HASH_BYTES / 2;
+
+            cb.require_equal(
+                "the root rows are padding",
+                prev.tag(Tag::Padding),
+                1.expr(),
+            );
+            for (rotation, cells, root) in [
+                (Rotation::prev(), &prev, cur_rlc.first_root.clone()),
+                (Rotation::cur(), &cur, cur_rlc.root.clone()),
+            ] {
+                cb.require_equal(
+                    "the bytes of a root row hold the root",
+                    bytes_word(cells),
+                    root,
+                );
+                for (column, start) in root_word.iter().zip([1, 1 + half]) {
+                    cb.require_equal(
+                        "the root word is the 128 bits of the bytes of the root row",
+                        meta.query_advice(*column, rotation),
+                        be_rlc(&cells.bytes[start..start + half], 256.expr()),
+                    );
+                }
+            }
+            cb.gate(meta.query_fixed(q_last, Rotation::cur()))
+        });
+
+        // The nibbles of the content bytes.  A single byte item is below 0x80,
+        // and the content of a single byte string is at least 0x80.
+        for j in 0..NUM_ITEM_BYTES - 1 {
+            meta.lookup("mpt nibbles", |meta| {
+                let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+                let (cur, _) = query(meta, Rotation::cur());
+                let shift = if j == 0 {
+                    let short = cur.is_short.clone();
+                    cur.is_node() * (short.clone() - cur.len_flags[1].clone() * not::expr(short))
+                } else {
+                    0.expr()
+                };
+                vec![
+                    (
+                        q_enable.clone() * (cur.bytes[j + 1].clone() + 128.expr() * shift.clone()),
+                        nibble_table[0],
+                    ),
+                    (
+                        q_enable * (cur.hi[j].clone() + 8.expr() * shift),
+                        nibble_table[1],
+                    ),
+                ]
+            });
+        }
+        meta.lookup("mpt short value", |meta| {
+            let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+            let (cur, _) = query(meta, Rotation::cur());
+            let single = cur.tag(Tag::StorageValue) * cur.len_flags[2].clone();
+            vec![(
+                q_enable * (cur.bytes[2].clone() - 128.expr() * single),
+                nibble_table[0],
+            )]
+        });
+        for is_new in [false, true] {
+            let side = |cells: &MptCells<Expression<F>>| {
+                if is_new {
+                    cells.c.clone()
+                } else {
+                    cells.s.clone()
+                }
+            };
+            // The length of a short list is at most 55, the lengths of the
+            // long lists take one or two bytes.
+            meta.lookup("mpt node length", |meta| {
+                let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+                let side = side(&query(meta, Rotation::cur()).0);
+                let [short_list, long_list_1, long_list_2] = side.hdr;
+                let len = side.node_len;
+                let hi = side.hdr_hi;
+                vec![(
+                    q_enable
+                        * (short_list * (len.clone() + (255 - RLP_MAX_SHORT).expr())
+                            + long_list_1 * len.clone()
+                            + long_list_2 * (len - 256.expr() * hi)),
+                    nibble_table[0],
+                )]
+            });
+            meta.lookup("mpt long node length", |meta| {
+                let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+                let side = side(&query(meta, Rotation::cur()).0);
+                let [_, long_list_1, long_list_2] = side.hdr;
+                vec![(
+                    q_enable
+                        * (long_list_1 * (side.node_len - (RLP_MAX_SHORT + 1).expr())
+                            + long_list_2 * (side.hdr_hi - 1.expr())),
+                    nibble_table[0],
+                )]
+            });
+            meta.lookup_any("mpt node hash", |meta| {
+                let (cur, cur_rlc) = query(meta, Rotation::cur());
+                let (side, side_rlc) = if is_new {
+                    (cur.c, cur_rlc.c)
+                } else {
+                    (cur.s, cur_rlc.s)
+                };
+                let enable = meta.query_fixed(q_enable, Rotation::cur()) * side.lookup_on;
+                let input = [
+                    enable.clone(),
+                    enable.clone() * side_rlc.lookup_rlc,
+                    enable.clone() * side.lookup_len,
+                    enable * side_rlc.lookup_hash,
+                ];
+                let table = [
+                    keccak_table.is_enabled,
+                    keccak_table.input_rlc,
+                    keccak_table.input_len,
+                    keccak_table.output_rlc,
+                ]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+                input.into_iter().zip(table).collect()
+            });
+        }
+
+        Self {
+            q_enable,
+            q_first,
+            q_last,
+            nibble_table,
+            cells,
+            rlcs,
+            root_word,
+            instance,
+            mpt_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> MptCircuitConfig<F> {
+    fn load_nibble_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "mpt nibble table",
+            |mut table| {
+                for byte in 0..256u64 {
+                    table.assign_cell(
+                        || "byte",
+                        self.nibble_table[0],
+                        byte as usize,
+                        || Value::known(F::from(byte)),
+                    )?;
+                    table.assign_cell(
+                        || "high nibble",
+                        self.nibble_table[1],
+                        byte as usize,
+                        || Value::known(F::from(byte >> 4)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign the rows of the updates, with their MPT table rows, and the
+    /// nibble table.  The words of the roots are copied to the instance.
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rows: &[MptRow<F>],
+        rlc_rows: Value<Vec<MptRlcCells<F>>>,
+        table_rows: &[MptUpdateRow<Value<F>>],
+    ) -> Result<(), Error> {
+        self.load_nibble_table(layouter)?;
+        let root_words = layouter.assign_region(
+            || "mpt circuit",
+            |mut region| {
+                let mut table_rows = table_rows.iter();
+                let mut root_words = Vec::new();
+                for (offset, row) in rows.iter().enumerate() {
+                    self.assign_row(&mut region, offset, row, offset + 1 == rows.len())?;
+                    let is_root = offset + 2 >= rows.len();
+                    let root_word = if is_root {
+                        row.root_word()
+                    } else {
+                        [F::ZERO; 2]
+                    };
+                    for (column, value) in self.root_word.iter().zip(root_word) {
+                        let cell = region.assign_advice(
+                            || format!("mpt root word at {}", offset),
+                            *column,
+                            offset,
+                            || Value::known(value),
+                        )?;
+                        if is_root {
+                            root_words.push(cell);
+                        }
+                    }
+                    let rlcs: Value<Vec<F>> = rlc_rows
+                        .as_ref()
+                        .map(|rlc_rows| rlc_rows[offset].to_vec().into_iter().copied().collect());
+                    for (idx, column) in self.rlcs.to_vec().into_iter().enumerate() {
+                        region.assign_advice(
+                            || format!("mpt rlc cell {} at {}", idx, offset),
+                            *column,
+                            offset,
+                            || rlcs.as_ref().map(|rlcs| rlcs[idx]),
+                        )?;
+                    }
+                    let table_row = if row.tag == Tag::Start {
+                        *table_rows.next().expect("one table row per update")
+                    } else {
+                        MptUpdateRow([Value::known(F::ZERO); 7])
+                    };
+                    self.mpt_table.assign(&mut region, offset, &table_row)?;
+                }
+                self.mpt_table.annotate_columns_in_region(&mut region);
+                Ok(root_words)
+            },
+        )?;
+        for (idx, cell) in root_words.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), self.instance, idx)?;
+        }
+        Ok(())
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &MptRow<F>,
+        is_last: bool,
+    ) -> Result<(), Error> {
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, true),
+            ("q_first", self.q_first, offset == 0),
+            ("q_last", self.q_last, is_last),
+        ] {
+            region.assign_fixed(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+        for (idx, (column, value)) in self
+            .cells
+            .to_vec()
+            .into_iter()
+            .zip(row.cells.to_vec())
+            .enumerate()
+        {
+            region.assign_advice(
+                || format!("mpt cell {} at {}", idx, offset),
+                *column,
+                offset,
+                || Value::known(*value),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// MptCircuit
+#[derive(Default, Clone, Debug)]
+pub struct MptCircuit<F: Field> {
+    updates: MptUpdates,
+    num_rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MptCircuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(num_rows: usize, updates: MptUpdates) -> Self {
+        MptCircuit {
+            updates,
+            num_rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of rows that can be assigned in this circuit
+    pub fn capacity(&self) -> Option<usize> {
+        (self.num_rows > 0).then(|| self.num_rows.saturating_sub(Self::unusable_rows()))
+    }
+}
+
+impl<F: Field> SubCircuit<F> for MptCircuit<F> {
+    type Config = MptCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    /// The `block.circuits_params.max_mpt_rows` parameter, when enabled, sets
+    /// up the circuit to support a fixed number of rows, independently of the
+    /// rows required by the updates.
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            block.circuits_params.max_mpt_rows,
+            block.mpt_updates.clone(),
+        )
+    }
+
+    /// The old root of the first update and the new root of the last one, as
+    /// their high and low 128 bits
+    fn instance(&self) -> Vec<Vec<F>> {
+        let words = [self.updates.old_root(), self.updates.new_root()]
+            .iter()
+            .flat_map(|root| {
+                let (lo, hi) = split_u256(root);
+                [hi, lo].map(|word| word.to_scalar().expect("128 bits fit in the field"))
+            })
+            .collect();
+        vec![words]
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let rows =
+            mpt_rows::<F>(&block.mpt_updates, F::ONE, F::ONE, None).expect("no capacity limit");
+        (rows.len(), block.circuits_params.max_mpt_rows)
+    }
+
+    /// Make the assignments to the MptCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let capacity = self.capacity();
+        // The first phase cells don't depend on the challenges
+        let rows = mpt_rows(&self.updates, F::ONE, F::ONE, capacity)?;
+        let rlc_rows = challenges
+            .keccak_input()
+            .zip(challenges.evm_word())
+            .map(|(r, word_r)| {
+                mpt_rows(&self.updates, r, word_r, capacity)
+                    .expect("the rows fit in the circuit")
+                    .into_iter()
+                    .map(|row| row.rlcs)
+                    .collect()
+            });
+        let table_rows = self.updates.proof_table_assignments(challenges.evm_word());
+        config.assign(layouter, &rows, rlc_rows, &table_rows)
+    }
+}
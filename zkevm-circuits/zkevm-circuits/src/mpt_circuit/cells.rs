@@ -0,0 +1,561 @@
+//! The cells of a row of the MPT circuit, generic over the columns, their
+//! queries and their values.  The cells of the first phase don't depend on the
+//! challenges, the cells of the second phase hold the RLCs.
+use super::param::*;
+use eth_types::Field;
+use gadgets::util::{sum, Expr};
+use halo2_proofs::plonk::Expression;
+
+/// The first phase cells of a row
+#[derive(Clone, Debug)]
+pub(crate) struct MptCells<T> {
+    /// One-hot tag of the row
+    pub(crate) tags: [T; NUM_TAGS],
+    /// The item of a node row: the header byte, 0 for a single byte item,
+    /// followed by the content, padded with zeros
+    pub(crate) bytes: [T; NUM_ITEM_BYTES],
+    /// One-hot length of the content of the item
+    pub(crate) len_flags: [T; NUM_ITEM_BYTES],
+    /// The item is a single byte without header
+    pub(crate) is_short: T,
+    /// The path of a path item has an odd number of nibbles
+    pub(crate) is_odd: T,
+    /// The high nibbles of the content bytes
+    pub(crate) hi: [T; NUM_ITEM_BYTES - 1],
+    /// The address or the storage key on the key rows, the hash of the new
+    /// child on the branch and extension rows walked in lockstep, or the
+    /// nibbles of the key left to walk on the rows where the key is absent
+    pub(crate) aux: [T; NUM_AUX],
+    /// 0 for the key rows and the rows walked in lockstep, 1 and 2 for the
+    /// rows of the old and new tries only, 3 for the end and padding rows
+    pub(crate) stage: T,
+    /// The row holds both the old and the new node
+    pub(crate) lockstep: T,
+    /// Last row of a node
+    pub(crate) node_end: T,
+    /// The node goes on in the next row
+    pub(crate) node_open: T,
+    /// The node diverges from the key
+    pub(crate) role_wrong: T,
+    /// The node is the sibling moved up or down by a split branch
+    pub(crate) role_moved: T,
+    /// Index of the child of a branch row
+    pub(crate) idx: T,
+    /// The child is on the path of the key
+    pub(crate) is_mod: T,
+    /// The child is the other child of a split branch
+    pub(crate) is_moved: T,
+    /// Number of children on the path of the key seen in the branch
+    pub(crate) mod_seen: T,
+    /// Number of moved children seen in the branch
+    pub(crate) moved_seen: T,
+    /// Index of the child on the path of the key, once seen
+    pub(crate) mod_idx: T,
+    /// The moved child is a branch, which keeps its hash
+    pub(crate) moved_direct: T,
+    /// A moved node is expected
+    pub(crate) moved_pending: T,
+    /// The length of the list of the fields of an account
+    pub(crate) account_len: T,
+    /// The length of the fields of an account seen so far
+    pub(crate) value_len: T,
+    /// The rows walk a storage trie
+    pub(crate) in_storage: T,
+    /// One-hot MPT proof type of the update
+    pub(crate) proof_flags: [T; NUM_PROOF_TYPES],
+    /// Length of the items of the node after its path
+    pub(crate) rest_len: T,
+    /// `rest_len` of the node that diverges from the key in the old trie
+    pub(crate) other_rest_len: T,
+    /// The old trie
+    pub(crate) s: SideCells<T>,
+    /// The new trie
+    pub(crate) c: SideCells<T>,
+}
+
+/// The first phase cells of a row for one of the tries
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SideCells<T> {
+    /// The row walks this trie
+    pub(crate) on: T,
+    /// The child of the row is empty
+    pub(crate) empty: T,
+    /// Length of the bytes of the row added to the node
+    pub(crate) item_len: T,
+    /// Length of the payload of the node up to this row
+    pub(crate) node_len: T,
+    /// One-hot kind of the header of the node: short list, or long list with
+    /// a length of one or two bytes
+    pub(crate) hdr: [T; 3],
+    /// High byte of the length of a long list with a length of two bytes
+    pub(crate) hdr_hi: T,
+    /// Lookup to the keccak table
+    pub(crate) lookup_on: T,
+    /// Length of the lookup input
+    pub(crate) lookup_len: T,
+    /// The next node is empty
+    pub(crate) hash_empty: T,
+    /// The child on the path of the key is empty
+    pub(crate) next_empty: T,
+    /// The root of a key row is the empty root
+    pub(crate) root_empty: T,
+    /// Number of children of the last branch
+    pub(crate) count: T,
+    /// The walk ends at a node diverging from the key
+    pub(crate) wrong: T,
+    /// The walk ends at a split branch
+    pub(crate) split: T,
+    /// The next row of the trie is a branch
+    pub(crate) expect_branch: T,
+}
+
+/// The second phase cells of a row
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MptRlcCells<T> {
+    /// RLC of the item
+    pub(crate) item_rlc: T,
+    /// Power of the randomness for the length of the item
+    pub(crate) item_pow: T,
+    /// RLC of the nibbles of a path item
+    pub(crate) path_rlc: T,
+    /// Power of the randomness for the number of nibbles of a path item
+    pub(crate) path_pow: T,
+    /// The value of a value item
+    pub(crate) value: T,
+    /// RLC of the nibbles of the key
+    pub(crate) key_full: T,
+    /// RLC of the nibbles of the key walked so far
+    pub(crate) acc: T,
+    /// Power of the randomness for the nibbles walked so far
+    pub(crate) mult: T,
+    /// `acc` before the row
+    pub(crate) cur_acc: T,
+    /// `mult` before the row
+    pub(crate) cur_mult: T,
+    /// `acc` at the end of the lockstep walk
+    pub(crate) lock_acc: T,
+    /// `mult` at the end of the lockstep walk
+    pub(crate) lock_mult: T,
+    /// `acc` of the moved child
+    pub(crate) moved_acc: T,
+    /// `mult` of the moved child
+    pub(crate) moved_mult: T,
+    /// Hash of the moved child
+    pub(crate) moved_hash: T,
+    /// RLC of the nibbles of the key of the node, up to the end of its path
+    pub(crate) node_key: T,
+    /// Power of the randomness for the nibbles of `node_key`
+    pub(crate) node_mult: T,
+    /// RLC of the items of the node after its path
+    pub(crate) rest_rlc: T,
+    /// `node_key` of the node that diverges from the key in the old trie
+    pub(crate) other_key: T,
+    /// `node_mult` of the node that diverges from the key in the old trie
+    pub(crate) other_mult: T,
+    /// `rest_rlc` of the node that diverges from the key in the old trie
+    pub(crate) other_rest_rlc: T,
+    /// Inverse witness of the non-zero checks of the row
+    pub(crate) inv: T,
+    /// Old value of the update
+    pub(crate) old_value: T,
+    /// New value of the update
+    pub(crate) new_value: T,
+    /// Storage key of the update
+    pub(crate) storage_key: T,
+    /// Old root of the first update
+    pub(crate) first_root: T,
+    /// New root of the last update so far
+    pub(crate) root: T,
+    /// The old trie
+    pub(crate) s: SideRlcCells<T>,
+    /// The new trie
+    pub(crate) c: SideRlcCells<T>,
+}
+
+/// The second phase cells of a row for one of the tries
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SideRlcCells<T> {
+    /// RLC of the bytes of the row added to the node
+    pub(crate) item: T,
+    /// Power of the randomness for the length of `item`
+    pub(crate) item_pow: T,
+    /// RLC of the payload of the node up to this row
+    pub(crate) node_rlc: T,
+    /// Power of the randomness for the length of `node_rlc`
+    pub(crate) node_pow: T,
+    /// RLC of the lookup input
+    pub(crate) lookup_rlc: T,
+    /// Word RLC of the lookup output
+    pub(crate) lookup_hash: T,
+    /// Hash of the next node
+    pub(crate) hash: T,
+    /// Hash of the child on the path of the key
+    pub(crate) next: T,
+    /// Inverse witness of the empty root check
+    pub(crate) root_inv: T,
+    /// Nonce of the account
+    pub(crate) nonce: T,
+    /// Word RLC of the balance of the account
+    pub(crate) balance: T,
+    /// Word RLC of the storage root of the account
+    pub(crate) storage_root: T,
+    /// Word RLC of the code hash of the account
+    pub(crate) code_hash: T,
+    /// Word RLC of the storage value
+    pub(crate) value: T,
+}
+
+impl<T: Default> Default for MptCells<T> {
+    fn default() -> Self {
+        let unit: MptCells<()> = MptCells {
+            tags: [(); NUM_TAGS],
+            bytes: [(); NUM_ITEM_BYTES],
+            len_flags: [(); NUM_ITEM_BYTES],
+            is_short: (),
+            is_odd: (),
+            hi: [(); NUM_ITEM_BYTES - 1],
+            aux: [(); NUM_AUX],
+            stage: (),
+            lockstep: (),
+            node_end: (),
+            node_open: (),
+            role_wrong: (),
+            role_moved: (),
+            idx: (),
+            is_mod: (),
+            is_moved: (),
+            mod_seen: (),
+            moved_seen: (),
+            mod_idx: (),
+            moved_direct: (),
+            moved_pending: (),
+            account_len: (),
+            value_len: (),
+            in_storage: (),
+            proof_flags: [(); NUM_PROOF_TYPES],
+            rest_len: (),
+            other_rest_len: (),
+            s: SideCells::default(),
+            c: SideCells::default(),
+        };
+        unit.map(|_| T::default())
+    }
+}
+
+impl<T> MptCells<T> {
+    pub(crate) fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> MptCells<U> {
+        MptCells {
+            tags: array_init::array_init(|i| f(&self.tags[i])),
+            bytes: array_init::array_init(|i| f(&self.bytes[i])),
+            len_flags: array_init::array_init(|i| f(&self.len_flags[i])),
+            is_short: f(&self.is_short),
+            is_odd: f(&self.is_odd),
+            hi: array_init::array_init(|i| f(&self.hi[i])),
+            aux: array_init::array_init(|i| f(&self.aux[i])),
+            stage: f(&self.stage),
+            lockstep: f(&self.lockstep),
+            node_end: f(&self.node_end),
+            node_open: f(&self.node_open),
+            role_wrong: f(&self.role_wrong),
+            role_moved: f(&self.role_moved),
+            idx: f(&self.idx),
+            is_mod: f(&self.is_mod),
+            is_moved: f(&self.is_moved),
+            mod_seen: f(&self.mod_seen),
+            moved_seen: f(&self.moved_seen),
+            mod_idx: f(&self.mod_idx),
+            moved_direct: f(&self.moved_direct),
+            moved_pending: f(&self.moved_pending),
+            account_len: f(&self.account_len),
+            value_len: f(&self.value_len),
+            in_storage: f(&self.in_storage),
+            proof_flags: array_init::array_init(|i| f(&self.proof_flags[i])),
+            rest_len: f(&self.rest_len),
+            other_rest_len: f(&self.other_rest_len),
+            s: self.s.map(&mut f),
+            c: self.c.map(&mut f),
+        }
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<&T> {
+        let mut cells: Vec<&T> = Vec::new();
+        cells.extend(self.tags.iter());
+        cells.extend(self.bytes.iter());
+        cells.extend(self.len_flags.iter());
+        cells.extend([&self.is_short, &self.is_odd]);
+        cells.extend(self.hi.iter());
+        cells.extend(self.aux.iter());
+        cells.extend([
+            &self.stage,
+            &self.lockstep,
+            &self.node_end,
+            &self.node_open,
+            &self.role_wrong,
+            &self.role_moved,
+            &self.idx,
+            &self.is_mod,
+            &self.is_moved,
+            &self.mod_seen,
+            &self.moved_seen,
+            &self.mod_idx,
+            &self.moved_direct,
+            &self.moved_pending,
+            &self.account_len,
+            &self.value_len,
+            &self.in_storage,
+        ]);
+        cells.extend(self.proof_flags.iter());
+        cells.extend([&self.rest_len, &self.other_rest_len]);
+        cells.extend(self.s.to_vec());
+        cells.extend(self.c.to_vec());
+        cells
+    }
+}
+
+impl<T> SideCells<T> {
+    pub(crate) fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> SideCells<U> {
+        SideCells {
+            on: f(&self.on),
+            empty: f(&self.empty),
+            item_len: f(&self.item_len),
+            node_len: f(&self.node_len),
+            hdr: array_init::array_init(|i| f(&self.hdr[i])),
+            hdr_hi: f(&self.hdr_hi),
+            lookup_on: f(&self.lookup_on),
+            lookup_len: f(&self.lookup_len),
+            hash_empty: f(&self.hash_empty),
+            next_empty: f(&self.next_empty),
+            root_empty: f(&self.root_empty),
+            count: f(&self.count),
+            wrong: f(&self.wrong),
+            split: f(&self.split),
+            expect_branch: f(&self.expect_branch),
+        }
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<&T> {
+        let mut cells = vec![&self.on, &self.empty, &self.item_len, &self.node_len];
+        cells.extend(self.hdr.iter());
+        cells.extend([
+            &self.hdr_hi,
+            &self.lookup_on,
+            &self.lookup_len,
+            &self.hash_empty,
+            &self.next_empty,
+            &self.root_empty,
+            &self.count,
+            &self.wrong,
+            &self.split,
+            &self.expect_branch,
+        ]);
+        cells
+    }
+}
+
+impl<T> MptRlcCells<T> {
+    pub(crate) fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> MptRlcCells<U> {
+        MptRlcCells {
+            item_rlc: f(&self.item_rlc),
+            item_pow: f(&self.item_pow),
+            path_rlc: f(&self.path_rlc),
+            path_pow: f(&self.path_pow),
+            value: f(&self.value),
+            key_full: f(&self.key_full),
+            acc: f(&self.acc),
+            mult: f(&self.mult),
+            cur_acc: f(&self.cur_acc),
+            cur_mult: f(&self.cur_mult),
+            lock_acc: f(&self.lock_acc),
+            lock_mult: f(&self.lock_mult),
+            moved_acc: f(&self.moved_acc),
+            moved_mult: f(&self.moved_mult),
+            moved_hash: f(&self.moved_hash),
+            node_key: f(&self.node_key),
+            node_mult: f(&self.node_mult),
+            rest_rlc: f(&self.rest_rlc),
+            other_key: f(&self.other_key),
+            other_mult: f(&self.other_mult),
+            other_rest_rlc: f(&self.other_rest_rlc),
+            inv: f(&self.inv),
+            old_value: f(&self.old_value),
+            new_value: f(&self.new_value),
+            storage_key: f(&self.storage_key),
+            first_root: f(&self.first_root),
+            root: f(&self.root),
+            s: self.s.map(&mut f),
+            c: self.c.map(&mut f),
+        }
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<&T> {
+        let mut cells = vec![
+            &self.item_rlc,
+            &self.item_pow,
+            &self.path_rlc,
+            &self.path_pow,
+            &self.value,
+            &self.key_full,
+            &self.acc,
+            &self.mult,
+            &self.cur_acc,
+            &self.cur_mult,
+            &self.lock_acc,
+            &self.lock_mult,
+            &self.moved_acc,
+            &self.moved_mult,
+            &self.moved_hash,
+            &self.node_key,
+            &self.node_mult,
+            &self.rest_rlc,
+            &self.other_key,
+            &self.other_mult,
+            &self.other_rest_rlc,
+            &self.inv,
+            &self.old_value,
+            &self.new_value,
+            &self.storage_key,
+            &self.first_root,
+            &self.root,
+        ];
+        cells.extend(self.s.to_vec());
+        cells.extend(self.c.to_vec());
+        cells
+    }
+}
+
+impl<T> SideRlcCells<T> {
+    pub(crate) fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> SideRlcCells<U> {
+        SideRlcCells {
+            item: f(&self.item),
+            item_pow: f(&self.item_pow),
+            node_rlc: f(&self.node_rlc),
+            node_pow: f(&self.node_pow),
+            lookup_rlc: f(&self.lookup_rlc),
+            lookup_hash: f(&self.lookup_hash),
+            hash: f(&self.hash),
+            next: f(&self.next),
+            root_inv: f(&self.root_inv),
+            nonce: f(&self.nonce),
+            balance: f(&self.balance),
+            storage_root: f(&self.storage_root),
+            code_hash: f(&self.code_hash),
+            value: f(&self.value),
+        }
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<&T> {
+        vec![
+            &self.item,
+            &self.item_pow,
+            &self.node_rlc,
+            &self.node_pow,
+            &self.lookup_rlc,
+            &self.lookup_hash,
+            &self.hash,
+            &self.next,
+            &self.root_inv,
+            &self.nonce,
+            &self.balance,
+            &self.storage_root,
+            &self.code_hash,
+            &self.value,
+        ]
+    }
+}
+
+impl<F: Field> MptCells<Expression<F>> {
+    pub(crate) fn tag(&self, tag: Tag) -> Expression<F> {
+        self.tags[tag as usize].clone()
+    }
+
+    fn tags(&self, tags: &[Tag]) -> Expression<F> {
+        sum::expr(tags.iter().map(|tag| self.tag(*tag)))
+    }
+
+    pub(crate) fn is_key(&self) -> Expression<F> {
+        self.tags(&[Tag::Start, Tag::StorageKey])
+    }
+
+    /// The row holds an item of a node
+    pub(crate) fn is_node(&self) -> Expression<F> {
+        self.tags(&[
+            Tag::Branch,
+            Tag::ExtensionPath,
+            Tag::ExtensionChild,
+            Tag::LeafPath,
+            Tag::AccountNonce,
+            Tag::AccountBalance,
+            Tag::AccountStorageRoot,
+            Tag::AccountCodeHash,
+            Tag::StorageValue,
+        ])
+    }
+
+    /// The row is part of a trie walk
+    pub(crate) fn is_walk(&self) -> Expression<F> {
+        self.is_node() + self.tag(Tag::Empty)
+    }
+
+    pub(crate) fn is_path(&self) -> Expression<F> {
+        self.tags(&[Tag::ExtensionPath, Tag::LeafPath])
+    }
+
+    pub(crate) fn is_child(&self) -> Expression<F> {
+        self.tags(&[Tag::Branch, Tag::ExtensionChild])
+    }
+
+    /// The node of the row is on the path of the key
+    pub(crate) fn role_path(&self) -> Expression<F> {
+        1.expr() - self.role_wrong.clone() - self.role_moved.clone()
+    }
+
+    /// The row ends the walk of a trie
+    pub(crate) fn is_terminal(&self) -> Expression<F> {
+        self.tag(Tag::Empty)
+            + self.node_end.clone() * self.role_wrong.clone()
+            + self.tags(&[Tag::AccountCodeHash, Tag::StorageValue]) * self.role_path()
+    }
+
+    /// Length of the content of the item
+    pub(crate) fn len(&self) -> Expression<F> {
+        sum::expr(
+            self.len_flags
+                .iter()
+                .enumerate()
+                .map(|(k, flag)| k.expr() * flag.clone()),
+        )
+    }
+
+    /// Length of the item
+    pub(crate) fn item_len(&self) -> Expression<F> {
+        self.len() + 1.expr() - self.is_short.clone()
+    }
+
+    /// `flags[k] + .. + flags[NUM_ITEM_BYTES - 1]`, 1 when the content has at
+    /// least `k` bytes
+    pub(crate) fn len_at_least(&self, k: usize) -> Expression<F> {
+        sum::expr(self.len_flags.iter().skip(k))
+    }
+
+    /// The low nibble of the content byte `j`, with `1 <= j < NUM_ITEM_BYTES`
+    pub(crate) fn lo(&self, j: usize) -> Expression<F> {
+        self.bytes[j].clone() - 16.expr() * self.hi[j - 1].clone()
+    }
+
+    /// The nibbles of the key hash held in the content bytes of a key row
+    pub(crate) fn key_nibbles(&self) -> Vec<Expression<F>> {
+        (1..=NUM_HASH_BYTES)
+            .flat_map(|j| [self.hi[j - 1].clone(), self.lo(j)])
+            .collect()
+    }
+
+    /// The update is a storage update
+    pub(crate) fn needs_storage(&self) -> Expression<F> {
+        self.proof_flags[4].clone() + self.proof_flags[5].clone()
+    }
+
+    /// The update proves that a key is absent, leaving the tries unchanged
+    pub(crate) fn is_non_existing(&self) -> Expression<F> {
+        self.proof_flags[3].clone() + self.proof_flags[5].clone()
+    }
+}
@@ -0,0 +1,52 @@
+pub use super::MptCircuit;
+
+use crate::{
+    mpt_circuit::{MptCircuitConfig, MptCircuitConfigArgs},
+    table::{KeccakTable, MptTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for MptCircuit<F> {
+    type Config = (MptCircuitConfig<F>, KeccakTable, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mpt_table = MptTable::construct(meta);
+        let keccak_table = KeccakTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            MptCircuitConfig::new(
+                meta,
+                MptCircuitConfigArgs {
+                    mpt_table,
+                    keccak_table: keccak_table.clone(),
+                    challenges,
+                },
+            )
+        };
+        (config, keccak_table, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, keccak_table, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&mut layouter);
+        // The nodes and the keys hashed by the circuit
+        keccak_table.dev_load(&mut layouter, &self.updates.keccak_inputs(), &challenges)?;
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
@@ -0,0 +1,72 @@
+pub(crate) const MAX_DEGREE: usize = 9;
+
+/// Number of bytes of an item: the header and up to 33 bytes of content.  The
+/// longest items are the storage values, a string of 33 bytes holding the RLP
+/// encoding of a word.
+pub(crate) const NUM_ITEM_BYTES: usize = 34;
+/// Number of auxiliary cells of a row, enough for the nibbles of a key.
+pub(crate) const NUM_AUX: usize = 64;
+/// Number of nibbles of a key hash.
+pub(crate) const NUM_KEY_NIBBLES: usize = 64;
+/// Number of bytes of a hash.
+pub(crate) const NUM_HASH_BYTES: usize = 32;
+/// Number of bytes of an address.
+pub(crate) const NUM_ADDRESS_BYTES: usize = 20;
+/// Number of children of a branch.
+pub(crate) const NUM_BRANCH_CHILDREN: usize = 16;
+/// Number of MPT proof types.
+pub(crate) const NUM_PROOF_TYPES: usize = 6;
+
+/// RLP header of an empty string, used for the empty children of a branch and
+/// its empty value.
+pub(crate) const RLP_EMPTY: u64 = 0x80;
+/// RLP header of a 32 bytes string, used for the hashes.
+pub(crate) const RLP_HASH: u64 = 0xa0;
+/// RLP header of a short list.
+pub(crate) const RLP_SHORT_LIST: u64 = 0xc0;
+/// RLP header byte of a long list whose length takes one byte.
+pub(crate) const RLP_LIST_1: u64 = 0xf8;
+/// RLP header byte of a long list whose length takes two bytes.
+pub(crate) const RLP_LIST_2: u64 = 0xf9;
+/// RLP header byte of a long string whose length takes one byte.
+pub(crate) const RLP_STRING_1: u64 = 0xb8;
+/// Maximum length of the payload of a short list.
+pub(crate) const RLP_MAX_SHORT: u64 = 55;
+
+/// The tag of a row.  An update is laid out as a `Start` row, the walk of the
+/// account trie and an `End` row, followed for the storage updates by a
+/// `StorageKey` row, the walk of the storage trie and an `End` row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Tag {
+    /// Unused row
+    Padding,
+    /// First row of an update, holding the MPT table row
+    Start,
+    /// First row of the storage trie walk of a storage update
+    StorageKey,
+    /// A child of a branch
+    Branch,
+    /// The path of an extension
+    ExtensionPath,
+    /// The child of an extension
+    ExtensionChild,
+    /// The path of a leaf
+    LeafPath,
+    /// The nonce of an account leaf
+    AccountNonce,
+    /// The balance of an account leaf
+    AccountBalance,
+    /// The storage root of an account leaf
+    AccountStorageRoot,
+    /// The code hash of an account leaf
+    AccountCodeHash,
+    /// The value of a storage leaf
+    StorageValue,
+    /// The key is absent at an empty child, or in an empty trie
+    Empty,
+    /// Last row of a trie walk
+    End,
+}
+
+/// Number of tags.
+pub(crate) const NUM_TAGS: usize = Tag::End as usize + 1;
@@ -0,0 +1,800 @@
+//! The witness of the MptCircuit: the rows of the walks of the updates, with
+//! the registers carried from row to row.
+use super::{
+    cells::{MptCells, MptRlcCells, SideCells, SideRlcCells},
+    param::*,
+};
+use crate::witness::{
+    hex_prefix, key_nibbles, MptUpdate, MptUpdates, Node, Terminal, TrieProof, EMPTY_ROOT,
+};
+use eth_types::{Field, ToBigEndian, Word, H256};
+use ethers_core::utils::{
+    keccak256,
+    rlp::{self, Rlp},
+};
+use halo2_proofs::plonk::Error;
+
+/// A row of the MptCircuit, with the values of its cells
+#[derive(Clone, Debug)]
+pub(crate) struct MptRow<F> {
+    pub(crate) tag: Tag,
+    pub(crate) cells: MptCells<F>,
+    pub(crate) rlcs: MptRlcCells<F>,
+}
+
+impl<F: Field> MptRow<F> {
+    /// The high and low 128 bits of the root held by the bytes of a root row
+    pub(crate) fn root_word(&self) -> [F; 2] {
+        let half = NUM_HASH_BYTES / 2;
+        [1, 1 + half].map(|start| be_rlc(&self.cells.bytes[start..start + half], F::from(256)))
+    }
+}
+
+/// The role of a leaf or an extension in a walk
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    /// On the path of the key
+    Path,
+    /// Diverging from the key
+    Wrong,
+    /// Moved by the update
+    Moved,
+}
+
+fn be_rlc<F: Field>(values: &[F], base: F) -> F {
+    values.iter().fold(F::ZERO, |acc, value| acc * base + value)
+}
+
+fn le_rlc<F: Field>(values: &[F], base: F) -> F {
+    values
+        .iter()
+        .rev()
+        .fold(F::ZERO, |acc, value| acc * base + value)
+}
+
+fn fields<F: Field>(bytes: &[u8]) -> Vec<F> {
+    bytes.iter().map(|byte| F::from(*byte as u64)).collect()
+}
+
+fn flag<F: Field>(value: bool) -> F {
+    F::from(value as u64)
+}
+
+fn inverse<F: Field>(value: F) -> F {
+    value.invert().unwrap_or(F::ZERO)
+}
+
+fn child_item(child: Option<H256>) -> Vec<u8> {
+    match child {
+        Some(hash) => [&[RLP_HASH as u8][..], hash.as_bytes()].concat(),
+        None => vec![RLP_EMPTY as u8],
+    }
+}
+
+fn side_mut<T>(cells: &mut MptCells<T>, is_new: bool) -> &mut SideCells<T> {
+    if is_new {
+        &mut cells.c
+    } else {
+        &mut cells.s
+    }
+}
+
+fn side_rlc_mut<T>(rlcs: &mut MptRlcCells<T>, is_new: bool) -> &mut SideRlcCells<T> {
+    if is_new {
+        &mut rlcs.c
+    } else {
+        &mut rlcs.s
+    }
+}
+
+/// The rows of the MPT updates, padded to `capacity` rows when given.  The
+/// cells of the second phase are computed with the `keccak_input` and the
+/// `evm_word` randomness.
+pub(crate) fn mpt_rows<F: Field>(
+    updates: &MptUpdates,
+    randomness: F,
+    word_randomness: F,
+    capacity: Option<usize>,
+) -> Result<Vec<MptRow<F>>, Error> {
+    let mut witness = Witness {
+        r: randomness,
+        word_r: word_randomness,
+        empty_root: be_rlc(&fields(EMPTY_ROOT.as_bytes()), word_randomness),
+        old_root: be_rlc(&fields(&updates.old_root().to_be_bytes()), word_randomness),
+        rows: Vec::new(),
+    };
+    for proof in updates.proofs() {
+        witness.start(&proof.update);
+        witness.walk(&proof.account, false);
+        witness.end();
+        if let (Some(storage), Some(key)) = (&proof.storage, proof.update.storage_key()) {
+            witness.storage_key(key);
+            witness.walk(storage, true);
+            witness.end();
+        }
+    }
+    // The last two rows are always padding rows, holding the old root of the
+    // first update and the new root of the last update
+    let num_rows = match capacity {
+        Some(capacity) if witness.rows.len() + 2 > capacity => return Err(Error::BoundsFailure),
+        Some(capacity) => capacity,
+        None => witness.rows.len() + 2,
+    };
+    while witness.rows.len() + 2 < num_rows {
+        witness.padding();
+    }
+    witness.root(updates.old_root());
+    witness.root(updates.new_root());
+    Ok(witness.rows)
+}
+
+struct Witness<F> {
+    r: F,
+    word_r: F,
+    empty_root: F,
+    /// Word RLC of the root before the updates
+    old_root: F,
+    rows: Vec<MptRow<F>>,
+}
+
+impl<F: Field> Witness<F> {
+    /// A new row with the registers of the previous row
+    fn row(&self, tag: Tag) -> MptRow<F> {
+        let mut row = self.rows.last().cloned().unwrap_or_else(|| {
+            let mut rlcs = MptRlcCells::<()>::default().map(|_| F::ZERO);
+            rlcs.first_root = self.old_root;
+            rlcs.root = self.old_root;
+            MptRow {
+                tag,
+                cells: MptCells::<()>::default().map(|_| F::ZERO),
+                rlcs,
+            }
+        });
+        row.tag = tag;
+        let cells = &mut row.cells;
+        for cell in cells
+            .tags
+            .iter_mut()
+            .chain(cells.bytes.iter_mut())
+            .chain(cells.len_flags.iter_mut())
+            .chain(cells.hi.iter_mut())
+            .chain(cells.aux.iter_mut())
+            .chain([
+                &mut cells.is_short,
+                &mut cells.is_odd,
+                &mut cells.lockstep,
+                &mut cells.node_end,
+                &mut cells.node_open,
+                &mut cells.role_wrong,
+                &mut cells.role_moved,
+                &mut cells.is_mod,
+                &mut cells.is_moved,
+                &mut cells.moved_direct,
+            ])
+        {
+            *cell = F::ZERO;
+        }
+        cells.tags[tag as usize] = F::ONE;
+        for side in [&mut cells.s, &mut cells.c] {
+            for cell in side.hdr.iter_mut().chain([
+                &mut side.on,
+                &mut side.empty,
+                &mut side.item_len,
+                &mut side.hdr_hi,
+                &mut side.lookup_on,
+                &mut side.lookup_len,
+            ]) {
+                *cell = F::ZERO;
+            }
+        }
+        let rlcs = &mut row.rlcs;
+        for cell in [
+            &mut rlcs.item_rlc,
+            &mut rlcs.item_pow,
+            &mut rlcs.path_rlc,
+            &mut rlcs.path_pow,
+            &mut rlcs.value,
+            &mut rlcs.inv,
+        ] {
+            *cell = F::ZERO;
+        }
+        for side in [&mut rlcs.s, &mut rlcs.c] {
+            for cell in [
+                &mut side.item,
+                &mut side.item_pow,
+                &mut side.lookup_rlc,
+                &mut side.lookup_hash,
+                &mut side.root_inv,
+            ] {
+                *cell = F::ZERO;
+            }
+        }
+        row
+    }
+
+    fn prev(&self) -> &MptRow<F> {
+        self.rows.last().expect("a walk follows a key row")
+    }
+
+    fn pow(&self, exponent: usize) -> F {
+        self.r.pow_vartime([exponent as u64])
+    }
+
+    /// The key row of a walk: the hash of the key and the roots of the trie
+    fn key_row(&mut self, mut row: MptRow<F>, preimage: &[u8], roots: [F; 2]) {
+        let hash = H256(keccak256(preimage));
+        row.cells.stage = F::ZERO;
+        row.cells.moved_pending = F::ZERO;
+        self.set_bytes(&mut row.cells, &[&[0], hash.as_bytes()].concat());
+        for (aux, byte) in row.cells.aux.iter_mut().zip(preimage) {
+            *aux = F::from(*byte as u64);
+        }
+        row.cells.s.lookup_on = F::ONE;
+        row.cells.s.lookup_len = F::from(preimage.len() as u64);
+        row.rlcs.s.lookup_rlc = be_rlc(&fields(preimage), self.r);
+        row.rlcs.s.lookup_hash = be_rlc(&fields(hash.as_bytes()), self.word_r);
+        row.rlcs.key_full = le_rlc(&fields(&key_nibbles(&hash)), self.r);
+        row.rlcs.acc = F::ZERO;
+        row.rlcs.mult = F::ONE;
+        for (is_new, root) in [false, true].into_iter().zip(roots) {
+            let side = side_mut(&mut row.cells, is_new);
+            let side_rlc = side_rlc_mut(&mut row.rlcs, is_new);
+            let root_empty = root == self.empty_root;
+            side_rlc.hash = root;
+            side_rlc.root_inv = inverse(root - self.empty_root);
+            side.root_empty = flag(root_empty);
+            side.hash_empty = flag(root_empty);
+            side.count = F::from(2);
+            side.wrong = F::ZERO;
+            side.split = F::ZERO;
+            side.expect_branch = F::ZERO;
+            side_rlc.nonce = F::ZERO;
+            side_rlc.balance = F::ZERO;
+            side_rlc.storage_root = self.empty_root;
+            side_rlc.code_hash = F::ZERO;
+            side_rlc.value = F::ZERO;
+        }
+        self.rows.push(row);
+    }
+
+    fn start(&mut self, update: &MptUpdate) {
+        let mut row = self.row(Tag::Start);
+        let proof_type = update.mpt_proof_type() as usize;
+        for (idx, proof_flag) in row.cells.proof_flags.iter_mut().enumerate() {
+            *proof_flag = flag(idx + 1 == proof_type);
+        }
+        row.cells.in_storage = F::ZERO;
+        let (new_value, old_value) = update.value_assignments(self.word_r);
+        row.rlcs.old_value = old_value;
+        row.rlcs.new_value = new_value;
+        row.rlcs.storage_key = update
+            .storage_key()
+            .map(|key| be_rlc(&fields(&key.to_be_bytes()), self.word_r))
+            .unwrap_or(F::ZERO);
+        let (new_root, old_root) = update.root_assignments(self.word_r);
+        row.rlcs.root = new_root;
+        self.key_row(row, update.address().as_bytes(), [old_root, new_root]);
+    }
+
+    fn storage_key(&mut self, key: Word) {
+        let mut row = self.row(Tag::StorageKey);
+        row.cells.in_storage = F::ONE;
+        let roots = [row.rlcs.s.storage_root, row.rlcs.c.storage_root];
+        self.key_row(row, &key.to_be_bytes(), roots);
+    }
+
+    fn end(&mut self) {
+        let mut row = self.row(Tag::End);
+        row.cells.stage = F::from(3);
+        row.cells.moved_pending = F::ZERO;
+        self.rows.push(row);
+    }
+
+    fn padding(&mut self) {
+        let mut row = self.row(Tag::Padding);
+        row.cells.stage = F::from(3);
+        self.rows.push(row);
+    }
+
+    /// A padding row holding the bytes of a root
+    fn root(&mut self, root: Word) {
+        let mut row = self.row(Tag::Padding);
+        row.cells.stage = F::from(3);
+        self.set_bytes(&mut row.cells, &[&[0], &root.to_be_bytes()[..]].concat());
+        self.rows.push(row);
+    }
+
+    /// The rows of the walk of a trie, from the lockstep nodes to the terminal
+    /// of each side.
+    fn walk(&mut self, proof: &TrieProof, in_storage: bool) {
+        let key = key_nibbles(&proof.key);
+        let mut depth = 0;
+        for (old, new) in &proof.common {
+            match (old, new) {
+                (
+                    Node::Branch { children },
+                    Node::Branch {
+                        children: new_children,
+                    },
+                ) => {
+                    self.branch(None, children, Some(new_children), key[depth], None);
+                    depth += 1;
+                }
+                (
+                    Node::Extension { path, child },
+                    Node::Extension {
+                        child: new_child, ..
+                    },
+                ) => {
+                    self.extension(None, Role::Path, path, *child, Some(*new_child), &[]);
+                    depth += path.len();
+                }
+                _ => unreachable!("the lockstep nodes are branches or extensions"),
+            }
+        }
+        for (is_new, terminal) in [false, true].into_iter().zip(&proof.terminals) {
+            let side = Some(is_new);
+            let mut key = &key[depth..];
+            match terminal {
+                Terminal::Empty => self.empty(is_new, key),
+                Terminal::Leaf(node) => self.node(side, Role::Path, node, key, in_storage),
+                Terminal::Wrong(node) => self.node(side, Role::Wrong, node, key, in_storage),
+                Terminal::Split {
+                    extension,
+                    branch,
+                    moved,
+                    leaf,
+                } => {
+                    if let Some(Node::Extension { path, child }) = extension {
+                        self.extension(side, Role::Path, path, *child, None, key);
+                        key = &key[path.len()..];
+                    }
+                    let children = match branch {
+                        Node::Branch { children } => children,
+                        _ => unreachable!("a split node is a branch"),
+                    };
+                    let moved_idx = (0..NUM_BRANCH_CHILDREN)
+                        .find(|idx| *idx != key[0] as usize && children[*idx].is_some())
+                        .expect("a split branch has two children");
+                    self.branch(
+                        side,
+                        children,
+                        None,
+                        key[0],
+                        Some((moved_idx, moved.is_none())),
+                    );
+                    if let Some(moved) = moved {
+                        self.node(side, Role::Moved, moved, &[], in_storage);
+                    }
+                    self.node(side, Role::Path, leaf, &key[1..], in_storage);
+                }
+            }
+        }
+    }
+
+    fn node(&mut self, side: Option<bool>, role: Role, node: &Node, key: &[u8], in_storage: bool) {
+        match node {
+            Node::Leaf { path, value } => self.leaf(side, role, path, value, key, in_storage),
+            Node::Extension { path, child } => self.extension(side, role, path, *child, None, key),
+            Node::Branch { .. } => unreachable!("a terminal node is a leaf or an extension"),
+        }
+    }
+
+    /// A row of a walk on one side, or on both sides in lockstep for `None`
+    fn walk_row(&self, tag: Tag, side: Option<bool>, role: Role) -> MptRow<F> {
+        let mut row = self.row(tag);
+        row.cells.s.on = flag(side != Some(true));
+        row.cells.c.on = flag(side != Some(false));
+        row.cells.lockstep = flag(side.is_none());
+        row.cells.role_wrong = flag(role == Role::Wrong);
+        row.cells.role_moved = flag(role == Role::Moved);
+        row
+    }
+
+    /// Set the content bytes of a row, with their high nibbles
+    fn set_bytes(&self, cells: &mut MptCells<F>, bytes: &[u8]) {
+        for (j, byte) in bytes.iter().enumerate() {
+            cells.bytes[j] = F::from(*byte as u64);
+            if j > 0 {
+                cells.hi[j - 1] = F::from((*byte >> 4) as u64);
+            }
+        }
+    }
+
+    /// Set the RLP item of a row
+    fn set_item(&self, row: &mut MptRow<F>, item: &[u8]) {
+        let is_short = item.len() == 1 && item[0] < RLP_EMPTY as u8;
+        let bytes = if is_short {
+            vec![0, item[0]]
+        } else {
+            item.to_vec()
+        };
+        let len = bytes.len() - 1;
+        self.set_bytes(&mut row.cells, &bytes);
+        row.cells.len_flags[len] = F::ONE;
+        row.cells.is_short = flag(is_short);
+        row.rlcs.item_rlc = be_rlc(&fields(&bytes), self.r);
+        row.rlcs.item_pow = self.pow(len + 1 - is_short as usize);
+    }
+
+    /// Set the path item of a path row.  A diverging node gets the nibbles of
+    /// the key left to walk.
+    fn set_path(&self, row: &mut MptRow<F>, path: &[u8], is_leaf: bool, key: &[u8]) {
+        self.set_item(row, &rlp::encode(&hex_prefix(path, is_leaf)));
+        row.cells.is_odd = flag(path.len() % 2 == 1);
+        row.rlcs.path_rlc = le_rlc(&fields(path), self.r);
+        row.rlcs.path_pow = self.pow(path.len());
+        if row.cells.role_wrong == F::ONE {
+            self.set_key(row, key);
+            let head = le_rlc(&fields(&key[..path.len().min(key.len())]), self.r);
+            row.rlcs.inv = inverse(head - row.rlcs.path_rlc);
+        }
+    }
+
+    fn set_key(&self, row: &mut MptRow<F>, key: &[u8]) {
+        for (aux, nibble) in row.cells.aux.iter_mut().zip(key) {
+            *aux = F::from(*nibble as u64);
+        }
+    }
+
+    /// The new child of a lockstep row, in `aux`
+    fn set_new_child(&self, row: &mut MptRow<F>, child: Option<H256>) {
+        if let Some(child) = child {
+            for (aux, byte) in row.cells.aux.iter_mut().zip(child.as_bytes()) {
+                *aux = F::from(*byte as u64);
+            }
+        }
+        row.cells.c.empty = flag(child.is_none());
+    }
+
+    fn branch(
+        &mut self,
+        side: Option<bool>,
+        children: &[Option<H256>; NUM_BRANCH_CHILDREN],
+        new_children: Option<&[Option<H256>; NUM_BRANCH_CHILDREN]>,
+        mod_idx: u8,
+        moved: Option<(usize, bool)>,
+    ) {
+        for (idx, child) in children.iter().enumerate() {
+            let mut row = self.walk_row(Tag::Branch, side, Role::Path);
+            self.set_item(&mut row, &child_item(*child));
+            row.cells.idx = F::from(idx as u64);
+            row.cells.node_end = flag(idx == NUM_BRANCH_CHILDREN - 1);
+            row.cells.is_mod = flag(idx == mod_idx as usize);
+            if let Some((moved_idx, direct)) = moved {
+                row.cells.is_moved = flag(idx == moved_idx);
+                row.cells.moved_direct = flag(idx == moved_idx && direct);
+            }
+            match new_children {
+                Some(new_children) => {
+                    row.cells.s.empty = flag(child.is_none());
+                    self.set_new_child(&mut row, new_children[idx]);
+                }
+                None => side_mut(&mut row.cells, side == Some(true)).empty = flag(child.is_none()),
+            }
+            self.push_walk(row);
+        }
+    }
+
+    fn extension(
+        &mut self,
+        side: Option<bool>,
+        role: Role,
+        path: &[u8],
+        child: H256,
+        new_child: Option<H256>,
+        key: &[u8],
+    ) {
+        let mut row = self.walk_row(Tag::ExtensionPath, side, role);
+        self.set_path(&mut row, path, false, key);
+        self.push_walk(row);
+
+        let mut row = self.walk_row(Tag::ExtensionChild, side, role);
+        self.set_item(&mut row, &child_item(Some(child)));
+        row.cells.node_end = F::ONE;
+        if side.is_none() {
+            self.set_new_child(&mut row, new_child);
+        }
+        self.push_walk(row);
+    }
+
+    fn leaf(
+        &mut self,
+        side: Option<bool>,
+        role: Role,
+        path: &[u8],
+        value: &[u8],
+        key: &[u8],
+        in_storage: bool,
+    ) {
+        let mut row = self.walk_row(Tag::LeafPath, side, role);
+        self.set_path(&mut row, path, true, key);
+        self.push_walk(row);
+
+        if in_storage {
+            let mut row = self.walk_row(Tag::StorageValue, side, role);
+            self.set_item(&mut row, &rlp::encode(&value.to_vec()));
+            row.cells.node_end = F::ONE;
+            let word = Rlp::new(value).data().expect("a storage leaf holds a word");
+            row.rlcs.value = be_rlc(&fields(word), self.word_r);
+            if row.cells.is_short == F::ZERO {
+                row.rlcs.inv = inverse(row.cells.bytes[2]);
+            }
+            self.push_walk(row);
+            return;
+        }
+        let account = Rlp::new(value);
+        let items: Vec<&[u8]> = (0..4)
+            .map(|idx| {
+                account
+                    .at(idx)
+                    .expect("an account leaf holds 4 fields")
+                    .as_raw()
+            })
+            .collect();
+        let account_len: usize = items.iter().map(|item| item.len()).sum();
+        let mut value_len = 0;
+        for (tag, item) in [
+            Tag::AccountNonce,
+            Tag::AccountBalance,
+            Tag::AccountStorageRoot,
+            Tag::AccountCodeHash,
+        ]
+        .into_iter()
+        .zip(items)
+        {
+            let mut row = self.walk_row(tag, side, role);
+            self.set_item(&mut row, item);
+            row.cells.node_end = flag(tag == Tag::AccountCodeHash);
+            value_len += item.len();
+            row.cells.account_len = F::from(account_len as u64);
+            row.cells.value_len = F::from(value_len as u64);
+            let content = Rlp::new(item).data().expect("an account field is a string");
+            let base = if tag == Tag::AccountNonce {
+                F::from(256)
+            } else {
+                self.word_r
+            };
+            row.rlcs.value = be_rlc(&fields(content), base);
+            if matches!(tag, Tag::AccountNonce | Tag::AccountBalance) && !content.is_empty() {
+                row.rlcs.inv = inverse(row.cells.bytes[1]);
+            }
+            self.push_walk(row);
+        }
+    }
+
+    fn empty(&mut self, is_new: bool, key: &[u8]) {
+        let mut row = self.walk_row(Tag::Empty, Some(is_new), Role::Path);
+        self.set_key(&mut row, key);
+        self.push_walk(row);
+    }
+
+    /// Compute the registers of a walk row from the previous row and push it
+    fn push_walk(&mut self, mut row: MptRow<F>) {
+        let (r, word_r) = (self.r, self.word_r);
+        let prev = self.prev().clone();
+        let (p, p_rlc) = (&prev.cells, &prev.rlcs);
+        let tag = row.tag;
+        let is_node = tag != Tag::Empty;
+        let is_branch = tag == Tag::Branch;
+        let is_path = matches!(tag, Tag::ExtensionPath | Tag::LeafPath);
+        let is_child = matches!(tag, Tag::Branch | Tag::ExtensionChild);
+        let one = F::ONE;
+
+        let cells = &mut row.cells;
+        let rlcs = &mut row.rlcs;
+        cells.stage = cells.s.on + F::from(2) * cells.c.on - F::from(3) * cells.lockstep;
+        cells.node_open = flag::<F>(is_node) * (one - cells.node_end);
+        let role_path = one - cells.role_wrong - cells.role_moved;
+        let stage_delta = cells.stage - p.stage;
+        let old_start = stage_delta * cells.s.on;
+        let new_start = stage_delta * cells.c.on;
+        let open = p.node_open;
+        let branch_end = flag::<F>(is_branch) * cells.node_end;
+        let path_extension = flag::<F>(tag == Tag::ExtensionPath) * role_path;
+        let bytes_word = be_rlc(&cells.bytes[1..=NUM_HASH_BYTES], word_r);
+        let aux_word = be_rlc(&cells.aux[..NUM_HASH_BYTES], word_r);
+
+        // The branches
+        if is_branch {
+            cells.mod_seen = open * p.mod_seen + cells.is_mod;
+            cells.moved_seen = open * p.moved_seen + cells.is_moved;
+            cells.mod_idx = open * p.mod_idx + cells.is_mod * cells.idx;
+        }
+
+        // The walk of the key
+        rlcs.cur_acc = new_start * p_rlc.lock_acc + (one - new_start) * p_rlc.acc;
+        rlcs.cur_mult = new_start * p_rlc.lock_mult + (one - new_start) * p_rlc.mult;
+        rlcs.lock_acc = old_start * rlcs.cur_acc + (one - old_start) * p_rlc.lock_acc;
+        rlcs.lock_mult = old_start * rlcs.cur_mult + (one - old_start) * p_rlc.lock_mult;
+        rlcs.acc = rlcs.cur_acc
+            + branch_end * rlcs.cur_mult * cells.mod_idx
+            + path_extension * rlcs.cur_mult * rlcs.path_rlc;
+        rlcs.mult =
+            rlcs.cur_mult * (one + branch_end * (r - one) + path_extension * (rlcs.path_pow - one));
+
+        // The moved child of a split branch
+        if cells.is_moved == one {
+            rlcs.moved_acc = rlcs.cur_acc + rlcs.cur_mult * cells.idx;
+            rlcs.moved_mult = rlcs.cur_mult * r;
+            rlcs.moved_hash = bytes_word;
+        }
+        let moved_start = cells.role_moved * flag::<F>(is_path);
+        cells.moved_pending = cells.is_moved * (one - cells.moved_direct)
+            + (one - cells.is_moved - moved_start) * p.moved_pending;
+
+        // The items and the nodes of the sides
+        let len = cells
+            .len_flags
+            .iter()
+            .enumerate()
+            .fold(F::ZERO, |acc, (k, flag)| acc + F::from(k as u64) * flag);
+        let generic = (rlcs.item_rlc, rlcs.item_pow, len + one - cells.is_short);
+        let account_len = cells.account_len;
+        let account_header = F::from(RLP_STRING_1) * self.pow(3)
+            + (account_len + F::from(2)) * r * r
+            + F::from(RLP_LIST_1) * r
+            + account_len;
+        for is_new in [false, true] {
+            let prev_side = if is_new { &p.c } else { &p.s };
+            let prev_side_rlc = if is_new { &p_rlc.c } else { &p_rlc.s };
+            let mut side = if is_new {
+                cells.c.clone()
+            } else {
+                cells.s.clone()
+            };
+            let mut side_rlc = if is_new {
+                rlcs.c.clone()
+            } else {
+                rlcs.s.clone()
+            };
+            if side.on == F::ZERO {
+                continue;
+            }
+            let (item, item_pow, item_len) = if is_new && cells.lockstep == one && is_child {
+                let hash_item =
+                    F::from(RLP_HASH) * self.pow(NUM_HASH_BYTES) + be_rlc(&cells.aux[..32], r);
+                let full_pow = self.pow(NUM_HASH_BYTES + 1);
+                (
+                    hash_item
+                        - side.empty
+                            * (F::from(RLP_HASH) * self.pow(NUM_HASH_BYTES) - F::from(RLP_EMPTY)),
+                    full_pow - side.empty * (full_pow - r),
+                    F::from(NUM_HASH_BYTES as u64 + 1)
+                        - F::from(NUM_HASH_BYTES as u64) * side.empty,
+                )
+            } else {
+                generic
+            };
+            if branch_end == one {
+                side_rlc.item = item * r + F::from(RLP_EMPTY);
+                side_rlc.item_pow = item_pow * r;
+                side.item_len = item_len + one;
+            } else if tag == Tag::AccountNonce {
+                side_rlc.item = account_header * generic.1 + generic.0;
+                side_rlc.item_pow = generic.1 * self.pow(4);
+                side.item_len = generic.2 + F::from(4);
+            } else if is_node {
+                side_rlc.item = item;
+                side_rlc.item_pow = item_pow;
+                side.item_len = item_len;
+            }
+            if is_node {
+                side_rlc.node_rlc =
+                    open * prev_side_rlc.node_rlc * side_rlc.item_pow + side_rlc.item;
+                side_rlc.node_pow =
+                    (open * prev_side_rlc.node_pow + one - open) * side_rlc.item_pow;
+                side.node_len = open * prev_side.node_len + side.item_len;
+            }
+            if is_node && cells.node_end == one {
+                let node_len = side.node_len.get_lower_32() as u64;
+                let (header_rlc, header_len) = if node_len <= RLP_MAX_SHORT {
+                    side.hdr[0] = one;
+                    (F::from(RLP_SHORT_LIST + node_len), 1)
+                } else if node_len < 256 {
+                    side.hdr[1] = one;
+                    (F::from(RLP_LIST_1) * r + F::from(node_len), 2)
+                } else {
+                    side.hdr[2] = one;
+                    side.hdr_hi = F::from(node_len >> 8);
+                    (
+                        F::from(RLP_LIST_2) * r * r
+                            + F::from(node_len >> 8) * r
+                            + F::from(node_len & 0xff),
+                        3,
+                    )
+                };
+                side.lookup_on = one;
+                side.lookup_len = F::from(header_len + node_len);
+                side_rlc.lookup_rlc = header_rlc * side_rlc.node_pow + side_rlc.node_rlc;
+                side_rlc.lookup_hash = cells.role_moved * rlcs.moved_hash
+                    + (one - cells.role_moved) * prev_side_rlc.hash;
+            }
+
+            // The registers of the side
+            let child_word = if is_new && cells.lockstep == one {
+                aux_word
+            } else {
+                bytes_word
+            };
+            if (is_branch && cells.is_mod == one) || tag == Tag::ExtensionChild {
+                side_rlc.next = child_word;
+                side.next_empty = side.empty;
+            }
+            if is_child && cells.node_end == one && role_path == one {
+                side_rlc.hash = side_rlc.next;
+                side.hash_empty = side.next_empty;
+            }
+            side.wrong = prev_side.wrong + cells.node_end * cells.role_wrong;
+            side.split = prev_side.split + branch_end * (one - cells.lockstep);
+            if is_branch {
+                side.count = open * prev_side.count + one - side.empty;
+            }
+            side.expect_branch = flag::<F>(tag == Tag::ExtensionChild) * role_path;
+            if role_path == one {
+                match tag {
+                    Tag::AccountNonce => side_rlc.nonce = rlcs.value,
+                    Tag::AccountBalance => side_rlc.balance = rlcs.value,
+                    Tag::AccountStorageRoot => side_rlc.storage_root = rlcs.value,
+                    Tag::AccountCodeHash => side_rlc.code_hash = rlcs.value,
+                    Tag::StorageValue => side_rlc.value = rlcs.value,
+                    _ => {}
+                }
+            }
+            if tag == Tag::Empty {
+                rlcs.inv = inverse(side.count * (side.count - one));
+            }
+            *side_mut(cells, is_new) = side;
+            *side_rlc_mut(rlcs, is_new) = side_rlc;
+        }
+
+        // A leaf of the key isn't the empty account
+        if tag == Tag::AccountCodeHash && role_path == one {
+            let fields = [(&cells.s, &rlcs.s), (&cells.c, &rlcs.c)].into_iter().fold(
+                F::ZERO,
+                |acc, (side, side_rlc)| {
+                    acc + side.on
+                        * (side_rlc.nonce
+                            + r * side_rlc.balance
+                            + r * r * (side_rlc.storage_root - self.empty_root)
+                            + r * r * r * side_rlc.code_hash)
+                },
+            );
+            rlcs.inv = inverse(fields);
+        }
+
+        // The key of the node and its items after the path
+        if is_path {
+            let (base_acc, base_mult) = if cells.role_moved == one {
+                (rlcs.moved_acc, rlcs.moved_mult)
+            } else {
+                (rlcs.cur_acc, rlcs.cur_mult)
+            };
+            rlcs.node_key = base_acc + base_mult * rlcs.path_rlc;
+            rlcs.node_mult = base_mult * rlcs.path_pow;
+            rlcs.rest_rlc = F::ZERO;
+            cells.rest_len = F::ZERO;
+        } else if is_node {
+            let item_pow = cells.s.on * rlcs.s.item_pow + cells.c.on * rlcs.c.item_pow;
+            let item = cells.s.on * rlcs.s.item + cells.c.on * rlcs.c.item;
+            let item_len = cells.s.on * cells.s.item_len + cells.c.on * cells.c.item_len;
+            rlcs.rest_rlc = p_rlc.rest_rlc * item_pow + item;
+            cells.rest_len = p.rest_len + item_len;
+        }
+        if cells.s.on == one {
+            if cells.node_end * (cells.role_wrong + cells.role_moved) == one {
+                rlcs.other_key = rlcs.node_key;
+                rlcs.other_mult = rlcs.node_mult;
+                rlcs.other_rest_rlc = rlcs.rest_rlc;
+                cells.other_rest_len = cells.rest_len;
+            }
+            if cells.is_moved * cells.moved_direct == one {
+                rlcs.other_key = rlcs.cur_acc + rlcs.cur_mult * cells.idx;
+                rlcs.other_mult = rlcs.cur_mult * r;
+                rlcs.other_rest_rlc = rlcs.item_rlc;
+                cells.other_rest_len = F::from(NUM_HASH_BYTES as u64 + 1);
+            }
+        }
+        self.rows.push(row);
+    }
+}
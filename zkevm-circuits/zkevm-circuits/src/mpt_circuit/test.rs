@@ -0,0 +1,242 @@
+use super::*;
+use crate::{
+    table::AccountFieldTag,
+    util::unusable_rows,
+    witness::{key_nibbles, Node, Rw, RwMap},
+};
+use eth_types::{Address, EIP1186ProofResponse, ToBigEndian, Word, H256};
+use ethers_core::utils::{keccak256, rlp::RlpStream};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use log::error;
+use std::collections::HashMap;
+
+#[test]
+fn mpt_circuit_unusable_rows() {
+    assert_eq!(
+        MptCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, MptCircuit::<Fr>>(()),
+    )
+}
+
+fn account(
+    rw_counter: usize,
+    address: u64,
+    field_tag: AccountFieldTag,
+    value_prev: u64,
+    value: u64,
+) -> Rw {
+    Rw::Account {
+        rw_counter,
+        is_write: value != value_prev,
+        account_address: Address::from_low_u64_be(address),
+        field_tag,
+        value: Word::from(value),
+        value_prev: Word::from(value_prev),
+    }
+}
+
+fn storage(rw_counter: usize, address: u64, key: u64, value_prev: u64, value: u64) -> Rw {
+    Rw::AccountStorage {
+        rw_counter,
+        is_write: value != value_prev,
+        account_address: Address::from_low_u64_be(address),
+        storage_key: Word::from(key),
+        value: Word::from(value),
+        value_prev: Word::from(value_prev),
+        tx_id: 1,
+        committed_value: Word::from(value_prev),
+    }
+}
+
+fn table_assignments(rws: Vec<Rw>) -> Vec<Rw> {
+    let mut rw_map = HashMap::new();
+    for rw in rws {
+        rw_map.entry(rw.tag()).or_insert_with(Vec::new).push(rw);
+    }
+    RwMap(rw_map).table_assignments()
+}
+
+/// The updates of the rws, on the trie built from the values read by them
+fn updates(rws: Vec<Rw>) -> MptUpdates {
+    MptUpdates::from_rws_genesis(&table_assignments(rws)).unwrap()
+}
+
+/// The `eth_getProof` proof of a funded account alone in the state trie, and
+/// the root of the trie, which is the leaf of the account
+fn single_account_proof(address: u64, balance: u64) -> (EIP1186ProofResponse, H256) {
+    let address = Address::from_low_u64_be(address);
+    let mut account = RlpStream::new_list(4);
+    account
+        .append(&Word::zero())
+        .append(&Word::from(balance))
+        .append(&EMPTY_ROOT)
+        .append(&H256::zero());
+    let leaf = Node::Leaf {
+        path: key_nibbles(&H256(keccak256(address.as_bytes()))),
+        value: account.out().to_vec(),
+    }
+    .encode();
+    let root = H256(keccak256(&leaf));
+    let proof = EIP1186ProofResponse {
+        address,
+        account_proof: vec![leaf.into()],
+        ..Default::default()
+    };
+    (proof, root)
+}
+
+fn verify<F: Field>(k: u32, rws: Vec<Rw>, success: bool) {
+    let circuit = MptCircuit::<F>::new(2usize.pow(k), updates(rws));
+    verify_with_instance(k, &circuit, circuit.instance(), success);
+}
+
+fn verify_with_instance<F: Field>(
+    k: u32,
+    circuit: &MptCircuit<F>,
+    instance: Vec<Vec<F>>,
+    success: bool,
+) {
+    let prover = MockProver::<F>::run(k, circuit, instance).unwrap();
+    let verify_result = prover.verify();
+    if verify_result.is_ok() != success {
+        if let Some(errors) = verify_result.err() {
+            for error in errors.iter() {
+                error!("{}", error);
+            }
+        }
+        panic!();
+    }
+}
+
+/// Accounts with a balance, to fill the account trie with branches
+fn funded_accounts(n: u64) -> Vec<Rw> {
+    (1..=n)
+        .map(|address| account(address as usize, address, AccountFieldTag::Balance, 10, 10))
+        .collect()
+}
+
+#[test]
+fn mpt_circuit_empty() {
+    verify::<Fr>(10, vec![], true);
+}
+
+#[test]
+fn mpt_circuit_update_balance() {
+    let mut rws = funded_accounts(4);
+    rws.push(account(5, 2, AccountFieldTag::Balance, 10, 25));
+    rws.push(account(6, 3, AccountFieldTag::Nonce, 0, 1));
+    verify::<Fr>(12, rws, true);
+}
+
+#[test]
+fn mpt_circuit_insert_account() {
+    let mut rws = funded_accounts(4);
+    rws.push(account(5, 7, AccountFieldTag::Balance, 0, 3));
+    verify::<Fr>(12, rws, true);
+}
+
+#[test]
+fn mpt_circuit_insert_into_empty_trie() {
+    verify::<Fr>(
+        11,
+        vec![account(1, 1, AccountFieldTag::Balance, 0, 3)],
+        true,
+    );
+}
+
+#[test]
+fn mpt_circuit_delete_account() {
+    let mut rws = funded_accounts(4);
+    rws.push(account(5, 3, AccountFieldTag::Balance, 10, 0));
+    verify::<Fr>(12, rws, true);
+}
+
+#[test]
+fn mpt_circuit_non_existing_account() {
+    let mut rws = funded_accounts(4);
+    rws.push(account(5, 9, AccountFieldTag::CodeHash, 0, 0));
+    verify::<Fr>(12, rws, true);
+}
+
+#[test]
+fn mpt_circuit_storage() {
+    let mut rws = funded_accounts(2);
+    rws.extend([
+        storage(3, 1, 1, 5, 5),
+        storage(4, 1, 2, 6, 6),
+        // update
+        storage(5, 1, 1, 5, 8),
+        // insert
+        storage(6, 1, 3, 0, 9),
+        // delete
+        storage(7, 1, 2, 6, 0),
+        // the first slot of an account
+        storage(8, 2, 1, 0, 4),
+    ]);
+    verify::<Fr>(13, rws, true);
+}
+
+#[test]
+fn mpt_circuit_non_existing_storage() {
+    let mut rws = funded_accounts(2);
+    rws.extend([storage(3, 1, 1, 5, 5), storage(4, 1, 2, 0, 0)]);
+    verify::<Fr>(12, rws, true);
+}
+
+#[test]
+fn mpt_circuit_wrong_roots() {
+    let mut rws = funded_accounts(4);
+    rws.push(account(5, 2, AccountFieldTag::Balance, 10, 25));
+    rws.push(account(6, 3, AccountFieldTag::Nonce, 0, 1));
+    let circuit = MptCircuit::<Fr>::new(2usize.pow(12), updates(rws));
+    // The old root, then the new root, as their high and low 128 bits
+    for idx in 0..4 {
+        let mut instance = circuit.instance();
+        instance[0][idx] += Fr::ONE;
+        verify_with_instance(12, &circuit, instance, false);
+    }
+}
+
+#[test]
+fn mpt_circuit_empty_wrong_roots() {
+    let circuit = MptCircuit::<Fr>::new(2usize.pow(10), updates(vec![]));
+    let mut instance = circuit.instance();
+    // Without updates, the new root is the old root
+    instance[0][3] += Fr::ONE;
+    verify_with_instance(10, &circuit, instance, false);
+}
+
+#[test]
+fn mpt_circuit_state_proofs() {
+    let (proof, prev_state_root) = single_account_proof(1, 10);
+    let rws = table_assignments(vec![account(1, 1, AccountFieldTag::Balance, 10, 25)]);
+    // The trie built from the rws holds the same account
+    let genesis = MptUpdates::from_rws_genesis(&rws).unwrap();
+    assert_eq!(H256(genesis.old_root().to_be_bytes()), prev_state_root);
+    let state_root = H256(genesis.new_root().to_be_bytes());
+
+    let updates =
+        MptUpdates::from_rws_with_proofs(&rws, &[proof.clone()], prev_state_root, state_root)
+            .unwrap();
+    let circuit = MptCircuit::<Fr>::new(2usize.pow(11), updates);
+    verify_with_instance(11, &circuit, circuit.instance(), true);
+
+    // The proofs are required
+    assert!(MptUpdates::from_rws_with_proofs(&rws, &[], prev_state_root, state_root).is_err());
+    // The proofs must be against the previous state root
+    assert!(
+        MptUpdates::from_rws_with_proofs(&rws, &[proof.clone()], state_root, state_root).is_err()
+    );
+    // The updates must end at the state root of the block
+    assert!(
+        MptUpdates::from_rws_with_proofs(&rws, &[proof], prev_state_root, prev_state_root).is_err()
+    );
+}
+
+#[test]
+fn mpt_circuit_too_many_rows() {
+    let mut rws = funded_accounts(4);
+    rws.push(account(5, 2, AccountFieldTag::Balance, 10, 25));
+    let circuit = MptCircuit::<Fr>::new(64, updates(rws));
+    assert!(MockProver::<Fr>::run(12, &circuit, vec![]).is_err());
+}
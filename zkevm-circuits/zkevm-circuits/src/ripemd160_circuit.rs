@@ -0,0 +1,1102 @@
+//! The RIPEMD-160 circuit implementation.
+//!
+//! Each 64-byte block of the padded input is processed in
+//! [`ROWS_PER_BLOCK`](param::ROWS_PER_BLOCK) rows: 5 rows holding the state at
+//! the start of the block, one row per round of both lines and 5 rows
+//! combining the state at the end of both lines with the state at the start.
+//! A line only stores the word computed by each round, the other words of its
+//! state are the words computed by the previous rounds, rotated when
+//! necessary.  Every word is stored as 32 boolean cells so that the bitwise
+//! functions are simple expressions over the bits.  The message words are
+//! absorbed in the 16 first rounds, where their bytes are checked against the
+//! padding rules and accumulated into the input RLC, and are copied to the
+//! rounds using them.  The last row of the final block of an input exposes the
+//! hash in the [`Ripemd160Table`].
+mod param;
+mod util;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::Ripemd160Circuit as TestRipemd160Circuit;
+
+use self::{param::*, util::*};
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+        rlc,
+    },
+    table::{LookupTable, Ripemd160Table},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use eth_types::Field;
+use gadgets::util::{not, select, sum, Expr};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// Ripemd160CircuitConfig
+#[derive(Clone, Debug)]
+pub struct Ripemd160CircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_block_start: Column<Fixed>,
+    q_start: Column<Fixed>,
+    q_input: Column<Fixed>,
+    q_last_data_word: Column<Fixed>,
+    q_length: Column<Fixed>,
+    q_length_last: Column<Fixed>,
+    q_round: Column<Fixed>,
+    q_functions: [Column<Fixed>; NUM_FUNCTIONS],
+    q_rotations_left: [Column<Fixed>; NUM_ROUND_ROTATIONS],
+    q_rotations_right: [Column<Fixed>; NUM_ROUND_ROTATIONS],
+    q_ends: [Column<Fixed>; NUM_END_ROWS],
+    q_squeeze: Column<Fixed>,
+    k_left: Column<Fixed>,
+    k_right: Column<Fixed>,
+    h: Column<Fixed>,
+    word_x: [Column<Advice>; NUM_BITS_PER_WORD],
+    word_left: [Column<Advice>; NUM_BITS_PER_WORD],
+    word_right: [Column<Advice>; NUM_BITS_PER_WORD],
+    sum_left: [Column<Advice>; NUM_BITS_PER_WORD],
+    sum_right: [Column<Advice>; NUM_BITS_PER_WORD],
+    carry_sum_left: [Column<Advice>; 2],
+    carry_sum_right: [Column<Advice>; 2],
+    carry_left: Column<Advice>,
+    carry_right: Column<Advice>,
+    x: Column<Advice>,
+    x_left: Column<Advice>,
+    x_right: Column<Advice>,
+    is_final: Column<Advice>,
+    is_start: Column<Advice>,
+    is_paddings: [Column<Advice>; NUM_BYTES_PER_WORD],
+    data_rlcs: [Column<Advice>; NUM_BYTES_PER_WORD],
+    length: Column<Advice>,
+    /// The columns for other circuits to lookup RIPEMD-160 hash results
+    pub ripemd160_table: Ripemd160Table,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct Ripemd160CircuitConfigArgs<F: Field> {
+    /// Ripemd160Table
+    pub ripemd160_table: Ripemd160Table,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Ripemd160CircuitConfig<F> {
+    type ConfigArgs = Ripemd160CircuitConfigArgs<F>;
+
+    /// Return a new Ripemd160CircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            ripemd160_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = meta.fixed_column();
+        let q_first = meta.fixed_column();
+        let q_block_start = meta.fixed_column();
+        let q_start = meta.fixed_column();
+        let q_input = meta.fixed_column();
+        let q_last_data_word = meta.fixed_column();
+        let q_length = meta.fixed_column();
+        let q_length_last = meta.fixed_column();
+        let q_round = meta.fixed_column();
+        let q_functions = array_init::array_init(|_| meta.fixed_column());
+        let q_rotations_left = array_init::array_init(|_| meta.fixed_column());
+        let q_rotations_right = array_init::array_init(|_| meta.fixed_column());
+        let q_ends = array_init::array_init(|_| meta.fixed_column());
+        let q_squeeze = meta.fixed_column();
+        let k_left = meta.fixed_column();
+        let k_right = meta.fixed_column();
+        let h = meta.fixed_column();
+        let word_x = array_init::array_init(|_| meta.advice_column());
+        let word_left = array_init::array_init(|_| meta.advice_column());
+        let word_right = array_init::array_init(|_| meta.advice_column());
+        let sum_left = array_init::array_init(|_| meta.advice_column());
+        let sum_right = array_init::array_init(|_| meta.advice_column());
+        let carry_sum_left = array_init::array_init(|_| meta.advice_column());
+        let carry_sum_right = array_init::array_init(|_| meta.advice_column());
+        let carry_left = meta.advice_column();
+        let carry_right = meta.advice_column();
+        let x = meta.advice_column();
+        let x_left = meta.advice_column();
+        let x_right = meta.advice_column();
+        let is_final = meta.advice_column();
+        let is_start = meta.advice_column();
+        let is_paddings = array_init::array_init(|_| meta.advice_column());
+        let data_rlcs = array_init::array_init(|_| meta.advice_column_in(SecondPhase));
+        let length = meta.advice_column();
+
+        // The message words are copied from the input rows to the rounds
+        for column in [x, x_left, x_right] {
+            meta.enable_equality(column);
+        }
+
+        let bits =
+            |meta: &mut VirtualCells<F>, word: &[Column<Advice>; NUM_BITS_PER_WORD], rot: i32| {
+                word.iter()
+                    .map(|column| meta.query_advice(*column, Rotation(rot)))
+                    .collect::<Vec<_>>()
+            };
+        let carry = |meta: &mut VirtualCells<F>, columns: &[Column<Advice>]| {
+            columns
+                .iter()
+                .enumerate()
+                .fold(0.expr(), |acc, (idx, column)| {
+                    acc + meta.query_advice(*column, Rotation::cur()) * (1u64 << idx).expr()
+                })
+                * (1u64 << NUM_BITS_PER_WORD).expr()
+        };
+
+        meta.create_gate("boolean checks", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for column in word_x
+                .iter()
+                .chain(word_left.iter())
+                .chain(word_right.iter())
+                .chain(sum_left.iter())
+                .chain(sum_right.iter())
+                .chain(carry_sum_left.iter())
+                .chain(carry_sum_right.iter())
+                .chain(is_paddings.iter())
+                .chain([carry_left, carry_right, is_final, is_start].iter())
+            {
+                cb.require_boolean("boolean cell", meta.query_advice(*column, Rotation::cur()));
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("block flags", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_block_start = meta.query_fixed(q_block_start, Rotation::cur());
+            let is_final_prev = meta.query_advice(is_final, Rotation::prev());
+            cb.condition(q_block_start.clone(), |cb| {
+                // A new hash is started on the first row or after a final block
+                cb.require_equal(
+                    "is_start on the first row of the block",
+                    meta.query_advice(is_start, Rotation::cur()),
+                    select::expr(
+                        meta.query_fixed(q_first, Rotation::cur()),
+                        1.expr(),
+                        is_final_prev.clone(),
+                    ),
+                );
+            });
+            cb.condition(not::expr(q_block_start), |cb| {
+                cb.require_equal(
+                    "is_final is the same on all the rows of the block",
+                    meta.query_advice(is_final, Rotation::cur()),
+                    is_final_prev,
+                );
+                cb.require_equal(
+                    "is_start is the same on all the rows of the block",
+                    meta.query_advice(is_start, Rotation::cur()),
+                    meta.query_advice(is_start, Rotation::prev()),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("start", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The state is the initial hash value for a new hash, and the state
+            // at the end of the previous block otherwise
+            let word_left_cur = decode(&bits(meta, &word_left, 0));
+            cb.require_equal(
+                "state at the start of the block",
+                word_left_cur.clone(),
+                select::expr(
+                    meta.query_advice(is_start, Rotation::cur()),
+                    meta.query_fixed(h, Rotation::cur()),
+                    decode(&bits(meta, &word_left, -(NUM_END_ROWS as i32))),
+                ),
+            );
+            cb.require_equal(
+                "both lines start from the same state",
+                decode(&bits(meta, &word_right, 0)),
+                word_left_cur,
+            );
+            cb.gate(meta.query_fixed(q_start, Rotation::cur()))
+        });
+
+        meta.create_gate("round", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for (word, sum_word, carry_sum, carry_word, x, k, q_rotations, is_left) in [
+                (
+                    &word_left,
+                    &sum_left,
+                    &carry_sum_left,
+                    carry_left,
+                    x_left,
+                    k_left,
+                    &q_rotations_left,
+                    true,
+                ),
+                (
+                    &word_right,
+                    &sum_right,
+                    &carry_sum_right,
+                    carry_right,
+                    x_right,
+                    k_right,
+                    &q_rotations_right,
+                    false,
+                ),
+            ] {
+                let a = rotate_left(&bits(meta, word, -5), STATE_ROTATION);
+                let b = bits(meta, word, -1);
+                let c = bits(meta, word, -2);
+                let d = rotate_left(&bits(meta, word, -3), STATE_ROTATION);
+                let e = rotate_left(&bits(meta, word, -4), STATE_ROTATION);
+                // The right line uses the functions in reverse order
+                let f = sum::expr((0..NUM_FUNCTIONS).map(|idx| {
+                    let q_function = if is_left {
+                        q_functions[idx]
+                    } else {
+                        q_functions[NUM_FUNCTIONS - 1 - idx]
+                    };
+                    meta.query_fixed(q_function, Rotation::cur()) * function(idx, &b, &c, &d)
+                }));
+                let sum_bits = bits(meta, sum_word, 0);
+                cb.require_equal(
+                    "s = a + f(b, c, d) + x + k",
+                    decode(&sum_bits) + carry(meta, &carry_sum[..]),
+                    decode(&a)
+                        + f
+                        + meta.query_advice(x, Rotation::cur())
+                        + meta.query_fixed(k, Rotation::cur()),
+                );
+                let rotated_sum = sum::expr(q_rotations.iter().enumerate().map(|(idx, q)| {
+                    meta.query_fixed(*q, Rotation::cur())
+                        * decode(&rotate_left(&sum_bits, MIN_ROUND_ROTATION + idx))
+                }));
+                cb.require_equal(
+                    "t = (s <<< r) + e",
+                    decode(&bits(meta, word, 0)) + carry(meta, &[carry_word][..]),
+                    rotated_sum + decode(&e),
+                );
+            }
+            cb.gate(meta.query_fixed(q_round, Rotation::cur()))
+        });
+
+        meta.create_gate("end", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The words of the state at the end of each line
+            let left = |meta: &mut VirtualCells<F>, rot| bits(meta, &word_left, rot);
+            let right = |meta: &mut VirtualCells<F>, rot| bits(meta, &word_right, rot);
+            let rol = |bits: Vec<Expression<F>>| rotate_left(&bits, STATE_ROTATION);
+            let rows_to_start = -((NUM_START_ROWS + NUM_ROUNDS + 1) as i32);
+            let ends = [
+                // h0' = h1 + c + d', stored rotated
+                (
+                    rol(bits(meta, &word_left, 0)),
+                    [
+                        left(meta, rows_to_start + NUM_START_ROWS as i32),
+                        left(meta, -2),
+                        rol(right(meta, -3)),
+                    ],
+                ),
+                // h4' = h0 + b + c', stored rotated
+                (
+                    rol(bits(meta, &word_left, 0)),
+                    [
+                        rol(left(meta, rows_to_start)),
+                        left(meta, -2),
+                        right(meta, -3),
+                    ],
+                ),
+                // h3' = h4 + a + b', stored rotated
+                (
+                    rol(bits(meta, &word_left, 0)),
+                    [
+                        rol(left(meta, rows_to_start)),
+                        rol(left(meta, -7)),
+                        right(meta, -3),
+                    ],
+                ),
+                // h2' = h3 + e + a'
+                (
+                    bits(meta, &word_left, 0),
+                    [
+                        rol(left(meta, rows_to_start)),
+                        rol(left(meta, -7)),
+                        rol(right(meta, -8)),
+                    ],
+                ),
+                // h1' = h2 + d + e'
+                (
+                    bits(meta, &word_left, 0),
+                    [
+                        left(meta, rows_to_start),
+                        rol(left(meta, -7)),
+                        rol(right(meta, -8)),
+                    ],
+                ),
+            ];
+            for (q_end, (word, terms)) in q_ends.iter().zip(ends) {
+                cb.require_zero(
+                    "state at the end of the block",
+                    meta.query_fixed(*q_end, Rotation::cur())
+                        * (decode(&word) + carry(meta, &carry_sum_left[..])
+                            - sum::expr(terms.iter().map(|term| decode(term)))),
+                );
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("input", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_final = meta.query_advice(is_final, Rotation::cur());
+            let q_length = meta.query_fixed(q_length, Rotation::cur());
+            let w = bits(meta, &word_x, 0);
+            cb.require_equal(
+                "x is the message word",
+                meta.query_advice(x, Rotation::cur()),
+                decode(&w),
+            );
+            let mut is_padding_prev =
+                meta.query_advice(is_paddings[NUM_BYTES_PER_WORD - 1], Rotation::prev());
+            let mut data_rlc_prev =
+                meta.query_advice(data_rlcs[NUM_BYTES_PER_WORD - 1], Rotation::prev());
+            for idx in 0..NUM_BYTES_PER_WORD {
+                // The message words are little-endian
+                let lsb = idx * NUM_BITS_PER_BYTE;
+                let byte = decode(&w[lsb..lsb + NUM_BITS_PER_BYTE]);
+                let is_padding = meta.query_advice(is_paddings[idx], Rotation::cur());
+                let data_rlc = meta.query_advice(data_rlcs[idx], Rotation::cur());
+
+                let is_first_padding = is_padding.clone() - is_padding_prev.clone();
+                cb.require_boolean("padding is monotonic", is_first_padding.clone());
+                cb.require_equal(
+                    "first padding byte is 0x80",
+                    is_first_padding.clone() * byte.clone(),
+                    is_first_padding * 0x80.expr(),
+                );
+                // The length of the input is in the last two words of the
+                // final block
+                cb.require_zero(
+                    "padding bytes are zero",
+                    is_padding_prev
+                        * (1.expr() - q_length.clone() * is_final.clone())
+                        * byte.clone(),
+                );
+                cb.require_equal(
+                    "data_rlc accumulates the input bytes",
+                    data_rlc.clone(),
+                    select::expr(
+                        is_padding.clone(),
+                        data_rlc_prev.clone(),
+                        data_rlc_prev * challenges.keccak_input() + byte,
+                    ),
+                );
+                is_padding_prev = is_padding;
+                data_rlc_prev = data_rlc;
+            }
+            cb.require_equal(
+                "length accumulates the input bytes",
+                meta.query_advice(length, Rotation::cur()),
+                meta.query_advice(length, Rotation::prev())
+                    + sum::expr(is_paddings.map(|is_padding| {
+                        not::expr(meta.query_advice(is_padding, Rotation::cur()))
+                    })),
+            );
+            cb.gate(meta.query_fixed(q_input, Rotation::cur()))
+        });
+
+        meta.create_gate("input carry over", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The input data is reset at the start of a new hash
+            let keep = 1.expr()
+                - meta.query_fixed(q_block_start, Rotation::cur())
+                    * meta.query_advice(is_start, Rotation::cur());
+            cb.condition(
+                not::expr(meta.query_fixed(q_input, Rotation::cur())),
+                |cb| {
+                    for column in [
+                        length,
+                        data_rlcs[NUM_BYTES_PER_WORD - 1],
+                        is_paddings[NUM_BYTES_PER_WORD - 1],
+                    ] {
+                        cb.require_equal(
+                            "input data is carried over",
+                            meta.query_advice(column, Rotation::cur()),
+                            keep.clone() * meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                },
+            );
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("is final", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The block is final when the padding starts before the length
+            cb.require_equal(
+                "is_final == is_padding of the last data byte",
+                meta.query_advice(is_final, Rotation::cur()),
+                meta.query_advice(is_paddings[NUM_BYTES_PER_WORD - 1], Rotation::cur()),
+            );
+            cb.gate(meta.query_fixed(q_last_data_word, Rotation::cur()))
+        });
+
+        meta.create_gate("length", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            cb.condition(meta.query_advice(is_final, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "padded length is the length in bits",
+                    decode(&bits(meta, &word_x, -1))
+                        + decode(&bits(meta, &word_x, 0)) * (1u64 << NUM_BITS_PER_WORD).expr(),
+                    meta.query_advice(length, Rotation::cur()) * NUM_BITS_PER_BYTE.expr(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_length_last, Rotation::cur()))
+        });
+
+        meta.create_gate("squeeze", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The hash is h0 || .. || h4 with little-endian words, from the
+            // rows holding `h0 >>> 10, h4 >>> 10, h3 >>> 10, h2, h1`
+            let rol = |bits: Vec<Expression<F>>| rotate_left(&bits, STATE_ROTATION);
+            let hash_words = [
+                rol(bits(meta, &word_left, -4)),
+                bits(meta, &word_left, 0),
+                bits(meta, &word_left, -1),
+                rol(bits(meta, &word_left, -2)),
+                rol(bits(meta, &word_left, -3)),
+            ];
+            // The RLC of the hash as a big-endian word
+            let hash_bytes_le = hash_words
+                .iter()
+                .rev()
+                .flat_map(|word| {
+                    word.chunks(NUM_BITS_PER_BYTE)
+                        .rev()
+                        .map(decode)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            cb.require_equal(
+                "input_rlc",
+                meta.query_advice(ripemd160_table.input_rlc, Rotation::cur()),
+                meta.query_advice(data_rlcs[NUM_BYTES_PER_WORD - 1], Rotation::cur()),
+            );
+            cb.require_equal(
+                "input_len",
+                meta.query_advice(ripemd160_table.input_len, Rotation::cur()),
+                meta.query_advice(length, Rotation::cur()),
+            );
+            cb.require_equal(
+                "output_rlc",
+                meta.query_advice(ripemd160_table.output_rlc, Rotation::cur()),
+                rlc::expr(&hash_bytes_le, challenges.evm_word()),
+            );
+            cb.gate(meta.query_fixed(q_squeeze, Rotation::cur()))
+        });
+
+        meta.create_gate("table is_enabled", |meta| {
+            // A row of the table is enabled only at the end of a final block
+            vec![
+                meta.query_advice(ripemd160_table.is_enabled, Rotation::cur())
+                    - meta.query_fixed(q_squeeze, Rotation::cur())
+                        * meta.query_advice(is_final, Rotation::cur()),
+            ]
+        });
+
+        Self {
+            q_enable,
+            q_first,
+            q_block_start,
+            q_start,
+            q_input,
+            q_last_data_word,
+            q_length,
+            q_length_last,
+            q_round,
+            q_functions,
+            q_rotations_left,
+            q_rotations_right,
+            q_ends,
+            q_squeeze,
+            k_left,
+            k_right,
+            h,
+            word_x,
+            word_left,
+            word_right,
+            sum_left,
+            sum_right,
+            carry_sum_left,
+            carry_sum_right,
+            carry_left,
+            carry_right,
+            x,
+            x_left,
+            x_right,
+            is_final,
+            is_start,
+            is_paddings,
+            data_rlcs,
+            length,
+            ripemd160_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Ripemd160CircuitConfig<F> {
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[Ripemd160Row<F>],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assign ripemd160 rows",
+            |mut region| {
+                let mut x_cells = Vec::with_capacity(witness.len());
+                for (offset, ripemd160_row) in witness.iter().enumerate() {
+                    x_cells.push(self.set_row(&mut region, offset, ripemd160_row)?);
+                }
+                // Copy the message words of each block to the rounds
+                for block in x_cells.chunks(ROWS_PER_BLOCK) {
+                    for round in 0..NUM_ROUNDS {
+                        let (_, x_left, x_right) = &block[NUM_START_ROWS + round];
+                        for (cell, word) in [(x_left, R_LEFT[round]), (x_right, R_RIGHT[round])] {
+                            let (x, _, _) = &block[NUM_START_ROWS + word];
+                            region.constrain_equal(cell.cell(), x.cell())?;
+                        }
+                    }
+                }
+                if witness.is_empty() {
+                    // Keep an all-zero row for the disabled lookups
+                    self.ripemd160_table
+                        .assign_row(&mut region, 0, [Value::known(F::ZERO); 4])?;
+                }
+                self.ripemd160_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &Ripemd160Row<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let idx = offset % ROWS_PER_BLOCK;
+        let round = idx
+            .checked_sub(NUM_START_ROWS)
+            .filter(|round| *round < NUM_ROUNDS);
+        let end = idx.checked_sub(NUM_START_ROWS + NUM_ROUNDS);
+        // Fixed selectors
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, true),
+            ("q_first", self.q_first, offset == 0),
+            ("q_block_start", self.q_block_start, idx == 0),
+            ("q_start", self.q_start, idx < NUM_START_ROWS),
+            (
+                "q_input",
+                self.q_input,
+                (NUM_START_ROWS..=ROW_LAST_INPUT_WORD).contains(&idx),
+            ),
+            (
+                "q_last_data_word",
+                self.q_last_data_word,
+                idx == ROW_LAST_DATA_WORD,
+            ),
+            (
+                "q_length",
+                self.q_length,
+                idx > ROW_LAST_DATA_WORD && idx <= ROW_LAST_INPUT_WORD,
+            ),
+            (
+                "q_length_last",
+                self.q_length_last,
+                idx == ROW_LAST_INPUT_WORD,
+            ),
+            ("q_round", self.q_round, round.is_some()),
+            ("q_squeeze", self.q_squeeze, idx == ROWS_PER_BLOCK - 1),
+        ]
+        .into_iter()
+        .chain(
+            self.q_functions
+                .iter()
+                .enumerate()
+                .map(|(function, column)| {
+                    (
+                        "q_function",
+                        *column,
+                        round.map(|round| round / NUM_ROUNDS_PER_FUNCTION) == Some(function),
+                    )
+                }),
+        )
+        .chain(
+            self.q_rotations_left
+                .iter()
+                .enumerate()
+                .map(|(rotation, column)| {
+                    (
+                        "q_rotation_left",
+                        *column,
+                        round.map(|round| S_LEFT[round]) == Some(MIN_ROUND_ROTATION + rotation),
+                    )
+                }),
+        )
+        .chain(
+            self.q_rotations_right
+                .iter()
+                .enumerate()
+                .map(|(rotation, column)| {
+                    (
+                        "q_rotation_right",
+                        *column,
+                        round.map(|round| S_RIGHT[round]) == Some(MIN_ROUND_ROTATION + rotation),
+                    )
+                }),
+        )
+        .chain(
+            self.q_ends
+                .iter()
+                .enumerate()
+                .map(|(end_idx, column)| ("q_end", *column, end == Some(end_idx))),
+        ) {
+            region.assign_fixed(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+        let [k_left, k_right] = round
+            .map(|round| {
+                let function = round / NUM_ROUNDS_PER_FUNCTION;
+                [K_LEFT[function], K_RIGHT[function]]
+            })
+            .unwrap_or_default();
+        let h = if idx < NUM_START_ROWS {
+            start_words(&H)[idx]
+        } else {
+            0
+        };
+        for (name, column, value) in [
+            ("k_left", self.k_left, k_left),
+            ("k_right", self.k_right, k_right),
+            ("h", self.h, h),
+        ] {
+            region.assign_fixed(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+
+        // Words
+        for (name, columns, value) in [
+            ("x", &self.word_x, row.x),
+            ("left", &self.word_left, row.left),
+            ("right", &self.word_right, row.right),
+            ("sum_left", &self.sum_left, row.sum_left),
+            ("sum_right", &self.sum_right, row.sum_right),
+        ] {
+            for (bit, column) in into_bits(value).into_iter().zip(columns.iter()) {
+                region.assign_advice(
+                    || format!("assign {} {}", name, offset),
+                    *column,
+                    offset,
+                    || Value::known(F::from(bit as u64)),
+                )?;
+            }
+        }
+        for (name, columns, value) in [
+            (
+                "carry_sum_left",
+                &self.carry_sum_left[..],
+                row.carry_sum_left,
+            ),
+            (
+                "carry_sum_right",
+                &self.carry_sum_right[..],
+                row.carry_sum_right,
+            ),
+            ("carry_left", &[self.carry_left][..], row.carry_left),
+            ("carry_right", &[self.carry_right][..], row.carry_right),
+        ] {
+            for (idx, column) in columns.iter().enumerate() {
+                region.assign_advice(
+                    || format!("assign {} {}", name, offset),
+                    *column,
+                    offset,
+                    || Value::known(F::from((value >> idx) & 1)),
+                )?;
+            }
+        }
+        let [x, x_left, x_right] = [
+            ("x", self.x, row.x),
+            ("x_left", self.x_left, row.x_left),
+            ("x_right", self.x_right, row.x_right),
+        ]
+        .map(|(name, column, value)| {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )
+        });
+
+        // Input data
+        for (name, column, value) in [
+            ("is_final", self.is_final, row.is_final as u64),
+            ("is_start", self.is_start, row.is_start as u64),
+            ("length", self.length, row.length as u64),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        for (idx, (is_padding, data_rlc)) in row.is_paddings.iter().zip(row.data_rlcs).enumerate() {
+            region.assign_advice(
+                || format!("assign is_padding {} {}", idx, offset),
+                self.is_paddings[idx],
+                offset,
+                || Value::known(F::from(*is_padding as u64)),
+            )?;
+            region.assign_advice(
+                || format!("assign data_rlc {} {}", idx, offset),
+                self.data_rlcs[idx],
+                offset,
+                || data_rlc,
+            )?;
+        }
+
+        self.ripemd160_table.assign_row(
+            region,
+            offset,
+            if idx == ROWS_PER_BLOCK - 1 {
+                [
+                    Value::known(F::from(row.is_final as u64)),
+                    row.data_rlcs[NUM_BYTES_PER_WORD - 1],
+                    Value::known(F::from(row.length as u64)),
+                    row.hash_rlc,
+                ]
+            } else {
+                [Value::known(F::ZERO); 4]
+            },
+        )?;
+
+        Ok((x?, x_left?, x_right?))
+    }
+
+    fn annotate_circuit(&self, region: &mut Region<F>) {
+        region.name_column(|| "RIPEMD160_q_enable", self.q_enable);
+        region.name_column(|| "RIPEMD160_q_first", self.q_first);
+        region.name_column(|| "RIPEMD160_q_block_start", self.q_block_start);
+        region.name_column(|| "RIPEMD160_q_start", self.q_start);
+        region.name_column(|| "RIPEMD160_q_input", self.q_input);
+        region.name_column(|| "RIPEMD160_q_last_data_word", self.q_last_data_word);
+        region.name_column(|| "RIPEMD160_q_length", self.q_length);
+        region.name_column(|| "RIPEMD160_q_length_last", self.q_length_last);
+        region.name_column(|| "RIPEMD160_q_round", self.q_round);
+        region.name_column(|| "RIPEMD160_q_squeeze", self.q_squeeze);
+        region.name_column(|| "RIPEMD160_x", self.x);
+        region.name_column(|| "RIPEMD160_x_left", self.x_left);
+        region.name_column(|| "RIPEMD160_x_right", self.x_right);
+        region.name_column(|| "RIPEMD160_is_final", self.is_final);
+        region.name_column(|| "RIPEMD160_is_start", self.is_start);
+        region.name_column(|| "RIPEMD160_length", self.length);
+    }
+}
+
+/// A row of the RIPEMD-160 circuit
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Ripemd160Row<F> {
+    x: u32,
+    left: u32,
+    right: u32,
+    sum_left: u32,
+    sum_right: u32,
+    x_left: u32,
+    x_right: u32,
+    carry_sum_left: u64,
+    carry_sum_right: u64,
+    carry_left: u64,
+    carry_right: u64,
+    is_final: bool,
+    is_start: bool,
+    is_paddings: [bool; NUM_BYTES_PER_WORD],
+    data_rlcs: [Value<F>; NUM_BYTES_PER_WORD],
+    length: usize,
+    hash_rlc: Value<F>,
+}
+
+/// The words of the start rows of a block hashing from `h`.
+fn start_words(h: &[u32; 5]) -> [u32; NUM_START_ROWS] {
+    [
+        h[0].rotate_right(STATE_ROTATION as u32),
+        h[4].rotate_right(STATE_ROTATION as u32),
+        h[3].rotate_right(STATE_ROTATION as u32),
+        h[2],
+        h[1],
+    ]
+}
+
+/// Generates the rows hashing `bytes`.
+fn ripemd160<F: Field>(
+    rows: &mut Vec<Ripemd160Row<F>>,
+    bytes: &[u8],
+    challenges: Challenges<Value<F>>,
+) {
+    let mut padded = bytes.to_vec();
+    padded.push(0x80);
+    while (padded.len() + NUM_BYTES_LENGTH) % RATE != 0 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((bytes.len() * NUM_BITS_PER_BYTE) as u64).to_le_bytes());
+
+    let mut hs = H;
+    let mut length = 0;
+    let mut data_rlc = Value::known(F::ZERO);
+    let mut is_padding = false;
+    let num_blocks = padded.len() / RATE;
+    let rol = |value: u32| value.rotate_left(STATE_ROTATION as u32);
+    for (block_idx, block) in padded.chunks(RATE).enumerate() {
+        let xs = block
+            .chunks(NUM_BYTES_PER_WORD)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let mut block_rows = vec![
+            Ripemd160Row {
+                is_final: block_idx == num_blocks - 1,
+                is_start: block_idx == 0,
+                is_paddings: [is_padding; NUM_BYTES_PER_WORD],
+                data_rlcs: [data_rlc; NUM_BYTES_PER_WORD],
+                length,
+                ..Default::default()
+            };
+            ROWS_PER_BLOCK
+        ];
+
+        // Start
+        for (row, word) in block_rows.iter_mut().zip(start_words(&hs)) {
+            row.left = word;
+            row.right = word;
+        }
+
+        // Input
+        for (word_idx, word) in block.chunks(NUM_BYTES_PER_WORD).enumerate() {
+            let row = &mut block_rows[NUM_START_ROWS + word_idx];
+            row.x = xs[word_idx];
+            for (idx, byte) in word.iter().enumerate() {
+                let position = block_idx * RATE + word_idx * NUM_BYTES_PER_WORD + idx;
+                is_padding = position >= bytes.len();
+                if !is_padding {
+                    length += 1;
+                    data_rlc =
+                        data_rlc * challenges.keccak_input() + Value::known(F::from(*byte as u64));
+                }
+                row.is_paddings[idx] = is_padding;
+                row.data_rlcs[idx] = data_rlc;
+            }
+            row.length = length;
+        }
+        for row in block_rows[NUM_START_ROWS + NUM_WORDS_TO_ABSORB..].iter_mut() {
+            row.is_paddings = [is_padding; NUM_BYTES_PER_WORD];
+            row.data_rlcs = [data_rlc; NUM_BYTES_PER_WORD];
+            row.length = length;
+        }
+
+        // Rounds
+        for round in 0..NUM_ROUNDS {
+            let idx = NUM_START_ROWS + round;
+            let function = round / NUM_ROUNDS_PER_FUNCTION;
+            let [left, right] = [
+                (true, K_LEFT[function], R_LEFT[round], S_LEFT[round]),
+                (false, K_RIGHT[function], R_RIGHT[round], S_RIGHT[round]),
+            ]
+            .map(|(is_left, k, r, s)| {
+                let word = |rot: usize| {
+                    let row = &block_rows[idx - rot];
+                    if is_left {
+                        row.left
+                    } else {
+                        row.right
+                    }
+                };
+                let (a, b, c, d, e) = (rol(word(5)), word(1), word(2), rol(word(3)), rol(word(4)));
+                let f = if is_left {
+                    function_value(function, b, c, d)
+                } else {
+                    function_value(NUM_FUNCTIONS - 1 - function, b, c, d)
+                };
+                let sum = a as u64 + f as u64 + xs[r] as u64 + k as u64;
+                let t = (sum as u32).rotate_left(s as u32) as u64 + e as u64;
+                (xs[r], sum, t)
+            });
+            let row = &mut block_rows[idx];
+            (row.x_left, row.sum_left, row.carry_sum_left) =
+                (left.0, left.1 as u32, left.1 >> NUM_BITS_PER_WORD);
+            (row.left, row.carry_left) = (left.2 as u32, left.2 >> NUM_BITS_PER_WORD);
+            (row.x_right, row.sum_right, row.carry_sum_right) =
+                (right.0, right.1 as u32, right.1 >> NUM_BITS_PER_WORD);
+            (row.right, row.carry_right) = (right.2 as u32, right.2 >> NUM_BITS_PER_WORD);
+        }
+
+        // End
+        let end = NUM_START_ROWS + NUM_ROUNDS;
+        let left = |rot: usize| block_rows[end - rot].left;
+        let right = |rot: usize| block_rows[end - rot].right;
+        let sums = [
+            hs[1] as u64 + left(2) as u64 + rol(right(3)) as u64,
+            hs[2] as u64 + rol(left(3)) as u64 + rol(right(4)) as u64,
+            hs[3] as u64 + rol(left(4)) as u64 + rol(right(5)) as u64,
+            hs[4] as u64 + rol(left(5)) as u64 + right(1) as u64,
+            hs[0] as u64 + left(1) as u64 + right(2) as u64,
+        ];
+        hs = sums.map(|sum| sum as u32);
+        for (idx, (word, sum)) in start_words(&hs)
+            .into_iter()
+            .zip([sums[0], sums[4], sums[3], sums[2], sums[1]])
+            .enumerate()
+        {
+            let row = &mut block_rows[end + idx];
+            row.left = word;
+            row.right = word;
+            row.carry_sum_left = sum >> NUM_BITS_PER_WORD;
+        }
+
+        // The intermediate hash is also exposed on the last row, but the
+        // table row is enabled only for the final block
+        let hash = hs.iter().flat_map(|h| h.to_le_bytes()).collect::<Vec<_>>();
+        block_rows.last_mut().unwrap().hash_rlc = challenges
+            .evm_word()
+            .map(|challenge| rlc::value(hash.iter().rev(), challenge));
+        rows.extend(block_rows);
+    }
+}
+
+/// Generates the rows of the RIPEMD-160 circuit hashing all the `bytes`,
+/// padded with empty hashes up to `capacity` blocks.
+pub(crate) fn multi_ripemd160<F: Field>(
+    bytes: &[Vec<u8>],
+    challenges: Challenges<Value<F>>,
+    capacity: Option<usize>,
+) -> Result<Vec<Ripemd160Row<F>>, Error> {
+    let mut rows = Vec::new();
+    for bytes in bytes {
+        ripemd160(&mut rows, bytes, challenges);
+    }
+    if let Some(capacity) = capacity {
+        // Pad with no data hashes to the expected capacity
+        while rows.len() < capacity * ROWS_PER_BLOCK {
+            ripemd160(&mut rows, &[], challenges);
+        }
+        // Check that we are not over capacity
+        if rows.len() > capacity * ROWS_PER_BLOCK {
+            return Err(Error::BoundsFailure);
+        }
+    }
+    Ok(rows)
+}
+
+/// Number of blocks of the padded `bytes`.
+fn num_blocks(bytes: &[u8]) -> usize {
+    bytes.len() / RATE + 1 + usize::from(bytes.len() % RATE >= RATE - NUM_BYTES_LENGTH)
+}
+
+/// Ripemd160Circuit
+#[derive(Default, Clone, Debug)]
+pub struct Ripemd160Circuit<F: Field> {
+    inputs: Vec<Vec<u8>>,
+    num_rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SubCircuit<F> for Ripemd160Circuit<F> {
+    type Config = Ripemd160CircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column is queried at more than 9 distinct rotations
+        12
+    }
+
+    /// The `block.circuits_params.max_ripemd160_rows` parameter, when
+    /// enabled, sets up the circuit to support a fixed number of blocks,
+    /// independently of the blocks required by the inputs.
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            block.circuits_params.max_ripemd160_rows,
+            block.precompile_events.ripemd160.clone(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        (
+            block
+                .precompile_events
+                .ripemd160
+                .iter()
+                .map(|bytes| num_blocks(bytes) * ROWS_PER_BLOCK)
+                .sum(),
+            block.circuits_params.max_ripemd160_rows,
+        )
+    }
+
+    /// Make the assignments to the Ripemd160Circuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let witness = self.generate_witness(*challenges);
+        config.assign(layouter, witness.as_slice())
+    }
+}
+
+impl<F: Field> Ripemd160Circuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
+        Ripemd160Circuit {
+            inputs,
+            num_rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of blocks that can be hashed in this circuit
+    pub fn capacity(&self) -> Option<usize> {
+        if self.num_rows > 0 {
+            Some(self.num_rows.saturating_sub(Self::unusable_rows()) / ROWS_PER_BLOCK)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the witness using the data to be hashed
+    pub(crate) fn generate_witness(
+        &self,
+        challenges: Challenges<Value<F>>,
+    ) -> Vec<Ripemd160Row<F>> {
+        multi_ripemd160(self.inputs.as_slice(), challenges, self.capacity())
+            .expect("Too many inputs for given capacity")
+    }
+}
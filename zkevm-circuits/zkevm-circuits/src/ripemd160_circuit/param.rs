@@ -0,0 +1,73 @@
+pub(crate) const MAX_DEGREE: usize = 5;
+
+pub(crate) const NUM_BITS_PER_BYTE: usize = 8;
+pub(crate) const NUM_BYTES_PER_WORD: usize = 4;
+pub(crate) const NUM_BITS_PER_WORD: usize = NUM_BYTES_PER_WORD * NUM_BITS_PER_BYTE;
+pub(crate) const NUM_WORDS_TO_ABSORB: usize = 16;
+pub(crate) const RATE: usize = NUM_WORDS_TO_ABSORB * NUM_BYTES_PER_WORD;
+/// Number of bytes of the padding that encode the length of the input.
+pub(crate) const NUM_BYTES_LENGTH: usize = 8;
+pub(crate) const NUM_ROUNDS: usize = 80;
+pub(crate) const NUM_ROUNDS_PER_FUNCTION: usize = 16;
+pub(crate) const NUM_FUNCTIONS: usize = NUM_ROUNDS / NUM_ROUNDS_PER_FUNCTION;
+
+/// Rows holding the state at the start of a block, as the 5 last words
+/// computed by a line: `h0 >>> 10, h4 >>> 10, h3 >>> 10, h2, h1`.
+pub(crate) const NUM_START_ROWS: usize = 5;
+/// Rows holding the state at the end of a block, laid out as the start rows.
+pub(crate) const NUM_END_ROWS: usize = 5;
+pub(crate) const ROWS_PER_BLOCK: usize = NUM_START_ROWS + NUM_ROUNDS + NUM_END_ROWS;
+
+/// Row of the round absorbing the last word that can hold input bytes in the
+/// final block.
+pub(crate) const ROW_LAST_DATA_WORD: usize =
+    NUM_START_ROWS + NUM_WORDS_TO_ABSORB - NUM_BYTES_LENGTH / NUM_BYTES_PER_WORD - 1;
+/// Row of the round absorbing the last word of a block.
+pub(crate) const ROW_LAST_INPUT_WORD: usize = NUM_START_ROWS + NUM_WORDS_TO_ABSORB - 1;
+
+/// Rotation of the words of the state that are rotated when they are shifted.
+pub(crate) const STATE_ROTATION: usize = 10;
+/// Range of the rotations of a round.
+pub(crate) const MIN_ROUND_ROTATION: usize = 5;
+pub(crate) const NUM_ROUND_ROTATIONS: usize = 11;
+
+/// Initial hash value.
+pub(crate) const H: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// Round constants of the left and right lines, per function.
+pub(crate) const K_LEFT: [u32; NUM_FUNCTIONS] =
+    [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+pub(crate) const K_RIGHT: [u32; NUM_FUNCTIONS] =
+    [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// Message word absorbed by each round of the left and right lines.
+pub(crate) const R_LEFT: [usize; NUM_ROUNDS] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+pub(crate) const R_RIGHT: [usize; NUM_ROUNDS] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// Rotation of each round of the left and right lines.
+pub(crate) const S_LEFT: [usize; NUM_ROUNDS] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+pub(crate) const S_RIGHT: [usize; NUM_ROUNDS] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
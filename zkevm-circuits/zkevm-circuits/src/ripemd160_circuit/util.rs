@@ -0,0 +1,67 @@
+use super::param::NUM_BITS_PER_WORD;
+use eth_types::Field;
+use gadgets::util::{xor, Expr};
+use halo2_proofs::plonk::Expression;
+
+/// Composes a word from its little-endian bits.
+pub(crate) fn decode<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .rev()
+        .fold(0.expr(), |acc, bit| acc * 2.expr() + bit.clone())
+}
+
+/// Bits of `x <<< n`.
+pub(crate) fn rotate_left<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..NUM_BITS_PER_WORD)
+        .map(|idx| bits[(idx + NUM_BITS_PER_WORD - n) % NUM_BITS_PER_WORD].clone())
+        .collect()
+}
+
+/// The bitwise function of the rounds `16 * idx..16 * (idx + 1)` of the left
+/// line, and of the rounds `16 * (4 - idx)..16 * (5 - idx)` of the right line.
+pub(crate) fn function<F: Field>(
+    idx: usize,
+    x: &[Expression<F>],
+    y: &[Expression<F>],
+    z: &[Expression<F>],
+) -> Expression<F> {
+    let bits = x
+        .iter()
+        .zip(y.iter())
+        .zip(z.iter())
+        .map(|((x, y), z)| {
+            let (x, y, z) = (x.clone(), y.clone(), z.clone());
+            match idx {
+                // x ^ y ^ z
+                0 => xor::expr(xor::expr(x, y), z),
+                // (x & y) | (!x & z)
+                1 => x.clone() * y + (1.expr() - x) * z,
+                // (x | !y) ^ z
+                2 => xor::expr(1.expr() - y.clone() + x * y, z),
+                // (x & z) | (y & !z)
+                3 => x * z.clone() + y * (1.expr() - z),
+                // x ^ (y | !z)
+                4 => xor::expr(x, 1.expr() - z.clone() + y * z),
+                _ => unreachable!(),
+            }
+        })
+        .collect::<Vec<_>>();
+    decode(&bits)
+}
+
+/// The bitwise function of [`function`] on words.
+pub(crate) fn function_value(idx: usize, x: u32, y: u32, z: u32) -> u32 {
+    match idx {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        4 => x ^ (y | !z),
+        _ => unreachable!(),
+    }
+}
+
+/// Little-endian bits of a word.
+pub(crate) fn into_bits(value: u32) -> [bool; NUM_BITS_PER_WORD] {
+    array_init::array_init(|idx| (value >> idx) & 1 == 1)
+}
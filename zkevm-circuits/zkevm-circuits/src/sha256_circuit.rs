@@ -0,0 +1,882 @@
+//! The SHA-256 circuit implementation.
+//!
+//! Each 64-byte block of the padded input is processed in
+//! [`ROWS_PER_BLOCK`](param::ROWS_PER_BLOCK) rows: 4 rows holding the state at
+//! the start of the block, one row per round and 4 rows adding the state at
+//! the end of the rounds to the state at the start.  Every word is stored as 32
+//! boolean cells so that the bitwise functions are simple expressions over the
+//! bits.  The 16 first rounds absorb the message words, whose bytes are checked
+//! against the padding rules and accumulated into the input RLC, and the last
+//! row of the final block of an input exposes the hash in the
+//! [`Sha256Table`].
+mod param;
+mod util;
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::Sha256Circuit as TestSha256Circuit;
+
+use self::{param::*, util::*};
+use crate::{
+    evm_circuit::util::{
+        constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+        rlc,
+    },
+    table::{LookupTable, Sha256Table},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use eth_types::Field;
+use gadgets::util::{not, select, sum, Expr};
+use halo2_proofs::{
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+    },
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+/// Sha256CircuitConfig
+#[derive(Clone, Debug)]
+pub struct Sha256CircuitConfig<F> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_block_start: Column<Fixed>,
+    q_start: Column<Fixed>,
+    q_input: Column<Fixed>,
+    q_last_data_word: Column<Fixed>,
+    q_length: Column<Fixed>,
+    q_length_last: Column<Fixed>,
+    q_extend: Column<Fixed>,
+    q_round: Column<Fixed>,
+    q_end: Column<Fixed>,
+    q_squeeze: Column<Fixed>,
+    round_cst: Column<Fixed>,
+    h_a: Column<Fixed>,
+    h_e: Column<Fixed>,
+    word_w: [Column<Advice>; NUM_BITS_PER_WORD],
+    word_a: [Column<Advice>; NUM_BITS_PER_WORD],
+    word_e: [Column<Advice>; NUM_BITS_PER_WORD],
+    carry_w: [Column<Advice>; 2],
+    carry_a: [Column<Advice>; 3],
+    carry_e: [Column<Advice>; 3],
+    is_final: Column<Advice>,
+    is_start: Column<Advice>,
+    is_paddings: [Column<Advice>; NUM_BYTES_PER_WORD],
+    data_rlcs: [Column<Advice>; NUM_BYTES_PER_WORD],
+    length: Column<Advice>,
+    /// The columns for other circuits to lookup SHA-256 hash results
+    pub sha256_table: Sha256Table,
+    _marker: PhantomData<F>,
+}
+
+/// Circuit configuration arguments
+pub struct Sha256CircuitConfigArgs<F: Field> {
+    /// Sha256Table
+    pub sha256_table: Sha256Table,
+    /// Challenges randomness
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for Sha256CircuitConfig<F> {
+    type ConfigArgs = Sha256CircuitConfigArgs<F>;
+
+    /// Return a new Sha256CircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            sha256_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let q_enable = meta.fixed_column();
+        let q_first = meta.fixed_column();
+        let q_block_start = meta.fixed_column();
+        let q_start = meta.fixed_column();
+        let q_input = meta.fixed_column();
+        let q_last_data_word = meta.fixed_column();
+        let q_length = meta.fixed_column();
+        let q_length_last = meta.fixed_column();
+        let q_extend = meta.fixed_column();
+        let q_round = meta.fixed_column();
+        let q_end = meta.fixed_column();
+        let q_squeeze = meta.fixed_column();
+        let round_cst = meta.fixed_column();
+        let h_a = meta.fixed_column();
+        let h_e = meta.fixed_column();
+        let word_w = array_init::array_init(|_| meta.advice_column());
+        let word_a = array_init::array_init(|_| meta.advice_column());
+        let word_e = array_init::array_init(|_| meta.advice_column());
+        let carry_w = array_init::array_init(|_| meta.advice_column());
+        let carry_a = array_init::array_init(|_| meta.advice_column());
+        let carry_e = array_init::array_init(|_| meta.advice_column());
+        let is_final = meta.advice_column();
+        let is_start = meta.advice_column();
+        let is_paddings = array_init::array_init(|_| meta.advice_column());
+        let data_rlcs = array_init::array_init(|_| meta.advice_column_in(SecondPhase));
+        let length = meta.advice_column();
+
+        let bits =
+            |meta: &mut VirtualCells<F>, word: &[Column<Advice>; NUM_BITS_PER_WORD], rot: i32| {
+                word.iter()
+                    .map(|column| meta.query_advice(*column, Rotation(rot)))
+                    .collect::<Vec<_>>()
+            };
+        let carry = |meta: &mut VirtualCells<F>, columns: &[Column<Advice>]| {
+            columns
+                .iter()
+                .enumerate()
+                .fold(0.expr(), |acc, (idx, column)| {
+                    acc + meta.query_advice(*column, Rotation::cur()) * (1u64 << idx).expr()
+                })
+                * (1u64 << NUM_BITS_PER_WORD).expr()
+        };
+
+        meta.create_gate("boolean checks", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            for column in word_w
+                .iter()
+                .chain(word_a.iter())
+                .chain(word_e.iter())
+                .chain(carry_w.iter())
+                .chain(carry_a.iter())
+                .chain(carry_e.iter())
+                .chain(is_paddings.iter())
+                .chain([is_final, is_start].iter())
+            {
+                cb.require_boolean("boolean cell", meta.query_advice(*column, Rotation::cur()));
+            }
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("block flags", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let q_block_start = meta.query_fixed(q_block_start, Rotation::cur());
+            let is_final_prev = meta.query_advice(is_final, Rotation::prev());
+            cb.condition(q_block_start.clone(), |cb| {
+                // A new hash is started on the first row or after a final block
+                cb.require_equal(
+                    "is_start on the first row of the block",
+                    meta.query_advice(is_start, Rotation::cur()),
+                    select::expr(
+                        meta.query_fixed(q_first, Rotation::cur()),
+                        1.expr(),
+                        is_final_prev.clone(),
+                    ),
+                );
+            });
+            cb.condition(not::expr(q_block_start), |cb| {
+                cb.require_equal(
+                    "is_final is the same on all the rows of the block",
+                    meta.query_advice(is_final, Rotation::cur()),
+                    is_final_prev,
+                );
+                cb.require_equal(
+                    "is_start is the same on all the rows of the block",
+                    meta.query_advice(is_start, Rotation::cur()),
+                    meta.query_advice(is_start, Rotation::prev()),
+                );
+            });
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("start", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_start = meta.query_advice(is_start, Rotation::cur());
+            for (word, h) in [(&word_a, h_a), (&word_e, h_e)] {
+                // The state is the initial hash value for a new hash, and the
+                // state at the end of the previous block otherwise
+                let prev = decode(&bits(meta, word, -(NUM_END_ROWS as i32)));
+                cb.require_equal(
+                    "state at the start of the block",
+                    decode(&bits(meta, word, 0)),
+                    select::expr(is_start.clone(), meta.query_fixed(h, Rotation::cur()), prev),
+                );
+            }
+            cb.gate(meta.query_fixed(q_start, Rotation::cur()))
+        });
+
+        meta.create_gate("message schedule", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let w = decode(&bits(meta, &word_w, 0)) + carry(meta, &carry_w[..]);
+            let w_2 = bits(meta, &word_w, -2);
+            let w_7 = decode(&bits(meta, &word_w, -7));
+            let w_15 = bits(meta, &word_w, -15);
+            let w_16 = decode(&bits(meta, &word_w, -16));
+            cb.require_equal(
+                "w[i] = σ1(w[i - 2]) + w[i - 7] + σ0(w[i - 15]) + w[i - 16]",
+                w,
+                small_sigma(&w_2, SIGMA1) + w_7 + small_sigma(&w_15, SIGMA0) + w_16,
+            );
+            cb.gate(meta.query_fixed(q_extend, Rotation::cur()))
+        });
+
+        meta.create_gate("round", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let [a, b, c, d] = [-1, -2, -3, -4].map(|rot| bits(meta, &word_a, rot));
+            let [e, f, g, h] = [-1, -2, -3, -4].map(|rot| bits(meta, &word_e, rot));
+            let t1 = decode(&h)
+                + big_sigma(&e, BIG_SIGMA1)
+                + ch(&e, &f, &g)
+                + meta.query_fixed(round_cst, Rotation::cur())
+                + decode(&bits(meta, &word_w, 0));
+            let t2 = big_sigma(&a, BIG_SIGMA0) + maj(&a, &b, &c);
+            cb.require_equal(
+                "e = d + t1",
+                decode(&bits(meta, &word_e, 0)) + carry(meta, &carry_e[..]),
+                decode(&d) + t1.clone(),
+            );
+            cb.require_equal(
+                "a = t1 + t2",
+                decode(&bits(meta, &word_a, 0)) + carry(meta, &carry_a[..]),
+                t1 + t2,
+            );
+            cb.gate(meta.query_fixed(q_round, Rotation::cur()))
+        });
+
+        meta.create_gate("end", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The state after the last round is added to the state at the
+            // start of the block
+            for (word, carry_column) in [(&word_a, carry_a[0]), (&word_e, carry_e[0])] {
+                cb.require_equal(
+                    "state at the end of the block",
+                    decode(&bits(meta, word, 0)) + carry(meta, &[carry_column][..]),
+                    decode(&bits(meta, word, -(NUM_END_ROWS as i32)))
+                        + decode(&bits(meta, word, -((ROWS_PER_BLOCK - NUM_END_ROWS) as i32))),
+                );
+            }
+            cb.gate(meta.query_fixed(q_end, Rotation::cur()))
+        });
+
+        meta.create_gate("input", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            let is_final = meta.query_advice(is_final, Rotation::cur());
+            let q_length = meta.query_fixed(q_length, Rotation::cur());
+            let w = bits(meta, &word_w, 0);
+            let mut is_padding_prev =
+                meta.query_advice(is_paddings[NUM_BYTES_PER_WORD - 1], Rotation::prev());
+            let mut data_rlc_prev =
+                meta.query_advice(data_rlcs[NUM_BYTES_PER_WORD - 1], Rotation::prev());
+            for idx in 0..NUM_BYTES_PER_WORD {
+                // The message words are big-endian
+                let lsb = (NUM_BYTES_PER_WORD - 1 - idx) * NUM_BITS_PER_BYTE;
+                let byte = decode(&w[lsb..lsb + NUM_BITS_PER_BYTE]);
+                let is_padding = meta.query_advice(is_paddings[idx], Rotation::cur());
+                let data_rlc = meta.query_advice(data_rlcs[idx], Rotation::cur());
+
+                let is_first_padding = is_padding.clone() - is_padding_prev.clone();
+                cb.require_boolean("padding is monotonic", is_first_padding.clone());
+                cb.require_equal(
+                    "first padding byte is 0x80",
+                    is_first_padding.clone() * byte.clone(),
+                    is_first_padding * 0x80.expr(),
+                );
+                // The length of the input is in the last two words of the
+                // final block
+                cb.require_zero(
+                    "padding bytes are zero",
+                    is_padding_prev
+                        * (1.expr() - q_length.clone() * is_final.clone())
+                        * byte.clone(),
+                );
+                cb.require_equal(
+                    "data_rlc accumulates the input bytes",
+                    data_rlc.clone(),
+                    select::expr(
+                        is_padding.clone(),
+                        data_rlc_prev.clone(),
+                        data_rlc_prev * challenges.keccak_input() + byte,
+                    ),
+                );
+                is_padding_prev = is_padding;
+                data_rlc_prev = data_rlc;
+            }
+            cb.require_equal(
+                "length accumulates the input bytes",
+                meta.query_advice(length, Rotation::cur()),
+                meta.query_advice(length, Rotation::prev())
+                    + sum::expr(is_paddings.map(|is_padding| {
+                        not::expr(meta.query_advice(is_padding, Rotation::cur()))
+                    })),
+            );
+            cb.gate(meta.query_fixed(q_input, Rotation::cur()))
+        });
+
+        meta.create_gate("input carry over", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The input data is reset at the start of a new hash
+            let keep = 1.expr()
+                - meta.query_fixed(q_block_start, Rotation::cur())
+                    * meta.query_advice(is_start, Rotation::cur());
+            cb.condition(
+                not::expr(meta.query_fixed(q_input, Rotation::cur())),
+                |cb| {
+                    for column in [
+                        length,
+                        data_rlcs[NUM_BYTES_PER_WORD - 1],
+                        is_paddings[NUM_BYTES_PER_WORD - 1],
+                    ] {
+                        cb.require_equal(
+                            "input data is carried over",
+                            meta.query_advice(column, Rotation::cur()),
+                            keep.clone() * meta.query_advice(column, Rotation::prev()),
+                        );
+                    }
+                },
+            );
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("is final", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The block is final when the padding starts before the length
+            cb.require_equal(
+                "is_final == is_padding of the last data byte",
+                meta.query_advice(is_final, Rotation::cur()),
+                meta.query_advice(is_paddings[NUM_BYTES_PER_WORD - 1], Rotation::cur()),
+            );
+            cb.gate(meta.query_fixed(q_last_data_word, Rotation::cur()))
+        });
+
+        meta.create_gate("length", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            cb.condition(meta.query_advice(is_final, Rotation::cur()), |cb| {
+                cb.require_equal(
+                    "padded length is the length in bits",
+                    decode(&bits(meta, &word_w, -1)) * (1u64 << NUM_BITS_PER_WORD).expr()
+                        + decode(&bits(meta, &word_w, 0)),
+                    meta.query_advice(length, Rotation::cur()) * NUM_BITS_PER_BYTE.expr(),
+                );
+            });
+            cb.gate(meta.query_fixed(q_length_last, Rotation::cur()))
+        });
+
+        meta.create_gate("squeeze", |meta| {
+            let mut cb = BaseConstraintBuilder::new(MAX_DEGREE);
+            // The hash is H0 || .. || H7 with H0..H3 in `word_a` and H4..H7 in
+            // `word_e`, from the last row up
+            let hash_bytes_le = [(&word_e, -3), (&word_e, -2), (&word_e, -1), (&word_e, 0)]
+                .into_iter()
+                .chain([(&word_a, -3), (&word_a, -2), (&word_a, -1), (&word_a, 0)])
+                .flat_map(|(word, rot)| {
+                    let word_bits = bits(meta, word, rot);
+                    word_bits
+                        .chunks(NUM_BITS_PER_BYTE)
+                        .map(decode)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            cb.require_equal(
+                "input_rlc",
+                meta.query_advice(sha256_table.input_rlc, Rotation::cur()),
+                meta.query_advice(data_rlcs[NUM_BYTES_PER_WORD - 1], Rotation::cur()),
+            );
+            cb.require_equal(
+                "input_len",
+                meta.query_advice(sha256_table.input_len, Rotation::cur()),
+                meta.query_advice(length, Rotation::cur()),
+            );
+            cb.require_equal(
+                "output_rlc",
+                meta.query_advice(sha256_table.output_rlc, Rotation::cur()),
+                rlc::expr(&hash_bytes_le, challenges.evm_word()),
+            );
+            cb.gate(meta.query_fixed(q_squeeze, Rotation::cur()))
+        });
+
+        meta.create_gate("table is_enabled", |meta| {
+            // A row of the table is enabled only at the end of a final block
+            vec![
+                meta.query_advice(sha256_table.is_enabled, Rotation::cur())
+                    - meta.query_fixed(q_squeeze, Rotation::cur())
+                        * meta.query_advice(is_final, Rotation::cur()),
+            ]
+        });
+
+        Self {
+            q_enable,
+            q_first,
+            q_block_start,
+            q_start,
+            q_input,
+            q_last_data_word,
+            q_length,
+            q_length_last,
+            q_extend,
+            q_round,
+            q_end,
+            q_squeeze,
+            round_cst,
+            h_a,
+            h_e,
+            word_w,
+            word_a,
+            word_e,
+            carry_w,
+            carry_a,
+            carry_e,
+            is_final,
+            is_start,
+            is_paddings,
+            data_rlcs,
+            length,
+            sha256_table,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> Sha256CircuitConfig<F> {
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        witness: &[Sha256Row<F>],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assign sha256 rows",
+            |mut region| {
+                for (offset, sha256_row) in witness.iter().enumerate() {
+                    self.set_row(&mut region, offset, sha256_row)?;
+                }
+                if witness.is_empty() {
+                    // Keep an all-zero row for the disabled lookups
+                    self.sha256_table
+                        .assign_row(&mut region, 0, [Value::known(F::ZERO); 4])?;
+                }
+                self.sha256_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
+    fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: &Sha256Row<F>,
+    ) -> Result<(), Error> {
+        let idx = offset % ROWS_PER_BLOCK;
+        let is_round = (NUM_START_ROWS..NUM_START_ROWS + NUM_ROUNDS).contains(&idx);
+        let is_input = (NUM_START_ROWS..=ROW_LAST_INPUT_WORD).contains(&idx);
+        // Fixed selectors
+        for (name, column, value) in [
+            ("q_enable", self.q_enable, true),
+            ("q_first", self.q_first, offset == 0),
+            ("q_block_start", self.q_block_start, idx == 0),
+            ("q_start", self.q_start, idx < NUM_START_ROWS),
+            ("q_input", self.q_input, is_input),
+            (
+                "q_last_data_word",
+                self.q_last_data_word,
+                idx == ROW_LAST_DATA_WORD,
+            ),
+            (
+                "q_length",
+                self.q_length,
+                idx > ROW_LAST_DATA_WORD && idx <= ROW_LAST_INPUT_WORD,
+            ),
+            (
+                "q_length_last",
+                self.q_length_last,
+                idx == ROW_LAST_INPUT_WORD,
+            ),
+            ("q_extend", self.q_extend, is_round && !is_input),
+            ("q_round", self.q_round, is_round),
+            ("q_end", self.q_end, idx >= NUM_START_ROWS + NUM_ROUNDS),
+            ("q_squeeze", self.q_squeeze, idx == ROWS_PER_BLOCK - 1),
+        ] {
+            region.assign_fixed(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+        let round_cst = if is_round {
+            ROUND_CST[idx - NUM_START_ROWS]
+        } else {
+            0
+        };
+        let [h_a, h_e] = if idx < NUM_START_ROWS {
+            [H[3 - idx], H[7 - idx]]
+        } else {
+            [0, 0]
+        };
+        for (name, column, value) in [
+            ("round_cst", self.round_cst, round_cst),
+            ("h_a", self.h_a, h_a),
+            ("h_e", self.h_e, h_e),
+        ] {
+            region.assign_fixed(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+        }
+
+        // Words
+        for (name, columns, value) in [
+            ("w", &self.word_w, row.w),
+            ("a", &self.word_a, row.a),
+            ("e", &self.word_e, row.e),
+        ] {
+            for (bit, column) in into_bits(value).into_iter().zip(columns.iter()) {
+                region.assign_advice(
+                    || format!("assign {} {}", name, offset),
+                    *column,
+                    offset,
+                    || Value::known(F::from(bit as u64)),
+                )?;
+            }
+        }
+        for (name, columns, value) in [
+            ("carry_w", &self.carry_w[..], row.carry_w),
+            ("carry_a", &self.carry_a[..], row.carry_a),
+            ("carry_e", &self.carry_e[..], row.carry_e),
+        ] {
+            for (idx, column) in columns.iter().enumerate() {
+                region.assign_advice(
+                    || format!("assign {} {}", name, offset),
+                    *column,
+                    offset,
+                    || Value::known(F::from((value >> idx) & 1)),
+                )?;
+            }
+        }
+
+        // Input data
+        for (name, column, value) in [
+            ("is_final", self.is_final, row.is_final as u64),
+            ("is_start", self.is_start, row.is_start as u64),
+            ("length", self.length, row.length as u64),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", name, offset),
+                column,
+                offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        for (idx, (is_padding, data_rlc)) in row.is_paddings.iter().zip(row.data_rlcs).enumerate() {
+            region.assign_advice(
+                || format!("assign is_padding {} {}", idx, offset),
+                self.is_paddings[idx],
+                offset,
+                || Value::known(F::from(*is_padding as u64)),
+            )?;
+            region.assign_advice(
+                || format!("assign data_rlc {} {}", idx, offset),
+                self.data_rlcs[idx],
+                offset,
+                || data_rlc,
+            )?;
+        }
+
+        self.sha256_table.assign_row(
+            region,
+            offset,
+            if idx == ROWS_PER_BLOCK - 1 {
+                [
+                    Value::known(F::from(row.is_final as u64)),
+                    row.data_rlcs[NUM_BYTES_PER_WORD - 1],
+                    Value::known(F::from(row.length as u64)),
+                    row.hash_rlc,
+                ]
+            } else {
+                [Value::known(F::ZERO); 4]
+            },
+        )
+    }
+
+    fn annotate_circuit(&self, region: &mut Region<F>) {
+        region.name_column(|| "SHA256_q_enable", self.q_enable);
+        region.name_column(|| "SHA256_q_first", self.q_first);
+        region.name_column(|| "SHA256_q_block_start", self.q_block_start);
+        region.name_column(|| "SHA256_q_start", self.q_start);
+        region.name_column(|| "SHA256_q_input", self.q_input);
+        region.name_column(|| "SHA256_q_last_data_word", self.q_last_data_word);
+        region.name_column(|| "SHA256_q_length", self.q_length);
+        region.name_column(|| "SHA256_q_length_last", self.q_length_last);
+        region.name_column(|| "SHA256_q_extend", self.q_extend);
+        region.name_column(|| "SHA256_q_round", self.q_round);
+        region.name_column(|| "SHA256_q_end", self.q_end);
+        region.name_column(|| "SHA256_q_squeeze", self.q_squeeze);
+        region.name_column(|| "SHA256_is_final", self.is_final);
+        region.name_column(|| "SHA256_is_start", self.is_start);
+        region.name_column(|| "SHA256_length", self.length);
+    }
+}
+
+/// A row of the SHA-256 circuit
+#[derive(Clone, Debug)]
+pub(crate) struct Sha256Row<F> {
+    w: u32,
+    a: u32,
+    e: u32,
+    carry_w: u64,
+    carry_a: u64,
+    carry_e: u64,
+    is_final: bool,
+    is_start: bool,
+    is_paddings: [bool; NUM_BYTES_PER_WORD],
+    data_rlcs: [Value<F>; NUM_BYTES_PER_WORD],
+    length: usize,
+    hash_rlc: Value<F>,
+}
+
+fn small_sigma_value(x: u32, [r0, r1, s]: [usize; 3]) -> u32 {
+    x.rotate_right(r0 as u32) ^ x.rotate_right(r1 as u32) ^ (x >> s)
+}
+
+fn big_sigma_value(x: u32, [r0, r1, r2]: [usize; 3]) -> u32 {
+    x.rotate_right(r0 as u32) ^ x.rotate_right(r1 as u32) ^ x.rotate_right(r2 as u32)
+}
+
+/// Generates the rows hashing `bytes`.
+fn sha256<F: Field>(rows: &mut Vec<Sha256Row<F>>, bytes: &[u8], challenges: Challenges<Value<F>>) {
+    let mut padded = bytes.to_vec();
+    padded.push(0x80);
+    while (padded.len() + NUM_BYTES_LENGTH) % RATE != 0 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((bytes.len() * NUM_BITS_PER_BYTE) as u64).to_be_bytes());
+
+    let mut hs = H;
+    let mut length = 0;
+    let mut data_rlc = Value::known(F::ZERO);
+    let mut is_padding = false;
+    let num_blocks = padded.len() / RATE;
+    for (block_idx, block) in padded.chunks(RATE).enumerate() {
+        let is_start = block_idx == 0;
+        let is_final = block_idx == num_blocks - 1;
+        let mut block_rows = Vec::with_capacity(ROWS_PER_BLOCK);
+        let mut push_row = |w, a, e, carries: [u64; 3], is_paddings, data_rlcs, length| {
+            block_rows.push(Sha256Row {
+                w,
+                a,
+                e,
+                carry_w: carries[0],
+                carry_a: carries[1],
+                carry_e: carries[2],
+                is_final,
+                is_start,
+                is_paddings,
+                data_rlcs,
+                length,
+                hash_rlc: Value::known(F::ZERO),
+            })
+        };
+
+        // Start
+        let mut word_a = Vec::with_capacity(ROWS_PER_BLOCK);
+        let mut word_e = Vec::with_capacity(ROWS_PER_BLOCK);
+        for idx in 0..NUM_START_ROWS {
+            word_a.push(hs[3 - idx]);
+            word_e.push(hs[7 - idx]);
+            push_row(
+                0,
+                hs[3 - idx],
+                hs[7 - idx],
+                [0; 3],
+                [is_padding; NUM_BYTES_PER_WORD],
+                [data_rlc; NUM_BYTES_PER_WORD],
+                length,
+            );
+        }
+
+        // Rounds
+        let mut ws = Vec::with_capacity(NUM_ROUNDS);
+        for round in 0..NUM_ROUNDS {
+            let mut is_paddings = [is_padding; NUM_BYTES_PER_WORD];
+            let mut data_rlcs = [data_rlc; NUM_BYTES_PER_WORD];
+            let (w, carry_w) = if round < NUM_WORDS_TO_ABSORB {
+                let word = &block[round * NUM_BYTES_PER_WORD..(round + 1) * NUM_BYTES_PER_WORD];
+                for (idx, byte) in word.iter().enumerate() {
+                    let position = block_idx * RATE + round * NUM_BYTES_PER_WORD + idx;
+                    is_padding = position >= bytes.len();
+                    if !is_padding {
+                        length += 1;
+                        data_rlc = data_rlc * challenges.keccak_input()
+                            + Value::known(F::from(*byte as u64));
+                    }
+                    is_paddings[idx] = is_padding;
+                    data_rlcs[idx] = data_rlc;
+                }
+                (u32::from_be_bytes(word.try_into().unwrap()), 0)
+            } else {
+                let w = small_sigma_value(ws[round - 2], SIGMA1) as u64
+                    + ws[round - 7] as u64
+                    + small_sigma_value(ws[round - 15], SIGMA0) as u64
+                    + ws[round - 16] as u64;
+                (w as u32, w >> NUM_BITS_PER_WORD)
+            };
+            ws.push(w);
+
+            let row = NUM_START_ROWS + round;
+            let [a, b, c, d] = [1, 2, 3, 4].map(|rot| word_a[row - rot]);
+            let [e, f, g, h] = [1, 2, 3, 4].map(|rot| word_e[row - rot]);
+            let t1 = h as u64
+                + big_sigma_value(e, BIG_SIGMA1) as u64
+                + ((e & f) ^ (!e & g)) as u64
+                + ROUND_CST[round] as u64
+                + w as u64;
+            let t2 = big_sigma_value(a, BIG_SIGMA0) as u64 + ((a & b) ^ (a & c) ^ (b & c)) as u64;
+            let new_e = d as u64 + t1;
+            let new_a = t1 + t2;
+            word_a.push(new_a as u32);
+            word_e.push(new_e as u32);
+            push_row(
+                w,
+                new_a as u32,
+                new_e as u32,
+                [
+                    carry_w,
+                    new_a >> NUM_BITS_PER_WORD,
+                    new_e >> NUM_BITS_PER_WORD,
+                ],
+                is_paddings,
+                data_rlcs,
+                length,
+            );
+        }
+
+        // End
+        for idx in 0..NUM_END_ROWS {
+            let row = NUM_START_ROWS + NUM_ROUNDS + idx;
+            let a = word_a[row - NUM_END_ROWS] as u64 + word_a[idx] as u64;
+            let e = word_e[row - NUM_END_ROWS] as u64 + word_e[idx] as u64;
+            hs[3 - idx] = a as u32;
+            hs[7 - idx] = e as u32;
+            push_row(
+                0,
+                a as u32,
+                e as u32,
+                [0, a >> NUM_BITS_PER_WORD, e >> NUM_BITS_PER_WORD],
+                [is_padding; NUM_BYTES_PER_WORD],
+                [data_rlc; NUM_BYTES_PER_WORD],
+                length,
+            );
+        }
+
+        // The intermediate hash is also exposed on the last row, but the
+        // table row is enabled only for the final block
+        let hash = hs.iter().flat_map(|h| h.to_be_bytes()).collect::<Vec<_>>();
+        block_rows.last_mut().unwrap().hash_rlc = challenges
+            .evm_word()
+            .map(|challenge| rlc::value(hash.iter().rev(), challenge));
+        rows.extend(block_rows);
+    }
+}
+
+/// Generates the rows of the SHA-256 circuit hashing all the `bytes`, padded
+/// with empty hashes up to `capacity` blocks.
+pub(crate) fn multi_sha256<F: Field>(
+    bytes: &[Vec<u8>],
+    challenges: Challenges<Value<F>>,
+    capacity: Option<usize>,
+) -> Result<Vec<Sha256Row<F>>, Error> {
+    let mut rows = Vec::new();
+    for bytes in bytes {
+        sha256(&mut rows, bytes, challenges);
+    }
+    if let Some(capacity) = capacity {
+        // Pad with no data hashes to the expected capacity
+        while rows.len() < capacity * ROWS_PER_BLOCK {
+            sha256(&mut rows, &[], challenges);
+        }
+        // Check that we are not over capacity
+        if rows.len() > capacity * ROWS_PER_BLOCK {
+            return Err(Error::BoundsFailure);
+        }
+    }
+    Ok(rows)
+}
+
+/// Number of blocks of the padded `bytes`.
+fn num_blocks(bytes: &[u8]) -> usize {
+    bytes.len() / RATE + 1 + usize::from(bytes.len() % RATE >= RATE - NUM_BYTES_LENGTH)
+}
+
+/// Sha256Circuit
+#[derive(Default, Clone, Debug)]
+pub struct Sha256Circuit<F: Field> {
+    inputs: Vec<Vec<u8>>,
+    num_rows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SubCircuit<F> for Sha256Circuit<F> {
+    type Config = Sha256CircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column is queried at more than 6 distinct rotations
+        9
+    }
+
+    /// The `block.circuits_params.max_sha256_rows` parameter, when enabled,
+    /// sets up the circuit to support a fixed number of blocks, independently
+    /// of the blocks required by the inputs.
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        Self::new(
+            block.circuits_params.max_sha256_rows,
+            block.precompile_events.sha256.clone(),
+        )
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        (
+            block
+                .precompile_events
+                .sha256
+                .iter()
+                .map(|bytes| num_blocks(bytes) * ROWS_PER_BLOCK)
+                .sum(),
+            block.circuits_params.max_sha256_rows,
+        )
+    }
+
+    /// Make the assignments to the Sha256Circuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let witness = self.generate_witness(*challenges);
+        config.assign(layouter, witness.as_slice())
+    }
+}
+
+impl<F: Field> Sha256Circuit<F> {
+    /// Creates a new circuit instance
+    pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
+        Sha256Circuit {
+            inputs,
+            num_rows,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of blocks that can be hashed in this circuit
+    pub fn capacity(&self) -> Option<usize> {
+        if self.num_rows > 0 {
+            Some(self.num_rows.saturating_sub(Self::unusable_rows()) / ROWS_PER_BLOCK)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the witness using the data to be hashed
+    pub(crate) fn generate_witness(&self, challenges: Challenges<Value<F>>) -> Vec<Sha256Row<F>> {
+        multi_sha256(self.inputs.as_slice(), challenges, self.capacity())
+            .expect("Too many inputs for given capacity")
+    }
+}
@@ -0,0 +1,76 @@
+use super::*;
+use crate::util::unusable_rows;
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use log::error;
+
+#[test]
+fn sha256_circuit_unusable_rows() {
+    assert_eq!(
+        Sha256Circuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, Sha256Circuit::<Fr>>(()),
+    )
+}
+
+fn verify<F: Field>(k: u32, inputs: Vec<Vec<u8>>, success: bool) {
+    let circuit = Sha256Circuit::new(2usize.pow(k), inputs);
+
+    let prover = MockProver::<F>::run(k, &circuit, vec![]).unwrap();
+    let verify_result = prover.verify();
+    if verify_result.is_ok() != success {
+        if let Some(errors) = verify_result.err() {
+            for error in errors.iter() {
+                error!("{}", error);
+            }
+        }
+        panic!();
+    }
+}
+
+#[test]
+fn sha256_circuit_simple() {
+    let k = 11;
+    let inputs = vec![
+        vec![],
+        (0u8..1).collect::<Vec<_>>(),
+        (0u8..55).collect::<Vec<_>>(),
+        (0u8..56).collect::<Vec<_>>(),
+        (0u8..64).collect::<Vec<_>>(),
+        (0u8..119).collect::<Vec<_>>(),
+        (0u8..200).collect::<Vec<_>>(),
+    ];
+    verify::<Fr>(k, inputs, true);
+}
+
+#[test]
+fn sha256_circuit_table() {
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(0x100)),
+        Value::known(Fr::from(0x200)),
+        Value::known(Fr::from(0x300)),
+    );
+    let inputs = vec![
+        vec![],
+        (0u8..55).collect::<Vec<_>>(),
+        (0u8..64).collect::<Vec<_>>(),
+    ];
+    let rows = multi_sha256(&inputs, challenges, None).unwrap();
+    let table_rows = rows
+        .iter()
+        .skip(ROWS_PER_BLOCK - 1)
+        .step_by(ROWS_PER_BLOCK)
+        .filter(|row| row.is_final)
+        .collect::<Vec<_>>();
+    assert_eq!(table_rows.len(), inputs.len());
+    for (row, input) in table_rows.into_iter().zip(inputs.iter()) {
+        let [_, input_rlc, input_len, output_rlc] = Sha256Table::assignments(input, &challenges);
+        input_rlc
+            .zip(row.data_rlcs[NUM_BYTES_PER_WORD - 1])
+            .assert_if_known(|(a, b)| a == b);
+        input_len
+            .zip(Value::known(Fr::from(row.length as u64)))
+            .assert_if_known(|(a, b)| a == b);
+        output_rlc
+            .zip(row.hash_rlc)
+            .assert_if_known(|(a, b)| a == b);
+    }
+}
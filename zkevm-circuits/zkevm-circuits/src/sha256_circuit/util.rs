@@ -0,0 +1,106 @@
+use super::param::NUM_BITS_PER_WORD;
+use eth_types::Field;
+use gadgets::util::{xor, Expr};
+use halo2_proofs::plonk::Expression;
+
+/// Composes a word from its little-endian bits.
+pub(crate) fn decode<F: Field>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .rev()
+        .fold(0.expr(), |acc, bit| acc * 2.expr() + bit.clone())
+}
+
+/// Bits of `x >>> n`.
+pub(crate) fn rotate_right<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..NUM_BITS_PER_WORD)
+        .map(|idx| bits[(idx + n) % NUM_BITS_PER_WORD].clone())
+        .collect()
+}
+
+/// Bits of `x >> n`.
+pub(crate) fn shift_right<F: Field>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..NUM_BITS_PER_WORD)
+        .map(|idx| {
+            if idx + n < NUM_BITS_PER_WORD {
+                bits[idx + n].clone()
+            } else {
+                0.expr()
+            }
+        })
+        .collect()
+}
+
+/// Bitwise xor of three words.
+pub(crate) fn xor3<F: Field>(
+    a: &[Expression<F>],
+    b: &[Expression<F>],
+    c: &[Expression<F>],
+) -> Vec<Expression<F>> {
+    a.iter()
+        .zip(b.iter())
+        .zip(c.iter())
+        .map(|((a, b), c)| xor::expr(xor::expr(a.clone(), b.clone()), c.clone()))
+        .collect()
+}
+
+/// `σ(x) = (x >>> r0) ^ (x >>> r1) ^ (x >> s)` of the message schedule.
+pub(crate) fn small_sigma<F: Field>(
+    bits: &[Expression<F>],
+    [r0, r1, s]: [usize; 3],
+) -> Expression<F> {
+    decode(&xor3(
+        &rotate_right(bits, r0),
+        &rotate_right(bits, r1),
+        &shift_right(bits, s),
+    ))
+}
+
+/// `Σ(x) = (x >>> r0) ^ (x >>> r1) ^ (x >>> r2)` of the compression.
+pub(crate) fn big_sigma<F: Field>(
+    bits: &[Expression<F>],
+    [r0, r1, r2]: [usize; 3],
+) -> Expression<F> {
+    decode(&xor3(
+        &rotate_right(bits, r0),
+        &rotate_right(bits, r1),
+        &rotate_right(bits, r2),
+    ))
+}
+
+/// `Ch(e, f, g) = (e & f) ^ (!e & g)`
+pub(crate) fn ch<F: Field>(
+    e: &[Expression<F>],
+    f: &[Expression<F>],
+    g: &[Expression<F>],
+) -> Expression<F> {
+    let bits = e
+        .iter()
+        .zip(f.iter())
+        .zip(g.iter())
+        .map(|((e, f), g)| e.clone() * f.clone() + (1.expr() - e.clone()) * g.clone())
+        .collect::<Vec<_>>();
+    decode(&bits)
+}
+
+/// `Maj(a, b, c) = (a & b) ^ (a & c) ^ (b & c)`
+pub(crate) fn maj<F: Field>(
+    a: &[Expression<F>],
+    b: &[Expression<F>],
+    c: &[Expression<F>],
+) -> Expression<F> {
+    let bits = a
+        .iter()
+        .zip(b.iter())
+        .zip(c.iter())
+        .map(|((a, b), c)| {
+            a.clone() * b.clone() + a.clone() * c.clone() + b.clone() * c.clone()
+                - 2.expr() * a.clone() * b.clone() * c.clone()
+        })
+        .collect::<Vec<_>>();
+    decode(&bits)
+}
+
+/// Little-endian bits of a word.
+pub(crate) fn into_bits(value: u32) -> [bool; NUM_BITS_PER_WORD] {
+    array_init::array_init(|idx| (value >> idx) & 1 == 1)
+}
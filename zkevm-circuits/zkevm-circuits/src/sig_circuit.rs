@@ -0,0 +1,264 @@
+//! The Sig circuit implementation.
+//!
+//! The circuit verifies the signatures recovered by the ecRecover precompile
+//! calls of a block with the [`SignVerifyChip`] used by the Tx Circuit, and
+//! copies the verified message hash, signature and recovered address into the
+//! [`SigTable`] looked up by the EVM Circuit.
+//!
+//! Known limitations:
+//! - The recovery id `v` is not constrained against the parity of the `y` coordinate of the
+//!   signature point, so the table doesn't rule out the address of the other public key that
+//!   verifies the same signature.
+//! - The chip verifies the message hash reduced modulo the order of the secp256k1 group, so the
+//!   lookups of the calls with a message hash greater than the order fail.
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::SigCircuit as TestSigCircuit;
+
+use crate::{
+    table::{KeccakTable, LookupTable, SigTable},
+    tx_circuit::sign_verify::{SignVerifyChip, SignVerifyConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness,
+};
+use bus_mapping::precompile::EcRecoverEvent;
+use eth_types::{sign_types::SignData, Field, ToBigEndian};
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, Expression},
+};
+use log::error;
+use std::marker::PhantomData;
+
+/// Config for SigCircuit
+#[derive(Clone, Debug)]
+pub struct SigCircuitConfig<F: Field> {
+    sign_verify: SignVerifyConfig,
+    /// The columns for the EVM Circuit to lookup the recovered addresses
+    pub sig_table: SigTable,
+    _marker: PhantomData<F>,
+    // External tables
+    keccak_table: KeccakTable,
+}
+
+/// Circuit configuration arguments
+pub struct SigCircuitConfigArgs<F: Field> {
+    /// SigTable
+    pub sig_table: SigTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+}
+
+impl<F: Field> SubCircuitConfig<F> for SigCircuitConfig<F> {
+    type ConfigArgs = SigCircuitConfigArgs<F>;
+
+    /// Return a new SigCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            sig_table,
+            keccak_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        for column in [
+            sig_table.is_enabled,
+            sig_table.msg_hash_rlc,
+            sig_table.sig_r_rlc,
+            sig_table.sig_s_rlc,
+            sig_table.recovered_addr,
+        ] {
+            meta.enable_equality(column);
+        }
+
+        let sign_verify = SignVerifyConfig::new(meta, keccak_table.clone(), challenges);
+
+        Self {
+            sign_verify,
+            sig_table,
+            _marker: PhantomData,
+            keccak_table,
+        }
+    }
+}
+
+impl<F: Field> SigCircuitConfig<F> {
+    /// Load ECDSA RangeChip table.
+    pub fn load_aux_tables(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.sign_verify.load_range(layouter)
+    }
+}
+
+/// Sig Circuit for verifying the signatures of the ecRecover calls
+#[derive(Clone, Default, Debug)]
+pub struct SigCircuit<F: Field> {
+    /// Max number of verified signatures
+    pub max_verif: usize,
+    /// SignVerify chip
+    pub sign_verify: SignVerifyChip<F>,
+    /// ecRecover calls that recovered an address
+    pub events: Vec<EcRecoverEvent>,
+}
+
+impl<F: Field> SigCircuit<F> {
+    /// Return a new SigCircuit
+    pub fn new(max_verif: usize, events: Vec<EcRecoverEvent>) -> Self {
+        SigCircuit::<F> {
+            max_verif,
+            sign_verify: SignVerifyChip::new(max_verif),
+            events,
+        }
+    }
+
+    /// Return the minimum number of rows required to prove an input of a
+    /// particular size.
+    pub fn min_num_rows(num_verif: usize) -> usize {
+        if num_verif == 0 {
+            0
+        } else {
+            SignVerifyChip::<F>::min_num_rows(num_verif)
+        }
+    }
+}
+
+impl<F: Field> SubCircuit<F> for SigCircuit<F> {
+    type Config = SigCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    /// The `block.circuits_params.max_ecrecover` parameter, when enabled,
+    /// sets up the circuit to support a fixed number of signatures,
+    /// independently of the ecRecover calls of the block.
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let events = block.precompile_events.ecrecover.clone();
+        let max_verif = if block.circuits_params.max_ecrecover > 0 {
+            block.circuits_params.max_ecrecover
+        } else {
+            events.len()
+        };
+        Self::new(max_verif, events)
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let num_verif = block.precompile_events.ecrecover.len();
+        (
+            Self::min_num_rows(num_verif),
+            Self::min_num_rows(block.circuits_params.max_ecrecover.max(num_verif)),
+        )
+    }
+
+    /// Make the assignments to the SigCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.events.len() > self.max_verif {
+            error!(
+                "events.len() = {} > max_verif = {}",
+                self.events.len(),
+                self.max_verif
+            );
+            return Err(Error::Synthesis);
+        }
+        let sign_datas: Vec<SignData> = self
+            .events
+            .iter()
+            .map(|event| {
+                SignData::from_signature(
+                    event.sig_v.wrapping_sub(27),
+                    &event.sig_r,
+                    &event.sig_s,
+                    &event.msg_hash.to_be_bytes(),
+                )
+                .map_err(|e| {
+                    error!("ecRecover event to sign_data error: {:?}", e);
+                    Error::Synthesis
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let assigned_sig_verifs = if self.max_verif > 0 {
+            config.load_aux_tables(layouter)?;
+            self.sign_verify
+                .assign(&config.sign_verify, layouter, &sign_datas, challenges)?
+        } else {
+            Vec::new()
+        };
+
+        layouter.assign_region(
+            || "sig table",
+            |mut region| {
+                let sig_table = &config.sig_table;
+                for column in <SigTable as LookupTable<F>>::advice_columns(sig_table) {
+                    region.assign_advice(
+                        || "sig table all-zero row",
+                        column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                for (idx, assigned_sig_verif) in assigned_sig_verifs.iter().enumerate() {
+                    let offset = idx + 1;
+                    for (name, assigned, column) in [
+                        (
+                            "is_enabled",
+                            &assigned_sig_verif.is_enabled,
+                            sig_table.is_enabled,
+                        ),
+                        (
+                            "msg_hash_rlc",
+                            &assigned_sig_verif.msg_hash_rlc,
+                            sig_table.msg_hash_rlc,
+                        ),
+                        (
+                            "sig_r_rlc",
+                            &assigned_sig_verif.sig_r_rlc,
+                            sig_table.sig_r_rlc,
+                        ),
+                        (
+                            "sig_s_rlc",
+                            &assigned_sig_verif.sig_s_rlc,
+                            sig_table.sig_s_rlc,
+                        ),
+                        (
+                            "recovered_addr",
+                            &assigned_sig_verif.address,
+                            sig_table.recovered_addr,
+                        ),
+                    ] {
+                        assigned.copy_advice(|| name, &mut region, column, offset)?;
+                    }
+                    // Padding signatures are disabled, so their recovery id is
+                    // free.
+                    let sig_v = self.events.get(idx).map_or(0, |event| event.sig_v as u64);
+                    region.assign_advice(
+                        || "sig_v",
+                        sig_table.sig_v,
+                        offset,
+                        || Value::known(F::from(sig_v)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn instance(&self) -> Vec<Vec<F>> {
+        // The maingate expects an instance column, but we don't use it, so we return an
+        // "empty" instance column
+        vec![vec![]]
+    }
+}
@@ -0,0 +1,63 @@
+pub use super::SigCircuit;
+
+use crate::{
+    sig_circuit::{SigCircuitConfig, SigCircuitConfigArgs},
+    table::{KeccakTable, SigTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+};
+use bus_mapping::circuit_input_builder::keccak_inputs_sign_verify;
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for SigCircuit<F> {
+    type Config = (SigCircuitConfig<F>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let sig_table = SigTable::construct(meta);
+        let keccak_table = KeccakTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            SigCircuitConfig::new(
+                meta,
+                SigCircuitConfigArgs {
+                    sig_table,
+                    keccak_table,
+                    challenges,
+                },
+            )
+        };
+
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&mut layouter);
+
+        let sign_datas = self
+            .events
+            .iter()
+            .map(|event| event.sign_data())
+            .collect::<Vec<_>>();
+        config.keccak_table.dev_load(
+            &mut layouter,
+            &keccak_inputs_sign_verify(&sign_datas),
+            &challenges,
+        )?;
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
@@ -0,0 +1,84 @@
+use super::*;
+use crate::util::{log2_ceil, unusable_rows};
+use eth_types::{
+    sign_types::{pk_bytes_le, pk_bytes_swap_endianness},
+    word, Address, Word,
+};
+use ethers_core::utils::keccak256;
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+};
+
+#[test]
+fn sig_circuit_unusable_rows() {
+    assert_eq!(
+        SigCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, SigCircuit::<Fr>>(()),
+    )
+}
+
+fn run<F: Field>(max_verif: usize, events: Vec<EcRecoverEvent>) -> Result<(), Vec<VerifyFailure>> {
+    let k =
+        log2_ceil(SigCircuit::<Fr>::unusable_rows() + SigCircuit::<Fr>::min_num_rows(max_verif));
+    // SignVerifyChip -> ECDSAChip -> MainGate instance column
+    let circuit = SigCircuit::<F>::new(max_verif, events);
+
+    let prover = match MockProver::run(k, &circuit, vec![vec![]]) {
+        Ok(prover) => prover,
+        Err(e) => panic!("{:#?}", e),
+    };
+    prover.verify()
+}
+
+fn ecrecover_event(msg_hash: Word, sig_v: u8, sig_r: Word, sig_s: Word) -> EcRecoverEvent {
+    let pk = SignData::from_signature(sig_v - 27, &sig_r, &sig_s, &msg_hash.to_be_bytes())
+        .unwrap()
+        .pk;
+    let pk_hash = keccak256(pk_bytes_swap_endianness(&pk_bytes_le(&pk)));
+    EcRecoverEvent {
+        msg_hash,
+        sig_v,
+        sig_r,
+        sig_s,
+        recovered_addr: Address::from_slice(&pk_hash[12..]),
+    }
+}
+
+fn events() -> Vec<EcRecoverEvent> {
+    vec![
+        ecrecover_event(
+            word!("0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3"),
+            28,
+            word!("0x9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608"),
+            word!("0x4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada"),
+        ),
+        ecrecover_event(
+            word!("0x456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3"),
+            27,
+            word!("0x9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608"),
+            word!("0x4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada"),
+        ),
+    ]
+}
+
+#[test]
+fn sig_circuit_no_ecrecover() {
+    assert_eq!(run::<Fr>(0, vec![]), Ok(()));
+}
+
+#[test]
+fn sig_circuit_2ecrecover_2max() {
+    assert_eq!(run::<Fr>(2, events()), Ok(()));
+}
+
+#[test]
+fn sig_circuit_1ecrecover_2max() {
+    assert_eq!(run::<Fr>(2, events()[..1].to_vec()), Ok(()));
+}
+
+#[test]
+#[should_panic]
+fn sig_circuit_2ecrecover_1max() {
+    let _ = run::<Fr>(1, events());
+}
@@ -433,6 +433,12 @@ impl<F: Field> StateCircuit<F> {
     pub fn new(rw_map: RwMap, n_rows: usize) -> Self {
         let rows = rw_map.table_assignments();
         let updates = MptUpdates::mock_from(&rows);
+        Self::new_with_updates(rows, updates, n_rows)
+    }
+
+    /// make a new state circuit from the sorted rws and the MPT updates proven
+    /// by the MPT circuit
+    pub fn new_with_updates(rows: Vec<Rw>, updates: MptUpdates, n_rows: usize) -> Self {
         Self {
             rows,
             updates,
@@ -448,7 +454,11 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
     type Config = StateCircuitConfig<F>;
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        Self::new(block.rws.clone(), block.circuits_params.max_rws)
+        Self::new_with_updates(
+            block.rws.table_assignments(),
+            block.mpt_updates.clone(),
+            block.circuits_params.max_rws,
+        )
     }
 
     fn unusable_rows() -> usize {
@@ -356,6 +356,43 @@ fn tx_log_ok() {
     assert_eq!(verify(rows), Ok(()));
 }
 
+#[test]
+fn transient_storage_ok() {
+    let address = address!("0x0000000000000000000000000000000000000fe2");
+    let rows = vec![
+        Rw::TransientStorage {
+            rw_counter: 1,
+            is_write: true,
+            tx_id: 1,
+            account_address: address,
+            storage_key: U256::from(3u64),
+            value: U256::from(0x6fu64),
+            value_prev: U256::zero(),
+        },
+        Rw::TransientStorage {
+            rw_counter: 2,
+            is_write: false,
+            tx_id: 1,
+            account_address: address,
+            storage_key: U256::from(3u64),
+            value: U256::from(0x6fu64),
+            value_prev: U256::from(0x6fu64),
+        },
+        // The same slot starts from 0 again in the next transaction.
+        Rw::TransientStorage {
+            rw_counter: 3,
+            is_write: false,
+            tx_id: 2,
+            account_address: address,
+            storage_key: U256::from(3u64),
+            value: U256::zero(),
+            value_prev: U256::zero(),
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
 #[test]
 fn tx_log_bad() {
     // is_write is false
@@ -887,6 +924,62 @@ fn bad_initial_tx_access_list_account_value() {
     );
 }
 
+#[test]
+fn tx_created_account_ok() {
+    let address = address!("0x0000000000000000000000000000000000000fe2");
+    let rows = vec![
+        Rw::TxCreatedAccount {
+            rw_counter: 1,
+            is_write: true,
+            tx_id: 1,
+            account_address: address,
+            is_created: true,
+            is_created_prev: false,
+        },
+        Rw::TxCreatedAccount {
+            rw_counter: 2,
+            is_write: false,
+            tx_id: 1,
+            account_address: address,
+            is_created: true,
+            is_created_prev: true,
+        },
+        // The account isn't created in the next transaction.
+        Rw::TxCreatedAccount {
+            rw_counter: 3,
+            is_write: false,
+            tx_id: 2,
+            account_address: address,
+            is_created: false,
+            is_created_prev: false,
+        },
+    ];
+
+    assert_eq!(verify(rows), Ok(()));
+}
+
+#[test]
+fn bad_initial_tx_created_account_value() {
+    let rows = vec![Rw::TxCreatedAccount {
+        rw_counter: 1,
+        is_write: true,
+        tx_id: 1,
+        account_address: address!("0x0000000000000000000000000000000000000fe2"),
+        is_created: true,
+        is_created_prev: false,
+    }];
+
+    let overrides = HashMap::from([
+        ((AdviceColumn::InitialValue, 0), Fr::from(1)),
+        ((AdviceColumn::ValuePrev, 0), Fr::from(1)),
+    ]);
+
+    assert_error_matches(
+        verify_with_overrides(rows, overrides),
+        "initial TxCreatedAccount value is false",
+    );
+}
+
 #[test]
 fn bad_initial_tx_refund_value() {
     let rows = vec![Rw::TxRefund {
@@ -912,6 +1005,33 @@ fn bad_initial_tx_refund_value() {
     );
 }
 
+#[test]
+fn bad_initial_transient_storage_value() {
+    let rows = vec![Rw::TransientStorage {
+        rw_counter: 1,
+        is_write: false,
+        tx_id: 1,
+        account_address: address!("0x0000000000000000000000000000000000000fe2"),
+        storage_key: U256::from(3u64),
+        value: U256::zero(),
+        value_prev: U256::zero(),
+    }];
+    let v = Fr::from(10);
+    let overrides = HashMap::from([
+        ((AdviceColumn::IsWrite, 0), Fr::from(1)),
+        ((AdviceColumn::Value, 0), v),
+        ((AdviceColumn::ValuePrev, 0), v),
+        ((AdviceColumn::IsZero, 0), Fr::ZERO),
+        ((AdviceColumn::NonEmptyWitness, 0), v.invert().unwrap()),
+        ((AdviceColumn::InitialValue, 0), v),
+    ]);
+
+    assert_error_matches(
+        verify_with_overrides(rows, overrides),
+        "initial TransientStorage value is 0",
+    );
+}
+
 #[test]
 fn bad_initial_tx_log_value() {
     let rows = vec![Rw::TxLog {
@@ -16,6 +16,12 @@
 //! - [ ] Keccak Circuit
 //! - [ ] MPT Circuit
 //! - [x] PublicInputs Circuit
+//! - [x] Sig Circuit
+//! - [x] SHA-256 Circuit
+//! - [x] RIPEMD-160 Circuit
+//! - [x] ECC Circuit
+//! - [x] Blake2F Circuit
+//! - [x] ModExp Circuit
 //!
 //! And the following shared tables, with the circuits that use them:
 //!
@@ -40,36 +46,64 @@
 //! - [ ] Block Table
 //!   - [ ] EVM Circuit
 //!   - [x] PublicInputs Circuit
-//! - [ ] MPT Table
-//!   - [ ] MPT Circuit
-//!   - [ ] State Circuit
+//! - [x] MPT Table
+//!   - [x] MPT Circuit
+//!   - [x] State Circuit
 //! - [x] Keccak Table
 //!   - [ ] Keccak Circuit
 //!   - [ ] EVM Circuit
 //!   - [x] Bytecode Circuit
 //!   - [x] Tx Circuit
-//!   - [ ] MPT Circuit
+//!   - [x] Sig Circuit
+//!   - [x] MPT Circuit
+//! - [x] Sig Table
+//!   - [x] Sig Circuit
+//!   - [x] EVM Circuit
+//! - [x] SHA-256 Table
+//!   - [x] SHA-256 Circuit
+//!   - [x] EVM Circuit
+//! - [x] RIPEMD-160 Table
+//!   - [x] RIPEMD-160 Circuit
+//!   - [x] EVM Circuit
+//! - [x] ECC Table
+//!   - [x] ECC Circuit
+//!   - [x] EVM Circuit
+//! - [x] Blake2F Table
+//!   - [x] Blake2F Circuit
+//!   - [x] EVM Circuit
+//! - [x] ModExp Table
+//!   - [x] ModExp Circuit
+//!   - [x] EVM Circuit
 
 // FIXME: ignore unused long-time running tests
 // #[cfg(any(feature = "test", test))]
 // pub(crate) mod test;
 
 use crate::{
+    blake2f_circuit::{Blake2fCircuit, Blake2fCircuitConfig, Blake2fCircuitConfigArgs},
     bytecode_circuit::circuit::{
         BytecodeCircuit, BytecodeCircuitConfig, BytecodeCircuitConfigArgs,
     },
     copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
+    ecc_circuit::{EccCircuit, EccCircuitConfig, EccCircuitConfigArgs},
     evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs},
     exp_circuit::{ExpCircuit, ExpCircuitConfig},
     keccak_circuit::{KeccakCircuit, KeccakCircuitConfig, KeccakCircuitConfigArgs},
+    modexp_circuit::{ModExpCircuit, ModExpCircuitConfig, ModExpCircuitConfigArgs},
+    mpt_circuit::{MptCircuit, MptCircuitConfig, MptCircuitConfigArgs},
     pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
+    ripemd160_circuit::{Ripemd160Circuit, Ripemd160CircuitConfig, Ripemd160CircuitConfigArgs},
+    sha256_circuit::{Sha256Circuit, Sha256CircuitConfig, Sha256CircuitConfigArgs},
+    sig_circuit::{SigCircuit, SigCircuitConfig, SigCircuitConfigArgs},
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
-        BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, RwTable, TxTable,
+        Blake2fTable, BlockTable, ByteTable, BytecodeTable, CopyTable, EccTable, ExpTable,
+        KeccakTable, ModExpTable, MptTable, Ripemd160Table, RwTable, Sha256Table, SigTable,
+        TxTable,
     },
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
     util::{log2_ceil, Challenges, SubCircuit, SubCircuitConfig},
-    witness::{block_convert, Block, MptUpdates},
+    witness::{block_convert, Block},
 };
 use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
@@ -87,7 +121,7 @@ use std::array;
 #[derive(Clone)]
 pub struct SuperCircuitConfig<F: Field> {
     block_table: BlockTable,
-    mpt_table: MptTable,
+    byte_table: ByteTable,
     evm_circuit: EvmCircuitConfig<F>,
     state_circuit: StateCircuitConfig<F>,
     tx_circuit: TxCircuitConfig<F>,
@@ -96,6 +130,13 @@ pub struct SuperCircuitConfig<F: Field> {
     keccak_circuit: KeccakCircuitConfig<F>,
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
+    sig_circuit: SigCircuitConfig<F>,
+    sha256_circuit: Sha256CircuitConfig<F>,
+    ripemd160_circuit: Ripemd160CircuitConfig<F>,
+    ecc_circuit: EccCircuitConfig<F>,
+    blake2f_circuit: Blake2fCircuitConfig<F>,
+    modexp_circuit: ModExpCircuitConfig<F>,
+    mpt_circuit: MptCircuitConfig<F>,
 }
 
 /// Circuit configuration arguments
@@ -129,6 +170,13 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let exp_table = ExpTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        let sig_table = SigTable::construct(meta);
+        let sha256_table = Sha256Table::construct(meta);
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        let ecc_table = EccTable::construct(meta);
+        let blake2f_table = Blake2fTable::construct(meta);
+        let modexp_table = ModExpTable::construct(meta);
+        let byte_table = ByteTable::construct(meta);
 
         // Use a mock randomness instead of the randomness derived from the challange
         // (either from mock or real prover) to help debugging assignments.
@@ -194,6 +242,58 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             },
         );
         let exp_circuit = ExpCircuitConfig::new(meta, exp_table);
+        let sig_circuit = SigCircuitConfig::new(
+            meta,
+            SigCircuitConfigArgs {
+                sig_table,
+                keccak_table: keccak_table.clone(),
+                challenges: challenges.clone(),
+            },
+        );
+        let sha256_circuit = Sha256CircuitConfig::new(
+            meta,
+            Sha256CircuitConfigArgs {
+                sha256_table,
+                challenges: challenges.clone(),
+            },
+        );
+        let ripemd160_circuit = Ripemd160CircuitConfig::new(
+            meta,
+            Ripemd160CircuitConfigArgs {
+                ripemd160_table,
+                challenges: challenges.clone(),
+            },
+        );
+        let ecc_circuit = EccCircuitConfig::new(
+            meta,
+            EccCircuitConfigArgs {
+                ecc_table,
+                challenges: challenges.clone(),
+            },
+        );
+        let blake2f_circuit = Blake2fCircuitConfig::new(
+            meta,
+            Blake2fCircuitConfigArgs {
+                blake2f_table,
+                challenges: challenges.clone(),
+            },
+        );
+        let modexp_circuit = ModExpCircuitConfig::new(
+            meta,
+            ModExpCircuitConfigArgs {
+                modexp_table,
+                byte_table: byte_table.clone(),
+                challenges: challenges.clone(),
+            },
+        );
+        let mpt_circuit = MptCircuitConfig::new(
+            meta,
+            MptCircuitConfigArgs {
+                mpt_table,
+                keccak_table: keccak_table.clone(),
+                challenges: challenges.clone(),
+            },
+        );
         let evm_circuit = EvmCircuitConfig::new(
             meta,
             EvmCircuitConfigArgs {
@@ -205,13 +305,19 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 copy_table,
                 keccak_table,
                 exp_table,
+                sig_table,
+                sha256_table,
+                ripemd160_table,
+                ecc_table,
+                blake2f_table,
+                modexp_table,
                 is_taiko: false,
             },
         );
 
         Self {
             block_table,
-            mpt_table,
+            byte_table,
             evm_circuit,
             state_circuit,
             copy_circuit,
@@ -220,6 +326,13 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             keccak_circuit,
             pi_circuit,
             exp_circuit,
+            sig_circuit,
+            sha256_circuit,
+            ripemd160_circuit,
+            ecc_circuit,
+            blake2f_circuit,
+            modexp_circuit,
+            mpt_circuit,
         }
     }
 }
@@ -243,6 +356,20 @@ pub struct SuperCircuit<F: Field> {
     pub exp_circuit: ExpCircuit<F>,
     /// Keccak Circuit
     pub keccak_circuit: KeccakCircuit<F>,
+    /// Sig Circuit
+    pub sig_circuit: SigCircuit<F>,
+    /// SHA-256 Circuit
+    pub sha256_circuit: Sha256Circuit<F>,
+    /// RIPEMD-160 Circuit
+    pub ripemd160_circuit: Ripemd160Circuit<F>,
+    /// ECC Circuit
+    pub ecc_circuit: EccCircuit<F>,
+    /// Blake2F Circuit
+    pub blake2f_circuit: Blake2fCircuit<F>,
+    /// ModExp Circuit
+    pub modexp_circuit: ModExpCircuit<F>,
+    /// MPT Circuit
+    pub mpt_circuit: MptCircuit<F>,
     /// Circuits Parameters
     pub circuits_params: CircuitsParams,
     /// Mock randomness
@@ -275,6 +402,13 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             CopyCircuit::<F>::unusable_rows(),
             ExpCircuit::<F>::unusable_rows(),
             KeccakCircuit::<F>::unusable_rows(),
+            SigCircuit::<F>::unusable_rows(),
+            Sha256Circuit::<F>::unusable_rows(),
+            Ripemd160Circuit::<F>::unusable_rows(),
+            EccCircuit::<F>::unusable_rows(),
+            Blake2fCircuit::<F>::unusable_rows(),
+            ModExpCircuit::<F>::unusable_rows(),
+            MptCircuit::<F>::unusable_rows(),
         ])
         .unwrap()
     }
@@ -288,6 +422,13 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         let copy_circuit = CopyCircuit::new_from_block_no_external(block);
         let exp_circuit = ExpCircuit::new_from_block(block);
         let keccak_circuit = KeccakCircuit::new_from_block(block);
+        let sig_circuit = SigCircuit::new_from_block(block);
+        let sha256_circuit = Sha256Circuit::new_from_block(block);
+        let ripemd160_circuit = Ripemd160Circuit::new_from_block(block);
+        let ecc_circuit = EccCircuit::new_from_block(block);
+        let blake2f_circuit = Blake2fCircuit::new_from_block(block);
+        let modexp_circuit = ModExpCircuit::new_from_block(block);
+        let mpt_circuit = MptCircuit::new_from_block(block);
 
         SuperCircuit::<_> {
             evm_circuit,
@@ -298,6 +439,13 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             copy_circuit,
             exp_circuit,
             keccak_circuit,
+            sig_circuit,
+            sha256_circuit,
+            ripemd160_circuit,
+            ecc_circuit,
+            blake2f_circuit,
+            modexp_circuit,
+            mpt_circuit,
             circuits_params: block.circuits_params,
             mock_randomness: block.randomness,
         }
@@ -314,6 +462,13 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         instance.extend_from_slice(&self.state_circuit.instance());
         instance.extend_from_slice(&self.exp_circuit.instance());
         instance.extend_from_slice(&self.evm_circuit.instance());
+        instance.extend_from_slice(&self.sig_circuit.instance());
+        instance.extend_from_slice(&self.sha256_circuit.instance());
+        instance.extend_from_slice(&self.ripemd160_circuit.instance());
+        instance.extend_from_slice(&self.ecc_circuit.instance());
+        instance.extend_from_slice(&self.blake2f_circuit.instance());
+        instance.extend_from_slice(&self.modexp_circuit.instance());
+        instance.extend_from_slice(&self.mpt_circuit.instance());
 
         instance
     }
@@ -328,8 +483,18 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         let tx = TxCircuit::min_num_rows_block(block);
         let exp = ExpCircuit::min_num_rows_block(block);
         let pi = PiCircuit::min_num_rows_block(block);
-
-        let rows: Vec<(usize, usize)> = vec![evm, state, bytecode, copy, keccak, tx, exp, pi];
+        let sig = SigCircuit::min_num_rows_block(block);
+        let sha256 = Sha256Circuit::min_num_rows_block(block);
+        let ripemd160 = Ripemd160Circuit::min_num_rows_block(block);
+        let ecc = EccCircuit::min_num_rows_block(block);
+        let blake2f = Blake2fCircuit::min_num_rows_block(block);
+        let modexp = ModExpCircuit::min_num_rows_block(block);
+        let mpt = MptCircuit::min_num_rows_block(block);
+
+        let rows: Vec<(usize, usize)> = vec![
+            evm, state, bytecode, copy, keccak, tx, exp, pi, sig, sha256, ripemd160, ecc, blake2f,
+            modexp, mpt,
+        ];
         let (rows_without_padding, rows_with_padding): (Vec<usize>, Vec<usize>) =
             rows.into_iter().unzip();
         (
@@ -361,6 +526,20 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             .synthesize_sub(&config.evm_circuit, challenges, layouter)?;
         self.pi_circuit
             .synthesize_sub(&config.pi_circuit, challenges, layouter)?;
+        self.sig_circuit
+            .synthesize_sub(&config.sig_circuit, challenges, layouter)?;
+        self.sha256_circuit
+            .synthesize_sub(&config.sha256_circuit, challenges, layouter)?;
+        self.ripemd160_circuit
+            .synthesize_sub(&config.ripemd160_circuit, challenges, layouter)?;
+        self.ecc_circuit
+            .synthesize_sub(&config.ecc_circuit, challenges, layouter)?;
+        self.blake2f_circuit
+            .synthesize_sub(&config.blake2f_circuit, challenges, layouter)?;
+        self.modexp_circuit
+            .synthesize_sub(&config.modexp_circuit, challenges, layouter)?;
+        self.mpt_circuit
+            .synthesize_sub(&config.mpt_circuit, challenges, layouter)?;
         Ok(())
     }
 }
@@ -416,19 +595,13 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
             Value::known(block.randomness),
             Value::known(block.randomness),
         );
-        let rws = &self.state_circuit.rows;
 
         config.block_table.load(
             &mut layouter,
             &block.context,
             Value::known(block.randomness),
         )?;
-
-        config.mpt_table.load(
-            &mut layouter,
-            &MptUpdates::mock_from(rws),
-            Value::known(block.randomness),
-        )?;
+        config.byte_table.load(&mut layouter)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
@@ -26,6 +26,8 @@ use keccak256::plain::Keccak;
 use std::array;
 use strum_macros::{EnumCount, EnumIter};
 
+/// blake2f table
+pub(crate) mod blake2f_table;
 /// block table
 pub(crate) mod block_table;
 /// byte table
@@ -34,29 +36,45 @@ pub(crate) mod byte_table;
 pub(crate) mod bytecode_table;
 /// copy Table
 pub(crate) mod copy_table;
+/// ecc table
+pub(crate) mod ecc_table;
 /// exp(exponentiation) table
 pub(crate) mod exp_table;
 /// keccak table
 pub(crate) mod keccak_table;
+/// modexp table
+pub(crate) mod modexp_table;
 /// mpt table
 pub(crate) mod mpt_table;
 /// pi table
 pub(crate) mod pi_table;
+/// ripemd160 table
+pub(crate) mod ripemd160_table;
 /// rw table
 pub(crate) mod rw_table;
+/// sha256 table
+pub(crate) mod sha256_table;
+/// sig table
+pub(crate) mod sig_table;
 /// tx table
 pub(crate) mod tx_table;
 
+pub(crate) use blake2f_table::Blake2fTable;
 pub(crate) use block_table::{BlockContextFieldTag, BlockTable};
 pub(crate) use byte_table::ByteTable;
 pub(crate) use bytecode_table::{BytecodeFieldTag, BytecodeTable};
 pub(crate) use copy_table::CopyTable;
+pub(crate) use ecc_table::EccTable;
 pub(crate) use exp_table::ExpTable;
 pub(crate) use keccak_table::KeccakTable;
 
+pub(crate) use modexp_table::ModExpTable;
 pub(crate) use mpt_table::{MPTProofType, MptTable};
 pub(crate) use pi_table::{PiFieldTag, PiTable};
+pub(crate) use ripemd160_table::Ripemd160Table;
 pub(crate) use rw_table::RwTable;
+pub(crate) use sha256_table::Sha256Table;
+pub(crate) use sig_table::SigTable;
 pub(crate) use tx_table::{
     TxContextFieldTag, TxFieldTag, TxLogFieldTag, TxReceiptFieldTag, TxTable,
 };
@@ -0,0 +1,118 @@
+use super::*;
+
+use crate::blake2f_circuit::blake2f;
+
+/// Blake2F Table, used to verify the BLAKE2b compression of the input of the
+/// Blake2F precompile from RLC'ed input.
+#[derive(Clone, Copy, Debug)]
+pub struct Blake2fTable {
+    /// True when the row is enabled
+    pub is_enabled: Column<Advice>,
+    /// Number of rounds of the compression
+    pub rounds: Column<Advice>,
+    /// Byte array input as `RLC(reversed(input))`
+    pub input_rlc: Column<Advice>,
+    /// Byte array output as `RLC(reversed(output))`
+    pub output_rlc: Column<Advice>,
+}
+
+impl<F: Field> LookupTable<F> for Blake2fTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.rounds.into(),
+            self.input_rlc.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("rounds"),
+            String::from("input_rlc"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+impl Blake2fTable {
+    /// Construct a new Blake2fTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            rounds: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            output_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the Blake2F table assignments from a valid Blake2F input.
+    pub fn assignments<F: Field>(input: &[u8], challenges: &Challenges<Value<F>>) -> [Value<F>; 4] {
+        let rounds = u32::from_be_bytes(input[..4].try_into().unwrap());
+        let output = blake2f(input);
+        let [input_rlc, output_rlc] = [input, &output[..]].map(|bytes| {
+            challenges
+                .keccak_input()
+                .map(|challenge| rlc::value(bytes.iter().rev(), challenge))
+        });
+        [
+            Value::known(F::ONE),
+            Value::known(F::from(rounds as u64)),
+            input_rlc,
+            output_rlc,
+        ]
+    }
+
+    /// Assign a row of the table.
+    pub fn assign_row<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        values: [Value<F>; 4],
+    ) -> Result<(), Error> {
+        for (&column, value) in <Blake2fTable as LookupTable<F>>::advice_columns(self)
+            .iter()
+            .zip(values.iter())
+        {
+            region.assign_advice(|| format!("assign {}", offset), column, offset, || *value)?;
+        }
+        Ok(())
+    }
+
+    /// Provide this function for the case that we want to consume a Blake2F
+    /// table but without running the full Blake2F circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "blake2f table",
+            |mut region| {
+                let blake2f_table_columns = <Blake2fTable as LookupTable<F>>::advice_columns(self);
+                for column in blake2f_table_columns.iter() {
+                    region.assign_advice(
+                        || "blake2f table all-zero row",
+                        *column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                for (offset, input) in inputs.clone().into_iter().enumerate() {
+                    let row = Self::assignments(input, challenges);
+                    for (&column, value) in blake2f_table_columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("blake2f table row {}", offset + 1),
+                            column,
+                            offset + 1,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -23,6 +23,9 @@ pub enum BlockContextFieldTag {
     ChainId,
     /// Treasury accounts for receiving block base fee
     Treasury,
+    /// Blob Base Fee field.  Appended since its opcode follows `CHAINID` and
+    /// `SELFBALANCE` instead of a block field.
+    BlobBaseFee,
 }
 impl_expr!(BlockContextFieldTag);
 
@@ -11,7 +11,7 @@ pub struct CopyTable {
     pub is_first: Column<Advice>,
     /// The relevant ID for the read-write row, represented as a random linear
     /// combination. The ID may be one of the below:
-    /// 1. Call ID/Caller ID for CopyDataType::Memory
+    /// 1. Call ID/Caller ID for CopyDataType::Memory, CopyDataType::MemoryCopy
     /// 2. RLC encoding of bytecode hash for CopyDataType::Bytecode
     /// 3. Transaction ID for CopyDataType::TxCalldata, CopyDataType::TxLog
     pub id: Column<Advice>,
@@ -0,0 +1,165 @@
+use super::*;
+
+use bus_mapping::precompile::{EcAddOp, EcMulOp, PrecompileCalls};
+
+/// ECC Table, used to verify the BN254 point additions and scalar
+/// multiplications of the ecAdd and ecMul precompiles.
+///
+/// | op_type | arg1_rlc | arg2_rlc | arg3_rlc | arg4_rlc | output1_rlc | output2_rlc |
+/// | ------- | -------- | -------- | -------- | -------- | ----------- | ----------- |
+/// | ecAdd   | p.x      | p.y      | q.x      | q.y      | r.x         | r.y         |
+/// | ecMul   | p.x      | p.y      | s        | 0        | r.x         | r.y         |
+#[derive(Clone, Copy, Debug)]
+pub struct EccTable {
+    /// True when the row is enabled
+    pub is_enabled: Column<Advice>,
+    /// Address of the precompile of the operation
+    pub op_type: Column<Advice>,
+    /// RLC of the first argument
+    pub arg1_rlc: Column<Advice>,
+    /// RLC of the second argument
+    pub arg2_rlc: Column<Advice>,
+    /// RLC of the third argument
+    pub arg3_rlc: Column<Advice>,
+    /// RLC of the fourth argument
+    pub arg4_rlc: Column<Advice>,
+    /// RLC of the first output
+    pub output1_rlc: Column<Advice>,
+    /// RLC of the second output
+    pub output2_rlc: Column<Advice>,
+}
+
+impl<F: Field> LookupTable<F> for EccTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.op_type.into(),
+            self.arg1_rlc.into(),
+            self.arg2_rlc.into(),
+            self.arg3_rlc.into(),
+            self.arg4_rlc.into(),
+            self.output1_rlc.into(),
+            self.output2_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("op_type"),
+            String::from("arg1_rlc"),
+            String::from("arg2_rlc"),
+            String::from("arg3_rlc"),
+            String::from("arg4_rlc"),
+            String::from("output1_rlc"),
+            String::from("output2_rlc"),
+        ]
+    }
+}
+
+impl EccTable {
+    /// Construct a new EccTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            op_type: meta.advice_column(),
+            arg1_rlc: meta.advice_column_in(SecondPhase),
+            arg2_rlc: meta.advice_column_in(SecondPhase),
+            arg3_rlc: meta.advice_column_in(SecondPhase),
+            arg4_rlc: meta.advice_column_in(SecondPhase),
+            output1_rlc: meta.advice_column_in(SecondPhase),
+            output2_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    fn word_rlc<F: Field>(word: Word, challenges: &Challenges<Value<F>>) -> Value<F> {
+        challenges
+            .evm_word()
+            .map(|challenge| rlc::value(&word.to_le_bytes(), challenge))
+    }
+
+    /// Generate the ECC table assignments from an ecAdd call.
+    pub fn add_assignments<F: Field>(
+        op: &EcAddOp,
+        challenges: &Challenges<Value<F>>,
+    ) -> [Value<F>; 8] {
+        let [p_x, p_y, q_x, q_y, r_x, r_y] = [op.p.0, op.p.1, op.q.0, op.q.1, op.r.0, op.r.1]
+            .map(|word| Self::word_rlc(word, challenges));
+        [
+            Value::known(F::ONE),
+            Value::known(F::from(PrecompileCalls::Bn128Add.address())),
+            p_x,
+            p_y,
+            q_x,
+            q_y,
+            r_x,
+            r_y,
+        ]
+    }
+
+    /// Generate the ECC table assignments from an ecMul call.
+    pub fn mul_assignments<F: Field>(
+        op: &EcMulOp,
+        challenges: &Challenges<Value<F>>,
+    ) -> [Value<F>; 8] {
+        let [p_x, p_y, s, r_x, r_y] =
+            [op.p.0, op.p.1, op.s, op.r.0, op.r.1].map(|word| Self::word_rlc(word, challenges));
+        [
+            Value::known(F::ONE),
+            Value::known(F::from(PrecompileCalls::Bn128Mul.address())),
+            p_x,
+            p_y,
+            s,
+            Value::known(F::ZERO),
+            r_x,
+            r_y,
+        ]
+    }
+
+    /// Provide this function for the case that we want to consume an ECC
+    /// table but without running the full ECC circuit
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        add_ops: &[EcAddOp],
+        mul_ops: &[EcMulOp],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "ecc table",
+            |mut region| {
+                let ecc_table_columns = <EccTable as LookupTable<F>>::advice_columns(self);
+                for column in ecc_table_columns.iter() {
+                    region.assign_advice(
+                        || "ecc table all-zero row",
+                        *column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                let rows = std::iter::empty()
+                    .chain(
+                        add_ops
+                            .iter()
+                            .map(|op| Self::add_assignments(op, challenges)),
+                    )
+                    .chain(
+                        mul_ops
+                            .iter()
+                            .map(|op| Self::mul_assignments(op, challenges)),
+                    );
+                for (offset, row) in rows.enumerate() {
+                    for (&column, value) in ecc_table_columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("ecc table row {}", offset + 1),
+                            column,
+                            offset + 1,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,102 @@
+use super::*;
+
+use bus_mapping::precompile::ModExpOp;
+
+/// ModExp Table, used to verify the modular exponentiations of the modexp
+/// precompile on operands of up to 32 bytes with a nonzero modulus.  The
+/// operands and the result are RLC'ed as EVM words.
+#[derive(Clone, Copy, Debug)]
+pub struct ModExpTable {
+    /// True when the row is enabled
+    pub is_enabled: Column<Advice>,
+    /// RLC of the base
+    pub base_rlc: Column<Advice>,
+    /// RLC of the exponent
+    pub exponent_rlc: Column<Advice>,
+    /// RLC of the modulus
+    pub modulus_rlc: Column<Advice>,
+    /// RLC of the result, `base ** exponent % modulus`
+    pub result_rlc: Column<Advice>,
+}
+
+impl<F: Field> LookupTable<F> for ModExpTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.base_rlc.into(),
+            self.exponent_rlc.into(),
+            self.modulus_rlc.into(),
+            self.result_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("base_rlc"),
+            String::from("exponent_rlc"),
+            String::from("modulus_rlc"),
+            String::from("result_rlc"),
+        ]
+    }
+}
+
+impl ModExpTable {
+    /// Construct a new ModExpTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            base_rlc: meta.advice_column_in(SecondPhase),
+            exponent_rlc: meta.advice_column_in(SecondPhase),
+            modulus_rlc: meta.advice_column_in(SecondPhase),
+            result_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the ModExp table assignments from a modexp call.
+    pub fn assignments<F: Field>(op: &ModExpOp, challenges: &Challenges<Value<F>>) -> [Value<F>; 5] {
+        let [base, exponent, modulus, result] = [op.base, op.exponent, op.modulus, op.result]
+            .map(|word| {
+                challenges
+                    .evm_word()
+                    .map(|challenge| rlc::value(&word.to_le_bytes(), challenge))
+            });
+        [Value::known(F::ONE), base, exponent, modulus, result]
+    }
+
+    /// Provide this function for the case that we want to consume a ModExp
+    /// table but without running the full ModExp circuit
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        ops: &[ModExpOp],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "modexp table",
+            |mut region| {
+                let modexp_table_columns = <ModExpTable as LookupTable<F>>::advice_columns(self);
+                for column in modexp_table_columns.iter() {
+                    region.assign_advice(
+                        || "modexp table all-zero row",
+                        *column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                for (offset, op) in ops.iter().enumerate() {
+                    let row = Self::assignments(op, challenges);
+                    for (&column, value) in modexp_table_columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("modexp table row {}", offset + 1),
+                            column,
+                            offset + 1,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
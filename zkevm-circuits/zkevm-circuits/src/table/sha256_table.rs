@@ -0,0 +1,121 @@
+use super::*;
+
+use sha2::{Digest, Sha256};
+
+/// SHA-256 Table, used to verify the SHA-256 hashing of the input of the
+/// SHA2-256 precompile from RLC'ed input.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256Table {
+    /// True when the row is enabled
+    pub is_enabled: Column<Advice>,
+    /// Byte array input as `RLC(reversed(input))`
+    pub input_rlc: Column<Advice>,
+    /// Byte array input length
+    pub input_len: Column<Advice>,
+    /// RLC of the hash result
+    pub output_rlc: Column<Advice>,
+}
+
+impl<F: Field> LookupTable<F> for Sha256Table {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+impl Sha256Table {
+    /// Construct a new Sha256Table
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            input_len: meta.advice_column(),
+            output_rlc: meta.advice_column_in(SecondPhase),
+        }
+    }
+
+    /// Generate the SHA-256 table assignments from a byte array input.
+    pub fn assignments<F: Field>(input: &[u8], challenges: &Challenges<Value<F>>) -> [Value<F>; 4] {
+        let input_rlc = challenges
+            .keccak_input()
+            .map(|challenge| rlc::value(input.iter().rev(), challenge));
+        let output = Sha256::digest(input);
+        let output_rlc = challenges.evm_word().map(|challenge| {
+            rlc::value(
+                &Word::from_big_endian(output.as_slice()).to_le_bytes(),
+                challenge,
+            )
+        });
+        [
+            Value::known(F::ONE),
+            input_rlc,
+            Value::known(F::from(input.len() as u64)),
+            output_rlc,
+        ]
+    }
+
+    /// Assign a row of the table.
+    pub fn assign_row<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        values: [Value<F>; 4],
+    ) -> Result<(), Error> {
+        for (&column, value) in <Sha256Table as LookupTable<F>>::advice_columns(self)
+            .iter()
+            .zip(values.iter())
+        {
+            region.assign_advice(|| format!("assign {}", offset), column, offset, || *value)?;
+        }
+        Ok(())
+    }
+
+    /// Provide this function for the case that we want to consume a SHA-256
+    /// table but without running the full SHA-256 circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: impl IntoIterator<Item = &'a Vec<u8>> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sha256 table",
+            |mut region| {
+                let sha256_table_columns = <Sha256Table as LookupTable<F>>::advice_columns(self);
+                for column in sha256_table_columns.iter() {
+                    region.assign_advice(
+                        || "sha256 table all-zero row",
+                        *column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                for (offset, input) in inputs.clone().into_iter().enumerate() {
+                    let row = Self::assignments(input, challenges);
+                    for (&column, value) in sha256_table_columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("sha256 table row {}", offset + 1),
+                            column,
+                            offset + 1,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+use bus_mapping::precompile::EcRecoverEvent;
+
+/// Sig Table, used to verify the public key recovered by the ecRecover
+/// precompile from a message hash and a signature.
+#[derive(Clone, Copy, Debug)]
+pub struct SigTable {
+    /// True when the row is enabled
+    pub is_enabled: Column<Advice>,
+    /// RLC of the message hash
+    pub msg_hash_rlc: Column<Advice>,
+    /// Recovery id of the signature, 27 or 28
+    pub sig_v: Column<Advice>,
+    /// RLC of the signature `r`
+    pub sig_r_rlc: Column<Advice>,
+    /// RLC of the signature `s`
+    pub sig_s_rlc: Column<Advice>,
+    /// Address recovered from the signature
+    pub recovered_addr: Column<Advice>,
+}
+
+impl<F: Field> LookupTable<F> for SigTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.msg_hash_rlc.into(),
+            self.sig_v.into(),
+            self.sig_r_rlc.into(),
+            self.sig_s_rlc.into(),
+            self.recovered_addr.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("msg_hash_rlc"),
+            String::from("sig_v"),
+            String::from("sig_r_rlc"),
+            String::from("sig_s_rlc"),
+            String::from("recovered_addr"),
+        ]
+    }
+}
+
+impl SigTable {
+    /// Construct a new SigTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            msg_hash_rlc: meta.advice_column_in(SecondPhase),
+            sig_v: meta.advice_column(),
+            sig_r_rlc: meta.advice_column_in(SecondPhase),
+            sig_s_rlc: meta.advice_column_in(SecondPhase),
+            recovered_addr: meta.advice_column(),
+        }
+    }
+
+    /// Generate the sig table assignments from an ecRecover event.
+    pub fn assignments<F: Field>(
+        event: &EcRecoverEvent,
+        challenges: &Challenges<Value<F>>,
+    ) -> [Value<F>; 6] {
+        let [msg_hash_rlc, sig_r_rlc, sig_s_rlc] =
+            [event.msg_hash, event.sig_r, event.sig_s].map(|word| {
+                challenges
+                    .evm_word()
+                    .map(|challenge| rlc::value(&word.to_le_bytes(), challenge))
+            });
+        [
+            Value::known(F::ONE),
+            msg_hash_rlc,
+            Value::known(F::from(event.sig_v as u64)),
+            sig_r_rlc,
+            sig_s_rlc,
+            Value::known(
+                event
+                    .recovered_addr
+                    .to_scalar()
+                    .expect("unexpected Address -> Scalar conversion failure"),
+            ),
+        ]
+    }
+
+    /// Provide this function for the case that we want to consume a sig
+    /// table but without running the full sig circuit
+    pub fn dev_load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        events: impl IntoIterator<Item = &'a EcRecoverEvent> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sig table",
+            |mut region| {
+                let sig_table_columns = <SigTable as LookupTable<F>>::advice_columns(self);
+                for column in sig_table_columns.iter() {
+                    region.assign_advice(
+                        || "sig table all-zero row",
+                        *column,
+                        0,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                for (offset, event) in events.clone().into_iter().enumerate() {
+                    let row = Self::assignments(event, challenges);
+                    for (&column, value) in sig_table_columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("sig table row {}", offset + 1),
+                            column,
+                            offset + 1,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -1,10 +1,15 @@
 //! TaikoPiCircuit
+mod blob;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 mod dev;
 mod param;
 #[cfg(any(test))]
 mod test;
-use bus_mapping::circuit_input_builder::ProtocolInstance;
+use blob::{BlobCells, BlobConfig};
+use bus_mapping::circuit_input_builder::{
+    blob::{blob_data, BLOB_DATA_LEN},
+    ProtocolInstance,
+};
 
 use param::*;
 
@@ -154,64 +159,109 @@ impl<F: Field> PublicData<F> {
         }
     }
 
-    fn encode_field(&self, idx: usize) -> Vec<u8> {
-        match idx {
+    fn encode_field(&self, idx: usize) -> Result<Vec<u8>, Error> {
+        Ok(match idx {
             0 => self.protocol_instance.parentHash(),
             1 => self.protocol_instance.blockHash(),
             2 => self.protocol_instance.signalRoot(),
             3 => self.protocol_instance.graffiti(),
             4 => self.protocol_instance.meta_hash(),
             5 => self.protocol_instance.prover(),
-            6 => self.protocol_instance.tx_list_hash(),
-            7 => self.protocol_instance.point_value(),
+            6 => checked(self.protocol_instance.tx_list_hash())?,
+            7 => checked(self.protocol_instance.point_value())?,
             _ => unreachable!(),
-        }
+        })
     }
 
-    fn total_acc(&self, r: Value<F>) -> F {
+    fn total_acc(&self, r: Value<F>) -> Result<F, Error> {
         let mut rand = F::ZERO;
         r.map(|r| rand = r);
-        rlc::value(self.protocol_instance.abi_encode().iter().rev(), rand)
+        let encoded = checked(self.protocol_instance.abi_encode())?;
+        Ok(rlc::value(encoded.iter().rev(), rand))
     }
 
-    fn assignment(&self, idx: usize) -> Vec<F> {
-        self.encode_field(idx)
-            .iter()
-            .map(|b| F::from(*b as u64))
-            .collect()
+    fn assignment(&self, idx: usize) -> Result<Vec<F>, Error> {
+        Ok(bytes_assignment(&self.encode_field(idx)?))
     }
 
-    fn assignment_acc(&self, idx: usize, r: Value<F>) -> F {
+    fn assignment_acc(&self, idx: usize, r: Value<F>) -> Result<F, Error> {
         let mut rand = F::ZERO;
         r.map(|r| rand = r);
-        rlc::value(self.encode_field(idx).iter().rev(), rand)
+        Ok(rlc::value(self.encode_field(idx)?.iter().rev(), rand))
     }
 
-    fn keccak_hi_low(&self) -> [F; 2] {
-        let keccaked_pi = keccak256(self.protocol_instance.abi_encode());
-        [
-            rlc::value(keccaked_pi[0..16].iter().rev(), BYTE_POW_BASE.scalar()),
-            rlc::value(keccaked_pi[16..].iter().rev(), BYTE_POW_BASE.scalar()),
-        ]
+    fn keccak_hi_low(&self) -> Result<[F; 2], Error> {
+        Ok(hi_low(&self.keccak()?))
+    }
+
+    /// The hash of the blob hash and the tx list hash, whose reduction modulo
+    /// the BLS modulus is the point challenge
+    fn challenge(&self) -> Result<Vec<u8>, Error> {
+        let tx_list_hash = checked(self.protocol_instance.tx_list_hash_b256())?;
+        Ok(keccak256(
+            [
+                self.protocol_instance.block_metadata.blobHash.as_slice(),
+                tx_list_hash.as_slice(),
+            ]
+            .concat(),
+        )
+        .to_vec())
+    }
+
+    /// The data of the blob evaluated by the circuit, zero in calldata mode
+    fn blob_data(&self) -> Result<Vec<u8>, Error> {
+        if self.protocol_instance.blob_used() {
+            blob_data(&self.protocol_instance.blob).ok_or_else(|| {
+                log::error!("invalid protocol instance: blob too long");
+                Error::Synthesis
+            })
+        } else {
+            Ok(vec![0; BLOB_DATA_LEN])
+        }
     }
 
-    fn keccak(&self) -> Vec<u8> {
-        keccak256(self.protocol_instance.abi_encode()).to_vec()
+    /// The range of the tx list in the blob data, clamped to the data
+    fn tx_list_range(&self) -> (usize, usize) {
+        let metadata = &self.protocol_instance.block_metadata;
+        let offset = (metadata.txListByteOffset as usize).min(BLOB_DATA_LEN);
+        let size = (metadata.txListByteSize as usize).min(BLOB_DATA_LEN - offset);
+        (offset, size)
     }
 
-    fn keccak_assignment(&self) -> Vec<F> {
-        self.keccak().iter().map(|b| F::from(*b as u64)).collect()
+    /// The inputs of the keccak hashes looked up by the circuit
+    pub(crate) fn keccak_inputs(&self) -> Result<Vec<Vec<u8>>, Error> {
+        checked(self.protocol_instance.keccak_inputs())
     }
 
-    fn total_len(&self) -> usize {
-        self.protocol_instance.abi_encode().len()
+    fn keccak(&self) -> Result<Vec<u8>, Error> {
+        let encoded = checked(self.protocol_instance.abi_encode())?;
+        Ok(keccak256(encoded).to_vec())
     }
 
-    fn field_len(&self, idx: usize) -> usize {
-        self.encode_field(idx).len()
+    fn keccak_assignment(&self) -> Result<Vec<F>, Error> {
+        Ok(bytes_assignment(&self.keccak()?))
     }
 }
 
+/// The witness of a protocol instance with an invalid blob can't be generated
+fn checked<T>(result: Result<T, bus_mapping::Error>) -> Result<T, Error> {
+    result.map_err(|err| {
+        log::error!("invalid protocol instance: {:?}", err);
+        Error::Synthesis
+    })
+}
+
+fn bytes_assignment<F: Field>(bytes: &[u8]) -> Vec<F> {
+    bytes.iter().map(|b| F::from(*b as u64)).collect()
+}
+
+fn hi_low<F: Field>(bytes: &[u8]) -> [F; 2] {
+    [
+        rlc::value(bytes[0..16].iter().rev(), BYTE_POW_BASE.scalar()),
+        rlc::value(bytes[16..].iter().rev(), BYTE_POW_BASE.scalar()),
+    ]
+}
+
 /// PiCircuitConfig
 #[derive(Clone, Debug)]
 pub struct TaikoPiCircuitConfig<F: Field> {
@@ -231,6 +281,16 @@ pub struct TaikoPiCircuitConfig<F: Field> {
     keccak_bytes: FieldGadget<F>,
     keccak_hi_lo: [Cell<F>; 2],
 
+    meta_data: FieldGadget<F>,
+    blob_used: Cell<F>,
+    tx_list_offset: Cell<F>,
+    tx_list_size: Cell<F>,
+    tx_list_hash_rlc: Cell<F>,
+    challenge: FieldGadget<F>,
+    challenge_hi_lo: [Cell<F>; 2],
+    point_value_hi_lo: [Cell<F>; 2],
+    blob: BlobConfig<F>,
+
     total_acc: Cell<F>,
     block_table: BlockTable,
     keccak_table: KeccakTable,
@@ -297,12 +357,24 @@ impl<F: Field> SubCircuitConfig<F> for TaikoPiCircuitConfig<F> {
         let graffiti = FieldGadget::config(&mut cb, pi.graffiti().len());
         let meta_hash = FieldGadget::config(&mut cb, pi.meta_hash().len());
         let prover = FieldGadget::config(&mut cb, pi.prover().len());
-        let tx_list_hash = FieldGadget::config(&mut cb, pi.tx_list_hash().len());
-        let point_value = FieldGadget::config(&mut cb, pi.point_value().len());
+        let tx_list_hash = FieldGadget::config(&mut cb, DEFAULT_LEN);
+        let point_value = FieldGadget::config(&mut cb, DEFAULT_LEN);
 
         let total_acc = cb.query_one(S2);
         let keccak_bytes = FieldGadget::config(&mut cb, DEFAULT_LEN);
         let keccak_hi_lo = [cb.query_one(S1), cb.query_one(S1)];
+
+        let meta_data = FieldGadget::config(&mut cb, pi.meta_data().len());
+        let blob_used = cb.query_one(S1);
+        let tx_list_offset = cb.query_one(S1);
+        let tx_list_size = cb.query_one(S1);
+        let tx_list_hash_rlc = cb.query_one(S2);
+        let challenge = FieldGadget::config(&mut cb, DEFAULT_LEN);
+        let challenge_hi_lo = [cb.query_one(S1), cb.query_one(S1)];
+        let point_value_hi_lo = [cb.query_one(S1), cb.query_one(S1)];
+        // The maingate instance column comes after the keccak instance column
+        let blob = BlobConfig::configure(meta, &keccak_table, &byte_table, &challenges);
+
         meta.create_gate("PI acc constraints", |meta| {
             circuit!([meta, cb], {
                 ifx!(q!(q_enable) => {
@@ -336,7 +408,7 @@ impl<F: Field> SubCircuitConfig<F> for TaikoPiCircuitConfig<F> {
                         (
                             1.expr(),
                             total_acc.expr(),
-                            public_data.total_len().expr(),
+                            PI_LEN.expr(),
                             keccak_bytes.rlc_acc(evm_word.expr())
                         )
                         => @cb.table(Table::Keccak)
@@ -349,6 +421,63 @@ impl<F: Field> SubCircuitConfig<F> for TaikoPiCircuitConfig<F> {
                             require!(cell.expr() => epxr);
                             cb.enable_equality(cell.column());
                         });
+
+                    // The metadata is the preimage of the meta hash
+                    require!(
+                        (
+                            1.expr(),
+                            meta_data.rlc_acc(keccak_r.expr()),
+                            pi.meta_data().len().expr(),
+                            meta_hash.rlc_acc(evm_word.expr())
+                        )
+                        => @cb.table(Table::Keccak)
+                    );
+                    let meta_bytes = meta_data.bytes_expr();
+                    let blob_hash = &meta_bytes[BLOB_HASH_POS..BLOB_HASH_POS + DEFAULT_LEN];
+                    for (cell, pos) in [
+                        (&tx_list_offset, TX_LIST_BYTE_OFFSET_POS),
+                        (&tx_list_size, TX_LIST_BYTE_SIZE_POS),
+                    ] {
+                        let bytes = meta_bytes[pos..pos + UINT24_LEN].to_vec();
+                        require!(cell.expr() => bytes.rlc_rev(&BYTE_POW_BASE.expr()));
+                    }
+                    require!(blob_used.expr() => meta_bytes[BLOB_USED_POS].expr());
+                    ifx!(blob_used => {
+                        // The tx list hash is the hash of the tx list in the blob
+                        let tx_list_hash_acc = tx_list_hash.rlc_acc(evm_word.expr());
+                        require!(tx_list_hash_rlc.expr() => tx_list_hash_acc);
+                        let input = [blob_hash.to_vec(), tx_list_hash.bytes_expr()].concat();
+                        require!(
+                            (
+                                1.expr(),
+                                input.rlc_rev(&keccak_r.expr()),
+                                (2 * DEFAULT_LEN).expr(),
+                                challenge.rlc_acc(evm_word.expr())
+                            )
+                            => @cb.table(Table::Keccak)
+                        );
+                    } elsex {
+                        // The tx list hash is the blob hash, with no point value
+                        for (byte, blob_byte) in tx_list_hash.bytes_expr().iter().zip(blob_hash) {
+                            require!(byte.expr() => blob_byte.expr());
+                        }
+                        for byte in point_value.bytes_expr() {
+                            require!(byte => 0.expr());
+                        }
+                    });
+                    // The point challenge and value, bound to the blob evaluation
+                    for (cells, gadget) in [
+                        (&challenge_hi_lo, &challenge),
+                        (&point_value_hi_lo, &point_value),
+                    ] {
+                        for (cell, expr) in cells.iter().zip(gadget.hi_low_field()) {
+                            require!(cell.expr() => expr);
+                            cb.enable_equality(cell.column());
+                        }
+                    }
+                    for cell in [&blob_used, &tx_list_offset, &tx_list_size, &tx_list_hash_rlc] {
+                        cb.enable_equality(cell.column());
+                    }
                 });
             });
             cb.build_constraints()
@@ -372,6 +501,15 @@ impl<F: Field> SubCircuitConfig<F> for TaikoPiCircuitConfig<F> {
             total_acc,
             keccak_bytes,
             keccak_hi_lo,
+            meta_data,
+            blob_used,
+            tx_list_offset,
+            tx_list_size,
+            tx_list_hash_rlc,
+            challenge,
+            challenge_hi_lo,
+            point_value_hi_lo,
+            blob,
             block_table,
             keccak_table,
             byte_table,
@@ -388,7 +526,7 @@ impl<F: Field> TaikoPiCircuitConfig<F> {
     ) -> Result<(), Error> {
         let evm_word = challenge.evm_word();
         let keccak_r = challenge.keccak_input();
-        let hi_lo_cells = layouter.assign_region(
+        let (hi_lo_cells, blob_cells) = layouter.assign_region(
         || "Pi",
         |mut region| {
                 self.q_enable.enable(&mut region, 0)?;
@@ -396,12 +534,11 @@ impl<F: Field> TaikoPiCircuitConfig<F> {
                 region.annotate_columns(&self.columns);
 
                 assign!(region, self.parent_hash.0, 0 => (public_data.block_context.number - 1).as_u64().scalar())?;
-                assign!(region, self.parent_hash.2, 0 => public_data.assignment_acc(PARENT_HASH, evm_word))?;
+                assign!(region, self.parent_hash.2, 0 => public_data.assignment_acc(PARENT_HASH, evm_word)?)?;
                 assign!(region, self.block_hash.0, 0 => (public_data.block_context.number).as_u64().scalar())?;
-                assign!(region, self.block_hash.2, 0 => public_data.assignment_acc(BLOCK_HASH, evm_word))?;
+                assign!(region, self.block_hash.2, 0 => public_data.assignment_acc(BLOCK_HASH, evm_word)?)?;
 
-                let mut idx = 0;
-                [
+                for (idx, gadget) in [
                     &self.parent_hash.1,
                     &self.block_hash.1,
                     &self.signal_root,
@@ -410,25 +547,53 @@ impl<F: Field> TaikoPiCircuitConfig<F> {
                     &self.prover,
                     &self.tx_list_hash,
                     &self.point_value,
-                ].iter().for_each(|gadget| {
-                    gadget.assign(&mut region, 0, &public_data.assignment(idx))
+                ].iter().enumerate() {
+                    gadget.assign(&mut region, 0, &public_data.assignment(idx)?)
                         .expect(&format!("FieldGadget assignment failed at {:?}", idx));
-                    idx += 1;
-                });
+                }
 
-                self.keccak_bytes.assign(&mut region, 0, &public_data.keccak_assignment())
+                self.keccak_bytes.assign(&mut region, 0, &public_data.keccak_assignment()?)
                     .expect("Keccak bytes assignment failed");                
-                assign!(region, self.total_acc, 0 => public_data.total_acc(keccak_r))?;
-                let hi_low_assignment = public_data.keccak_hi_low();
+                assign!(region, self.total_acc, 0 => public_data.total_acc(keccak_r)?)?;
+                let hi_low_assignment = public_data.keccak_hi_low()?;
                 let hi = assign!(region, self.keccak_hi_lo[0], 0 => hi_low_assignment[0])?;
                 let lo = assign!(region, self.keccak_hi_lo[1], 0 => hi_low_assignment[1])?;
 
-                Ok([hi, lo])
+                let pi = &public_data.protocol_instance;
+                let offset = pi.block_metadata.txListByteOffset as u64;
+                let size = pi.block_metadata.txListByteSize as u64;
+                let hash_rlc = public_data.assignment_acc(TX_LIST_HASH, evm_word)?;
+                let challenge_bytes = public_data.challenge()?;
+                self.meta_data.assign(&mut region, 0, &bytes_assignment(&pi.meta_data()))?;
+                self.challenge.assign(&mut region, 0, &bytes_assignment(&challenge_bytes))?;
+                let mut assign_hi_lo = |cells: &[Cell<F>; 2], bytes: &[u8]| {
+                    let [hi, lo] = hi_low(bytes);
+                    Ok::<_, Error>([
+                        assign!(region, cells[0], 0 => hi)?,
+                        assign!(region, cells[1], 0 => lo)?,
+                    ])
+                };
+                let challenge = assign_hi_lo(&self.challenge_hi_lo, &challenge_bytes)?;
+                let point_value = assign_hi_lo(
+                    &self.point_value_hi_lo,
+                    &public_data.encode_field(POINT_VALUE)?,
+                )?;
+                let blob_cells = BlobCells {
+                    challenge,
+                    point_value,
+                    blob_used: assign!(region, self.blob_used, 0 => pi.blob_used().scalar())?,
+                    tx_list_offset: assign!(region, self.tx_list_offset, 0 => offset.scalar())?,
+                    tx_list_size: assign!(region, self.tx_list_size, 0 => size.scalar())?,
+                    tx_list_hash_rlc: assign!(region, self.tx_list_hash_rlc, 0 => hash_rlc)?,
+                };
+
+                Ok(([hi, lo], blob_cells))
         })?;
         for (i, cell) in hi_lo_cells.iter().enumerate() {
             layouter.constrain_instance(cell.cell(), self.keccak_instance, i)?;
         }
-        Ok(())
+        self.blob
+            .assign(layouter, challenge, public_data, &blob_cells)
     }
 }
 /// Public Inputs Circuit
@@ -454,8 +619,10 @@ impl<F: Field> SubCircuit<F> for TaikoPiCircuit<F> {
         CM_HEIGHT + 3
     }
 
-    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        (0, PublicData::new(block).total_len())
+    fn min_num_rows_block(_block: &witness::Block<F>) -> (usize, usize) {
+        // The blob is evaluated in calldata mode too, as a zero blob
+        let rows = BlobConfig::<F>::min_num_rows();
+        (rows, rows)
     }
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
@@ -464,7 +631,14 @@ impl<F: Field> SubCircuit<F> for TaikoPiCircuit<F> {
 
     /// Compute the public inputs for this circuit.
     fn instance(&self) -> Vec<Vec<F>> {
-        vec![self.public_data.keccak_hi_low().to_vec()]
+        // The maingate of the blob evaluation expects an instance column, but
+        // we don't use it, so we return an "empty" instance column.  The
+        // synthesis fails for an invalid blob, whose instance is left empty.
+        let keccak_hi_low = self
+            .public_data
+            .keccak_hi_low()
+            .map(|hi_low| hi_low.to_vec());
+        vec![keccak_hi_low.unwrap_or_default(), vec![]]
     }
 
     /// Make the assignments to the PiCircuit
@@ -0,0 +1,538 @@
+//! The evaluation of the tx list blob at the point challenge.
+//!
+//! The data bytes of the blob are laid out one per row, which lets the rows
+//! of the tx list be accumulated into the input of a keccak lookup and the
+//! rows of each field element be composed into its value.  The polynomial of
+//! the blob is then evaluated at the challenge with the barycentric formula by
+//! the IntegerChip over the BLS12-381 scalar field.
+
+use super::{checked, PublicData};
+use crate::{
+    table::{byte_table::ByteTable, KeccakTable, LookupTable},
+    tx_circuit::sign_verify::{integer_to_bytes_le, BIT_LEN_LIMB, NUMBER_OF_LIMBS},
+    util::Challenges,
+};
+use bls12_381::Scalar;
+use bus_mapping::circuit_input_builder::blob::{
+    BLOB_DATA_LEN, BLS_MODULUS, DATA_BYTES_PER_FIELD_ELEMENT, FIELD_ELEMENTS_PER_BLOB,
+    ROOTS_OF_UNITY,
+};
+use eth_types::Field;
+use gadgets::util::{not, Expr};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase},
+    poly::Rotation,
+};
+use integer::{
+    rns::{Integer, Rns},
+    AssignedInteger, IntegerChip, IntegerConfig, IntegerInstructions, Range, UnassignedInteger,
+};
+use itertools::Itertools;
+use log::error;
+use maingate::{
+    AssignedValue, MainGate, MainGateConfig, MainGateInstructions, RangeChip, RangeConfig,
+    RangeInstructions, RegionCtx, Term,
+};
+use num_bigint::BigUint;
+use std::{marker::PhantomData, rc::Rc};
+
+type BlobInteger<F> = AssignedInteger<Scalar, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>;
+
+/// The cells of the PI region bound to the blob evaluation
+#[derive(Clone, Debug)]
+pub(crate) struct BlobCells<F: Field> {
+    /// The hi and lo halves of the point challenge hash
+    pub(crate) challenge: [AssignedCell<F, F>; 2],
+    /// The hi and lo halves of the point value
+    pub(crate) point_value: [AssignedCell<F, F>; 2],
+    /// Whether the tx list is posted in the blob
+    pub(crate) blob_used: AssignedCell<F, F>,
+    /// The offset of the tx list in the blob data
+    pub(crate) tx_list_offset: AssignedCell<F, F>,
+    /// The size of the tx list
+    pub(crate) tx_list_size: AssignedCell<F, F>,
+    /// The RLC of the tx list hash
+    pub(crate) tx_list_hash_rlc: AssignedCell<F, F>,
+}
+
+/// Config for the blob evaluation
+#[derive(Clone, Debug)]
+pub(crate) struct BlobConfig<F: Field> {
+    q_enable: Column<Fixed>,
+    q_first: Column<Fixed>,
+    q_last: Column<Fixed>,
+    q_elem_first: Column<Fixed>,
+    /// The data byte of the row
+    byte: Column<Advice>,
+    /// Whether the row is before the tx list
+    before: Column<Advice>,
+    /// Whether the row is after the tx list
+    after: Column<Advice>,
+    /// The number of rows before the tx list, up to the row
+    pre_len: Column<Advice>,
+    /// The number of rows of the tx list, up to the row
+    tx_len: Column<Advice>,
+    /// The value of the field element of the row, up to the row
+    elem: Column<Advice>,
+    /// The RLC of the tx list, up to the row
+    tx_rlc: Column<Advice>,
+    // The offset, size, hash and blob mode of the tx list, in the last row
+    offset: Column<Advice>,
+    size: Column<Advice>,
+    hash_rlc: Column<Advice>,
+    blob_used: Column<Advice>,
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> BlobConfig<F> {
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        keccak_table: &KeccakTable,
+        byte_table: &ByteTable,
+        challenges: &Challenges<Expression<F>>,
+    ) -> Self {
+        let main_gate_config = MainGate::<F>::configure(meta);
+        let range_config = RangeChip::<F>::configure(
+            meta,
+            &main_gate_config,
+            vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS, 8],
+            Rns::<Scalar, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct().overflow_lengths(),
+        );
+
+        let [q_enable, q_first, q_last, q_elem_first] = [(); 4].map(|_| meta.fixed_column());
+        let [byte, before, after, pre_len, tx_len, elem, offset, size, blob_used] =
+            [(); 9].map(|_| meta.advice_column());
+        let [tx_rlc, hash_rlc] = [(); 2].map(|_| meta.advice_column_in(SecondPhase));
+        for column in [elem, offset, size, hash_rlc, blob_used] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("blob bytes", |meta| {
+            let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+            let not_first = not::expr(meta.query_fixed(q_first, Rotation::cur()));
+            let not_elem_first = not::expr(meta.query_fixed(q_elem_first, Rotation::cur()));
+            let q_last = meta.query_fixed(q_last, Rotation::cur());
+            let columns = [before, after, pre_len, tx_len, elem, tx_rlc];
+            // The values of the previous row, zero in the first row
+            let [before_prev, after_prev, pre_len_prev, tx_len_prev, elem_prev, tx_rlc_prev] =
+                columns
+                    .map(|column| not_first.clone() * meta.query_advice(column, Rotation::prev()));
+            let [before, after, pre_len, tx_len, elem, tx_rlc] =
+                columns.map(|column| meta.query_advice(column, Rotation::cur()));
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let [offset, size, blob_used] =
+                [offset, size, blob_used].map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_tx = 1.expr() - before.clone() - after.clone();
+
+            let mut constraints = vec![
+                (
+                    "before is boolean",
+                    before.clone() * not::expr(before.clone()),
+                ),
+                ("after is boolean", after.clone() * not::expr(after.clone())),
+                ("before and after", before.clone() * after.clone()),
+                // The tx list is a single range of rows
+                (
+                    "before only before",
+                    not_first.clone() * before.clone() * not::expr(before_prev),
+                ),
+                ("after only after", after_prev * not::expr(after.clone())),
+                ("pre_len", pre_len.clone() - pre_len_prev - before),
+                ("tx_len", tx_len.clone() - tx_len_prev - is_tx.clone()),
+                (
+                    "tx_rlc",
+                    tx_rlc.clone()
+                        - tx_rlc_prev
+                            * (is_tx.clone() * (challenges.keccak_input() - 1.expr()) + 1.expr())
+                        - is_tx * byte.clone(),
+                ),
+                (
+                    "elem",
+                    elem - not_elem_first * elem_prev * 256.expr() - byte,
+                ),
+            ];
+            // In blob mode, the range of the tx list is its range in the metadata
+            constraints.extend([
+                (
+                    "pre_len is offset",
+                    q_last.clone() * blob_used.clone() * (pre_len - offset),
+                ),
+                (
+                    "tx_len is size",
+                    q_last.clone() * blob_used.clone() * (tx_len - size),
+                ),
+                (
+                    "blob_used is boolean",
+                    q_last * blob_used.clone() * not::expr(blob_used),
+                ),
+            ]);
+
+            constraints
+                .into_iter()
+                .map(|(name, constraint)| (name, q_enable.clone() * constraint))
+                .collect_vec()
+        });
+
+        meta.lookup_any("blob byte", |meta| {
+            let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+            let byte = meta.query_advice(byte, Rotation::cur());
+            vec![(q_enable * byte, byte_table.table_exprs(meta)[0].clone())]
+        });
+
+        meta.lookup_any("tx list hash", |meta| {
+            let enable = meta.query_fixed(q_last, Rotation::cur())
+                * meta.query_advice(blob_used, Rotation::cur());
+            let input = [
+                enable.clone(),
+                enable.clone() * meta.query_advice(tx_rlc, Rotation::cur()),
+                enable.clone() * meta.query_advice(tx_len, Rotation::cur()),
+                enable * meta.query_advice(hash_rlc, Rotation::cur()),
+            ];
+            let table = [
+                keccak_table.is_enabled,
+                keccak_table.input_rlc,
+                keccak_table.input_len,
+                keccak_table.output_rlc,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+            input.into_iter().zip(table).collect()
+        });
+
+        Self {
+            q_enable,
+            q_first,
+            q_last,
+            q_elem_first,
+            byte,
+            before,
+            after,
+            pre_len,
+            tx_len,
+            elem,
+            tx_rlc,
+            offset,
+            size,
+            hash_rlc,
+            blob_used,
+            main_gate_config,
+            range_config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the minimum number of rows required by the blob evaluation.
+    pub(crate) fn min_num_rows() -> usize {
+        // The rows of an element and of the final evaluation are estimates of
+        // the rows used by the IntegerChip.
+        let rows_range_chip_table = 1 << (BIT_LEN_LIMB / NUMBER_OF_LIMBS);
+        let rows_element = 120;
+        let rows_evaluation = 400;
+        itertools::max([
+            rows_range_chip_table,
+            BLOB_DATA_LEN,
+            FIELD_ELEMENTS_PER_BLOB * rows_element + rows_evaluation,
+        ])
+        .unwrap()
+    }
+
+    /// Assign the blob of the tx list and its evaluation, bound to the cells
+    /// of the PI region.
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        challenges: &Challenges<Value<F>>,
+        public_data: &PublicData<F>,
+        cells: &BlobCells<F>,
+    ) -> Result<(), Error> {
+        let data = public_data.blob_data()?;
+        let elem_cells = self.assign_bytes(layouter, challenges, public_data, &data, cells)?;
+        RangeChip::new(self.range_config.clone()).load_table(layouter)?;
+        self.assign_evaluation(layouter, public_data, &data, &elem_cells, cells)
+    }
+
+    // Assign the data bytes of the blob, returning the cells of the values of
+    // the field elements.
+    fn assign_bytes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        challenges: &Challenges<Value<F>>,
+        public_data: &PublicData<F>,
+        data: &[u8],
+        cells: &BlobCells<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let (offset, size) = public_data.tx_list_range();
+        layouter.assign_region(
+            || "blob bytes",
+            |mut region| {
+                let mut pre_len = 0u64;
+                let mut tx_len = 0u64;
+                let mut tx_rlc = Value::known(F::ZERO);
+                let mut elem = F::ZERO;
+                let mut elem_cells = Vec::with_capacity(FIELD_ELEMENTS_PER_BLOB);
+                for (idx, byte) in data.iter().enumerate() {
+                    let before = idx < offset;
+                    let after = idx >= offset + size;
+                    let is_tx = !before && !after;
+                    let byte = F::from(*byte as u64);
+                    pre_len += before as u64;
+                    tx_len += is_tx as u64;
+                    if is_tx {
+                        tx_rlc = tx_rlc * challenges.keccak_input() + Value::known(byte);
+                    }
+                    let elem_first = idx % DATA_BYTES_PER_FIELD_ELEMENT == 0;
+                    elem = if elem_first {
+                        byte
+                    } else {
+                        elem * F::from(256) + byte
+                    };
+
+                    for (name, column, value) in [
+                        ("q_enable", self.q_enable, true),
+                        ("q_first", self.q_first, idx == 0),
+                        ("q_last", self.q_last, idx == data.len() - 1),
+                        ("q_elem_first", self.q_elem_first, elem_first),
+                    ] {
+                        region.assign_fixed(
+                            || name,
+                            column,
+                            idx,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                    }
+                    for (name, column, value) in [
+                        ("byte", self.byte, byte),
+                        ("before", self.before, F::from(before as u64)),
+                        ("after", self.after, F::from(after as u64)),
+                        ("pre_len", self.pre_len, F::from(pre_len)),
+                        ("tx_len", self.tx_len, F::from(tx_len)),
+                    ] {
+                        region.assign_advice(|| name, column, idx, || Value::known(value))?;
+                    }
+                    region.assign_advice(|| "tx_rlc", self.tx_rlc, idx, || tx_rlc)?;
+                    let elem_cell =
+                        region.assign_advice(|| "elem", self.elem, idx, || Value::known(elem))?;
+                    if idx % DATA_BYTES_PER_FIELD_ELEMENT == DATA_BYTES_PER_FIELD_ELEMENT - 1 {
+                        elem_cells.push(elem_cell);
+                    }
+                }
+
+                let last = data.len() - 1;
+                for (name, cell, column) in [
+                    ("offset", &cells.tx_list_offset, self.offset),
+                    ("size", &cells.tx_list_size, self.size),
+                    ("hash_rlc", &cells.tx_list_hash_rlc, self.hash_rlc),
+                    ("blob_used", &cells.blob_used, self.blob_used),
+                ] {
+                    cell.copy_advice(|| name, &mut region, column, last)?;
+                }
+
+                Ok(elem_cells)
+            },
+        )
+    }
+
+    // Assign the evaluation of the polynomial of the blob at the point
+    // challenge, P(z) = (z^N - 1) / N * sum(e_i * w_i / (z - w_i)).
+    fn assign_evaluation(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        public_data: &PublicData<F>,
+        data: &[u8],
+        elem_cells: &[AssignedCell<F, F>],
+        cells: &BlobCells<F>,
+    ) -> Result<(), Error> {
+        let z = checked(public_data.protocol_instance.point_challenge())?;
+        if ROOTS_OF_UNITY.contains(&z) {
+            error!("the point challenge is a root of unity");
+            return Err(Error::Synthesis);
+        }
+        let elements = data
+            .chunks_exact(DATA_BYTES_PER_FIELD_ELEMENT)
+            .map(|chunk| {
+                let mut bytes = [0u8; 32];
+                bytes[1..].copy_from_slice(chunk);
+                bytes.reverse();
+                Scalar::from_bytes(&bytes).unwrap()
+            })
+            .collect_vec();
+
+        let main_gate = MainGate::new(self.main_gate_config.clone());
+        let range_chip = RangeChip::new(self.range_config.clone());
+        let rns = Rc::new(Rns::<Scalar, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct());
+        let integer_chip = IntegerChip::new(
+            IntegerConfig::new(self.range_config.clone(), self.main_gate_config.clone()),
+            rns.clone(),
+        );
+        let unassigned = |value: Scalar| {
+            UnassignedInteger::from(Value::known(Integer::from_fe(value, rns.clone())))
+        };
+        let limb_base = F::from_u128(1u128 << BIT_LEN_LIMB);
+        let limb_bases = [
+            F::ONE,
+            limb_base,
+            limb_base.square(),
+            limb_base.square() * limb_base,
+        ];
+
+        layouter.assign_region(
+            || "blob evaluation",
+            |region| {
+                let mut ctx = RegionCtx::new(region, 0);
+
+                let assigned_z =
+                    integer_chip.assign_integer(&mut ctx, unassigned(z), Range::Remainder)?;
+                integer_chip.assert_in_field(&mut ctx, &assigned_z)?;
+                self.assign_challenge_reduction(
+                    &mut ctx,
+                    &main_gate,
+                    &range_chip,
+                    public_data,
+                    &assigned_z,
+                    &cells.challenge,
+                )?;
+
+                let mut sum = integer_chip.assign_constant(&mut ctx, Scalar::zero())?;
+                for ((element, root), elem_cell) in elements
+                    .iter()
+                    .zip(ROOTS_OF_UNITY.iter())
+                    .zip_eq(elem_cells)
+                {
+                    let assigned_element = integer_chip.assign_integer(
+                        &mut ctx,
+                        unassigned(*element),
+                        Range::Remainder,
+                    )?;
+                    // The element has 31 bytes, so its value is its native value
+                    let limbs = assigned_element.limbs();
+                    let (top_limb, _) = range_chip.decompose(
+                        &mut ctx,
+                        limbs[NUMBER_OF_LIMBS - 1].as_ref().value().copied(),
+                        8,
+                        DATA_BYTES_PER_FIELD_ELEMENT * 8 - (NUMBER_OF_LIMBS - 1) * BIT_LEN_LIMB,
+                    )?;
+                    ctx.constrain_equal(
+                        top_limb.cell(),
+                        limbs[NUMBER_OF_LIMBS - 1].as_ref().cell(),
+                    )?;
+                    let terms = limbs
+                        .iter()
+                        .zip(limb_bases)
+                        .map(|(limb, base)| Term::Assigned(limb.as_ref(), base))
+                        .collect_vec();
+                    let native = main_gate.compose(&mut ctx, &terms, F::ZERO)?;
+                    ctx.constrain_equal(native.cell(), elem_cell.cell())?;
+
+                    let root = integer_chip.assign_constant(&mut ctx, *root)?;
+                    let numerator = integer_chip.mul(&mut ctx, &assigned_element, &root)?;
+                    let denominator = integer_chip.sub(&mut ctx, &assigned_z, &root)?;
+                    let quotient =
+                        integer_chip.div_incomplete(&mut ctx, &numerator, &denominator)?;
+                    sum = integer_chip.add(&mut ctx, &sum, &quotient)?;
+                }
+
+                let mut z_pow = assigned_z.clone();
+                for _ in 0..FIELD_ELEMENTS_PER_BLOB.trailing_zeros() {
+                    z_pow = integer_chip.square(&mut ctx, &z_pow)?;
+                }
+                let one = integer_chip.assign_constant(&mut ctx, Scalar::one())?;
+                let z_pow_minus_one = integer_chip.sub(&mut ctx, &z_pow, &one)?;
+                let n_inv = Scalar::from(FIELD_ELEMENTS_PER_BLOB as u64)
+                    .invert()
+                    .unwrap();
+                let n_inv = integer_chip.assign_constant(&mut ctx, n_inv)?;
+                let factor = integer_chip.mul(&mut ctx, &z_pow_minus_one, &n_inv)?;
+                let value = integer_chip.mul(&mut ctx, &factor, &sum)?;
+                let value = integer_chip.reduce(&mut ctx, &value)?;
+                integer_chip.assert_in_field(&mut ctx, &value)?;
+
+                let value_le = integer_to_bytes_le(&mut ctx, &range_chip, &value)?;
+                for (half, cell) in value_le.chunks(16).rev().zip(&cells.point_value) {
+                    let terms = half
+                        .iter()
+                        .zip(byte_bases::<F>())
+                        .map(|(byte, base)| Term::Assigned(byte, base))
+                        .collect_vec();
+                    let composed = main_gate.compose(&mut ctx, &terms, F::ZERO)?;
+                    ctx.constrain_equal(composed.cell(), cell.cell())?;
+                }
+
+                log::debug!("blob evaluation: {} rows", ctx.offset());
+                Ok(())
+            },
+        )
+    }
+
+    // Constrain z to be the challenge hash c modulo the BLS modulus r, with
+    // c = z + k * r in halves of 128 bits:
+    //   c_lo + carry * 2^128 = z_lo + k * r_lo
+    //   c_hi = z_hi + k * r_hi + carry
+    // where k < 3 and carry < 3 are checked to be bytes.
+    fn assign_challenge_reduction(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        main_gate: &MainGate<F>,
+        range_chip: &RangeChip<F>,
+        public_data: &PublicData<F>,
+        assigned_z: &BlobInteger<F>,
+        challenge: &[AssignedCell<F, F>; 2],
+    ) -> Result<(), Error> {
+        let lo_mask = (BigUint::from(1u8) << 128) - 1u8;
+        let c = BigUint::from_bytes_be(&public_data.challenge()?);
+        let r = BigUint::from_bytes_be(&BLS_MODULUS.to_be_bytes::<32>());
+        let (k, z) = (&c / &r, &c % &r);
+        let carry = ((&z & &lo_mask) + &k * (&r & &lo_mask) - (&c & &lo_mask)) >> 128;
+        let to_field = |value: &BigUint| {
+            value
+                .to_u64_digits()
+                .iter()
+                .rev()
+                .fold(F::ZERO, |acc, digit| {
+                    acc * F::from_u128(1u128 << 64) + F::from(*digit)
+                })
+        };
+        let [r_hi, r_lo] = [&r >> 128, &r & &lo_mask].map(|half| to_field(&half));
+        let two_pow_128 = F::from_u128(1u128 << 64).square();
+
+        let (k, _) = range_chip.decompose(ctx, Value::known(to_field(&k)), 8, 8)?;
+        let (carry, _) = range_chip.decompose(ctx, Value::known(to_field(&carry)), 8, 8)?;
+        let [c_hi, c_lo] = challenge.clone().map(|cell| {
+            let copied = main_gate.assign_value(ctx, cell.value().copied())?;
+            ctx.constrain_equal(copied.cell(), cell.cell())?;
+            Ok::<_, Error>(copied)
+        });
+        let (c_hi, c_lo) = (c_hi?, c_lo?);
+
+        let z_le = integer_to_bytes_le(ctx, range_chip, assigned_z)?;
+        let half_terms = |bytes: &[AssignedValue<F>]| {
+            bytes
+                .iter()
+                .zip(byte_bases::<F>())
+                .map(|(byte, base)| (byte, -base))
+                .collect_vec()
+        };
+        let lo_terms = [(&c_lo, F::ONE), (&carry, two_pow_128), (&k, -r_lo)]
+            .into_iter()
+            .chain(half_terms(&z_le[..16]));
+        let hi_terms = [(&c_hi, F::ONE), (&carry, -F::ONE), (&k, -r_hi)]
+            .into_iter()
+            .chain(half_terms(&z_le[16..]));
+        for terms in [lo_terms.collect_vec(), hi_terms.collect_vec()] {
+            let terms = terms
+                .into_iter()
+                .map(|(value, coeff)| Term::Assigned(value, coeff))
+                .collect_vec();
+            let difference = main_gate.compose(ctx, &terms, F::ZERO)?;
+            main_gate.assert_zero(ctx, &difference)?;
+        }
+
+        Ok(())
+    }
+}
+
+// The powers of 256 of the bytes of a little endian half word
+fn byte_bases<F: Field>() -> impl Iterator<Item = F> {
+    std::iter::successors(Some(F::ONE), |base| Some(*base * F::from(256))).take(16)
+}
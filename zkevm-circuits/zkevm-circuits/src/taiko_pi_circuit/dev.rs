@@ -52,11 +52,9 @@ impl<F: Field> Circuit<F> for TaikoPiCircuit<F> {
             .block_table
             .load(&mut layouter, &evidance.block_context, randomness)?;
         // assign keccak table
-        config.keccak_table.dev_load(
-            &mut layouter,
-            vec![&evidance.protocol_instance.abi_encode()],
-            &challenges,
-        )?;
+        config
+            .keccak_table
+            .dev_load(&mut layouter, &evidance.keccak_inputs()?, &challenges)?;
         config.byte_table.load(&mut layouter)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
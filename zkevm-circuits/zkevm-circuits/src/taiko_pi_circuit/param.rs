@@ -11,5 +11,14 @@ pub const META_HASH: usize = 4;
 pub const PROVER: usize = 5;
 pub const TX_LIST_HASH: usize = 6;
 pub const POINT_VALUE: usize = 7;
+/// Length of the abi encoding of the fields hashed into the instance
+pub const PI_LEN: usize = (POINT_VALUE + 1) * DEFAULT_LEN;
 
-pub const CM_HEIGHT: usize = 20;
+/// Positions of the fields in the abi encoding of the BlockMetadata
+pub const BLOB_HASH_POS: usize = 2 * DEFAULT_LEN;
+pub const TX_LIST_BYTE_OFFSET_POS: usize = 11 * DEFAULT_LEN - UINT24_LEN;
+pub const TX_LIST_BYTE_SIZE_POS: usize = 12 * DEFAULT_LEN - UINT24_LEN;
+pub const BLOB_USED_POS: usize = 14 * DEFAULT_LEN - 1;
+pub const UINT24_LEN: usize = 3;
+
+pub const CM_HEIGHT: usize = 60;
@@ -4,7 +4,7 @@ use std::vec;
 
 use alloy_primitives::FixedBytes;
 use bus_mapping::circuit_input_builder::{
-    protocol_instance::Transition, BlockMetadata, Transaction,
+    blob::encode_blob, protocol_instance::Transition, BlockMetadata, Transaction,
 };
 use core::result::Result;
 use eth_types::{ToWord, H160, H256};
@@ -49,6 +49,7 @@ fn mock_public_data() -> PublicData<Fr> {
         },
         block_metadata: BlockMetadata::default(),
         prover: *PROVER_ADDR,
+        ..Default::default()
     };
     let block_context = BlockContext {
         number: 300.into(),
@@ -98,11 +99,26 @@ fn mock(
     }
 }
 
+/// A block posting its tx list in a blob, after `offset` bytes of other data
+fn mock_blob(tx_list: &[u8], offset: usize) -> witness::Block<Fr> {
+    let mut block = mock(Some(300.into()), Some(*THIS_HASH), Some(*LAST_HASH));
+    let protocol_instance = block.protocol_instance.as_mut().unwrap();
+    protocol_instance.blob = encode_blob(&[vec![0xff; offset], tx_list.to_vec()].concat());
+    protocol_instance.block_metadata = BlockMetadata {
+        blobHash: FixedBytes([0x01; 32]),
+        txListByteOffset: offset as u32,
+        txListByteSize: tx_list.len() as u32,
+        blobUsed: true,
+        ..Default::default()
+    };
+    block
+}
+
 #[test]
 fn test_default_pi() {
     let block = mock(Some(2.into()), None, None);
     let evidence = PublicData::new(&block);
-    let k = 17;
+    let k = 20;
     assert_eq!(run::<Fr>(k, evidence, None), Ok(()));
 }
 
@@ -111,7 +127,7 @@ fn test_simple_pi() {
     let block = mock(Some(300.into()), Some(*THIS_HASH), Some(*LAST_HASH));
     let evidence = PublicData::new(&block);
 
-    let k = 17;
+    let k = 20;
     assert_eq!(run::<Fr>(k, evidence, None), Ok(()));
 }
 
@@ -119,8 +135,8 @@ fn test_simple_pi() {
 fn test_fail_hi_lo() {
     let block = mock(Some(300.into()), Some(*THIS_HASH), Some(*LAST_HASH));
     let evidence = PublicData::new(&block);
-    let k = 17;
-    match run::<Fr>(k, evidence, Some(vec![vec![Fr::zero(), Fr::one()]])) {
+    let k = 20;
+    match run::<Fr>(k, evidence, Some(vec![vec![Fr::zero(), Fr::one()], vec![]])) {
         Ok(_) => unreachable!("this case must fail"),
         Err(errs) => {
             assert_eq!(errs.len(), 4);
@@ -142,7 +158,7 @@ fn test_fail_historical_hash() {
     block.context.history_hashes = vec![];
     let evidence = PublicData::new(&block);
 
-    let k = 17;
+    let k = 20;
     match run::<Fr>(k, evidence, None) {
         Ok(_) => unreachable!("this case must fail"),
         Err(errs) => {
@@ -177,3 +193,31 @@ fn test_from_integration() {
     let _pk2 = keygen_pk(&params, vk2.clone(), &circuit2).unwrap();
     println!("{:?}\n{:?}", vk1, vk2);
 }
+
+#[test]
+fn test_blob_pi() {
+    let block = mock_blob(&[0xaa; 100], 40);
+    let evidence = PublicData::new(&block);
+    assert_ne!(
+        evidence.protocol_instance.point_value().unwrap(),
+        vec![0; 32]
+    );
+
+    let k = 20;
+    assert_eq!(run::<Fr>(k, evidence, None), Ok(()));
+}
+
+#[test]
+fn test_fail_blob_tx_list_range() {
+    // The tx list exceeds the data of the blob
+    let mut block = mock_blob(&[0xaa; 100], 40);
+    let block_metadata = &mut block.protocol_instance.as_mut().unwrap().block_metadata;
+    block_metadata.txListByteOffset = BLOB_DATA_LEN as u32 - 50;
+    let evidence = PublicData::<Fr>::new(&block);
+    assert!(evidence.protocol_instance.blob_tx_list().is_err());
+
+    // The witness of the tx list can't be generated
+    let k = 20;
+    let circuit = TaikoPiCircuit::new(evidence);
+    assert!(MockProver::run(k, &circuit, circuit.instance()).is_err());
+}
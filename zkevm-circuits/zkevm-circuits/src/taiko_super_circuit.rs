@@ -19,20 +19,37 @@ use crate::exp_circuit::{ExpCircuit, ExpCircuitConfig};
 #[cfg(feature = "for-a7")]
 use crate::keccak_circuit::{KeccakCircuit, KeccakCircuitConfig, KeccakCircuitConfigArgs};
 #[cfg(feature = "for-a7")]
+use crate::ripemd160_circuit::{
+    Ripemd160Circuit, Ripemd160CircuitConfig, Ripemd160CircuitConfigArgs,
+};
+#[cfg(feature = "for-a7")]
+use crate::sha256_circuit::{Sha256Circuit, Sha256CircuitConfig, Sha256CircuitConfigArgs};
+#[cfg(feature = "for-a7")]
+use crate::sig_circuit::{SigCircuit, SigCircuitConfig, SigCircuitConfigArgs};
+#[cfg(feature = "for-a7")]
 use crate::state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs};
 #[cfg(feature = "for-a7")]
 use crate::table::{ByteTable, BytecodeTable, CopyTable, ExpTable};
 #[cfg(feature = "for-a7")]
-use crate::{table::MptTable, witness::MptUpdates};
+use crate::table::{Ripemd160Table, Sha256Table, SigTable};
 
 use crate::{
-    table::{BlockTable, ByteTable, KeccakTable},
+    blake2f_circuit::{Blake2fCircuit, Blake2fCircuitConfig, Blake2fCircuitConfigArgs},
+    ecc_circuit::{EccCircuit, EccCircuitConfig, EccCircuitConfigArgs},
+    modexp_circuit::{ModExpCircuit, ModExpCircuitConfig, ModExpCircuitConfigArgs},
+    mpt_circuit::{MptCircuit, MptCircuitConfig, MptCircuitConfigArgs},
+    table::{
+        Blake2fTable, BlockTable, ByteTable, EccTable, KeccakTable, ModExpTable, MptTable, TxTable,
+    },
     taiko_pi_circuit::{PublicData, TaikoPiCircuit, TaikoPiCircuitConfig, TaikoPiConfigArgs},
+    tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
     util::{log2_ceil, Challenges, SubCircuit, SubCircuitConfig},
     witness::{block_convert, Block},
 };
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams, ProtocolInstance},
+    circuit_input_builder::{
+        keccak_inputs_tx_circuit, CircuitInputBuilder, CircuitsParams, ProtocolInstance,
+    },
     mock::BlockData,
 };
 use eth_types::{geth_types::GethData, Field};
@@ -47,13 +64,10 @@ use snark_verifier_sdk::CircuitExt;
 /// Configuration of the Super Circuit
 #[derive(Clone)]
 pub struct SuperCircuitConfig<F: Field> {
-    #[cfg(feature = "for-a7")]
     tx_table: TxTable,
     #[cfg(feature = "for-a7")]
     rw_table: RwTable,
     #[cfg(feature = "for-a7")]
-    mpt_table: MptTable,
-    #[cfg(feature = "for-a7")]
     bytecode_table: BytecodeTable,
     #[cfg(feature = "for-a7")]
     pi_table: PiTable,
@@ -65,6 +79,7 @@ pub struct SuperCircuitConfig<F: Field> {
     #[cfg(feature = "for-a7")]
     exp_table: ExpTable,
     pi_circuit: TaikoPiCircuitConfig<F>,
+    tx_circuit: TxCircuitConfig<F>,
     #[cfg(feature = "for-a7")]
     anchor_tx_circuit: AnchorTxCircuitConfig<F>,
     #[cfg(feature = "for-a7")]
@@ -79,6 +94,16 @@ pub struct SuperCircuitConfig<F: Field> {
     exp_circuit: ExpCircuitConfig<F>,
     #[cfg(feature = "for-a7")]
     copy_circuit: CopyCircuitConfig<F>,
+    #[cfg(feature = "for-a7")]
+    sig_circuit: SigCircuitConfig<F>,
+    #[cfg(feature = "for-a7")]
+    sha256_circuit: Sha256CircuitConfig<F>,
+    #[cfg(feature = "for-a7")]
+    ripemd160_circuit: Ripemd160CircuitConfig<F>,
+    ecc_circuit: EccCircuitConfig<F>,
+    blake2f_circuit: Blake2fCircuitConfig<F>,
+    modexp_circuit: ModExpCircuitConfig<F>,
+    mpt_circuit: MptCircuitConfig<F>,
 }
 
 /// Circuit configuration arguments
@@ -95,11 +120,9 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         meta: &mut ConstraintSystem<F>,
         Self::ConfigArgs { challenges }: Self::ConfigArgs,
     ) -> Self {
-        #[cfg(feature = "for-a7")]
         let tx_table = TxTable::construct(meta);
         #[cfg(feature = "for-a7")]
         let rw_table = RwTable::construct(meta);
-        #[cfg(feature = "for-a7")]
         let mpt_table = MptTable::construct(meta);
         #[cfg(feature = "for-a7")]
         let bytecode_table = BytecodeTable::construct(meta);
@@ -114,6 +137,15 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         #[cfg(feature = "for-a7")]
         let exp_table = ExpTable::construct(meta);
+        #[cfg(feature = "for-a7")]
+        let sig_table = SigTable::construct(meta);
+        #[cfg(feature = "for-a7")]
+        let sha256_table = Sha256Table::construct(meta);
+        #[cfg(feature = "for-a7")]
+        let ripemd160_table = Ripemd160Table::construct(meta);
+        let ecc_table = EccTable::construct(meta);
+        let blake2f_table = Blake2fTable::construct(meta);
+        let modexp_table = ModExpTable::construct(meta);
 
         let pi_circuit = TaikoPiCircuitConfig::new(
             meta,
@@ -122,7 +154,16 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 block_table: block_table.clone(),
                 keccak_table: keccak_table.clone(),
                 byte_table: byte_table.clone(),
-                challenges,
+                challenges: challenges.clone(),
+            },
+        );
+
+        let tx_circuit = TxCircuitConfig::new(
+            meta,
+            TxCircuitConfigArgs {
+                tx_table: tx_table.clone(),
+                keccak_table: keccak_table.clone(),
+                challenges: challenges.clone(),
             },
         );
 
@@ -149,10 +190,77 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 copy_table,
                 keccak_table: keccak_table.clone(),
                 exp_table,
+                sig_table,
+                sha256_table,
+                ripemd160_table,
+                ecc_table,
+                blake2f_table,
+                modexp_table,
                 is_taiko: true,
             },
         );
 
+        #[cfg(feature = "for-a7")]
+        let (sig_circuit, sha256_circuit, ripemd160_circuit) = (
+            SigCircuitConfig::new(
+                meta,
+                SigCircuitConfigArgs {
+                    sig_table,
+                    keccak_table: keccak_table.clone(),
+                    challenges: challenges.clone(),
+                },
+            ),
+            Sha256CircuitConfig::new(
+                meta,
+                Sha256CircuitConfigArgs {
+                    sha256_table,
+                    challenges: challenges.clone(),
+                },
+            ),
+            Ripemd160CircuitConfig::new(
+                meta,
+                Ripemd160CircuitConfigArgs {
+                    ripemd160_table,
+                    challenges: challenges.clone(),
+                },
+            ),
+        );
+
+        // The ecc, blake2f and modexp circuits prove the precompile calls of
+        // the block and expose them in their tables.
+        let ecc_circuit = EccCircuitConfig::new(
+            meta,
+            EccCircuitConfigArgs {
+                ecc_table,
+                challenges: challenges.clone(),
+            },
+        );
+        let blake2f_circuit = Blake2fCircuitConfig::new(
+            meta,
+            Blake2fCircuitConfigArgs {
+                blake2f_table,
+                challenges: challenges.clone(),
+            },
+        );
+        let modexp_circuit = ModExpCircuitConfig::new(
+            meta,
+            ModExpCircuitConfigArgs {
+                modexp_table,
+                byte_table: byte_table.clone(),
+                challenges: challenges.clone(),
+            },
+        );
+        // The MPT circuit proves the state root transition of the block, with
+        // the roots in its instance.
+        let mpt_circuit = MptCircuitConfig::new(
+            meta,
+            MptCircuitConfigArgs {
+                mpt_table,
+                keccak_table: keccak_table.clone(),
+                challenges: challenges.clone(),
+            },
+        );
+
         #[cfg(feature = "for-a7")]
         let (keccak_circuit, bytecode_circuit, state_circuit, exp_circuit, copy_circuit) = {
             let keccak_circuit = KeccakCircuitConfig::new(
@@ -204,13 +312,10 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         };
 
         Self {
-            #[cfg(feature = "for-a7")]
             tx_table,
             #[cfg(feature = "for-a7")]
             rw_table,
             #[cfg(feature = "for-a7")]
-            mpt_table,
-            #[cfg(feature = "for-a7")]
             bytecode_table,
             #[cfg(feature = "for-a7")]
             copy_table,
@@ -219,6 +324,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             #[cfg(feature = "for-a7")]
             pi_table,
             pi_circuit,
+            tx_circuit,
             block_table,
             keccak_table,
             byte_table,
@@ -236,6 +342,16 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             exp_circuit,
             #[cfg(feature = "for-a7")]
             copy_circuit,
+            #[cfg(feature = "for-a7")]
+            sig_circuit,
+            #[cfg(feature = "for-a7")]
+            sha256_circuit,
+            #[cfg(feature = "for-a7")]
+            ripemd160_circuit,
+            ecc_circuit,
+            blake2f_circuit,
+            modexp_circuit,
+            mpt_circuit,
         }
     }
 }
@@ -245,6 +361,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
 pub struct SuperCircuit<F: Field> {
     /// Public Input Circuit
     pub pi_circuit: TaikoPiCircuit<F>,
+    /// Tx Circuit
+    pub tx_circuit: TxCircuit<F>,
 
     /// Anchor Transaction Circuit
     #[cfg(feature = "for-a7")]
@@ -263,6 +381,16 @@ pub struct SuperCircuit<F: Field> {
     pub(crate) copy_circuit: CopyCircuit<F>,
     #[cfg(feature = "for-a7")]
     pub(crate) exp_circuit: ExpCircuit<F>,
+    #[cfg(feature = "for-a7")]
+    pub(crate) sig_circuit: SigCircuit<F>,
+    #[cfg(feature = "for-a7")]
+    pub(crate) sha256_circuit: Sha256Circuit<F>,
+    #[cfg(feature = "for-a7")]
+    pub(crate) ripemd160_circuit: Ripemd160Circuit<F>,
+    pub(crate) ecc_circuit: EccCircuit<F>,
+    pub(crate) blake2f_circuit: Blake2fCircuit<F>,
+    pub(crate) modexp_circuit: ModExpCircuit<F>,
+    pub(crate) mpt_circuit: MptCircuit<F>,
 
     /// Block witness
     pub block: Block<F>,
@@ -285,11 +413,30 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
     type Config = SuperCircuitConfig<F>;
 
     fn unusable_rows() -> usize {
-        TaikoPiCircuit::<F>::unusable_rows()
+        itertools::max([
+            TaikoPiCircuit::<F>::unusable_rows(),
+            TxCircuit::<F>::unusable_rows(),
+            EccCircuit::<F>::unusable_rows(),
+            Blake2fCircuit::<F>::unusable_rows(),
+            ModExpCircuit::<F>::unusable_rows(),
+            MptCircuit::<F>::unusable_rows(),
+        ])
+        .unwrap()
     }
 
     fn new_from_block(block: &Block<F>) -> Self {
         let pi_circuit = TaikoPiCircuit::new_from_block(block);
+        let tx_circuit = TxCircuit::new_from_block(block);
+        let ecc_circuit = EccCircuit::new_from_block(block);
+        let blake2f_circuit = Blake2fCircuit::new_from_block(block);
+        let modexp_circuit = ModExpCircuit::new_from_block(block);
+        let mpt_circuit = MptCircuit::new_from_block(block);
+        #[cfg(feature = "for-a7")]
+        let (sig_circuit, sha256_circuit, ripemd160_circuit) = (
+            SigCircuit::new_from_block(block),
+            Sha256Circuit::new_from_block(block),
+            Ripemd160Circuit::new_from_block(block),
+        );
         #[cfg(feature = "for-a7")]
         let (
             anchor_tx_circuit,
@@ -318,6 +465,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
 
         SuperCircuit::<_> {
             pi_circuit,
+            tx_circuit,
             #[cfg(feature = "for-a7")]
             anchor_tx_circuit,
             #[cfg(feature = "for-a7")]
@@ -332,6 +480,16 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             copy_circuit,
             #[cfg(feature = "for-a7")]
             exp_circuit,
+            #[cfg(feature = "for-a7")]
+            sig_circuit,
+            #[cfg(feature = "for-a7")]
+            sha256_circuit,
+            #[cfg(feature = "for-a7")]
+            ripemd160_circuit,
+            ecc_circuit,
+            blake2f_circuit,
+            modexp_circuit,
+            mpt_circuit,
             block: block.clone(),
         }
     }
@@ -340,6 +498,11 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
     fn instance(&self) -> Vec<Vec<F>> {
         let mut instance = Vec::new();
         instance.extend_from_slice(&self.pi_circuit.instance());
+        instance.extend_from_slice(&self.tx_circuit.instance());
+        #[cfg(feature = "for-a7")]
+        instance.extend_from_slice(&self.sig_circuit.instance());
+        instance.extend_from_slice(&self.ecc_circuit.instance());
+        instance.extend_from_slice(&self.mpt_circuit.instance());
         instance
     }
 
@@ -347,6 +510,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
     fn min_num_rows_block(block: &Block<F>) -> (usize, usize) {
         [
             TaikoPiCircuit::min_num_rows_block(block),
+            TxCircuit::min_num_rows_block(block),
             #[cfg(feature = "for-a7")]
             AnchorTxCircuit::min_num_rows_block(block),
             #[cfg(feature = "for-a7")]
@@ -361,6 +525,16 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             CopyCircuit::min_num_rows_block(block),
             #[cfg(feature = "for-a7")]
             ExpCircuit::min_num_rows_block(block),
+            #[cfg(feature = "for-a7")]
+            SigCircuit::min_num_rows_block(block),
+            #[cfg(feature = "for-a7")]
+            Sha256Circuit::min_num_rows_block(block),
+            #[cfg(feature = "for-a7")]
+            Ripemd160Circuit::min_num_rows_block(block),
+            EccCircuit::min_num_rows_block(block),
+            Blake2fCircuit::min_num_rows_block(block),
+            ModExpCircuit::min_num_rows_block(block),
+            MptCircuit::min_num_rows_block(block),
         ]
         .iter()
         .fold((0, 0), |(x1, y1), (x2, y2)| {
@@ -377,6 +551,16 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
     ) -> Result<(), Error> {
         self.pi_circuit
             .synthesize_sub(&config.pi_circuit, challenges, layouter)?;
+        self.tx_circuit
+            .synthesize_sub(&config.tx_circuit, challenges, layouter)?;
+        self.ecc_circuit
+            .synthesize_sub(&config.ecc_circuit, challenges, layouter)?;
+        self.blake2f_circuit
+            .synthesize_sub(&config.blake2f_circuit, challenges, layouter)?;
+        self.modexp_circuit
+            .synthesize_sub(&config.modexp_circuit, challenges, layouter)?;
+        self.mpt_circuit
+            .synthesize_sub(&config.mpt_circuit, challenges, layouter)?;
         #[cfg(feature = "for-a7")]
         {
             self.anchor_tx_circuit.synthesize_sub(
@@ -396,6 +580,15 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
                 .synthesize_sub(&config.copy_circuit, challenges, layouter)?;
             self.exp_circuit
                 .synthesize_sub(&config.exp_circuit, challenges, layouter)?;
+            self.sig_circuit
+                .synthesize_sub(&config.sig_circuit, challenges, layouter)?;
+            self.sha256_circuit
+                .synthesize_sub(&config.sha256_circuit, challenges, layouter)?;
+            self.ripemd160_circuit.synthesize_sub(
+                &config.ripemd160_circuit,
+                challenges,
+                layouter,
+            )?;
         }
 
         Ok(())
@@ -439,6 +632,17 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
     ) -> Result<(), Error> {
         let challenges = challenges.values(&mut layouter);
         let randomness = challenges.evm_word();
+        // All txs go through the SignVerifyChip, including the anchor tx which is
+        // signed by the golden touch address.
+        let tx_keccak_inputs =
+            keccak_inputs_tx_circuit(&self.tx_circuit.txs, self.tx_circuit.chain_id).map_err(
+                |e| {
+                    log::error!("keccak_inputs_tx_circuit error: {:?}", e);
+                    Error::Synthesis
+                },
+            )?;
+        let mpt_keccak_inputs = self.block.mpt_updates.keccak_inputs();
+        let pi_keccak_inputs = self.pi_circuit.public_data.keccak_inputs()?;
         config
             .block_table
             .load(&mut layouter, &self.block.context, randomness)?;
@@ -447,9 +651,7 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
             self.block
                 .sha3_inputs
                 .iter()
-                .chain(std::iter::once(
-                    &self.pi_circuit.public_data.protocol_instance.abi_encode(),
-                ))
+                .chain(&pi_keccak_inputs)
                 .chain(
                     &self
                         .block
@@ -458,7 +660,9 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
                         .into_iter()
                         .map(|b| b.1.bytes)
                         .collect_vec(),
-                ),
+                )
+                .chain(&tx_keccak_inputs)
+                .chain(&mpt_keccak_inputs),
             &challenges,
         )?;
         config.byte_table.load(&mut layouter)?;
@@ -469,13 +673,6 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
                 self.block.protocol_instance.as_ref().unwrap(),
                 &challenges,
             )?;
-            config.tx_table.load(
-                &mut layouter,
-                &self.block.txs,
-                self.block.circuits_params.max_txs,
-                self.block.circuits_params.max_calldata,
-                &challenges,
-            )?;
             self.block.rws.check_rw_counter_sanity();
             config.rw_table.load(
                 &mut layouter,
@@ -492,11 +689,6 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
             config
                 .copy_table
                 .load(&mut layouter, &self.block, &challenges)?;
-            config.mpt_table.load(
-                &mut layouter,
-                &MptUpdates::mock_from(&self.state_circuit.rows),
-                randomness,
-            )?;
         }
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
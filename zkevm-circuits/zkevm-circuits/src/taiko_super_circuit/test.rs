@@ -28,10 +28,8 @@ pub fn test_super_circuit(
     protocol_instance: ProtocolInstance,
     circuits_params: CircuitsParams,
 ) {
-    let (_, circuit, instance, _) =
+    let (k, circuit, instance, _) =
         SuperCircuit::<Fr>::build(block, circuits_params, protocol_instance).unwrap();
-    // TODO: fix k from build
-    let k = 18;
     let prover = MockProver::run(k, &circuit, instance).unwrap();
     let res = prover.verify_par();
     if let Err(err) = res {
@@ -169,6 +167,14 @@ fn taiko_serial_test_super_circuit_1tx_3max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        max_ecrecover: 0,
+        max_sha256_rows: 0,
+        max_ripemd160_rows: 0,
+        max_ec_add: 0,
+        max_ec_mul: 0,
+        max_modexp: 0,
+        max_blake2f_rows: 0,
+        max_mpt_rows: 0,
     };
     test_super_circuit(block, protocol_instance, circuits_params);
 }
@@ -186,6 +192,14 @@ fn taiko_serial_test_super_circuit_2tx_3max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        max_ecrecover: 0,
+        max_sha256_rows: 0,
+        max_ripemd160_rows: 0,
+        max_ec_add: 0,
+        max_ec_mul: 0,
+        max_modexp: 0,
+        max_blake2f_rows: 0,
+        max_mpt_rows: 0,
     };
     test_super_circuit(block, protocol_instance, circuits_params);
 }
@@ -87,8 +87,8 @@ impl<F: Field> SignVerifyChip<F> {
         // region. TODO: Figure out a way to get these numbers automatically.
         let rows_range_chip_table = 295188;
         let rows_ecc_chip_aux = 226;
-        let rows_ecdsa_chip_verification = 104471;
-        let rows_signature_address_verify = 76;
+        let rows_ecdsa_chip_verification = 104491;
+        let rows_signature_address_verify = 92;
         std::cmp::max(
             rows_range_chip_table,
             (rows_ecc_chip_aux + rows_ecdsa_chip_verification + rows_signature_address_verify)
@@ -108,9 +108,9 @@ impl<F: Field> Default for SignVerifyChip<F> {
     }
 }
 
-const NUMBER_OF_LIMBS: usize = 4;
-const BIT_LEN_LIMB: usize = 72;
-const BIT_LEN_LAST_LIMB: usize = 256 - (NUMBER_OF_LIMBS - 1) * BIT_LEN_LIMB;
+pub(crate) const NUMBER_OF_LIMBS: usize = 4;
+pub(crate) const BIT_LEN_LIMB: usize = 72;
+pub(crate) const BIT_LEN_LAST_LIMB: usize = 256 - (NUMBER_OF_LIMBS - 1) * BIT_LEN_LIMB;
 
 /// SignVerify Configuration
 #[derive(Debug, Clone)]
@@ -213,7 +213,7 @@ impl SignVerifyConfig {
     }
 
     #[rustfmt::skip]
-    fn configure_rlc<F: Field>(
+    pub(crate) fn configure_rlc<F: Field>(
         meta: &mut ConstraintSystem<F>,
         name: &'static str,
         main_gate_config: MainGateConfig,
@@ -304,18 +304,25 @@ pub(crate) struct AssignedECDSA<F: Field> {
     pk_x_le: [AssignedValue<F>; 32],
     pk_y_le: [AssignedValue<F>; 32],
     msg_hash_le: [AssignedValue<F>; 32],
+    sig_r_le: [AssignedValue<F>; 32],
+    sig_s_le: [AssignedValue<F>; 32],
 }
 
 #[derive(Debug)]
 pub(crate) struct AssignedSignatureVerify<F: Field> {
     pub(crate) address: AssignedValue<F>,
     pub(crate) msg_hash_rlc: AssignedValue<F>,
+    pub(crate) sig_r_rlc: AssignedValue<F>,
+    pub(crate) sig_s_rlc: AssignedValue<F>,
+    /// True when the address is not zero, that is when the signature isn't
+    /// padding.
+    pub(crate) is_enabled: AssignedValue<F>,
 }
 
 // Return an array of bytes that corresponds to the little endian representation
 // of the integer, adding the constraints to verify the correctness of the
 // conversion (byte range check included).
-fn integer_to_bytes_le<F: Field, FE: PrimeField>(
+pub(crate) fn integer_to_bytes_le<F: Field, FE: PrimeField>(
     ctx: &mut RegionCtx<'_, F>,
     range_chip: &RangeChip<F>,
     int: &AssignedInteger<FE, F, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
@@ -325,9 +332,10 @@ fn integer_to_bytes_le<F: Field, FE: PrimeField>(
         .iter()
         .zip_eq([BIT_LEN_LIMB, BIT_LEN_LIMB, BIT_LEN_LIMB, BIT_LEN_LAST_LIMB])
         .map(|(limb, bit_len)| {
-            range_chip
-                .decompose(ctx, limb.as_ref().value().copied(), 8, bit_len)
-                .map(|(_, byte)| byte)
+            let (composed, bytes) =
+                range_chip.decompose(ctx, limb.as_ref().value().copied(), 8, bit_len)?;
+            ctx.constrain_equal(composed.cell(), limb.as_ref().cell())?;
+            Ok(bytes)
         })
         .collect::<Result<Vec<_>, _>>()?
         .into_iter()
@@ -384,6 +392,8 @@ impl<F: Field> SignVerifyChip<F> {
 
         let r_assigned = scalar_chip.assign_integer(ctx, integer_r, Range::Remainder)?;
         let s_assigned = scalar_chip.assign_integer(ctx, integer_s, Range::Remainder)?;
+        let sig_r_le = integer_to_bytes_le(ctx, range_chip, &r_assigned)?;
+        let sig_s_le = integer_to_bytes_le(ctx, range_chip, &s_assigned)?;
         let sig = AssignedEcdsaSig {
             r: r_assigned,
             s: s_assigned,
@@ -413,6 +423,8 @@ impl<F: Field> SignVerifyChip<F> {
             pk_x_le,
             pk_y_le,
             msg_hash_le,
+            sig_r_le,
+            sig_s_le,
         })
     }
 
@@ -538,6 +550,7 @@ impl<F: Field> SignVerifyChip<F> {
             )
         };
         let is_address_zero = main_gate.is_zero(ctx, &address)?;
+        let is_enabled = main_gate.sub_from_constant(ctx, &is_address_zero, F::ONE)?;
 
         // Ref. spec SignVerifyChip 3. Verify that the signed message in the ecdsa_chip
         // with RLC encoding corresponds to msg_hash_rlc
@@ -599,11 +612,37 @@ impl<F: Field> SignVerifyChip<F> {
                 .chain(pk_hash_hi.into_iter().rev().map(Term::unassigned)),
         )?;
 
+        // The signature is accumulated as EVM words, to be looked up by the
+        // ecRecover precompile
+        let [sig_r_rlc, sig_s_rlc] = [
+            ("sig_r", &assigned_ecdsa.sig_r_le, sign_data.signature.0),
+            ("sig_s", &assigned_ecdsa.sig_s_le, sign_data.signature.1),
+        ]
+        .map(|(name, assigned_le, value)| {
+            self.assign_rlc_le(
+                config,
+                ctx,
+                chips,
+                name,
+                config.q_rlc_evm_word,
+                challenges.evm_word(),
+                assigned_le
+                    .iter()
+                    .zip(value.to_bytes())
+                    .map(|(assigned, byte)| {
+                        Term::assigned(assigned.cell(), Value::known(F::from(byte as u64)))
+                    }),
+            )
+        });
+
         self.enable_keccak_lookup(config, ctx, &is_address_zero, &pk_rlc, &pk_hash_rlc)?;
 
         Ok(AssignedSignatureVerify {
             address,
             msg_hash_rlc,
+            sig_r_rlc: sig_r_rlc?,
+            sig_s_rlc: sig_s_rlc?,
+            is_enabled,
         })
     }
 
@@ -7,7 +7,8 @@ pub use block::{block_convert, protocol_instancetable_assignments, Block, BlockC
 mod bytecode;
 pub use bytecode::Bytecode;
 mod mpt;
-pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates};
+pub(crate) use mpt::{hex_prefix, key_nibbles, MptProof, Node, Terminal, TrieProof};
+pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates, EMPTY_ROOT};
 mod rw;
 pub use bus_mapping::circuit_input_builder::ExecStep;
 pub use rw::{Rw, RwMap, RwRow};
@@ -11,12 +11,13 @@ use bus_mapping::{
         self, protocol_instance, CircuitsParams, CopyEvent, ExpEvent, ProtocolInstance,
         ANCHOR_METHOD_SIGNATURE,
     },
+    precompile::PrecompileEvents,
     Error,
 };
 use eth_types::{Address, Field, ToBigEndian, ToLittleEndian, ToScalar, ToWord, Word, H256};
 use halo2_proofs::circuit::Value;
 
-use super::{tx::tx_convert, Bytecode, ExecStep, Rw, RwMap, Transaction};
+use super::{tx::tx_convert, Bytecode, ExecStep, MptUpdates, Rw, RwMap, Transaction};
 
 // TODO: Remove fields that are duplicated in`eth_block`
 /// Block is the struct used by all circuits, which contains all the needed
@@ -34,6 +35,9 @@ pub struct Block<F> {
     pub end_block_last: ExecStep,
     /// Read write events in the RwTable
     pub rws: RwMap,
+    /// The updates of the state trie by the account and storage rws, with
+    /// their proofs
+    pub mpt_updates: MptUpdates,
     /// Bytecode used in the block
     pub bytecodes: HashMap<Word, Bytecode>,
     /// The block context
@@ -48,6 +52,8 @@ pub struct Block<F> {
     pub circuits_params: CircuitsParams,
     /// Inputs to the SHA3 opcode
     pub sha3_inputs: Vec<Vec<u8>>,
+    /// Inputs of the precompile calls proven by dedicated circuits
+    pub precompile_events: PrecompileEvents,
     /// State root of the previous block
     pub prev_state_root: Word, // TODO: Make this H256
     /// Keccak inputs
@@ -193,6 +199,8 @@ pub struct BlockContext {
     pub mix_hash: H256,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
+    /// The blob base fee, the price of the blob gas
+    pub blob_base_fee: Word,
     /// The hash of previous blocks
     pub history_hashes: Vec<Word>,
     /// The chain id
@@ -242,6 +250,13 @@ impl BlockContext {
                     randomness
                         .map(|randomness| rlc::value(&self.base_fee.to_le_bytes(), randomness)),
                 ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::BlobBaseFee as u64)),
+                    Value::known(F::ZERO),
+                    randomness.map(|randomness| {
+                        rlc::value(&self.blob_base_fee.to_le_bytes(), randomness)
+                    }),
+                ],
                 [
                     Value::known(F::from(BlockContextFieldTag::ChainId as u64)),
                     Value::known(F::ZERO),
@@ -285,6 +300,7 @@ impl From<&circuit_input_builder::Block> for BlockContext {
             timestamp: block.timestamp,
             mix_hash: block.mix_hash,
             base_fee: block.base_fee,
+            blob_base_fee: block.blob_base_fee,
             history_hashes: block.history_hashes.clone(),
             chain_id: block.chain_id,
             block_hash: block
@@ -303,11 +319,31 @@ pub fn block_convert<F: Field>(
 ) -> Result<Block<F>, Error> {
     let rws = RwMap::from(&block.container);
     rws.check_value();
+    // The mock blocks of the tests have neither state proofs nor a previous
+    // state root, every other block must prove its state root transition.
+    let mpt_updates = if block.state_proofs.is_empty() && block.prev_state_root.is_zero() {
+        MptUpdates::from_rws_genesis(&rws.table_assignments())
+    } else {
+        MptUpdates::from_rws_with_proofs(
+            &rws.table_assignments(),
+            &block.state_proofs,
+            H256(block.prev_state_root.to_be_bytes()),
+            block.eth_block.state_root,
+        )
+    }
+    .map_err(|err| {
+        log::error!("mpt witness generation failed: {:?}", err);
+        Error::InternalError("state proofs don't prove the state root transition of the block")
+    })?;
+    // The MPT circuit hashes the trie nodes and the keys of the updates
+    let mut keccak_inputs = block.sha3_inputs.clone();
+    keccak_inputs.extend(mpt_updates.keccak_inputs());
     Ok(Block {
         // randomness: F::from(0x100), // Special value to reveal elements after RLC
         randomness: F::from(0xcafeu64),
         context: block.into(),
         rws,
+        mpt_updates,
         txs: block
             .txs()
             .iter()
@@ -327,13 +363,14 @@ pub fn block_convert<F: Field>(
         copy_events: block.copy_events.clone(),
         exp_events: block.exp_events.clone(),
         sha3_inputs: block.sha3_inputs.clone(),
+        precompile_events: block.precompile_events.clone(),
         circuits_params: block.circuits_params,
         exp_circuit_pad_to: <usize>::default(),
         prev_state_root: block.prev_state_root,
         // Use EVM Circuit's related inputs for keccak inputs
         // Otherwise, it will fail due to tx.v in keccak_inputs_tx_circuit
         // keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
-        keccak_inputs: block.sha3_inputs.clone(),
+        keccak_inputs,
         eth_block: block.eth_block.clone(),
         protocol_instance: block.protocol_instance.clone(),
     })
@@ -1,25 +1,41 @@
+mod trie;
+
+pub use trie::EMPTY_ROOT;
+pub(crate) use trie::{hex_prefix, key_nibbles, Node, TrieError};
+
 use crate::{
     evm_circuit::{util::rlc, witness::Rw},
     table::{AccountFieldTag, MPTProofType},
 };
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word};
+use eth_types::{
+    Address, EIP1186ProofResponse, Field, ToBigEndian, ToLittleEndian, ToScalar, Word, H256,
+};
+use ethers_core::utils::{
+    keccak256,
+    rlp::{self, Rlp, RlpStream},
+};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
 use std::collections::BTreeMap;
+use trie::Trie;
 
 /// An MPT update whose validity is proved by the MptCircuit
 #[derive(Debug, Clone, Copy)]
 pub struct MptUpdate {
     key: Key,
-    old_value: Word,
-    new_value: Word,
-    old_root: Word,
-    new_root: Word,
+    pub(crate) old_value: Word,
+    pub(crate) new_value: Word,
+    pub(crate) old_root: Word,
+    pub(crate) new_root: Word,
 }
 
 impl MptUpdate {
     fn proof_type<F: Field>(&self) -> F {
-        let proof_type = match self.key {
+        F::from(self.mpt_proof_type() as u64)
+    }
+
+    pub(crate) fn mpt_proof_type(&self) -> MPTProofType {
+        match self.key {
             Key::AccountStorage { .. } => {
                 if self.old_value.is_zero() && self.new_value.is_zero() {
                     MPTProofType::NonExistingStorageProof
@@ -28,16 +44,268 @@ impl MptUpdate {
                 }
             }
             Key::Account { field_tag, .. } => field_tag.into(),
+        }
+    }
+
+    pub(crate) fn address(&self) -> Address {
+        match self.key {
+            Key::Account { address, .. } | Key::AccountStorage { address, .. } => address,
+        }
+    }
+
+    /// The storage key of a storage update, `None` for an account update
+    pub(crate) fn storage_key(&self) -> Option<Word> {
+        match self.key {
+            Key::Account { .. } => None,
+            Key::AccountStorage { storage_key, .. } => Some(storage_key),
+        }
+    }
+}
+
+/// The walk of the nodes of a trie for a key, before and after an update.
+/// The nodes are walked in lockstep while the two sides have the same
+/// structure, which ends with a terminal on each side.
+#[derive(Debug, Clone)]
+pub(crate) struct TrieProof {
+    /// The key hash in the trie
+    pub(crate) key: H256,
+    /// The branches and extensions above the terminals, before and after
+    pub(crate) common: Vec<(Node, Node)>,
+    /// The terminals before and after the update
+    pub(crate) terminals: [Terminal; 2],
+}
+
+/// The end of the walk of a key on one side of a [`TrieProof`]
+#[derive(Debug, Clone)]
+pub(crate) enum Terminal {
+    /// The key is in an empty child of a branch, or the trie is empty
+    Empty,
+    /// The leaf of the key
+    Leaf(Node),
+    /// A leaf or an extension whose path diverges from the key
+    Wrong(Node),
+    /// The leaf of the key under a branch with two children, and an optional
+    /// extension above the branch.  The other child of the branch is the node
+    /// moved up when the leaf is removed, `None` when it's a branch which is
+    /// moved by its hash only.
+    Split {
+        extension: Option<Node>,
+        branch: Node,
+        moved: Option<Node>,
+        leaf: Node,
+    },
+}
+
+impl TrieProof {
+    fn new(trie: &Trie, roots: [Option<H256>; 2], key: H256) -> Result<Self, TrieError> {
+        let nibbles = key_nibbles(&key);
+        let s_walk = trie.walk(roots[0], &nibbles)?;
+        let c_walk = trie.walk(roots[1], &nibbles)?;
+        let mut common = Vec::new();
+        let mut depth = 0;
+        for (s_node, c_node) in s_walk.iter().zip(c_walk.iter()) {
+            match (s_node, c_node) {
+                (Node::Branch { .. }, Node::Branch { .. }) => depth += 1,
+                (Node::Extension { path, .. }, Node::Extension { path: c_path, .. })
+                    if path == c_path && nibbles[depth..].starts_with(path) =>
+                {
+                    depth += path.len()
+                }
+                _ => break,
+            }
+            common.push((s_node.clone(), c_node.clone()));
+        }
+        let terminal =
+            |walk: &[Node]| Terminal::new(trie, &walk[common.len()..], &nibbles[depth..]);
+        Ok(Self {
+            key,
+            terminals: [terminal(&s_walk)?, terminal(&c_walk)?],
+            common,
+        })
+    }
+
+    /// All the nodes of the proof, which are hashed in the keccak table
+    pub(crate) fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.common
+            .iter()
+            .flat_map(|(s_node, c_node)| [s_node, c_node])
+            .chain(self.terminals.iter().flat_map(|terminal| terminal.nodes()))
+    }
+}
+
+impl Terminal {
+    fn new(trie: &Trie, walk: &[Node], key: &[u8]) -> Result<Self, TrieError> {
+        Ok(match walk {
+            [] => Self::Empty,
+            [leaf @ Node::Leaf { path, .. }] if path[..] == key[..] => Self::Leaf(leaf.clone()),
+            [node @ (Node::Leaf { .. } | Node::Extension { .. })] => Self::Wrong(node.clone()),
+            [extension @ Node::Extension { path, .. }, branch, leaf] => Self::split(
+                trie,
+                Some(extension.clone()),
+                branch,
+                leaf,
+                &key[path.len()..],
+            )?,
+            [branch, leaf] => Self::split(trie, None, branch, leaf, key)?,
+            _ => return Err(TrieError::InvalidProof),
+        })
+    }
+
+    fn split(
+        trie: &Trie,
+        extension: Option<Node>,
+        branch: &Node,
+        leaf: &Node,
+        key: &[u8],
+    ) -> Result<Self, TrieError> {
+        let children = match branch {
+            Node::Branch { children } => children,
+            _ => return Err(TrieError::InvalidProof),
+        };
+        let mut others = children
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, child)| child.filter(|_| idx != key[0] as usize));
+        let moved = match (others.next(), others.next(), leaf) {
+            (Some(moved), None, Node::Leaf { path, .. }) if path[..] == key[1..] => moved,
+            _ => return Err(TrieError::InvalidProof),
+        };
+        // A moved node missing from the database is the child of an
+        // extension that was split, which is always a branch.
+        let moved = match trie.node(moved) {
+            Ok(Node::Branch { .. }) | Err(_) => None,
+            Ok(node) => Some(node.clone()),
+        };
+        Ok(Self::Split {
+            extension,
+            branch: branch.clone(),
+            moved,
+            leaf: leaf.clone(),
+        })
+    }
+
+    fn nodes(&self) -> Vec<&Node> {
+        match self {
+            Self::Empty => vec![],
+            Self::Leaf(node) | Self::Wrong(node) => vec![node],
+            Self::Split {
+                extension,
+                branch,
+                moved,
+                leaf,
+            } => extension
+                .iter()
+                .chain([branch])
+                .chain(moved.iter())
+                .chain([leaf])
+                .collect(),
+        }
+    }
+}
+
+/// The proofs of an MPT update in the account trie and, for a storage
+/// update, in the storage trie of the account.
+#[derive(Debug, Clone)]
+pub(crate) struct MptProof {
+    pub(crate) update: MptUpdate,
+    pub(crate) account: TrieProof,
+    pub(crate) storage: Option<TrieProof>,
+}
+
+/// The fields of an account leaf.  An absent account has the default fields,
+/// with a code hash of 0 as in the state circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AccountState {
+    pub(crate) nonce: Word,
+    pub(crate) balance: Word,
+    pub(crate) storage_root: H256,
+    pub(crate) code_hash: H256,
+}
+
+impl Default for AccountState {
+    fn default() -> Self {
+        Self {
+            nonce: Word::zero(),
+            balance: Word::zero(),
+            storage_root: EMPTY_ROOT,
+            code_hash: H256::zero(),
+        }
+    }
+}
+
+impl AccountState {
+    /// The account stored in a leaf value, or the default account when absent
+    pub(crate) fn decode(value: Option<&[u8]>) -> Result<Self, TrieError> {
+        let rlp = match value {
+            Some(value) => Rlp::new(value),
+            None => return Ok(Self::default()),
         };
-        F::from(proof_type as u64)
+        Ok(Self {
+            nonce: rlp.val_at(0)?,
+            balance: rlp.val_at(1)?,
+            storage_root: rlp.val_at(2)?,
+            code_hash: rlp.val_at(3)?,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&self.nonce);
+        stream.append(&self.balance);
+        stream.append(&self.storage_root);
+        stream.append(&self.code_hash);
+        stream.out().to_vec()
+    }
+
+    fn field(&self, field_tag: AccountFieldTag) -> Word {
+        match field_tag {
+            AccountFieldTag::Nonce => self.nonce,
+            AccountFieldTag::Balance => self.balance,
+            AccountFieldTag::CodeHash | AccountFieldTag::NonExisting => {
+                Word::from_big_endian(self.code_hash.as_bytes())
+            }
+        }
+    }
+
+    fn set_field(&mut self, field_tag: AccountFieldTag, value: Word) {
+        match field_tag {
+            AccountFieldTag::Nonce => self.nonce = value,
+            AccountFieldTag::Balance => self.balance = value,
+            AccountFieldTag::CodeHash | AccountFieldTag::NonExisting => {
+                self.code_hash = H256(value.to_be_bytes())
+            }
+        }
     }
 }
 
+/// The key hash of an account in the account trie
+pub(crate) fn account_key(address: Address) -> H256 {
+    H256(keccak256(address.as_bytes()))
+}
+
+/// The key hash of a slot in a storage trie
+pub(crate) fn storage_key(key: Word) -> H256 {
+    H256(keccak256(key.to_be_bytes()))
+}
+
+/// The value of a storage slot in a leaf, 0 when absent
+pub(crate) fn decode_storage_value(value: Option<&[u8]>) -> Result<Word, TrieError> {
+    Ok(value
+        .map(|value| rlp::decode(value))
+        .transpose()?
+        .unwrap_or_default())
+}
+
+fn root_word(root: Option<H256>) -> Word {
+    Word::from_big_endian(root.unwrap_or(EMPTY_ROOT).as_bytes())
+}
+
 /// All the MPT updates in the MptCircuit, accessible by their key
 #[derive(Default, Clone, Debug)]
 pub struct MptUpdates {
     old_root: Word,
     updates: BTreeMap<Key, MptUpdate>,
+    proofs: Vec<MptProof>,
 }
 
 /// The field element encoding of an MPT update, which is used by the MptTable
@@ -49,6 +317,13 @@ impl MptUpdates {
         self.old_root
     }
 
+    /// The root after the updates
+    pub(crate) fn new_root(&self) -> Word {
+        self.proofs
+            .last()
+            .map_or(self.old_root, |proof| proof.update.new_root)
+    }
+
     pub(crate) fn get(&self, row: &Rw) -> Option<MptUpdate> {
         key(row).map(|key| *self.updates.get(&key).expect("missing key in mpt updates"))
     }
@@ -81,37 +356,210 @@ impl MptUpdates {
         MptUpdates {
             updates: map,
             old_root: mock_old_root,
+            proofs: Vec::new(),
         }
     }
 
+    /// Compute the MPT updates of the rws with their real roots.  The state
+    /// before the block is given by the `eth_getProof` proofs of the accounts
+    /// and storage slots accessed in the block, which must be against
+    /// `prev_state_root`, and the updates must end at the `state_root` of the
+    /// block.
+    pub(crate) fn from_rws_with_proofs(
+        rows: &[Rw],
+        proofs: &[EIP1186ProofResponse],
+        prev_state_root: H256,
+        state_root: H256,
+    ) -> Result<Self, TrieError> {
+        let mut trie = Trie::default();
+        for proof in proofs {
+            let proof_root = proof
+                .account_proof
+                .first()
+                .map(|node| H256(keccak256(node)));
+            if proof_root != Some(prev_state_root) {
+                log::error!(
+                    "mpt witness: proof of {:?} is against {:?}, not the previous state root {:?}",
+                    proof.address,
+                    proof_root,
+                    prev_state_root
+                );
+                return Err(TrieError::InvalidProof);
+            }
+            trie.add_proof(&proof.account_proof)?;
+            for storage_proof in &proof.storage_proof {
+                trie.add_proof(&storage_proof.proof)?;
+            }
+        }
+
+        // Without the proofs, the walks fail on the missing root node
+        let root = Some(prev_state_root).filter(|root| *root != EMPTY_ROOT);
+        let updates = Self::apply(trie, root, changes(rows))?;
+        if updates.new_root() != root_word(Some(state_root)) {
+            log::error!(
+                "mpt witness: updates end at {:#x}, not the state root {:?} of the block",
+                updates.new_root(),
+                state_root
+            );
+            return Err(TrieError::InvalidProof);
+        }
+        Ok(updates)
+    }
+
+    /// Compute the MPT updates of the rws on a trie built from the initial
+    /// values read in the rws.  This is for the test blocks, which have no
+    /// state proofs: the roots are not those of any block.
+    pub(crate) fn from_rws_genesis(rows: &[Rw]) -> Result<Self, TrieError> {
+        let changes = changes(rows);
+        let mut trie = Trie::default();
+        let root = genesis(&mut trie, &changes)?;
+        Self::apply(trie, root, changes)
+    }
+
+    /// Apply the `changes` to the trie from `root`, checking their old values
+    fn apply(
+        mut trie: Trie,
+        mut root: Option<H256>,
+        changes: Vec<(Key, Word, Word)>,
+    ) -> Result<Self, TrieError> {
+        let old_root = root_word(root);
+        let mut updates = BTreeMap::new();
+        let mut mpt_proofs = Vec::new();
+        for (key_exists, old_value, new_value) in changes {
+            let key = key_exists.set_non_exists(old_value, new_value);
+            let account_key = account_key(key.address_value());
+            let account_nibbles = key_nibbles(&account_key);
+            let old_account = AccountState::decode(trie.get(root, &account_nibbles)?.as_deref())?;
+            let mut new_account = old_account;
+            let storage = match key {
+                Key::Account { field_tag, .. } => {
+                    if old_account.field(field_tag) != old_value {
+                        log::error!("mpt witness: {:?} doesn't match the state proofs", key);
+                        return Err(TrieError::InvalidProof);
+                    }
+                    new_account.set_field(field_tag, new_value);
+                    None
+                }
+                Key::AccountStorage {
+                    storage_key: slot, ..
+                } => {
+                    let storage_root =
+                        Some(old_account.storage_root).filter(|root| *root != EMPTY_ROOT);
+                    let slot_key = storage_key(slot);
+                    let slot_nibbles = key_nibbles(&slot_key);
+                    let value = trie.get(storage_root, &slot_nibbles)?;
+                    if decode_storage_value(value.as_deref())? != old_value {
+                        log::error!("mpt witness: {:?} doesn't match the state proofs", key);
+                        return Err(TrieError::InvalidProof);
+                    }
+                    let value = (!new_value.is_zero()).then(|| rlp::encode(&new_value).to_vec());
+                    let new_storage_root =
+                        trie.update(storage_root, &slot_nibbles, value.as_deref())?;
+                    new_account.storage_root = new_storage_root.unwrap_or(EMPTY_ROOT);
+                    Some(TrieProof::new(
+                        &trie,
+                        [storage_root, new_storage_root],
+                        slot_key,
+                    )?)
+                }
+            };
+            let value = (new_account != AccountState::default()).then(|| new_account.encode());
+            let new_root = trie.update(root, &account_nibbles, value.as_deref())?;
+
+            let update = MptUpdate {
+                key,
+                old_value,
+                new_value,
+                old_root: root_word(root),
+                new_root: root_word(new_root),
+            };
+            updates.insert(key_exists, update);
+            mpt_proofs.push(MptProof {
+                update,
+                account: TrieProof::new(&trie, [root, new_root], account_key)?,
+                storage,
+            });
+            root = new_root;
+        }
+
+        Ok(MptUpdates {
+            old_root,
+            updates,
+            proofs: mpt_proofs,
+        })
+    }
+
+    /// The proofs of the updates, in the order they are applied
+    pub(crate) fn proofs(&self) -> &[MptProof] {
+        &self.proofs
+    }
+
+    /// The inputs hashed by the MPT circuit: the trie nodes, the addresses and
+    /// the storage keys of the updates.
+    pub(crate) fn keccak_inputs(&self) -> Vec<Vec<u8>> {
+        self.proofs
+            .iter()
+            .flat_map(|proof| {
+                let key_preimages = std::iter::once(proof.update.address().as_bytes().to_vec())
+                    .chain(
+                        proof
+                            .update
+                            .storage_key()
+                            .map(|key| key.to_be_bytes().to_vec()),
+                    );
+                let nodes = proof
+                    .account
+                    .nodes()
+                    .chain(proof.storage.iter().flat_map(|storage| storage.nodes()))
+                    .map(Node::encode)
+                    .collect::<Vec<_>>();
+                key_preimages.chain(nodes)
+            })
+            .unique()
+            .collect()
+    }
+
     pub(crate) fn table_assignments<F: Field>(
         &self,
         randomness: Value<F>,
     ) -> Vec<MptUpdateRow<Value<F>>> {
         self.updates
             .values()
-            .map(|update| {
-                let (new_root, old_root) = randomness
-                    .map(|randomness| update.root_assignments(randomness))
-                    .unzip();
-                let (new_value, old_value) = randomness
-                    .map(|randomness| update.value_assignments(randomness))
-                    .unzip();
-                MptUpdateRow([
-                    Value::known(update.key.address()),
-                    randomness.map(|randomness| update.key.storage_key(randomness)),
-                    Value::known(update.proof_type()),
-                    new_root,
-                    old_root,
-                    new_value,
-                    old_value,
-                ])
-            })
+            .map(|update| update.table_assignment(randomness))
+            .collect()
+    }
+
+    /// The MptTable rows of the updates, in the order of their proofs
+    pub(crate) fn proof_table_assignments<F: Field>(
+        &self,
+        randomness: Value<F>,
+    ) -> Vec<MptUpdateRow<Value<F>>> {
+        self.proofs
+            .iter()
+            .map(|proof| proof.update.table_assignment(randomness))
             .collect()
     }
 }
 
 impl MptUpdate {
+    fn table_assignment<F: Field>(&self, randomness: Value<F>) -> MptUpdateRow<Value<F>> {
+        let (new_root, old_root) = randomness
+            .map(|randomness| self.root_assignments(randomness))
+            .unzip();
+        let (new_value, old_value) = randomness
+            .map(|randomness| self.value_assignments(randomness))
+            .unzip();
+        MptUpdateRow([
+            Value::known(self.key.address()),
+            randomness.map(|randomness| self.key.storage_key(randomness)),
+            Value::known(self.proof_type()),
+            new_root,
+            old_root,
+            new_value,
+            old_value,
+        ])
+    }
+
     pub(crate) fn value_assignments<F: Field>(&self, word_randomness: F) -> (F, F) {
         let assign = |x: Word| match self.key {
             Key::Account {
@@ -180,10 +628,11 @@ impl Key {
         }
     }
     fn address<F: Field>(&self) -> F {
+        self.address_value().to_scalar().unwrap()
+    }
+    fn address_value(&self) -> Address {
         match self {
-            Self::Account { address, .. } | Self::AccountStorage { address, .. } => {
-                address.to_scalar().unwrap()
-            }
+            Self::Account { address, .. } | Self::AccountStorage { address, .. } => *address,
         }
     }
     fn storage_key<F: Field>(&self, randomness: F) -> F {
@@ -244,3 +693,56 @@ fn value_prev(row: &Rw) -> Word {
         _ => unreachable!(),
     }
 }
+
+/// The key of each account field and storage slot written or read in the
+/// rows, with its value before and after the block
+fn changes(rows: &[Rw]) -> Vec<(Key, Word, Word)> {
+    rows.iter()
+        .group_by(|row| key(row))
+        .into_iter()
+        .filter_map(|(key, rows)| key.map(|key| (key, rows)))
+        .map(|(key, mut rows)| {
+            let first = rows.next().unwrap();
+            let last = rows.last().unwrap_or(first);
+            (key, value_prev(first), value(last))
+        })
+        .collect()
+}
+
+// Build the trie of the state before the block from the initial values of the
+// accounts and storage slots read in the block.
+fn genesis(trie: &mut Trie, changes: &[(Key, Word, Word)]) -> Result<Option<H256>, TrieError> {
+    let mut accounts: BTreeMap<Address, (AccountState, BTreeMap<Word, Word>)> = BTreeMap::new();
+    for (key, old_value, _) in changes {
+        let (account, storage) = accounts.entry(key.address_value()).or_default();
+        match *key {
+            Key::Account { field_tag, .. } => account.set_field(field_tag, *old_value),
+            // The storage keys are sorted by tx, so the first one holds the
+            // value before the block.
+            Key::AccountStorage { storage_key, .. } => {
+                storage.entry(storage_key).or_insert(*old_value);
+            }
+        }
+    }
+
+    let mut root = None;
+    for (address, (mut account, storage)) in accounts {
+        let mut storage_root = None;
+        for (slot, value) in storage.iter().filter(|(_, value)| !value.is_zero()) {
+            storage_root = trie.update(
+                storage_root,
+                &key_nibbles(&storage_key(*slot)),
+                Some(&rlp::encode(value)[..]),
+            )?;
+        }
+        account.storage_root = storage_root.unwrap_or(EMPTY_ROOT);
+        if account != AccountState::default() {
+            root = trie.update(
+                root,
+                &key_nibbles(&account_key(address)),
+                Some(&account.encode()[..]),
+            )?;
+        }
+    }
+    Ok(root)
+}
@@ -0,0 +1,365 @@
+//! A minimal Merkle Patricia Trie over a database of hashed nodes, used to
+//! compute the roots and the proofs of the MPT updates of a block.
+use eth_types::{Bytes, H256};
+use ethers_core::utils::{
+    keccak256,
+    rlp::{DecoderError, Rlp, RlpStream},
+};
+use std::collections::HashMap;
+
+/// The root of the empty trie, `keccak256(rlp(""))`
+pub const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// Error while walking or updating the trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TrieError {
+    /// A node needed by the walk is not in the database
+    MissingNode(H256),
+    /// A node can't be decoded, or is embedded in its parent
+    InvalidNode,
+    /// The nodes walked for a key have an unexpected layout, or hold values
+    /// that don't match the rws
+    InvalidProof,
+}
+
+impl From<DecoderError> for TrieError {
+    fn from(_: DecoderError) -> Self {
+        Self::InvalidNode
+    }
+}
+
+/// A node of the trie, with its children referenced by hash.  Paths are
+/// sequences of nibbles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Node {
+    Leaf { path: Vec<u8>, value: Vec<u8> },
+    Extension { path: Vec<u8>, child: H256 },
+    Branch { children: [Option<H256>; 16] },
+}
+
+impl Node {
+    /// The RLP encoding of the node
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut stream;
+        match self {
+            Self::Leaf { path, value } => {
+                stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(path, true));
+                stream.append(value);
+            }
+            Self::Extension { path, child } => {
+                stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(path, false));
+                stream.append(&child.as_bytes().to_vec());
+            }
+            Self::Branch { children } => {
+                stream = RlpStream::new_list(17);
+                for child in children {
+                    match child {
+                        Some(child) => stream.append(&child.as_bytes().to_vec()),
+                        None => stream.append_empty_data(),
+                    };
+                }
+                stream.append_empty_data();
+            }
+        }
+        stream.out().to_vec()
+    }
+
+    /// Decode a node from its RLP encoding.  Embedded children and branch
+    /// values don't appear in the state tries and are rejected.
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self, TrieError> {
+        let rlp = Rlp::new(bytes);
+        let child_hash = |item: Rlp| -> Result<H256, TrieError> {
+            let child: Vec<u8> = item.as_val()?;
+            if !item.is_data() || child.len() != 32 {
+                return Err(TrieError::InvalidNode);
+            }
+            Ok(H256::from_slice(&child))
+        };
+        match rlp.item_count()? {
+            2 => {
+                let (path, is_leaf) = decode_hex_prefix(&rlp.val_at::<Vec<u8>>(0)?)?;
+                if is_leaf {
+                    Ok(Self::Leaf {
+                        path,
+                        value: rlp.val_at(1)?,
+                    })
+                } else {
+                    Ok(Self::Extension {
+                        path,
+                        child: child_hash(rlp.at(1)?)?,
+                    })
+                }
+            }
+            17 => {
+                let mut children = [None; 16];
+                for (idx, child) in children.iter_mut().enumerate() {
+                    let item = rlp.at(idx)?;
+                    if !item.is_empty() {
+                        *child = Some(child_hash(item)?);
+                    }
+                }
+                if !rlp.at(16)?.is_empty() {
+                    return Err(TrieError::InvalidNode);
+                }
+                Ok(Self::Branch { children })
+            }
+            _ => Err(TrieError::InvalidNode),
+        }
+    }
+
+    /// The keccak hash of the RLP encoding of the node
+    pub(crate) fn hash(&self) -> H256 {
+        H256(keccak256(self.encode()))
+    }
+}
+
+/// The nibbles of a key hash, most significant first
+pub(crate) fn key_nibbles(key: &H256) -> Vec<u8> {
+    key.as_bytes()
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .collect()
+}
+
+/// Hex prefix encoding of a path, with the leaf flag and the parity in the
+/// high nibble of the first byte.
+pub(crate) fn hex_prefix(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let is_odd = path.len() % 2 == 1;
+    let flag = 2 * is_leaf as u8 + is_odd as u8;
+    let (first, rest) = if is_odd {
+        ((flag << 4) | path[0], &path[1..])
+    } else {
+        (flag << 4, path)
+    };
+    std::iter::once(first)
+        .chain(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]))
+        .collect()
+}
+
+fn decode_hex_prefix(bytes: &[u8]) -> Result<(Vec<u8>, bool), TrieError> {
+    let (&first, rest) = bytes.split_first().ok_or(TrieError::InvalidNode)?;
+    let flag = first >> 4;
+    if flag > 3 || (flag % 2 == 0 && first & 0xf != 0) {
+        return Err(TrieError::InvalidNode);
+    }
+    let mut path = Vec::with_capacity(2 * rest.len() + 1);
+    if flag % 2 == 1 {
+        path.push(first & 0xf);
+    }
+    path.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0xf]));
+    Ok((path, flag >= 2))
+}
+
+/// The nodes walked from the root for a key, ending at the leaf of the key,
+/// or at the node where the key diverges from the trie.
+pub(crate) type Walk = Vec<Node>;
+
+/// The nodes of a trie known to the witness generation
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Trie {
+    nodes: HashMap<H256, Node>,
+}
+
+impl Trie {
+    /// Add the nodes of an `eth_getProof` proof to the database
+    pub(crate) fn add_proof(&mut self, proof: &[Bytes]) -> Result<(), TrieError> {
+        for bytes in proof {
+            self.nodes
+                .insert(H256(keccak256(bytes)), Node::decode(bytes)?);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn node(&self, hash: H256) -> Result<&Node, TrieError> {
+        self.nodes.get(&hash).ok_or(TrieError::MissingNode(hash))
+    }
+
+    fn insert_node(&mut self, node: Node) -> Result<H256, TrieError> {
+        // Nodes shorter than a hash are embedded in their parent, which never
+        // happens with the 32 bytes keys of the state tries.
+        let encoding = node.encode();
+        if encoding.len() < 32 {
+            return Err(TrieError::InvalidNode);
+        }
+        let hash = H256(keccak256(encoding));
+        self.nodes.insert(hash, node);
+        Ok(hash)
+    }
+
+    /// The nodes walked from `root` following `key`
+    pub(crate) fn walk(&self, root: Option<H256>, key: &[u8]) -> Result<Walk, TrieError> {
+        let mut walk = Vec::new();
+        let mut next = root;
+        let mut depth = 0;
+        while let Some(hash) = next {
+            let node = self.node(hash)?.clone();
+            next = match &node {
+                Node::Branch { children } => {
+                    depth += 1;
+                    children[key[depth - 1] as usize]
+                }
+                Node::Extension { path, child } if key[depth..].starts_with(path) => {
+                    depth += path.len();
+                    Some(*child)
+                }
+                _ => None,
+            };
+            walk.push(node);
+        }
+        Ok(walk)
+    }
+
+    /// The value stored at `key`, if any
+    pub(crate) fn get(&self, root: Option<H256>, key: &[u8]) -> Result<Option<Vec<u8>>, TrieError> {
+        let walk = self.walk(root, key)?;
+        let depth = walk_depth(&walk, key);
+        Ok(match walk.last() {
+            Some(Node::Leaf { path, value }) if key[depth..] == path[..] => Some(value.clone()),
+            _ => None,
+        })
+    }
+
+    /// Set or remove the value at `key`, returning the new root
+    pub(crate) fn update(
+        &mut self,
+        root: Option<H256>,
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<Option<H256>, TrieError> {
+        self.update_at(root, key, value)
+    }
+
+    fn update_at(
+        &mut self,
+        hash: Option<H256>,
+        path: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<Option<H256>, TrieError> {
+        let leaf = |path: &[u8], value: &[u8]| Node::Leaf {
+            path: path.to_vec(),
+            value: value.to_vec(),
+        };
+        let hash = match hash {
+            Some(hash) => hash,
+            None => {
+                return value
+                    .map(|value| self.insert_node(leaf(path, value)))
+                    .transpose()
+            }
+        };
+        match self.node(hash)?.clone() {
+            Node::Leaf {
+                path: leaf_path,
+                value: leaf_value,
+            } => {
+                if leaf_path == path {
+                    return value
+                        .map(|value| self.insert_node(leaf(path, value)))
+                        .transpose();
+                }
+                let value = match value {
+                    Some(value) => value,
+                    None => return Ok(Some(hash)),
+                };
+                let common = common_prefix(&leaf_path, path);
+                let mut children = [None; 16];
+                children[leaf_path[common] as usize] =
+                    Some(self.insert_node(leaf(&leaf_path[common + 1..], &leaf_value))?);
+                children[path[common] as usize] =
+                    Some(self.insert_node(leaf(&path[common + 1..], value))?);
+                let branch = self.insert_node(Node::Branch { children })?;
+                self.prefixed(&path[..common], Some(branch))
+            }
+            Node::Extension {
+                path: ext_path,
+                child,
+            } => {
+                if path.starts_with(&ext_path) {
+                    let child = self.update_at(Some(child), &path[ext_path.len()..], value)?;
+                    return self.prefixed(&ext_path, child);
+                }
+                let value = match value {
+                    Some(value) => value,
+                    None => return Ok(Some(hash)),
+                };
+                let common = common_prefix(&ext_path, path);
+                let mut children = [None; 16];
+                children[ext_path[common] as usize] = Some(if ext_path.len() == common + 1 {
+                    child
+                } else {
+                    self.insert_node(Node::Extension {
+                        path: ext_path[common + 1..].to_vec(),
+                        child,
+                    })?
+                });
+                children[path[common] as usize] =
+                    Some(self.insert_node(leaf(&path[common + 1..], value))?);
+                let branch = self.insert_node(Node::Branch { children })?;
+                self.prefixed(&path[..common], Some(branch))
+            }
+            Node::Branch { mut children } => {
+                let idx = path[0] as usize;
+                children[idx] = self.update_at(children[idx], &path[1..], value)?;
+                let mut remaining = children
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, child)| child.map(|child| (idx as u8, child)));
+                match (remaining.next(), remaining.next()) {
+                    (None, _) => Ok(None),
+                    // A branch with a single child collapses into its child.
+                    // This needs the sibling of the removed key, which is
+                    // only in the database when it was in the proofs.
+                    (Some((idx, child)), None) => self.prefixed(&[idx], Some(child)),
+                    _ => self.insert_node(Node::Branch { children }).map(Some),
+                }
+            }
+        }
+    }
+
+    /// The node for `child` with its path extended by `prefix`: leaves and
+    /// extensions absorb the prefix, branches get an extension.
+    fn prefixed(&mut self, prefix: &[u8], child: Option<H256>) -> Result<Option<H256>, TrieError> {
+        let child = match child {
+            Some(child) if !prefix.is_empty() => child,
+            _ => return Ok(child),
+        };
+        let node = match self.node(child)?.clone() {
+            Node::Branch { .. } => Node::Extension {
+                path: prefix.to_vec(),
+                child,
+            },
+            Node::Leaf { path, value } => Node::Leaf {
+                path: [prefix, &path].concat(),
+                value,
+            },
+            Node::Extension { path, child } => Node::Extension {
+                path: [prefix, &path].concat(),
+                child,
+            },
+        };
+        self.insert_node(node).map(Some)
+    }
+}
+
+/// The number of nibbles of the key consumed by the walk, up to the last node
+fn walk_depth(walk: &[Node], key: &[u8]) -> usize {
+    let mut depth = 0;
+    for node in walk.iter().take(walk.len().saturating_sub(1)) {
+        depth += match node {
+            Node::Branch { .. } => 1,
+            Node::Extension { path, .. } => path.len(),
+            Node::Leaf { .. } => 0,
+        };
+    }
+    debug_assert!(depth <= key.len());
+    depth
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}